@@ -1,6 +1,5 @@
-use std::io::{self, Read, Write};
+use std::io;
 
-use bytes::{Buf, BufMut};
 use prost::Message;
 
 use protobuf::conformance::{
@@ -12,40 +11,17 @@ use tests::{roundtrip, RoundtripResult};
 
 fn main() -> io::Result<()> {
     env_logger::init();
-    let mut bytes = vec![0; 4];
-
-    loop {
-        bytes.resize(4, 0);
-
-        if io::stdin().read_exact(&mut bytes).is_err() {
-            // No more test cases.
-            return Ok(());
-        }
-
-        let len = bytes.as_slice().get_u32_le() as usize;
-
-        bytes.resize(len, 0);
-        io::stdin().read_exact(&mut bytes)?;
-
-        let result = match ConformanceRequest::decode(bytes.as_slice()) {
+    prost_conformance::serve(|bytes| {
+        let result = match ConformanceRequest::decode(bytes) {
             Ok(request) => handle_request(request),
             Err(error) => conformance_response::Result::ParseError(format!("{error:?}")),
         };
 
-        let response = ConformanceResponse {
+        ConformanceResponse {
             result: Some(result),
-        };
-
-        let len = response.encoded_len();
-        bytes.clear();
-        bytes.put_u32_le(len as u32);
-        response.encode(&mut bytes)?;
-        assert_eq!(len + 4, bytes.len());
-
-        let mut stdout = io::stdout();
-        stdout.lock().write_all(&bytes)?;
-        stdout.flush()?;
-    }
+        }
+        .encode_to_vec()
+    })
 }
 
 fn handle_request(request: ConformanceRequest) -> conformance_response::Result {
@@ -1,12 +1,19 @@
-#![cfg(not(target_os = "windows"))]
+// The conformance test runner is a native binary, which is not available on Windows or wasm.
+#![cfg(not(any(target_os = "windows", target_family = "wasm")))]
 
 use std::env;
-use std::process::Command;
 
+use prost_conformance::{FailureList, Runner};
 use protobuf::conformance;
 
+const FAILURE_LIST: &str = "failing_tests.txt";
+
 /// Runs the protobuf conformance test. This must be done in an integration test
 /// so that Cargo will build the proto-conformance binary.
+///
+/// Set `PROST_CONFORMANCE_JUNIT` to a path to write the results as JUnit XML, and set
+/// `PROST_CONFORMANCE_UPDATE_FAILURE_LIST` to update the failure list with the results instead
+/// of failing.
 #[test]
 fn test_conformance() {
     // Get the path to the proto-conformance binary. Adapted from
@@ -21,13 +28,26 @@ fn test_conformance() {
         })
         .unwrap();
 
-    let status = Command::new(conformance::test_runner())
-        .arg("--enforce_recommended")
-        .arg("--failure_list")
-        .arg("failing_tests.txt")
-        .arg(proto_conformance)
-        .status()
+    let report = Runner::new(conformance::test_runner())
+        .enforce_recommended(true)
+        .failure_list(FAILURE_LIST)
+        .run(proto_conformance)
         .expect("failed to execute conformance-test-runner");
 
-    assert!(status.success(), "proto conformance test failed");
+    if let Some(path) = env::var_os("PROST_CONFORMANCE_JUNIT") {
+        report
+            .write_junit("prost", path)
+            .expect("failed to write JUnit report");
+    }
+
+    if env::var_os("PROST_CONFORMANCE_UPDATE_FAILURE_LIST").is_some() {
+        let mut failures = FailureList::read(FAILURE_LIST).expect("failed to read failure list");
+        failures.update(&report);
+        failures
+            .write(FAILURE_LIST)
+            .expect("failed to write failure list");
+        return;
+    }
+
+    assert!(report.passed(), "proto conformance test failed");
 }
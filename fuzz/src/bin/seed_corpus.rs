@@ -0,0 +1,56 @@
+//! Writes the seed corpus of the fuzz targets to `fuzz/corpus/<target>/`.
+//!
+//! The corpus holds the seeds of `fuzz::seeds`, and the binary test data of the Protobuf sources
+//! for the targets which decode messages.
+
+use std::fs;
+use std::io::Result;
+use std::path::Path;
+
+const GOLDEN_FILES: &[&str] = &[
+    "golden_message",
+    "golden_message_proto3",
+    "golden_packed_fields_message",
+];
+
+fn main() -> Result<()> {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus");
+
+    for target in fuzz::TARGETS {
+        let dir = corpus.join(target.name);
+        fs::create_dir_all(&dir)?;
+
+        for (i, seed) in fuzz::seeds(target.name).into_iter().enumerate() {
+            fs::write(dir.join(format!("seed-{i}")), seed)?;
+        }
+
+        if !matches!(
+            target.name,
+            "proto3" | "proto2" | "decode" | "length_delimited"
+        ) {
+            continue;
+        }
+        for name in GOLDEN_FILES {
+            let data = match fs::read(protobuf::test_data_dir().join(name)) {
+                Ok(data) => data,
+                Err(error) => {
+                    eprintln!("skipping {name}: {error}");
+                    continue;
+                }
+            };
+            let data = if target.name == "length_delimited" {
+                let mut buf = Vec::new();
+                prost::encode_length_delimiter(data.len(), &mut buf).unwrap();
+                buf.extend_from_slice(&data);
+                buf
+            } else {
+                data
+            };
+            fs::write(dir.join(name), data)?;
+        }
+
+        println!("wrote {}", dir.display());
+    }
+
+    Ok(())
+}
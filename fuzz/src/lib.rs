@@ -0,0 +1,298 @@
+//! The fuzz targets.
+//!
+//! Each target is a function which panics if `prost` misbehaves on its input. The `cargo fuzz`
+//! binaries in `fuzzers/` call these functions, the `seed_corpus` binary writes the seed inputs of
+//! [`seeds`] to the corpus, and the tests run every target on the seeds, so that the targets are
+//! also exercised by `cargo test` and `cargo miri test`.
+
+use std::fmt::Debug;
+use std::str::{from_utf8, FromStr};
+
+use prost::{Message, Name};
+use prost_types::{Any, Duration, FieldMask, Timestamp};
+use protobuf::test_messages::proto2::TestAllTypesProto2;
+use protobuf::test_messages::proto3::TestAllTypesProto3;
+use tests::roundtrip;
+
+/// A fuzz target and the name of its binary.
+pub struct Target {
+    pub name: &'static str,
+    pub run: fn(&[u8]),
+}
+
+/// All fuzz targets.
+pub const TARGETS: &[Target] = &[
+    Target {
+        name: "proto3",
+        run: proto3,
+    },
+    Target {
+        name: "proto2",
+        run: proto2,
+    },
+    Target {
+        name: "decode",
+        run: decode,
+    },
+    Target {
+        name: "length_delimited",
+        run: length_delimited,
+    },
+    Target {
+        name: "any",
+        run: any,
+    },
+    Target {
+        name: "parse_date",
+        run: parse_date,
+    },
+    Target {
+        name: "parse_duration",
+        run: parse_duration,
+    },
+];
+
+/// Round-trips the input through a `TestAllTypesProto3` message.
+pub fn proto3(data: &[u8]) {
+    let _ = roundtrip::<TestAllTypesProto3>(data).unwrap_error();
+}
+
+/// Round-trips the input through a `TestAllTypesProto2` message.
+pub fn proto2(data: &[u8]) {
+    let _ = roundtrip::<TestAllTypesProto2>(data).unwrap_error();
+}
+
+/// Decodes the input as several message types, and checks that the decoded messages encode to
+/// their encoded length.
+pub fn decode(data: &[u8]) {
+    fn check<M: Message + Default>(data: &[u8]) {
+        if let Ok(message) = M::decode(data) {
+            assert_eq!(message.encode_to_vec().len(), message.encoded_len());
+            assert_eq!(
+                message.encode_to_vec_deterministic().len(),
+                message.encoded_len()
+            );
+        }
+    }
+
+    check::<TestAllTypesProto3>(data);
+    check::<TestAllTypesProto2>(data);
+    check::<Any>(data);
+    check::<prost_types::Struct>(data);
+    check::<prost_types::FileDescriptorSet>(data);
+
+    // Inspecting the wire format never fails, whatever the input.
+    let _ = prost::wire::debug_dump(data);
+    for field in prost::wire::FieldIter::new(data).flatten() {
+        assert!(field.offset < data.len());
+    }
+}
+
+/// Decodes the input as a stream of length-delimited `TestAllTypesProto3` messages, and checks
+/// that the stream encodes and decodes again to the same messages.
+pub fn length_delimited(data: &[u8]) {
+    let mut buf = data;
+    let mut messages = Vec::new();
+    while !buf.is_empty() {
+        match TestAllTypesProto3::decode_length_delimited(&mut buf) {
+            Ok(message) => messages.push(message),
+            Err(_) => return,
+        }
+    }
+
+    let encode = |messages: &[TestAllTypesProto3]| {
+        let mut buf = Vec::new();
+        for message in messages {
+            let start = buf.len();
+            message.encode_length_delimited(&mut buf).unwrap();
+            let len = prost::decode_length_delimiter(&buf[start..]).unwrap();
+            assert_eq!(len, message.encoded_len());
+            assert_eq!(buf.len() - start, prost::length_delimiter_len(len) + len);
+        }
+        buf
+    };
+    let encoded = encode(&messages);
+
+    let mut buf = encoded.as_slice();
+    let mut decoded = Vec::new();
+    while !buf.is_empty() {
+        decoded.push(TestAllTypesProto3::decode_length_delimited(&mut buf).unwrap());
+    }
+    assert_eq!(decoded.len(), messages.len());
+    assert_eq!(encode(&decoded), encoded);
+}
+
+/// Decodes the input as an `Any` message, and round-trips its value through the well-known types
+/// it may hold.
+pub fn any(data: &[u8]) {
+    fn check<M: Message + Name + Default + PartialEq + Debug>(any: &Any) {
+        if let Ok(message) = any.to_msg::<M>() {
+            let packed = Any::from_msg(&message).unwrap();
+            assert_eq!(packed.type_url, M::type_url());
+            assert_eq!(packed.to_msg::<M>().unwrap(), message);
+        }
+    }
+
+    let Ok(any) = Any::decode(data) else {
+        return;
+    };
+    check::<Duration>(&any);
+    check::<Timestamp>(&any);
+    check::<FieldMask>(&any);
+    check::<Any>(&any);
+}
+
+/// Parses the input as an RFC 3339 timestamp, and compares the result with `chrono`.
+pub fn parse_date(data: &[u8]) {
+    // input must be text
+    let Ok(original_text) = from_utf8(data) else {
+        return;
+    };
+
+    // parse input as a datetime
+    let Ok(timestamp) = Timestamp::from_str(original_text) else {
+        let chrono_parse = chrono::DateTime::parse_from_rfc3339(original_text);
+        assert!(
+            chrono_parse.is_err(),
+            "prost failed to parse time, but chrono does parse this time: {}",
+            original_text
+        );
+        return;
+    };
+
+    // roundtrip to and from string
+    let roundtrip_text = format!("{timestamp}");
+    assert_eq!(Ok(&timestamp), roundtrip_text.parse().as_ref());
+
+    // chrono can only parse year 0000 till 9999
+    if let Ok(chrono_time) = chrono::DateTime::parse_from_rfc3339(original_text) {
+        if chrono_time.timestamp_subsec_nanos() > 999_999_999 {
+            // prost ignores leap seconds, but chrono increases the nanos in that case
+            return;
+        }
+
+        assert_eq!(timestamp.seconds, chrono_time.timestamp());
+        assert_eq!(timestamp.nanos, chrono_time.timestamp_subsec_nanos() as i32);
+
+        // roundtrip using chrono
+        let chrono_text = chrono_time.to_utc().to_rfc3339();
+        assert_eq!(
+            roundtrip_text.strip_suffix("Z").unwrap(),
+            chrono_text.strip_suffix("+00:00").unwrap()
+        );
+        assert_eq!(Ok(&timestamp), chrono_text.parse().as_ref());
+    }
+}
+
+/// Parses the input as a duration, and checks that it formats to a string which parses to the
+/// same duration.
+pub fn parse_duration(data: &[u8]) {
+    // input must be text
+    let Ok(original_text) = from_utf8(data) else {
+        return;
+    };
+
+    // parse input as a duration
+    let Ok(duration) = Duration::from_str(original_text) else {
+        if original_text.ends_with("s") {
+            assert!(
+                original_text.parse::<f64>().is_err(),
+                "prost failed to parse duration, but it seems to be a valid number: {}",
+                original_text
+            );
+        }
+        return;
+    };
+
+    // roundtrip to and from string
+    let roundtrip_text = format!("{duration}");
+    assert_eq!(Ok(&duration), roundtrip_text.parse().as_ref());
+}
+
+/// Returns the seed inputs of the target named `target`.
+///
+/// The `seed_corpus` binary adds the binary test data of the Protobuf sources to these seeds.
+pub fn seeds(target: &str) -> Vec<Vec<u8>> {
+    let message = TestAllTypesProto3 {
+        optional_int32: -1,
+        optional_string: "seed".to_string(),
+        optional_nested_message: Some(Default::default()),
+        repeated_int64: vec![1, -1, i64::MAX],
+        map_string_string: [("key".to_string(), "value".to_string())].into(),
+        ..TestAllTypesProto3::default()
+    };
+    let duration = Duration {
+        seconds: 3,
+        nanos: 500_000_000,
+    };
+    let timestamp = Timestamp {
+        seconds: 1_700_000_000,
+        nanos: 1,
+    };
+
+    match target {
+        "proto3" | "decode" => vec![Vec::new(), message.encode_to_vec()],
+        "proto2" => vec![
+            Vec::new(),
+            TestAllTypesProto2 {
+                optional_int32: Some(-1),
+                optional_string: Some("seed".to_string()),
+                ..TestAllTypesProto2::default()
+            }
+            .encode_to_vec(),
+        ],
+        "length_delimited" => {
+            let mut stream = Vec::new();
+            message.encode_length_delimited(&mut stream).unwrap();
+            TestAllTypesProto3::default()
+                .encode_length_delimited(&mut stream)
+                .unwrap();
+            vec![Vec::new(), stream]
+        }
+        "any" => vec![
+            Any::from_msg(&duration).unwrap().encode_to_vec(),
+            Any::from_msg(&timestamp).unwrap().encode_to_vec(),
+            Any::from_msg(&Any::from_msg(&duration).unwrap())
+                .unwrap()
+                .encode_to_vec(),
+        ],
+        "parse_date" => vec![
+            b"1972-01-01T10:00:20.021Z".to_vec(),
+            b"2023-11-14T22:13:20.000000001+01:00".to_vec(),
+        ],
+        "parse_duration" => vec![b"3.5s".to_vec(), b"-0.000000001s".to_vec()],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn targets_accept_seeds() {
+        for target in TARGETS {
+            let seeds = seeds(target.name);
+            assert!(!seeds.is_empty(), "no seeds for {}", target.name);
+            for seed in seeds {
+                (target.run)(&seed);
+            }
+        }
+    }
+
+    #[test]
+    fn targets_accept_malformed_inputs() {
+        let inputs: &[&[u8]] = &[
+            &[0x08],
+            &[0x0a, 0xff, 0xff, 0xff, 0xff, 0x0f],
+            &[0x0b, 0x0c],
+            &[0xff; 11],
+            "\u{1F980}s".as_bytes(),
+        ];
+        for target in TARGETS {
+            for input in inputs {
+                (target.run)(input);
+            }
+        }
+    }
+}
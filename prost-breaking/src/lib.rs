@@ -0,0 +1,684 @@
+//! Detects breaking wire-format changes between two versions of a Protobuf schema.
+//!
+//! [`check`] compares the messages and enums of two [`FileDescriptorSet`]s by their
+//! fully-qualified names, and returns the changes after which data encoded with one version can
+//! no longer be decoded correctly with the other:
+//!
+//! * a message or an enum was removed;
+//! * a field was removed, or changed its number, without reserving the old number;
+//! * a field changed to a type with a different encoding, or to another message or enum type;
+//! * a field changed between singular and repeated, or to or from `required`;
+//! * a field moved into, out of, or between oneofs;
+//! * an enum value was removed without reserving its number.
+//!
+//! Changes which keep the wire format, such as renaming a field, or changing between `int32`,
+//! `uint32`, `int64`, `uint64` and `bool`, are not reported.
+//!
+//! ```
+//! use prost_breaking::{check, BreakingChange};
+//! use prost_types::{DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet};
+//! use prost_types::field_descriptor_proto::{Label, Type};
+//!
+//! fn schema(number: i32) -> FileDescriptorSet {
+//!     let field = FieldDescriptorProto {
+//!         name: Some("id".into()),
+//!         number: Some(number),
+//!         label: Some(Label::Optional as i32),
+//!         r#type: Some(Type::Int64 as i32),
+//!         ..Default::default()
+//!     };
+//!     let message = DescriptorProto {
+//!         name: Some("Item".into()),
+//!         field: vec![field],
+//!         ..Default::default()
+//!     };
+//!     let file = FileDescriptorProto {
+//!         name: Some("items.proto".into()),
+//!         package: Some("shop".into()),
+//!         message_type: vec![message],
+//!         ..Default::default()
+//!     };
+//!     FileDescriptorSet { file: vec![file] }
+//! }
+//!
+//! let changes = check(&schema(1), &schema(2));
+//! assert_eq!(
+//!     changes,
+//!     [BreakingChange::FieldNumberChanged {
+//!         message: ".shop.Item".to_string(),
+//!         field: "id".to_string(),
+//!         old: 1,
+//!         new: 2,
+//!     }]
+//! );
+//! assert_eq!(
+//!     changes[0].to_string(),
+//!     "field id of .shop.Item changed its number from 1 to 2"
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::{DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorSet};
+
+/// A change between two versions of a schema which breaks the wire format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BreakingChange {
+    /// A message was removed.
+    MessageRemoved {
+        /// The fully-qualified name of the message.
+        message: String,
+    },
+    /// A field was removed without reserving its number.
+    FieldRemoved {
+        /// The fully-qualified name of the message.
+        message: String,
+        /// The name of the field.
+        field: String,
+        /// The number of the field.
+        number: i32,
+    },
+    /// A field changed its number without reserving the old number.
+    FieldNumberChanged {
+        /// The fully-qualified name of the message.
+        message: String,
+        /// The name of the field.
+        field: String,
+        /// The old number of the field.
+        old: i32,
+        /// The new number of the field.
+        new: i32,
+    },
+    /// A field changed to a type which is not compatible on the wire.
+    FieldTypeChanged {
+        /// The fully-qualified name of the message.
+        message: String,
+        /// The number of the field.
+        number: i32,
+        /// The old type of the field, such as `int32` or `.shop.Item`.
+        old: String,
+        /// The new type of the field.
+        new: String,
+    },
+    /// A field changed between singular and repeated, or to or from `required`.
+    FieldCardinalityChanged {
+        /// The fully-qualified name of the message.
+        message: String,
+        /// The number of the field.
+        number: i32,
+        /// The old label of the field.
+        old: Label,
+        /// The new label of the field.
+        new: Label,
+    },
+    /// A field moved into, out of, or between oneofs.
+    FieldOneofChanged {
+        /// The fully-qualified name of the message.
+        message: String,
+        /// The number of the field.
+        number: i32,
+        /// The name of the old oneof of the field, if any.
+        old: Option<String>,
+        /// The name of the new oneof of the field, if any.
+        new: Option<String>,
+    },
+    /// An enum was removed.
+    EnumRemoved {
+        /// The fully-qualified name of the enum.
+        enumeration: String,
+    },
+    /// An enum value was removed without reserving its number.
+    EnumValueRemoved {
+        /// The fully-qualified name of the enum.
+        enumeration: String,
+        /// The name of the value.
+        value: String,
+        /// The number of the value.
+        number: i32,
+    },
+}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakingChange::MessageRemoved { message } => {
+                write!(f, "message {message} was removed")
+            }
+            BreakingChange::FieldRemoved {
+                message,
+                field,
+                number,
+            } => write!(
+                f,
+                "field {field} ({number}) of {message} was removed without reserving its number"
+            ),
+            BreakingChange::FieldNumberChanged {
+                message,
+                field,
+                old,
+                new,
+            } => write!(
+                f,
+                "field {field} of {message} changed its number from {old} to {new}"
+            ),
+            BreakingChange::FieldTypeChanged {
+                message,
+                number,
+                old,
+                new,
+            } => write!(
+                f,
+                "field {number} of {message} changed its type from {old} to {new}"
+            ),
+            BreakingChange::FieldCardinalityChanged {
+                message,
+                number,
+                old,
+                new,
+            } => write!(
+                f,
+                "field {number} of {message} changed from {} to {}",
+                label_name(*old),
+                label_name(*new)
+            ),
+            BreakingChange::FieldOneofChanged {
+                message,
+                number,
+                old,
+                new,
+            } => {
+                let oneof = |oneof: &Option<String>| match oneof {
+                    Some(name) => format!("oneof {name}"),
+                    None => "no oneof".to_string(),
+                };
+                write!(
+                    f,
+                    "field {number} of {message} moved from {} to {}",
+                    oneof(old),
+                    oneof(new)
+                )
+            }
+            BreakingChange::EnumRemoved { enumeration } => {
+                write!(f, "enum {enumeration} was removed")
+            }
+            BreakingChange::EnumValueRemoved {
+                enumeration,
+                value,
+                number,
+            } => write!(
+                f,
+                "value {value} ({number}) of {enumeration} was removed without reserving its number"
+            ),
+        }
+    }
+}
+
+/// Returns the breaking changes from the `old` to the `new` version of a schema, ordered by the
+/// names of the messages and enums.
+///
+/// Both file descriptor sets should include the imported files, as written by
+/// `protoc --include_imports`, or the types of imported files which are only present in one of
+/// them are reported as removed.
+pub fn check(old: &FileDescriptorSet, new: &FileDescriptorSet) -> Vec<BreakingChange> {
+    let old = Schema::new(old);
+    let new = Schema::new(new);
+    let mut changes = Vec::new();
+
+    for (name, old_message) in &old.messages {
+        match new.messages.get(name) {
+            Some(new_message) => check_message(name, old_message, new_message, &mut changes),
+            None => changes.push(BreakingChange::MessageRemoved {
+                message: name.clone(),
+            }),
+        }
+    }
+
+    for (name, old_enum) in &old.enums {
+        match new.enums.get(name) {
+            Some(new_enum) => check_enum(name, old_enum, new_enum, &mut changes),
+            None => changes.push(BreakingChange::EnumRemoved {
+                enumeration: name.clone(),
+            }),
+        }
+    }
+
+    changes
+}
+
+/// The messages and enums of a schema, by their fully-qualified names.
+struct Schema<'a> {
+    messages: BTreeMap<String, &'a DescriptorProto>,
+    enums: BTreeMap<String, &'a EnumDescriptorProto>,
+}
+
+impl<'a> Schema<'a> {
+    fn new(set: &'a FileDescriptorSet) -> Schema<'a> {
+        let mut schema = Schema {
+            messages: BTreeMap::new(),
+            enums: BTreeMap::new(),
+        };
+        for file in &set.file {
+            let scope = if file.package().is_empty() {
+                String::new()
+            } else {
+                format!(".{}", file.package())
+            };
+            for message in &file.message_type {
+                schema.add_message(&scope, message);
+            }
+            for enum_type in &file.enum_type {
+                schema
+                    .enums
+                    .insert(format!("{scope}.{}", enum_type.name()), enum_type);
+            }
+        }
+        schema
+    }
+
+    fn add_message(&mut self, scope: &str, message: &'a DescriptorProto) {
+        let name = format!("{scope}.{}", message.name());
+        for nested in &message.nested_type {
+            self.add_message(&name, nested);
+        }
+        for enum_type in &message.enum_type {
+            self.enums
+                .insert(format!("{name}.{}", enum_type.name()), enum_type);
+        }
+        self.messages.insert(name, message);
+    }
+}
+
+fn check_message(
+    name: &str,
+    old: &DescriptorProto,
+    new: &DescriptorProto,
+    changes: &mut Vec<BreakingChange>,
+) {
+    for old_field in &old.field {
+        let number = old_field.number();
+        let Some(new_field) = new.field.iter().find(|field| field.number() == number) else {
+            let reserved = new
+                .reserved_range
+                .iter()
+                .any(|range| range.start() <= number && number < range.end());
+            if reserved {
+                continue;
+            }
+            let field = old_field.name().to_string();
+            changes.push(
+                match new
+                    .field
+                    .iter()
+                    .find(|field| field.name() == old_field.name())
+                {
+                    Some(renumbered) => BreakingChange::FieldNumberChanged {
+                        message: name.to_string(),
+                        field,
+                        old: number,
+                        new: renumbered.number(),
+                    },
+                    None => BreakingChange::FieldRemoved {
+                        message: name.to_string(),
+                        field,
+                        number,
+                    },
+                },
+            );
+            continue;
+        };
+
+        let (old_type, new_type) = (field_type(old_field), field_type(new_field));
+        if wire_class(&old_type) != wire_class(&new_type) {
+            changes.push(BreakingChange::FieldTypeChanged {
+                message: name.to_string(),
+                number,
+                old: old_type,
+                new: new_type,
+            });
+        }
+
+        let (old_label, new_label) = (old_field.label(), new_field.label());
+        if (old_label == Label::Repeated) != (new_label == Label::Repeated)
+            || (old_label == Label::Required) != (new_label == Label::Required)
+        {
+            changes.push(BreakingChange::FieldCardinalityChanged {
+                message: name.to_string(),
+                number,
+                old: old_label,
+                new: new_label,
+            });
+        }
+
+        let (old_oneof, new_oneof) = (oneof_name(old, old_field), oneof_name(new, new_field));
+        if old_oneof != new_oneof {
+            changes.push(BreakingChange::FieldOneofChanged {
+                message: name.to_string(),
+                number,
+                old: old_oneof,
+                new: new_oneof,
+            });
+        }
+    }
+}
+
+fn check_enum(
+    name: &str,
+    old: &EnumDescriptorProto,
+    new: &EnumDescriptorProto,
+    changes: &mut Vec<BreakingChange>,
+) {
+    for old_value in &old.value {
+        let number = old_value.number();
+        let present = new.value.iter().any(|value| value.number() == number);
+        // The end of enum reserved ranges is inclusive.
+        let reserved = new
+            .reserved_range
+            .iter()
+            .any(|range| range.start() <= number && number <= range.end());
+        if !present && !reserved {
+            changes.push(BreakingChange::EnumValueRemoved {
+                enumeration: name.to_string(),
+                value: old_value.name().to_string(),
+                number,
+            });
+        }
+    }
+}
+
+/// Returns the type of a field, which is the name of the message or enum for those fields.
+fn field_type(field: &FieldDescriptorProto) -> String {
+    match field.r#type() {
+        Type::Message | Type::Enum | Type::Group if field.type_name.is_some() => {
+            field.type_name().to_string()
+        }
+        ty => ty.as_str_name().trim_start_matches("TYPE_").to_lowercase(),
+    }
+}
+
+/// Returns the class of types with the same encoding as the given type. Message and enum types
+/// are only compatible with themselves.
+fn wire_class(ty: &str) -> &str {
+    match ty {
+        "int32" | "uint32" | "int64" | "uint64" | "bool" => "varint",
+        "sint32" | "sint64" => "zigzag",
+        "fixed32" | "sfixed32" => "fixed32",
+        "fixed64" | "sfixed64" => "fixed64",
+        "string" | "bytes" => "bytes",
+        ty => ty,
+    }
+}
+
+/// Returns the name of the oneof of a field, ignoring the synthetic oneofs of proto3 `optional`
+/// fields.
+fn oneof_name(message: &DescriptorProto, field: &FieldDescriptorProto) -> Option<String> {
+    if field.proto3_optional() {
+        return None;
+    }
+    let index = usize::try_from(field.oneof_index?).ok()?;
+    message
+        .oneof_decl
+        .get(index)
+        .map(|oneof| oneof.name().to_string())
+}
+
+fn label_name(label: Label) -> &'static str {
+    match label {
+        Label::Optional => "singular",
+        Label::Required => "required",
+        Label::Repeated => "repeated",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::descriptor_proto::ReservedRange;
+    use prost_types::{EnumValueDescriptorProto, FileDescriptorProto, OneofDescriptorProto};
+
+    use super::*;
+
+    fn field(name: &str, number: i32, label: Label, ty: Type) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.into()),
+            number: Some(number),
+            label: Some(label as i32),
+            r#type: Some(ty as i32),
+            ..Default::default()
+        }
+    }
+
+    fn message(name: &str, fields: Vec<FieldDescriptorProto>) -> DescriptorProto {
+        DescriptorProto {
+            name: Some(name.into()),
+            field: fields,
+            ..Default::default()
+        }
+    }
+
+    fn set(messages: Vec<DescriptorProto>, enums: Vec<EnumDescriptorProto>) -> FileDescriptorSet {
+        let file = FileDescriptorProto {
+            name: Some("shop.proto".into()),
+            package: Some("shop".into()),
+            message_type: messages,
+            enum_type: enums,
+            ..Default::default()
+        };
+        FileDescriptorSet { file: vec![file] }
+    }
+
+    fn status(values: &[(&str, i32)]) -> EnumDescriptorProto {
+        EnumDescriptorProto {
+            name: Some("Status".into()),
+            value: values
+                .iter()
+                .map(|(name, number)| EnumValueDescriptorProto {
+                    name: Some((*name).into()),
+                    number: Some(*number),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn order() -> DescriptorProto {
+        let item = message("Item", vec![field("sku", 1, Label::Optional, Type::String)]);
+
+        let mut order = message(
+            "Order",
+            vec![
+                field("id", 1, Label::Optional, Type::Int64),
+                field("note", 2, Label::Optional, Type::String),
+                field("total", 3, Label::Optional, Type::Fixed64),
+                field("tags", 4, Label::Repeated, Type::String),
+            ],
+        );
+        order.nested_type.push(item);
+        order
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let schema = set(vec![order()], vec![status(&[("PENDING", 0)])]);
+        assert_eq!(check(&schema, &schema), []);
+    }
+
+    #[test]
+    fn test_compatible_changes() {
+        let old = set(vec![order()], vec![status(&[("PENDING", 0)])]);
+        let mut new_order = order();
+        new_order.field[0].set_type(Type::Uint64);
+        new_order.field[1].name = Some("comment".into());
+        new_order.field[1].set_type(Type::Bytes);
+        new_order.field[2].set_type(Type::Sfixed64);
+        new_order
+            .field
+            .push(field("currency", 5, Label::Optional, Type::String));
+        new_order.field.remove(3);
+        new_order.reserved_range.push(ReservedRange {
+            start: Some(4),
+            end: Some(5),
+        });
+        let new = set(
+            vec![new_order],
+            vec![status(&[("PENDING", 0), ("SHIPPED", 1)])],
+        );
+
+        assert_eq!(check(&old, &new), []);
+    }
+
+    #[test]
+    fn test_removed_types() {
+        let old = set(vec![order()], vec![status(&[("PENDING", 0)])]);
+        let new = set(vec![message("Order", order().field)], vec![]);
+
+        assert_eq!(
+            check(&old, &new),
+            [
+                BreakingChange::MessageRemoved {
+                    message: ".shop.Order.Item".to_string(),
+                },
+                BreakingChange::EnumRemoved {
+                    enumeration: ".shop.Status".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_field_changes() {
+        let old = set(vec![order()], vec![]);
+        let mut new_order = order();
+        new_order.field[0].set_type(Type::Sint64);
+        new_order.field[1].number = Some(6);
+        new_order.field[2].set_label(Label::Repeated);
+        new_order.field.remove(3);
+        let new = set(vec![new_order], vec![]);
+
+        let changes = check(&old, &new);
+        assert_eq!(
+            changes,
+            [
+                BreakingChange::FieldTypeChanged {
+                    message: ".shop.Order".to_string(),
+                    number: 1,
+                    old: "int64".to_string(),
+                    new: "sint64".to_string(),
+                },
+                BreakingChange::FieldNumberChanged {
+                    message: ".shop.Order".to_string(),
+                    field: "note".to_string(),
+                    old: 2,
+                    new: 6,
+                },
+                BreakingChange::FieldCardinalityChanged {
+                    message: ".shop.Order".to_string(),
+                    number: 3,
+                    old: Label::Optional,
+                    new: Label::Repeated,
+                },
+                BreakingChange::FieldRemoved {
+                    message: ".shop.Order".to_string(),
+                    field: "tags".to_string(),
+                    number: 4,
+                },
+            ]
+        );
+        assert_eq!(
+            changes[2].to_string(),
+            "field 3 of .shop.Order changed from singular to repeated"
+        );
+    }
+
+    #[test]
+    fn test_message_type_changed() {
+        let mut old_field = field("item", 1, Label::Optional, Type::Message);
+        old_field.type_name = Some(".shop.Item".into());
+        let mut new_field = old_field.clone();
+        new_field.type_name = Some(".shop.Product".into());
+        let old = set(vec![message("Line", vec![old_field])], vec![]);
+        let new = set(vec![message("Line", vec![new_field])], vec![]);
+
+        assert_eq!(
+            check(&old, &new),
+            [BreakingChange::FieldTypeChanged {
+                message: ".shop.Line".to_string(),
+                number: 1,
+                old: ".shop.Item".to_string(),
+                new: ".shop.Product".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_oneof_changes() {
+        let mut old_payment = message(
+            "Payment",
+            vec![
+                field("card", 1, Label::Optional, Type::String),
+                field("amount", 2, Label::Optional, Type::Int64),
+            ],
+        );
+        old_payment.oneof_decl.push(OneofDescriptorProto {
+            name: Some("method".to_string()),
+            options: None,
+        });
+        old_payment.oneof_decl.push(OneofDescriptorProto {
+            name: Some("_amount".to_string()),
+            options: None,
+        });
+        old_payment.field[0].oneof_index = Some(0);
+        old_payment.field[1].oneof_index = Some(1);
+        old_payment.field[1].proto3_optional = Some(true);
+
+        let mut new_payment = old_payment.clone();
+        new_payment.field[0].oneof_index = None;
+        new_payment.field[1].oneof_index = None;
+        new_payment.field[1].proto3_optional = None;
+
+        let old = set(vec![old_payment], vec![]);
+        let new = set(vec![new_payment], vec![]);
+
+        assert_eq!(
+            check(&old, &new),
+            [BreakingChange::FieldOneofChanged {
+                message: ".shop.Payment".to_string(),
+                number: 1,
+                old: Some("method".to_string()),
+                new: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_enum_value_removed() {
+        let old = set(
+            vec![],
+            vec![status(&[("PENDING", 0), ("SHIPPED", 1), ("LOST", 2)])],
+        );
+        let mut new_status = status(&[("PENDING", 0)]);
+        new_status
+            .reserved_range
+            .push(prost_types::enum_descriptor_proto::EnumReservedRange {
+                start: Some(2),
+                end: Some(2),
+            });
+        let new = set(vec![], vec![new_status]);
+
+        let changes = check(&old, &new);
+        assert_eq!(
+            changes,
+            [BreakingChange::EnumValueRemoved {
+                enumeration: ".shop.Status".to_string(),
+                value: "SHIPPED".to_string(),
+                number: 1,
+            }]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "value SHIPPED (1) of .shop.Status was removed without reserving its number"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! `prost-breaking` reports the breaking wire-format changes between two versions of a Protobuf
+//! schema, given as encoded `FileDescriptorSet`s:
+//!
+//! ```text
+//! prost-breaking old.binpb new.binpb
+//! ```
+//!
+//! Each breaking change is printed on a line of its own. The exit status is 0 if there are no
+//! breaking changes, 1 if there are, and 2 if the file descriptor sets could not be read.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::ExitCode;
+
+use prost::Message;
+use prost_types::FileDescriptorSet;
+
+fn main() -> ExitCode {
+    let args = env::args_os().skip(1).collect::<Vec<_>>();
+    let [old, new] = args.as_slice() else {
+        eprintln!("usage: prost-breaking <old descriptor set> <new descriptor set>");
+        return ExitCode::from(2);
+    };
+
+    let (old, new) = match (load(old.as_ref()), load(new.as_ref())) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(error), _) | (_, Err(error)) => {
+            eprintln!("prost-breaking: {error}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let changes = prost_breaking::check(&old, &new);
+    for change in &changes {
+        println!("{change}");
+    }
+    if changes.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn load(path: &Path) -> io::Result<FileDescriptorSet> {
+    let buf = fs::read(path)
+        .map_err(|error| io::Error::new(error.kind(), format!("{}: {error}", path.display())))?;
+    FileDescriptorSet::decode(buf.as_slice()).map_err(|error| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: invalid FileDescriptorSet: {error}", path.display()),
+        )
+    })
+}
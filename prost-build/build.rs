@@ -0,0 +1,28 @@
+#[cfg(feature = "vendored-protoc")]
+#[path = "build/protobuf.rs"]
+mod protobuf;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // Build or download `protoc`, which `protoc_from_env` falls back to if `PROTOC` is not set.
+    #[cfg(feature = "vendored-protoc")]
+    {
+        let out_dir = std::path::PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+        let protobuf_dir =
+            protobuf::install(&out_dir, false).expect("failed to build vendored protoc");
+        let protoc = if cfg!(windows) {
+            "protoc.exe"
+        } else {
+            "protoc"
+        };
+        println!(
+            "cargo:rustc-env=PROST_VENDORED_PROTOC={}",
+            protobuf_dir.join("bin").join(protoc).display()
+        );
+        println!(
+            "cargo:rustc-env=PROST_VENDORED_PROTOC_INCLUDE={}",
+            protobuf_dir.join("include").display()
+        );
+    }
+}
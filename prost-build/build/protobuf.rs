@@ -0,0 +1,277 @@
+//! Builds the pinned version of Protobuf from source, or downloads a prebuilt artifact.
+//!
+//! This module is shared by the build scripts of `prost-build`, for the `vendored-protoc`
+//! feature, and of the internal `protobuf` crate, for the conformance and integration tests.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+// Protobuf version to fetch
+pub const PROTOBUF_VERSION: &str = "25.8";
+const PROTOBUF_TAG: &str = "v25.8";
+
+// Location of the prebuilt protobuf artifacts, which are created by
+// `protobuf/package-prebuilt.sh`.
+const PREBUILT_URL: &str = "https://github.com/tokio-rs/prost/releases/download/protobuf-v25.8";
+
+// SHA-256 checksums of the prebuilt protobuf artifacts.
+const PREBUILT_CHECKSUMS: &str = include_str!("prebuilt.sha256");
+
+/// Installs Protobuf into `out_dir`, and returns the installation directory.
+///
+/// If `PROST_PREBUILT_PROTOBUF=1` is set, a prebuilt artifact for the host is downloaded, and
+/// Protobuf is only built from source if that fails. The conformance test runner is built if
+/// `conformance` is set and the host is not Windows.
+pub fn install(out_dir: &Path, conformance: bool) -> Result<PathBuf> {
+    println!("cargo:rerun-if-env-changed=PROST_PREBUILT_PROTOBUF");
+    println!("cargo:rerun-if-env-changed=PROST_PREBUILT_PROTOBUF_URL");
+    println!("cargo:rerun-if-env-changed=PROST_PREBUILT_PROTOBUF_SHA256");
+
+    let protobuf_dir = out_dir.join(format!("protobuf-{PROTOBUF_VERSION}"));
+    if protobuf_dir.exists() {
+        return Ok(protobuf_dir);
+    }
+
+    let prebuilt = env::var("PROST_PREBUILT_PROTOBUF").is_ok_and(|value| value == "1");
+    let downloaded = prebuilt
+        && match download_protobuf(out_dir, &protobuf_dir) {
+            Ok(()) => true,
+            Err(error) => {
+                println!(
+                    "cargo:warning=failed to download prebuilt protobuf, building from source: {error:#}"
+                );
+                false
+            }
+        };
+    if !downloaded {
+        build_protobuf(out_dir, &protobuf_dir, conformance)?;
+    }
+    Ok(protobuf_dir)
+}
+
+/// Downloads and unpacks the prebuilt protobuf artifact for the host.
+///
+/// The artifact is a `.tar.gz` archive with the layout of the installed protobuf, that is `bin/`
+/// with `protoc` and `conformance-test-runner`, `include/` and `lib/`, and additionally
+/// `protobuf-src/` with the `.proto` files of the protobuf source tree. Its SHA-256 checksum must
+/// be listed in `prebuilt.sha256`, or be set with `PROST_PREBUILT_PROTOBUF_SHA256`.
+fn download_protobuf(out_dir: &Path, protobuf_dir: &Path) -> Result<()> {
+    let host = env::var("HOST").context("HOST not set")?;
+    let artifact = format!("protobuf-{PROTOBUF_VERSION}-{host}.tar.gz");
+    let base_url = env::var("PROST_PREBUILT_PROTOBUF_URL").unwrap_or_else(|_| PREBUILT_URL.into());
+    let url = format!("{}/{artifact}", base_url.trim_end_matches('/'));
+
+    let expected = match env::var("PROST_PREBUILT_PROTOBUF_SHA256") {
+        Ok(checksum) => checksum,
+        Err(_) => PREBUILT_CHECKSUMS
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .find_map(|line| {
+                let (checksum, name) = line.split_once(char::is_whitespace)?;
+                (name.trim() == artifact).then(|| checksum.to_owned())
+            })
+            .with_context(|| format!("no checksum is known for {artifact}"))?,
+    };
+
+    let tempdir = tempfile::Builder::new()
+        .prefix("protobuf")
+        .tempdir_in(out_dir)
+        .context("failed to create temporary directory")?;
+    let archive = tempdir.path().join(&artifact);
+    run(Command::new("curl")
+        .args([
+            "--fail",
+            "--location",
+            "--silent",
+            "--show-error",
+            "--output",
+        ])
+        .arg(&archive)
+        .arg(&url))
+    .with_context(|| format!("failed to download {url}"))?;
+
+    let actual = sha256(&archive)?;
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        anyhow::bail!("checksum mismatch for {artifact}: expected {expected}, got {actual}");
+    }
+
+    let prefix_dir = tempdir.path().join("prefix");
+    fs::create_dir(&prefix_dir).context("failed to create prefix directory")?;
+    run(Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(&prefix_dir))
+    .with_context(|| format!("failed to unpack {artifact}"))?;
+
+    fs::rename(&prefix_dir, protobuf_dir).context("failed to move protobuf dir")?;
+    Ok(())
+}
+
+/// Returns the hex-encoded SHA-256 checksum of a file.
+fn sha256(path: &Path) -> Result<String> {
+    let output = Command::new("sha256sum")
+        .arg(path)
+        .output()
+        .or_else(|_| {
+            Command::new("shasum")
+                .args(["-a", "256"])
+                .arg(path)
+                .output()
+        })
+        .context("failed to execute sha256sum or shasum")?;
+    if !output.status.success() {
+        anyhow::bail!("failed to compute the checksum of {}", path.display());
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .and_then(|output| output.split_whitespace().next().map(str::to_owned))
+        .context("invalid checksum output")
+}
+
+fn run(command: &mut Command) -> Result<()> {
+    let status = command
+        .status()
+        .with_context(|| format!("failed to execute {:?}", command.get_program()))?;
+    if !status.success() {
+        anyhow::bail!("{:?} exited with {status}", command.get_program());
+    }
+    Ok(())
+}
+
+fn build_protobuf(out_dir: &Path, protobuf_dir: &Path, conformance: bool) -> Result<()> {
+    let build_dir = out_dir.join(format!("build-protobuf-{PROTOBUF_VERSION}"));
+    fs::create_dir_all(&build_dir).context("failed to create build directory")?;
+
+    let tempdir = tempfile::Builder::new()
+        .prefix("protobuf")
+        .tempdir_in(out_dir)
+        .context("failed to create temporary directory")?;
+
+    let prefix_dir = tempdir.path().join("prefix");
+    fs::create_dir(&prefix_dir).context("failed to create prefix directory")?;
+
+    write_cmake_file(&build_dir, conformance)?;
+    build_with_cmake(&build_dir, &prefix_dir, conformance)?;
+
+    fs::rename(&prefix_dir, protobuf_dir).context("failed to move protobuf dir")?;
+    Ok(())
+}
+
+fn write_cmake_file(build_dir: &Path, conformance: bool) -> Result<()> {
+    let system_processor = match () {
+        _ if cfg!(target_arch = "aarch64") => "aarch64",
+        _ if cfg!(target_arch = "x86_64") => "x86_64",
+        _ => "unknown",
+    };
+
+    let build_conformance = if conformance && !cfg!(windows) {
+        "ON"
+    } else {
+        "OFF"
+    };
+
+    let cmake_content = format!(
+        r#"cmake_minimum_required(VERSION 3.14)
+
+# Set processor type BEFORE project() so CMake detection works correctly
+set(CMAKE_SYSTEM_PROCESSOR {system_processor})
+
+project(protobuf-fetcher C CXX)
+
+include(FetchContent)
+
+FetchContent_Declare(
+  protobuf
+  GIT_REPOSITORY https://github.com/protocolbuffers/protobuf.git
+  GIT_TAG {tag}
+  GIT_SHALLOW TRUE
+)
+
+set(CMAKE_CXX_STANDARD 14)
+set(CMAKE_CXX_STANDARD_REQUIRED ON)
+set(ABSL_PROPAGATE_CXX_STD ON)
+set(ABSL_USE_EXTERNAL_GOOGLETEST ON)
+set(ABSL_BUILD_TESTING OFF)
+set(ABSL_ENABLE_INSTALL ON)
+set(protobuf_BUILD_CONFORMANCE {conformance})
+set(protobuf_BUILD_TESTS OFF)
+set(protobuf_ABSL_PROVIDER "module")
+
+# On macOS with Nix, abseil's multi-arch support conflicts with Nix's --target flags.
+# Only apply workaround if we detect Nix environment (CMAKE_C_FLAGS contains --target).
+if(APPLE AND CMAKE_OSX_ARCHITECTURES STREQUAL "arm64")
+  # Check if CMAKE_C_FLAGS suggests we're in Nix (contains --target)
+  string(FIND "${{CMAKE_C_FLAGS}}" "--target" NIX_DETECTED)
+  if(NOT NIX_DETECTED EQUAL -1)
+    message(STATUS "Detected Nix environment, applying abseil workaround")
+    set(_SAVED_APPLE "${{APPLE}}")
+    set(APPLE FALSE CACHE BOOL "" FORCE)
+  endif()
+endif()
+
+FetchContent_MakeAvailable(protobuf)
+
+# Restore APPLE flag after configuration
+if(_SAVED_APPLE)
+  set(APPLE "${{_SAVED_APPLE}}" CACHE BOOL "" FORCE)
+endif()
+"#,
+        system_processor = system_processor,
+        tag = PROTOBUF_TAG,
+        conformance = build_conformance
+    );
+
+    fs::write(build_dir.join("CMakeLists.txt"), cmake_content)
+        .context("failed to write CMakeLists.txt")
+}
+
+fn build_with_cmake(build_dir: &Path, prefix_dir: &Path, conformance: bool) -> Result<()> {
+    // `protoc` and the conformance test runner run on the host, also when cross-compiling, such
+    // as the tests for wasm targets.
+    let host = env::var("HOST").context("HOST not set")?;
+    let mut config = cmake::Config::new(build_dir);
+    config
+        .target(&host)
+        .host(&host)
+        .define("CMAKE_INSTALL_PREFIX", prefix_dir)
+        .define("CMAKE_CXX_STANDARD", "14")
+        .define("ABSL_PROPAGATE_CXX_STD", "ON")
+        .out_dir(build_dir);
+
+    if cfg!(target_arch = "aarch64") {
+        config
+            .define("ABSL_USE_EXTERNAL_GOOGLETEST", "ON")
+            .define("ABSL_BUILD_TESTING", "OFF");
+    }
+
+    // On Windows, reduce parallel build jobs to avoid resource exhaustion
+    if cfg!(windows) {
+        config.build_arg("-j2");
+    }
+
+    config.build();
+
+    // Copy conformance-test-runner if it was built (non-Windows only)
+    if conformance && !cfg!(windows) {
+        let conformance_runner = build_dir
+            .join("build")
+            .join("_deps")
+            .join("protobuf-build")
+            .join("conformance_test_runner");
+
+        if conformance_runner.exists() {
+            fs::copy(
+                &conformance_runner,
+                prefix_dir.join("bin").join("conformance-test-runner"),
+            )
+            .context("failed to copy conformance-test-runner")?;
+        }
+    }
+
+    Ok(())
+}
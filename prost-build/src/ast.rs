@@ -39,6 +39,32 @@ impl Comments {
         }
     }
 
+    /// Converts links to Protobuf types, such as `[Foo][pkg.Foo]`, into intra-doc links.
+    ///
+    /// `resolve` returns the Rust path of the type with the given Protobuf name, or `None` if the
+    /// name is not a known type, in which case the link is kept unchanged.
+    pub(crate) fn resolve_links<F>(&mut self, resolve: F)
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        // Markdown cleanup escapes the brackets of links which have no reference definition.
+        static RULE_LINK: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"\\?\[([^\[\]]+?)\\?\]\\?\[(\.?[A-Za-z_][A-Za-z0-9_.\\]*)\\?\]").unwrap()
+        });
+
+        for line in self.leading.iter_mut().chain(self.trailing.iter_mut()) {
+            *line = RULE_LINK
+                .replace_all(line, |captures: &regex::Captures| {
+                    let text = captures[1].trim_end_matches('\\');
+                    match resolve(&captures[2].replace('\\', "")) {
+                        Some(path) => format!("[{text}][{path}]"),
+                        None => captures[0].to_string(),
+                    }
+                })
+                .into_owned();
+        }
+    }
+
     /// Appends the comments to a buffer with indentation.
     ///
     /// Each level of indentation corresponds to four space (' ') characters.
@@ -447,4 +473,56 @@ mod tests {
             assert_eq!(expected, comments.leading, "failed {}", t.name);
         }
     }
+
+    #[test]
+    fn test_comment_resolve_links() {
+        struct TestCases {
+            name: &'static str,
+            input: &'static str,
+            expected: &'static str,
+        }
+
+        let tests = vec![
+            TestCases {
+                name: "qualified",
+                input: "See [Foo][pkg.Foo].",
+                expected: "/// See [Foo][super::pkg::Foo].\n",
+            },
+            TestCases {
+                name: "fully_qualified",
+                input: "See [the foo][.pkg.Foo].",
+                expected: "/// See [the foo][super::pkg::Foo].\n",
+            },
+            TestCases {
+                name: "escaped",
+                input: "See \\[Foo\\]\\[pkg.Foo\\].",
+                expected: "/// See [Foo][super::pkg::Foo].\n",
+            },
+            TestCases {
+                name: "unknown",
+                input: "See [Bar][pkg.Bar].",
+                expected: "/// See [Bar][pkg.Bar].\n",
+            },
+            TestCases {
+                name: "not_a_link",
+                input: "See [Foo] and [0, 9).",
+                expected: "/// See \\[Foo\\] and [0, 9).\n",
+            },
+        ];
+        for t in tests {
+            let mut input = Comments {
+                leading_detached: vec![],
+                leading: vec![t.input.to_string()],
+                trailing: vec![],
+            };
+            input.resolve_links(|name| {
+                matches!(name, "pkg.Foo" | ".pkg.Foo").then(|| "super::pkg::Foo".to_string())
+            });
+
+            let mut actual = "".to_string();
+            input.append_with_indent(0, &mut actual);
+
+            assert_eq!(t.expected, actual, "failed {}", t.name);
+        }
+    }
 }
@@ -0,0 +1,169 @@
+//! Caching of generated code, see `Config::enable_codegen_cache`.
+//!
+//! After generating the code of a set of `.proto` files, `compile_protos` writes a cache file to
+//! the output directory, holding a digest of its inputs, the `.proto` files imported by the
+//! compiled files, and the files it wrote. A later run whose inputs have the same digest, and
+//! whose outputs all still exist, skips `protoc` and code generation.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::Hasher;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+/// A digest of the inputs of code generation.
+///
+/// The digest is not stable across Rust releases, which only causes a cache miss.
+#[derive(Clone)]
+pub(crate) struct Digest(DefaultHasher);
+
+impl Digest {
+    pub(crate) fn new() -> Digest {
+        Digest(DefaultHasher::new())
+    }
+
+    /// Adds a value to the digest, delimited from the values added before and after it.
+    pub(crate) fn add(&mut self, bytes: &[u8]) {
+        self.0.write_usize(bytes.len());
+        self.0.write(bytes);
+    }
+
+    /// Adds the path and the contents of a file to the digest.
+    pub(crate) fn add_file(&mut self, path: &Path) -> Result<()> {
+        self.add(path.as_os_str().as_encoded_bytes());
+        self.add(&fs::read(path)?);
+        Ok(())
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+/// The contents of a cache file.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct CacheEntry {
+    /// The digest of the inputs, including the contents of the dependencies.
+    pub(crate) digest: u64,
+    /// The imported `.proto` files found in the include directories.
+    pub(crate) dependencies: Vec<PathBuf>,
+    /// The files written by code generation.
+    pub(crate) outputs: Vec<PathBuf>,
+}
+
+impl CacheEntry {
+    /// Reads the cache file at `path`, returning `None` if it does not exist or is invalid.
+    pub(crate) fn read(path: &Path) -> Option<CacheEntry> {
+        let contents = fs::read_to_string(path).ok()?;
+        let mut entry = CacheEntry::default();
+        let mut lines = contents.lines();
+        entry.digest = u64::from_str_radix(lines.next()?.strip_prefix("digest ")?, 16).ok()?;
+        for line in lines {
+            if let Some(dependency) = line.strip_prefix("dependency ") {
+                entry.dependencies.push(dependency.into());
+            } else if let Some(output) = line.strip_prefix("output ") {
+                entry.outputs.push(output.into());
+            } else {
+                return None;
+            }
+        }
+        Some(entry)
+    }
+
+    /// Writes the cache file to `path`.
+    ///
+    /// The file is written to a temporary file which replaces it, so that an interrupted build
+    /// never leaves a partial cache file behind.
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        let mut contents = format!("digest {:016x}\n", self.digest);
+        let lines = self
+            .dependencies
+            .iter()
+            .map(|dependency| ("dependency", dependency))
+            .chain(self.outputs.iter().map(|output| ("output", output)));
+        for (kind, path) in lines {
+            let path = path.to_str().filter(|path| !path.contains('\n'));
+            let path = path.ok_or_else(|| {
+                Error::new(ErrorKind::InvalidInput, "cached paths must be valid UTF-8")
+            })?;
+            contents.push_str(&format!("{kind} {path}\n"));
+        }
+
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, contents)?;
+        fs::rename(&tmp_path, path)
+    }
+
+    /// Returns whether the entry is up to date with the inputs digested in `key`.
+    pub(crate) fn is_fresh(&self, key: &Digest) -> bool {
+        let mut digest = key.clone();
+        for dependency in &self.dependencies {
+            if digest.add_file(dependency).is_err() {
+                return false;
+            }
+        }
+        digest.finish() == self.digest && self.outputs.iter().all(|output| output.is_file())
+    }
+}
+
+/// Returns the name of the cache file of a set of `.proto` files, so that the calls to
+/// `compile_protos` of a build script writing to the same directory have separate cache files.
+pub(crate) fn cache_file_name(protos: &[impl AsRef<Path>]) -> String {
+    let mut digest = Digest::new();
+    for proto in protos {
+        digest.add(proto.as_ref().as_os_str().as_encoded_bytes());
+    }
+    format!(".prost-build-{:016x}.cache", digest.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_entry_roundtrip() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join(cache_file_name(&["a.proto"]));
+        let entry = CacheEntry {
+            digest: 0x0123_4567_89ab_cdef,
+            dependencies: vec!["protos/b.proto".into()],
+            outputs: vec!["out/a.rs".into(), "out/b.rs".into()],
+        };
+
+        entry.write(&path).unwrap();
+        assert_eq!(CacheEntry::read(&path), Some(entry));
+        assert_eq!(CacheEntry::read(&tempdir.path().join("missing")), None);
+    }
+
+    #[test]
+    fn test_cache_entry_freshness() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dependency = tempdir.path().join("b.proto");
+        let output = tempdir.path().join("b.rs");
+        fs::write(&dependency, "syntax = \"proto3\";").unwrap();
+        fs::write(&output, "").unwrap();
+
+        let mut key = Digest::new();
+        key.add(b"config");
+        let mut digest = key.clone();
+        digest.add_file(&dependency).unwrap();
+        let entry = CacheEntry {
+            digest: digest.finish(),
+            dependencies: vec![dependency.clone()],
+            outputs: vec![output.clone()],
+        };
+        assert!(entry.is_fresh(&key));
+
+        let mut other_key = Digest::new();
+        other_key.add(b"other config");
+        assert!(!entry.is_fresh(&other_key));
+
+        fs::write(&dependency, "syntax = \"proto2\";").unwrap();
+        assert!(!entry.is_fresh(&key));
+        fs::write(&dependency, "syntax = \"proto3\";").unwrap();
+        assert!(entry.is_fresh(&key));
+
+        fs::remove_file(&output).unwrap();
+        assert!(!entry.is_fresh(&key));
+    }
+}
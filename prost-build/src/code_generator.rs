@@ -1,6 +1,6 @@
 use std::ascii;
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::iter;
 
 use itertools::{Either, Itertools};
@@ -15,9 +15,10 @@ use prost_types::{
 };
 
 use crate::ast::{Comments, Method, Service};
-use crate::context::Context;
+use crate::config::byte_string_literal;
+use crate::context::{Comparisons, Context};
 use crate::ident::{strip_enum_prefix, to_snake, to_upper_camel};
-use crate::Config;
+use crate::{Config, StringType, Wrapper};
 
 mod c_escaping;
 use c_escaping::unescape_c_escape_string;
@@ -25,6 +26,10 @@ use c_escaping::unescape_c_escape_string;
 mod syntax;
 use syntax::Syntax;
 
+mod heapless;
+
+mod validate;
+
 /// State object for the code generation process on a single input file.
 pub struct CodeGenerator<'a, 'b> {
     context: &'a mut Context<'b>,
@@ -37,6 +42,22 @@ pub struct CodeGenerator<'a, 'b> {
     buf: &'a mut String,
 }
 
+/// Returns the comparison traits to derive for a message or oneof, which are implemented
+/// separately if its floats are compared by their total order.
+fn comparison_derives(comparisons: Option<Comparisons>, total_order: bool) -> &'static str {
+    match comparisons {
+        _ if total_order => "",
+        None => "PartialEq, ",
+        Some(Comparisons::EqHash) => "PartialEq, Eq, Hash, ",
+        Some(Comparisons::Ord) => "PartialEq, Eq, Hash, PartialOrd, Ord, ",
+    }
+}
+
+/// Returns `true` if the field holds `float` or `double` values.
+fn is_float(field: &FieldDescriptorProto) -> bool {
+    matches!(field.r#type(), Type::Float | Type::Double)
+}
+
 fn push_indent(buf: &mut String, depth: u8) {
     for _ in 0..depth {
         buf.push_str("    ");
@@ -103,12 +124,35 @@ impl OneofField {
     }
 }
 
+/// How the values of a field are held, which determines its serde attribute.
+#[derive(Clone, Copy)]
+enum SerdeShape<'a> {
+    /// A field without presence.
+    Singular,
+    /// A proto2 `required` field.
+    Required,
+    /// A field wrapped in an `Option`.
+    Optional,
+    Repeated,
+    /// A map field, with the descriptor of the map values.
+    Map(&'a FieldDescriptorProto),
+    /// A variant of a oneof.
+    Variant,
+}
+
 impl<'b> CodeGenerator<'_, 'b> {
     fn config(&self) -> &Config {
         self.context.config()
     }
 
-    pub(crate) fn generate(context: &mut Context<'b>, file: FileDescriptorProto, buf: &mut String) {
+    /// Generates the code of a file into `buf`. If `split_messages` is set, the code of each
+    /// top-level message is returned with the name of the message instead.
+    pub(crate) fn generate(
+        context: &mut Context<'b>,
+        file: FileDescriptorProto,
+        buf: &mut String,
+        split_messages: bool,
+    ) -> Vec<(String, String)> {
         let source_info = file.source_code_info.map(|mut s| {
             s.location.retain(|loc| {
                 let len = loc.path.len();
@@ -135,10 +179,16 @@ impl<'b> CodeGenerator<'_, 'b> {
             code_gen.package
         );
 
+        let mut messages = Vec::new();
         code_gen.path.push(4);
         for (idx, message) in file.message_type.into_iter().enumerate() {
             code_gen.path.push(idx as i32);
+            let start = code_gen.buf.len();
+            let message_name = message.name().to_string();
             code_gen.append_message(message);
+            if split_messages && code_gen.buf.len() > start {
+                messages.push((message_name, code_gen.buf.split_off(start)));
+            }
             code_gen.path.pop();
         }
         code_gen.path.pop();
@@ -151,6 +201,14 @@ impl<'b> CodeGenerator<'_, 'b> {
         }
         code_gen.path.pop();
 
+        code_gen.path.push(7);
+        for (idx, extension) in file.extension.into_iter().enumerate() {
+            code_gen.path.push(idx as i32);
+            code_gen.append_extension(extension);
+            code_gen.path.pop();
+        }
+        code_gen.path.pop();
+
         if code_gen.context.service_generator_mut().is_some() {
             code_gen.path.push(6);
             for (idx, service) in file.service.into_iter().enumerate() {
@@ -165,6 +223,8 @@ impl<'b> CodeGenerator<'_, 'b> {
 
             code_gen.path.pop();
         }
+
+        messages
     }
 
     fn append_message(&mut self, message: DescriptorProto) {
@@ -240,31 +300,61 @@ impl<'b> CodeGenerator<'_, 'b> {
             })
             .collect();
 
+        let message_ref = self.context.should_generate_message_ref(&fq_message_name);
+        let arena_message = self.context.should_generate_arena_message(&fq_message_name);
+
         self.append_doc(&fq_message_name, None);
         self.append_type_attributes(&fq_message_name);
         self.append_message_attributes(&fq_message_name);
+        let attributes = self
+            .context
+            .provided_message_attributes(&fq_message_name, &message);
+        self.append_provided_attributes(attributes);
+        let comparisons = self.context.message_comparisons(&fq_message_name);
+        // Floats only implement the comparison traits with their total order, which the derived
+        // impls don't use.
+        let total_order = comparisons.is_some()
+            && fields.iter().any(|field| {
+                let value = field
+                    .descriptor
+                    .type_name
+                    .as_ref()
+                    .and_then(|type_name| map_types.get(type_name))
+                    .map_or(&field.descriptor, |(_, value)| value);
+                is_float(value)
+            });
         self.push_indent();
         self.buf.push_str(&format!(
-            "#[derive(Clone, {}PartialEq, {}{}::Message)]\n",
+            "#[derive(Clone, {}{}{}::Message)]\n",
             if self.context.can_message_derive_copy(&fq_message_name) {
                 "Copy, "
             } else {
                 ""
             },
-            if self.context.can_message_derive_eq(&fq_message_name) {
-                "Eq, Hash, "
-            } else {
-                ""
-            },
+            comparison_derives(comparisons, total_order),
             self.context.prost_path()
         ));
         self.append_prost_path_attribute();
         self.append_skip_debug(&fq_message_name);
+        self.append_json();
+        self.append_arbitrary(&fq_message_name);
+        self.append_text_format();
+        self.append_text_format_debug();
+        self.append_field_mask();
+        self.append_const_default(&fq_message_name);
+        self.append_proto_cmp(&fq_message_name);
+        self.append_accessors(&fq_message_name);
+        if !self.context.has_presence_bits(&fq_message_name) {
+            self.append_serde(true);
+        }
+        self.append_non_exhaustive(&fq_message_name);
         self.push_indent();
         self.buf.push_str("pub struct ");
         self.buf.push_str(&to_upper_camel(&message_name));
         self.buf.push_str(" {\n");
 
+        let presence_bits = self.presence_bits(&fq_message_name, &fields);
+
         self.depth += 1;
         self.path.push(2);
         for field in &fields {
@@ -276,7 +366,11 @@ impl<'b> CodeGenerator<'_, 'b> {
                 .and_then(|type_name| map_types.get(type_name))
             {
                 Some((key, value)) => self.append_map_field(&fq_message_name, field, key, value),
-                None => self.append_field(&fq_message_name, field),
+                None => self.append_field(
+                    &fq_message_name,
+                    field,
+                    presence_bits.get(&field.descriptor.number()).copied(),
+                ),
             }
             self.path.pop();
         }
@@ -290,11 +384,63 @@ impl<'b> CodeGenerator<'_, 'b> {
         }
         self.path.pop();
 
+        if !presence_bits.is_empty() {
+            self.append_presence_bits_field(presence_bits.len());
+        }
+
+        if self.context.should_preserve_extensions(&fq_message_name) {
+            self.append_extension_set_field(&fq_message_name, &message);
+        }
+
+        if self
+            .context
+            .should_preserve_unknown_fields(&fq_message_name)
+        {
+            self.append_unknown_fields_field(&fq_message_name);
+        }
+
         self.depth -= 1;
         self.push_indent();
         self.buf.push_str("}\n");
 
-        if !message.enum_type.is_empty() || !nested_types.is_empty() || !oneof_fields.is_empty() {
+        if let (Some(comparisons), true) = (comparisons, total_order) {
+            let mut members = fields
+                .iter()
+                .map(|field| {
+                    let value = field
+                        .descriptor
+                        .type_name
+                        .as_ref()
+                        .and_then(|type_name| map_types.get(type_name))
+                        .map_or(&field.descriptor, |(_, value)| value);
+                    (field.rust_name(), is_float(value))
+                })
+                .collect::<Vec<_>>();
+            members.extend(oneof_fields.iter().map(|oneof| (oneof.rust_name(), false)));
+            if !presence_bits.is_empty() {
+                members.push(("_presence_bits".to_string(), false));
+            }
+            if self.context.should_preserve_extensions(&fq_message_name) {
+                members.push(("_extensions".to_string(), false));
+            }
+            if self
+                .context
+                .should_preserve_unknown_fields(&fq_message_name)
+            {
+                members.push(("_unknown_fields".to_string(), false));
+            }
+            self.append_total_order_struct_impls(
+                &to_upper_camel(&message_name),
+                &members,
+                comparisons,
+            );
+        }
+
+        if !message.enum_type.is_empty()
+            || !nested_types.is_empty()
+            || !oneof_fields.is_empty()
+            || !message.extension.is_empty()
+        {
             self.push_mod(&message_name);
             self.path.push(3);
             for (nested_type, idx) in nested_types {
@@ -316,12 +462,225 @@ impl<'b> CodeGenerator<'_, 'b> {
                 self.append_oneof(&fq_message_name, oneof);
             }
 
+            if message_ref {
+                for oneof in &oneof_fields {
+                    self.append_oneof_ref(&fq_message_name, oneof);
+                }
+            }
+
+            if arena_message {
+                for oneof in &oneof_fields {
+                    self.append_oneof_arena(&fq_message_name, oneof);
+                }
+            }
+
+            self.path.push(6);
+            for (idx, extension) in message.extension.into_iter().enumerate() {
+                self.path.push(idx as i32);
+                self.append_extension(extension);
+                self.path.pop();
+            }
+            self.path.pop();
+
             self.pop_mod();
         }
 
+        if message_ref {
+            self.append_message_ref(
+                &message_name,
+                &fq_message_name,
+                &fields,
+                &map_types,
+                &oneof_fields,
+            );
+        }
+
+        if arena_message {
+            self.append_arena_message(
+                &message_name,
+                &fq_message_name,
+                &fields,
+                &map_types,
+                &oneof_fields,
+            );
+        }
+
+        if self.config().generate_builders && !self.context.should_use_heapless(&fq_message_name) {
+            self.append_builder(
+                &message_name,
+                &fq_message_name,
+                &fields,
+                &map_types,
+                &oneof_fields,
+            );
+        }
+
+        if self.config().generate_oneof_accessors && !oneof_fields.is_empty() {
+            self.append_oneof_accessors(&message_name, &fq_message_name, &oneof_fields);
+        }
+
+        if self.config().generate_field_numbers {
+            self.append_field_numbers(&message_name, &fields, &oneof_fields);
+        }
+
+        if self.context.should_validate(&fq_message_name) {
+            self.append_validate(
+                &message_name,
+                &fq_message_name,
+                &fields,
+                &map_types,
+                &oneof_fields,
+            );
+        }
+
         if self.config().enable_type_names {
             self.append_type_name(&message_name, &fq_message_name);
         }
+
+        if let Some(descriptor) = self.context.message_descriptor(&fq_message_name) {
+            let descriptor = byte_string_literal(descriptor);
+            self.append_message_descriptor(&message_name, &fq_message_name, &descriptor);
+        }
+
+        if self.config().enable_serde && self.context.has_presence_bits(&fq_message_name) {
+            self.append_serde_impls(&message_name);
+        }
+
+        if self.config().generate_conversions && !self.context.should_use_heapless(&fq_message_name)
+        {
+            self.append_conversions(&message_name);
+        }
+
+        if self.config().enable_reflection {
+            self.append_reflect_message(&message_name);
+        }
+
+        for other_message_name in self.context.version_counterparts(&fq_message_name) {
+            self.append_version_conversion(&message_name, &other_message_name);
+        }
+    }
+
+    /// Appends the conversion from the message of another version of the package, which is
+    /// encoded and decoded as this message.
+    fn append_version_conversion(&mut self, message_name: &str, other_message_name: &str) {
+        let prost_path = self.context.prost_path();
+        let other_type = self.resolve_ident(other_message_name);
+
+        self.buf.push_str(&format!(
+            "impl ::core::convert::TryFrom<{other_type}> for {} {{\n",
+            to_upper_camel(message_name)
+        ));
+        self.depth += 1;
+
+        self.buf
+            .push_str(&format!("type Error = {prost_path}::DecodeError;\n"));
+        self.buf.push_str(&format!(
+            "fn try_from(value: {other_type}) -> ::core::result::Result<Self, Self::Error> {{\n"
+        ));
+        self.buf.push_str(&format!(
+            "{prost_path}::Message::decode({prost_path}::Message::encode_to_vec(&value).as_slice())\n"
+        ));
+        self.buf.push_str("}\n");
+
+        self.depth -= 1;
+        self.buf.push_str("}\n");
+    }
+
+    /// Appends the constants holding the encoded descriptor and the full name of the message.
+    fn append_message_descriptor(
+        &mut self,
+        message_name: &str,
+        fq_message_name: &str,
+        descriptor: &str,
+    ) {
+        self.buf
+            .push_str(&format!("impl {} {{\n", to_upper_camel(message_name)));
+        self.depth += 1;
+        self.buf.push_str(
+            "/// The encoded `DescriptorProto` of this message, see `Config::embed_message_descriptors`.\n",
+        );
+        self.buf.push_str(&format!(
+            "pub const DESCRIPTOR_PROTO: &'static [u8] = {descriptor};\n"
+        ));
+        self.buf
+            .push_str("/// The fully qualified Protobuf name of this message.\n");
+        self.buf.push_str(&format!(
+            "pub const FULL_NAME: &'static str = \"{}\";\n",
+            fq_message_name.trim_start_matches('.')
+        ));
+        self.depth -= 1;
+        self.buf.push_str("}\n");
+    }
+
+    /// Appends the serde implementations of a message with presence bits, which can't be derived
+    /// as its fields don't hold their presence. They follow the JSON mapping of the message, as
+    /// the derived implementations do.
+    fn append_serde_impls(&mut self, message_name: &str) {
+        let prost_path = self.context.prost_path();
+        let message_name = to_upper_camel(message_name);
+
+        self.buf.push_str(&format!(
+            "impl {prost_path}::serde::Serialize for {message_name} {{\n"
+        ));
+        self.depth += 1;
+        self.buf.push_str(&format!(
+            "fn serialize<S: {prost_path}::serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {{\n"
+        ));
+        self.buf.push_str(&format!(
+            "{prost_path}::json::serde::Message::serialize(self, serializer)\n"
+        ));
+        self.buf.push_str("}\n");
+        self.depth -= 1;
+        self.buf.push_str("}\n");
+
+        self.buf.push_str(&format!(
+            "impl<'de> {prost_path}::serde::Deserialize<'de> for {message_name} {{\n"
+        ));
+        self.depth += 1;
+        self.buf.push_str(&format!(
+            "fn deserialize<D: {prost_path}::serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {{\n"
+        ));
+        self.buf.push_str(&format!(
+            "{prost_path}::json::serde::Message::deserialize(deserializer)\n"
+        ));
+        self.buf.push_str("}\n");
+        self.depth -= 1;
+        self.buf.push_str("}\n");
+    }
+
+    /// Appends the conversions of the message from its encoding, and to its encoding.
+    fn append_conversions(&mut self, message_name: &str) {
+        let prost_path = self.context.prost_path();
+        let message_name = to_upper_camel(message_name);
+
+        for source in ["&[u8]", &format!("{prost_path}::bytes::Bytes")] {
+            self.buf.push_str(&format!(
+                "impl ::core::convert::TryFrom<{source}> for {message_name} {{\n"
+            ));
+            self.depth += 1;
+            self.buf
+                .push_str(&format!("type Error = {prost_path}::DecodeError;\n"));
+            self.buf.push_str(&format!(
+                "fn try_from(value: {source}) -> ::core::result::Result<Self, Self::Error> {{\n"
+            ));
+            self.buf
+                .push_str(&format!("{prost_path}::Message::decode(value)\n"));
+            self.buf.push_str("}\n");
+            self.depth -= 1;
+            self.buf.push_str("}\n");
+        }
+
+        self.buf.push_str(&format!(
+            "impl ::core::convert::From<{message_name}> for {prost_path}::alloc::vec::Vec<u8> {{\n"
+        ));
+        self.depth += 1;
+        self.buf
+            .push_str(&format!("fn from(value: {message_name}) -> Self {{\n"));
+        self.buf
+            .push_str(&format!("{prost_path}::Message::encode_to_vec(&value)\n"));
+        self.buf.push_str("}\n");
+        self.depth -= 1;
+        self.buf.push_str("}\n");
     }
 
     fn append_type_name(&mut self, message_name: &str, fq_message_name: &str) {
@@ -363,6 +722,34 @@ impl<'b> CodeGenerator<'_, 'b> {
         self.buf.push_str("}\n");
     }
 
+    /// Appends the `ReflectMessage` implementation, which takes the descriptor of the message from
+    /// the `DESCRIPTOR_POOL` of the package module.
+    fn append_reflect_message(&mut self, message_name: &str) {
+        let reflect_path = format!("{}::reflect", self.config().prost_types_path_or_default());
+
+        self.buf.push_str(&format!(
+            "impl {reflect_path}::ReflectMessage for {} {{\n",
+            to_upper_camel(message_name)
+        ));
+        self.depth += 1;
+
+        let full_name = format!(
+            "{}{}{}{}{message_name}",
+            self.package.trim_matches('.'),
+            if self.package.is_empty() { "" } else { "." },
+            self.type_path.join("."),
+            if self.type_path.is_empty() { "" } else { "." },
+        );
+        let pool_path = format!("{}DESCRIPTOR_POOL", "super::".repeat(self.type_path.len()));
+
+        self.buf.push_str(&format!(
+            "fn descriptor(&self) -> {reflect_path}::MessageDescriptor {{ {pool_path}.get_message(\"{full_name}\") }}\n",
+        ));
+
+        self.depth -= 1;
+        self.buf.push_str("}\n");
+    }
+
     fn append_type_attributes(&mut self, fq_message_name: &str) {
         assert_eq!(b'.', fq_message_name.as_bytes()[0]);
         for attribute in self.context.type_attributes(fq_message_name) {
@@ -381,6 +768,14 @@ impl<'b> CodeGenerator<'_, 'b> {
         }
     }
 
+    fn append_provided_attributes(&mut self, attributes: Vec<String>) {
+        for attribute in attributes {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str(&attribute);
+            self.buf.push('\n');
+        }
+    }
+
     fn append_prost_path_attribute(&mut self) {
         if let Some(prost_path_attribute) = self.context.prost_path_attribute() {
             self.buf.push_str(prost_path_attribute);
@@ -396,85 +791,342 @@ impl<'b> CodeGenerator<'_, 'b> {
         }
     }
 
-    fn append_enum_attributes(&mut self, fq_message_name: &str) {
-        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
-        for attribute in self.context.enum_attributes(fq_message_name) {
+    fn append_json(&mut self) {
+        if self.config().enable_json {
             push_indent(self.buf, self.depth);
-            self.buf.push_str(attribute);
+            self.buf.push_str("#[prost(json)]");
             self.buf.push('\n');
         }
     }
 
-    fn append_field_attributes(&mut self, fq_message_name: &str, field_name: &str) {
-        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
-        for attribute in self.context.field_attributes(fq_message_name, field_name) {
+    /// Appends the attribute which derives `Arbitrary` for a message, oneof or enum, unless it
+    /// is part of a heapless message.
+    fn append_arbitrary(&mut self, fq_name: &str) {
+        if self.config().generate_arbitrary && !self.context.should_use_heapless(fq_name) {
             push_indent(self.buf, self.depth);
-            self.buf.push_str(attribute);
+            self.buf.push_str("#[prost(arbitrary)]");
             self.buf.push('\n');
         }
     }
 
-    fn append_field(&mut self, fq_message_name: &str, field: &Field) {
-        let type_ = field.descriptor.r#type();
-        let repeated = field.descriptor.label() == Label::Repeated;
-        let deprecated = self.deprecated(&field.descriptor);
-        let optional = self.optional(&field.descriptor);
-        let boxed = self
-            .context
-            .should_box_message_field(fq_message_name, &field.descriptor);
-        let ty = self.resolve_type(&field.descriptor, fq_message_name);
-
-        debug!(
-            "    field: {:?}, type: {:?}, boxed: {}",
-            field.descriptor.name(),
-            ty,
-            boxed
-        );
+    fn append_text_format(&mut self) {
+        if self.config().enable_text_format || self.config().text_format_debug {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str("#[prost(text_format)]");
+            self.buf.push('\n');
+        }
+    }
 
-        self.append_doc(fq_message_name, Some(field.descriptor.name()));
+    fn append_text_format_debug(&mut self) {
+        if self.config().text_format_debug {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str("#[prost(text_format_debug)]");
+            self.buf.push('\n');
+        }
+    }
 
-        if deprecated {
-            self.push_indent();
-            self.buf.push_str("#[deprecated]\n");
+    fn append_field_mask(&mut self) {
+        if self.config().enable_field_masks {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str("#[prost(field_mask)]");
+            self.buf.push('\n');
         }
+    }
 
-        self.push_indent();
-        self.buf.push_str("#[prost(");
-        let type_tag = self.field_type_tag(&field.descriptor);
-        self.buf.push_str(&type_tag);
+    fn append_const_default(&mut self, fq_message_name: &str) {
+        if self.context.should_generate_const_default(fq_message_name) {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str("#[prost(const_default)]");
+            self.buf.push('\n');
+        }
+    }
 
-        if type_ == Type::Bytes {
-            let bytes_type = self
-                .context
-                .bytes_type(fq_message_name, field.descriptor.name());
-            self.buf
-                .push_str(&format!(" = {:?}", bytes_type.annotation()));
+    fn append_proto_cmp(&mut self, fq_message_name: &str) {
+        if self.context.should_generate_proto_cmp(fq_message_name) {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str("#[prost(proto_cmp)]");
+            self.buf.push('\n');
         }
+    }
 
-        match field.descriptor.label() {
-            Label::Optional => {
-                if optional {
-                    self.buf.push_str(", optional");
-                }
-            }
-            Label::Required => self.buf.push_str(", required"),
-            Label::Repeated => {
-                self.buf.push_str(", repeated");
-                if can_pack(&field.descriptor)
-                    && !field
-                        .descriptor
-                        .options
-                        .as_ref()
-                        .map_or(self.syntax == Syntax::Proto3, |options| options.packed())
-                {
-                    self.buf.push_str(", packed = \"false\"");
-                }
-            }
+    fn append_accessors(&mut self, fq_message_name: &str) {
+        if self.context.should_be_non_exhaustive(fq_message_name) {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str("#[prost(accessors)]");
+            self.buf.push('\n');
         }
+    }
 
-        if boxed {
+    fn append_non_exhaustive(&mut self, fq_name: &str) {
+        if self.context.should_be_non_exhaustive(fq_name) {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str("#[non_exhaustive]");
+            self.buf.push('\n');
+        }
+    }
+
+    /// Appends the serde derives of a message, oneof or enum. Missing fields of messages are
+    /// deserialized as their default values.
+    fn append_serde(&mut self, default: bool) {
+        if self.config().enable_serde {
+            let prost_path = self.context.prost_path();
+            push_indent(self.buf, self.depth);
+            self.buf.push_str(&format!(
+                "#[derive({prost_path}::serde::Serialize, {prost_path}::serde::Deserialize)]\n"
+            ));
+            push_indent(self.buf, self.depth);
+            self.buf.push_str(&format!(
+                "#[serde(crate = \"{prost_path}::serde\"{})]\n",
+                if default { ", default" } else { "" }
+            ));
+        }
+    }
+
+    /// Returns whether the serde traits of the named message are derived. Messages with presence
+    /// bits implement them with the JSON mapping instead, see `append_serde_impls`.
+    fn derives_serde(&self, fq_message_name: &str) -> bool {
+        self.config().enable_serde && !self.context.has_presence_bits(fq_message_name)
+    }
+
+    /// Appends the serde attribute of a field, which renames it to its JSON name and converts its
+    /// values with an adapter of `prost::json::serde` where their serde representation differs
+    /// from the Protobuf JSON mapping.
+    fn append_serde_field(
+        &mut self,
+        fq_message_name: &str,
+        field: &FieldDescriptorProto,
+        shape: SerdeShape<'_>,
+    ) {
+        // The variants of oneofs are serialized by the serde implementations of their own enums.
+        if !self.config().enable_serde
+            || (!matches!(shape, SerdeShape::Variant) && !self.derives_serde(fq_message_name))
+        {
+            return;
+        }
+        let serde = format!("{}::json::serde", self.context.prost_path());
+        let json_name = field
+            .json_name
+            .clone()
+            .unwrap_or_else(|| to_json_name(field.name()));
+        let mut attributes = vec![format!("rename = \"{json_name}\"")];
+        if json_name != field.name() {
+            attributes.push(format!("alias = \"{}\"", field.name()));
+        }
+
+        let adapter = match shape {
+            // Substituted fields and open enums are serialized by their own serde implementations.
+            SerdeShape::Singular
+            | SerdeShape::Optional
+            | SerdeShape::Required
+            | SerdeShape::Repeated
+                if self
+                    .context
+                    .message_type(fq_message_name, field.name())
+                    .is_some()
+                    || self.context.should_use_open_enum(fq_message_name, field) =>
+            {
+                None
+            }
+            // Map values of type `string` are always `String`s.
+            SerdeShape::Map(value) if value.r#type() == Type::String => None,
+            SerdeShape::Map(value) => self.serde_adapter(fq_message_name, value),
+            _ => self.serde_adapter(fq_message_name, field),
+        };
+        let adapter = match (shape, adapter) {
+            (_, None) => None,
+            (SerdeShape::Singular | SerdeShape::Required | SerdeShape::Variant, adapter) => adapter,
+            (SerdeShape::Optional, Some(adapter)) => {
+                Some(format!("{serde}::Optional::<{adapter}>"))
+            }
+            (SerdeShape::Repeated, Some(adapter)) => {
+                Some(format!("{serde}::Repeated::<{adapter}>"))
+            }
+            (SerdeShape::Map(_), Some(adapter)) => Some(format!("{serde}::MapValues::<{adapter}>")),
+        };
+        if let Some(adapter) = adapter {
+            attributes.push(format!("with = \"{adapter}\""));
+        }
+
+        // Like the JSON mapping, fields without presence are omitted if they have their default
+        // value, and fields with presence are omitted if they are not set.
+        match shape {
+            SerdeShape::Singular | SerdeShape::Repeated | SerdeShape::Map(_) => {
+                attributes.push(format!("skip_serializing_if = \"{serde}::is_default\""))
+            }
+            SerdeShape::Optional => attributes
+                .push("skip_serializing_if = \"::core::option::Option::is_none\"".to_string()),
+            SerdeShape::Required | SerdeShape::Variant => {}
+        }
+
+        self.push_indent();
+        self.buf
+            .push_str(&format!("#[serde({})]\n", attributes.join(", ")));
+    }
+
+    /// Returns the adapter of `prost::json::serde` which converts the values of a field, if their
+    /// serde representation differs from the Protobuf JSON mapping.
+    fn serde_adapter(&self, fq_message_name: &str, field: &FieldDescriptorProto) -> Option<String> {
+        let serde = format!("{}::json::serde", self.context.prost_path());
+        match field.r#type() {
+            Type::Int64
+            | Type::Sint64
+            | Type::Sfixed64
+            | Type::Uint64
+            | Type::Fixed64
+            | Type::Float
+            | Type::Double
+            | Type::Bytes => Some(format!("{serde}::Scalar")),
+            Type::String
+                if self.context.string_type(fq_message_name, field.name())
+                    == StringType::Utf8Bytes =>
+            {
+                Some(format!("{serde}::Scalar"))
+            }
+            Type::Enum => Some(format!(
+                "{serde}::Enumeration::<{}>",
+                self.resolve_ident(field.type_name())
+            )),
+            _ => None,
+        }
+    }
+
+    fn append_enum_attributes(&mut self, fq_message_name: &str) {
+        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
+        for attribute in self.context.enum_attributes(fq_message_name) {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str(attribute);
+            self.buf.push('\n');
+        }
+    }
+
+    fn append_oneof_attributes(&mut self, fq_oneof_name: &str) {
+        assert_eq!(b'.', fq_oneof_name.as_bytes()[0]);
+        for attribute in self.context.oneof_attributes(fq_oneof_name) {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str(attribute);
+            self.buf.push('\n');
+        }
+    }
+
+    fn append_variant_attributes(&mut self, fq_type_name: &str, variant_name: &str) {
+        assert_eq!(b'.', fq_type_name.as_bytes()[0]);
+        for attribute in self.context.variant_attributes(fq_type_name, variant_name) {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str(attribute);
+            self.buf.push('\n');
+        }
+    }
+
+    fn append_field_attributes(&mut self, fq_message_name: &str, field_name: &str) {
+        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
+        for attribute in self.context.field_attributes(fq_message_name, field_name) {
+            push_indent(self.buf, self.depth);
+            self.buf.push_str(attribute);
+            self.buf.push('\n');
+        }
+    }
+
+    fn append_field(&mut self, fq_message_name: &str, field: &Field, presence_bit: Option<u32>) {
+        let type_ = field.descriptor.r#type();
+        let repeated = field.descriptor.label() == Label::Repeated;
+        let deprecated = self.deprecated(&field.descriptor);
+        let optional = self.optional(fq_message_name, &field.descriptor);
+        let wrapper = self
+            .context
+            .message_field_wrapper(fq_message_name, &field.descriptor);
+        let boxed = wrapper.is_some();
+        let lazy = self
+            .context
+            .should_lazy_message_field(fq_message_name, &field.descriptor);
+        let heapless = self.context.should_use_heapless(fq_message_name);
+        let ty = self.resolve_field_type(&field.descriptor, fq_message_name);
+        let convert = !is_message(&field.descriptor)
+            && self
+                .context
+                .message_type(fq_message_name, field.descriptor.name())
+                .is_some()
+            || self
+                .context
+                .should_use_open_enum(fq_message_name, &field.descriptor);
+
+        debug!(
+            "    field: {:?}, type: {:?}, boxed: {}, lazy: {}",
+            field.descriptor.name(),
+            ty,
+            boxed,
+            lazy
+        );
+
+        self.append_doc(fq_message_name, Some(field.descriptor.name()));
+
+        if deprecated {
+            self.push_indent();
+            self.buf.push_str("#[deprecated]\n");
+        }
+
+        self.push_indent();
+        self.buf.push_str("#[prost(");
+        let type_tag = self.field_type_tag(&field.descriptor);
+        self.buf.push_str(&type_tag);
+
+        if heapless && matches!(type_, Type::String | Type::Bytes) {
+            self.buf.push_str(&format!(
+                " = \"heapless\", max_len = \"{}\"",
+                heapless::max_len(fq_message_name, &field.descriptor)
+            ));
+        } else if type_ == Type::Bytes {
+            let bytes_type = self
+                .context
+                .bytes_type(fq_message_name, field.descriptor.name());
+            self.buf
+                .push_str(&format!(" = {:?}", bytes_type.annotation()));
+        }
+        if type_ == Type::String {
+            let string_type = self
+                .context
+                .string_type(fq_message_name, field.descriptor.name());
+            if string_type != StringType::String {
+                self.buf
+                    .push_str(&format!(" = {:?}", string_type.annotation()));
+            }
+        }
+
+        match field.descriptor.label() {
+            Label::Optional => {
+                if optional {
+                    self.buf.push_str(", optional");
+                }
+                if let Some(bit) = presence_bit {
+                    self.buf.push_str(&format!(", presence_bit = \"{bit}\""));
+                }
+            }
+            Label::Required => {
+                self.buf.push_str(", required");
+                if optional {
+                    self.buf.push_str(", presence");
+                }
+            }
+            Label::Repeated => {
+                self.buf.push_str(", repeated");
+                if can_pack(&field.descriptor) && !self.packed(&field.descriptor) {
+                    self.buf.push_str(", packed = \"false\"");
+                }
+            }
+        }
+
+        if wrapper == Some(Wrapper::Box) {
             self.buf.push_str(", boxed");
         }
+        if lazy {
+            self.buf.push_str(", lazy");
+        }
+        if convert {
+            self.buf.push_str(", convert");
+        }
+        if self.debug_redact(&field.descriptor) {
+            self.buf.push_str(", redact");
+        }
+        self.buf.push_str(&json_name_attribute(&field.descriptor));
         self.buf.push_str(", tag = \"");
         self.buf.push_str(&field.descriptor.number().to_string());
 
@@ -489,20 +1141,7 @@ impl<'b> CodeGenerator<'_, 'b> {
                 }
                 self.buf.push_str("\\\"");
             } else if type_ == Type::Enum {
-                let mut enum_value = to_upper_camel(default);
-                if self.config().strip_enum_prefix {
-                    // Field types are fully qualified, so we extract
-                    // the last segment and strip it from the left
-                    // side of the default value.
-                    let enum_type = field
-                        .descriptor
-                        .type_name
-                        .as_ref()
-                        .and_then(|ty| ty.split('.').next_back())
-                        .unwrap();
-
-                    enum_value = strip_enum_prefix(&to_upper_camel(enum_type), &enum_value)
-                }
+                let enum_value = self.enum_default_variant(&field.descriptor, default);
                 self.buf.push_str(&enum_value);
             } else {
                 self.buf.push_str(&default.escape_default().to_string());
@@ -510,29 +1149,58 @@ impl<'b> CodeGenerator<'_, 'b> {
         }
 
         self.buf.push_str("\")]\n");
+        let shape = if repeated {
+            SerdeShape::Repeated
+        } else if optional {
+            SerdeShape::Optional
+        } else if field.descriptor.label() == Label::Required {
+            SerdeShape::Required
+        } else {
+            SerdeShape::Singular
+        };
+        self.append_serde_field(fq_message_name, &field.descriptor, shape);
         self.append_field_attributes(fq_message_name, field.descriptor.name());
+        let attributes = self
+            .context
+            .provided_field_attributes(fq_message_name, &field.descriptor);
+        self.append_provided_attributes(attributes);
         self.push_indent();
         self.buf.push_str("pub ");
         self.buf.push_str(&field.rust_name());
         self.buf.push_str(": ");
 
         let prost_path = self.context.prost_path();
+        // Fields with a presence bit hold their value directly.
+        let optional = optional && presence_bit.is_none();
 
-        if repeated {
+        if repeated && heapless {
+            self.buf.push_str(&format!("{prost_path}::heapless::Vec<"));
+        } else if repeated {
             self.buf
                 .push_str(&format!("{prost_path}::alloc::vec::Vec<"));
         } else if optional {
             self.buf.push_str("::core::option::Option<");
         }
-        if boxed {
-            self.buf
-                .push_str(&format!("{prost_path}::alloc::boxed::Box<"));
+        if lazy {
+            self.buf.push_str(&format!("{prost_path}::Lazy<"));
+        }
+        if let Some(wrapper) = wrapper {
+            self.buf.push_str(&wrapper.rust_type(prost_path));
+            self.buf.push('<');
         }
         self.buf.push_str(&ty);
         if boxed {
             self.buf.push('>');
         }
-        if repeated || optional {
+        if lazy {
+            self.buf.push('>');
+        }
+        if repeated && heapless {
+            self.buf.push_str(&format!(
+                ", {}>",
+                heapless::max_count(fq_message_name, &field.descriptor)
+            ));
+        } else if repeated || optional {
             self.buf.push('>');
         }
         self.buf.push_str(",\n");
@@ -545,8 +1213,14 @@ impl<'b> CodeGenerator<'_, 'b> {
         key: &FieldDescriptorProto,
         value: &FieldDescriptorProto,
     ) {
-        let key_ty = self.resolve_type(key, fq_message_name);
-        let value_ty = self.resolve_type(value, fq_message_name);
+        if self.context.should_use_heapless(fq_message_name) {
+            panic!(
+                "map field {} in heapless message {fq_message_name} is not supported",
+                field.descriptor.name()
+            );
+        }
+        let key_ty = self.resolve_map_entry_type(key, fq_message_name);
+        let value_ty = self.resolve_map_entry_type(value, fq_message_name);
 
         debug!(
             "    map field: {:?}, key type: {:?}, value type: {:?}",
@@ -565,13 +1239,24 @@ impl<'b> CodeGenerator<'_, 'b> {
         let value_tag = self.map_value_type_tag(value);
 
         self.buf.push_str(&format!(
-            "#[prost({} = \"{}, {}\", tag = \"{}\")]\n",
+            "#[prost({} = \"{}, {}\"{}{}, tag = \"{}\")]\n",
             map_type.annotation(),
             key_tag,
             value_tag,
+            if self.debug_redact(&field.descriptor) {
+                ", redact"
+            } else {
+                ""
+            },
+            json_name_attribute(&field.descriptor),
             field.descriptor.number()
         ));
+        self.append_serde_field(fq_message_name, &field.descriptor, SerdeShape::Map(value));
         self.append_field_attributes(fq_message_name, field.descriptor.name());
+        let attributes = self
+            .context
+            .provided_field_attributes(fq_message_name, &field.descriptor);
+        self.append_provided_attributes(attributes);
         self.push_indent();
         match map_type {
             crate::MapType::HashMap => {
@@ -591,6 +1276,15 @@ impl<'b> CodeGenerator<'_, 'b> {
                     value_ty
                 ));
             }
+            crate::MapType::Custom(ref path) => {
+                self.buf.push_str(&format!(
+                    "pub {}: {}<{}, {}>,\n",
+                    field.rust_name(),
+                    path,
+                    key_ty,
+                    value_ty
+                ));
+            }
         }
     }
 
@@ -612,6 +1306,11 @@ impl<'b> CodeGenerator<'_, 'b> {
                 .map(|field| field.descriptor.number())
                 .join(", "),
         ));
+        if self.derives_serde(fq_message_name) {
+            // The members of a oneof are members of the JSON object of the message.
+            self.push_indent();
+            self.buf.push_str("#[serde(flatten)]\n");
+        }
         self.append_field_attributes(fq_message_name, oneof.descriptor.name());
         self.push_indent();
         self.buf.push_str(&format!(
@@ -621,98 +1320,1886 @@ impl<'b> CodeGenerator<'_, 'b> {
         ));
     }
 
-    fn append_oneof(&mut self, fq_message_name: &str, oneof: &OneofField) {
-        self.path.push(8);
-        self.path.push(oneof.path_index);
-        self.append_doc(fq_message_name, None);
-        self.path.pop();
-        self.path.pop();
+    /// Returns the indices of the presence bits of the fields whose presence is tracked in the
+    /// presence bits of the message, by field number.
+    fn presence_bits(&self, fq_message_name: &str, fields: &[Field]) -> HashMap<i32, u32> {
+        fields
+            .iter()
+            .filter(|field| {
+                self.context
+                    .should_use_presence_bit(fq_message_name, &field.descriptor)
+            })
+            .zip(0..)
+            .map(|(field, bit)| (field.descriptor.number(), bit))
+            .collect()
+    }
+
+    fn append_presence_bits_field(&mut self, bits: usize) {
+        self.push_indent();
+        self.buf.push_str("#[prost(presence_bits)]\n");
+        self.push_indent();
+        self.buf.push_str("#[doc(hidden)]\n");
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "pub _presence_bits: [u32; {}],\n",
+            bits.div_ceil(32)
+        ));
+    }
+
+    fn append_unknown_fields_field(&mut self, fq_message_name: &str) {
+        self.push_indent();
+        self.buf.push_str("#[prost(unknown_fields)]\n");
+        self.append_serde_skip(fq_message_name);
+        self.push_indent();
+        self.buf.push_str("#[doc(hidden)]\n");
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "pub _unknown_fields: {}::UnknownFieldSet,\n",
+            self.context.prost_path()
+        ));
+    }
+
+    /// Excludes a field which is not part of the JSON mapping from serialization.
+    fn append_serde_skip(&mut self, fq_message_name: &str) {
+        if self.derives_serde(fq_message_name) {
+            self.push_indent();
+            self.buf.push_str("#[serde(skip)]\n");
+        }
+    }
+
+    fn append_extension_set_field(&mut self, fq_message_name: &str, message: &DescriptorProto) {
+        let ranges = message
+            .extension_range
+            .iter()
+            .map(|range| format!("{}..{}", range.start(), range.end()))
+            .join(", ");
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "#[prost(extension_set = \"{}\", extension_ranges = \"{}\")]\n",
+            &fq_message_name[1..],
+            ranges
+        ));
+        self.append_serde_skip(fq_message_name);
+        self.push_indent();
+        self.buf.push_str("#[doc(hidden)]\n");
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "pub _extensions: {}::ExtensionSet,\n",
+            self.context.prost_path()
+        ));
+    }
+
+    fn append_extension(&mut self, extension: FieldDescriptorProto) {
+        debug!("  extension: {:?}", extension.name());
+
+        let fq_extension_name = self.fq_name(extension.name());
+        let repeated = extension.label() == Label::Repeated;
+        let ty = self.resolve_type(&extension, &fq_extension_name);
+
+        self.append_doc(&fq_extension_name, None);
+        let prost_path = self.context.prost_path();
+
+        let codec = match extension.r#type() {
+            Type::Enum => Cow::Borrowed("int32"),
+            _ => self.field_type_tag(&extension),
+        };
+        let codec = format!("{prost_path}::encoding::{codec}");
+        // Only groups need the tag to find their end.
+        let (tag_param, tag_arg) = if extension.r#type() == Type::Group {
+            ("tag", "tag, ")
+        } else {
+            ("_", "")
+        };
+        let (ty, encode, merge) = if repeated {
+            let packed = can_pack(&extension)
+                && extension
+                    .options
+                    .as_ref()
+                    .and_then(|options| options.packed)
+                    .unwrap_or(self.syntax == Syntax::Proto3);
+            (
+                format!("{prost_path}::alloc::vec::Vec<{ty}>"),
+                if packed {
+                    "encode_packed"
+                } else {
+                    "encode_repeated"
+                },
+                "merge_repeated",
+            )
+        } else {
+            (ty, "encode", "merge")
+        };
+
+        push_indent(self.buf, self.depth);
+        self.buf.push_str(&format!(
+            "pub const {}: {prost_path}::Extension<{ty}> = {prost_path}::Extension::new(\n",
+            to_snake(extension.name()).to_uppercase()
+        ));
+        self.depth += 1;
+        push_indent(self.buf, self.depth);
+        self.buf.push_str(&format!(
+            "{:?},\n",
+            extension.extendee().trim_start_matches('.')
+        ));
+        push_indent(self.buf, self.depth);
+        self.buf.push_str(&format!(
+            "{:?},\n",
+            fq_extension_name.trim_start_matches('.')
+        ));
+        push_indent(self.buf, self.depth);
+        self.buf.push_str(&format!("{},\n", extension.number()));
+        push_indent(self.buf, self.depth);
+        if repeated {
+            self.buf.push_str(&format!(
+                "|tag, values, buf| {codec}::{encode}(tag, values, buf),\n"
+            ));
+        } else {
+            self.buf.push_str(&format!("{codec}::{encode},\n"));
+        }
+        push_indent(self.buf, self.depth);
+        self.buf.push_str(&format!(
+            "|{tag_param}, wire_type, value, buf, ctx| {codec}::{merge}({tag_arg}wire_type, value, buf, ctx),\n"
+        ));
+        self.depth -= 1;
+        push_indent(self.buf, self.depth);
+        self.buf.push_str(");\n");
+    }
+
+    fn append_oneof(&mut self, fq_message_name: &str, oneof: &OneofField) {
+        self.path.push(8);
+        self.path.push(oneof.path_index);
+        self.append_doc(fq_message_name, None);
+        self.path.pop();
+        self.path.pop();
+
+        let oneof_name = format!("{}.{}", fq_message_name, oneof.descriptor.name());
+        self.append_type_attributes(&oneof_name);
+        self.append_oneof_attributes(&oneof_name);
+        self.push_indent();
+
+        let can_oneof_derive_copy = oneof.fields.iter().all(|field| {
+            self.context
+                .can_field_derive_copy(fq_message_name, &field.descriptor)
+        });
+        let comparisons = self.context.oneof_comparisons(
+            fq_message_name,
+            oneof.fields.iter().map(|field| &field.descriptor),
+        );
+        let total_order =
+            comparisons.is_some() && oneof.fields.iter().any(|field| is_float(&field.descriptor));
+        self.buf.push_str(&format!(
+            "#[derive(Clone, {}{}{}::Oneof)]\n",
+            if can_oneof_derive_copy { "Copy, " } else { "" },
+            comparison_derives(comparisons, total_order),
+            self.context.prost_path()
+        ));
+        self.append_prost_path_attribute();
+        self.append_skip_debug(fq_message_name);
+        self.append_json();
+        self.append_arbitrary(fq_message_name);
+        self.append_text_format();
+        self.append_field_mask();
+        self.append_proto_cmp(fq_message_name);
+        self.append_serde(false);
+        self.append_non_exhaustive(&oneof_name);
+        self.push_indent();
+        self.buf.push_str("pub enum ");
+        self.buf.push_str(&oneof.type_name());
+        self.buf.push_str(" {\n");
+
+        self.path.push(2);
+        self.depth += 1;
+        for field in &oneof.fields {
+            self.path.push(field.path_index);
+            self.append_doc(fq_message_name, Some(field.descriptor.name()));
+            self.path.pop();
+
+            if self.deprecated(&field.descriptor) {
+                self.push_indent();
+                self.buf.push_str("#[deprecated]\n");
+            }
+
+            self.push_indent();
+            let mut ty_tag = self.field_type_tag(&field.descriptor);
+            if self.context.should_use_heapless(fq_message_name)
+                && matches!(field.descriptor.r#type(), Type::String | Type::Bytes)
+            {
+                ty_tag = Cow::Owned(format!(
+                    "{ty_tag} = \"heapless\", max_len = \"{}\"",
+                    heapless::max_len(fq_message_name, &field.descriptor)
+                ));
+            }
+            if self.debug_redact(&field.descriptor) {
+                ty_tag = Cow::Owned(format!("{ty_tag}, redact"));
+            }
+            ty_tag = Cow::Owned(format!(
+                "{ty_tag}{}",
+                json_name_attribute(&field.descriptor)
+            ));
+            self.buf.push_str(&format!(
+                "#[prost({}, tag = \"{}\")]\n",
+                ty_tag,
+                field.descriptor.number()
+            ));
+            self.append_serde_field(fq_message_name, &field.descriptor, SerdeShape::Variant);
+            self.append_field_attributes(&oneof_name, field.descriptor.name());
+            self.append_variant_attributes(&oneof_name, field.descriptor.name());
+            let attributes = self
+                .context
+                .provided_field_attributes(fq_message_name, &field.descriptor);
+            self.append_provided_attributes(attributes);
+
+            self.push_indent();
+            let ty = self.resolve_type(&field.descriptor, fq_message_name);
+
+            let wrapper = self.context.oneof_field_wrapper(
+                fq_message_name,
+                oneof.descriptor.name(),
+                &field.descriptor,
+            );
+
+            debug!(
+                "    oneof: {:?}, type: {:?}, wrapper: {:?}",
+                field.descriptor.name(),
+                ty,
+                wrapper
+            );
+
+            if let Some(wrapper) = wrapper {
+                self.buf.push_str(&format!(
+                    "{}({}<{}>),\n",
+                    to_upper_camel(field.descriptor.name()),
+                    wrapper.rust_type(self.context.prost_path()),
+                    ty
+                ));
+            } else {
+                self.buf.push_str(&format!(
+                    "{}({}),\n",
+                    to_upper_camel(field.descriptor.name()),
+                    ty
+                ));
+            }
+        }
+        self.depth -= 1;
+        self.path.pop();
+
+        self.push_indent();
+        self.buf.push_str("}\n");
+
+        if let (Some(comparisons), true) = (comparisons, total_order) {
+            let variants = oneof
+                .fields
+                .iter()
+                .map(|field| {
+                    (
+                        to_upper_camel(field.descriptor.name()),
+                        is_float(&field.descriptor),
+                    )
+                })
+                .collect::<Vec<_>>();
+            self.append_total_order_enum_impls(&oneof.type_name(), &variants, comparisons);
+        }
+
+        self.append_large_oneof_accessors(fq_message_name, oneof);
+    }
+
+    /// Appends the comparison impls of a message with floats, which compare its floats by their
+    /// total order. `members` are the names of the fields of the struct, in declaration order,
+    /// and whether they hold floats.
+    fn append_total_order_struct_impls(
+        &mut self,
+        type_name: &str,
+        members: &[(String, bool)],
+        comparisons: Comparisons,
+    ) {
+        let total_ord = format!("{}::total_order::TotalOrd", self.context.prost_path());
+        let eq = members
+            .iter()
+            .map(|(name, float)| {
+                if *float {
+                    format!("{total_ord}::total_eq(&self.{name}, &other.{name})")
+                } else {
+                    format!("self.{name} == other.{name}")
+                }
+            })
+            .join(" && ");
+        let hash = members
+            .iter()
+            .map(|(name, float)| {
+                if *float {
+                    format!("{total_ord}::total_hash(&self.{name}, state);\n")
+                } else {
+                    format!("::core::hash::Hash::hash(&self.{name}, state);\n")
+                }
+            })
+            .join("");
+        let cmp = members
+            .iter()
+            .map(|(name, float)| {
+                if *float {
+                    format!("{total_ord}::total_cmp(&self.{name}, &other.{name})")
+                } else {
+                    format!("::core::cmp::Ord::cmp(&self.{name}, &other.{name})")
+                }
+            })
+            .reduce(|cmp, next| format!("{cmp}.then_with(|| {next})"))
+            .expect("a message with floats has fields");
+        self.append_total_order_impls(type_name, &eq, &hash, &cmp, comparisons);
+    }
+
+    /// Appends the comparison impls of a oneof with floats, which compare its floats by their
+    /// total order. `variants` are the names of the variants of the enum, and whether they hold
+    /// floats.
+    fn append_total_order_enum_impls(
+        &mut self,
+        type_name: &str,
+        variants: &[(String, bool)],
+        comparisons: Comparisons,
+    ) {
+        let total_ord = format!("{}::total_order::TotalOrd", self.context.prost_path());
+        let arms = |f: &dyn Fn(bool) -> String| {
+            variants
+                .iter()
+                .map(|(name, float)| {
+                    format!("(Self::{name}(a), Self::{name}(b)) => {},\n", f(*float))
+                })
+                .join("")
+        };
+        // Values of different variants are ordered like the variants.
+        let other_variants = |value: &str| {
+            if variants.len() > 1 {
+                format!("_ => {value},\n")
+            } else {
+                String::new()
+            }
+        };
+        let eq = format!(
+            "match (self, other) {{\n{}{}}}",
+            arms(&|float| if float {
+                format!("{total_ord}::total_eq(a, b)")
+            } else {
+                "a == b".to_string()
+            }),
+            other_variants("false"),
+        );
+        let hash = format!(
+            "::core::hash::Hash::hash(&::core::mem::discriminant(self), state);\n\
+             match self {{\n{}}}\n",
+            variants
+                .iter()
+                .map(|(name, float)| if *float {
+                    format!("Self::{name}(value) => {total_ord}::total_hash(value, state),\n")
+                } else {
+                    format!("Self::{name}(value) => ::core::hash::Hash::hash(value, state),\n")
+                })
+                .join(""),
+        );
+        let index = variants
+            .iter()
+            .enumerate()
+            .map(|(index, (name, _))| format!("Self::{name}(_) => {index}usize,\n"))
+            .join("");
+        let cmp = format!(
+            "match (self, other) {{\n{}{}}}",
+            arms(&|float| if float {
+                format!("{total_ord}::total_cmp(a, b)")
+            } else {
+                "::core::cmp::Ord::cmp(a, b)".to_string()
+            }),
+            other_variants(&format!(
+                "{{\n\
+                 let index = |value: &Self| match value {{\n{index}}};\n\
+                 ::core::cmp::Ord::cmp(&index(self), &index(other))\n\
+                 }}"
+            )),
+        );
+        self.append_total_order_impls(type_name, &eq, &hash, &cmp, comparisons);
+    }
+
+    /// Appends the `PartialEq`, `Eq` and `Hash` impls of a type, and its `PartialOrd` and `Ord`
+    /// impls if requested, given the bodies of `eq`, `hash` and `cmp`.
+    fn append_total_order_impls(
+        &mut self,
+        type_name: &str,
+        eq: &str,
+        hash: &str,
+        cmp: &str,
+        comparisons: Comparisons,
+    ) {
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "#[allow(deprecated)]\n\
+             impl ::core::cmp::PartialEq for {type_name} {{\n\
+             fn eq(&self, other: &Self) -> bool {{\n\
+             {eq}\n\
+             }}\n\
+             }}\n"
+        ));
+        self.push_indent();
+        self.buf
+            .push_str(&format!("impl ::core::cmp::Eq for {type_name} {{}}\n"));
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "#[allow(deprecated)]\n\
+             impl ::core::hash::Hash for {type_name} {{\n\
+             fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {{\n\
+             {hash}\
+             }}\n\
+             }}\n"
+        ));
+        if comparisons == Comparisons::Ord {
+            self.push_indent();
+            self.buf.push_str(&format!(
+                "impl ::core::cmp::PartialOrd for {type_name} {{\n\
+                 fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {{\n\
+                 ::core::option::Option::Some(::core::cmp::Ord::cmp(self, other))\n\
+                 }}\n\
+                 }}\n"
+            ));
+            self.push_indent();
+            self.buf.push_str(&format!(
+                "#[allow(deprecated)]\n\
+                 impl ::core::cmp::Ord for {type_name} {{\n\
+                 fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {{\n\
+                 {cmp}\n\
+                 }}\n\
+                 }}\n"
+            ));
+        }
+    }
+
+    /// Appends methods returning references to the messages of the variants of a oneof which
+    /// are boxed because of their size, and conversions from those messages, so that their
+    /// `Box` rarely has to be named.
+    fn append_large_oneof_accessors(&mut self, fq_message_name: &str, oneof: &OneofField) {
+        let large = oneof
+            .fields
+            .iter()
+            .filter(|field| {
+                self.context
+                    .is_large_oneof_field(fq_message_name, &field.descriptor)
+                    && self.context.oneof_field_wrapper(
+                        fq_message_name,
+                        oneof.descriptor.name(),
+                        &field.descriptor,
+                    ) == Some(Wrapper::Box)
+            })
+            .collect::<Vec<_>>();
+        if large.is_empty() {
+            return;
+        }
+
+        let prost_path = self.context.prost_path().to_string();
+        let oneof_type = oneof.type_name();
+        let types = oneof
+            .fields
+            .iter()
+            .map(|field| self.resolve_type(&field.descriptor, fq_message_name))
+            .collect::<Vec<_>>();
+        let otherwise = if oneof.fields.len() > 1 {
+            "_ => ::core::option::Option::None,"
+        } else {
+            ""
+        };
+
+        let mut methods = Vec::new();
+        let mut conversions = Vec::new();
+        for field in large {
+            let descriptor = &field.descriptor;
+            let variant = to_upper_camel(descriptor.name());
+            let field_name = to_snake(descriptor.name());
+            let field_name = field_name.trim_start_matches("r#");
+            let deprecated = if self.deprecated(descriptor) {
+                "#[deprecated]\n"
+            } else {
+                ""
+            };
+            let ty = self.resolve_type(descriptor, fq_message_name);
+            methods.push(format!(
+                "/// Returns the message of [`Self::{variant}`], if it is the variant.
+                {deprecated}pub fn as_{field_name}(&self) -> ::core::option::Option<&{ty}> {{
+                    match self {{
+                        Self::{variant}(value) => ::core::option::Option::Some(&**value),
+                        {otherwise}
+                    }}
+                }}
+                /// Returns the mutable message of [`Self::{variant}`], if it is the variant.
+                {deprecated}pub fn as_{field_name}_mut(&mut self) -> ::core::option::Option<&mut {ty}> {{
+                    match self {{
+                        Self::{variant}(value) => ::core::option::Option::Some(&mut **value),
+                        {otherwise}
+                    }}
+                }}"
+            ));
+            // The variant can only be chosen by the type of the message if it is unique.
+            if types.iter().filter(|other| **other == ty).count() == 1 {
+                conversions.push(format!(
+                    "#[allow(deprecated)]
+                    impl ::core::convert::From<{ty}> for {oneof_type} {{
+                        fn from(value: {ty}) -> Self {{
+                            Self::{variant}({prost_path}::alloc::boxed::Box::new(value))
+                        }}
+                    }}"
+                ));
+            }
+        }
+
+        self.buf.push_str(&format!(
+            "#[allow(deprecated)]
+            impl {oneof_type} {{
+                {}
+            }}
+            {}
+            ",
+            methods.join("\n"),
+            conversions.join("\n"),
+        ));
+    }
+
+    fn append_message_ref(
+        &mut self,
+        message_name: &str,
+        fq_message_name: &str,
+        fields: &[Field],
+        map_types: &HashMap<String, (FieldDescriptorProto, FieldDescriptorProto)>,
+        oneof_fields: &[OneofField],
+    ) {
+        let prost_path = self.context.prost_path().to_string();
+        let owned_name = to_upper_camel(message_name);
+        let ref_name = format!("{owned_name}Ref");
+        let message_mod = to_snake(message_name);
+
+        // The struct declaration, the `merge_field` match arms, and the owned field initializers.
+        let mut declarations = Vec::new();
+        let mut merge_arms = Vec::new();
+        let mut conversions = Vec::new();
+        let mut borrows = false;
+        let mut deprecated = false;
+
+        for field in fields {
+            let descriptor = &field.descriptor;
+            let name = field.rust_name();
+            let number = descriptor.number();
+            deprecated |= self.deprecated(descriptor);
+
+            if let Some((key, value)) = descriptor
+                .type_name
+                .as_ref()
+                .and_then(|type_name| map_types.get(type_name))
+            {
+                borrows |= ref_borrows(key) || ref_borrows(value);
+                declarations.push(format!(
+                    "pub {name}: {prost_path}::alloc::vec::Vec<({}, {})>,",
+                    self.resolve_ref_type(key),
+                    self.resolve_ref_type(value)
+                ));
+                merge_arms.push((
+                    number,
+                    name.clone(),
+                    format!(
+                        "{prost_path}::encoding::borrowed::map::merge({}::merge, {}::merge, &mut self.{name}, buf, ctx)",
+                        self.ref_codec(key),
+                        self.ref_codec(value)
+                    ),
+                ));
+                let owned_key = match self.ref_conversion(key, fq_message_name, true) {
+                    Some(conversion) => format!("{conversion}(*key)"),
+                    None => "*key".to_string(),
+                };
+                let owned_value = match self.ref_conversion(value, fq_message_name, true) {
+                    Some(conversion) if value.r#type() != Type::String => {
+                        format!("{conversion}(value)")
+                    }
+                    Some(conversion) => format!("{conversion}(*value)"),
+                    None => "*value".to_string(),
+                };
+                conversions.push(format!(
+                    "{name}: self.{name}.iter().map(|(key, value)| ({owned_key}, {owned_value})).collect(),"
+                ));
+                continue;
+            }
+
+            let repeated = descriptor.label() == Label::Repeated;
+            let optional = self.optional(fq_message_name, descriptor);
+            let wrapper = self
+                .context
+                .message_field_wrapper(fq_message_name, descriptor);
+            borrows |= ref_borrows(descriptor);
+
+            let mut ty = self.resolve_ref_type(descriptor);
+            if let Some(wrapper) = wrapper {
+                ty = format!("{}<{ty}>", wrapper.rust_type(&prost_path));
+            }
+            if repeated {
+                ty = format!("{prost_path}::alloc::vec::Vec<{ty}>");
+            } else if optional {
+                ty = format!("::core::option::Option<{ty}>");
+            }
+            declarations.push(format!("pub {name}: {ty},"));
+
+            let codec = self.ref_codec(descriptor);
+            let tag_arg = if descriptor.r#type() == Type::Group {
+                "tag, "
+            } else {
+                ""
+            };
+            let merge = if repeated {
+                format!("{codec}::merge_repeated({tag_arg}wire_type, &mut self.{name}, buf, ctx)")
+            } else if optional {
+                format!("{codec}::merge({tag_arg}wire_type, self.{name}.get_or_insert_with(::core::default::Default::default), buf, ctx)")
+            } else {
+                format!("{codec}::merge({tag_arg}wire_type, &mut self.{name}, buf, ctx)")
+            };
+            merge_arms.push((number, name.clone(), merge));
+
+            let conversion = match self.ref_conversion(descriptor, fq_message_name, false) {
+                None if repeated => format!("self.{name}.clone()"),
+                None => format!("self.{name}"),
+                Some(conversion)
+                    if self
+                        .context
+                        .should_lazy_message_field(fq_message_name, descriptor) =>
+                {
+                    let lazy = format!("{prost_path}::Lazy::new");
+                    if repeated {
+                        format!(
+                            "self.{name}.iter().map(|value| {lazy}({conversion}(value))).collect()"
+                        )
+                    } else if optional {
+                        format!("self.{name}.as_ref().map(|value| {lazy}({conversion}(value)))")
+                    } else {
+                        format!("{lazy}({conversion}(&self.{name}))")
+                    }
+                }
+                Some(conversion) if is_message(descriptor) => {
+                    if repeated {
+                        format!("self.{name}.iter().map({conversion}).collect()")
+                    } else if optional {
+                        format!("self.{name}.as_ref().map({conversion})")
+                    } else {
+                        format!("{conversion}(&self.{name})")
+                    }
+                }
+                Some(conversion) => {
+                    if repeated {
+                        format!("self.{name}.iter().copied().map({conversion}).collect()")
+                    } else if optional {
+                        format!("self.{name}.map({conversion})")
+                    } else {
+                        format!("{conversion}(self.{name})")
+                    }
+                }
+            };
+            conversions.push(format!("{name}: {conversion},"));
+        }
+
+        for oneof in oneof_fields {
+            let name = oneof.rust_name();
+            let owned_type = format!("{message_mod}::{}", oneof.type_name());
+            let ref_type = format!("{owned_type}Ref");
+            let lifetime = if oneof
+                .fields
+                .iter()
+                .any(|field| ref_borrows(&field.descriptor))
+            {
+                borrows = true;
+                "<'a>"
+            } else {
+                ""
+            };
+            declarations.push(format!(
+                "pub {name}: ::core::option::Option<{ref_type}{lifetime}>,"
+            ));
+
+            let mut variants = Vec::new();
+            for field in &oneof.fields {
+                let descriptor = &field.descriptor;
+                let variant = to_upper_camel(descriptor.name());
+                deprecated |= self.deprecated(descriptor);
+
+                let tag_arg = if descriptor.r#type() == Type::Group {
+                    "tag, "
+                } else {
+                    ""
+                };
+                merge_arms.push((
+                    descriptor.number(),
+                    name.clone(),
+                    format!(
+                        "{{
+                            let mut value = match self.{name}.take() {{
+                                ::core::option::Option::Some({ref_type}::{variant}(value)) => value,
+                                _ => ::core::default::Default::default(),
+                            }};
+                            let result = {}::merge({tag_arg}wire_type, &mut value, buf, ctx);
+                            self.{name} = ::core::option::Option::Some({ref_type}::{variant}(value));
+                            result
+                        }}",
+                        self.ref_codec(descriptor)
+                    ),
+                ));
+
+                let value = match self.ref_conversion(descriptor, fq_message_name, false) {
+                    Some(conversion) if descriptor.r#type() != Type::String => {
+                        format!("{conversion}(value)")
+                    }
+                    Some(conversion) => format!("{conversion}(*value)"),
+                    None => "*value".to_string(),
+                };
+                variants.push(format!(
+                    "{ref_type}::{variant}(value) => {owned_type}::{variant}({value}),"
+                ));
+            }
+            conversions.push(format!(
+                "{name}: self.{name}.as_ref().map(|value| match value {{ {} }}),",
+                variants.join("\n")
+            ));
+        }
+
+        // The lifetime parameter must be used even if no field borrows from the buffer.
+        if !borrows {
+            declarations.push("#[doc(hidden)]".to_string());
+            declarations.push("pub _marker: ::core::marker::PhantomData<&'a ()>,".to_string());
+        }
+
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "/// A borrowed view of [`{owned_name}`], see [`MessageRef`]({prost_path}::MessageRef).\n"
+        ));
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "#[derive(Clone, PartialEq, {}Default)]\n",
+            if self.context.should_skip_debug(fq_message_name) {
+                ""
+            } else {
+                "Debug, "
+            }
+        ));
+        self.push_indent();
+        self.buf
+            .push_str(&format!("pub struct {ref_name}<'a> {{\n"));
+        self.depth += 1;
+        for declaration in &declarations {
+            self.push_indent();
+            self.buf.push_str(declaration);
+            self.buf.push('\n');
+        }
+        self.depth -= 1;
+        self.push_indent();
+        self.buf.push_str("}\n");
+
+        if deprecated {
+            self.push_indent();
+            self.buf.push_str("#[allow(deprecated)]\n");
+        }
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "impl<'a> {prost_path}::MessageRef<'a> for {ref_name}<'a> {{\n"
+        ));
+        self.depth += 1;
+        self.push_indent();
+        self.buf.push_str(&format!("type Owned = {owned_name};\n"));
+
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "fn merge_field(&mut self, tag: u32, wire_type: {prost_path}::encoding::WireType, buf: &mut &'a [u8], ctx: {prost_path}::encoding::DecodeContext<'_>) -> ::core::result::Result<(), {prost_path}::DecodeError> {{\n"
+        ));
+        self.depth += 1;
+        let skip = format!("{prost_path}::encoding::skip_field(wire_type, tag, buf, ctx)");
+        self.push_indent();
+        if merge_arms.is_empty() {
+            self.buf.push_str(&skip);
+            self.buf.push('\n');
+        } else {
+            self.buf.push_str("match tag {\n");
+            for (number, name, merge) in &merge_arms {
+                self.buf.push_str(&format!(
+                    "{number} => {merge}.map_err(|mut error| {{ error.push_field({ref_name:?}, {:?}, {number}); error }}),\n",
+                    name.trim_start_matches("r#")
+                ));
+            }
+            self.buf.push_str(&format!("_ => {skip},\n"));
+            self.buf.push_str("}\n");
+        }
+        self.depth -= 1;
+        self.push_indent();
+        self.buf.push_str("}\n");
+
+        self.push_indent();
+        self.buf
+            .push_str(&format!("fn to_owned_message(&self) -> {owned_name} {{\n"));
+        self.buf.push_str(&format!("{owned_name} {{\n"));
+        for conversion in &conversions {
+            self.buf.push_str(conversion);
+            self.buf.push('\n');
+        }
+        if self.context.should_preserve_unknown_fields(fq_message_name)
+            || self.context.should_preserve_extensions(fq_message_name)
+        {
+            self.buf.push_str("..::core::default::Default::default()\n");
+        }
+        self.buf.push_str("}\n");
+        self.push_indent();
+        self.buf.push_str("}\n");
+        self.depth -= 1;
+        self.push_indent();
+        self.buf.push_str("}\n");
+
+        self.push_indent();
+        self.buf.push_str(&format!("impl {ref_name}<'_> {{\n"));
+        self.depth += 1;
+        self.push_indent();
+        self.buf
+            .push_str("/// Copies the view into a new owned message.\n");
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "pub fn to_owned(&self) -> {owned_name} {{ {prost_path}::MessageRef::to_owned_message(self) }}\n"
+        ));
+        for getter in self.default_getters(fq_message_name, fields) {
+            self.buf.push_str(&getter);
+        }
+        self.depth -= 1;
+        self.push_indent();
+        self.buf.push_str("}\n");
+    }
+
+    fn append_arena_message(
+        &mut self,
+        message_name: &str,
+        fq_message_name: &str,
+        fields: &[Field],
+        map_types: &HashMap<String, (FieldDescriptorProto, FieldDescriptorProto)>,
+        oneof_fields: &[OneofField],
+    ) {
+        let prost_path = self.context.prost_path().to_string();
+        let owned_name = to_upper_camel(message_name);
+        let arena_name = format!("{owned_name}Arena");
+        let message_mod = to_snake(message_name);
+        let vec = format!("{prost_path}::bumpalo::collections::Vec");
+        let new_in = format!("{prost_path}::encoding::arena::NewIn::new_in(arena)");
+
+        // The struct declaration, the `merge_field` match arms, and the `new_in` field initializers.
+        let mut declarations = Vec::new();
+        let mut merge_arms = Vec::new();
+        let mut initializers = Vec::new();
+        let mut borrows = false;
+        let mut deprecated = false;
+
+        for field in fields {
+            let descriptor = &field.descriptor;
+            let name = field.rust_name();
+            let number = descriptor.number();
+            deprecated |= self.deprecated(descriptor);
+
+            if let Some((key, value)) = descriptor
+                .type_name
+                .as_ref()
+                .and_then(|type_name| map_types.get(type_name))
+            {
+                borrows = true;
+                declarations.push(format!(
+                    "pub {name}: {vec}<'bump, ({}, {})>,",
+                    self.resolve_arena_type(key),
+                    self.resolve_arena_type(value)
+                ));
+                merge_arms.push((
+                    number,
+                    name.clone(),
+                    format!(
+                        "{prost_path}::encoding::arena::map::merge({}, {}, &mut self.{name}, buf, arena, ctx)",
+                        self.arena_map_merge(key),
+                        self.arena_map_merge(value)
+                    ),
+                ));
+                initializers.push(format!("{name}: {vec}::new_in(arena),"));
+                continue;
+            }
+
+            let repeated = descriptor.label() == Label::Repeated;
+            let optional = self.optional(fq_message_name, descriptor);
+            let boxed = self
+                .context
+                .should_box_message_field(fq_message_name, descriptor);
+            borrows |= repeated || ref_borrows(descriptor);
+
+            let mut ty = self.resolve_arena_type(descriptor);
+            if boxed {
+                ty = format!("&'bump mut {ty}");
+            }
+            if repeated {
+                ty = format!("{vec}<'bump, {ty}>");
+            } else if optional {
+                ty = format!("::core::option::Option<{ty}>");
+            }
+            declarations.push(format!("pub {name}: {ty},"));
+
+            let (codec, arena_arg) = self.arena_codec(descriptor);
+            let tag_arg = if descriptor.r#type() == Type::Group {
+                "tag, "
+            } else {
+                ""
+            };
+            let merge = if repeated && arena_arg.is_empty() {
+                format!(
+                    "{prost_path}::encoding::arena::merge_repeated_scalar({prost_path}::encoding::WireType::{}, {codec}::merge, wire_type, &mut self.{name}, buf, ctx)",
+                    scalar_wire_type(descriptor)
+                )
+            } else if repeated {
+                format!("{codec}::merge_repeated({tag_arg}wire_type, &mut self.{name}, buf, {arena_arg}ctx)")
+            } else if optional {
+                format!("{codec}::merge({tag_arg}wire_type, self.{name}.get_or_insert_with(|| {new_in}), buf, {arena_arg}ctx)")
+            } else {
+                format!("{codec}::merge({tag_arg}wire_type, &mut self.{name}, buf, {arena_arg}ctx)")
+            };
+            merge_arms.push((number, name.clone(), merge));
+
+            initializers.push(if repeated {
+                format!("{name}: {vec}::new_in(arena),")
+            } else if optional {
+                format!("{name}: ::core::option::Option::None,")
+            } else {
+                format!("{name}: {new_in},")
+            });
+        }
+
+        for oneof in oneof_fields {
+            let name = oneof.rust_name();
+            let arena_type = format!("{message_mod}::{}Arena", oneof.type_name());
+            let lifetime = if oneof
+                .fields
+                .iter()
+                .any(|field| ref_borrows(&field.descriptor))
+            {
+                borrows = true;
+                "<'bump>"
+            } else {
+                ""
+            };
+            declarations.push(format!(
+                "pub {name}: ::core::option::Option<{arena_type}{lifetime}>,"
+            ));
+            initializers.push(format!("{name}: ::core::option::Option::None,"));
+
+            for field in &oneof.fields {
+                let descriptor = &field.descriptor;
+                let variant = to_upper_camel(descriptor.name());
+                deprecated |= self.deprecated(descriptor);
+
+                let (codec, arena_arg) = self.arena_codec(descriptor);
+                let tag_arg = if descriptor.r#type() == Type::Group {
+                    "tag, "
+                } else {
+                    ""
+                };
+                merge_arms.push((
+                    descriptor.number(),
+                    name.clone(),
+                    format!(
+                        "{{
+                            let mut value = match self.{name}.take() {{
+                                ::core::option::Option::Some({arena_type}::{variant}(value)) => value,
+                                _ => {new_in},
+                            }};
+                            let result = {codec}::merge({tag_arg}wire_type, &mut value, buf, {arena_arg}ctx);
+                            self.{name} = ::core::option::Option::Some({arena_type}::{variant}(value));
+                            result
+                        }}"
+                    ),
+                ));
+            }
+        }
+
+        // The lifetime parameter must be used even if no field is allocated in the arena.
+        if !borrows {
+            declarations.push("#[doc(hidden)]".to_string());
+            declarations.push("pub _marker: ::core::marker::PhantomData<&'bump ()>,".to_string());
+            initializers.push("_marker: ::core::marker::PhantomData,".to_string());
+        }
+
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "/// [`{owned_name}`] decoded into an arena, see [`ArenaMessage`]({prost_path}::ArenaMessage).\n"
+        ));
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "#[derive(PartialEq{})]\n",
+            if self.context.should_skip_debug(fq_message_name) {
+                ""
+            } else {
+                ", Debug"
+            }
+        ));
+        self.push_indent();
+        self.buf
+            .push_str(&format!("pub struct {arena_name}<'bump> {{\n"));
+        self.depth += 1;
+        for declaration in &declarations {
+            self.push_indent();
+            self.buf.push_str(declaration);
+            self.buf.push('\n');
+        }
+        self.depth -= 1;
+        self.push_indent();
+        self.buf.push_str("}\n");
+
+        if deprecated {
+            self.push_indent();
+            self.buf.push_str("#[allow(deprecated)]\n");
+        }
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "impl<'bump> {prost_path}::ArenaMessage<'bump> for {arena_name}<'bump> {{\n"
+        ));
+        self.depth += 1;
+
+        self.push_indent();
+        self.buf.push_str("#[allow(unused_variables)]\n");
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "fn new_in(arena: &'bump {prost_path}::bumpalo::Bump) -> Self {{\n"
+        ));
+        self.buf.push_str("Self {\n");
+        for initializer in &initializers {
+            self.buf.push_str(initializer);
+            self.buf.push('\n');
+        }
+        self.buf.push_str("}\n");
+        self.push_indent();
+        self.buf.push_str("}\n");
+
+        self.push_indent();
+        self.buf.push_str("#[allow(unused_variables)]\n");
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "fn merge_field(&mut self, tag: u32, wire_type: {prost_path}::encoding::WireType, buf: &mut impl {prost_path}::bytes::Buf, arena: &'bump {prost_path}::bumpalo::Bump, ctx: {prost_path}::encoding::DecodeContext<'_>) -> ::core::result::Result<(), {prost_path}::DecodeError> {{\n"
+        ));
+        self.depth += 1;
+        let skip = format!("{prost_path}::encoding::skip_field(wire_type, tag, buf, ctx)");
+        self.push_indent();
+        if merge_arms.is_empty() {
+            self.buf.push_str(&skip);
+            self.buf.push('\n');
+        } else {
+            self.buf.push_str("match tag {\n");
+            for (number, name, merge) in &merge_arms {
+                self.buf.push_str(&format!(
+                    "{number} => {merge}.map_err(|mut error| {{ error.push_field({arena_name:?}, {:?}, {number}); error }}),\n",
+                    name.trim_start_matches("r#")
+                ));
+            }
+            self.buf.push_str(&format!("_ => {skip},\n"));
+            self.buf.push_str("}\n");
+        }
+        self.depth -= 1;
+        self.push_indent();
+        self.buf.push_str("}\n");
+        self.depth -= 1;
+        self.push_indent();
+        self.buf.push_str("}\n");
+
+        let getters = self.default_getters(fq_message_name, fields);
+        if !getters.is_empty() {
+            self.push_indent();
+            self.buf.push_str(&format!("impl {arena_name}<'_> {{\n"));
+            for getter in getters {
+                self.buf.push_str(&getter);
+            }
+            self.push_indent();
+            self.buf.push_str("}\n");
+        }
+    }
+
+    fn append_builder(
+        &mut self,
+        message_name: &str,
+        fq_message_name: &str,
+        fields: &[Field],
+        map_types: &HashMap<String, (FieldDescriptorProto, FieldDescriptorProto)>,
+        oneof_fields: &[OneofField],
+    ) {
+        let prost_path = self.context.prost_path().to_string();
+        let message_type = to_upper_camel(message_name);
+        let builder_type = format!("{message_type}Builder");
+        let into = "::core::convert::Into";
+
+        // The flags of the required fields which have been set, and the setter methods.
+        let mut required = Vec::new();
+        let mut setters = Vec::new();
+        let presence_bits = self.presence_bits(fq_message_name, fields);
+
+        for field in fields {
+            let descriptor = &field.descriptor;
+            let name = field.rust_name();
+            let base_name = name.trim_start_matches("r#");
+            let setter = builder_setter_name(&name);
+            let deprecated = if self.deprecated(descriptor) {
+                "#[deprecated]\n"
+            } else {
+                ""
+            };
+
+            if let Some((key, value)) = descriptor
+                .type_name
+                .as_ref()
+                .and_then(|type_name| map_types.get(type_name))
+            {
+                let key_ty = self.resolve_map_entry_type(key, fq_message_name);
+                let value_ty = self.resolve_map_entry_type(value, fq_message_name);
+                let insert = match self.context.map_type(fq_message_name, descriptor.name()) {
+                    crate::MapType::Custom(_) => format!(
+                        "::core::iter::Extend::extend(&mut self.inner.{name}, ::core::iter::once((key.into(), value.into())));"
+                    ),
+                    _ => format!("self.inner.{name}.insert(key.into(), value.into());"),
+                };
+                setters.push(format!(
+                    "/// Sets the entries of [`{message_type}::{base_name}`].
+                    {deprecated}pub fn {setter}<I, K, V>(mut self, entries: I) -> Self
+                    where
+                        I: ::core::iter::IntoIterator<Item = (K, V)>,
+                        K: {into}<{key_ty}>,
+                        V: {into}<{value_ty}>,
+                    {{
+                        self.inner.{name} = entries.into_iter().map(|(key, value)| (key.into(), value.into())).collect();
+                        self
+                    }}
+                    /// Inserts an entry into [`{message_type}::{base_name}`].
+                    {deprecated}pub fn insert_{base_name}(mut self, key: impl {into}<{key_ty}>, value: impl {into}<{value_ty}>) -> Self {{
+                        {insert}
+                        self
+                    }}"
+                ));
+                continue;
+            }
+
+            let ty = self.resolve_field_type(descriptor, fq_message_name);
+            let value = match self
+                .context
+                .message_field_wrapper(fq_message_name, descriptor)
+            {
+                Some(wrapper) => format!("{}::new(value.into())", wrapper.rust_type(&prost_path)),
+                None => "value.into()".to_string(),
+            };
+            let lazy = self
+                .context
+                .should_lazy_message_field(fq_message_name, descriptor);
+            let value = if lazy {
+                format!("{prost_path}::Lazy::new({value})")
+            } else {
+                value
+            };
+
+            if descriptor.label() == Label::Repeated {
+                let values = if lazy {
+                    format!("values.into_iter().map(|value| {value}).collect()")
+                } else {
+                    format!("values.into_iter().map({into}::into).collect()")
+                };
+                setters.push(format!(
+                    "/// Sets the values of [`{message_type}::{base_name}`].
+                    {deprecated}pub fn {setter}<I>(mut self, values: I) -> Self
+                    where
+                        I: ::core::iter::IntoIterator,
+                        I::Item: {into}<{ty}>,
+                    {{
+                        self.inner.{name} = {values};
+                        self
+                    }}
+                    /// Appends a value to [`{message_type}::{base_name}`].
+                    {deprecated}pub fn push_{base_name}(mut self, value: impl {into}<{ty}>) -> Self {{
+                        self.inner.{name}.push({value});
+                        self
+                    }}"
+                ));
+            } else if let Some(&bit) = presence_bits.get(&descriptor.number()) {
+                setters.push(format!(
+                    "/// Sets [`{message_type}::{base_name}`].
+                    {deprecated}pub fn {setter}(mut self, value: impl {into}<{ty}>) -> Self {{
+                        self.inner.{name} = {value};
+                        self.inner._presence_bits[{}] |= {};
+                        self
+                    }}",
+                    bit / 32,
+                    1_u32 << (bit % 32),
+                ));
+            } else {
+                let value = if self.optional(fq_message_name, descriptor) {
+                    format!("::core::option::Option::Some({value})")
+                } else {
+                    value
+                };
+                let flag = if descriptor.label() == Label::Required {
+                    required.push((format!("has_{base_name}"), descriptor.name().to_string()));
+                    format!("self.has_{base_name} = true;\n")
+                } else {
+                    String::new()
+                };
+                setters.push(format!(
+                    "/// Sets [`{message_type}::{base_name}`].
+                    {deprecated}pub fn {setter}(mut self, value: impl {into}<{ty}>) -> Self {{
+                        self.inner.{name} = {value};
+                        {flag}self
+                    }}"
+                ));
+            }
+        }
+
+        for oneof in oneof_fields {
+            let name = oneof.rust_name();
+            let base_name = name.trim_start_matches("r#");
+            let oneof_type = format!("{}::{}", to_snake(message_name), oneof.type_name());
+            for field in &oneof.fields {
+                let descriptor = &field.descriptor;
+                let variant = to_upper_camel(descriptor.name());
+                let setter = builder_setter_name(&to_snake(descriptor.name()));
+                let deprecated = if self.deprecated(descriptor) {
+                    "#[deprecated]\n"
+                } else {
+                    ""
+                };
+                let ty = self.resolve_type(descriptor, fq_message_name);
+                let value = match self.context.oneof_field_wrapper(
+                    fq_message_name,
+                    oneof.descriptor.name(),
+                    descriptor,
+                ) {
+                    Some(wrapper) => {
+                        format!("{}::new(value.into())", wrapper.rust_type(&prost_path))
+                    }
+                    None => "value.into()".to_string(),
+                };
+                setters.push(format!(
+                    "/// Sets [`{message_type}::{base_name}`] to [`{oneof_type}::{variant}`].
+                    {deprecated}pub fn {setter}(mut self, value: impl {into}<{ty}>) -> Self {{
+                        self.inner.{name} = ::core::option::Option::Some({oneof_type}::{variant}({value}));
+                        self
+                    }}"
+                ));
+            }
+        }
+
+        let full_name = fq_message_name.trim_start_matches('.');
+        let derive_debug = if self.context.should_skip_debug(fq_message_name) {
+            ""
+        } else {
+            "Debug, "
+        };
+        let flags = required
+            .iter()
+            .map(|(flag, _)| format!("{flag}: bool,\n"))
+            .join("");
+        let checks = required
+            .iter()
+            .map(|(flag, field)| {
+                format!(
+                    "if !self.{flag} {{
+                        return ::core::result::Result::Err({prost_path}::BuildError::missing_field({full_name:?}, {field:?}));
+                    }}\n"
+                )
+            })
+            .join("");
+        let set_flags = required
+            .iter()
+            .map(|(flag, _)| format!("{flag}: true,\n"))
+            .join("");
+
+        self.buf.push_str(&format!(
+            "impl {message_type} {{
+                /// Returns a builder for the message.
+                pub fn builder() -> {builder_type} {{
+                    {builder_type}::default()
+                }}
+            }}
+            /// A builder for [`{message_type}`].
+            #[derive(Clone, {derive_debug}Default)]
+            pub struct {builder_type} {{
+                inner: {message_type},
+                {flags}}}
+            {allow_deprecated}impl {builder_type} {{
+                /// Creates a builder for a default message.
+                pub fn new() -> Self {{
+                    Self::default()
+                }}
+                {setters}
+                /// Builds the message.
+                ///
+                /// Returns an error if a required field has not been set.
+                pub fn build(self) -> ::core::result::Result<{message_type}, {prost_path}::BuildError> {{
+                    {checks}::core::result::Result::Ok(self.inner)
+                }}
+            }}
+            impl ::core::convert::From<{message_type}> for {builder_type} {{
+                fn from(message: {message_type}) -> Self {{
+                    {builder_type} {{
+                        inner: message,
+                        {set_flags}}}
+                }}
+            }}
+",
+            setters = setters.join("\n"),
+            allow_deprecated = if setters.iter().any(|setter| setter.contains("#[deprecated]")) {
+                "#[allow(deprecated)]\n"
+            } else {
+                ""
+            },
+        ));
+    }
+
+    fn append_oneof_accessors(
+        &mut self,
+        message_name: &str,
+        fq_message_name: &str,
+        oneof_fields: &[OneofField],
+    ) {
+        let message_type = to_upper_camel(message_name);
+        let mut accessors = Vec::new();
+
+        for oneof in oneof_fields {
+            let name = oneof.rust_name();
+            let base_name = name.trim_start_matches("r#");
+            let oneof_type = format!("{}::{}", to_snake(message_name), oneof.type_name());
+            let mut cases = Vec::new();
+            for field in &oneof.fields {
+                let descriptor = &field.descriptor;
+                let variant = to_upper_camel(descriptor.name());
+                let field_name = to_snake(descriptor.name());
+                let field_name = field_name.trim_start_matches("r#");
+                let deprecated = if self.deprecated(descriptor) {
+                    "#[deprecated]\n"
+                } else {
+                    ""
+                };
+                let ty = self.resolve_type(descriptor, fq_message_name);
+                let (value, reference) = match self.context.oneof_field_wrapper(
+                    fq_message_name,
+                    oneof.descriptor.name(),
+                    descriptor,
+                ) {
+                    Some(wrapper) => (
+                        format!(
+                            "{}::new(value)",
+                            wrapper.rust_type(self.context.prost_path())
+                        ),
+                        "&**value",
+                    ),
+                    None => ("value".to_string(), "value"),
+                };
+                accessors.push(format!(
+                    "/// Returns the value of `{}` if it is the field of [`{message_type}::{name}`] which is set.
+                    {deprecated}pub fn {base_name}_as_{field_name}(&self) -> ::core::option::Option<&{ty}> {{
+                        match self.{name} {{
+                            ::core::option::Option::Some({oneof_type}::{variant}(ref value)) => ::core::option::Option::Some({reference}),
+                            _ => ::core::option::Option::None,
+                        }}
+                    }}
+                    /// Sets [`{message_type}::{name}`] to [`{oneof_type}::{variant}`].
+                    {deprecated}pub fn set_{base_name}_{field_name}(&mut self, value: {ty}) {{
+                        self.{name} = ::core::option::Option::Some({oneof_type}::{variant}({value}));
+                    }}",
+                    descriptor.name(),
+                ));
+                cases.push(format!(
+                    "{oneof_type}::{variant}(_) => {:?},",
+                    descriptor.name()
+                ));
+            }
+            accessors.push(format!(
+                "/// Returns the Protobuf name of the field of [`{message_type}::{name}`] which is
+                /// set, or `None` if no field is set.
+                pub fn which_{base_name}(&self) -> ::core::option::Option<&'static str> {{
+                    self.{name}.as_ref().map(|value| match value {{
+                        {}
+                    }})
+                }}",
+                cases.join("\n"),
+            ));
+        }
+
+        self.buf.push_str(&format!(
+            "{allow_deprecated}impl {message_type} {{
+                {}
+            }}
+",
+            accessors.join("\n"),
+            allow_deprecated = if accessors
+                .iter()
+                .any(|accessor| accessor.contains("#[deprecated]"))
+            {
+                "#[allow(deprecated)]\n"
+            } else {
+                ""
+            },
+        ));
+    }
+
+    /// Appends the field number constants of a message, and its `fields` method.
+    fn append_field_numbers(
+        &mut self,
+        message_name: &str,
+        fields: &[Field],
+        oneof_fields: &[OneofField],
+    ) {
+        let prost_path = self.context.prost_path().to_string();
+        let message_type = to_upper_camel(message_name);
+        let mut fields = fields
+            .iter()
+            .chain(oneof_fields.iter().flat_map(|oneof| &oneof.fields))
+            .map(|field| &field.descriptor)
+            .collect::<Vec<_>>();
+        fields.sort_by_key(|descriptor| descriptor.number());
+
+        let mut constants = Vec::new();
+        let mut infos = Vec::new();
+        for descriptor in fields {
+            let name = descriptor.name();
+            let constant = format!(
+                "{}_FIELD_NUMBER",
+                to_snake(name).trim_start_matches("r#").to_uppercase()
+            );
+            constants.push(format!(
+                "/// The field number of `{name}`.
+                pub const {constant}: u32 = {};",
+                descriptor.number()
+            ));
+            infos.push(format!(
+                "{prost_path}::wire::FieldInfo {{
+                    name: {name:?},
+                    number: Self::{constant},
+                    wire_type: {prost_path}::wire::WireType::{},
+                }},",
+                self.wire_type(descriptor)
+            ));
+        }
+
+        self.buf.push_str(&format!(
+            "impl {message_type} {{
+                {}
+                /// Returns the name, number and wire type of each field of the message, ordered
+                /// by number.
+                pub const fn fields() -> &'static [{prost_path}::wire::FieldInfo] {{
+                    const FIELDS: &[{prost_path}::wire::FieldInfo] = &[{}];
+                    FIELDS
+                }}
+            }}
+",
+            constants.join("\n"),
+            infos.join("\n"),
+        ));
+    }
+
+    /// Returns the name of the `WireType` variant a field is encoded with.
+    fn wire_type(&self, field: &FieldDescriptorProto) -> &'static str {
+        if field.label() == Label::Repeated && can_pack(field) && self.packed(field) {
+            return "LengthDelimited";
+        }
+        match field.r#type() {
+            Type::Float | Type::Fixed32 | Type::Sfixed32 => "ThirtyTwoBit",
+            Type::Double | Type::Fixed64 | Type::Sfixed64 => "SixtyFourBit",
+            Type::String | Type::Bytes | Type::Message => "LengthDelimited",
+            Type::Group => "StartGroup",
+            Type::Int32
+            | Type::Int64
+            | Type::Uint32
+            | Type::Uint64
+            | Type::Sint32
+            | Type::Sint64
+            | Type::Bool
+            | Type::Enum => "Varint",
+        }
+    }
+
+    /// Returns `true` if a repeated scalar field is packed.
+    fn packed(&self, field: &FieldDescriptorProto) -> bool {
+        // Fields without an explicit `packed` option use the default of the syntax, even if they
+        // have other options.
+        field
+            .options
+            .as_ref()
+            .and_then(|options| options.packed)
+            .unwrap_or(self.syntax == Syntax::Proto3)
+    }
+
+    fn append_validate(
+        &mut self,
+        message_name: &str,
+        fq_message_name: &str,
+        fields: &[Field],
+        map_types: &HashMap<String, (FieldDescriptorProto, FieldDescriptorProto)>,
+        oneof_fields: &[OneofField],
+    ) {
+        let prost_path = self.context.prost_path().to_string();
+        let message_type = to_upper_camel(message_name);
+        let format = format!("{prost_path}::alloc::format!");
+        let mut checks = Vec::new();
+        let mut deprecated = false;
+
+        for field in fields {
+            let descriptor = &field.descriptor;
+            let rules = validate::field_rules(fq_message_name, descriptor);
+            if rules.is_ignored() {
+                continue;
+            }
+            let name = field.rust_name();
+            let path = format!("{:?}", descriptor.name());
+            let len = checks.len();
+
+            if let Some((key, value)) = descriptor
+                .type_name
+                .as_ref()
+                .and_then(|type_name| map_types.get(type_name))
+            {
+                let map_rules = match &rules.r#type {
+                    None => Default::default(),
+                    Some(validate::TypeRules::Map(map_rules)) => (**map_rules).clone(),
+                    Some(_) => panic!(
+                        "the (buf.validate.field) rules of map field {} in {fq_message_name} are not map rules",
+                        descriptor.name()
+                    ),
+                };
+                if rules.is_required() {
+                    checks.push(format!(
+                        "if self.{name}.is_empty() {{ violations.push({path}, \"required\", \"value is required\"); }}"
+                    ));
+                }
+                if let Some(min) = map_rules.min_pairs {
+                    checks.push(format!(
+                        "if (self.{name}.len() as u64) < {min} {{ violations.push({path}, \"map.min_pairs\", \"map must be at least {min} entries\"); }}"
+                    ));
+                }
+                if let Some(max) = map_rules.max_pairs {
+                    checks.push(format!(
+                        "if self.{name}.len() as u64 > {max} {{ violations.push({path}, \"map.max_pairs\", \"map must be at most {max} entries\"); }}"
+                    ));
+                }
+                let key_checks = map_rules
+                    .keys
+                    .filter(|rules| !rules.is_ignored())
+                    .map(|rules| self.validate_value(fq_message_name, key, &rules, "&path"))
+                    .unwrap_or_default();
+                let value_rules = map_rules.values.unwrap_or_default();
+                let value_checks = if value_rules.is_ignored() {
+                    String::new()
+                } else {
+                    self.validate_value(fq_message_name, value, &value_rules, "&path")
+                };
+                if !key_checks.is_empty() || !value_checks.is_empty() {
+                    let (entries, key_checks) =
+                        match (key_checks.is_empty(), value_checks.is_empty()) {
+                            (_, true) => (
+                                format!("key in self.{name}.keys()"),
+                                format!("let value = key; {key_checks}"),
+                            ),
+                            (true, false) => {
+                                (format!("(key, value) in &self.{name}"), String::new())
+                            }
+                            (false, false) => (
+                                format!("(key, value) in &self.{name}"),
+                                format!("{{ let value = key; {key_checks} }}"),
+                            ),
+                        };
+                    checks.push(format!(
+                        "for {entries} {{
+                            let path = {format}(\"{}[{{:?}}]\", key);
+                            {key_checks}
+                            {value_checks}
+                        }}",
+                        descriptor.name()
+                    ));
+                }
+            } else if descriptor.label() == Label::Repeated {
+                let repeated_rules = match &rules.r#type {
+                    None => Default::default(),
+                    Some(validate::TypeRules::Repeated(repeated_rules)) => {
+                        (**repeated_rules).clone()
+                    }
+                    Some(_) => panic!(
+                        "the (buf.validate.field) rules of repeated field {} in {fq_message_name} are not repeated rules",
+                        descriptor.name()
+                    ),
+                };
+                if rules.is_required() {
+                    checks.push(format!(
+                        "if self.{name}.is_empty() {{ violations.push({path}, \"required\", \"value is required\"); }}"
+                    ));
+                }
+                if let Some(min) = repeated_rules.min_items {
+                    checks.push(format!(
+                        "if (self.{name}.len() as u64) < {min} {{ violations.push({path}, \"repeated.min_items\", \"must contain at least {min} item(s)\"); }}"
+                    ));
+                }
+                if let Some(max) = repeated_rules.max_items {
+                    checks.push(format!(
+                        "if self.{name}.len() as u64 > {max} {{ violations.push({path}, \"repeated.max_items\", \"must contain no more than {max} item(s)\"); }}"
+                    ));
+                }
+                if repeated_rules.unique() {
+                    if matches!(descriptor.r#type(), Type::Message | Type::Group) {
+                        panic!(
+                            "the repeated.unique rule of field {} in {fq_message_name} only applies to scalar fields",
+                            descriptor.name()
+                        );
+                    }
+                    checks.push(format!(
+                        "if self.{name}.iter().enumerate().any(|(index, value)| self.{name}[..index].contains(value)) {{
+                            violations.push({path}, \"repeated.unique\", \"repeated value must contain unique items\");
+                        }}"
+                    ));
+                }
+                let item_rules = repeated_rules.items.unwrap_or_default();
+                let item_checks = if item_rules.is_ignored() {
+                    String::new()
+                } else {
+                    let checks =
+                        self.validate_value(fq_message_name, descriptor, &item_rules, "&path");
+                    self.open_enum_value(fq_message_name, descriptor, checks)
+                };
+                if !item_checks.is_empty() {
+                    checks.push(format!(
+                        "for (index, value) in self.{name}.iter().enumerate() {{
+                            let path = {format}(\"{}[{{}}]\", index);
+                            {item_checks}
+                        }}",
+                        descriptor.name()
+                    ));
+                }
+            } else {
+                let value_checks = self.validate_value(fq_message_name, descriptor, &rules, &path);
+                let value_checks = self.open_enum_value(fq_message_name, descriptor, value_checks);
+                if self.optional(fq_message_name, descriptor) {
+                    let missing =
+                        format!("violations.push({path}, \"required\", \"value is required\");");
+                    match (value_checks.is_empty(), rules.is_required()) {
+                        (true, false) => (),
+                        (true, true) => checks.push(format!("if self.{name}.is_none() {{ {missing} }}")),
+                        (false, required) => checks.push(format!(
+                            "if let ::core::option::Option::Some(value) = &self.{name} {{ {value_checks} }}{}",
+                            if required { format!(" else {{ {missing} }}") } else { String::new() }
+                        )),
+                    }
+                } else {
+                    // Required fields of proto2 messages are always set, so only fields with
+                    // implicit presence are checked for their zero value.
+                    if rules.is_required() && descriptor.label() != Label::Required {
+                        if let Some((zero, _)) = zero_value(descriptor) {
+                            let zero = self.open_enum_value(
+                                fq_message_name,
+                                descriptor,
+                                format!("if {zero} {{ violations.push({path}, \"required\", \"value is required\"); }}"),
+                            );
+                            checks.push(format!("{{ let value = &self.{name}; {zero} }}"));
+                        }
+                    }
+                    if !value_checks.is_empty() {
+                        checks.push(format!("{{ let value = &self.{name}; {value_checks} }}"));
+                    }
+                }
+            }
+
+            deprecated |= checks.len() > len && self.deprecated(descriptor);
+        }
+
+        for oneof in oneof_fields {
+            let name = oneof.rust_name();
+            let oneof_type = format!("{}::{}", to_snake(message_name), oneof.type_name());
+            if validate::is_oneof_required(&oneof.descriptor) {
+                checks.push(format!(
+                    "if self.{name}.is_none() {{ violations.push({:?}, \"required\", \"exactly one field is required in oneof\"); }}",
+                    oneof.descriptor.name()
+                ));
+            }
+            for field in &oneof.fields {
+                let descriptor = &field.descriptor;
+                let rules = validate::field_rules(fq_message_name, descriptor);
+                if rules.is_ignored() {
+                    continue;
+                }
+                let path = format!("{:?}", descriptor.name());
+                let value_checks = self.validate_value(fq_message_name, descriptor, &rules, &path);
+                if !value_checks.is_empty() {
+                    checks.push(format!(
+                        "if let ::core::option::Option::Some({oneof_type}::{}(value)) = &self.{name} {{ {value_checks} }}",
+                        to_upper_camel(descriptor.name())
+                    ));
+                    deprecated |= self.deprecated(descriptor);
+                }
+            }
+        }
+
+        let body = if checks.is_empty() {
+            "::core::result::Result::Ok(())".to_string()
+        } else {
+            format!(
+                "let mut violations = {prost_path}::validate::Violations::new();
+                {}
+                violations.finish()",
+                checks.join("\n")
+            )
+        };
+        self.buf.push_str(&format!(
+            "#[allow(unused_comparisons, clippy::absurd_extreme_comparisons, clippy::manual_range_contains)]
+            {allow_deprecated}impl {prost_path}::validate::Validate for {message_type} {{
+                fn validate(&self) -> ::core::result::Result<(), {prost_path}::validate::ValidationError> {{
+                    {body}
+                }}
+            }}
+",
+            allow_deprecated = if deprecated {
+                "#[allow(deprecated)]\n"
+            } else {
+                ""
+            },
+        ));
+    }
+
+    /// Returns the checks of the rules of a singular value, or of an item of a repeated or map
+    /// field, which is bound to `value`. `path` is an expression of the field path of the value.
+    fn validate_value(
+        &self,
+        fq_message_name: &str,
+        descriptor: &FieldDescriptorProto,
+        rules: &validate::FieldRules,
+        path: &str,
+    ) -> String {
+        let prost_path = self.context.prost_path();
+        let enum_type =
+            (descriptor.r#type() == Type::Enum).then(|| self.resolve_ident(descriptor.type_name()));
+        let field_name = format!("{}.{}", fq_message_name, descriptor.name());
+        let mut checks = validate::value_checks(
+            rules,
+            descriptor,
+            &field_name,
+            enum_type.as_deref(),
+            prost_path,
+            path,
+        );
+        if matches!(descriptor.r#type(), Type::Message | Type::Group)
+            && self.context.should_validate(descriptor.type_name())
+        {
+            checks.push_str(&format!(
+                "violations.nested({path}, {prost_path}::validate::Validate::validate(value));\n"
+            ));
+        }
+        if checks.is_empty() {
+            return checks;
+        }
+        match zero_value(descriptor) {
+            Some((_, non_zero)) if rules.ignore_if_zero() => {
+                format!("if {non_zero} {{ {checks} }}")
+            }
+            _ => format!("{{ {checks} }}"),
+        }
+    }
+
+    /// Wraps the checks of the value of an open enum field, bound to `value`, in a block which
+    /// binds its `i32` value to `value` instead.
+    fn open_enum_value(
+        &self,
+        fq_message_name: &str,
+        descriptor: &FieldDescriptorProto,
+        checks: String,
+    ) -> String {
+        if checks.is_empty()
+            || !self
+                .context
+                .should_use_open_enum(fq_message_name, descriptor)
+        {
+            return checks;
+        }
+        format!("{{ let value = &::core::primitive::i32::from(*value); {checks} }}")
+    }
+
+    fn append_oneof_ref(&mut self, fq_message_name: &str, oneof: &OneofField) {
+        let prost_path = self.context.prost_path().to_string();
+        let owned_name = oneof.type_name();
+        let lifetime = if oneof
+            .fields
+            .iter()
+            .any(|field| ref_borrows(&field.descriptor))
+        {
+            "<'a>"
+        } else {
+            ""
+        };
 
-        let oneof_name = format!("{}.{}", fq_message_name, oneof.descriptor.name());
-        self.append_type_attributes(&oneof_name);
-        self.append_enum_attributes(&oneof_name);
         self.push_indent();
-
-        let can_oneof_derive_copy = oneof.fields.iter().all(|field| {
-            self.context
-                .can_field_derive_copy(fq_message_name, &field.descriptor)
-        });
-        let can_oneof_derive_eq = oneof.fields.iter().all(|field| {
-            self.context
-                .can_field_derive_eq(fq_message_name, &field.descriptor)
-        });
+        self.buf
+            .push_str(&format!("/// A borrowed view of [`{owned_name}`].\n"));
+        self.push_indent();
         self.buf.push_str(&format!(
-            "#[derive(Clone, {}PartialEq, {}{}::Oneof)]\n",
-            if can_oneof_derive_copy { "Copy, " } else { "" },
-            if can_oneof_derive_eq {
-                "Eq, Hash, "
-            } else {
+            "#[derive(Clone, PartialEq{})]\n",
+            if self.context.should_skip_debug(fq_message_name) {
                 ""
-            },
-            self.context.prost_path()
+            } else {
+                ", Debug"
+            }
         ));
-        self.append_prost_path_attribute();
-        self.append_skip_debug(fq_message_name);
         self.push_indent();
-        self.buf.push_str("pub enum ");
-        self.buf.push_str(&oneof.type_name());
-        self.buf.push_str(" {\n");
-
-        self.path.push(2);
+        self.buf
+            .push_str(&format!("pub enum {owned_name}Ref{lifetime} {{\n"));
         self.depth += 1;
         for field in &oneof.fields {
-            self.path.push(field.path_index);
-            self.append_doc(fq_message_name, Some(field.descriptor.name()));
-            self.path.pop();
-
-            if self.deprecated(&field.descriptor) {
-                self.push_indent();
-                self.buf.push_str("#[deprecated]\n");
+            let mut ty = self.resolve_ref_type(&field.descriptor);
+            if let Some(wrapper) = self.context.oneof_field_wrapper(
+                fq_message_name,
+                oneof.descriptor.name(),
+                &field.descriptor,
+            ) {
+                ty = format!("{}<{ty}>", wrapper.rust_type(&prost_path));
             }
-
             self.push_indent();
-            let ty_tag = self.field_type_tag(&field.descriptor);
             self.buf.push_str(&format!(
-                "#[prost({}, tag = \"{}\")]\n",
-                ty_tag,
-                field.descriptor.number()
+                "{}({ty}),\n",
+                to_upper_camel(field.descriptor.name())
             ));
-            self.append_field_attributes(&oneof_name, field.descriptor.name());
+        }
+        self.depth -= 1;
+        self.push_indent();
+        self.buf.push_str("}\n");
+    }
 
-            self.push_indent();
-            let ty = self.resolve_type(&field.descriptor, fq_message_name);
+    fn append_oneof_arena(&mut self, fq_message_name: &str, oneof: &OneofField) {
+        let owned_name = oneof.type_name();
+        let lifetime = if oneof
+            .fields
+            .iter()
+            .any(|field| ref_borrows(&field.descriptor))
+        {
+            "<'bump>"
+        } else {
+            ""
+        };
 
-            let boxed = self.context.should_box_oneof_field(
+        self.push_indent();
+        self.buf
+            .push_str(&format!("/// [`{owned_name}`] decoded into an arena.\n"));
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "#[derive(PartialEq{})]\n",
+            if self.context.should_skip_debug(fq_message_name) {
+                ""
+            } else {
+                ", Debug"
+            }
+        ));
+        self.push_indent();
+        self.buf
+            .push_str(&format!("pub enum {owned_name}Arena{lifetime} {{\n"));
+        self.depth += 1;
+        for field in &oneof.fields {
+            let mut ty = self.resolve_arena_type(&field.descriptor);
+            if self.context.should_box_oneof_field(
                 fq_message_name,
                 oneof.descriptor.name(),
                 &field.descriptor,
-            );
-
-            debug!(
-                "    oneof: {:?}, type: {:?}, boxed: {}",
-                field.descriptor.name(),
-                ty,
-                boxed
-            );
-
-            if boxed {
-                self.buf.push_str(&format!(
-                    "{}({}::alloc::boxed::Box<{}>),\n",
-                    to_upper_camel(field.descriptor.name()),
-                    self.context.prost_path(),
-                    ty
-                ));
-            } else {
-                self.buf.push_str(&format!(
-                    "{}({}),\n",
-                    to_upper_camel(field.descriptor.name()),
-                    ty
-                ));
+            ) {
+                ty = format!("&'bump mut {ty}");
             }
+            self.push_indent();
+            self.buf.push_str(&format!(
+                "{}({ty}),\n",
+                to_upper_camel(field.descriptor.name())
+            ));
         }
         self.depth -= 1;
-        self.path.pop();
-
         self.push_indent();
         self.buf.push_str("}\n");
     }
@@ -728,12 +3215,43 @@ impl<'b> CodeGenerator<'_, 'b> {
 
     fn append_doc(&mut self, fq_name: &str, field_name: Option<&str>) {
         if !self.context.should_disable_comments(fq_name, field_name) {
-            if let Some(comments) = self.location().map(Comments::from_location) {
+            if let Some(mut comments) = self.location().map(Comments::from_location) {
+                self.resolve_doc_links(&mut comments, fq_name);
                 comments.append_with_indent(self.depth, self.buf);
             }
         }
     }
 
+    /// Converts the links to Protobuf types in the comments of the item named `scope` into
+    /// intra-doc links, if enabled.
+    fn resolve_doc_links(&self, comments: &mut Comments, scope: &str) {
+        if self.context.config().intra_doc_links {
+            comments.resolve_links(|name| {
+                self.resolve_type_name(name, scope)
+                    .map(|fq_name| self.resolve_ident(&fq_name))
+            });
+        }
+    }
+
+    /// Returns the fully-qualified name of the message or enum type `name`, which is looked up
+    /// from `scope` outwards following the Protobuf scoping rules.
+    fn resolve_type_name(&self, name: &str, scope: &str) -> Option<String> {
+        if name.starts_with('.') {
+            return self.context.contains_type(name).then(|| name.to_string());
+        }
+        let mut scope = scope;
+        loop {
+            let fq_name = format!("{scope}.{name}");
+            if self.context.contains_type(&fq_name) {
+                return Some(fq_name);
+            }
+            if scope.is_empty() {
+                return None;
+            }
+            scope = scope.rsplit_once('.').map_or("", |(parent, _)| parent);
+        }
+    }
+
     fn append_enum(&mut self, desc: EnumDescriptorProto) {
         debug!("  enum: {:?}", desc.name());
 
@@ -754,6 +3272,10 @@ impl<'b> CodeGenerator<'_, 'b> {
         self.append_doc(&fq_proto_enum_name, None);
         self.append_type_attributes(&fq_proto_enum_name);
         self.append_enum_attributes(&fq_proto_enum_name);
+        let attributes = self
+            .context
+            .provided_enum_attributes(&fq_proto_enum_name, &desc);
+        self.append_provided_attributes(attributes);
         self.push_indent();
         let dbg = if self.context.should_skip_debug(&fq_proto_enum_name) {
             ""
@@ -766,6 +3288,9 @@ impl<'b> CodeGenerator<'_, 'b> {
             self.context.prost_path(),
         ));
         self.append_prost_path_attribute();
+        self.append_arbitrary(&fq_proto_enum_name);
+        self.append_serde(false);
+        self.append_non_exhaustive(&fq_proto_enum_name);
         self.push_indent();
         self.buf.push_str("#[repr(i32)]\n");
         self.push_indent();
@@ -782,7 +3307,13 @@ impl<'b> CodeGenerator<'_, 'b> {
             self.path.push(variant.path_idx as i32);
 
             self.append_doc(&fq_proto_enum_name, Some(variant.proto_name));
+            if self.config().enable_serde {
+                self.push_indent();
+                self.buf
+                    .push_str(&format!("#[serde(rename = \"{}\")]\n", variant.proto_name));
+            }
             self.append_field_attributes(&fq_proto_enum_name, variant.proto_name);
+            self.append_variant_attributes(&fq_proto_enum_name, variant.proto_name);
             if variant.deprecated {
                 self.push_indent();
                 self.buf.push_str("#[deprecated]\n");
@@ -867,16 +3398,18 @@ impl<'b> CodeGenerator<'_, 'b> {
         self.depth += 1;
 
         for variant in variant_mappings.iter() {
-            self.push_indent();
-            self.buf.push('\"');
-            self.buf.push_str(variant.proto_name);
-            self.buf.push_str("\" => Some(");
-            if variant.deprecated {
-                self.buf.push_str("#[allow(deprecated)] ");
+            for name in iter::once(variant.proto_name).chain(variant.aliases.iter().copied()) {
+                self.push_indent();
+                self.buf.push('\"');
+                self.buf.push_str(name);
+                self.buf.push_str("\" => Some(");
+                if variant.deprecated {
+                    self.buf.push_str("#[allow(deprecated)] ");
+                }
+                self.buf.push_str("Self::");
+                self.buf.push_str(&variant.generated_variant_name);
+                self.buf.push_str("),\n");
             }
-            self.buf.push_str("Self::");
-            self.buf.push_str(&variant.generated_variant_name);
-            self.buf.push_str("),\n");
         }
         self.push_indent();
         self.buf.push_str("_ => None,\n");
@@ -889,20 +3422,96 @@ impl<'b> CodeGenerator<'_, 'b> {
         self.push_indent();
         self.buf.push_str("}\n"); // End of from_str_name()
 
+        let ignore_case = self.context.should_ignore_enum_case(&fq_proto_enum_name);
+        if ignore_case {
+            self.append_enum_from_str_name_ignore_case(&variant_mappings);
+        }
+
         self.path.pop();
         self.depth -= 1;
         self.push_indent();
         self.buf.push_str("}\n"); // End of impl
+
+        if self
+            .context
+            .should_generate_enum_str_impls(&fq_proto_enum_name)
+        {
+            self.append_enum_str_impls(&enum_name, ignore_case);
+        }
+    }
+
+    fn append_enum_from_str_name_ignore_case(&mut self, variant_mappings: &[EnumVariantMapping]) {
+        self.push_indent();
+        self.buf.push_str(
+            "/// Creates an enum from field names used in the ProtoBuf definition, without regard to\n",
+        );
+        self.push_indent();
+        self.buf.push_str("/// ASCII case.\n");
+        self.push_indent();
+        self.buf.push_str(
+            "pub fn from_str_name_ignore_case(value: &str) -> ::core::option::Option<Self> {\n",
+        );
+        self.depth += 1;
+
+        for variant in variant_mappings {
+            for name in iter::once(variant.proto_name).chain(variant.aliases.iter().copied()) {
+                self.push_indent();
+                self.buf.push_str(&format!(
+                    "if value.eq_ignore_ascii_case(\"{name}\") {{ return Some({}Self::{}); }}\n",
+                    if variant.deprecated {
+                        "#[allow(deprecated)] "
+                    } else {
+                        ""
+                    },
+                    variant.generated_variant_name
+                ));
+            }
+        }
+        self.push_indent();
+        self.buf.push_str("None\n");
+
+        self.depth -= 1;
+        self.push_indent();
+        self.buf.push_str("}\n"); // End of from_str_name_ignore_case()
+    }
+
+    fn append_enum_str_impls(&mut self, enum_name: &str, ignore_case: bool) {
+        let prost_path = self.context.prost_path().to_string();
+        let from_str_name = if ignore_case {
+            "from_str_name_ignore_case"
+        } else {
+            "from_str_name"
+        };
+
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "impl ::core::fmt::Display for {enum_name} {{\n\
+             fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{\n\
+             f.write_str(self.as_str_name())\n\
+             }}\n\
+             }}\n"
+        ));
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "impl ::core::str::FromStr for {enum_name} {{\n\
+             type Err = {prost_path}::UnknownEnumName;\n\
+             fn from_str(value: &str) -> ::core::result::Result<Self, Self::Err> {{\n\
+             Self::{from_str_name}(value).ok_or_else(|| {prost_path}::UnknownEnumName(::core::convert::Into::into(value)))\n\
+             }}\n\
+             }}\n"
+        ));
     }
 
     fn push_service(&mut self, service: ServiceDescriptorProto) {
         let name = service.name().to_owned();
         debug!("  service: {name:?}");
 
-        let comments = self
+        let fq_service_name = self.fq_name(&name);
+        let mut comments = self
             .location()
             .map(Comments::from_location)
             .unwrap_or_default();
+        self.resolve_doc_links(&mut comments, &fq_service_name);
 
         self.path.push(2);
         let methods = service
@@ -913,10 +3522,11 @@ impl<'b> CodeGenerator<'_, 'b> {
                 debug!("  method: {:?}", method.name());
 
                 self.path.push(idx as i32);
-                let comments = self
+                let mut comments = self
                     .location()
                     .map(Comments::from_location)
                     .unwrap_or_default();
+                self.resolve_doc_links(&mut comments, &fq_service_name);
                 self.path.pop();
 
                 let name = method.name.take().unwrap();
@@ -995,17 +3605,264 @@ impl<'b> CodeGenerator<'_, 'b> {
             Type::Int32 | Type::Sfixed32 | Type::Sint32 | Type::Enum => String::from("i32"),
             Type::Int64 | Type::Sfixed64 | Type::Sint64 => String::from("i64"),
             Type::Bool => String::from("bool"),
-            Type::String => format!("{}::alloc::string::String", self.context.prost_path()),
+            Type::String if self.context.should_use_heapless(fq_message_name) => format!(
+                "{}::heapless::String<{}>",
+                self.context.prost_path(),
+                heapless::max_len(fq_message_name, field)
+            ),
+            Type::Bytes if self.context.should_use_heapless(fq_message_name) => format!(
+                "{}::heapless::Vec<u8, {}>",
+                self.context.prost_path(),
+                heapless::max_len(fq_message_name, field)
+            ),
+            Type::String => match self.context.string_type(fq_message_name, field.name()) {
+                StringType::String => {
+                    format!("{}::alloc::string::String", self.context.prost_path())
+                }
+                StringType::Utf8Bytes => format!("{}::Utf8Bytes", self.context.prost_path()),
+                StringType::Custom(path) => path,
+            },
             Type::Bytes => match self.context.bytes_type(fq_message_name, field.name()) {
                 crate::BytesType::Vec => {
                     format!("{}::alloc::vec::Vec<u8>", self.context.prost_path())
                 }
                 crate::BytesType::Bytes => format!("{}::bytes::Bytes", self.context.prost_path()),
+                crate::BytesType::Custom(path) => path,
             },
             Type::Group | Type::Message => self.resolve_ident(field.type_name()),
         }
     }
 
+    /// Resolves the type of a message field outside of a oneof, which is replaced by the type
+    /// configured with `Config::message_type`, if any, or wrapped in an `OpenEnum` for
+    /// enumeration fields configured with `Config::open_enums`.
+    fn resolve_field_type(&self, field: &FieldDescriptorProto, fq_message_name: &str) -> String {
+        if let Some(rust_type) = self.context.message_type(fq_message_name, field.name()) {
+            return rust_type.to_string();
+        }
+        if self.context.should_use_open_enum(fq_message_name, field) {
+            return format!(
+                "{}::OpenEnum<{}>",
+                self.context.prost_path(),
+                self.resolve_ident(field.type_name())
+            );
+        }
+        self.resolve_type(field, fq_message_name)
+    }
+
+    /// Resolves the type of a field, map key, or map value in a borrowed view.
+    /// Returns the name of the enum variant of the default value `default` of an enum field.
+    fn enum_default_variant(&self, field: &FieldDescriptorProto, default: &str) -> String {
+        let enum_value = to_upper_camel(default);
+        if !self.config().strip_enum_prefix {
+            return enum_value;
+        }
+        // Field types are fully qualified, so we extract
+        // the last segment and strip it from the left
+        // side of the default value.
+        let enum_type = field
+            .type_name
+            .as_ref()
+            .and_then(|ty| ty.split('.').next_back())
+            .unwrap();
+
+        strip_enum_prefix(&to_upper_camel(enum_type), &enum_value)
+    }
+
+    /// Returns the getters of the optional scalar fields of a borrowed view or an arena message,
+    /// which return the value of the field, or its default value if the field is unset.
+    ///
+    /// These match the getters which `#[derive(Message)]` generates for owned messages.
+    fn default_getters(&self, fq_message_name: &str, fields: &[Field]) -> Vec<String> {
+        let mut getters = Vec::new();
+        for field in fields {
+            let descriptor = &field.descriptor;
+            if descriptor.label() == Label::Repeated
+                || is_message(descriptor)
+                || !self.optional(fq_message_name, descriptor)
+            {
+                continue;
+            }
+
+            let name = field.rust_name();
+            let doc_name = name.trim_start_matches("r#");
+            let default = descriptor.default_value.as_deref();
+            let (ty, value) = match descriptor.r#type() {
+                Type::Enum => {
+                    let ty = self.resolve_ident(descriptor.type_name());
+                    let default = match default {
+                        Some(default) => {
+                            format!("{ty}::{}", self.enum_default_variant(descriptor, default))
+                        }
+                        None => "::core::default::Default::default()".to_string(),
+                    };
+                    let value = format!(
+                        "self.{name}.and_then(|value| <{ty} as ::core::convert::TryFrom<i32>>::try_from(value).ok()).unwrap_or({default})"
+                    );
+                    (ty, value)
+                }
+                Type::String => {
+                    let default = format!("{:?}", default.unwrap_or_default());
+                    (
+                        "&str".to_string(),
+                        format!("self.{name}.unwrap_or({default})"),
+                    )
+                }
+                Type::Bytes => {
+                    let default = default.map(unescape_c_escape_string).unwrap_or_default();
+                    let default: String = default
+                        .into_iter()
+                        .flat_map(ascii::escape_default)
+                        .map(char::from)
+                        .collect();
+                    (
+                        "&[u8]".to_string(),
+                        format!("self.{name}.unwrap_or(&b\"{default}\"[..])"),
+                    )
+                }
+                _ => {
+                    let ty = self.resolve_type(descriptor, fq_message_name);
+                    let default = match default {
+                        Some("inf") => format!("{ty}::INFINITY"),
+                        Some("-inf") => format!("{ty}::NEG_INFINITY"),
+                        Some("nan") => format!("{ty}::NAN"),
+                        Some(default) if ty == "f32" || ty == "f64" => format!("{default}_{ty}"),
+                        Some(default) => default.to_string(),
+                        None => "::core::default::Default::default()".to_string(),
+                    };
+                    let value = format!("self.{name}.unwrap_or({default})");
+                    (ty, value)
+                }
+            };
+            getters.push(format!(
+                "/// Returns the value of `{doc_name}`, or the default value if `{doc_name}` is unset.\n\
+                 pub fn {name}(&self) -> {ty} {{ {value} }}\n"
+            ));
+        }
+        getters
+    }
+
+    fn resolve_ref_type(&self, field: &FieldDescriptorProto) -> String {
+        match field.r#type() {
+            Type::String => String::from("&'a str"),
+            Type::Bytes => String::from("&'a [u8]"),
+            Type::Group | Type::Message => {
+                format!("{}Ref<'a>", self.resolve_ident(field.type_name()))
+            }
+            // Other types do not depend on the configuration of the field.
+            _ => self.resolve_type(field, ""),
+        }
+    }
+
+    /// Returns the path of the module with the functions decoding a field of a borrowed view.
+    fn ref_codec(&self, field: &FieldDescriptorProto) -> String {
+        let prost_path = self.context.prost_path();
+        match field.r#type() {
+            Type::String => format!("{prost_path}::encoding::borrowed::str"),
+            Type::Bytes => format!("{prost_path}::encoding::borrowed::bytes"),
+            Type::Message => format!("{prost_path}::encoding::borrowed::message"),
+            Type::Group => format!("{prost_path}::encoding::borrowed::group"),
+            Type::Enum => format!("{prost_path}::encoding::int32"),
+            _ => format!("{prost_path}::encoding::{}", self.field_type_tag(field)),
+        }
+    }
+
+    /// Resolves the type of a field, map key, or map value in an arena message.
+    fn resolve_arena_type(&self, field: &FieldDescriptorProto) -> String {
+        match field.r#type() {
+            Type::String => String::from("&'bump str"),
+            Type::Bytes => String::from("&'bump [u8]"),
+            Type::Group | Type::Message => {
+                format!("{}Arena<'bump>", self.resolve_ident(field.type_name()))
+            }
+            // Other types do not depend on the configuration of the field.
+            _ => self.resolve_type(field, ""),
+        }
+    }
+
+    /// Returns the path of the module with the functions decoding a field of an arena message,
+    /// and the `arena` argument those functions take, if any.
+    fn arena_codec(&self, field: &FieldDescriptorProto) -> (String, &'static str) {
+        let prost_path = self.context.prost_path();
+        match field.r#type() {
+            Type::String => (format!("{prost_path}::encoding::arena::str"), "arena, "),
+            Type::Bytes => (format!("{prost_path}::encoding::arena::bytes"), "arena, "),
+            Type::Message => (format!("{prost_path}::encoding::arena::message"), "arena, "),
+            Type::Group => (format!("{prost_path}::encoding::arena::group"), "arena, "),
+            Type::Enum => (format!("{prost_path}::encoding::int32"), ""),
+            _ => (
+                format!("{prost_path}::encoding::{}", self.field_type_tag(field)),
+                "",
+            ),
+        }
+    }
+
+    /// Returns the function merging a map key or value of an arena message.
+    fn arena_map_merge(&self, field: &FieldDescriptorProto) -> String {
+        match self.arena_codec(field) {
+            (codec, "") => format!("{codec}::merge"),
+            (codec, _) => {
+                format!("|wire_type, value, buf, ctx| {codec}::merge(wire_type, value, buf, arena, ctx)")
+            }
+        }
+    }
+
+    /// Returns the path of the function converting a value of a borrowed view to the owned type,
+    /// or `None` if the value is copied.
+    ///
+    /// `str` slices are passed by value, message views by reference, and `[u8]` slices either way.
+    fn ref_conversion(
+        &self,
+        field: &FieldDescriptorProto,
+        fq_message_name: &str,
+        map_entry: bool,
+    ) -> Option<String> {
+        let prost_path = self.context.prost_path();
+        match field.r#type() {
+            Type::String => Some(
+                match self.context.string_type(fq_message_name, field.name()) {
+                    StringType::Utf8Bytes if !map_entry => {
+                        format!("{prost_path}::Utf8Bytes::copy_from_str")
+                    }
+                    StringType::Custom(_) if !map_entry => {
+                        String::from("::core::convert::From::from")
+                    }
+                    _ => format!("{prost_path}::alloc::string::String::from"),
+                },
+            ),
+            Type::Bytes => Some(
+                match self.context.bytes_type(fq_message_name, field.name()) {
+                    crate::BytesType::Vec => String::from("<[u8]>::to_vec"),
+                    crate::BytesType::Bytes => {
+                        format!("{prost_path}::bytes::Bytes::copy_from_slice")
+                    }
+                    crate::BytesType::Custom(_) => String::from("::core::convert::From::from"),
+                },
+            ),
+            Type::Group | Type::Message => {
+                Some(format!("{prost_path}::MessageRef::to_owned_message"))
+            }
+            Type::Enum
+                if !map_entry && self.context.should_use_open_enum(fq_message_name, field) =>
+            {
+                Some(String::from("::core::convert::From::from"))
+            }
+            _ => None,
+        }
+    }
+
+    /// Resolves the type of a map key or value. Map strings are always `String`s.
+    fn resolve_map_entry_type(
+        &self,
+        field: &FieldDescriptorProto,
+        fq_message_name: &str,
+    ) -> String {
+        match field.r#type() {
+            Type::String => format!("{}::alloc::string::String", self.context.prost_path()),
+            _ => self.resolve_type(field, fq_message_name),
+        }
+    }
+
     fn resolve_ident(&self, pb_ident: &str) -> String {
         // protoc should always give fully qualified identifiers.
         assert_eq!(".", &pb_ident[..1]);
@@ -1080,11 +3937,15 @@ impl<'b> CodeGenerator<'_, 'b> {
         }
     }
 
-    fn optional(&self, field: &FieldDescriptorProto) -> bool {
+    fn optional(&self, fq_message_name: &str, field: &FieldDescriptorProto) -> bool {
         if field.proto3_optional.unwrap_or(false) {
             return true;
         }
 
+        if field.label() == Label::Required {
+            return self.context.should_track_required_presence(fq_message_name);
+        }
+
         if field.label() != Label::Optional {
             return false;
         }
@@ -1100,6 +3961,15 @@ impl<'b> CodeGenerator<'_, 'b> {
         field.options.as_ref().is_some_and(FieldOptions::deprecated)
     }
 
+    /// Returns `true` if the field options includes the `debug_redact` option, which marks the
+    /// field as sensitive.
+    fn debug_redact(&self, field: &FieldDescriptorProto) -> bool {
+        field
+            .options
+            .as_ref()
+            .is_some_and(FieldOptions::debug_redact)
+    }
+
     /// Returns the fully-qualified name, starting with a dot
     fn fq_name(&self, message_name: &str) -> String {
         format!(
@@ -1113,6 +3983,66 @@ impl<'b> CodeGenerator<'_, 'b> {
     }
 }
 
+/// Returns `true` if the type of the field in a borrowed view borrows from the decoded buffer, or
+/// if the type of the field in an arena message is allocated in the arena.
+fn ref_borrows(field: &FieldDescriptorProto) -> bool {
+    matches!(
+        field.r#type(),
+        Type::String | Type::Bytes | Type::Message | Type::Group
+    )
+}
+
+/// Returns the JSON name `protoc` derives from a field name, for descriptors without one.
+fn to_json_name(field_name: &str) -> String {
+    let mut json_name = String::with_capacity(field_name.len());
+    let mut capitalize_next = false;
+    for c in field_name.chars() {
+        if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            json_name.push(c.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            json_name.push(c);
+        }
+    }
+    json_name
+}
+
+/// Returns the `json_name` entry of the `#[prost(...)]` attribute of a field whose JSON name is
+/// declared with the `json_name` option, or an empty string if it uses the derived name.
+fn json_name_attribute(field: &FieldDescriptorProto) -> String {
+    match field.json_name {
+        Some(ref json_name) if *json_name != to_json_name(field.name()) => {
+            format!(", json_name = {json_name:?}")
+        }
+        _ => String::new(),
+    }
+}
+
+/// Returns the name of the builder setter of a field, which must not clash with the other methods
+/// of the builder.
+fn builder_setter_name(field_name: &str) -> String {
+    match field_name {
+        "build" | "new" => format!("set_{field_name}"),
+        _ => field_name.to_string(),
+    }
+}
+
+/// Returns `true` if the field holds a message or a group.
+fn is_message(field: &FieldDescriptorProto) -> bool {
+    matches!(field.r#type(), Type::Message | Type::Group)
+}
+
+/// Returns the name of the `WireType` variant of an unpacked scalar value of the field.
+fn scalar_wire_type(field: &FieldDescriptorProto) -> &'static str {
+    match field.r#type() {
+        Type::Float | Type::Fixed32 | Type::Sfixed32 => "ThirtyTwoBit",
+        Type::Double | Type::Fixed64 | Type::Sfixed64 => "SixtyFourBit",
+        _ => "Varint",
+    }
+}
+
 /// Returns `true` if the repeated field type can be packed.
 fn can_pack(field: &FieldDescriptorProto) -> bool {
     matches!(
@@ -1140,6 +4070,8 @@ struct EnumVariantMapping<'a> {
     proto_number: i32,
     generated_variant_name: String,
     deprecated: bool,
+    /// The names of the values which are aliases of this value.
+    aliases: Vec<&'a str>,
 }
 
 fn build_enum_value_mappings<'a>(
@@ -1147,16 +4079,18 @@ fn build_enum_value_mappings<'a>(
     do_strip_enum_prefix: bool,
     enum_values: &'a [EnumValueDescriptorProto],
 ) -> Vec<EnumVariantMapping<'a>> {
-    let mut numbers = HashSet::new();
+    let mut numbers = HashMap::<i32, usize>::new();
     let mut generated_names = HashMap::new();
-    let mut mappings = Vec::new();
+    let mut mappings: Vec<EnumVariantMapping<'a>> = Vec::new();
 
     for (idx, value) in enum_values.iter().enumerate() {
         // Skip duplicate enum values. Protobuf allows this when the
         // 'allow_alias' option is set.
-        if !numbers.insert(value.number()) {
+        if let Some(&index) = numbers.get(&value.number()) {
+            mappings[index].aliases.push(value.name());
             continue;
         }
+        numbers.insert(value.number(), mappings.len());
 
         let mut generated_variant_name = to_upper_camel(value.name());
         if do_strip_enum_prefix {
@@ -1176,6 +4110,7 @@ fn build_enum_value_mappings<'a>(
             proto_number: value.number(),
             generated_variant_name,
             deprecated: enum_field_deprecated(value),
+            aliases: Vec::new(),
         })
     }
     mappings
@@ -1187,3 +4122,22 @@ fn enum_field_deprecated(value: &EnumValueDescriptorProto) -> bool {
         .as_ref()
         .is_some_and(EnumValueOptions::deprecated)
 }
+
+/// Returns expressions which are `true` if the value of a scalar field, bound to `value`, is its
+/// zero value, and if it is not.
+fn zero_value(field: &FieldDescriptorProto) -> Option<(&'static str, &'static str)> {
+    match field.r#type() {
+        Type::String => Some((
+            "::core::convert::AsRef::<str>::as_ref(value).is_empty()",
+            "!::core::convert::AsRef::<str>::as_ref(value).is_empty()",
+        )),
+        Type::Bytes => Some((
+            "::core::convert::AsRef::<[u8]>::as_ref(value).is_empty()",
+            "!::core::convert::AsRef::<[u8]>::as_ref(value).is_empty()",
+        )),
+        Type::Bool => Some(("!*value", "*value")),
+        Type::Float | Type::Double => Some(("*value == 0.0", "*value != 0.0")),
+        Type::Group | Type::Message => None,
+        _ => Some(("*value == 0", "*value != 0")),
+    }
+}
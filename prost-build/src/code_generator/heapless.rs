@@ -0,0 +1,66 @@
+//! The capacities of the fields of heapless messages.
+//!
+//! The capacities are read from the `(prost.heapless)` option, which is declared in the
+//! `prost/heapless.proto` file bundled with `prost-build`.
+
+use prost::{Extension, Message};
+use prost_types::FieldDescriptorProto;
+
+const FIELD_OPTIONS: Extension<HeaplessFieldOptions> = Extension::new(
+    "google.protobuf.FieldOptions",
+    "prost.heapless",
+    50300,
+    prost::encoding::message::encode,
+    |_, wire_type, value, buf, ctx| prost::encoding::message::merge(wire_type, value, buf, ctx),
+);
+
+#[derive(Clone, PartialEq, Message)]
+struct HeaplessFieldOptions {
+    #[prost(uint32, optional, tag = "1")]
+    max_len: Option<u32>,
+    #[prost(uint32, optional, tag = "2")]
+    max_count: Option<u32>,
+}
+
+fn field_options(fq_message_name: &str, field: &FieldDescriptorProto) -> HeaplessFieldOptions {
+    field
+        .options
+        .as_ref()
+        .and_then(|options| options._extensions.get(&FIELD_OPTIONS).transpose())
+        .transpose()
+        .unwrap_or_else(|error| {
+            panic!(
+                "invalid (prost.heapless) option of field {} in {fq_message_name}: {error}",
+                field.name()
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the `max_len` capacity of a `string` or `bytes` field of a heapless message.
+///
+/// Panics if the capacity is not set.
+pub(super) fn max_len(fq_message_name: &str, field: &FieldDescriptorProto) -> u32 {
+    field_options(fq_message_name, field)
+        .max_len
+        .unwrap_or_else(|| {
+            panic!(
+                "field {} in heapless message {fq_message_name} requires a (prost.heapless).max_len option",
+                field.name()
+            )
+        })
+}
+
+/// Returns the `max_count` capacity of a repeated field of a heapless message.
+///
+/// Panics if the capacity is not set.
+pub(super) fn max_count(fq_message_name: &str, field: &FieldDescriptorProto) -> u32 {
+    field_options(fq_message_name, field)
+        .max_count
+        .unwrap_or_else(|| {
+            panic!(
+                "repeated field {} in heapless message {fq_message_name} requires a (prost.heapless).max_count option",
+                field.name()
+            )
+        })
+}
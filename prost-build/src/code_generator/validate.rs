@@ -0,0 +1,765 @@
+//! Code generation for the [protovalidate](https://protovalidate.com/) rules of fields.
+//!
+//! The rules are read from the `(buf.validate.field)` and `(buf.validate.oneof)` options, which
+//! `protoc` stores as extensions of the field and oneof options. Only the subset of the
+//! `buf/validate/validate.proto` messages needed by the supported rules is declared here.
+
+use std::fmt::{Debug, Display, Write};
+
+use prost::{Extension, Message, Oneof};
+use prost_types::field_descriptor_proto::Type;
+use prost_types::{FieldDescriptorProto, OneofDescriptorProto};
+
+/// The field number of the `buf.validate` extensions of the descriptor options.
+const EXTENSION_NUMBER: u32 = 1159;
+
+const FIELD_RULES: Extension<FieldRules> = Extension::new(
+    "google.protobuf.FieldOptions",
+    "buf.validate.field",
+    EXTENSION_NUMBER,
+    prost::encoding::message::encode,
+    |_, wire_type, value, buf, ctx| prost::encoding::message::merge(wire_type, value, buf, ctx),
+);
+
+const ONEOF_RULES: Extension<OneofRules> = Extension::new(
+    "google.protobuf.OneofOptions",
+    "buf.validate.oneof",
+    EXTENSION_NUMBER,
+    prost::encoding::message::encode,
+    |_, wire_type, value, buf, ctx| prost::encoding::message::merge(wire_type, value, buf, ctx),
+);
+
+/// The `IGNORE_IF_ZERO_VALUE` value of the `buf.validate.Ignore` enum.
+const IGNORE_IF_ZERO_VALUE: i32 = 1;
+/// The `IGNORE_ALWAYS` value of the `buf.validate.Ignore` enum.
+const IGNORE_ALWAYS: i32 = 3;
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct FieldRules {
+    #[prost(bool, optional, tag = "25")]
+    pub required: Option<bool>,
+    #[prost(int32, optional, tag = "27")]
+    pub ignore: Option<i32>,
+    #[prost(
+        oneof = "TypeRules",
+        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 18, 19"
+    )]
+    pub r#type: Option<TypeRules>,
+}
+
+#[derive(Clone, PartialEq, Oneof)]
+pub(super) enum TypeRules {
+    #[prost(message, tag = "1")]
+    Float(FloatRules),
+    #[prost(message, tag = "2")]
+    Double(DoubleRules),
+    #[prost(message, tag = "3")]
+    Int32(Int32Rules),
+    #[prost(message, tag = "4")]
+    Int64(Int64Rules),
+    #[prost(message, tag = "5")]
+    Uint32(UInt32Rules),
+    #[prost(message, tag = "6")]
+    Uint64(UInt64Rules),
+    #[prost(message, tag = "7")]
+    Sint32(SInt32Rules),
+    #[prost(message, tag = "8")]
+    Sint64(SInt64Rules),
+    #[prost(message, tag = "9")]
+    Fixed32(Fixed32Rules),
+    #[prost(message, tag = "10")]
+    Fixed64(Fixed64Rules),
+    #[prost(message, tag = "11")]
+    Sfixed32(SFixed32Rules),
+    #[prost(message, tag = "12")]
+    Sfixed64(SFixed64Rules),
+    #[prost(message, tag = "13")]
+    Bool(BoolRules),
+    #[prost(message, tag = "14")]
+    String(StringRules),
+    #[prost(message, tag = "15")]
+    Bytes(BytesRules),
+    #[prost(message, tag = "16")]
+    Enum(EnumRules),
+    #[prost(message, tag = "18")]
+    Repeated(Box<RepeatedRules>),
+    #[prost(message, tag = "19")]
+    Map(Box<MapRules>),
+}
+
+/// The range and list rules shared by the numeric types.
+struct Bounds<T> {
+    r#const: Option<T>,
+    lt: Option<T>,
+    lte: Option<T>,
+    gt: Option<T>,
+    gte: Option<T>,
+    r#in: Vec<T>,
+    not_in: Vec<T>,
+}
+
+macro_rules! numeric_rules {
+    ($name:ident, $ty:ty, $kind:ident) => {
+        #[derive(Clone, PartialEq, Message)]
+        pub(super) struct $name {
+            #[prost($kind, optional, tag = "1")]
+            r#const: Option<$ty>,
+            #[prost($kind, optional, tag = "2")]
+            lt: Option<$ty>,
+            #[prost($kind, optional, tag = "3")]
+            lte: Option<$ty>,
+            #[prost($kind, optional, tag = "4")]
+            gt: Option<$ty>,
+            #[prost($kind, optional, tag = "5")]
+            gte: Option<$ty>,
+            #[prost($kind, repeated, tag = "6")]
+            r#in: Vec<$ty>,
+            #[prost($kind, repeated, tag = "7")]
+            not_in: Vec<$ty>,
+        }
+
+        impl $name {
+            fn bounds(&self) -> Bounds<$ty> {
+                Bounds {
+                    r#const: self.r#const,
+                    lt: self.lt,
+                    lte: self.lte,
+                    gt: self.gt,
+                    gte: self.gte,
+                    r#in: self.r#in.clone(),
+                    not_in: self.not_in.clone(),
+                }
+            }
+        }
+    };
+}
+
+numeric_rules!(Int32Rules, i32, int32);
+numeric_rules!(Int64Rules, i64, int64);
+numeric_rules!(UInt32Rules, u32, uint32);
+numeric_rules!(UInt64Rules, u64, uint64);
+numeric_rules!(SInt32Rules, i32, sint32);
+numeric_rules!(SInt64Rules, i64, sint64);
+numeric_rules!(Fixed32Rules, u32, fixed32);
+numeric_rules!(Fixed64Rules, u64, fixed64);
+numeric_rules!(SFixed32Rules, i32, sfixed32);
+numeric_rules!(SFixed64Rules, i64, sfixed64);
+
+macro_rules! float_rules {
+    ($name:ident, $ty:ty, $kind:ident) => {
+        #[derive(Clone, PartialEq, Message)]
+        pub(super) struct $name {
+            #[prost($kind, optional, tag = "1")]
+            r#const: Option<$ty>,
+            #[prost($kind, optional, tag = "2")]
+            lt: Option<$ty>,
+            #[prost($kind, optional, tag = "3")]
+            lte: Option<$ty>,
+            #[prost($kind, optional, tag = "4")]
+            gt: Option<$ty>,
+            #[prost($kind, optional, tag = "5")]
+            gte: Option<$ty>,
+            #[prost($kind, repeated, tag = "6")]
+            r#in: Vec<$ty>,
+            #[prost($kind, repeated, tag = "7")]
+            not_in: Vec<$ty>,
+            #[prost(bool, optional, tag = "8")]
+            finite: Option<bool>,
+        }
+
+        impl $name {
+            fn bounds(&self) -> Bounds<$ty> {
+                Bounds {
+                    r#const: self.r#const,
+                    lt: self.lt,
+                    lte: self.lte,
+                    gt: self.gt,
+                    gte: self.gte,
+                    r#in: self.r#in.clone(),
+                    not_in: self.not_in.clone(),
+                }
+            }
+        }
+    };
+}
+
+float_rules!(FloatRules, f32, float);
+float_rules!(DoubleRules, f64, double);
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct BoolRules {
+    #[prost(bool, optional, tag = "1")]
+    r#const: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct StringRules {
+    #[prost(string, optional, tag = "1")]
+    r#const: Option<String>,
+    #[prost(uint64, optional, tag = "19")]
+    len: Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    min_len: Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    max_len: Option<u64>,
+    #[prost(uint64, optional, tag = "20")]
+    len_bytes: Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    min_bytes: Option<u64>,
+    #[prost(uint64, optional, tag = "5")]
+    max_bytes: Option<u64>,
+    #[prost(string, optional, tag = "6")]
+    pattern: Option<String>,
+    #[prost(string, optional, tag = "7")]
+    prefix: Option<String>,
+    #[prost(string, optional, tag = "8")]
+    suffix: Option<String>,
+    #[prost(string, optional, tag = "9")]
+    contains: Option<String>,
+    #[prost(string, optional, tag = "23")]
+    not_contains: Option<String>,
+    #[prost(string, repeated, tag = "10")]
+    r#in: Vec<String>,
+    #[prost(string, repeated, tag = "11")]
+    not_in: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct BytesRules {
+    #[prost(bytes, optional, tag = "1")]
+    r#const: Option<Vec<u8>>,
+    #[prost(uint64, optional, tag = "13")]
+    len: Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    min_len: Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    max_len: Option<u64>,
+    #[prost(bytes, optional, tag = "5")]
+    prefix: Option<Vec<u8>>,
+    #[prost(bytes, optional, tag = "6")]
+    suffix: Option<Vec<u8>>,
+    #[prost(bytes, optional, tag = "7")]
+    contains: Option<Vec<u8>>,
+    #[prost(bytes, repeated, tag = "8")]
+    r#in: Vec<Vec<u8>>,
+    #[prost(bytes, repeated, tag = "9")]
+    not_in: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct EnumRules {
+    #[prost(int32, optional, tag = "1")]
+    r#const: Option<i32>,
+    #[prost(bool, optional, tag = "2")]
+    defined_only: Option<bool>,
+    #[prost(int32, repeated, tag = "3")]
+    r#in: Vec<i32>,
+    #[prost(int32, repeated, tag = "4")]
+    not_in: Vec<i32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct RepeatedRules {
+    #[prost(uint64, optional, tag = "1")]
+    pub min_items: Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    pub max_items: Option<u64>,
+    #[prost(bool, optional, tag = "3")]
+    pub unique: Option<bool>,
+    #[prost(message, optional, tag = "4")]
+    pub items: Option<FieldRules>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct MapRules {
+    #[prost(uint64, optional, tag = "1")]
+    pub min_pairs: Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    pub max_pairs: Option<u64>,
+    #[prost(message, optional, tag = "4")]
+    pub keys: Option<FieldRules>,
+    #[prost(message, optional, tag = "5")]
+    pub values: Option<FieldRules>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct OneofRules {
+    #[prost(bool, optional, tag = "1")]
+    required: Option<bool>,
+}
+
+impl FieldRules {
+    /// Returns `true` if the field must be set.
+    pub fn is_required(&self) -> bool {
+        self.required()
+    }
+
+    /// Returns `true` if the rules of the field are never checked.
+    pub fn is_ignored(&self) -> bool {
+        self.ignore == Some(IGNORE_ALWAYS)
+    }
+
+    /// Returns `true` if the rules of the field are not checked when it has its zero value.
+    pub fn ignore_if_zero(&self) -> bool {
+        self.ignore == Some(IGNORE_IF_ZERO_VALUE)
+    }
+}
+
+/// Returns the `(buf.validate.field)` rules of a field.
+pub(super) fn field_rules(fq_message_name: &str, field: &FieldDescriptorProto) -> FieldRules {
+    field
+        .options
+        .as_ref()
+        .and_then(|options| options._extensions.get(&FIELD_RULES).transpose())
+        .transpose()
+        .unwrap_or_else(|error| {
+            panic!(
+                "invalid (buf.validate.field) option of field {} in {fq_message_name}: {error}",
+                field.name()
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Returns `true` if the `(buf.validate.oneof).required` option of a oneof is set.
+pub(super) fn is_oneof_required(oneof: &OneofDescriptorProto) -> bool {
+    oneof
+        .options
+        .as_ref()
+        .and_then(|options| options._extensions.get(&ONEOF_RULES).ok().flatten())
+        .is_some_and(|rules| rules.required())
+}
+
+/// Generates the checks of the type rules of a value.
+///
+/// The code expects a `value` reference to the Rust value of the field, and a `violations`
+/// collector. `path` is an expression of the field path of the value. Panics if the rules do not
+/// match the type of the field.
+pub(super) fn value_checks(
+    rules: &FieldRules,
+    field: &FieldDescriptorProto,
+    field_name: &str,
+    enum_type: Option<&str>,
+    prost_path: &str,
+    path: &str,
+) -> String {
+    let Some(type_rules) = &rules.r#type else {
+        return String::new();
+    };
+    let mut checks = Checks {
+        out: String::new(),
+        path,
+    };
+    match (type_rules, field.r#type()) {
+        (TypeRules::Float(rules), Type::Float) => {
+            checks.numeric("float", &rules.bounds(), float_literal("f32"));
+            checks.finite("float", rules.finite());
+        }
+        (TypeRules::Double(rules), Type::Double) => {
+            checks.numeric("double", &rules.bounds(), float_literal("f64"));
+            checks.finite("double", rules.finite());
+        }
+        (TypeRules::Int32(rules), Type::Int32) => checks.integer("int32", &rules.bounds()),
+        (TypeRules::Int64(rules), Type::Int64) => checks.integer("int64", &rules.bounds()),
+        (TypeRules::Uint32(rules), Type::Uint32) => checks.integer("uint32", &rules.bounds()),
+        (TypeRules::Uint64(rules), Type::Uint64) => checks.integer("uint64", &rules.bounds()),
+        (TypeRules::Sint32(rules), Type::Sint32) => checks.integer("sint32", &rules.bounds()),
+        (TypeRules::Sint64(rules), Type::Sint64) => checks.integer("sint64", &rules.bounds()),
+        (TypeRules::Fixed32(rules), Type::Fixed32) => checks.integer("fixed32", &rules.bounds()),
+        (TypeRules::Fixed64(rules), Type::Fixed64) => checks.integer("fixed64", &rules.bounds()),
+        (TypeRules::Sfixed32(rules), Type::Sfixed32) => checks.integer("sfixed32", &rules.bounds()),
+        (TypeRules::Sfixed64(rules), Type::Sfixed64) => checks.integer("sfixed64", &rules.bounds()),
+        (TypeRules::Bool(rules), Type::Bool) => {
+            if let Some(value) = rules.r#const {
+                checks.check(
+                    if value { "!*value" } else { "*value" },
+                    "bool.const",
+                    &format!("must equal {value}"),
+                );
+            }
+        }
+        (TypeRules::String(rules), Type::String) => checks.string(rules, prost_path),
+        (TypeRules::Bytes(rules), Type::Bytes) => checks.bytes(rules),
+        (TypeRules::Enum(rules), Type::Enum) => checks.enumeration(rules, enum_type),
+        (_, ty) => panic!(
+            "the (buf.validate.field) rules of field {field_name} do not apply to its type {}",
+            ty.as_str_name()
+        ),
+    }
+    checks.out
+}
+
+struct Checks<'a> {
+    out: String,
+    path: &'a str,
+}
+
+impl Checks<'_> {
+    fn check(&mut self, violated: &str, rule_id: &str, message: &str) {
+        writeln!(
+            self.out,
+            "if {violated} {{ violations.push({}, {rule_id:?}, {message:?}); }}",
+            self.path
+        )
+        .unwrap();
+    }
+
+    fn integer<T: Copy + Display + PartialOrd>(&mut self, kind: &str, bounds: &Bounds<T>) {
+        self.numeric(kind, bounds, |value| value.to_string());
+    }
+
+    fn numeric<T: Copy + Display + PartialOrd>(
+        &mut self,
+        kind: &str,
+        bounds: &Bounds<T>,
+        literal: impl Fn(T) -> String,
+    ) {
+        let float = kind == "float" || kind == "double";
+        if let Some(value) = bounds.r#const {
+            self.check(
+                &format!("*value != {}", literal(value)),
+                &format!("{kind}.const"),
+                &format!("must equal {value}"),
+            );
+        }
+
+        // The conditions under which a bound is violated, its rule name, and its description.
+        let upper = match (bounds.lt, bounds.lte) {
+            (Some(lt), _) => Some((format!("*value >= {}", literal(lt)), "lt", lt, "less than")),
+            (None, Some(lte)) => Some((
+                format!("*value > {}", literal(lte)),
+                "lte",
+                lte,
+                "less than or equal to",
+            )),
+            (None, None) => None,
+        };
+        let lower = match (bounds.gt, bounds.gte) {
+            (Some(gt), _) => Some((
+                format!("*value <= {}", literal(gt)),
+                "gt",
+                gt,
+                "greater than",
+            )),
+            (None, Some(gte)) => Some((
+                format!("*value < {}", literal(gte)),
+                "gte",
+                gte,
+                "greater than or equal to",
+            )),
+            (None, None) => None,
+        };
+        let nan = if float { "value.is_nan() || " } else { "" };
+        match (lower, upper) {
+            (Some((violated, rule, bound, description)), None)
+            | (None, Some((violated, rule, bound, description))) => self.check(
+                &format!("{nan}{violated}"),
+                &format!("{kind}.{rule}"),
+                &format!("must be {description} {bound}"),
+            ),
+            (Some(lower), Some(upper)) => {
+                // An upper bound below the lower bound excludes the range between the bounds.
+                let exclusive = upper.2 < lower.2;
+                let (operator, conjunction, suffix) = if exclusive {
+                    ("&&", "or", "_exclusive")
+                } else {
+                    ("||", "and", "")
+                };
+                let violated = format!("{} {operator} {}", lower.0, upper.0);
+                self.check(
+                    &if float {
+                        format!("{nan}({violated})")
+                    } else {
+                        violated
+                    },
+                    &format!("{kind}.{}_{}{suffix}", lower.1, upper.1),
+                    &format!(
+                        "must be {} {} {conjunction} {} {}",
+                        lower.3, lower.2, upper.3, upper.2
+                    ),
+                );
+            }
+            (None, None) => (),
+        }
+
+        if !bounds.r#in.is_empty() {
+            self.check(
+                &format!(
+                    "![{}].contains(value)",
+                    list(&bounds.r#in, |value| literal(*value))
+                ),
+                &format!("{kind}.in"),
+                &format!("must be in list [{}]", list(&bounds.r#in, T::to_string)),
+            );
+        }
+        if !bounds.not_in.is_empty() {
+            self.check(
+                &format!(
+                    "[{}].contains(value)",
+                    list(&bounds.not_in, |value| literal(*value))
+                ),
+                &format!("{kind}.not_in"),
+                &format!(
+                    "must not be in list [{}]",
+                    list(&bounds.not_in, T::to_string)
+                ),
+            );
+        }
+    }
+
+    fn finite(&mut self, kind: &str, finite: bool) {
+        if finite {
+            self.check(
+                "!value.is_finite()",
+                &format!("{kind}.finite"),
+                "must be finite",
+            );
+        }
+    }
+
+    fn string(&mut self, rules: &StringRules, prost_path: &str) {
+        self.out
+            .push_str("let value: &str = ::core::convert::AsRef::as_ref(value);\n");
+        if let Some(value) = &rules.r#const {
+            self.check(
+                &format!("value != {value:?}"),
+                "string.const",
+                &format!("must equal `{value}`"),
+            );
+        }
+        let lengths = [
+            (rules.len, "!=", "len", "", "characters"),
+            (rules.min_len, "<", "min_len", "at least ", "characters"),
+            (rules.max_len, ">", "max_len", "at most ", "characters"),
+            (rules.len_bytes, "!=", "len_bytes", "", "bytes"),
+            (rules.min_bytes, "<", "min_bytes", "at least ", "bytes"),
+            (rules.max_bytes, ">", "max_bytes", "at most ", "bytes"),
+        ];
+        for (len, operator, rule, qualifier, unit) in lengths {
+            if let Some(len) = len {
+                let value_len = if unit == "bytes" {
+                    "value.len()"
+                } else {
+                    "value.chars().count()"
+                };
+                self.check(
+                    &format!("({value_len} as u64) {operator} {len}"),
+                    &format!("string.{rule}"),
+                    &format!("must be {qualifier}{len} {unit}"),
+                );
+            }
+        }
+        if let Some(pattern) = &rules.pattern {
+            if let Err(error) = regex::Regex::new(pattern) {
+                panic!("invalid (buf.validate.field).string.pattern {pattern:?}: {error}");
+            }
+            writeln!(
+                self.out,
+                "static PATTERN: {prost_path}::validate::Pattern = {prost_path}::validate::Pattern::new({pattern:?});"
+            )
+            .unwrap();
+            self.check(
+                "!PATTERN.is_match(value)",
+                "string.pattern",
+                &format!("does not match regex pattern `{pattern}`"),
+            );
+        }
+        let affixes = [
+            (
+                &rules.prefix,
+                "!value.starts_with",
+                "prefix",
+                "does not have prefix",
+            ),
+            (
+                &rules.suffix,
+                "!value.ends_with",
+                "suffix",
+                "does not have suffix",
+            ),
+            (
+                &rules.contains,
+                "!value.contains",
+                "contains",
+                "does not contain substring",
+            ),
+            (
+                &rules.not_contains,
+                "value.contains",
+                "not_contains",
+                "contains substring",
+            ),
+        ];
+        for (affix, violated, rule, description) in affixes {
+            if let Some(affix) = affix {
+                self.check(
+                    &format!("{violated}({affix:?})"),
+                    &format!("string.{rule}"),
+                    &format!("{description} `{affix}`"),
+                );
+            }
+        }
+        let string_list = |values: &[String]| list(values, |value| format!("{value:?}"));
+        if !rules.r#in.is_empty() {
+            self.check(
+                &format!("![{}].contains(&value)", string_list(&rules.r#in)),
+                "string.in",
+                &format!("must be in list [{}]", rules.r#in.join(", ")),
+            );
+        }
+        if !rules.not_in.is_empty() {
+            self.check(
+                &format!("[{}].contains(&value)", string_list(&rules.not_in)),
+                "string.not_in",
+                &format!("must not be in list [{}]", rules.not_in.join(", ")),
+            );
+        }
+    }
+
+    fn bytes(&mut self, rules: &BytesRules) {
+        self.out
+            .push_str("let value: &[u8] = ::core::convert::AsRef::as_ref(value);\n");
+        if let Some(value) = &rules.r#const {
+            self.check(
+                &format!("value != {}", bytes_literal(value)),
+                "bytes.const",
+                &format!("must be {}", hex(value)),
+            );
+        }
+        let lengths = [
+            (rules.len, "!=", "len", ""),
+            (rules.min_len, "<", "min_len", "at least "),
+            (rules.max_len, ">", "max_len", "at most "),
+        ];
+        for (len, operator, rule, qualifier) in lengths {
+            if let Some(len) = len {
+                self.check(
+                    &format!("(value.len() as u64) {operator} {len}"),
+                    &format!("bytes.{rule}"),
+                    &format!("must be {qualifier}{len} bytes"),
+                );
+            }
+        }
+        if let Some(prefix) = &rules.prefix {
+            self.check(
+                &format!("!value.starts_with({})", bytes_literal(prefix)),
+                "bytes.prefix",
+                &format!("does not have prefix {}", hex(prefix)),
+            );
+        }
+        if let Some(suffix) = &rules.suffix {
+            self.check(
+                &format!("!value.ends_with({})", bytes_literal(suffix)),
+                "bytes.suffix",
+                &format!("does not have suffix {}", hex(suffix)),
+            );
+        }
+        if let Some(contains) = &rules.contains {
+            self.check(
+                &format!(
+                    "!value.windows({}).any(|window| window == {})",
+                    contains.len().max(1),
+                    bytes_literal(contains)
+                ),
+                "bytes.contains",
+                &format!("does not contain {}", hex(contains)),
+            );
+        }
+        if !rules.r#in.is_empty() {
+            self.check(
+                &format!(
+                    "![{}].contains(&value)",
+                    list(&rules.r#in, |value| bytes_literal(value))
+                ),
+                "bytes.in",
+                &format!(
+                    "must be in list [{}]",
+                    list(&rules.r#in, |value| hex(value))
+                ),
+            );
+        }
+        if !rules.not_in.is_empty() {
+            self.check(
+                &format!(
+                    "[{}].contains(&value)",
+                    list(&rules.not_in, |value| bytes_literal(value))
+                ),
+                "bytes.not_in",
+                &format!(
+                    "must not be in list [{}]",
+                    list(&rules.not_in, |value| hex(value))
+                ),
+            );
+        }
+    }
+
+    fn enumeration(&mut self, rules: &EnumRules, enum_type: Option<&str>) {
+        if let Some(value) = rules.r#const {
+            self.check(
+                &format!("*value != {value}"),
+                "enum.const",
+                &format!("must equal {value}"),
+            );
+        }
+        if rules.defined_only() {
+            let enum_type = enum_type.expect("enum type");
+            self.check(
+                &format!(
+                    "<{enum_type} as ::core::convert::TryFrom<i32>>::try_from(*value).is_err()"
+                ),
+                "enum.defined_only",
+                "must be one of the defined enum values",
+            );
+        }
+        if !rules.r#in.is_empty() {
+            self.check(
+                &format!("![{}].contains(value)", list(&rules.r#in, i32::to_string)),
+                "enum.in",
+                &format!("must be in list [{}]", list(&rules.r#in, i32::to_string)),
+            );
+        }
+        if !rules.not_in.is_empty() {
+            self.check(
+                &format!("[{}].contains(value)", list(&rules.not_in, i32::to_string)),
+                "enum.not_in",
+                &format!(
+                    "must not be in list [{}]",
+                    list(&rules.not_in, i32::to_string)
+                ),
+            );
+        }
+    }
+}
+
+fn list<T>(values: &[T], format: impl Fn(&T) -> String) -> String {
+    values.iter().map(format).collect::<Vec<_>>().join(", ")
+}
+
+/// Returns a function formatting float values as Rust literals of the type `ty`.
+fn float_literal<T: Copy + Debug + Into<f64>>(ty: &'static str) -> impl Fn(T) -> String {
+    move |value| {
+        let float: f64 = value.into();
+        if float.is_nan() {
+            format!("{ty}::NAN")
+        } else if float == f64::INFINITY {
+            format!("{ty}::INFINITY")
+        } else if float == f64::NEG_INFINITY {
+            format!("{ty}::NEG_INFINITY")
+        } else {
+            format!("{value:?}{ty}")
+        }
+    }
+}
+
+fn bytes_literal(value: &[u8]) -> String {
+    let mut literal = String::from("&b\"");
+    for byte in value {
+        write!(literal, "\\x{byte:02x}").unwrap();
+    }
+    literal.push_str("\"[..]");
+    literal
+}
+
+fn hex(value: &[u8]) -> String {
+    value.iter().map(|byte| format!("{byte:02x}")).collect()
+}
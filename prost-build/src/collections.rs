@@ -1,23 +1,40 @@
 /// The map collection type to output for Protobuf `map` fields.
 #[non_exhaustive]
-#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[derive(Default, Clone, Debug, PartialEq)]
 pub(crate) enum MapType {
     /// The [`std::collections::HashMap`] type.
     #[default]
     HashMap,
     /// The [`std::collections::BTreeMap`] type.
     BTreeMap,
+    /// A custom map type, given by its path.
+    Custom(String),
 }
 
 /// The bytes collection type to output for Protobuf `bytes` fields.
 #[non_exhaustive]
-#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[derive(Default, Clone, Debug, PartialEq)]
 pub(crate) enum BytesType {
     /// The [`prost::alloc::vec::Vec<u8>`] type.
     #[default]
     Vec,
     /// The [`bytes::Bytes`](prost::bytes::Bytes) type.
     Bytes,
+    /// A custom container implementing [`prost::BytesAdapter`], given by its path.
+    Custom(String),
+}
+
+/// The string type to output for Protobuf `string` fields.
+#[non_exhaustive]
+#[derive(Default, Clone, Debug, PartialEq)]
+pub(crate) enum StringType {
+    /// The [`prost::alloc::string::String`] type.
+    #[default]
+    String,
+    /// The [`prost::Utf8Bytes`] type.
+    Utf8Bytes,
+    /// A custom container implementing [`prost::StringAdapter`], given by its path.
+    Custom(String),
 }
 
 impl MapType {
@@ -26,16 +43,29 @@ impl MapType {
         match self {
             MapType::HashMap => "map",
             MapType::BTreeMap => "btree_map",
+            MapType::Custom(_) => "custom_map",
         }
     }
 }
 
 impl BytesType {
     /// The `prost-derive` annotation type corresponding to the bytes type.
-    pub fn annotation(&self) -> &'static str {
+    pub fn annotation(&self) -> &str {
         match self {
             BytesType::Vec => "vec",
             BytesType::Bytes => "bytes",
+            BytesType::Custom(path) => path,
+        }
+    }
+}
+
+impl StringType {
+    /// The `prost-derive` annotation type corresponding to the string type.
+    pub fn annotation(&self) -> &str {
+        match self {
+            StringType::String => "string",
+            StringType::Utf8Bytes => "bytes",
+            StringType::Custom(path) => path,
         }
     }
 }
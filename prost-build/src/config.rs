@@ -12,17 +12,67 @@ use log::debug;
 use log::trace;
 
 use prost::Message;
-use prost_types::{FileDescriptorProto, FileDescriptorSet};
+use prost_types::{DescriptorProto, FileDescriptorProto, FileDescriptorSet};
 
+use crate::cache::{self, CacheEntry, Digest};
 use crate::code_generator::CodeGenerator;
 use crate::context::Context;
+use crate::editions;
 use crate::extern_paths::ExternPaths;
+use crate::ident::{to_snake, to_upper_camel};
 use crate::message_graph::MessageGraph;
+use crate::native_parser;
 use crate::path::PathMap;
-use crate::BytesType;
+use crate::DeriveProvider;
 use crate::MapType;
 use crate::Module;
 use crate::ServiceGenerator;
+use crate::{BytesType, StringType};
+
+/// The `prost/heapless.proto` file, which declares the capacities of the fields of heapless
+/// messages.
+const HEAPLESS_PROTO: &str = include_str!("../proto/prost/heapless.proto");
+
+/// The code of each generated module, and the files of the messages split out of the modules, by
+/// their path relative to the output directory.
+type GeneratedModules = (HashMap<Module, String>, Vec<(String, String)>);
+
+/// How the generated code of each package is split into files, see [`Config::output_layout`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// A single file per package, named after the package, such as `foo.bar.rs`.
+    #[default]
+    FilePerPackage,
+    /// A file per package, which includes a file per top-level message of the package, such as
+    /// `foo.bar/Shirt.rs`.
+    FilePerMessage,
+}
+
+/// The pointer type which holds the messages of wrapped message fields, see
+/// [`Config::wrapper_type`] and [`Config::recursive_wrapper`].
+///
+/// There is no `Rc` wrapper, since messages must be `Send` and `Sync`.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Wrapper {
+    /// The owned [`Box`] type. Cloning the field clones its message.
+    #[default]
+    Box,
+    /// The shared [`Arc`](std::sync::Arc) type. Cloning the field only clones the pointer, and
+    /// modifying the message, such as by decoding into it, first copies it if it is shared.
+    Arc,
+}
+
+impl Wrapper {
+    /// The path of the wrapper type, below the `prost` crate at `prost_path`.
+    pub(crate) fn rust_type(self, prost_path: &str) -> String {
+        match self {
+            Wrapper::Box => format!("{prost_path}::alloc::boxed::Box"),
+            Wrapper::Arc => format!("{prost_path}::alloc::sync::Arc"),
+        }
+    }
+}
 
 /// Configuration options for Protobuf code generation.
 ///
@@ -30,27 +80,78 @@ use crate::ServiceGenerator;
 pub struct Config {
     pub(crate) file_descriptor_set_path: Option<PathBuf>,
     pub(crate) service_generator: Option<Box<dyn ServiceGenerator>>,
+    pub(crate) derive_providers: Vec<Box<dyn DeriveProvider>>,
     pub(crate) map_type: PathMap<MapType>,
+    pub(crate) custom_map_type: PathMap<String>,
+    pub(crate) message_types: PathMap<String>,
     pub(crate) bytes_type: PathMap<BytesType>,
+    pub(crate) custom_bytes_type: PathMap<String>,
+    pub(crate) string_type: PathMap<StringType>,
+    pub(crate) custom_string_type: PathMap<String>,
+    pub(crate) cow: PathMap<()>,
     pub(crate) type_attributes: PathMap<String>,
     pub(crate) message_attributes: PathMap<String>,
     pub(crate) enum_attributes: PathMap<String>,
+    pub(crate) oneof_attributes: PathMap<String>,
+    pub(crate) variant_attributes: PathMap<String>,
     pub(crate) field_attributes: PathMap<String>,
     pub(crate) boxed: PathMap<()>,
+    pub(crate) wrapper_types: PathMap<Wrapper>,
+    pub(crate) large_oneof_variant_size: Option<usize>,
+    pub(crate) box_recursive: bool,
+    pub(crate) recursive_wrapper: Wrapper,
+    pub(crate) lazy: PathMap<()>,
+    pub(crate) presence_bits: PathMap<()>,
+    pub(crate) open_enums: PathMap<()>,
+    pub(crate) enum_display_from_str: PathMap<()>,
+    pub(crate) enum_from_str_ignore_case: PathMap<()>,
+    pub(crate) derive_eq_hash: PathMap<()>,
+    pub(crate) derive_ord: PathMap<()>,
+    pub(crate) float_total_order: PathMap<()>,
+    pub(crate) proto_cmp: PathMap<()>,
     pub(crate) prost_types: bool,
     pub(crate) strip_enum_prefix: bool,
     pub(crate) out_dir: Option<PathBuf>,
     pub(crate) extern_paths: Vec<(String, String)>,
+    pub(crate) version_conversions: Vec<(String, String)>,
     pub(crate) default_package_filename: String,
+    pub(crate) output_layout: Layout,
     pub(crate) enable_type_names: bool,
+    pub(crate) enable_json: bool,
+    pub(crate) enable_serde: bool,
+    pub(crate) enable_text_format: bool,
+    pub(crate) text_format_debug: bool,
+    pub(crate) enable_field_masks: bool,
+    pub(crate) enable_reflection: bool,
+    pub(crate) generate_builders: bool,
+    pub(crate) generate_oneof_accessors: bool,
+    pub(crate) generate_conversions: bool,
+    pub(crate) generate_arbitrary: bool,
+    pub(crate) generate_field_numbers: bool,
+    pub(crate) embed_file_descriptor_sets: bool,
+    pub(crate) embed_message_descriptors: bool,
     pub(crate) type_name_domains: PathMap<String>,
     pub(crate) protoc_args: Vec<OsString>,
     pub(crate) protoc_executable: PathBuf,
     pub(crate) disable_comments: PathMap<()>,
+    pub(crate) intra_doc_links: bool,
     pub(crate) skip_debug: PathMap<()>,
+    pub(crate) unknown_fields: PathMap<()>,
+    pub(crate) extensions: PathMap<()>,
+    pub(crate) required_presence: PathMap<()>,
+    pub(crate) const_defaults: PathMap<()>,
+    pub(crate) non_exhaustive: PathMap<()>,
+    pub(crate) message_refs: PathMap<()>,
+    pub(crate) arena_messages: PathMap<()>,
+    pub(crate) validate_messages: PathMap<()>,
+    pub(crate) heapless_messages: PathMap<()>,
     pub(crate) skip_protoc_run: bool,
     pub(crate) skip_source_info: bool,
+    pub(crate) codegen_cache: bool,
+    pub(crate) native_parser: bool,
     pub(crate) include_file: Option<PathBuf>,
+    pub(crate) include_file_features: Vec<(String, String)>,
+    pub(crate) include_file_extern_paths: bool,
     pub(crate) prost_path: Option<String>,
     pub(crate) prost_types_path: Option<String>,
     #[cfg(feature = "format")]
@@ -124,6 +225,91 @@ impl Config {
         self
     }
 
+    /// Configure the code generator to generate a custom Rust map type for Protobuf [`map`][1]
+    /// type fields, such as `indexmap::IndexMap` or `rustc_hash::FxHashMap`.
+    ///
+    /// The generated field has the type `{map_type}<K, V>`. The map type must implement
+    /// `Default`, `Extend<(K, V)>` and `FromIterator<(K, V)>`, and iterate by reference as
+    /// `(&K, &V)` pairs; these bounds are required by the functions of
+    /// `prost::encoding::custom_map`, which encode and decode the field. Maps with enumeration
+    /// values also need `get` and `insert` methods with the signatures of
+    /// [`HashMap`](std::collections::HashMap). Custom map fields do not support field masks.
+    ///
+    /// Custom map types take precedence over [`btree_map`](Self::btree_map).
+    ///
+    /// # Arguments
+    ///
+    /// **`path`** - a path to specific fields, messages, or packages. For details about matching
+    /// fields see [`btree_map`](Self::btree_map).
+    ///
+    /// **`map_type`** - the path of the Rust map type, without generic arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Use an `IndexMap`, which preserves the order of the entries, for a field.
+    /// config.map_type(".my_messages.MyMessageType.my_map_field", "::indexmap::IndexMap");
+    /// ```
+    ///
+    /// [1]: https://protobuf.dev/programming-guides/proto3/#maps
+    pub fn map_type<P, T>(&mut self, path: P, map_type: T) -> &mut Self
+    where
+        P: AsRef<str>,
+        T: AsRef<str>,
+    {
+        self.custom_map_type
+            .insert(path.as_ref().to_string(), map_type.as_ref().to_string());
+        self
+    }
+
+    /// Configure the code generator to replace the Rust type of matched fields with a type of the
+    /// user, such as a domain newtype for a UUID, a decimal or an IP address.
+    ///
+    /// For message fields, the type replaces the generated message type and must implement
+    /// [`Message`](prost::Message), `Clone`, `PartialEq` and `Debug`, like generated messages.
+    ///
+    /// For scalar fields, such as `bytes` or `string` fields, the field is annotated with
+    /// `#[prost(convert)]`, and its values are converted to and from the Rust type the field would
+    /// otherwise have when it is encoded and decoded. The type must implement `From` of that type,
+    /// that type must implement `From` of it, and it must implement `Clone`, `PartialEq`, `Debug`
+    /// and `Default`. Converted `string` fields are not validated as UTF-8 beyond the conversion.
+    ///
+    /// The generated messages do not derive `Eq` and `Hash` when one of their fields is replaced.
+    /// Fields of oneofs and map fields are never replaced, and replaced fields are not supported
+    /// by [`message_refs`](Self::message_refs), [`arena_messages`](Self::arena_messages),
+    /// [`validate_messages`](Self::validate_messages) and
+    /// [`enable_field_masks`](Self::enable_field_masks). With
+    /// [`enable_serde`](Self::enable_serde), the type must implement the serde traits itself.
+    ///
+    /// # Arguments
+    ///
+    /// **`path`** - a path matching any number of fields. For details about matching fields see
+    /// [`btree_map`](Self::btree_map).
+    ///
+    /// **`rust_type`** - the path of the Rust type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // A `bytes` field holding a `my_crate::Uuid`, which implements `From<Vec<u8>>`, while
+    /// // `Vec<u8>` implements `From<my_crate::Uuid>`.
+    /// config.message_type(".my_messages.User.id", "::my_crate::Uuid");
+    ///
+    /// // A message field holding a `my_crate::Money`, which implements `prost::Message`.
+    /// config.message_type(".my_messages.Order.price", "::my_crate::Money");
+    /// ```
+    pub fn message_type<P, T>(&mut self, path: P, rust_type: T) -> &mut Self
+    where
+        P: AsRef<str>,
+        T: AsRef<str>,
+    {
+        self.message_types
+            .insert(path.as_ref().to_string(), rust_type.as_ref().to_string());
+        self
+    }
+
     /// Configure the code generator to generate Rust [`bytes::Bytes`](prost::bytes::Bytes) fields for Protobuf
     /// [`bytes`][2] type fields.
     ///
@@ -169,6 +355,9 @@ impl Config {
     /// config.bytes(&["my_bytes_field", ".foo.bar"]);
     /// ```
     ///
+    /// Other containers, such as `Arc<[u8]>`, are configured with
+    /// [`bytes_type`](Self::bytes_type).
+    ///
     /// [2]: https://protobuf.dev/programming-guides/proto3/#scalar
     /// [3]: https://doc.rust-lang.org/std/vec/struct.Vec.html
     pub fn bytes<I, S>(&mut self, paths: I) -> &mut Self
@@ -184,6 +373,171 @@ impl Config {
         self
     }
 
+    /// Configure the code generator to generate a custom Rust container type for Protobuf
+    /// [`bytes`][1] type fields, such as `Arc<[u8]>` or a small vector with inline storage.
+    ///
+    /// The container type must implement [`prost::BytesAdapter`], which encodes and decodes the
+    /// field, as well as `AsRef<[u8]>`, `From<&[u8]>`, `Clone`, `PartialEq` and `Debug`.
+    /// `prost` implements `BytesAdapter` for `Box<[u8]>` and `Arc<[u8]>`. Custom bytes fields do
+    /// not support [`enable_json`](Self::enable_json),
+    /// [`enable_text_format`](Self::enable_text_format) and
+    /// [`enable_field_masks`](Self::enable_field_masks), unless the container type implements
+    /// the traits these options rely on.
+    ///
+    /// Custom bytes types take precedence over [`bytes`](Self::bytes).
+    ///
+    /// # Arguments
+    ///
+    /// **`path`** - a path to specific fields, messages, or packages. For details about matching
+    /// fields see [`bytes`](Self::bytes).
+    ///
+    /// **`bytes_type`** - the path of the Rust container type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Share the payloads of a message type between clones of the message.
+    /// config.bytes_type(".my_messages.Blob", "::std::sync::Arc<[u8]>");
+    /// ```
+    ///
+    /// [1]: https://protobuf.dev/programming-guides/proto3/#scalar
+    pub fn bytes_type<P, T>(&mut self, path: P, bytes_type: T) -> &mut Self
+    where
+        P: AsRef<str>,
+        T: AsRef<str>,
+    {
+        self.custom_bytes_type
+            .insert(path.as_ref().to_string(), bytes_type.as_ref().to_string());
+        self
+    }
+
+    /// Configure the code generator to generate [`prost::Utf8Bytes`] fields for Protobuf
+    /// [`string`][1] type fields.
+    ///
+    /// `Utf8Bytes` is a UTF-8 string backed by [`bytes::Bytes`](prost::bytes::Bytes). When a
+    /// message is decoded from a `Bytes` buffer, matched fields share the memory of the buffer
+    /// instead of being copied into a new `String`, which avoids an allocation per field.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific fields, messages, or packages which should use
+    /// `Utf8Bytes` for Protobuf `string` fields. For details about matching see
+    /// [`bytes`](Self::bytes). Map keys and values are always generated as [`String`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Match a specific field in a message type.
+    /// config.utf8_bytes(&[".my_messages.MyMessageType.my_string_field"]);
+    ///
+    /// // Match all string fields.
+    /// config.utf8_bytes(&["."]);
+    /// ```
+    ///
+    /// Other string types are configured with [`string_type`](Self::string_type).
+    ///
+    /// [1]: https://protobuf.dev/programming-guides/proto3/#scalar
+    pub fn utf8_bytes<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.string_type.clear();
+        for matcher in paths {
+            self.string_type
+                .insert(matcher.as_ref().to_string(), StringType::Utf8Bytes);
+        }
+        self
+    }
+
+    /// Configure the code generator to generate a custom Rust string type for Protobuf
+    /// [`string`][1] type fields, such as a small string which stores short values inline.
+    ///
+    /// Messages dominated by short strings, such as tags and identifiers, then decode without
+    /// allocating a `String` per field. The string type must implement
+    /// [`prost::StringAdapter`], which encodes and decodes the field, as well as `AsRef<str>`,
+    /// `From<&str>`, `Clone`, `PartialEq` and `Debug`. Map keys and values are always generated
+    /// as [`String`]. Custom string fields do not support [`enable_json`](Self::enable_json),
+    /// [`enable_text_format`](Self::enable_text_format) and
+    /// [`enable_field_masks`](Self::enable_field_masks), unless the string type implements the
+    /// traits these options rely on.
+    ///
+    /// Custom string types take precedence over [`utf8_bytes`](Self::utf8_bytes).
+    ///
+    /// # Arguments
+    ///
+    /// **`path`** - a path to specific fields, messages, or packages. For details about matching
+    /// fields see [`bytes`](Self::bytes).
+    ///
+    /// **`string_type`** - the path of the Rust string type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Store the short identifiers of a message type inline.
+    /// config.string_type(".my_messages.Label", "::my_crate::ShortString");
+    /// ```
+    ///
+    /// [1]: https://protobuf.dev/programming-guides/proto3/#scalar
+    pub fn string_type<P, T>(&mut self, path: P, string_type: T) -> &mut Self
+    where
+        P: AsRef<str>,
+        T: AsRef<str>,
+    {
+        self.custom_string_type
+            .insert(path.as_ref().to_string(), string_type.as_ref().to_string());
+        self
+    }
+
+    /// Configure the code generator to generate `Cow<'static, str>` and `Cow<'static, [u8]>`
+    /// fields for Protobuf [`string`][1] and [`bytes`][1] type fields.
+    ///
+    /// A `Cow` field can borrow a static value, so messages built from constants, and the
+    /// default values of proto2 fields, do not allocate. Decoding a message always stores owned
+    /// values, and reuses the capacity of a field which is already owned. `prost` implements
+    /// [`prost::StringAdapter`] and [`prost::BytesAdapter`] for these types, and they are
+    /// otherwise generated as [custom string types](Self::string_type): map keys and values are
+    /// always generated as usual, and `Cow` fields do not support
+    /// [`enable_json`](Self::enable_json), [`enable_text_format`](Self::enable_text_format),
+    /// [`enable_field_masks`](Self::enable_field_masks) and [`message_refs`](Self::message_refs).
+    ///
+    /// Custom string and bytes types configured with [`string_type`](Self::string_type) and
+    /// [`bytes_type`](Self::bytes_type) take precedence over `Cow`.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific fields, messages, or packages. For details about matching
+    /// fields see [`bytes`](Self::bytes).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Build the static catalog entries of a message type without allocating.
+    /// config.cow([".my_messages.CatalogEntry"]);
+    /// ```
+    ///
+    /// ```rust,ignore
+    /// const fn entry(name: &'static str) -> CatalogEntry {
+    ///     CatalogEntry { name: Cow::Borrowed(name) }
+    /// }
+    /// ```
+    ///
+    /// [1]: https://protobuf.dev/programming-guides/proto3/#scalar
+    pub fn cow<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for matcher in paths {
+            self.cow.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
     /// Add additional attribute to matched fields.
     ///
     /// # Arguments
@@ -305,7 +659,7 @@ impl Config {
         self
     }
 
-    /// Add additional attribute to matched enums and one-ofs.
+    /// Add additional attribute to matched enums.
     ///
     /// # Arguments
     ///
@@ -319,8 +673,9 @@ impl Config {
     /// type is matched by multiple calls of the method, all relevant attributes are added to
     /// it.
     ///
-    /// For things like serde it might be needed to combine with [field
-    /// attributes](Self::field_attribute).
+    /// The `enum`s implementing oneofs are not matched, see
+    /// [`oneof_attribute`](Self::oneof_attribute). For things like serde it might be needed to
+    /// combine with [variant attributes](Self::variant_attribute).
     ///
     /// # Examples
     ///
@@ -334,16 +689,6 @@ impl Config {
     /// config.enum_attribute("my_messages.MyMessageType.MyNestedEnumType",
     ///                       "#[derive(Serialize)] #[serde(rename_all = \"snake_case\")]");
     /// ```
-    ///
-    /// # Oneof fields
-    ///
-    /// The `oneof` fields don't have a type name of their own inside Protobuf. Therefore, the
-    /// field name can be used both with `enum_attribute` and `field_attribute` ‒ the first is
-    /// placed before the `enum` type definition, the other before the field inside corresponding
-    /// message `struct`.
-    ///
-    /// In other words, to place an attribute on the `enum` implementing the `oneof`, the match
-    /// would look like `my_messages.MyNestedMessageType.oneofname`.
     pub fn enum_attribute<P, A>(&mut self, path: P, attribute: A) -> &mut Self
     where
         P: AsRef<str>,
@@ -354,115 +699,1073 @@ impl Config {
         self
     }
 
-    /// Wrap matched fields in a `Box`.
+    /// Add additional attribute to the `enum`s implementing matched oneofs.
     ///
     /// # Arguments
     ///
-    /// **`path`** - a path matching any number of fields. These fields get the attribute.
-    /// For details about matching fields see [`btree_map`](Self::btree_map).
+    /// **`paths`** - a path matching any number of oneofs. The `oneof` fields don't have a type
+    /// name of their own inside Protobuf, so a oneof is matched by the path of the message
+    /// followed by the name of the oneof, such as `my_messages.MyMessageType.oneofname`. For
+    /// details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// **`attribute`** - an arbitrary string to be placed before each matched `enum`. The
+    /// expected usage are additional attributes, but anything is allowed.
+    ///
+    /// The calls to this method are cumulative. They don't overwrite previous calls and if a
+    /// oneof is matched by multiple calls of the method, all relevant attributes are added to
+    /// it. Unlike [`type_attribute`](Self::type_attribute), the messages and enums under a
+    /// matched path don't get the attribute, and unlike
+    /// [`field_attribute`](Self::field_attribute), neither does the oneof field of the message.
     ///
     /// # Examples
     ///
     /// ```rust
     /// # let mut config = prost_build::Config::new();
-    /// config.boxed(".my_messages.MyMessageType.my_field");
+    /// // Serialize all oneofs of a package as internally tagged enums.
+    /// config.oneof_attribute(".my_messages", "#[serde(tag = \"type\")]");
     /// ```
-    pub fn boxed<P>(&mut self, path: P) -> &mut Self
+    pub fn oneof_attribute<P, A>(&mut self, path: P, attribute: A) -> &mut Self
     where
         P: AsRef<str>,
+        A: AsRef<str>,
     {
-        self.boxed.insert(path.as_ref().to_string(), ());
-        self
-    }
-
-    /// Configures the code generator to use the provided service generator.
-    pub fn service_generator(&mut self, service_generator: Box<dyn ServiceGenerator>) -> &mut Self {
-        self.service_generator = Some(service_generator);
-        self
-    }
-
-    /// Configures the code generator to not use the `prost_types` crate for Protobuf well-known
-    /// types, and instead generate Protobuf well-known types from their `.proto` definitions.
-    pub fn compile_well_known_types(&mut self) -> &mut Self {
-        self.prost_types = false;
+        self.oneof_attributes
+            .insert(path.as_ref().to_string(), attribute.as_ref().to_string());
         self
     }
 
-    /// Configures the code generator to omit documentation comments on generated Protobuf types.
+    /// Add additional attribute to the variants of matched enums and oneofs.
     ///
-    /// # Example
+    /// # Arguments
     ///
-    /// Occasionally `.proto` files contain code blocks which are not valid Rust. To avoid doctest
-    /// failures, annotate the invalid code blocks with an [`ignore` or `no_run` attribute][1], or
-    /// disable doctests for the crate with a [Cargo.toml entry][2]. If neither of these options
-    /// are possible, then omit comments on generated code during doctest builds:
+    /// **`path`** - a path matching any number of variants. A variant is matched by the path of
+    /// its enum followed by the Protobuf name of the value, such as
+    /// `my_messages.MyEnumType.MY_VALUE`, or by the path of its oneof followed by the name of the
+    /// field, such as `my_messages.MyMessageType.oneofname.fieldname`. For details about matching
+    /// see [`btree_map`](Self::btree_map).
     ///
-    /// ```rust,no_run
-    /// # fn main() -> std::io::Result<()> {
-    /// let mut config = prost_build::Config::new();
-    /// config.disable_comments(&["."]);
-    /// config.compile_protos(&["src/frontend.proto", "src/backend.proto"], &["src"])?;
-    /// #     Ok(())
-    /// # }
-    /// ```
+    /// **`attribute`** - an arbitrary string to be placed before each matched variant. The
+    /// expected usage are additional attributes, but anything is allowed.
     ///
-    /// As with other options which take a set of paths, comments can be disabled on a per-package
-    /// or per-symbol basis.
+    /// The calls to this method are cumulative. Unlike
+    /// [`field_attribute`](Self::field_attribute), which also matches variants, the fields of
+    /// messages don't get the attribute.
     ///
-    /// [1]: https://doc.rust-lang.org/rustdoc/write-documentation/documentation-tests.html#attributes
-    /// [2]: https://doc.rust-lang.org/cargo/reference/cargo-targets.html#configuring-a-target
-    pub fn disable_comments<I, S>(&mut self, paths: I) -> &mut Self
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Serialize an enum value under another name.
+    /// config.variant_attribute(".my_messages.MyEnumType.MY_VALUE",
+    ///                          "#[serde(rename = \"value\")]");
+    /// ```
+    pub fn variant_attribute<P, A>(&mut self, path: P, attribute: A) -> &mut Self
     where
-        I: IntoIterator<Item = S>,
-        S: AsRef<str>,
+        P: AsRef<str>,
+        A: AsRef<str>,
     {
-        self.disable_comments.clear();
-        for matcher in paths {
-            self.disable_comments
-                .insert(matcher.as_ref().to_string(), ());
-        }
+        self.variant_attributes
+            .insert(path.as_ref().to_string(), attribute.as_ref().to_string());
         self
     }
 
-    /// Skips generating `impl Debug` for types
-    pub fn skip_debug<I, S>(&mut self, paths: I) -> &mut Self
+    /// Wrap matched fields in a `Box`.
+    ///
+    /// # Arguments
+    ///
+    /// **`path`** - a path matching any number of fields. These fields get the attribute.
+    /// For details about matching fields see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config.boxed(".my_messages.MyMessageType.my_field");
+    /// ```
+    pub fn boxed<P>(&mut self, path: P) -> &mut Self
     where
-        I: IntoIterator<Item = S>,
-        S: AsRef<str>,
+        P: AsRef<str>,
     {
-        self.skip_debug.clear();
-        for matcher in paths {
-            self.skip_debug.insert(matcher.as_ref().to_string(), ());
-        }
+        self.boxed.insert(path.as_ref().to_string(), ());
         self
     }
 
-    /// Declare an externally provided Protobuf package or type.
+    /// Wrap the messages of matched message fields in the given pointer type.
     ///
-    /// `extern_path` allows `prost` types in external crates to be referenced in generated code.
+    /// With [`Wrapper::Arc`], cloning a message only clones the pointer of the field, so a large
+    /// message shared by many others, such as the payload of envelopes which are cloned for each
+    /// of their recipients, is not copied. Decoding into a shared message, or modifying it with
+    /// [`Arc::make_mut`](std::sync::Arc::make_mut), first copies it if it is shared, so the
+    /// other messages are unchanged. [`Wrapper::Box`] has the same effect as
+    /// [`boxed`](Self::boxed).
     ///
-    /// When `prost` compiles a `.proto` which includes an import of another `.proto`, it will
-    /// automatically recursively compile the imported file as well. `extern_path` can be used
-    /// to instead substitute types from an external crate.
+    /// Only singular message fields and the message fields of oneofs are wrapped; repeated
+    /// fields and fields of other types are generated as usual.
     ///
-    /// # Example
+    /// # Arguments
     ///
-    /// As an example, consider a crate, `uuid`, with a `prost`-generated `Uuid` type:
+    /// **`path`** - a path matching any number of fields. These fields get the wrapper.
+    /// For details about matching fields see [`btree_map`](Self::btree_map).
     ///
-    /// ```proto
-    /// // uuid.proto
+    /// **`wrapper`** - the pointer type holding the messages of the fields.
     ///
-    /// syntax = "proto3";
-    /// package uuid;
+    /// # Examples
     ///
-    /// message Uuid {
-    ///     string uuid_str = 1;
-    /// }
+    /// ```rust
+    /// # use prost_build::Wrapper;
+    /// # let mut config = prost_build::Config::new();
+    /// config.wrapper_type(".my_messages.Envelope.payload", Wrapper::Arc);
     /// ```
+    pub fn wrapper_type<P>(&mut self, path: P, wrapper: Wrapper) -> &mut Self
+    where
+        P: AsRef<str>,
+    {
+        self.wrapper_types
+            .insert(path.as_ref().to_string(), wrapper);
+        self
+    }
+
+    /// Wrap the message fields of oneofs in a `Box` if their messages are larger than
+    /// `max_size` bytes.
     ///
-    /// The `uuid` crate implements some traits for `Uuid`, and publicly exports it:
+    /// A oneof is generated as an `enum`, which is as large as its largest variant. A oneof
+    /// holding a large message next to small fields therefore makes every value of the oneof
+    /// large, copies the whole `enum` when it is moved, and triggers
+    /// `clippy::large_enum_variant`, which warns at 200 bytes by default. With this option,
+    /// variants whose messages are estimated to be larger than `max_size` bytes on 64-bit
+    /// targets hold a `Box` of the message, as with [`boxed`](Self::boxed).
     ///
-    /// ```rust,ignore
+    /// The oneof `enum` keeps a variant for each field, so it can still be matched
+    /// exhaustively. For each boxed variant `Bar`, it gets `as_bar` and `as_bar_mut` methods
+    /// returning references to the message, and implements `From` for the message if no other
+    /// variant holds the same type.
+    ///
+    /// The size of a message is estimated from its fields, so messages whose types are replaced
+    /// with [`message_type`](Self::message_type), and oneofs of
+    /// [heapless messages](Self::heapless_messages), are never boxed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config.box_large_oneof_variants(200);
+    /// ```
+    ///
+    /// ```rust,ignore
+    /// let kind = event::Kind::from(Snapshot::default());
+    /// if let Some(snapshot) = kind.as_snapshot() {
+    ///     println!("{} entries", snapshot.entries.len());
+    /// }
+    /// ```
+    pub fn box_large_oneof_variants(&mut self, max_size: usize) -> &mut Self {
+        self.large_oneof_variant_size = Some(max_size);
+        self
+    }
+
+    /// Configures whether message fields which would make a message contain itself are wrapped
+    /// automatically.
+    ///
+    /// A message whose type transitively contains itself in a non-repeated message field could
+    /// not be sized, so such fields are wrapped in the pointer chosen with
+    /// [`recursive_wrapper`](Self::recursive_wrapper) by default. With automatic wrapping
+    /// disabled, only the fields matched by [`boxed`](Self::boxed) are wrapped, and every
+    /// recursive message must have such a field in each of its cycles to compile.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config
+    ///     .box_recursive(false)
+    ///     .boxed(".my_messages.Tree.left")
+    ///     .boxed(".my_messages.Tree.right");
+    /// ```
+    pub fn box_recursive(&mut self, enabled: bool) -> &mut Self {
+        self.box_recursive = enabled;
+        self
+    }
+
+    /// Configures the pointer wrapping the message fields which would make a message contain
+    /// itself, `Box` by default.
+    ///
+    /// With [`Wrapper::Arc`], the nodes of self-referential trees, such as the children of
+    /// syntax trees, are shared: cloning a tree clones its root, and the subtrees are only
+    /// copied when they are modified through a clone. `prost` implements its traits for
+    /// `Arc<M>` when `M: Clone`, which generated messages are.
+    ///
+    /// Fields matched by [`boxed`](Self::boxed) stay boxed, and recursive fields are not wrapped
+    /// at all if [`box_recursive`](Self::box_recursive) is disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config.recursive_wrapper(prost_build::Wrapper::Arc);
+    /// ```
+    pub fn recursive_wrapper(&mut self, wrapper: Wrapper) -> &mut Self {
+        self.recursive_wrapper = wrapper;
+        self
+    }
+
+    /// Decode matched message fields lazily, by wrapping them in a [`prost::Lazy`].
+    ///
+    /// Decoding a message only copies the encoded bytes of its lazy fields, and a lazy field is
+    /// decoded when it is first accessed with [`Lazy::get`] or [`Lazy::get_mut`]. Lazy fields
+    /// which are not modified are encoded by writing out their original bytes. Decode errors in
+    /// a lazy field are only reported when it is accessed.
+    ///
+    /// Only singular and repeated message fields outside of oneofs can be lazy; other matched
+    /// fields are generated as usual. `Lazy` requires the `std` feature of `prost`.
+    ///
+    /// # Arguments
+    ///
+    /// **`path`** - a path matching any number of fields. These fields get the attribute.
+    /// For details about matching fields see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config.lazy(".my_messages.MyMessageType.my_field");
+    /// ```
+    ///
+    /// [`prost::Lazy`]: https://docs.rs/prost/latest/prost/struct.Lazy.html
+    /// [`Lazy::get`]: https://docs.rs/prost/latest/prost/struct.Lazy.html#method.get
+    /// [`Lazy::get_mut`]: https://docs.rs/prost/latest/prost/struct.Lazy.html#method.get_mut
+    pub fn lazy<P>(&mut self, path: P) -> &mut Self
+    where
+        P: AsRef<str>,
+    {
+        self.lazy.insert(path.as_ref().to_string(), ());
+        self
+    }
+
+    /// Track the presence of matched proto3 `optional` scalar fields in presence bits, instead
+    /// of generating them as an `Option<T>`.
+    ///
+    /// A matched field is generated as a plain `T`, which holds the default value while the field
+    /// is unset, and its presence is tracked by a bit of the hidden `_presence_bits` field of the
+    /// message. The message gets `set_`, `has_` and `clear_` methods for the field, which must be
+    /// used to change its presence: assigning to the field directly does not set it. This avoids
+    /// unwrapping an `Option` for each access of messages with many optional fields, while keeping
+    /// their presence semantics.
+    ///
+    /// The bits are a `[u32; N]`, so that a message with many optional fields is smaller than
+    /// with an `Option<T>` per field, each of which takes the alignment of `T` for its
+    /// discriminant. The generated `Debug` implementation still prints these fields as
+    /// `Some(value)` or `None`, and their encoding and JSON mapping are unchanged. With
+    /// [serde implementations](Self::enable_serde), messages with presence bits implement the
+    /// serde traits with their JSON mapping, which must then be [enabled](Self::enable_json) as
+    /// well; otherwise their fields remain `Option`s.
+    ///
+    /// Optional fields of messages which generate [borrowed views](Self::message_refs),
+    /// [arena messages](Self::arena_messages) or [validation](Self::validate_messages), and
+    /// fields whose types are replaced, remain `Option`s.
+    ///
+    /// # Migration
+    ///
+    /// Code using a field which moves to presence bits changes as follows:
+    ///
+    /// | `Option<T>` field                     | Presence bit               |
+    /// |---------------------------------------|----------------------------|
+    /// | `message.field = Some(value)`         | `message.set_field(value)` |
+    /// | `message.field = None`                | `message.clear_field()`    |
+    /// | `message.field.is_some()`             | `message.has_field()`      |
+    /// | `message.field.unwrap_or_default()`   | `message.field`            |
+    ///
+    /// Struct literals of the message must start from `Default::default()` and set the fields
+    /// with their setters, or with the [builder](Self::generate_builders) of the message.
+    ///
+    /// # Arguments
+    ///
+    /// **`path`** - a path matching any number of fields. These fields get the attribute.
+    /// For details about matching fields see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Track the presence of all optional scalar fields in presence bits.
+    /// config.presence_bits(".");
+    /// ```
+    pub fn presence_bits<P>(&mut self, path: P) -> &mut Self
+    where
+        P: AsRef<str>,
+    {
+        self.presence_bits.insert(path.as_ref().to_string(), ());
+        self
+    }
+
+    /// Generate matched enumeration fields as a [`prost::OpenEnum`] of the generated Rust enum,
+    /// instead of an `i32`.
+    ///
+    /// An `OpenEnum` is either a `Known` variant of the enum, or an `Unknown` value which is not
+    /// one of its variants, such as a variant added by a newer version of the schema. Matching on
+    /// the field is therefore type-safe, while unknown values are still encoded again unchanged.
+    /// Unknown values of closed (proto2) enums are kept in the same way as known values, as they
+    /// are for `i32` fields.
+    ///
+    /// Oneof and map fields are not affected, and keep their `i32` values.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific fields, messages, or packages which should use open enums.
+    /// For details about matching fields see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Match a specific field in a message type.
+    /// config.open_enums(&[".my_messages.MyMessageType.my_enum_field"]);
+    ///
+    /// // Match all enumeration fields.
+    /// config.open_enums(&["."]);
+    /// ```
+    ///
+    /// [`prost::OpenEnum`]: https://docs.rs/prost/latest/prost/enum.OpenEnum.html
+    pub fn open_enums<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.open_enums.clear();
+        for matcher in paths {
+            self.open_enums.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Implement `Display` and `FromStr` for matched enumerations, using the names of their
+    /// values in the Protobuf definition.
+    ///
+    /// `Display` writes the name returned by `as_str_name`, and `FromStr` parses names with
+    /// `from_str_name`, which also accepts the names of aliases declared with the `allow_alias`
+    /// option. Parsing an unknown name fails with a [`prost::UnknownEnumName`] error. Names are
+    /// matched exactly, unless the enumeration is also matched by
+    /// [`enum_from_str_ignore_case`](Self::enum_from_str_ignore_case).
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific enumerations, or packages whose enumerations should
+    /// implement `Display` and `FromStr`. For details about matching see
+    /// [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Implement `Display` and `FromStr` for all enumerations.
+    /// config.enum_display_from_str(&["."]);
+    ///
+    /// // Implement `Display` and `FromStr` for a specific enumeration.
+    /// config.enum_display_from_str(&[".my_messages.Level"]);
+    /// ```
+    ///
+    /// [`prost::UnknownEnumName`]: https://docs.rs/prost/latest/prost/struct.UnknownEnumName.html
+    pub fn enum_display_from_str<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.enum_display_from_str.clear();
+        for matcher in paths {
+            self.enum_display_from_str
+                .insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Parse the value names of matched enumerations without regard to ASCII case.
+    ///
+    /// Matched enumerations get an additional `from_str_name_ignore_case` method, which is used by
+    /// the `FromStr` implementation generated with
+    /// [`enum_display_from_str`](Self::enum_display_from_str), so that user input such as
+    /// `level_high` parses as `LEVEL_HIGH`. `as_str_name` and `Display` are not affected.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific enumerations, or packages whose enumerations should parse
+    /// names without regard to case. For details about matching see
+    /// [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config.enum_display_from_str(&["."]);
+    /// config.enum_from_str_ignore_case(&[".my_messages.Level"]);
+    /// ```
+    pub fn enum_from_str_ignore_case<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.enum_from_str_ignore_case.clear();
+        for matcher in paths {
+            self.enum_from_str_ignore_case
+                .insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Derive `Eq` and `Hash` for matched messages and their oneofs, so that they can be used as
+    /// the keys of a `HashMap` or the values of a `HashSet`.
+    ///
+    /// Messages whose fields all implement `Eq` and `Hash` always derive them. Matched messages
+    /// also derive them when they have repeated message fields, or `BTreeMap` map fields, as long
+    /// as the messages of these fields are matched as well. Messages with `float` or `double`
+    /// fields derive them only if they are also matched by
+    /// [`float_total_order`](Self::float_total_order).
+    ///
+    /// Matched messages which can't implement the traits, e.g. because they have a `HashMap`
+    /// field, a field whose type is configured with [`message_type`](Self::message_type) or
+    /// [`string_type`](Self::string_type), or a recursive field, are generated as usual.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages whose messages should derive `Eq`
+    /// and `Hash`. For details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Derive `Eq` and `Hash` for all messages which can implement them.
+    /// config.btree_map(&["."]);
+    /// config.derive_eq_hash(&["."]);
+    /// ```
+    pub fn derive_eq_hash<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.derive_eq_hash.clear();
+        for matcher in paths {
+            self.derive_eq_hash.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Derive `Eq`, `Hash`, `PartialOrd` and `Ord` for matched messages and their oneofs, so that
+    /// they can be sorted, or used as the keys of a `BTreeMap`.
+    ///
+    /// The same messages as with [`derive_eq_hash`](Self::derive_eq_hash) are eligible, except
+    /// that messages preserving unknown fields or extensions, and message fields of types from
+    /// other crates, don't implement `Ord`. Messages are ordered by their fields, in declaration
+    /// order, then by their oneofs. Matched messages which can't implement `Ord` still derive
+    /// `Eq` and `Hash` if they can.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages whose messages should derive the
+    /// traits. For details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config.derive_ord(&[".my_messages.Version"]);
+    /// ```
+    pub fn derive_ord<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.derive_ord.clear();
+        for matcher in paths {
+            self.derive_ord.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Compare the `float` and `double` fields of matched messages by their total order, so that
+    /// messages with such fields can implement `Eq`, `Hash` and `Ord` with
+    /// [`derive_eq_hash`](Self::derive_eq_hash) and [`derive_ord`](Self::derive_ord).
+    ///
+    /// The total order is the one of [`f64::total_cmp`]: two values are equal only if their bits
+    /// are equal, so `NaN` equals itself, and `-0.0` is less than `0.0`. Because the generated
+    /// `PartialEq` implementation has to agree with `Eq`, it also compares floats this way,
+    /// rather than with `==`. Repeated fields, optional fields, oneof variants and map values of
+    /// floats are compared element by element with [`prost::total_order::TotalOrd`].
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages whose messages should compare floats
+    /// by their total order. For details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config.derive_eq_hash(&[".geometry"]);
+    /// config.float_total_order(&[".geometry.Point"]);
+    /// ```
+    ///
+    /// [`prost::total_order::TotalOrd`]: https://docs.rs/prost/latest/prost/total_order/trait.TotalOrd.html
+    pub fn float_total_order<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.float_total_order.clear();
+        for matcher in paths {
+            self.float_total_order
+                .insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Implement `prost::proto_ord::ProtoOrd` for matched messages, ordering them by field number,
+    /// then by value, as described in the [`prost::proto_ord`] documentation.
+    ///
+    /// Unlike [`derive_ord`](Self::derive_ord), the order doesn't depend on the declaration order
+    /// of the fields, so sorting and deduplicating messages agrees with other runtimes ordering
+    /// messages by the same rules. Floats are compared by their total order, maps by their
+    /// entries sorted by key, and unknown fields and extensions after the known fields. Matched
+    /// messages which can't implement the trait, e.g. because they have a field whose type is
+    /// configured with [`message_type`](Self::message_type), a custom string, bytes or map type,
+    /// or a message field of a type from another crate or which isn't matched, are generated as
+    /// usual.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages whose messages should implement
+    /// `ProtoOrd`. For details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Order all messages by their fields.
+    /// config.proto_cmp(&["."]);
+    /// ```
+    ///
+    /// [`prost::proto_ord`]: https://docs.rs/prost/latest/prost/proto_ord/index.html
+    pub fn proto_cmp<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.proto_cmp.clear();
+        for matcher in paths {
+            self.proto_cmp.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Configures the code generator to use the provided service generator.
+    pub fn service_generator(&mut self, service_generator: Box<dyn ServiceGenerator>) -> &mut Self {
+        self.service_generator = Some(service_generator);
+        self
+    }
+
+    /// Adds a derive provider, which generates attributes for messages, enums and fields from
+    /// their descriptors.
+    ///
+    /// Several providers can be added, and their attributes are emitted in the order the
+    /// providers were added. See [`DeriveProvider`] for an example.
+    pub fn derive_provider(&mut self, derive_provider: Box<dyn DeriveProvider>) -> &mut Self {
+        self.derive_providers.push(derive_provider);
+        self
+    }
+
+    /// Configures the code generator to not use the `prost_types` crate for Protobuf well-known
+    /// types, and instead generate Protobuf well-known types from their `.proto` definitions.
+    pub fn compile_well_known_types(&mut self) -> &mut Self {
+        self.prost_types = false;
+        self
+    }
+
+    /// Configures the code generator to omit documentation comments on generated Protobuf types.
+    ///
+    /// # Example
+    ///
+    /// Occasionally `.proto` files contain code blocks which are not valid Rust. To avoid doctest
+    /// failures, annotate the invalid code blocks with an [`ignore` or `no_run` attribute][1], or
+    /// disable doctests for the crate with a [Cargo.toml entry][2]. If neither of these options
+    /// are possible, then omit comments on generated code during doctest builds:
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let mut config = prost_build::Config::new();
+    /// config.disable_comments(&["."]);
+    /// config.compile_protos(&["src/frontend.proto", "src/backend.proto"], &["src"])?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// As with other options which take a set of paths, comments can be disabled on a per-package
+    /// or per-symbol basis.
+    ///
+    /// [1]: https://doc.rust-lang.org/rustdoc/write-documentation/documentation-tests.html#attributes
+    /// [2]: https://doc.rust-lang.org/cargo/reference/cargo-targets.html#configuring-a-target
+    pub fn disable_comments<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.disable_comments.clear();
+        for matcher in paths {
+            self.disable_comments
+                .insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Configures the code generator to convert links to Protobuf types in comments into
+    /// intra-doc links.
+    ///
+    /// Comments in `.proto` files commonly refer to other types with reference-style links whose
+    /// reference is the Protobuf name of the type, such as `[Foo][my.package.Foo]`. Rustdoc does
+    /// not know these references, and renders them as plain text. With this option, links to
+    /// message and enum types are rewritten to the Rust paths of the generated types, such as
+    /// `[Foo][super::package::Foo]`, which rustdoc renders as links. Names which are not fully
+    /// qualified are resolved following the Protobuf scoping rules, relative to the commented
+    /// item. Links to other names are kept unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config.intra_doc_links(true);
+    /// ```
+    pub fn intra_doc_links(&mut self, enable: bool) -> &mut Self {
+        self.intra_doc_links = enable;
+        self
+    }
+
+    /// Skips generating `impl Debug` for types
+    pub fn skip_debug<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.skip_debug.clear();
+        for matcher in paths {
+            self.skip_debug.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Preserve unknown fields on matched messages.
+    ///
+    /// Matched messages get an additional hidden `_unknown_fields` field of type
+    /// [`UnknownFieldSet`](prost::UnknownFieldSet). Fields which are not part of the message
+    /// definition are stored there while decoding and written back while encoding, so that
+    /// data added by a newer version of the schema survives a decode/encode round-trip.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages which should preserve unknown
+    /// fields. For details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Preserve unknown fields on all messages.
+    /// config.preserve_unknown_fields(&["."]);
+    ///
+    /// // Preserve unknown fields on a single message type.
+    /// config.preserve_unknown_fields(&[".my_messages.MyMessageType"]);
+    /// ```
+    pub fn preserve_unknown_fields<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.unknown_fields.clear();
+        for matcher in paths {
+            self.unknown_fields.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Track the presence of the required fields of matched proto2 messages.
+    ///
+    /// By default, required fields are generated as plain values, so a required field which is
+    /// missing from the decoded data can not be told apart from one set to its default value.
+    /// The required fields of matched messages are generated as `Option` fields instead, along
+    /// with `has_<field>` and `clear_<field>` methods, and are reported by
+    /// [`Message::missing_required_fields`](prost::Message::missing_required_fields) and
+    /// [`Message::check_initialized`](prost::Message::check_initialized) when they are not set.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages whose required fields should track
+    /// presence. For details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Track required fields on all messages.
+    /// config.required_field_presence(&["."]);
+    ///
+    /// // Track required fields on a single message type.
+    /// config.required_field_presence(&[".my_messages.MyMessageType"]);
+    /// ```
+    pub fn required_field_presence<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.required_presence.clear();
+        for matcher in paths {
+            self.required_presence
+                .insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Generate a constant default value for matched messages.
+    ///
+    /// Matched messages get a `pub const DEFAULT: Self` with the same value as
+    /// `Default::default()`, including the declared defaults of proto2 fields, and a
+    /// `default_instance()` method returning a reference to a static default message. Both can
+    /// be used without allocating, e.g. by getters returning a default message for a field which
+    /// is not set.
+    ///
+    /// A message only gets a constant default if all of its fields have one, so matched messages
+    /// are skipped if they have:
+    ///
+    /// - `HashMap` or custom map fields, see [`btree_map`](Self::btree_map);
+    /// - lazy fields, or fields whose types are replaced with [`message_type`](Self::message_type)
+    ///   or generated as open enums;
+    /// - singular fields of custom string or bytes types;
+    /// - required fields without presence which hold a message without a constant default, or a
+    ///   non-empty default value of a type other than [`Utf8Bytes`](prost::Utf8Bytes) or
+    ///   [`Bytes`](prost::bytes::Bytes).
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages whose messages should get a constant
+    /// default. For details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Generate constant defaults for all messages which allow it.
+    /// config.const_defaults(&["."]);
+    ///
+    /// // Generate a constant default for a single message type.
+    /// config.const_defaults(&[".my_messages.MyMessageType"]);
+    /// ```
+    pub fn const_defaults<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.const_defaults.clear();
+        for matcher in paths {
+            self.const_defaults.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Mark matched messages, oneofs and enums `#[non_exhaustive]`, and generate a constructor
+    /// and accessors for matched messages.
+    ///
+    /// Adding a field to a message or a oneof, or a value to an enum, is then not a breaking change
+    /// of the generated Rust API, so that libraries can expose generated types in their public API
+    /// and still evolve their `.proto` files in minor releases. Since other crates can't construct
+    /// matched messages with struct expressions, or match matched oneofs and enums exhaustively,
+    /// each matched message `Foo` gets:
+    ///
+    /// - `Foo::new()`, which returns the default message;
+    /// - for each field `bar`, a `with_bar` method which sets the field and returns the message, a
+    ///   `bar_mut` method which returns a mutable reference to the field, and a `bar` method which
+    ///   returns a reference to it, unless the field already has a getter, as optional scalar and
+    ///   enumeration fields do.
+    ///
+    /// The `with_` methods of fields with [presence bits](Self::presence_bits) also set their
+    /// presence, and those fields get no `_mut` methods, as their presence is changed with their
+    /// `set_` and `clear_` methods.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, oneofs or enums, or packages whose types should
+    /// be non-exhaustive. For details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Make all types of a package non-exhaustive.
+    /// config.non_exhaustive_messages(&[".my_messages"]);
+    /// ```
+    ///
+    /// Matched messages are then constructed with their accessors:
+    ///
+    /// ```rust,ignore
+    /// let person = Person::new().with_name("Ferris".to_string()).with_id(7);
+    /// assert_eq!(person.name(), "Ferris");
+    /// ```
+    pub fn non_exhaustive_messages<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.non_exhaustive.clear();
+        for matcher in paths {
+            self.non_exhaustive.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Preserve extensions on matched messages.
+    ///
+    /// Matched messages which declare extension ranges get an additional hidden `_extensions`
+    /// field of type [`ExtensionSet`](prost::ExtensionSet), and implement
+    /// [`Extendable`](prost::Extendable). Extension fields are stored there while decoding and
+    /// written back while encoding, and can be accessed with the `Extension` constants which are
+    /// generated for each `extend` declaration, regardless of this setting.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages which should preserve extensions.
+    /// For details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Preserve extensions on all extendable messages.
+    /// config.preserve_extensions(&["."]);
+    ///
+    /// // Preserve extensions on a single message type.
+    /// config.preserve_extensions(&[".my_messages.MyMessageType"]);
+    /// ```
+    pub fn preserve_extensions<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.extensions.clear();
+        for matcher in paths {
+            self.extensions.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Generate borrowed views of matched messages.
+    ///
+    /// For each matched message `Foo`, an additional `FooRef<'a>` struct is generated, which
+    /// implements [`MessageRef`](prost::MessageRef). The `string` and `bytes` fields of the view
+    /// are `&'a str` and `&'a [u8]` slices of the decoded buffer, its message fields are views of
+    /// the nested messages, and its map fields are `Vec`s of key-value pairs in the order they
+    /// were decoded. Other fields have the same type as in the owned message. The view is
+    /// converted to the owned message with `FooRef::to_owned`. Views skip unknown fields and
+    /// extensions.
+    ///
+    /// A view is only generated if all the message types it refers to also get views, so a
+    /// message which refers to an extern type, including the well-known types provided by
+    /// `prost-types`, does not get one.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages which should get views. For
+    /// details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Generate views of all messages.
+    /// config.message_refs(&["."]);
+    ///
+    /// // Generate views of the messages in a package.
+    /// config.message_refs(&[".my_messages"]);
+    /// ```
+    pub fn message_refs<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.message_refs.clear();
+        for matcher in paths {
+            self.message_refs.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Generate arena-allocated variants of matched messages.
+    ///
+    /// For each matched message `Foo`, an additional `FooArena<'bump>` struct is generated, which
+    /// implements [`ArenaMessage`](prost::ArenaMessage) and is decoded into a [`bumpalo`] arena
+    /// with `FooArena::decode_in`. The `string` and `bytes` fields of the arena message are
+    /// `&'bump str` and `&'bump [u8]` slices copied into the arena, its repeated fields are
+    /// `bumpalo::collections::Vec`s, its message fields are arena messages as well, and its map
+    /// fields are `bumpalo::collections::Vec`s of key-value pairs in the order they were decoded.
+    /// Boxed message fields, including recursive ones, are `&'bump mut` references into the
+    /// arena. Other fields have the same type as in the owned message. Arena messages skip
+    /// unknown fields and extensions.
+    ///
+    /// Decoding an arena message does not use the global allocator, and everything decoded into
+    /// an arena is freed at once when it is reset or dropped, which suits request and response
+    /// messages that live exactly as long as the request. The generated code requires the
+    /// `arena` feature of `prost`.
+    ///
+    /// An arena message is only generated if all the message types it refers to also get arena
+    /// messages, so a message which refers to an extern type, including the well-known types
+    /// provided by `prost-types`, does not get one.
+    ///
+    /// [`bumpalo`]: https://docs.rs/bumpalo
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages which should get arena messages. For
+    /// details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Generate arena messages for all messages.
+    /// config.arena_messages(&["."]);
+    ///
+    /// // Generate arena messages for the messages in a package.
+    /// config.arena_messages(&[".my_messages"]);
+    /// ```
+    pub fn arena_messages<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.arena_messages.clear();
+        for matcher in paths {
+            self.arena_messages.insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Generate validation of matched messages from their [protovalidate] rules.
+    ///
+    /// Matched messages implement [`Validate`](prost::validate::Validate), which checks the rules
+    /// set with the `(buf.validate.field)` and `(buf.validate.oneof)` options, and the rules of
+    /// the matched messages in their fields. The `buf/validate/validate.proto` file must be
+    /// available to `protoc`, and the `validate` feature of `prost` must be enabled.
+    ///
+    /// The `required` and `ignore` rules, the rules of the scalar types, and the `repeated` and
+    /// `map` rules are supported. CEL expressions, the well-known string formats such as `email`,
+    /// the bytes `pattern` rule, and the rules of the `Any`, `Duration` and `Timestamp` types are
+    /// not checked. Invalid rules, such as a `pattern` which is not a valid regular expression or
+    /// rules which do not match the type of the field, cause a panic.
+    ///
+    /// [protovalidate]: https://protovalidate.com/
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages which should be validated. For
+    /// details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Validate all messages.
+    /// config.validate_messages(&["."]);
+    ///
+    /// // Validate the messages in a package.
+    /// config.validate_messages(&[".my_messages"]);
+    /// ```
+    pub fn validate_messages<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.validate_messages.clear();
+        for matcher in paths {
+            self.validate_messages
+                .insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Generate matched messages with fixed-capacity `heapless` fields, which do not allocate.
+    ///
+    /// The `string` and `bytes` fields of matched messages are generated as
+    /// [`heapless::String<N>`] and `heapless::Vec<u8, N>`, and repeated fields as
+    /// `heapless::Vec<T, N>`. The capacities are set with the `(prost.heapless)` field option
+    /// declared in `prost/heapless.proto`, which `prost-build` makes available to `protoc`:
+    ///
+    /// ```proto
+    /// import "prost/heapless.proto";
+    ///
+    /// message Reading {
+    ///   string sensor = 1 [(prost.heapless).max_len = 16];
+    ///   repeated sint32 samples = 2 [(prost.heapless).max_count = 8];
+    /// }
+    /// ```
+    ///
+    /// Decoding a value which exceeds the capacity of its field fails with a `DecodeError`. A
+    /// `string` or `bytes` field without a `max_len` option, a repeated field without a
+    /// `max_count` option, and a map field cause a panic. The `heapless` feature of `prost`
+    /// must be enabled.
+    ///
+    /// The fields of heapless messages do not allocate, but `prost` itself still requires the
+    /// `alloc` crate, and boxed fields of recursive messages are allocated. Builders are not
+    /// generated for heapless messages, and JSON and text format mappings, field masks,
+    /// validation, borrowed views and arena messages are not supported for them.
+    ///
+    /// [`heapless::String<N>`]: https://docs.rs/heapless/latest/heapless/struct.String.html
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages, or packages which should be generated with
+    /// heapless fields. For details about matching see [`btree_map`](Self::btree_map).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// // Generate all messages with heapless fields.
+    /// config.heapless_messages(&["."]);
+    ///
+    /// // Generate the messages in a package with heapless fields.
+    /// config.heapless_messages(&[".my_messages"]);
+    /// ```
+    pub fn heapless_messages<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.heapless_messages.clear();
+        for matcher in paths {
+            self.heapless_messages
+                .insert(matcher.as_ref().to_string(), ());
+        }
+        self
+    }
+
+    /// Declare an externally provided Protobuf package or type.
+    ///
+    /// `extern_path` allows `prost` types in external crates to be referenced in generated code.
+    ///
+    /// When `prost` compiles a `.proto` which includes an import of another `.proto`, it will
+    /// automatically recursively compile the imported file as well. `extern_path` can be used
+    /// to instead substitute types from an external crate.
+    ///
+    /// # Example
+    ///
+    /// As an example, consider a crate, `uuid`, with a `prost`-generated `Uuid` type:
+    ///
+    /// ```proto
+    /// // uuid.proto
+    ///
+    /// syntax = "proto3";
+    /// package uuid;
+    ///
+    /// message Uuid {
+    ///     string uuid_str = 1;
+    /// }
+    /// ```
+    ///
+    /// The `uuid` crate implements some traits for `Uuid`, and publicly exports it:
+    ///
+    /// ```rust,ignore
     /// // lib.rs in the uuid crate
     ///
     /// include!(concat!(env!("OUT_DIR"), "/uuid.rs"));
@@ -549,6 +1852,35 @@ impl Config {
         self
     }
 
+    /// Generates conversions between the messages of two versions of a package.
+    ///
+    /// Each message of `package` which has a message with the same name in `other_package`
+    /// implements `TryFrom` for it, and the other way around, so that code migrating between the
+    /// versions of an API, such as `.my.api.v1` and `.my.api.v2`, does not need to convert each
+    /// field by hand. Both packages must be compiled by the same `Config`.
+    ///
+    /// Fields are matched by field number, as they are on the wire: the message is encoded and
+    /// decoded as the other message. Fields which only exist in one of the versions are dropped,
+    /// unless the target message preserves unknown fields, and fields whose types changed
+    /// compatibly, such as from `int32` to `int64`, are converted. The conversion fails with a
+    /// [`prost::DecodeError`] if a set field has an incompatible type in the other version.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config.version_conversion(".my.api.v1", ".my.api.v2");
+    /// ```
+    pub fn version_conversion<P1, P2>(&mut self, package: P1, other_package: P2) -> &mut Self
+    where
+        P1: Into<String>,
+        P2: Into<String>,
+    {
+        self.version_conversions
+            .push((package.into(), other_package.into()));
+        self
+    }
+
     /// When set, the `FileDescriptorSet` generated by `protoc` is written to the provided
     /// filesystem path.
     ///
@@ -616,50 +1948,425 @@ impl Config {
     ///     .skip_source_info()
     ///     .compile_protos(&["src/items.proto"], &["src/"]);
     /// ```
-    pub fn skip_source_info(&mut self) -> &mut Self {
-        self.skip_source_info = true;
-        self
-    }
-
-    /// Configures the code generator to not strip the enum name from variant names.
+    pub fn skip_source_info(&mut self) -> &mut Self {
+        self.skip_source_info = true;
+        self
+    }
+
+    /// Skips running `protoc` and generating code in [`compile_protos`](Self::compile_protos) when
+    /// nothing changed since the previous run.
+    ///
+    /// Cargo reruns a build script whenever any file of its package changes, which re-invokes
+    /// `protoc` for every `.proto` file. With this option, `compile_protos` writes a cache file
+    /// to the output directory, holding a digest of the contents of the `.proto` files and of the
+    /// files they import from the include directories, of this configuration, of the build script
+    /// executable, and of the versions of `prost-build` and `protoc`. A later run with the same
+    /// digest, whose generated files all still exist, returns without running `protoc`.
+    ///
+    /// Generated files are only written when their content changes, so caching is also safe when
+    /// writing the generated code outside of `OUT_DIR` with [`out_dir`](Self::out_dir), such as to
+    /// a source directory under version control. Files imported from the include directory of
+    /// `protoc` itself are covered by its version. The cache is not used with
+    /// [`skip_protoc_run`](Self::skip_protoc_run).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Result;
+    /// fn main() -> Result<()> {
+    ///     prost_build::Config::new()
+    ///         .enable_codegen_cache()
+    ///         .compile_protos(&["src/items.proto"], &["src/"])?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn enable_codegen_cache(&mut self) -> &mut Self {
+        self.codegen_cache = true;
+        self
+    }
+
+    /// Parse `.proto` files with the parser built into `prost-build`, instead of running `protoc`.
+    ///
+    /// This removes the need for a `protoc` binary, for example in CI or when cross-compiling.
+    /// The parser supports files using the `proto2` and `proto3` syntaxes, and bundles the
+    /// well-known types in `google/protobuf`, which are used when they are not found in the include
+    /// directories. It does not support editions, and does not interpret custom options, so it
+    /// can't be used with [`heapless_messages`](Self::heapless_messages). The `protoc_arg`
+    /// arguments are ignored.
+    ///
+    /// The parsed file descriptor set is still written to
+    /// [`file_descriptor_set_path`](Self::file_descriptor_set_path) if set.
+    ///
+    /// In `build.rs`:
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Result;
+    /// fn main() -> Result<()> {
+    ///     prost_build::Config::new()
+    ///         .use_native_parser()
+    ///         .compile_protos(&["src/items.proto"], &["src/"])?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn use_native_parser(&mut self) -> &mut Self {
+        self.native_parser = true;
+        self
+    }
+
+    /// Configures the code generator to not strip the enum name from variant names.
+    ///
+    /// Protobuf enum definitions commonly include the enum name as a prefix of every variant name.
+    /// This style is non-idiomatic in Rust, so by default `prost` strips the enum name prefix from
+    /// variants which include it. Configuring this option prevents `prost` from stripping the
+    /// prefix.
+    pub fn retain_enum_prefix(&mut self) -> &mut Self {
+        self.strip_enum_prefix = false;
+        self
+    }
+
+    /// Configures the output directory where generated Rust files will be written.
+    ///
+    /// If unset, defaults to the `OUT_DIR` environment variable. `OUT_DIR` is set by Cargo when
+    /// executing build scripts, so `out_dir` typically does not need to be configured.
+    pub fn out_dir<P>(&mut self, path: P) -> &mut Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.out_dir = Some(path.into());
+        self
+    }
+
+    /// Configures what filename protobufs with no package definition are written to.
+    /// The filename will be appended with the `.rs` extension.
+    pub fn default_package_filename<S>(&mut self, filename: S) -> &mut Self
+    where
+        S: Into<String>,
+    {
+        self.default_package_filename = filename.into();
+        self
+    }
+
+    /// Configures how the generated code of each package is split into files.
+    ///
+    /// By default, the code of a package is written to a single file, which grows with the
+    /// number of messages of the package. With [`Layout::FilePerMessage`], the code of each
+    /// top-level message, including its nested types, is written to a file of its own, which the
+    /// file of the package includes. The files of the messages of package `foo.bar` are written to
+    /// the `foo.bar` directory next to `foo.bar.rs`, and named after the messages, such as
+    /// `foo.bar/Shirt.rs`.
+    ///
+    /// The package files are used exactly as with the default layout, so
+    /// [`include_file`](Self::include_file) and `include!` of the package files keep working.
+    /// The layout applies to the files written by [`compile_protos`](Self::compile_protos) and
+    /// [`compile_fds`](Self::compile_fds); [`generate`](Self::generate) always returns the code
+    /// of each package as a whole.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config.output_layout(prost_build::Layout::FilePerMessage);
+    /// ```
+    pub fn output_layout(&mut self, layout: Layout) -> &mut Self {
+        self.output_layout = layout;
+        self
+    }
+
+    /// Configures the code generator to include type names.
+    ///
+    /// Message types will implement `Name` trait, which provides type and package name.
+    /// This is needed for encoding messages as `Any` type.
+    pub fn enable_type_names(&mut self) -> &mut Self {
+        self.enable_type_names = true;
+        self
+    }
+
+    /// Configures the code generator to support the Protobuf JSON mapping.
+    ///
+    /// Message types will implement the `prost::json::JsonMessage` trait, which converts messages
+    /// to and from their [canonical JSON representation][1]. This requires the `json` feature of
+    /// `prost`, and of `prost-types` if well-known types are used.
+    ///
+    /// [1]: https://protobuf.dev/programming-guides/json/
+    pub fn enable_json(&mut self) -> &mut Self {
+        self.enable_json = true;
+        self
+    }
+
+    /// Configures the code generator to derive serde's `Serialize` and `Deserialize` traits,
+    /// following the [Protobuf JSON mapping][1].
+    ///
+    /// Messages, oneofs and enums derive the serde traits through the `serde` re-export of
+    /// `prost`, which requires its `serde` feature, so the generated code does not depend on
+    /// `serde` directly. The derived representation is that of the JSON mapping:
+    ///
+    /// - Fields are renamed to their JSON names, such as `displayName`, and their Protobuf names
+    ///   are accepted as aliases.
+    /// - Enumeration values are serialized as the names of their values, and names or numbers
+    ///   are accepted.
+    /// - The fields of a oneof are flattened into the message.
+    /// - 64-bit integers are serialized as strings, bytes as base64 strings, and non-finite
+    ///   floats as `"NaN"`, `"Infinity"` or `"-Infinity"`.
+    /// - Fields without presence which have their default value, and unset optional fields,
+    ///   are omitted.
+    ///
+    /// The conversions are done by the adapters in `prost::json::serde`. Unknown fields and
+    /// extensions are skipped. Lazy fields and heapless messages are not supported, and types
+    /// from [`extern_path`](Self::extern_path), such as the well-known types of `prost-types`,
+    /// must implement the serde traits themselves.
+    ///
+    /// Unlike [`enable_json`](Self::enable_json), which implements the mapping without serde,
+    /// the serde implementations work with any serde data format.
+    ///
+    /// [1]: https://protobuf.dev/programming-guides/json/
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config.enable_serde();
+    /// ```
+    pub fn enable_serde(&mut self) -> &mut Self {
+        self.enable_serde = true;
+        self
+    }
+
+    /// Configures the code generator to support the Protobuf text format.
+    ///
+    /// Message types will implement the `prost::text_format::TextFormatMessage` trait, which
+    /// converts messages to and from the [text format][1] used for golden files, debugging output
+    /// and configuration files. This requires the `text-format` feature of `prost`, and of
+    /// `prost-types` if well-known types are used.
+    ///
+    /// [1]: https://protobuf.dev/reference/protobuf/textformat-spec/
+    pub fn enable_text_format(&mut self) -> &mut Self {
+        self.enable_text_format = true;
+        self
+    }
+
+    /// Configures the code generator to implement `Debug` for messages with the Protobuf text
+    /// format, instead of the output of a derived `Debug`.
+    ///
+    /// Fields are printed with their Protobuf names, enumeration values by name and bytes as
+    /// escaped strings, so that logs are comparable with those of other Protobuf runtimes. `{:?}`
+    /// prints the message on a single line and `{:#?}` with one field per line. Sensitive fields,
+    /// with the `debug_redact` option, are left out. This implies
+    /// [`enable_text_format`](Self::enable_text_format), and message types must implement
+    /// `Clone`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let request = HelloRequest { name: "Ferris".into(), kind: Kind::Crab as i32 };
+    /// assert_eq!(format!("{request:?}"), r#"name: "Ferris" kind: KIND_CRAB"#);
+    /// ```
+    pub fn text_format_debug(&mut self) -> &mut Self {
+        self.text_format_debug = true;
+        self
+    }
+
+    /// Configures the code generator to support field masks.
+    ///
+    /// Message types will implement the `prost::field_mask::FieldMaskMessage` trait, which allows
+    /// a `prost_types::FieldMask` to be validated against, applied to and merged into messages of
+    /// the type. They also implement `prost::diff::DiffMessage`, which allows the field mask of
+    /// the differences between two messages to be computed with `prost_types::FieldMask::diff`.
+    /// Message and oneof types must implement `Clone`, and the well-known types used by the
+    /// messages must come from `prost-types`, or also be generated with field mask support.
+    pub fn enable_field_masks(&mut self) -> &mut Self {
+        self.enable_field_masks = true;
+        self
+    }
+
+    /// Configures the code generator to support runtime reflection.
+    ///
+    /// Message types will implement the `prost_types::reflect::ReflectMessage` trait, which
+    /// returns the `MessageDescriptor` of the message type and gets and sets fields by number as
+    /// `prost_types::reflect::Value`s, so that generic code such as redaction or logging filters
+    /// can operate on any message. This requires the `reflect` feature of `prost-types`.
+    ///
+    /// The file descriptors are embedded as with
+    /// [`embed_file_descriptor_sets`](Self::embed_file_descriptor_sets), and each generated
+    /// package module also gets a `DESCRIPTOR_POOL` static from which the descriptors are taken.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use prost_types::reflect::{ReflectMessage, Value};
+    ///
+    /// let mut request = helloworld::HelloRequest::default();
+    /// request.set_field_by_number(1, Value::String("Ferris".into()));
+    /// assert_eq!(request.name, "Ferris");
+    /// assert_eq!(request.descriptor().full_name(), "helloworld.HelloRequest");
+    /// ```
+    pub fn enable_reflection(&mut self) -> &mut Self {
+        self.enable_reflection = true;
+        self
+    }
+
+    /// Configures the code generator to generate builders for messages.
+    ///
+    /// For each message `Foo`, a `FooBuilder` type is generated, which is returned by
+    /// `Foo::builder()`. The builder has a setter for each field, which accepts any value that
+    /// converts `Into` the field type and wraps it in `Some` or `Box` as needed. Repeated and map
+    /// fields also get `push_` and `insert_` methods which add a single value, and each field of
+    /// a oneof gets a setter which selects it. `FooBuilder::build` returns the message, or a
+    /// [`BuildError`](prost::BuildError) if a proto2 `required` field has not been set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let person = Person::builder()
+    ///     .name("Ferris")
+    ///     .id(7)
+    ///     .push_emails("ferris@example.com")
+    ///     .build()?;
+    /// ```
+    pub fn generate_builders(&mut self, enabled: bool) -> &mut Self {
+        self.generate_builders = enabled;
+        self
+    }
+
+    /// Configures the code generator to generate accessors for the oneofs of messages.
+    ///
+    /// For each field `bar` of a oneof `foo`, the message gets a `foo_as_bar` method, which
+    /// returns a reference to the value of `bar` if it is the field which is set, and a
+    /// `set_foo_bar` method, which sets it. The `which_foo` method returns the Protobuf name of
+    /// the field which is set, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut shape = Shape::default();
+    /// shape.set_kind_circle(Circle { radius: 1.0 });
+    /// assert_eq!(shape.which_kind(), Some("circle"));
+    /// assert_eq!(shape.kind_as_circle().map(|circle| circle.radius), Some(1.0));
+    /// assert_eq!(shape.kind_as_square(), None);
+    /// ```
+    pub fn generate_oneof_accessors(&mut self, enabled: bool) -> &mut Self {
+        self.generate_oneof_accessors = enabled;
+        self
+    }
+
+    /// Configures the code generator to implement the standard conversion traits between messages
+    /// and their encoding.
+    ///
+    /// Each message implements `TryFrom<&[u8]>` and `TryFrom<Bytes>`, which decode the message,
+    /// and `Vec<u8>` implements `From` for the message, which encodes it. This makes messages
+    /// usable with APIs which are generic over these traits, such as the column and key types of
+    /// database clients. Heapless messages are left out, since they are used without an allocator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let bytes = Vec::<u8>::from(shirt.clone());
+    /// assert_eq!(Shirt::try_from(bytes.as_slice())?, shirt);
+    /// ```
+    pub fn generate_conversions(&mut self, enabled: bool) -> &mut Self {
+        self.generate_conversions = enabled;
+        self
+    }
+
+    /// Configures the code generator to implement [`arbitrary::Arbitrary`] for messages, oneofs
+    /// and enums, for property testing and fuzzing.
+    ///
+    /// The generated code requires the `arbitrary` feature of `prost`. Arbitrary enumeration
+    /// fields hold variants of their enum, and nested messages are generated up to the recursion
+    /// limit of decoding, so that every arbitrary message can be encoded and decoded again. The
+    /// types of fields replaced with [`message_type`](Self::message_type),
+    /// [`bytes_type`](Self::bytes_type) or [`string_type`](Self::string_type) must implement
+    /// `Arbitrary` themselves. Heapless messages are left out, since their field types don't
+    /// implement it.
+    ///
+    /// Arbitrary `float` and `double` fields may be NaN, which is not equal to itself, so a
+    /// round-trip property should compare the encodings of messages with such fields.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use prost::arbitrary::{Arbitrary, Unstructured};
+    ///
+    /// let mut u = Unstructured::new(input);
+    /// let message = Shirt::arbitrary(&mut u)?;
+    /// assert_eq!(Shirt::decode(message.encode_to_vec().as_slice())?, message);
+    /// ```
     ///
-    /// Protobuf enum definitions commonly include the enum name as a prefix of every variant name.
-    /// This style is non-idiomatic in Rust, so by default `prost` strips the enum name prefix from
-    /// variants which include it. Configuring this option prevents `prost` from stripping the
-    /// prefix.
-    pub fn retain_enum_prefix(&mut self) -> &mut Self {
-        self.strip_enum_prefix = false;
+    /// [`arbitrary::Arbitrary`]: https://docs.rs/arbitrary/latest/arbitrary/trait.Arbitrary.html
+    pub fn generate_arbitrary(&mut self, enabled: bool) -> &mut Self {
+        self.generate_arbitrary = enabled;
         self
     }
 
-    /// Configures the output directory where generated Rust files will be written.
+    /// Configures the code generator to generate constants for the field numbers of messages.
     ///
-    /// If unset, defaults to the `OUT_DIR` environment variable. `OUT_DIR` is set by Cargo when
-    /// executing build scripts, so `out_dir` typically does not need to be configured.
-    pub fn out_dir<P>(&mut self, path: P) -> &mut Self
-    where
-        P: Into<PathBuf>,
-    {
-        self.out_dir = Some(path.into());
+    /// For each field `bar` of a message `Foo`, including the fields of its oneofs, `Foo` gets a
+    /// `BAR_FIELD_NUMBER: u32` constant. `Foo::fields()` returns the
+    /// [`FieldInfo`](prost::wire::FieldInfo) of each field, with its Protobuf name, its number
+    /// and the wire type it is encoded with, ordered by number. Code which handles encoded
+    /// messages with [`prost::wire`], or builds field masks, can then refer to fields by name
+    /// instead of by their numbers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use prost::wire::FieldIter;
+    ///
+    /// let colors = FieldIter::new(&buf)
+    ///     .filter_map(Result::ok)
+    ///     .filter(|field| field.number == Shirt::COLOR_FIELD_NUMBER)
+    ///     .count();
+    /// assert!(Shirt::fields().iter().any(|field| field.name == "color"));
+    /// ```
+    pub fn generate_field_numbers(&mut self, enabled: bool) -> &mut Self {
+        self.generate_field_numbers = enabled;
         self
     }
 
-    /// Configures what filename protobufs with no package definition are written to.
-    /// The filename will be appended with the `.rs` extension.
-    pub fn default_package_filename<S>(&mut self, filename: S) -> &mut Self
-    where
-        S: Into<String>,
-    {
-        self.default_package_filename = filename.into();
+    /// Configures the code generator to embed the file descriptors of the compiled `.proto`
+    /// files.
+    ///
+    /// Each generated package module gets a `FILE_DESCRIPTOR_SET: &[u8]` constant holding the
+    /// encoded `FileDescriptorSet` of the `.proto` files of the package and of the files they
+    /// import, so that gRPC server reflection and dynamic clients can describe the types without
+    /// running `protoc` again. If an [`include_file`](Self::include_file) is configured, it also
+    /// gets a `FILE_DESCRIPTOR_SETS: &[&[u8]]` constant which lists the constants of all modules.
+    ///
+    /// The constants can be registered with a reflection service, or added to a
+    /// `prost_types::reflect::DescriptorPool` with `add_encoded_file_descriptor_set`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut pool = prost_types::reflect::DescriptorPool::new();
+    /// pool.add_encoded_file_descriptor_set(helloworld::FILE_DESCRIPTOR_SET)?;
+    /// ```
+    pub fn embed_file_descriptor_sets(&mut self, enabled: bool) -> &mut Self {
+        self.embed_file_descriptor_sets = enabled;
         self
     }
 
-    /// Configures the code generator to include type names.
+    /// Configures the code generator to embed the descriptor of each message in its type.
     ///
-    /// Message types will implement `Name` trait, which provides type and package name.
-    /// This is needed for encoding messages as `Any` type.
-    pub fn enable_type_names(&mut self) -> &mut Self {
-        self.enable_type_names = true;
+    /// Each message gets a `DESCRIPTOR_PROTO: &'static [u8]` constant holding its encoded
+    /// `DescriptorProto`, including its nested types, and a `FULL_NAME: &'static str` constant
+    /// holding its fully qualified Protobuf name, such as `helloworld.HelloRequest`. This lets
+    /// runtime systems introspect the schema of a concrete type without carrying the file
+    /// descriptor sets of [`embed_file_descriptor_sets`](Self::embed_file_descriptor_sets). The
+    /// descriptor is that of the `.proto` file; the types of its fields refer to other types by
+    /// their fully qualified names, which it does not describe.
+    ///
+    /// The name is a constant, rather than a method, so that it does not shadow the `full_name`
+    /// method of `prost::Name`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let descriptor = prost_types::DescriptorProto::decode(HelloRequest::DESCRIPTOR_PROTO)?;
+    /// assert_eq!(descriptor.name(), "HelloRequest");
+    /// assert_eq!(HelloRequest::FULL_NAME, "helloworld.HelloRequest");
+    /// ```
+    pub fn embed_message_descriptors(&mut self, enabled: bool) -> &mut Self {
+        self.embed_message_descriptors = enabled;
         self
     }
 
@@ -794,6 +2501,70 @@ impl Config {
         self
     }
 
+    /// Gates the module of a package in the [include file](Self::include_file) behind a Cargo
+    /// feature.
+    ///
+    /// The `pub mod` of the package, which also holds the modules of its sub-packages, gets a
+    /// `#[cfg(feature = "...")]` attribute, so that crates compiling a large schema can let their
+    /// users opt into the packages they need. The generated files of all packages are still
+    /// written.
+    ///
+    /// The generated code of a package refers to the types of the packages it imports, so the
+    /// feature of a package must enable the features of the packages it depends on, in the
+    /// `[features]` of the crate, e.g. `pubsub = ["iam"]`.
+    ///
+    /// # Arguments
+    ///
+    /// **`package`** - the fully-qualified name of a Protobuf package, such as `.my.package`.
+    ///
+    /// **`feature`** - the name of the Cargo feature enabling the package.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config
+    ///     .include_file("mod.rs")
+    ///     .include_file_feature(".google.cloud.storage", "storage")
+    ///     .include_file_feature(".google.cloud.pubsub", "pubsub");
+    /// ```
+    pub fn include_file_feature<P, F>(&mut self, package: P, feature: F) -> &mut Self
+    where
+        P: Into<String>,
+        F: Into<String>,
+    {
+        self.include_file_features
+            .push((package.into(), feature.into()));
+        self
+    }
+
+    /// Configures the [include file](Self::include_file) to re-export the types of extern paths.
+    ///
+    /// For each path configured with [`extern_path`](Self::extern_path), the module of its
+    /// Protobuf package gets a `pub use` of the Rust path, so that the module tree matches the
+    /// package hierarchy of the schema, including the packages which are not generated. A path
+    /// whose last component starts with an uppercase letter, such as `.uuid.Uuid`, is taken to be
+    /// a type and is re-exported by its name; other paths are taken to be packages and all of
+    /// their items are re-exported. Paths of nested types and paths into generated packages are
+    /// not re-exported, as the generated code already defines them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let mut config = prost_build::Config::new();
+    /// config
+    ///     .extern_path(".uuid.Uuid", "::uuid::Uuid")
+    ///     .extern_path(".common", "::common_protos")
+    ///     .include_file("mod.rs")
+    ///     // Generates `pub mod uuid { pub use ::uuid::Uuid as Uuid; }` and
+    ///     // `pub mod common { pub use ::common_protos::*; }` in `mod.rs`.
+    ///     .include_file_extern_paths(true);
+    /// ```
+    pub fn include_file_extern_paths(&mut self, enabled: bool) -> &mut Self {
+        self.include_file_extern_paths = enabled;
+        self
+    }
+
     // IMPROVEMENT: https://github.com/tokio-rs/prost/pull/1022/files#r1563818651
     /// Configures the code generator to format the output code via `prettyplease`.
     ///
@@ -824,17 +2595,26 @@ impl Config {
     /// }
     /// ```
     pub fn compile_fds(&mut self, fds: FileDescriptorSet) -> Result<()> {
-        let mut target_is_env = false;
-        let target: PathBuf = self.out_dir.clone().map(Ok).unwrap_or_else(|| {
-            env::var_os("OUT_DIR")
+        self.write_fds(fds).map(|_| ())
+    }
+
+    /// Returns the output directory, and whether it is `OUT_DIR`.
+    fn target(&self) -> Result<(PathBuf, bool)> {
+        match &self.out_dir {
+            Some(out_dir) => Ok((out_dir.clone(), false)),
+            None => env::var_os("OUT_DIR")
+                .map(|val| (val.into(), true))
                 .ok_or_else(|| {
                     Error::new(ErrorKind::Other, "OUT_DIR environment variable is not set")
-                })
-                .map(|val| {
-                    target_is_env = true;
-                    Into::into(val)
-                })
-        })?;
+                }),
+        }
+    }
+
+    /// Generates and writes the code of a file descriptor set, returning the paths of the written
+    /// files.
+    fn write_fds(&mut self, fds: FileDescriptorSet) -> Result<Vec<PathBuf>> {
+        let (target, target_is_env) = self.target()?;
+        let mut outputs = Vec::new();
 
         let requests = fds
             .file
@@ -857,7 +2637,7 @@ impl Config {
             })
             .collect::<HashMap<Module, String>>();
 
-        let modules = self.generate(requests)?;
+        let (modules, message_files) = self.generate_modules(requests, self.output_layout)?;
         for (module, content) in &modules {
             let file_name = file_names
                 .get(module)
@@ -865,24 +2645,32 @@ impl Config {
             let output_path = target.join(file_name);
 
             write_file_if_changed(&output_path, content.as_bytes())?;
+            outputs.push(output_path);
+        }
+        for (file_name, content) in &message_files {
+            let output_path = target.join(file_name);
+            if let Some(parent) = output_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            write_file_if_changed(&output_path, content.as_bytes())?;
+            outputs.push(output_path);
         }
 
         if let Some(ref include_file) = self.include_file {
             let path = target.join(include_file);
             trace!("Writing include file: {}", path.display());
-            let mut buffer = Vec::new();
-            self.write_line(&mut buffer, 0, "// This file is @generated by prost-build.")?;
-            self.write_includes(
+            let buffer = self.include_file_contents(
                 modules.keys().collect(),
-                &mut buffer,
                 if target_is_env { None } else { Some(&target) },
                 &file_names,
             )?;
 
             write_file_if_changed(&path, &buffer)?;
+            outputs.push(path);
         }
 
-        Ok(())
+        Ok(outputs)
     }
 
     /// Loads `.proto` files as a [`FileDescriptorSet`]. This allows inspection of the descriptors
@@ -920,6 +2708,10 @@ impl Config {
         protos: &[impl AsRef<Path>],
         includes: &[impl AsRef<Path>],
     ) -> Result<FileDescriptorSet> {
+        if self.native_parser && !self.skip_protoc_run {
+            return self.parse_fds(protos, includes);
+        }
+
         let tmp;
         let file_descriptor_set_path = if let Some(path) = &self.file_descriptor_set_path {
             path.clone()
@@ -959,6 +2751,20 @@ impl Config {
                 cmd.arg("-I").arg(protoc_include);
             }
 
+            // Make the `(prost.heapless)` option available to the protos of heapless messages.
+            let heapless_include;
+            if !self.heapless_messages.matchers.is_empty() {
+                heapless_include = tempfile::Builder::new()
+                    .prefix("prost-build-heapless")
+                    .tempdir()?;
+                fs::create_dir(heapless_include.path().join("prost"))?;
+                fs::write(
+                    heapless_include.path().join("prost").join("heapless.proto"),
+                    HEAPLESS_PROTO,
+                )?;
+                cmd.arg("-I").arg(heapless_include.path());
+            }
+
             for arg in &self.protoc_args {
                 cmd.arg(arg);
             }
@@ -1009,6 +2815,26 @@ impl Config {
         Ok(file_descriptor_set)
     }
 
+    /// Loads the `FileDescriptorSet` with the native parser, see `use_native_parser`.
+    fn parse_fds(
+        &self,
+        protos: &[impl AsRef<Path>],
+        includes: &[impl AsRef<Path>],
+    ) -> Result<FileDescriptorSet> {
+        if !self.heapless_messages.matchers.is_empty() {
+            return Err(Error::new(
+                ErrorKind::Other,
+                "heapless messages are not supported by the native parser",
+            ));
+        }
+
+        let file_descriptor_set = native_parser::parse(protos, includes, !self.skip_source_info)?;
+        if let Some(path) = &self.file_descriptor_set_path {
+            fs::write(path, file_descriptor_set.encode_to_vec())?;
+        }
+        Ok(file_descriptor_set)
+    }
+
     /// Compile `.proto` files into Rust files during a Cargo build with additional code generator
     /// configuration options.
     ///
@@ -1040,9 +2866,90 @@ impl Config {
         // this figured out.
         // [1]: https://doc.rust-lang.org/cargo/reference/build-scripts.html#outputs-of-the-build-script
 
+        if !self.codegen_cache || self.skip_protoc_run {
+            let file_descriptor_set = self.load_fds(protos, includes)?;
+            return self.compile_fds(file_descriptor_set);
+        }
+
+        // The key is digested before running `protoc`, so that a `.proto` file changed meanwhile
+        // is compiled again by the next run.
+        let key = self.cache_key(protos, includes);
+        let cache_path = self.target()?.0.join(cache::cache_file_name(protos));
+        if let Some(key) = &key {
+            if CacheEntry::read(&cache_path).is_some_and(|entry| entry.is_fresh(key)) {
+                debug!("generated code is up to date: {}", cache_path.display());
+                return Ok(());
+            }
+        }
+
         let file_descriptor_set = self.load_fds(protos, includes)?;
+        let dependencies = file_descriptor_set
+            .file
+            .iter()
+            .filter_map(|file| {
+                includes
+                    .iter()
+                    .map(|include| include.as_ref().join(file.name()))
+                    .find(|path| path.is_file())
+            })
+            .collect::<Vec<_>>();
+        let mut outputs = self.write_fds(file_descriptor_set)?;
+        outputs.extend(self.file_descriptor_set_path.clone());
+
+        if let Some(mut digest) = key {
+            if dependencies
+                .iter()
+                .all(|dependency| digest.add_file(dependency).is_ok())
+            {
+                let entry = CacheEntry {
+                    digest: digest.finish(),
+                    dependencies,
+                    outputs,
+                };
+                if let Err(error) = entry.write(&cache_path) {
+                    debug!("not caching generated code: {error}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the digest of the inputs of [`Self::compile_protos`], other than the imported
+    /// files, see [`Self::enable_codegen_cache`]. Returns `None` if an input can't be read, in
+    /// which case the code is always generated.
+    fn cache_key(
+        &self,
+        protos: &[impl AsRef<Path>],
+        includes: &[impl AsRef<Path>],
+    ) -> Option<Digest> {
+        let mut digest = Digest::new();
+        digest.add(env!("CARGO_PKG_VERSION").as_bytes());
+        digest.add(format!("{self:?}").as_bytes());
+        #[cfg(feature = "format")]
+        digest.add(&[self.fmt as u8]);
+
+        let build_script = env::current_exe().and_then(fs::metadata).ok()?;
+        digest
+            .add(format!("{:?} {}", build_script.modified().ok()?, build_script.len()).as_bytes());
+
+        if !self.native_parser {
+            let output = Command::new(&self.protoc_executable)
+                .arg("--version")
+                .output()
+                .ok()?;
+            digest.add(&output.stdout);
+            if let Some(protoc_include) = protoc_include_from_env() {
+                digest.add(protoc_include.as_os_str().as_encoded_bytes());
+            }
+        }
 
-        self.compile_fds(file_descriptor_set)
+        for include in includes {
+            digest.add(include.as_ref().as_os_str().as_encoded_bytes());
+        }
+        for proto in protos {
+            digest.add_file(proto.as_ref()).ok()?;
+        }
+        Some(digest)
     }
 
     pub(crate) fn prost_path_or_default(&self) -> &str {
@@ -1053,44 +2960,93 @@ impl Config {
         self.prost_types_path.as_deref().unwrap_or("::prost_types")
     }
 
+    /// Generates the contents of an include file for the given modules, as written to
+    /// [`Self::include_file`] by [`Self::compile_fds`].
+    ///
+    /// The generated file includes the files of the modules by their file names relative to the
+    /// include file, so the generated files must be placed in the same directory. This is used by
+    /// `protoc` plugins, which return the include file alongside the generated files instead of
+    /// writing them to `OUT_DIR`.
+    pub fn generate_include_file(&self, modules: Vec<&Module>) -> Result<String> {
+        let file_names = modules
+            .iter()
+            .map(|module| {
+                (
+                    (*module).clone(),
+                    module.to_file_name_or(&self.default_package_filename),
+                )
+            })
+            .collect::<HashMap<Module, String>>();
+        let buffer = self.include_file_contents(modules, Some(&PathBuf::new()), &file_names)?;
+        String::from_utf8(buffer).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+
+    fn include_file_contents(
+        &self,
+        modules: Vec<&Module>,
+        basepath: Option<&PathBuf>,
+        file_names: &HashMap<Module, String>,
+    ) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.write_line(&mut buffer, 0, "// This file is @generated by prost-build.")?;
+        self.write_includes(modules.clone(), &mut buffer, basepath, file_names)?;
+        if self.embed_file_descriptor_sets {
+            self.write_file_descriptor_sets(modules, &mut buffer)?;
+        }
+        Ok(buffer)
+    }
+
     pub(crate) fn write_includes(
         &self,
-        mut modules: Vec<&Module>,
+        modules: Vec<&Module>,
         outfile: &mut impl Write,
         basepath: Option<&PathBuf>,
         file_names: &HashMap<Module, String>,
     ) -> Result<()> {
-        modules.sort();
+        let mut items = modules
+            .iter()
+            .map(|module| {
+                let file_name = file_names
+                    .get(*module)
+                    .expect("every module should have a filename");
+                let line = if basepath.is_some() {
+                    format!("include!(\"{file_name}\");")
+                } else {
+                    format!("include!(concat!(env!(\"OUT_DIR\"), \"/{file_name}\"));")
+                };
+                ((*module).clone(), line)
+            })
+            .collect::<Vec<_>>();
+        if self.include_file_extern_paths {
+            items.extend(self.extern_path_reexports(&modules));
+        }
+        items.sort();
 
+        let features = self.include_file_feature_modules();
         let mut stack = Vec::new();
 
-        for module in modules {
+        for (module, line) in &items {
             while !module.starts_with(&stack) {
                 stack.pop();
                 self.write_line(outfile, stack.len(), "}")?;
             }
             while stack.len() < module.len() {
-                self.write_line(
-                    outfile,
-                    stack.len(),
-                    &format!("pub mod {} {{", module.part(stack.len())),
-                )?;
                 stack.push(module.part(stack.len()).to_owned());
-            }
-
-            let file_name = file_names
-                .get(module)
-                .expect("every module should have a filename");
-
-            if basepath.is_some() {
-                self.write_line(outfile, stack.len(), &format!("include!(\"{file_name}\");"))?;
-            } else {
+                if let Some(feature) = features.get(&Module::from_parts(&stack)) {
+                    self.write_line(
+                        outfile,
+                        stack.len() - 1,
+                        &format!("#[cfg(feature = \"{feature}\")]"),
+                    )?;
+                }
                 self.write_line(
                     outfile,
-                    stack.len(),
-                    &format!("include!(concat!(env!(\"OUT_DIR\"), \"/{file_name}\"));"),
+                    stack.len() - 1,
+                    &format!("pub mod {} {{", module.part(stack.len() - 1)),
                 )?;
             }
+
+            self.write_line(outfile, stack.len(), line)?;
         }
 
         for depth in (0..stack.len()).rev() {
@@ -1100,6 +3056,81 @@ impl Config {
         Ok(())
     }
 
+    /// Returns the `pub use` items re-exporting the extern paths into the modules of their
+    /// packages, see [`Self::include_file_extern_paths`].
+    fn extern_path_reexports(&self, modules: &[&Module]) -> Vec<(Module, String)> {
+        let mut reexports = Vec::new();
+        for (proto_path, rust_path) in &self.extern_paths {
+            let mut parts = proto_path
+                .split('.')
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>();
+            let type_index = parts
+                .iter()
+                .position(|part| part.starts_with(|c: char| c.is_ascii_uppercase()));
+            let line = match type_index {
+                None => format!("pub use {rust_path}::*;"),
+                Some(index) if index + 1 == parts.len() => {
+                    let name = to_upper_camel(parts.pop().unwrap());
+                    format!("pub use {rust_path} as {name};")
+                }
+                Some(_) => continue,
+            };
+
+            let module = Module::from_parts(parts.into_iter().map(to_snake));
+            if module.is_empty() || modules.contains(&&module) {
+                continue;
+            }
+            reexports.push((module, line));
+        }
+        reexports
+    }
+
+    /// Returns the features gating the modules of packages, see [`Self::include_file_feature`].
+    fn include_file_feature_modules(&self) -> HashMap<Module, &str> {
+        self.include_file_features
+            .iter()
+            .map(|(package, feature)| {
+                (
+                    Module::from_protobuf_package_name(package),
+                    feature.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    fn write_file_descriptor_sets(
+        &self,
+        mut modules: Vec<&Module>,
+        outfile: &mut impl Write,
+    ) -> Result<()> {
+        modules.sort();
+
+        let features = self.include_file_feature_modules();
+
+        self.write_line(
+            outfile,
+            0,
+            "/// The encoded `FileDescriptorSet`s of all packages, see `Config::embed_file_descriptor_sets`.",
+        )?;
+        self.write_line(outfile, 0, "pub const FILE_DESCRIPTOR_SETS: &[&[u8]] = &[")?;
+        for module in modules {
+            let path = module
+                .parts()
+                .chain(["FILE_DESCRIPTOR_SET"])
+                .collect::<Vec<_>>()
+                .join("::");
+            for len in 1..=module.len() {
+                let parent = Module::from_parts(module.parts().take(len));
+                if let Some(feature) = features.get(&parent) {
+                    self.write_line(outfile, 1, &format!("#[cfg(feature = \"{feature}\")]"))?;
+                }
+            }
+            self.write_line(outfile, 1, &format!("{path},"))?;
+        }
+        self.write_line(outfile, 0, "];")
+    }
+
     fn write_line(&self, outfile: &mut impl Write, depth: usize, line: &str) -> Result<()> {
         outfile.write_all(format!("{}{}\n", ("    ").to_owned().repeat(depth), line).as_bytes())
     }
@@ -1114,9 +3145,36 @@ impl Config {
         &mut self,
         requests: Vec<(Module, FileDescriptorProto)>,
     ) -> Result<HashMap<Module, String>> {
+        self.generate_modules(requests, Layout::FilePerPackage)
+            .map(|(modules, _)| modules)
+    }
+
+    /// Generates the code of the modules with the given layout.
+    fn generate_modules(
+        &mut self,
+        mut requests: Vec<(Module, FileDescriptorProto)>,
+        layout: Layout,
+    ) -> Result<GeneratedModules> {
         let mut modules = HashMap::new();
+        let mut message_files = Vec::new();
         let mut packages = HashMap::new();
 
+        let file_descriptor_sets = if self.embed_file_descriptor_sets || self.enable_reflection {
+            encode_file_descriptor_sets(&requests)
+        } else {
+            HashMap::new()
+        };
+        let message_descriptors = if self.embed_message_descriptors {
+            encode_message_descriptors(&requests)
+        } else {
+            HashMap::new()
+        };
+
+        for (_, file) in &mut requests {
+            editions::lower_features(file)
+                .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+        }
+
         let message_graph = MessageGraph::new(requests.iter().map(|x| &x.1));
         let extern_paths = ExternPaths::new(
             &self.extern_paths,
@@ -1125,7 +3183,7 @@ impl Config {
             self.prost_types,
         )
         .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
-        let mut context = Context::new(self, message_graph, extern_paths);
+        let mut context = Context::new(self, message_graph, extern_paths, message_descriptors);
 
         for (request_module, request_fd) in requests {
             // Only record packages that have services
@@ -1135,7 +3193,20 @@ impl Config {
             let buf = modules
                 .entry(request_module.clone())
                 .or_insert_with(String::new);
-            CodeGenerator::generate(&mut context, request_fd, buf);
+            let messages = CodeGenerator::generate(
+                &mut context,
+                request_fd,
+                buf,
+                layout == Layout::FilePerMessage,
+            );
+            let file_name =
+                request_module.to_file_name_or(&context.config().default_package_filename);
+            let directory = file_name.trim_end_matches(".rs");
+            for (message_name, content) in messages {
+                let message_file = format!("{directory}/{message_name}.rs");
+                buf.push_str(&format!("include!(\"{message_file}\");\n"));
+                message_files.push((message_file, content));
+            }
             if buf.is_empty() {
                 // Did not generate any code, remove from list to avoid inclusion in include file or output file list
                 modules.remove(&request_module);
@@ -1149,25 +3220,42 @@ impl Config {
             }
         }
 
-        #[cfg(feature = "format")]
-        if self.fmt {
-            for buf in modules.values_mut() {
+        for (module, file_descriptor_set) in file_descriptor_sets {
+            if let Some(buf) = modules.get_mut(&module) {
+                buf.push_str(
+                    "/// The encoded `FileDescriptorSet` of the `.proto` files of this package and of the files they import.\n",
+                );
+                buf.push_str("pub const FILE_DESCRIPTOR_SET: &[u8] = ");
+                buf.push_str(&byte_string_literal(&file_descriptor_set));
+                buf.push_str(";\n");
+                if self.enable_reflection {
+                    let reflect_path = format!("{}::reflect", self.prost_types_path_or_default());
+                    buf.push_str(
+                        "/// The descriptors of the message types of this package, see `Config::enable_reflection`.\n",
+                    );
+                    buf.push_str(&format!(
+                        "pub static DESCRIPTOR_POOL: {reflect_path}::EmbeddedDescriptorPool = {reflect_path}::EmbeddedDescriptorPool::new(FILE_DESCRIPTOR_SET);\n"
+                    ));
+                }
+            }
+        }
+
+        let bufs = modules
+            .values_mut()
+            .chain(message_files.iter_mut().map(|(_, content)| content));
+        for buf in bufs {
+            #[cfg(feature = "format")]
+            if self.fmt {
                 let file = syn::parse_file(buf).unwrap();
                 let formatted = prettyplease::unparse(&file);
                 *buf = formatted;
             }
-        }
-
-        self.add_generated_modules(&mut modules);
 
-        Ok(modules)
-    }
-
-    fn add_generated_modules(&mut self, modules: &mut HashMap<Module, String>) {
-        for buf in modules.values_mut() {
             let with_generated = "// This file is @generated by prost-build.\n".to_string() + buf;
             *buf = with_generated;
         }
+
+        Ok((modules, message_files))
     }
 }
 
@@ -1176,6 +3264,96 @@ impl Config {
 /// This function will create a file if it does not exist,
 /// and will entirely replace its contents if it does. When
 /// the contents is already correct, it doesn't touch to the file.
+/// Encodes the `FileDescriptorSet` of each module, which holds the files of the module and the
+/// files they import, in the order of the requests.
+fn encode_file_descriptor_sets(
+    requests: &[(Module, FileDescriptorProto)],
+) -> HashMap<Module, Vec<u8>> {
+    let mut file_descriptor_sets = HashMap::new();
+    for (module, _) in requests {
+        if file_descriptor_sets.contains_key(module) {
+            continue;
+        }
+
+        let mut names = requests
+            .iter()
+            .filter(|(request_module, _)| request_module == module)
+            .map(|(_, file)| file.name())
+            .collect::<Vec<_>>();
+        let mut index = 0;
+        while index < names.len() {
+            let name = names[index];
+            let dependencies = requests
+                .iter()
+                .filter(|(_, file)| file.name() == name)
+                .flat_map(|(_, file)| &file.dependency);
+            for dependency in dependencies {
+                if !names.contains(&dependency.as_str()) {
+                    names.push(dependency);
+                }
+            }
+            index += 1;
+        }
+
+        let file_descriptor_set = FileDescriptorSet {
+            file: requests
+                .iter()
+                .filter(|(_, file)| names.contains(&file.name()))
+                .map(|(_, file)| file.clone())
+                .collect(),
+        };
+        file_descriptor_sets.insert(module.clone(), file_descriptor_set.encode_to_vec());
+    }
+    file_descriptor_sets
+}
+
+/// Formats bytes as a Rust byte string literal.
+/// Encodes the `DescriptorProto` of each message of the requests, including nested messages, by
+/// the fully qualified name of the message.
+fn encode_message_descriptors(
+    requests: &[(Module, FileDescriptorProto)],
+) -> HashMap<String, Vec<u8>> {
+    fn encode(
+        scope: &str,
+        messages: &[DescriptorProto],
+        descriptors: &mut HashMap<String, Vec<u8>>,
+    ) {
+        for message in messages {
+            let fq_message_name = format!("{scope}.{}", message.name());
+            descriptors.insert(fq_message_name.clone(), message.encode_to_vec());
+            encode(&fq_message_name, &message.nested_type, descriptors);
+        }
+    }
+
+    let mut descriptors = HashMap::new();
+    for (_, file) in requests {
+        let scope = if file.package().is_empty() {
+            String::new()
+        } else {
+            format!(".{}", file.package())
+        };
+        encode(&scope, &file.message_type, &mut descriptors);
+    }
+    descriptors
+}
+
+pub(crate) fn byte_string_literal(bytes: &[u8]) -> String {
+    let mut literal = String::with_capacity(bytes.len() + 3);
+    literal.push_str("b\"");
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => {
+                literal.push('\\');
+                literal.push(byte as char);
+            }
+            b' '..=b'~' => literal.push(byte as char),
+            _ => literal.push_str(&format!("\\x{byte:02x}")),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
 fn write_file_if_changed(path: &Path, content: &[u8]) -> std::io::Result<()> {
     let previous_content = fs::read(path);
 
@@ -1196,27 +3374,78 @@ impl default::Default for Config {
         Config {
             file_descriptor_set_path: None,
             service_generator: None,
+            derive_providers: Vec::new(),
             map_type: PathMap::default(),
+            custom_map_type: PathMap::default(),
+            message_types: PathMap::default(),
             bytes_type: PathMap::default(),
+            custom_bytes_type: PathMap::default(),
+            string_type: PathMap::default(),
+            custom_string_type: PathMap::default(),
+            cow: PathMap::default(),
             type_attributes: PathMap::default(),
             message_attributes: PathMap::default(),
             enum_attributes: PathMap::default(),
+            oneof_attributes: PathMap::default(),
+            variant_attributes: PathMap::default(),
             field_attributes: PathMap::default(),
             boxed: PathMap::default(),
+            wrapper_types: PathMap::default(),
+            large_oneof_variant_size: None,
+            box_recursive: true,
+            recursive_wrapper: Wrapper::Box,
+            lazy: PathMap::default(),
+            presence_bits: PathMap::default(),
+            open_enums: PathMap::default(),
+            enum_display_from_str: PathMap::default(),
+            enum_from_str_ignore_case: PathMap::default(),
+            derive_eq_hash: PathMap::default(),
+            derive_ord: PathMap::default(),
+            proto_cmp: PathMap::default(),
+            float_total_order: PathMap::default(),
             prost_types: true,
             strip_enum_prefix: true,
             out_dir: None,
             extern_paths: Vec::new(),
+            version_conversions: Vec::new(),
             default_package_filename: "_".to_string(),
+            output_layout: Layout::default(),
             enable_type_names: false,
+            enable_json: false,
+            enable_serde: false,
+            enable_text_format: false,
+            text_format_debug: false,
+            enable_field_masks: false,
+            enable_reflection: false,
+            generate_builders: false,
+            generate_oneof_accessors: false,
+            generate_conversions: false,
+            generate_arbitrary: false,
+            generate_field_numbers: false,
+            embed_file_descriptor_sets: false,
+            embed_message_descriptors: false,
             type_name_domains: PathMap::default(),
             protoc_args: Vec::new(),
             protoc_executable: protoc_from_env(),
             disable_comments: PathMap::default(),
+            intra_doc_links: false,
             skip_debug: PathMap::default(),
+            unknown_fields: PathMap::default(),
+            extensions: PathMap::default(),
+            required_presence: PathMap::default(),
+            const_defaults: PathMap::default(),
+            non_exhaustive: PathMap::default(),
+            message_refs: PathMap::default(),
+            arena_messages: PathMap::default(),
+            validate_messages: PathMap::default(),
+            heapless_messages: PathMap::default(),
             skip_protoc_run: false,
             skip_source_info: false,
+            codegen_cache: false,
+            native_parser: false,
             include_file: None,
+            include_file_features: Vec::new(),
+            include_file_extern_paths: false,
             prost_path: None,
             prost_types_path: None,
             #[cfg(feature = "format")]
@@ -1230,21 +3459,83 @@ impl fmt::Debug for Config {
         fmt.debug_struct("Config")
             .field("file_descriptor_set_path", &self.file_descriptor_set_path)
             .field("service_generator", &self.service_generator.is_some())
+            .field("derive_providers", &self.derive_providers.len())
             .field("map_type", &self.map_type)
+            .field("custom_map_type", &self.custom_map_type)
+            .field("message_types", &self.message_types)
             .field("bytes_type", &self.bytes_type)
+            .field("custom_bytes_type", &self.custom_bytes_type)
+            .field("string_type", &self.string_type)
+            .field("custom_string_type", &self.custom_string_type)
+            .field("cow", &self.cow)
             .field("type_attributes", &self.type_attributes)
+            .field("message_attributes", &self.message_attributes)
+            .field("enum_attributes", &self.enum_attributes)
+            .field("oneof_attributes", &self.oneof_attributes)
+            .field("variant_attributes", &self.variant_attributes)
             .field("field_attributes", &self.field_attributes)
+            .field("boxed", &self.boxed)
+            .field("wrapper_types", &self.wrapper_types)
+            .field("large_oneof_variant_size", &self.large_oneof_variant_size)
+            .field("box_recursive", &self.box_recursive)
+            .field("recursive_wrapper", &self.recursive_wrapper)
             .field("prost_types", &self.prost_types)
             .field("strip_enum_prefix", &self.strip_enum_prefix)
             .field("out_dir", &self.out_dir)
             .field("extern_paths", &self.extern_paths)
+            .field("version_conversions", &self.version_conversions)
             .field("default_package_filename", &self.default_package_filename)
+            .field("output_layout", &self.output_layout)
             .field("enable_type_names", &self.enable_type_names)
+            .field("enable_json", &self.enable_json)
+            .field("enable_serde", &self.enable_serde)
+            .field("enable_text_format", &self.enable_text_format)
+            .field("text_format_debug", &self.text_format_debug)
+            .field("enable_field_masks", &self.enable_field_masks)
+            .field("enable_reflection", &self.enable_reflection)
+            .field("generate_builders", &self.generate_builders)
+            .field("generate_oneof_accessors", &self.generate_oneof_accessors)
+            .field("generate_conversions", &self.generate_conversions)
+            .field("generate_arbitrary", &self.generate_arbitrary)
+            .field("generate_field_numbers", &self.generate_field_numbers)
+            .field(
+                "embed_file_descriptor_sets",
+                &self.embed_file_descriptor_sets,
+            )
+            .field("embed_message_descriptors", &self.embed_message_descriptors)
             .field("type_name_domains", &self.type_name_domains)
             .field("protoc_args", &self.protoc_args)
+            .field("protoc_executable", &self.protoc_executable)
             .field("disable_comments", &self.disable_comments)
+            .field("intra_doc_links", &self.intra_doc_links)
             .field("skip_debug", &self.skip_debug)
+            .field("unknown_fields", &self.unknown_fields)
+            .field("extensions", &self.extensions)
+            .field("required_presence", &self.required_presence)
+            .field("const_defaults", &self.const_defaults)
+            .field("non_exhaustive", &self.non_exhaustive)
+            .field("message_refs", &self.message_refs)
+            .field("arena_messages", &self.arena_messages)
+            .field("lazy", &self.lazy)
+            .field("presence_bits", &self.presence_bits)
+            .field("open_enums", &self.open_enums)
+            .field("enum_display_from_str", &self.enum_display_from_str)
+            .field("enum_from_str_ignore_case", &self.enum_from_str_ignore_case)
+            .field("derive_eq_hash", &self.derive_eq_hash)
+            .field("derive_ord", &self.derive_ord)
+            .field("proto_cmp", &self.proto_cmp)
+            .field("float_total_order", &self.float_total_order)
+            .field("validate_messages", &self.validate_messages)
+            .field("heapless_messages", &self.heapless_messages)
+            .field("skip_protoc_run", &self.skip_protoc_run)
+            .field("skip_source_info", &self.skip_source_info)
+            .field("native_parser", &self.native_parser)
+            .field("codegen_cache", &self.codegen_cache)
+            .field("include_file", &self.include_file)
+            .field("include_file_features", &self.include_file_features)
+            .field("include_file_extern_paths", &self.include_file_extern_paths)
             .field("prost_path", &self.prost_path)
+            .field("prost_types_path", &self.prost_types_path)
             .finish()
     }
 }
@@ -1268,14 +3559,34 @@ pub fn error_message_protoc_not_found() -> String {
 }
 
 /// Returns the path to the `protoc` binary.
+///
+/// With the `vendored-protoc` feature, this is the vendored `protoc` unless `PROTOC` is set.
 pub fn protoc_from_env() -> PathBuf {
     env::var_os("PROTOC")
         .map(PathBuf::from)
-        .unwrap_or(PathBuf::from("protoc"))
+        .unwrap_or_else(default_protoc)
+}
+
+#[cfg(feature = "vendored-protoc")]
+fn default_protoc() -> PathBuf {
+    PathBuf::from(env!("PROST_VENDORED_PROTOC"))
+}
+
+#[cfg(not(feature = "vendored-protoc"))]
+fn default_protoc() -> PathBuf {
+    PathBuf::from("protoc")
 }
 
 /// Returns the path to the Protobuf include directory.
+///
+/// With the `vendored-protoc` feature, this is the include directory of the vendored `protoc`
+/// unless `PROTOC` or `PROTOC_INCLUDE` is set.
 pub fn protoc_include_from_env() -> Option<PathBuf> {
+    #[cfg(feature = "vendored-protoc")]
+    if env::var_os("PROTOC").is_none() && env::var_os("PROTOC_INCLUDE").is_none() {
+        return Some(PathBuf::from(env!("PROST_VENDORED_PROTOC_INCLUDE")));
+    }
+
     let protoc_include: PathBuf = env::var_os("PROTOC_INCLUDE")?.into();
 
     if !protoc_include.exists() {
@@ -1382,6 +3693,8 @@ mod tests {
 
     #[test]
     fn test_error_unset_out_dir() {
+        // Cargo sets `OUT_DIR` for the tests of crates with a build script.
+        env::remove_var("OUT_DIR");
         let mut config = Config::new();
 
         let err = config
@@ -1389,4 +3702,44 @@ mod tests {
             .unwrap_err();
         assert_eq!(err.to_string(), "OUT_DIR environment variable is not set")
     }
+
+    #[test]
+    fn test_embed_file_descriptor_sets() {
+        let file = |name: &str, package: &str, dependency: &[&str]| FileDescriptorProto {
+            name: Some(name.to_string()),
+            package: Some(package.to_string()),
+            dependency: dependency.iter().map(|name| name.to_string()).collect(),
+            ..Default::default()
+        };
+        let requests = [
+            file("base.proto", "base", &[]),
+            file("common.proto", "common", &["base.proto"]),
+            file("a.proto", "app", &["common.proto"]),
+            file("b.proto", "app", &[]),
+        ]
+        .map(|file| (Module::from_protobuf_package_name(file.package()), file));
+
+        let sets = encode_file_descriptor_sets(&requests);
+        let names = |package: &str| {
+            let set = FileDescriptorSet::decode(
+                sets[&Module::from_protobuf_package_name(package)].as_slice(),
+            )
+            .unwrap();
+            set.file
+                .into_iter()
+                .map(|file| file.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("base"), ["base.proto"]);
+        assert_eq!(names("common"), ["base.proto", "common.proto"]);
+        assert_eq!(
+            names("app"),
+            ["base.proto", "common.proto", "a.proto", "b.proto"]
+        );
+
+        assert_eq!(
+            byte_string_literal(b"a\"\\\x00\n\xff"),
+            r#"b"a\"\\\x00\x0a\xff""#
+        );
+    }
 }
@@ -1,13 +1,24 @@
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 use prost_types::{
     field_descriptor_proto::{Label, Type},
-    FieldDescriptorProto,
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto,
 };
 
 use crate::extern_paths::ExternPaths;
 use crate::message_graph::MessageGraph;
-use crate::{BytesType, Config, MapType, ServiceGenerator};
+use crate::path::PathMap;
+use crate::{BytesType, Config, MapType, ServiceGenerator, StringType, Wrapper};
+
+/// The comparison traits implemented by a generated message or oneof, besides `PartialEq`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Comparisons {
+    /// `Eq` and `Hash`.
+    EqHash,
+    /// `Eq`, `Hash`, `PartialOrd` and `Ord`.
+    Ord,
+}
 
 /// The context providing all the global information needed to generate code.
 /// It also provides a more disciplined access to Config
@@ -20,6 +31,7 @@ pub struct Context<'a> {
     message_graph: MessageGraph,
     extern_paths: ExternPaths,
     prost_path_attribute: Option<String>,
+    message_descriptors: HashMap<String, Vec<u8>>,
 }
 
 impl<'a> Context<'a> {
@@ -27,6 +39,7 @@ impl<'a> Context<'a> {
         config: &'a mut Config,
         message_graph: MessageGraph,
         extern_paths: ExternPaths,
+        message_descriptors: HashMap<String, Vec<u8>>,
     ) -> Self {
         let prost_path_attribute = config
             .prost_path
@@ -38,6 +51,7 @@ impl<'a> Context<'a> {
             message_graph,
             extern_paths,
             prost_path_attribute,
+            message_descriptors,
         }
     }
 
@@ -49,6 +63,45 @@ impl<'a> Context<'a> {
         self.config.service_generator.as_deref_mut()
     }
 
+    /// Returns the attributes the derive providers generate for a message.
+    pub fn provided_message_attributes(
+        &self,
+        fq_message_name: &str,
+        message: &DescriptorProto,
+    ) -> Vec<String> {
+        self.config
+            .derive_providers
+            .iter()
+            .flat_map(|provider| provider.message_attributes(fq_message_name, message))
+            .collect()
+    }
+
+    /// Returns the attributes the derive providers generate for an enum.
+    pub fn provided_enum_attributes(
+        &self,
+        fq_enum_name: &str,
+        enum_type: &EnumDescriptorProto,
+    ) -> Vec<String> {
+        self.config
+            .derive_providers
+            .iter()
+            .flat_map(|provider| provider.enum_attributes(fq_enum_name, enum_type))
+            .collect()
+    }
+
+    /// Returns the attributes the derive providers generate for a field.
+    pub fn provided_field_attributes(
+        &self,
+        fq_message_name: &str,
+        field: &FieldDescriptorProto,
+    ) -> Vec<String> {
+        self.config
+            .derive_providers
+            .iter()
+            .flat_map(|provider| provider.field_attributes(fq_message_name, field))
+            .collect()
+    }
+
     pub fn prost_path(&self) -> &str {
         self.config.prost_path_or_default()
     }
@@ -61,6 +114,37 @@ impl<'a> Context<'a> {
         self.extern_paths.resolve_ident(pb_ident)
     }
 
+    /// Returns the fully-qualified names of the messages of other versions of the package of
+    /// `fq_message_name` with the same name, configured with `Config::version_conversion`.
+    pub(crate) fn version_counterparts(&self, fq_message_name: &str) -> Vec<String> {
+        self.config
+            .version_conversions
+            .iter()
+            .flat_map(|(package, other_package)| {
+                [(package, other_package), (other_package, package)]
+            })
+            .filter_map(|(package, other_package)| {
+                let name = fq_message_name.strip_prefix(package.as_str())?;
+                name.starts_with('.')
+                    .then(|| format!("{other_package}{name}"))
+            })
+            .filter(|other| self.message_graph.get_message(other).is_some())
+            .collect()
+    }
+
+    /// Returns the encoded `DescriptorProto` of the named message, if it is embedded with
+    /// `Config::embed_message_descriptors`.
+    pub(crate) fn message_descriptor(&self, fq_message_name: &str) -> Option<&[u8]> {
+        self.message_descriptors
+            .get(fq_message_name)
+            .map(Vec::as_slice)
+    }
+
+    /// Returns `true` if `fq_type_name` is the name of a message or enum type.
+    pub(crate) fn contains_type(&self, fq_type_name: &str) -> bool {
+        self.message_graph.contains_type(fq_type_name)
+    }
+
     /// Returns an iterator over the additional attributes configured
     /// for the named type.
     pub fn type_attributes(&self, fq_type_name: &str) -> impl Iterator<Item = &str> {
@@ -88,6 +172,28 @@ impl<'a> Context<'a> {
             .map(|s| s.as_str())
     }
 
+    /// Returns an iterator over the additional attributes configured
+    /// for the named oneof.
+    pub fn oneof_attributes(&self, fq_oneof_name: &str) -> impl Iterator<Item = &str> {
+        self.config
+            .oneof_attributes
+            .get(fq_oneof_name)
+            .map(|s| s.as_str())
+    }
+
+    /// Returns an iterator over the additional attributes configured
+    /// for the named variant of an enum or oneof.
+    pub fn variant_attributes(
+        &self,
+        fq_type_name: &str,
+        variant_name: &str,
+    ) -> impl Iterator<Item = &str> {
+        self.config
+            .variant_attributes
+            .get_field(fq_type_name, variant_name)
+            .map(|s| s.as_str())
+    }
+
     /// Returns an iterator over the additional attributes configured
     /// for the named message field.
     pub fn field_attributes(
@@ -102,86 +208,380 @@ impl<'a> Context<'a> {
     }
 
     /// Returns the bytes type configured for the named message field.
+    ///
+    /// Custom bytes types configured with `Config::bytes_type` take precedence over `cow`, which
+    /// takes precedence over `bytes`.
     pub(crate) fn bytes_type(&self, fq_message_name: &str, field_name: &str) -> BytesType {
+        if let Some(path) = self
+            .config
+            .custom_bytes_type
+            .get_first_field(fq_message_name, field_name)
+        {
+            return BytesType::Custom(path.clone());
+        }
+        if self.should_use_cow(fq_message_name, field_name) {
+            return BytesType::Custom(format!(
+                "{}::alloc::borrow::Cow<'static, [u8]>",
+                self.prost_path()
+            ));
+        }
         self.config
             .bytes_type
             .get_first_field(fq_message_name, field_name)
-            .copied()
+            .cloned()
             .unwrap_or_default()
     }
 
+    /// Returns the string type configured for the named message field.
+    ///
+    /// Custom string types configured with `Config::string_type` take precedence over `cow`,
+    /// which takes precedence over `utf8_bytes`.
+    pub(crate) fn string_type(&self, fq_message_name: &str, field_name: &str) -> StringType {
+        if let Some(path) = self
+            .config
+            .custom_string_type
+            .get_first_field(fq_message_name, field_name)
+        {
+            return StringType::Custom(path.clone());
+        }
+        if self.should_use_cow(fq_message_name, field_name) {
+            return StringType::Custom(format!(
+                "{}::alloc::borrow::Cow<'static, str>",
+                self.prost_path()
+            ));
+        }
+        self.config
+            .string_type
+            .get_first_field(fq_message_name, field_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if the named `string` or `bytes` field is a `Cow`, as configured with
+    /// `Config::cow`.
+    fn should_use_cow(&self, fq_message_name: &str, field_name: &str) -> bool {
+        self.config
+            .cow
+            .get_first_field(fq_message_name, field_name)
+            .is_some()
+    }
+
+    /// Returns the Rust type configured with `Config::message_type` for the named message field,
+    /// which replaces the generated type of the field.
+    pub(crate) fn message_type(&self, fq_message_name: &str, field_name: &str) -> Option<&str> {
+        self.config
+            .message_types
+            .get_first_field(fq_message_name, field_name)
+            .map(|s| s.as_str())
+    }
+
+    /// Returns whether this enumeration field is generated as a `prost::OpenEnum`, as configured
+    /// with `Config::open_enums`.
+    ///
+    /// Fields whose type is replaced with `Config::message_type`, oneof fields, and map fields
+    /// keep their types.
+    pub(crate) fn should_use_open_enum(
+        &self,
+        fq_message_name: &str,
+        field: &FieldDescriptorProto,
+    ) -> bool {
+        field.r#type() == Type::Enum
+            && (field.oneof_index.is_none() || field.proto3_optional())
+            && self.message_type(fq_message_name, field.name()).is_none()
+            && self
+                .config
+                .open_enums
+                .get_first_field(fq_message_name, field.name())
+                .is_some()
+    }
+
     /// Returns the map type configured for the named message field.
+    ///
+    /// Custom map types configured with `Config::map_type` take precedence over `btree_map`.
     pub(crate) fn map_type(&self, fq_message_name: &str, field_name: &str) -> MapType {
+        if let Some(path) = self
+            .config
+            .custom_map_type
+            .get_first_field(fq_message_name, field_name)
+        {
+            return MapType::Custom(path.clone());
+        }
         self.config
             .map_type
             .get_first_field(fq_message_name, field_name)
-            .copied()
+            .cloned()
             .unwrap_or_default()
     }
 
-    /// Returns whether the Rust type for this message field needs to be `Box<_>`.
+    /// Returns whether the Rust type for this message field needs to be wrapped, in a `Box<_>` or
+    /// an `Arc<_>`.
     ///
-    /// This can be explicitly configured with `Config::boxed`, or necessary
-    /// to prevent an infinitely sized type definition in case when the type of
-    /// a non-repeated message field transitively contains the message itself.
+    /// This can be explicitly configured with `Config::boxed` or `Config::wrapper_type`, or
+    /// necessary to prevent an infinitely sized type definition in case when the type of a
+    /// non-repeated message field transitively contains the message itself.
     pub fn should_box_message_field(
         &self,
         fq_message_name: &str,
         field: &FieldDescriptorProto,
     ) -> bool {
-        self.should_box_impl(fq_message_name, None, field)
+        self.wrapper_impl(fq_message_name, None, field).is_some()
     }
 
-    /// Returns whether the Rust type for this field in the oneof needs to be `Box<_>`.
+    /// Returns whether the Rust type for this field in the oneof needs to be wrapped, in a
+    /// `Box<_>` or an `Arc<_>`.
     ///
-    /// This can be explicitly configured with `Config::boxed`, or necessary
-    /// to prevent an infinitely sized type definition in case when the type of
-    /// a non-repeated message field transitively contains the message itself.
+    /// This can be explicitly configured with `Config::boxed` or `Config::wrapper_type`, or
+    /// necessary to prevent an infinitely sized type definition in case when the type of a
+    /// non-repeated message field transitively contains the message itself.
     pub fn should_box_oneof_field(
         &self,
         fq_message_name: &str,
         oneof_name: &str,
         field: &FieldDescriptorProto,
     ) -> bool {
-        self.should_box_impl(fq_message_name, Some(oneof_name), field)
+        self.wrapper_impl(fq_message_name, Some(oneof_name), field)
+            .is_some()
     }
 
-    fn should_box_impl(
+    /// Returns the wrapper of this message field, or `None` if the field holds its message
+    /// directly.
+    pub fn message_field_wrapper(
+        &self,
+        fq_message_name: &str,
+        field: &FieldDescriptorProto,
+    ) -> Option<Wrapper> {
+        self.wrapper_impl(fq_message_name, None, field)
+    }
+
+    /// Returns the wrapper of this field in the oneof, or `None` if the variant holds its value
+    /// directly.
+    pub fn oneof_field_wrapper(
+        &self,
+        fq_message_name: &str,
+        oneof_name: &str,
+        field: &FieldDescriptorProto,
+    ) -> Option<Wrapper> {
+        self.wrapper_impl(fq_message_name, Some(oneof_name), field)
+    }
+
+    fn wrapper_impl(
         &self,
         fq_message_name: &str,
         oneof: Option<&str>,
         field: &FieldDescriptorProto,
-    ) -> bool {
+    ) -> Option<Wrapper> {
         if field.label() == Label::Repeated {
             // Repeated field are stored in Vec, therefore it is already heap allocated
-            return false;
-        }
-        let fd_type = field.r#type();
-        if (fd_type == Type::Message || fd_type == Type::Group)
-            && self
-                .message_graph
-                .is_nested(field.type_name(), fq_message_name)
-        {
-            return true;
+            return None;
         }
         let config_path = match oneof {
             None => Cow::Borrowed(fq_message_name),
             Some(oneof_name) => Cow::Owned(format!("{fq_message_name}.{oneof_name}")),
         };
+        let fd_type = field.r#type();
+        if fd_type == Type::Message || fd_type == Type::Group {
+            if let Some(wrapper) = self
+                .config
+                .wrapper_types
+                .get_first_field(&config_path, field.name())
+            {
+                return Some(*wrapper);
+            }
+        }
         if self
             .config
             .boxed
             .get_first_field(&config_path, field.name())
             .is_some()
         {
-            return true;
+            return Some(Wrapper::Box);
+        }
+        if (fd_type == Type::Message || fd_type == Type::Group)
+            && self
+                .message_graph
+                .is_nested(field.type_name(), fq_message_name)
+        {
+            return self
+                .config
+                .box_recursive
+                .then_some(self.config.recursive_wrapper);
+        }
+        if oneof.is_some() && self.is_large_oneof_field(fq_message_name, field) {
+            return Some(Wrapper::Box);
+        }
+        None
+    }
+
+    /// Returns whether this message field of a oneof is boxed because the estimated size of its
+    /// message exceeds the size configured with `Config::box_large_oneof_variants`.
+    pub fn is_large_oneof_field(
+        &self,
+        fq_message_name: &str,
+        field: &FieldDescriptorProto,
+    ) -> bool {
+        let Some(max_size) = self.config.large_oneof_variant_size else {
+            return false;
+        };
+        matches!(field.r#type(), Type::Message | Type::Group)
+            && !self.should_use_heapless(fq_message_name)
+            && self.message_type(fq_message_name, field.name()).is_none()
+            && self
+                .estimated_message_size(field.type_name())
+                .is_some_and(|size| size > max_size)
+    }
+
+    /// Returns an estimate of the size in bytes of the generated type of the named message on
+    /// 64-bit targets, or `None` if the message is not known.
+    ///
+    /// The estimate adds up the sizes of the fields, ignoring padding, the discriminants of
+    /// `Option`s and hidden fields. It terminates, since fields which would make the type
+    /// recursive are counted as boxed.
+    fn estimated_message_size(&self, fq_message_name: &str) -> Option<usize> {
+        let message = self.message_graph.get_message(fq_message_name)?;
+        let mut size = 0;
+        let mut oneofs = vec![None; message.oneof_decl.len()];
+        for field in &message.field {
+            match field.oneof_index {
+                Some(index) if !field.proto3_optional() => {
+                    let Some(oneof) = oneofs.get_mut(index as usize) else {
+                        continue;
+                    };
+                    let oneof_name = message.oneof_decl[index as usize].name();
+                    let field_size =
+                        if self.should_box_oneof_field(fq_message_name, oneof_name, field) {
+                            8
+                        } else {
+                            self.estimated_field_size(fq_message_name, field)
+                        };
+                    *oneof = Some(oneof.map_or(field_size, |size: usize| size.max(field_size)));
+                }
+                _ => size += self.estimated_field_size(fq_message_name, field),
+            }
         }
-        false
+        // A oneof is as large as its largest variant, and its discriminant.
+        let oneofs: usize = oneofs.into_iter().flatten().map(|oneof| oneof + 8).sum();
+        Some(size + oneofs)
+    }
+
+    /// Returns an estimate of the size in bytes of the generated type of a message field on
+    /// 64-bit targets.
+    fn estimated_field_size(&self, fq_message_name: &str, field: &FieldDescriptorProto) -> usize {
+        if field.label() == Label::Repeated {
+            let map = self
+                .message_graph
+                .get_message(field.type_name())
+                .and_then(|message| message.options.as_ref())
+                .is_some_and(|options| options.map_entry());
+            return match self.map_type(fq_message_name, field.name()) {
+                MapType::HashMap if map => 48,
+                _ => 24,
+            };
+        }
+        match field.r#type() {
+            Type::Message | Type::Group => {
+                // Recursive fields are counted as boxed even if `Config::box_recursive` is
+                // disabled, so that the estimate terminates.
+                if self.should_box_message_field(fq_message_name, field)
+                    || self.message_type(fq_message_name, field.name()).is_some()
+                    || self
+                        .message_graph
+                        .is_nested(field.type_name(), fq_message_name)
+                {
+                    8
+                } else {
+                    self.estimated_message_size(field.type_name()).unwrap_or(24)
+                }
+            }
+            Type::String => 24,
+            Type::Bytes => match self.bytes_type(fq_message_name, field.name()) {
+                BytesType::Bytes => 32,
+                _ => 24,
+            },
+            Type::Double
+            | Type::Int64
+            | Type::Uint64
+            | Type::Fixed64
+            | Type::Sfixed64
+            | Type::Sint64 => 8,
+            Type::Float
+            | Type::Int32
+            | Type::Uint32
+            | Type::Fixed32
+            | Type::Sfixed32
+            | Type::Sint32
+            | Type::Enum => 4,
+            Type::Bool => 1,
+        }
+    }
+
+    /// Returns whether the presence of this optional scalar field is tracked in the presence bits
+    /// of the message, instead of an `Option<_>`.
+    ///
+    /// This is configured with `Config::presence_bits`, and only applies to proto3 `optional`
+    /// scalar fields of messages without variants or implementations which expect such fields
+    /// to be `Option`s. With serde, the fields are only tracked in presence bits if the JSON
+    /// mapping is enabled as well, which then implements the serde traits of the message.
+    pub fn should_use_presence_bit(
+        &self,
+        fq_message_name: &str,
+        field: &FieldDescriptorProto,
+    ) -> bool {
+        field.proto3_optional()
+            && !matches!(field.r#type(), Type::Message | Type::Group)
+            && self.message_type(fq_message_name, field.name()).is_none()
+            && !self.should_use_open_enum(fq_message_name, field)
+            && (!self.config.enable_serde || self.config.enable_json)
+            && !self.should_generate_message_ref(fq_message_name)
+            && !self.should_generate_arena_message(fq_message_name)
+            && !self.should_validate(fq_message_name)
+            && self
+                .config
+                .presence_bits
+                .get_first_field(fq_message_name, field.name())
+                .is_some()
+    }
+
+    /// Returns whether the named message has fields whose presence is tracked in presence bits.
+    pub fn has_presence_bits(&self, fq_message_name: &str) -> bool {
+        self.message_graph
+            .get_message(fq_message_name)
+            .is_some_and(|message| {
+                message
+                    .field
+                    .iter()
+                    .any(|field| self.should_use_presence_bit(fq_message_name, field))
+            })
+    }
+
+    /// Returns whether the Rust type for this message field is wrapped in `Lazy<_>`.
+    ///
+    /// This is configured with `Config::lazy`, and only applies to message fields outside of
+    /// oneofs.
+    pub fn should_lazy_message_field(
+        &self,
+        fq_message_name: &str,
+        field: &FieldDescriptorProto,
+    ) -> bool {
+        field.r#type() == Type::Message
+            && (field.oneof_index.is_none() || field.proto3_optional())
+            && self
+                .config
+                .lazy
+                .get_first_field(fq_message_name, field.name())
+                .is_some()
     }
 
     /// Returns `true` if this message can automatically derive Copy trait.
     pub fn can_message_derive_copy(&self, fq_message_name: &str) -> bool {
         assert_eq!(".", &fq_message_name[..1]);
+        // The unknown field set is heap allocated.
+        if self.should_preserve_unknown_fields(fq_message_name) {
+            return false;
+        }
+        // So is the extension set.
+        if self.should_preserve_extensions(fq_message_name) {
+            return false;
+        }
         self.message_graph
             .get_message(fq_message_name)
             .unwrap()
@@ -202,10 +602,16 @@ impl<'a> Context<'a> {
         if field.label() == Label::Repeated {
             false
         } else if field.r#type() == Type::Message {
-            // nested and boxed messages cannot derive Copy
+            // nested, boxed, wrapped and lazy messages cannot derive Copy
             if self
                 .message_graph
                 .is_nested(field.type_name(), fq_message_name)
+                || self.should_lazy_message_field(fq_message_name, field)
+                || self
+                    .config
+                    .wrapper_types
+                    .get_first_field(fq_message_name, field.name())
+                    .is_some()
             {
                 return false;
             }
@@ -254,6 +660,11 @@ impl<'a> Context<'a> {
     pub fn can_field_derive_eq(&self, fq_message_name: &str, field: &FieldDescriptorProto) -> bool {
         assert_eq!(".", &fq_message_name[..1]);
 
+        // The traits implemented by the types of substituted fields are unknown.
+        if self.message_type(fq_message_name, field.name()).is_some() {
+            return false;
+        }
+
         if field.r#type() == Type::Message {
             if field.label() == Label::Repeated
                 || self
@@ -285,6 +696,217 @@ impl<'a> Context<'a> {
         }
     }
 
+    /// Returns the comparison traits which this message implements, as requested with
+    /// `Config::derive_eq_hash` and `Config::derive_ord`, or derived automatically.
+    pub(crate) fn message_comparisons(&self, fq_message_name: &str) -> Option<Comparisons> {
+        assert_eq!(".", &fq_message_name[..1]);
+        let requested = self.requested_comparisons(fq_message_name);
+        let msg = self.message_graph.get_message(fq_message_name).unwrap();
+        let can_derive = |comparisons| {
+            !self.should_use_heapless(fq_message_name)
+                && (comparisons == Comparisons::EqHash
+                    || !(self.should_preserve_unknown_fields(fq_message_name)
+                        || self.should_preserve_extensions(fq_message_name)))
+                && msg
+                    .field
+                    .iter()
+                    .all(|field| self.can_field_derive(fq_message_name, field, comparisons))
+        };
+        match requested {
+            Some(Comparisons::Ord) if can_derive(Comparisons::Ord) => Some(Comparisons::Ord),
+            Some(_) if can_derive(Comparisons::EqHash) => Some(Comparisons::EqHash),
+            _ if self.can_message_derive_eq(fq_message_name) => Some(Comparisons::EqHash),
+            _ => None,
+        }
+    }
+
+    /// Returns the comparison traits which a oneof of this message with the given fields
+    /// implements.
+    pub(crate) fn oneof_comparisons<'b>(
+        &self,
+        fq_message_name: &str,
+        fields: impl Iterator<Item = &'b FieldDescriptorProto> + Clone,
+    ) -> Option<Comparisons> {
+        let can_derive = |comparisons| {
+            fields
+                .clone()
+                .all(|field| self.can_field_derive(fq_message_name, field, comparisons))
+        };
+        match self.requested_comparisons(fq_message_name) {
+            Some(Comparisons::Ord) if can_derive(Comparisons::Ord) => Some(Comparisons::Ord),
+            Some(_) if can_derive(Comparisons::EqHash) => Some(Comparisons::EqHash),
+            _ if fields
+                .clone()
+                .all(|field| self.can_field_derive_eq(fq_message_name, field)) =>
+            {
+                Some(Comparisons::EqHash)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the comparison traits requested for this message with `Config::derive_eq_hash`
+    /// and `Config::derive_ord`.
+    fn requested_comparisons(&self, fq_message_name: &str) -> Option<Comparisons> {
+        if self.config.derive_ord.get(fq_message_name).next().is_some() {
+            Some(Comparisons::Ord)
+        } else if self
+            .config
+            .derive_eq_hash
+            .get(fq_message_name)
+            .next()
+            .is_some()
+        {
+            Some(Comparisons::EqHash)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the type of this field implements the comparison traits, in a message
+    /// which requested them.
+    fn can_field_derive(
+        &self,
+        fq_message_name: &str,
+        field: &FieldDescriptorProto,
+        comparisons: Comparisons,
+    ) -> bool {
+        // The traits implemented by the types of substituted fields are unknown.
+        if self.message_type(fq_message_name, field.name()).is_some()
+            || self.should_lazy_message_field(fq_message_name, field)
+        {
+            return false;
+        }
+
+        match field.r#type() {
+            Type::Message | Type::Group => {
+                let message = self.message_graph.get_message(field.type_name()).unwrap();
+                if message.options.as_ref().is_some_and(|o| o.map_entry()) {
+                    // Only `BTreeMap` implements `Hash` and `Ord`.
+                    // The key and value types are configured for the map field.
+                    self.map_type(fq_message_name, field.name()) == MapType::BTreeMap
+                        && message.field.iter().all(|entry_field| {
+                            let entry_field = FieldDescriptorProto {
+                                name: field.name.clone(),
+                                ..entry_field.clone()
+                            };
+                            self.can_field_derive(fq_message_name, &entry_field, comparisons)
+                        })
+                } else if self
+                    .message_graph
+                    .is_nested(field.type_name(), fq_message_name)
+                {
+                    false
+                } else if self.resolve_extern_ident(field.type_name()).is_some() {
+                    // The traits implemented by messages of other crates are unknown, but they
+                    // derive `Eq` and `Hash` if they can.
+                    comparisons == Comparisons::EqHash
+                        && self.can_message_derive_eq(field.type_name())
+                } else {
+                    self.message_comparisons(field.type_name()) >= Some(comparisons)
+                }
+            }
+            Type::Float | Type::Double => self.should_use_float_total_order(fq_message_name),
+            Type::String => !matches!(
+                self.string_type(fq_message_name, field.name()),
+                StringType::Custom(_)
+            ),
+            Type::Bytes => !matches!(
+                self.bytes_type(fq_message_name, field.name()),
+                BytesType::Custom(_)
+            ),
+            _ => true,
+        }
+    }
+
+    /// Returns `true` if the `float` and `double` fields of this message are compared by their
+    /// total order, as configured with `Config::float_total_order`.
+    pub(crate) fn should_use_float_total_order(&self, fq_message_name: &str) -> bool {
+        self.config
+            .float_total_order
+            .get(fq_message_name)
+            .next()
+            .is_some()
+    }
+
+    /// Returns whether the named message should implement `ProtoOrd`, as configured with
+    /// `Config::proto_cmp`, and the types of all of its fields implement it.
+    pub(crate) fn should_generate_proto_cmp(&self, fq_message_name: &str) -> bool {
+        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
+        self.can_message_proto_cmp(fq_message_name, &mut Vec::new())
+    }
+
+    /// Returns `true` if the named message can implement `ProtoOrd`, assuming that the messages
+    /// which are being checked, in `visiting`, do.
+    fn can_message_proto_cmp(&self, fq_message_name: &str, visiting: &mut Vec<String>) -> bool {
+        // A recursive message implements the trait if its other fields do.
+        if visiting.iter().any(|name| name == fq_message_name) {
+            return true;
+        }
+        if self.config.proto_cmp.get(fq_message_name).next().is_none()
+            || self.should_use_heapless(fq_message_name)
+        {
+            return false;
+        }
+        let Some(message) = self.message_graph.get_message(fq_message_name) else {
+            return false;
+        };
+        visiting.push(fq_message_name.to_string());
+        let can_compare = message
+            .field
+            .iter()
+            .all(|field| self.can_field_proto_cmp(fq_message_name, field, visiting));
+        visiting.pop();
+        can_compare
+    }
+
+    /// Returns `true` if the type of this field implements `ProtoOrd`.
+    fn can_field_proto_cmp(
+        &self,
+        fq_message_name: &str,
+        field: &FieldDescriptorProto,
+        visiting: &mut Vec<String>,
+    ) -> bool {
+        // The traits implemented by the types of substituted fields are unknown.
+        if self.message_type(fq_message_name, field.name()).is_some() {
+            return false;
+        }
+
+        match field.r#type() {
+            Type::Message | Type::Group => {
+                // The traits implemented by messages of other crates are unknown.
+                if self.resolve_extern_ident(field.type_name()).is_some() {
+                    return false;
+                }
+                let message = self.message_graph.get_message(field.type_name()).unwrap();
+                if message.options.as_ref().is_some_and(|o| o.map_entry()) {
+                    // The key and value types are configured for the map field.
+                    !matches!(
+                        self.map_type(fq_message_name, field.name()),
+                        MapType::Custom(_)
+                    ) && message.field.iter().all(|entry_field| {
+                        let entry_field = FieldDescriptorProto {
+                            name: field.name.clone(),
+                            ..entry_field.clone()
+                        };
+                        self.can_field_proto_cmp(fq_message_name, &entry_field, visiting)
+                    })
+                } else {
+                    self.can_message_proto_cmp(field.type_name(), visiting)
+                }
+            }
+            Type::String => !matches!(
+                self.string_type(fq_message_name, field.name()),
+                StringType::Custom(_)
+            ),
+            Type::Bytes => !matches!(
+                self.bytes_type(fq_message_name, field.name()),
+                BytesType::Custom(_)
+            ),
+            _ => true,
+        }
+    }
+
     pub fn should_disable_comments(&self, fq_message_name: &str, field_name: Option<&str>) -> bool {
         if let Some(field_name) = field_name {
             self.config
@@ -306,6 +928,235 @@ impl<'a> Context<'a> {
         self.config.skip_debug.get(fq_message_name).next().is_some()
     }
 
+    /// Returns whether the named enumeration should implement `Display` and `FromStr`.
+    pub fn should_generate_enum_str_impls(&self, fq_enum_name: &str) -> bool {
+        assert_eq!(b'.', fq_enum_name.as_bytes()[0]);
+        self.config
+            .enum_display_from_str
+            .get(fq_enum_name)
+            .next()
+            .is_some()
+    }
+
+    /// Returns whether the value names of the named enumeration should be parsed without regard
+    /// to case.
+    pub fn should_ignore_enum_case(&self, fq_enum_name: &str) -> bool {
+        assert_eq!(b'.', fq_enum_name.as_bytes()[0]);
+        self.config
+            .enum_from_str_ignore_case
+            .get(fq_enum_name)
+            .next()
+            .is_some()
+    }
+
+    /// Returns whether the required fields of the named message should track presence.
+    pub fn should_track_required_presence(&self, fq_message_name: &str) -> bool {
+        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
+        self.config
+            .required_presence
+            .get(fq_message_name)
+            .next()
+            .is_some()
+    }
+
+    /// Returns whether the named message should get a constant default, as configured with
+    /// `Config::const_defaults`, and all of its fields have a constant default.
+    pub fn should_generate_const_default(&self, fq_message_name: &str) -> bool {
+        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
+        self.config
+            .const_defaults
+            .get(fq_message_name)
+            .next()
+            .is_some()
+            && self.can_message_have_const_default(fq_message_name)
+    }
+
+    /// Returns whether the named message, oneof or enum should be non-exhaustive, as configured
+    /// with `Config::non_exhaustive_messages`.
+    pub fn should_be_non_exhaustive(&self, fq_name: &str) -> bool {
+        assert_eq!(b'.', fq_name.as_bytes()[0]);
+        self.config.non_exhaustive.get(fq_name).next().is_some()
+    }
+
+    fn can_message_have_const_default(&self, fq_message_name: &str) -> bool {
+        self.message_graph
+            .get_message(fq_message_name)
+            .is_some_and(|message| {
+                message
+                    .field
+                    .iter()
+                    .all(|field| self.can_field_have_const_default(fq_message_name, field))
+            })
+    }
+
+    /// Returns `true` if the generated type of this message field has a constant default.
+    fn can_field_have_const_default(
+        &self,
+        fq_message_name: &str,
+        field: &FieldDescriptorProto,
+    ) -> bool {
+        // Oneof fields are `None` by default.
+        if field.oneof_index.is_some() && !field.proto3_optional() {
+            return true;
+        }
+        let repeated = field.label() == Label::Repeated;
+        // Required fields without presence hold their default value, other fields with a default
+        // value are `None` by default.
+        let required = field.label() == Label::Required
+            && !self.should_track_required_presence(fq_message_name);
+
+        match field.r#type() {
+            Type::Message | Type::Group => {
+                let map_entry = self
+                    .message_graph
+                    .get_message(field.type_name())
+                    .and_then(|message| message.options.as_ref())
+                    .is_some_and(|options| options.map_entry());
+                if map_entry {
+                    self.map_type(fq_message_name, field.name()) == MapType::BTreeMap
+                } else if self.should_lazy_message_field(fq_message_name, field) {
+                    false
+                } else if required {
+                    self.message_type(fq_message_name, field.name()).is_none()
+                        && !self.should_box_message_field(fq_message_name, field)
+                        && self.resolve_extern_ident(field.type_name()).is_none()
+                        && self.should_generate_const_default(field.type_name())
+                } else {
+                    true
+                }
+            }
+            _ if self.message_type(fq_message_name, field.name()).is_some()
+                || self.should_use_open_enum(fq_message_name, field) =>
+            {
+                false
+            }
+            _ if repeated => true,
+            Type::String | Type::Bytes => {
+                let (custom, from_static) = if field.r#type() == Type::String {
+                    let string_type = self.string_type(fq_message_name, field.name());
+                    (
+                        matches!(string_type, StringType::Custom(_)),
+                        string_type == StringType::Utf8Bytes,
+                    )
+                } else {
+                    let bytes_type = self.bytes_type(fq_message_name, field.name());
+                    (
+                        matches!(bytes_type, BytesType::Custom(_)),
+                        bytes_type == BytesType::Bytes,
+                    )
+                };
+                // Only `Utf8Bytes` and `Bytes` values are created from a static default value.
+                !custom
+                    && (!required
+                        || field.default_value().is_empty()
+                        || (from_static && !self.should_use_heapless(fq_message_name)))
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns whether the named message should preserve unknown fields.
+    pub fn should_preserve_unknown_fields(&self, fq_message_name: &str) -> bool {
+        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
+        self.config
+            .unknown_fields
+            .get(fq_message_name)
+            .next()
+            .is_some()
+    }
+
+    /// Returns whether the named message declares extension ranges and should preserve its
+    /// extensions.
+    pub fn should_preserve_extensions(&self, fq_message_name: &str) -> bool {
+        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
+        self.config.extensions.get(fq_message_name).next().is_some()
+            && self
+                .message_graph
+                .get_message(fq_message_name)
+                .is_some_and(|message| !message.extension_range.is_empty())
+    }
+
+    /// Returns whether a borrowed view should be generated for the named message.
+    ///
+    /// Views are generated for matched messages whose message fields, including map values and
+    /// oneof fields, all refer to message types which get views as well.
+    pub fn should_generate_message_ref(&self, fq_message_name: &str) -> bool {
+        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
+        self.can_generate_variant(
+            fq_message_name,
+            &self.config.message_refs,
+            &mut HashSet::new(),
+        )
+    }
+
+    /// Returns whether an arena message should be generated for the named message.
+    ///
+    /// Arena messages are generated for matched messages whose message fields, including map
+    /// values and oneof fields, all refer to message types which get arena messages as well.
+    pub fn should_generate_arena_message(&self, fq_message_name: &str) -> bool {
+        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
+        self.can_generate_variant(
+            fq_message_name,
+            &self.config.arena_messages,
+            &mut HashSet::new(),
+        )
+    }
+
+    /// Returns whether a variant of the named message, such as a borrowed view, can be generated
+    /// for the messages matched by `paths`.
+    fn can_generate_variant(
+        &self,
+        fq_message_name: &str,
+        paths: &PathMap<()>,
+        visited: &mut HashSet<String>,
+    ) -> bool {
+        // Messages on a cycle are checked once; the other messages on the cycle decide.
+        if !visited.insert(fq_message_name.to_string()) {
+            return true;
+        }
+        if self.resolve_extern_ident(fq_message_name).is_some() {
+            return false;
+        }
+        let Some(message) = self.message_graph.get_message(fq_message_name) else {
+            return false;
+        };
+        // Map entries are not generated, and only need their value type to get a variant.
+        let map_entry = message
+            .options
+            .as_ref()
+            .is_some_and(|options| options.map_entry());
+        if !map_entry && paths.get(fq_message_name).next().is_none() {
+            return false;
+        }
+        message.field.iter().all(|field| match field.r#type() {
+            Type::Message | Type::Group => {
+                self.can_generate_variant(field.type_name(), paths, visited)
+            }
+            _ => true,
+        })
+    }
+
+    /// Returns whether a `Validate` implementation should be generated for the named message.
+    pub fn should_validate(&self, fq_message_name: &str) -> bool {
+        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
+        self.config
+            .validate_messages
+            .get(fq_message_name)
+            .next()
+            .is_some()
+            && self.resolve_extern_ident(fq_message_name).is_none()
+    }
+
+    /// Returns whether the named message should be generated with heapless fields.
+    pub fn should_use_heapless(&self, fq_message_name: &str) -> bool {
+        assert_eq!(b'.', fq_message_name.as_bytes()[0]);
+        self.config
+            .heapless_messages
+            .get(fq_message_name)
+            .next()
+            .is_some()
+    }
+
     /// Returns the type name domain URL for the named message,
     /// or an empty string if such is not configured.
     pub fn type_name_domain(&self, fq_message_name: &str) -> &str {
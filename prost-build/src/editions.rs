@@ -0,0 +1,171 @@
+//! Support for files using Protobuf Editions.
+//!
+//! The code generator describes fields in terms of the `proto2` and `proto3` syntaxes. Before code
+//! is generated for a file using editions, the resolved features of each field are lowered onto
+//! its descriptor, so that the field is described as it would be in a `proto2` or `proto3` file
+//! with the same semantics.
+//!
+//! The `enum_type` feature needs no lowering, because enumerations of `proto2` and `proto3` files
+//! are generated alike: enumeration fields hold an `i32`, which keeps unknown values.
+
+use prost_types::feature_set::{
+    FieldPresence, MessageEncoding, RepeatedFieldEncoding, Utf8Validation,
+};
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::{DescriptorProto, FeatureSet, FieldDescriptorProto, FileDescriptorProto};
+
+/// Lowers the features of a file using editions onto its field descriptors:
+///
+/// * Message fields with the `DELIMITED` message encoding become groups.
+/// * Fields with `LEGACY_REQUIRED` presence become required fields.
+/// * Other singular fields with `EXPLICIT` presence are marked as `proto3_optional`.
+/// * Repeated scalar fields set the `packed` option according to their repeated field encoding.
+/// * String fields with the `NONE` UTF-8 validation become bytes fields, because `String` fields
+///   always reject invalid UTF-8. The same applies to the values of map fields, but maps with
+///   string keys which are not validated are rejected.
+///
+/// Files using the `proto2` or `proto3` syntax are left unchanged.
+pub(crate) fn lower_features(file: &mut FileDescriptorProto) -> Result<(), String> {
+    if file.syntax() != "editions" {
+        return Ok(());
+    }
+
+    let edition = file.edition();
+    let features = FeatureSet::edition_defaults(edition)
+        .ok_or_else(|| {
+            format!(
+                "{} uses the unsupported edition {}",
+                file.name(),
+                edition.as_str_name()
+            )
+        })?
+        .resolve(file.options.as_ref().and_then(|o| o.features.as_ref()));
+
+    let scope = if file.package().is_empty() {
+        String::new()
+    } else {
+        format!(".{}", file.package())
+    };
+    for message in &mut file.message_type {
+        lower_message(&scope, message, &features)?;
+    }
+    for extension in &mut file.extension {
+        lower_field(extension, &features, false);
+    }
+    Ok(())
+}
+
+fn lower_message(
+    scope: &str,
+    message: &mut DescriptorProto,
+    features: &FeatureSet,
+) -> Result<(), String> {
+    let fq_message_name = format!("{scope}.{}", message.name());
+    let features = features.resolve(message.options.as_ref().and_then(|o| o.features.as_ref()));
+
+    // Map fields are always length-delimited, even when the delimited encoding is inherited, and
+    // the fields of map entries are generated by the code generator itself.
+    if message.options.as_ref().is_some_and(|o| o.map_entry()) {
+        return Ok(());
+    }
+    let map_entries = message
+        .nested_type
+        .iter()
+        .filter(|nested| nested.options.as_ref().is_some_and(|o| o.map_entry()))
+        .map(|nested| format!("{fq_message_name}.{}", nested.name()))
+        .collect::<Vec<_>>();
+
+    for field in &mut message.field {
+        let oneof_features = field
+            .oneof_index
+            .and_then(|index| message.oneof_decl.get(index as usize))
+            .and_then(|oneof| oneof.options.as_ref()?.features.as_ref());
+        let is_map = map_entries.iter().any(|entry| entry == field.type_name());
+        let field_features = features.resolve(oneof_features);
+        if is_map {
+            let entry_name = &field.type_name()[fq_message_name.len() + 1..];
+            let entry = message
+                .nested_type
+                .iter_mut()
+                .find(|nested| nested.name() == entry_name)
+                .expect("map entries are nested in the message of the map field");
+            lower_map_entry(&fq_message_name, field, entry, &field_features)?;
+        }
+        lower_field(field, &field_features, is_map);
+    }
+    for extension in &mut message.extension {
+        lower_field(extension, &features, false);
+    }
+    for nested in &mut message.nested_type {
+        lower_message(&fq_message_name, nested, &features)?;
+    }
+    Ok(())
+}
+
+/// Lowers the UTF-8 validation of a map field onto the key and value fields of its entry, which
+/// inherit the features of the map field.
+fn lower_map_entry(
+    fq_message_name: &str,
+    field: &FieldDescriptorProto,
+    entry: &mut DescriptorProto,
+    scope: &FeatureSet,
+) -> Result<(), String> {
+    let features = scope.resolve(field.options.as_ref().and_then(|o| o.features.as_ref()));
+    for entry_field in &mut entry.field {
+        let entry_features = features.resolve(
+            entry_field
+                .options
+                .as_ref()
+                .and_then(|o| o.features.as_ref()),
+        );
+        if entry_field.r#type() != Type::String
+            || entry_features.utf8_validation() != Utf8Validation::None
+        {
+            continue;
+        }
+        if entry_field.number() == 1 {
+            return Err(format!(
+                "{fq_message_name}.{} is a map with string keys whose UTF-8 validation is NONE, \
+                 which is not supported",
+                field.name()
+            ));
+        }
+        entry_field.set_type(Type::Bytes);
+    }
+    Ok(())
+}
+
+/// Lowers the features of a field, which inherits the features of its scope.
+fn lower_field(field: &mut FieldDescriptorProto, scope: &FeatureSet, is_map: bool) {
+    let features = scope.resolve(field.options.as_ref().and_then(|o| o.features.as_ref()));
+    if field.r#type() == Type::String && features.utf8_validation() == Utf8Validation::None {
+        field.set_type(Type::Bytes);
+    }
+    if field.r#type() == Type::Message
+        && !is_map
+        && features.message_encoding() == MessageEncoding::Delimited
+    {
+        field.set_type(Type::Group);
+    }
+
+    if field.label() == Label::Repeated {
+        if !matches!(
+            field.r#type(),
+            Type::String | Type::Bytes | Type::Message | Type::Group
+        ) {
+            field.options.get_or_insert_with(Default::default).packed =
+                Some(features.repeated_field_encoding() == RepeatedFieldEncoding::Packed);
+        }
+        return;
+    }
+
+    match features.field_presence() {
+        FieldPresence::LegacyRequired => field.set_label(Label::Required),
+        FieldPresence::Explicit
+            if field.oneof_index.is_none() && field.r#type() != Type::Message =>
+        {
+            field.proto3_optional = Some(true)
+        }
+        _ => (),
+    }
+}
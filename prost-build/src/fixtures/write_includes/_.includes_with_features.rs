@@ -0,0 +1,20 @@
+pub mod common {
+    pub use ::common_protos::*;
+}
+pub mod foo {
+    include!(concat!(env!("OUT_DIR"), "/foo.rs"));
+    #[cfg(feature = "bar")]
+    pub mod bar {
+        include!(concat!(env!("OUT_DIR"), "/foo.bar.rs"));
+        pub mod baz {
+            include!(concat!(env!("OUT_DIR"), "/foo.bar.baz.rs"));
+        }
+    }
+}
+#[cfg(feature = "qux")]
+pub mod qux {
+    include!(concat!(env!("OUT_DIR"), "/qux.rs"));
+}
+pub mod uuid {
+    pub use ::uuid::Uuid as Uuid;
+}
@@ -133,28 +133,49 @@
 //! ```
 //!
 //! [`protobuf-src`]: https://docs.rs/protobuf-src
+//!
+//! ### Vendored `protoc`
+//!
+//! With the `vendored-protoc` feature, `prost-build` builds a pinned version of `protoc` from
+//! source when it is compiled, and uses it unless `PROTOC` is set. This makes code generation
+//! reproducible without a system installation of `protoc`, but requires CMake and a C++ compiler.
+//! Set `PROST_PREBUILT_PROTOBUF=1` to download a checksum-verified prebuilt `protoc` for the host
+//! instead, falling back to the build from source if it is not available.
+//!
+//! ### Native parser
+//!
+//! [`Config::use_native_parser`] parses `.proto` files with a parser built into `prost-build`,
+//! without running `protoc` at all. It supports files using the `proto2` and `proto3` syntaxes,
+//! and bundles the well-known types, but does not support editions or custom options.
 
 use std::io::Result;
 use std::path::Path;
 
-use prost_types::FileDescriptorSet;
+use prost_types::{DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorSet};
 
 mod ast;
 pub use crate::ast::{Comments, Method, Service};
 
+mod cache;
 mod collections;
-pub(crate) use collections::{BytesType, MapType};
+pub(crate) use collections::{BytesType, MapType, StringType};
 
 mod code_generator;
 mod context;
+mod editions;
 mod extern_paths;
 mod ident;
 mod message_graph;
+mod native_parser;
 mod path;
 
+mod service;
+pub use service::ServiceDefinitionGenerator;
+
 mod config;
 pub use config::{
-    error_message_protoc_not_found, protoc_from_env, protoc_include_from_env, Config,
+    error_message_protoc_not_found, protoc_from_env, protoc_include_from_env, Config, Layout,
+    Wrapper,
 };
 
 mod module;
@@ -173,7 +194,8 @@ pub use module::Module;
 /// details like how errors are handled or if it is asynchronous. Then the user provides an
 /// implementation of the generated trait in the application code and plugs it into the framework.
 ///
-/// Such framework isn't part of Prost at present.
+/// Such framework isn't part of Prost at present, but [`ServiceDefinitionGenerator`] generates
+/// transport-agnostic descriptions of services which frameworks can build upon.
 pub trait ServiceGenerator {
     /// Generates a Rust interface or implementation for a service, writing the
     /// result to `buf`.
@@ -205,6 +227,83 @@ pub trait ServiceGenerator {
     fn finalize_package(&mut self, _package: &str, _buf: &mut String) {}
 }
 
+/// A derive provider generates attributes for messages, enums and fields from their descriptors.
+///
+/// `DeriveProvider` lets a crate attach the derives and helper attributes of a framework, such as
+/// a GraphQL or OpenAPI library, to the types they apply to. Rather than matching paths with
+/// [`Config::type_attribute`] calls, a provider decides from the descriptor of each type, which
+/// includes its custom options in the `_extensions` field of its options.
+///
+/// Derive providers are registered with a code generator using the `Config::derive_provider`
+/// method. The attributes of all providers are emitted in registration order, after the
+/// attributes configured by path.
+///
+/// # Example
+///
+/// ```rust
+/// use prost::UnknownFieldValue;
+/// use prost_build::DeriveProvider;
+/// use prost_types::DescriptorProto;
+///
+/// /// Derives `async_graphql::SimpleObject` for messages with the `(graphql.object) = true`
+/// /// custom option, declared with field number 50000.
+/// struct GraphQlObjects;
+///
+/// impl DeriveProvider for GraphQlObjects {
+///     fn message_attributes(&self, _: &str, message: &DescriptorProto) -> Vec<String> {
+///         let enabled = message.options.as_ref().is_some_and(|options| {
+///             let mut fields = options._extensions.fields().iter();
+///             fields.any(|field| field.tag == 50000 && field.value == UnknownFieldValue::Varint(1))
+///         });
+///         if enabled {
+///             vec!["#[derive(async_graphql::SimpleObject)]".to_string()]
+///         } else {
+///             Vec::new()
+///         }
+///     }
+/// }
+///
+/// let mut config = prost_build::Config::new();
+/// config.derive_provider(Box::new(GraphQlObjects));
+/// ```
+pub trait DeriveProvider {
+    /// Returns the attributes of the message `fq_message_name`, described by `message`.
+    ///
+    /// The default implementation returns no attributes.
+    fn message_attributes(
+        &self,
+        _fq_message_name: &str,
+        _message: &DescriptorProto,
+    ) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns the attributes of the enum `fq_enum_name`, described by `enum_type`.
+    ///
+    /// The default implementation returns no attributes.
+    fn enum_attributes(
+        &self,
+        _fq_enum_name: &str,
+        _enum_type: &EnumDescriptorProto,
+    ) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns the attributes of a field of the message `fq_message_name`, described by `field`.
+    ///
+    /// This is called for the fields of messages and for the variants generated for the fields
+    /// of oneofs.
+    ///
+    /// The default implementation returns no attributes.
+    fn field_attributes(
+        &self,
+        _fq_message_name: &str,
+        _field: &FieldDescriptorProto,
+    ) -> Vec<String> {
+        Vec::new()
+    }
+}
+
 /// Compile `.proto` files into Rust files during a Cargo build.
 ///
 /// The generated `.rs` files are written to the Cargo `OUT_DIR` directory, suitable for use with
@@ -405,6 +504,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn native_parser_smoke_test() {
+        let _ = env_logger::try_init();
+        let tempdir = tempfile::tempdir().unwrap();
+
+        Config::new()
+            .use_native_parser()
+            .service_generator(Box::new(ServiceTraitGenerator))
+            .out_dir(tempdir.path())
+            .compile_protos(&["src/fixtures/smoke_test/smoke_test.proto"], &["src"])
+            .unwrap();
+
+        assert_eq_fixture_file!(
+            if cfg!(feature = "format") {
+                "src/fixtures/smoke_test/_expected_smoke_test_formatted.rs"
+            } else {
+                "src/fixtures/smoke_test/_expected_smoke_test.rs"
+            },
+            tempdir.path().join("smoke_test.rs")
+        );
+    }
+
     #[test]
     fn finalize_package() {
         let _ = env_logger::try_init();
@@ -547,6 +668,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn native_parser_fixtures() {
+        let _ = env_logger::try_init();
+        let tempdir = tempfile::tempdir().unwrap();
+
+        Config::new()
+            .use_native_parser()
+            .out_dir(tempdir.path())
+            .boxed("Container.data.foo")
+            .boxed("Bar.qux")
+            .compile_protos(
+                &[
+                    "src/fixtures/field_attributes/field_attributes.proto",
+                    "src/fixtures/deprecated/all_deprecated.proto",
+                    "src/fixtures/imports_empty/imports_empty.proto",
+                ],
+                &[
+                    "src/fixtures/field_attributes",
+                    "src/fixtures/deprecated",
+                    "src/fixtures/imports_empty",
+                ],
+            )
+            .unwrap();
+
+        assert_eq_fixture_file!(
+            if cfg!(feature = "format") {
+                "src/fixtures/field_attributes/_expected_field_attributes_formatted.rs"
+            } else {
+                "src/fixtures/field_attributes/_expected_field_attributes.rs"
+            },
+            tempdir.path().join("field_attributes.rs")
+        );
+        assert_eq_fixture_file!(
+            "src/fixtures/deprecated/_all_deprecated.rs",
+            tempdir.path().join("all_deprecated.rs")
+        );
+        assert!(!tempdir.path().join("google.protobuf.rs").exists());
+    }
+
     #[test]
     fn deterministic_include_file() {
         let _ = env_logger::try_init();
@@ -607,6 +767,39 @@ mod tests {
         assert_eq_fixture_contents!("src/fixtures/write_includes/_.includes.rs", actual);
     }
 
+    #[test]
+    fn write_includes_with_features_and_extern_paths() {
+        let modules = [
+            Module::from_protobuf_package_name("foo"),
+            Module::from_protobuf_package_name("foo.bar"),
+            Module::from_protobuf_package_name("foo.bar.baz"),
+            Module::from_protobuf_package_name("qux"),
+        ];
+
+        let file_names = modules
+            .iter()
+            .map(|m| (m.clone(), m.to_file_name_or("_")))
+            .collect();
+
+        let mut buf = Vec::new();
+        Config::new()
+            .extern_path(".common", "::common_protos")
+            .extern_path(".foo.External", "::external::External")
+            .extern_path(".foo.bar.Generated", "::external::Generated")
+            .extern_path(".uuid.Uuid", "::uuid::Uuid")
+            .extern_path(".uuid.Uuid.Nested", "::uuid::Nested")
+            .include_file_feature(".foo.bar", "bar")
+            .include_file_feature(".qux", "qux")
+            .include_file_extern_paths(true)
+            .write_includes(modules.iter().collect(), &mut buf, None, &file_names)
+            .unwrap();
+        let actual = String::from_utf8(buf).unwrap();
+        assert_eq_fixture_contents!(
+            "src/fixtures/write_includes/_.includes_with_features.rs",
+            actual
+        );
+    }
+
     #[test]
     fn test_generate_deprecated() {
         let _ = env_logger::try_init();
@@ -625,4 +818,46 @@ mod tests {
             tempdir.path().join("all_deprecated.rs")
         );
     }
+
+    #[test]
+    fn test_codegen_cache() {
+        let _ = env_logger::try_init();
+        let tempdir = tempfile::tempdir().unwrap();
+        let proto_dir = tempdir.path().join("protos");
+        let out_dir = tempdir.path().join("out");
+        std::fs::create_dir_all(&proto_dir).unwrap();
+        std::fs::create_dir_all(&out_dir).unwrap();
+        let proto = proto_dir.join("cached.proto");
+        let output = out_dir.join("cached.rs");
+        std::fs::write(&proto, "syntax = \"proto3\"; package cached; message A {}").unwrap();
+
+        let compile = || {
+            Config::new()
+                .enable_codegen_cache()
+                .out_dir(&out_dir)
+                .compile_protos(&[&proto], &[&proto_dir])
+                .unwrap();
+        };
+
+        compile();
+        assert!(std::fs::read_to_string(&output)
+            .unwrap()
+            .contains("pub struct A"));
+
+        // Nothing changed, so the output is not generated again.
+        std::fs::write(&output, "// stale").unwrap();
+        compile();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "// stale");
+
+        std::fs::write(&proto, "syntax = \"proto3\"; package cached; message B {}").unwrap();
+        compile();
+        assert!(std::fs::read_to_string(&output)
+            .unwrap()
+            .contains("pub struct B"));
+
+        // A removed output is generated again.
+        std::fs::remove_file(&output).unwrap();
+        compile();
+        assert!(output.is_file());
+    }
 }
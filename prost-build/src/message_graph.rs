@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use petgraph::algo::has_path_connecting;
 use petgraph::graph::NodeIndex;
@@ -16,6 +16,7 @@ pub struct MessageGraph {
     index: HashMap<String, NodeIndex>,
     graph: Graph<String, ()>,
     messages: HashMap<String, DescriptorProto>,
+    enums: HashSet<String>,
 }
 
 impl MessageGraph {
@@ -24,6 +25,7 @@ impl MessageGraph {
             index: HashMap::new(),
             graph: Graph::new(),
             messages: HashMap::new(),
+            enums: HashSet::new(),
         };
 
         for file in files {
@@ -35,6 +37,11 @@ impl MessageGraph {
             for msg in &file.message_type {
                 msg_graph.add_message(&package, msg);
             }
+            for enum_type in &file.enum_type {
+                msg_graph
+                    .enums
+                    .insert(format!("{}.{}", package, enum_type.name()));
+            }
         }
 
         msg_graph
@@ -65,6 +72,11 @@ impl MessageGraph {
         }
         self.messages.insert(msg_name.clone(), msg.clone());
 
+        for enum_type in &msg.enum_type {
+            self.enums
+                .insert(format!("{}.{}", msg_name, enum_type.name()));
+        }
+
         for msg in &msg.nested_type {
             self.add_message(&msg_name, msg);
         }
@@ -75,6 +87,11 @@ impl MessageGraph {
         self.messages.get(message)
     }
 
+    /// Returns true if `name` is the fully-qualified name of a message or enum type.
+    pub fn contains_type(&self, name: &str) -> bool {
+        self.messages.contains_key(name) || self.enums.contains(name)
+    }
+
     /// Returns true if message type `inner` is nested in message type `outer`.
     pub fn is_nested(&self, outer: &str, inner: &str) -> bool {
         let outer = match self.index.get(outer) {
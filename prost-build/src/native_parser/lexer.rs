@@ -0,0 +1,336 @@
+//! Tokenizer for `.proto` files.
+
+use super::ParseError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Token {
+    Ident(String),
+    Int(u64),
+    Float(f64),
+    /// A string literal, with escapes already decoded.
+    Str(Vec<u8>),
+    Symbol(char),
+    /// A comment, in the format of the comments in `SourceCodeInfo`.
+    Comment(String),
+}
+
+/// A token with its zero-based position in the source.
+#[derive(Clone, Debug)]
+pub(super) struct Spanned {
+    pub token: Token,
+    pub line: usize,
+    pub col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    src: &'a str,
+    line: usize,
+    col: usize,
+}
+
+/// Splits the source of a `.proto` file into tokens, including comments.
+pub(super) fn tokenize(src: &str) -> Result<Vec<Spanned>, ParseError> {
+    let mut lexer = Lexer {
+        chars: src.char_indices().peekable(),
+        src,
+        line: 0,
+        col: 0,
+    };
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token()? {
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+impl Lexer<'_> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        let mut chars = self.chars.clone();
+        chars.next();
+        chars.next().map(|(_, c)| c)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let (_, c) = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.col = 0;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
+    }
+
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.src.len(), |&(i, _)| i)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.line, self.col, message)
+    }
+
+    fn next_token(&mut self) -> Result<Option<Spanned>, ParseError> {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+        let (line, col) = (self.line, self.col);
+        let Some(c) = self.peek() else {
+            return Ok(None);
+        };
+
+        let token = if c == '/' && self.peek_second() == Some('/') {
+            self.line_comment()
+        } else if c == '/' && self.peek_second() == Some('*') {
+            self.block_comment()?
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = self.offset();
+            while self
+                .peek()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                self.bump();
+            }
+            let end = self.offset();
+            Token::Ident(self.src[start..end].to_owned())
+        } else if c.is_ascii_digit()
+            || (c == '.' && self.peek_second().is_some_and(|c| c.is_ascii_digit()))
+        {
+            self.number()?
+        } else if c == '"' || c == '\'' {
+            self.string()?
+        } else {
+            self.bump();
+            Token::Symbol(c)
+        };
+
+        Ok(Some(Spanned {
+            token,
+            line,
+            col,
+            end_line: self.line,
+            end_col: self.col,
+        }))
+    }
+
+    fn line_comment(&mut self) -> Token {
+        self.bump();
+        self.bump();
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                break;
+            }
+            text.push(c);
+            self.bump();
+        }
+        text.push('\n');
+        Token::Comment(text)
+    }
+
+    fn block_comment(&mut self) -> Result<Token, ParseError> {
+        self.bump();
+        self.bump();
+        let mut raw = String::new();
+        loop {
+            match self.bump() {
+                Some('*') if self.peek() == Some('/') => {
+                    self.bump();
+                    break;
+                }
+                Some(c) => raw.push(c),
+                None => return Err(self.error("unterminated block comment")),
+            }
+        }
+        // Like protoc, strip the leading `*` of continuation lines.
+        let mut text = String::new();
+        for (i, line) in raw.split('\n').enumerate() {
+            if i > 0 {
+                text.push('\n');
+                let trimmed = line.trim_start();
+                text.push_str(trimmed.strip_prefix('*').unwrap_or(trimmed));
+            } else {
+                text.push_str(line);
+            }
+        }
+        Ok(Token::Comment(text))
+    }
+
+    fn number(&mut self) -> Result<Token, ParseError> {
+        let start = self.offset();
+        let mut is_float = false;
+        if self.peek() == Some('0') && matches!(self.peek_second(), Some('x' | 'X')) {
+            self.bump();
+            self.bump();
+            let digits = self.offset();
+            while self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                self.bump();
+            }
+            let end = self.offset();
+            return u64::from_str_radix(&self.src[digits..end], 16)
+                .map(Token::Int)
+                .map_err(|_| self.error("invalid hexadecimal integer"));
+        }
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() {
+                self.bump();
+            } else if c == '.' {
+                is_float = true;
+                self.bump();
+            } else if c == 'e' || c == 'E' {
+                is_float = true;
+                self.bump();
+                if matches!(self.peek(), Some('+' | '-')) {
+                    self.bump();
+                }
+            } else {
+                break;
+            }
+        }
+        let text = &self.src[start..self.offset()];
+        if matches!(self.peek(), Some('f' | 'F')) {
+            is_float = true;
+            self.bump();
+        }
+        if self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(self.error(format!("invalid number: {text}")));
+        }
+        if is_float {
+            text.parse()
+                .map(Token::Float)
+                .map_err(|_| self.error(format!("invalid float: {text}")))
+        } else if text.len() > 1 && text.starts_with('0') {
+            u64::from_str_radix(&text[1..], 8)
+                .map(Token::Int)
+                .map_err(|_| self.error(format!("invalid octal integer: {text}")))
+        } else {
+            text.parse()
+                .map(Token::Int)
+                .map_err(|_| self.error(format!("integer out of range: {text}")))
+        }
+    }
+
+    fn string(&mut self) -> Result<Token, ParseError> {
+        let quote = self.bump().unwrap();
+        let mut value = Vec::new();
+        loop {
+            let c = match self.bump() {
+                Some('\n') | None => return Err(self.error("unterminated string literal")),
+                Some(c) if c == quote => break,
+                Some(c) => c,
+            };
+            if c != '\\' {
+                let mut utf8 = [0; 4];
+                value.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+                continue;
+            }
+            let escape = self
+                .bump()
+                .ok_or_else(|| self.error("unterminated string literal"))?;
+            match escape {
+                'a' => value.push(0x07),
+                'b' => value.push(0x08),
+                'f' => value.push(0x0c),
+                'n' => value.push(b'\n'),
+                'r' => value.push(b'\r'),
+                't' => value.push(b'\t'),
+                'v' => value.push(0x0b),
+                '\\' | '\'' | '"' | '?' => value.push(escape as u8),
+                'x' | 'X' => {
+                    let digits = self.digits(16, 2);
+                    if digits.is_empty() {
+                        return Err(self.error("expected hex digits for escape sequence"));
+                    }
+                    value.push(u8::from_str_radix(&digits, 16).unwrap());
+                }
+                '0'..='7' => {
+                    let digits = escape.to_string() + &self.digits(8, 2);
+                    let byte = u32::from_str_radix(&digits, 8).unwrap();
+                    value.push(
+                        u8::try_from(byte)
+                            .map_err(|_| self.error("octal escape sequence out of range"))?,
+                    );
+                }
+                'u' | 'U' => {
+                    let len = if escape == 'u' { 4 } else { 8 };
+                    let digits = self.digits(16, len);
+                    let c = u32::from_str_radix(&digits, 16)
+                        .ok()
+                        .filter(|_| digits.len() == len)
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| self.error("invalid unicode escape sequence"))?;
+                    let mut utf8 = [0; 4];
+                    value.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+                }
+                _ => return Err(self.error(format!("invalid escape sequence: \\{escape}"))),
+            }
+        }
+        Ok(Token::Str(value))
+    }
+
+    fn digits(&mut self, radix: u32, max: usize) -> String {
+        let mut digits = String::new();
+        while digits.len() < max && self.peek().is_some_and(|c| c.is_digit(radix)) {
+            digits.push(self.bump().unwrap());
+        }
+        digits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(src: &str) -> Vec<Token> {
+        tokenize(src)
+            .unwrap()
+            .into_iter()
+            .map(|spanned| spanned.token)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokens("int32 foo_bar = 0x1F; // trailing\n"),
+            vec![
+                Token::Ident("int32".to_owned()),
+                Token::Ident("foo_bar".to_owned()),
+                Token::Symbol('='),
+                Token::Int(31),
+                Token::Symbol(';'),
+                Token::Comment(" trailing\n".to_owned()),
+            ]
+        );
+        assert_eq!(
+            tokens("1.5 1e3 .5 017 'a\\x41\\101\\n'"),
+            vec![
+                Token::Float(1.5),
+                Token::Float(1000.0),
+                Token::Float(0.5),
+                Token::Int(15),
+                Token::Str(b"aAA\n".to_vec()),
+            ]
+        );
+        assert_eq!(
+            tokens("/* block\n * comment */"),
+            vec![Token::Comment(" block\n comment ".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_errors() {
+        assert!(tokenize("\"unterminated").is_err());
+        assert!(tokenize("/* unterminated").is_err());
+        assert!(tokenize("12abc").is_err());
+    }
+}
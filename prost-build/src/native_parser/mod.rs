@@ -0,0 +1,432 @@
+//! A parser for `.proto` files, used instead of `protoc` by
+//! [`Config::use_native_parser`](crate::Config::use_native_parser).
+//!
+//! The parser supports files using the `proto2` and `proto3` syntaxes. Custom options are kept as
+//! uninterpreted options, and files using editions are rejected. The well-known types in
+//! `google/protobuf` are bundled, and used when they are not found in the include directories.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Component, Path, PathBuf};
+
+use prost_types::{FileDescriptorProto, FileDescriptorSet};
+
+mod lexer;
+mod parser;
+mod resolve;
+
+/// The `.proto` files bundled with `prost-build`.
+const BUNDLED_FILES: &[(&str, &str)] = &[
+    (
+        "google/protobuf/any.proto",
+        include_str!("../../proto/google/protobuf/any.proto"),
+    ),
+    (
+        "google/protobuf/api.proto",
+        include_str!("../../proto/google/protobuf/api.proto"),
+    ),
+    (
+        "google/protobuf/descriptor.proto",
+        include_str!("../../proto/google/protobuf/descriptor.proto"),
+    ),
+    (
+        "google/protobuf/duration.proto",
+        include_str!("../../proto/google/protobuf/duration.proto"),
+    ),
+    (
+        "google/protobuf/empty.proto",
+        include_str!("../../proto/google/protobuf/empty.proto"),
+    ),
+    (
+        "google/protobuf/field_mask.proto",
+        include_str!("../../proto/google/protobuf/field_mask.proto"),
+    ),
+    (
+        "google/protobuf/source_context.proto",
+        include_str!("../../proto/google/protobuf/source_context.proto"),
+    ),
+    (
+        "google/protobuf/struct.proto",
+        include_str!("../../proto/google/protobuf/struct.proto"),
+    ),
+    (
+        "google/protobuf/timestamp.proto",
+        include_str!("../../proto/google/protobuf/timestamp.proto"),
+    ),
+    (
+        "google/protobuf/type.proto",
+        include_str!("../../proto/google/protobuf/type.proto"),
+    ),
+    (
+        "google/protobuf/wrappers.proto",
+        include_str!("../../proto/google/protobuf/wrappers.proto"),
+    ),
+];
+
+/// An error in the source of a `.proto` file, at a zero-based line and column.
+#[derive(Debug)]
+pub(crate) struct ParseError {
+    line: usize,
+    col: usize,
+    message: String,
+}
+
+impl ParseError {
+    fn new(line: usize, col: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line,
+            col,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Like protoc, report one-based lines and columns.
+        write!(f, "{}:{}: {}", self.line + 1, self.col + 1, self.message)
+    }
+}
+
+/// Parses the `.proto` files and their imports into a `FileDescriptorSet`, like
+/// `protoc --include_imports`.
+///
+/// Source code info is included if `source_info` is set.
+pub(crate) fn parse(
+    protos: &[impl AsRef<Path>],
+    includes: &[impl AsRef<Path>],
+    source_info: bool,
+) -> Result<FileDescriptorSet> {
+    let mut loader = Loader {
+        includes: includes
+            .iter()
+            .map(|include| include.as_ref().to_path_buf())
+            .filter(|include| include.exists())
+            .collect(),
+        source_info,
+        files: Vec::new(),
+        loaded: HashSet::new(),
+        loading: Vec::new(),
+    };
+    for proto in protos {
+        let name = loader.proto_name(proto.as_ref())?;
+        loader.load(&name, None)?;
+    }
+
+    let mut files = loader.files;
+    resolve::resolve(&mut files).map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+    Ok(FileDescriptorSet { file: files })
+}
+
+struct Loader {
+    includes: Vec<PathBuf>,
+    source_info: bool,
+    /// The loaded files, with each file after its imports.
+    files: Vec<FileDescriptorProto>,
+    loaded: HashSet<String>,
+    /// The files being loaded, to detect import cycles.
+    loading: Vec<String>,
+}
+
+impl Loader {
+    /// Returns the name of a `.proto` file relative to the include directory containing it.
+    fn proto_name(&self, proto: &Path) -> Result<String> {
+        // Like protoc, a path which does not exist is looked up in the include directories.
+        let Ok(canonical) = proto.canonicalize() else {
+            return Ok(to_name(proto));
+        };
+        self.includes
+            .iter()
+            .filter_map(|include| include.canonicalize().ok())
+            .find_map(|include| canonical.strip_prefix(include).ok().map(to_name))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "{}: file does not reside within any include directory",
+                        proto.display()
+                    ),
+                )
+            })
+    }
+
+    fn load(&mut self, name: &str, importer: Option<&str>) -> Result<()> {
+        if self.loaded.contains(name) {
+            return Ok(());
+        }
+        if let Some(index) = self.loading.iter().position(|loading| loading == name) {
+            let mut cycle = self.loading[index..].to_vec();
+            cycle.push(name.to_owned());
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("import cycle: {}", cycle.join(" -> ")),
+            ));
+        }
+
+        let source = self.read(name).map_err(|error| match importer {
+            Some(importer) => Error::new(
+                error.kind(),
+                format!("{importer}: import \"{name}\" was not found: {error}"),
+            ),
+            None => error,
+        })?;
+        let file = parser::parse_file(name, &source, self.source_info)
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, format!("{name}:{error}")))?;
+
+        self.loading.push(name.to_owned());
+        for dependency in &file.dependency {
+            self.load(dependency, Some(name))?;
+        }
+        self.loading.pop();
+
+        self.loaded.insert(name.to_owned());
+        self.files.push(file);
+        Ok(())
+    }
+
+    fn read(&self, name: &str) -> Result<String> {
+        for include in &self.includes {
+            let path = include.join(name);
+            if path.is_file() {
+                return fs::read_to_string(path);
+            }
+        }
+        BUNDLED_FILES
+            .iter()
+            .find(|(bundled, _)| *bundled == name)
+            .map(|(_, source)| source.to_string())
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("{name}: file not found")))
+    }
+}
+
+/// Converts a relative path to the name of a `.proto` file, which uses `/` as separator.
+fn to_name(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::field_descriptor_proto::{Label, Type};
+
+    use super::*;
+
+    /// Writes the files to a temporary directory and parses the first one.
+    fn parse_files(files: &[(&str, &str)]) -> Result<FileDescriptorSet> {
+        let tempdir = tempfile::tempdir().unwrap();
+        for (name, source) in files {
+            let path = tempdir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+        parse(&[tempdir.path().join(files[0].0)], &[tempdir.path()], true)
+    }
+
+    #[test]
+    fn test_parse_proto3() {
+        let fds = parse_files(&[(
+            "test.proto",
+            r#"
+            syntax = "proto3";
+            package foo.bar;
+
+            import "google/protobuf/timestamp.proto";
+
+            message Outer {
+              message Inner {
+                Kind kind = 1;
+              }
+              enum Kind {
+                KIND_UNSPECIFIED = 0;
+              }
+              optional string display_name = 1 [json_name = "name"];
+              map<string, Inner> inner_by_key = 2;
+              oneof choice {
+                int32 number = 3;
+                google.protobuf.Timestamp at = 4;
+              }
+              repeated .foo.bar.Outer.Inner inners = 5 [packed = false];
+            }
+            "#,
+        )])
+        .unwrap();
+
+        let names: Vec<_> = fds.file.iter().map(|file| file.name()).collect();
+        assert_eq!(names, ["google/protobuf/timestamp.proto", "test.proto"]);
+
+        let file = &fds.file[1];
+        assert_eq!(file.syntax(), "proto3");
+        assert_eq!(file.package(), "foo.bar");
+        let outer = &file.message_type[0];
+        let inner = &outer.nested_type[0];
+        assert_eq!(inner.field[0].r#type(), Type::Enum);
+        assert_eq!(inner.field[0].type_name(), ".foo.bar.Outer.Kind");
+        assert_eq!(inner.field[0].json_name(), "kind");
+
+        let display_name = &outer.field[0];
+        assert!(display_name.proto3_optional());
+        assert_eq!(display_name.json_name(), "name");
+        assert_eq!(display_name.oneof_index, Some(1));
+        assert_eq!(outer.oneof_decl[1].name(), "_display_name");
+
+        let map = &outer.field[1];
+        assert_eq!(map.label(), Label::Repeated);
+        assert_eq!(map.type_name(), ".foo.bar.Outer.InnerByKeyEntry");
+        assert_eq!(map.json_name(), "innerByKey");
+        let entry = &outer.nested_type[1];
+        assert_eq!(entry.name(), "InnerByKeyEntry");
+        assert!(entry.options.as_ref().unwrap().map_entry());
+        assert_eq!(entry.field[1].type_name(), ".foo.bar.Outer.Inner");
+
+        assert_eq!(outer.oneof_decl[0].name(), "choice");
+        assert_eq!(outer.field[3].oneof_index, Some(0));
+        assert_eq!(outer.field[3].type_name(), ".google.protobuf.Timestamp");
+        assert_eq!(outer.field[4].type_name(), ".foo.bar.Outer.Inner");
+        assert!(!outer.field[4].options.as_ref().unwrap().packed());
+    }
+
+    #[test]
+    fn test_parse_proto2() {
+        let fds = parse_files(&[(
+            "test.proto",
+            r#"
+            package test;
+
+            message Message {
+              required int32 id = 1;
+              optional bytes data = 2 [default = "\001a\n"];
+              optional Color color = 3 [default = RED];
+              optional double ratio = 4 [default = -inf];
+              repeated group Item = 5 {
+                optional string name = 1;
+              }
+              extensions 100 to max;
+              reserved 6, 8 to 10;
+              reserved "old";
+            }
+
+            enum Color {
+              option allow_alias = true;
+              RED = 0;
+              CRIMSON = 0;
+            }
+
+            extend Message {
+              optional int32 extra = 100;
+            }
+
+            service Service {
+              rpc Call(Message) returns (stream Message);
+            }
+            "#,
+        )])
+        .unwrap();
+
+        let file = &fds.file[0];
+        assert_eq!(file.syntax, None);
+        let message = &file.message_type[0];
+        assert_eq!(message.field[0].label(), Label::Required);
+        assert_eq!(message.field[1].default_value(), "\\001a\\n");
+        assert_eq!(message.field[2].r#type(), Type::Enum);
+        assert_eq!(message.field[2].default_value(), "RED");
+        assert_eq!(message.field[3].default_value(), "-inf");
+
+        let item = &message.field[4];
+        assert_eq!(item.name(), "item");
+        assert_eq!(item.r#type(), Type::Group);
+        assert_eq!(item.type_name(), ".test.Message.Item");
+        assert_eq!(message.nested_type[0].name(), "Item");
+
+        assert_eq!(message.extension_range[0].start, Some(100));
+        assert_eq!(message.extension_range[0].end, Some(536_870_912));
+        let reserved: Vec<_> = message
+            .reserved_range
+            .iter()
+            .map(|range| (range.start(), range.end()))
+            .collect();
+        assert_eq!(reserved, [(6, 7), (8, 11)]);
+        assert_eq!(message.reserved_name, ["old"]);
+
+        assert!(file.enum_type[0].options.as_ref().unwrap().allow_alias());
+        assert_eq!(file.extension[0].extendee(), ".test.Message");
+
+        let method = &file.service[0].method[0];
+        assert_eq!(method.input_type(), ".test.Message");
+        assert!(!method.client_streaming());
+        assert!(method.server_streaming());
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let fds = parse_files(&[(
+            "test.proto",
+            r#"syntax = "proto3";
+
+// Detached.
+
+// Leading
+// comment.
+message Message { // Message trailing.
+  /* Block. */
+  int32 field = 1; // Field trailing.
+}
+"#,
+        )])
+        .unwrap();
+
+        let locations = &fds.file[0].source_code_info.as_ref().unwrap().location;
+        let message = locations.iter().find(|l| l.path == [4, 0]).unwrap();
+        assert_eq!(message.leading_detached_comments, [" Detached.\n"]);
+        assert_eq!(message.leading_comments(), " Leading\n comment.\n");
+        assert_eq!(message.trailing_comments(), " Message trailing.\n");
+        assert_eq!(message.span, [6, 0, 9, 1]);
+
+        let field = locations.iter().find(|l| l.path == [4, 0, 2, 0]).unwrap();
+        assert_eq!(field.leading_comments(), " Block. ");
+        assert_eq!(field.trailing_comments(), " Field trailing.\n");
+        assert_eq!(field.span, [8, 2, 18]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |files: &[(&str, &str)]| parse_files(files).unwrap_err().to_string();
+
+        assert_eq!(
+            error(&[("a.proto", "syntax = \"proto3\";\nmessage A { B b = 1; }")]),
+            "a.proto: \"B\" is not defined"
+        );
+        assert_eq!(
+            error(&[(
+                "a.proto",
+                "syntax = \"proto3\";\nmessage A {\n  int32 a = 1\n}"
+            )]),
+            "a.proto:4:1: expected \";\""
+        );
+        assert_eq!(
+            error(&[("a.proto", "edition = \"2023\";")]),
+            "a.proto:1:1: editions are not supported by the native parser"
+        );
+        assert_eq!(
+            error(&[("a.proto", "message A { int32 a = 1; }")]),
+            "a.proto:1:13: expected \"required\", \"optional\", or \"repeated\""
+        );
+        assert_eq!(
+            error(&[
+                ("a.proto", "import \"b.proto\";"),
+                ("b.proto", "import \"a.proto\";")
+            ]),
+            "import cycle: a.proto -> b.proto -> a.proto"
+        );
+        assert_eq!(
+            error(&[("a.proto", "import \"missing.proto\";")]),
+            "a.proto: import \"missing.proto\" was not found: missing.proto: file not found"
+        );
+    }
+}
@@ -0,0 +1,1368 @@
+//! Parser for `.proto` files using the `proto2` or `proto3` syntax.
+//!
+//! The parser produces a `FileDescriptorProto` whose type references are not yet resolved: the
+//! `type_name`, `extendee`, `input_type` and `output_type` fields are set as written in the
+//! source, and the `type` of fields referring to messages or enums is unset. They are filled in by
+//! the `resolve` module once all imports are loaded.
+
+use prost_types::descriptor_proto::{ExtensionRange, ReservedRange};
+use prost_types::enum_descriptor_proto::EnumReservedRange;
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::field_options::{CType, JsType};
+use prost_types::file_options::OptimizeMode;
+use prost_types::method_options::IdempotencyLevel;
+use prost_types::source_code_info::Location;
+use prost_types::uninterpreted_option::NamePart;
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, EnumOptions, EnumValueDescriptorProto, EnumValueOptions,
+    ExtensionRangeOptions, FieldDescriptorProto, FieldOptions, FileDescriptorProto, FileOptions,
+    MessageOptions, MethodDescriptorProto, MethodOptions, OneofDescriptorProto, OneofOptions,
+    ServiceDescriptorProto, ServiceOptions, SourceCodeInfo, UninterpretedOption,
+};
+
+use super::lexer::{tokenize, Spanned, Token};
+use super::ParseError;
+
+/// The largest field number, which is the value of `max` in the ranges of messages.
+const MAX_FIELD_NUMBER: i32 = 536_870_911;
+
+/// Parses the source of the `.proto` file `name`.
+///
+/// Locations and comments are recorded in `source_code_info` if `source_info` is set.
+pub(super) fn parse_file(
+    name: &str,
+    src: &str,
+    source_info: bool,
+) -> Result<FileDescriptorProto, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+        comment_start: 0,
+        proto3: false,
+        locations: Vec::new(),
+    };
+    let mut file = parser.file()?;
+    file.name = Some(name.to_owned());
+    if source_info {
+        file.source_code_info = Some(SourceCodeInfo {
+            location: parser.locations,
+        });
+    }
+    Ok(file)
+}
+
+/// The value of an option.
+#[derive(Debug)]
+enum Value {
+    Ident(String),
+    Int(bool, u64),
+    Float(f64),
+    Str(Vec<u8>),
+    Aggregate(String),
+}
+
+/// An option name and value, like `deprecated = true` or `(my.option).field = 1`.
+struct ProtoOption {
+    name: Vec<NamePart>,
+    value: Value,
+}
+
+impl ProtoOption {
+    /// Returns the name of a built-in option, or `None` for custom options.
+    fn builtin_name(&self) -> Option<&str> {
+        match &self.name[..] {
+            [part] if !part.is_extension => Some(&part.name_part),
+            _ => None,
+        }
+    }
+
+    fn bool(&self) -> Result<bool, String> {
+        match &self.value {
+            Value::Ident(ident) if ident == "true" => Ok(true),
+            Value::Ident(ident) if ident == "false" => Ok(false),
+            _ => Err(format!(
+                "value of option {} must be a bool",
+                self.name_string()
+            )),
+        }
+    }
+
+    fn string(&self) -> Result<String, String> {
+        match &self.value {
+            Value::Str(bytes) => String::from_utf8(bytes.clone())
+                .map_err(|_| format!("value of option {} is not UTF-8", self.name_string())),
+            _ => Err(format!(
+                "value of option {} must be a string",
+                self.name_string()
+            )),
+        }
+    }
+
+    fn enumeration(&self, from_str_name: fn(&str) -> Option<i32>) -> Result<i32, String> {
+        match &self.value {
+            Value::Ident(ident) => from_str_name(ident),
+            _ => None,
+        }
+        .ok_or_else(|| format!("invalid value for option {}", self.name_string()))
+    }
+
+    fn name_string(&self) -> String {
+        self.name
+            .iter()
+            .map(|part| {
+                if part.is_extension {
+                    format!("({})", part.name_part)
+                } else {
+                    part.name_part.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Converts a custom option, or a built-in option which is not interpreted by the parser, to
+    /// an `UninterpretedOption`.
+    fn uninterpreted(self) -> UninterpretedOption {
+        let mut option = UninterpretedOption {
+            name: self.name,
+            ..Default::default()
+        };
+        match self.value {
+            Value::Ident(ident) => option.identifier_value = Some(ident),
+            Value::Int(false, value) => option.positive_int_value = Some(value),
+            Value::Int(true, value) => {
+                option.negative_int_value = Some((value as i64).wrapping_neg())
+            }
+            Value::Float(value) => option.double_value = Some(value),
+            Value::Str(value) => option.string_value = Some(value),
+            Value::Aggregate(value) => option.aggregate_value = Some(value),
+        }
+        option
+    }
+}
+
+/// The inclusive ranges and the names of a `reserved` statement.
+type Reserved = (Vec<(i32, i32)>, Vec<String>);
+
+#[derive(Clone, Copy, PartialEq)]
+enum FieldKind {
+    Message,
+    Oneof,
+    Extension,
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    /// Index of the next token, which may be a comment.
+    pos: usize,
+    /// Index of the first comment which is not yet attached to an element.
+    comment_start: usize,
+    proto3: bool,
+    locations: Vec<Location>,
+}
+
+impl Parser {
+    // Token handling
+
+    /// Returns the index of the next token which is not a comment.
+    fn next_index(&self) -> usize {
+        let mut index = self.pos;
+        while matches!(
+            self.tokens.get(index),
+            Some(Spanned {
+                token: Token::Comment(_),
+                ..
+            })
+        ) {
+            index += 1;
+        }
+        index
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens
+            .get(self.next_index())
+            .map(|spanned| &spanned.token)
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens[self.next_index()..]
+            .iter()
+            .filter(|spanned| !matches!(spanned.token, Token::Comment(_)))
+            .nth(n)
+            .map(|spanned| &spanned.token)
+    }
+
+    fn peek_ident(&self, ident: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(i)) if i == ident)
+    }
+
+    fn peek_symbol(&self, symbol: char) -> bool {
+        self.peek() == Some(&Token::Symbol(symbol))
+    }
+
+    fn next(&mut self) -> Result<Token, ParseError> {
+        let index = self.next_index();
+        let token = self
+            .tokens
+            .get(index)
+            .ok_or_else(|| self.error("unexpected end of file"))?
+            .token
+            .clone();
+        self.pos = index + 1;
+        self.comment_start = self.pos;
+        Ok(token)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        match self.tokens.get(self.next_index()).or(self.tokens.last()) {
+            Some(spanned) => ParseError::new(spanned.line, spanned.col, message),
+            None => ParseError::new(0, 0, message),
+        }
+    }
+
+    fn eat_symbol(&mut self, symbol: char) -> Result<bool, ParseError> {
+        if self.peek_symbol(symbol) {
+            self.next()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn eat_ident(&mut self, ident: &str) -> Result<bool, ParseError> {
+        if self.peek_ident(ident) {
+            self.next()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: char) -> Result<(), ParseError> {
+        if self.eat_symbol(symbol)? {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected \"{symbol}\"")))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        if self.eat_ident(keyword)? {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected \"{keyword}\"")))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, ParseError> {
+        match self.peek() {
+            Some(Token::Ident(_)) => match self.next()? {
+                Token::Ident(ident) => Ok(ident),
+                _ => unreachable!(),
+            },
+            _ => Err(self.error("expected identifier")),
+        }
+    }
+
+    /// Parses a dotted name, like `foo.bar.Baz`.
+    fn full_ident(&mut self) -> Result<String, ParseError> {
+        let mut name = self.ident()?;
+        while self.eat_symbol('.')? {
+            name.push('.');
+            name.push_str(&self.ident()?);
+        }
+        Ok(name)
+    }
+
+    /// Parses a reference to a type, which may be fully qualified with a leading dot.
+    fn type_name(&mut self) -> Result<String, ParseError> {
+        let absolute = self.eat_symbol('.')?;
+        let name = self.full_ident()?;
+        Ok(if absolute { format!(".{name}") } else { name })
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        let bytes = self.bytes()?;
+        String::from_utf8(bytes).map_err(|_| self.error("string is not UTF-8"))
+    }
+
+    /// Parses a string literal, concatenating adjacent literals.
+    fn bytes(&mut self) -> Result<Vec<u8>, ParseError> {
+        let mut value = match self.peek() {
+            Some(Token::Str(_)) => Vec::new(),
+            _ => return Err(self.error("expected string")),
+        };
+        while let Some(Token::Str(_)) = self.peek() {
+            if let Token::Str(bytes) = self.next()? {
+                value.extend(bytes);
+            }
+        }
+        Ok(value)
+    }
+
+    fn int(&mut self) -> Result<i32, ParseError> {
+        let negative = self.eat_symbol('-')?;
+        match self.peek() {
+            Some(&Token::Int(value)) => {
+                let value = if negative {
+                    i64::try_from(value).map(|v| -v).ok()
+                } else {
+                    i64::try_from(value).ok()
+                }
+                .and_then(|v| i32::try_from(v).ok())
+                .ok_or_else(|| self.error("integer out of range"))?;
+                self.next()?;
+                Ok(value)
+            }
+            _ => Err(self.error("expected integer")),
+        }
+    }
+
+    fn field_number(&mut self) -> Result<i32, ParseError> {
+        if self.eat_ident("max")? {
+            return Ok(MAX_FIELD_NUMBER);
+        }
+        let number = self.int()?;
+        if !(1..=MAX_FIELD_NUMBER).contains(&number) {
+            return Err(self.error("field number out of range"));
+        }
+        Ok(number)
+    }
+
+    // Source locations
+
+    /// Starts the location of the element beginning at the next token, and attaches the
+    /// comments preceding it.
+    fn start_location(&mut self, path: &[i32]) -> usize {
+        let next = self.next_index();
+        let start = self
+            .tokens
+            .get(next)
+            .map_or((0, 0), |token| (token.line, token.col));
+
+        // Group the comments into blocks of comments on consecutive lines.
+        let mut blocks: Vec<(String, usize)> = Vec::new();
+        let mut previous_end = None;
+        for spanned in &self.tokens[self.comment_start.min(next)..next] {
+            let Token::Comment(text) = &spanned.token else {
+                continue;
+            };
+            match blocks.last_mut() {
+                Some((block, end)) if previous_end == Some(spanned.line.saturating_sub(1)) => {
+                    block.push_str(text);
+                    *end = spanned.end_line;
+                }
+                _ => blocks.push((text.clone(), spanned.end_line)),
+            }
+            previous_end = Some(spanned.end_line);
+        }
+
+        let leading = match blocks.last() {
+            Some(&(_, end)) if end + 1 >= start.0 => blocks.pop().map(|(text, _)| text),
+            _ => None,
+        };
+        self.comment_start = next;
+
+        self.locations.push(Location {
+            path: path.to_vec(),
+            span: vec![start.0 as i32, start.1 as i32],
+            leading_comments: leading,
+            trailing_comments: None,
+            leading_detached_comments: blocks.into_iter().map(|(text, _)| text).collect(),
+        });
+        self.locations.len() - 1
+    }
+
+    /// Attaches a comment on the same line as the previous token as the trailing comment of the
+    /// location.
+    fn trailing_comment(&mut self, location: usize) {
+        let Some(previous) = self.pos.checked_sub(1).map(|index| &self.tokens[index]) else {
+            return;
+        };
+        if let Some(Spanned {
+            token: Token::Comment(text),
+            line,
+            ..
+        }) = self.tokens.get(self.pos)
+        {
+            if *line == previous.end_line {
+                self.locations[location].trailing_comments = Some(text.clone());
+                self.pos += 1;
+                self.comment_start = self.pos;
+            }
+        }
+    }
+
+    /// Ends the location at the previous token.
+    fn end_location(&mut self, location: usize) {
+        let Some(previous) = self.pos.checked_sub(1).map(|index| &self.tokens[index]) else {
+            return;
+        };
+        let span = &mut self.locations[location].span;
+        if previous.end_line as i32 != span[0] {
+            span.push(previous.end_line as i32);
+        }
+        span.push(previous.end_col as i32);
+    }
+
+    // Grammar
+
+    fn file(&mut self) -> Result<FileDescriptorProto, ParseError> {
+        let mut file = FileDescriptorProto::default();
+
+        if self.peek_ident("syntax") {
+            self.next()?;
+            self.expect_symbol('=')?;
+            let syntax = self.string()?;
+            match syntax.as_str() {
+                "proto2" => {}
+                "proto3" => {
+                    self.proto3 = true;
+                    file.syntax = Some(syntax);
+                }
+                _ => return Err(self.error(format!("unsupported syntax \"{syntax}\""))),
+            }
+            self.expect_symbol(';')?;
+        } else if self.peek_ident("edition") {
+            return Err(self.error("editions are not supported by the native parser"));
+        }
+
+        while self.peek().is_some() {
+            if self.eat_symbol(';')? {
+                continue;
+            }
+            let keyword = match self.peek() {
+                Some(Token::Ident(ident)) => ident.clone(),
+                _ => return Err(self.error("expected top-level statement")),
+            };
+            match keyword.as_str() {
+                "import" => {
+                    self.next()?;
+                    let index = file.dependency.len() as i32;
+                    if self.eat_ident("public")? {
+                        file.public_dependency.push(index);
+                    } else if self.eat_ident("weak")? {
+                        file.weak_dependency.push(index);
+                    }
+                    file.dependency.push(self.string()?);
+                    self.expect_symbol(';')?;
+                }
+                "package" => {
+                    self.next()?;
+                    if file.package.is_some() {
+                        return Err(self.error("multiple package definitions"));
+                    }
+                    file.package = Some(self.full_ident()?);
+                    self.expect_symbol(';')?;
+                }
+                "option" => {
+                    self.next()?;
+                    let option = self.option_statement()?;
+                    let options = file.options.get_or_insert_with(Default::default);
+                    self.check(set_file_option(options, option))?;
+                }
+                "message" => {
+                    let path = [4, file.message_type.len() as i32];
+                    file.message_type.push(self.message(&path)?);
+                }
+                "enum" => {
+                    let path = [5, file.enum_type.len() as i32];
+                    file.enum_type.push(self.enumeration(&path)?);
+                }
+                "service" => {
+                    let path = [6, file.service.len() as i32];
+                    file.service.push(self.service(&path)?);
+                }
+                "extend" => {
+                    self.next()?;
+                    self.extend(&[], 7, &mut file.extension, 4, &mut file.message_type)?;
+                }
+                _ => return Err(self.error(format!("unexpected \"{keyword}\""))),
+            }
+        }
+
+        Ok(file)
+    }
+
+    fn check<T>(&self, result: Result<T, String>) -> Result<T, ParseError> {
+        result.map_err(|message| self.error(message))
+    }
+
+    /// Parses `option name = value;` after the `option` keyword.
+    fn option_statement(&mut self) -> Result<ProtoOption, ParseError> {
+        let option = self.option()?;
+        self.expect_symbol(';')?;
+        Ok(option)
+    }
+
+    /// Parses `name = value`.
+    fn option(&mut self) -> Result<ProtoOption, ParseError> {
+        let mut name = Vec::new();
+        loop {
+            if self.eat_symbol('(')? {
+                let name_part = self.type_name()?;
+                self.expect_symbol(')')?;
+                name.push(NamePart {
+                    name_part,
+                    is_extension: true,
+                });
+            } else {
+                name.push(NamePart {
+                    name_part: self.ident()?,
+                    is_extension: false,
+                });
+            }
+            if !self.eat_symbol('.')? {
+                break;
+            }
+        }
+        self.expect_symbol('=')?;
+        let value = self.value()?;
+        Ok(ProtoOption { name, value })
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        let negative = self.eat_symbol('-')?;
+        match self.peek() {
+            Some(&Token::Int(value)) => {
+                self.next()?;
+                Ok(Value::Int(negative, value))
+            }
+            Some(&Token::Float(value)) => {
+                self.next()?;
+                Ok(Value::Float(if negative { -value } else { value }))
+            }
+            Some(Token::Ident(_)) => {
+                let ident = self.ident()?;
+                if negative {
+                    if ident != "inf" && ident != "nan" {
+                        return Err(self.error("expected number"));
+                    }
+                    Ok(Value::Ident(format!("-{ident}")))
+                } else {
+                    Ok(Value::Ident(ident))
+                }
+            }
+            Some(Token::Str(_)) if !negative => Ok(Value::Str(self.bytes()?)),
+            Some(Token::Symbol('{')) if !negative => Ok(Value::Aggregate(self.aggregate()?)),
+            _ => Err(self.error("expected option value")),
+        }
+    }
+
+    /// Parses a message literal in the text format, keeping its text as is.
+    fn aggregate(&mut self) -> Result<String, ParseError> {
+        self.expect_symbol('{')?;
+        let mut depth = 0;
+        let mut text = Vec::new();
+        loop {
+            let token = self.next()?;
+            let part = match token {
+                Token::Symbol('{') => {
+                    depth += 1;
+                    "{".to_owned()
+                }
+                Token::Symbol('}') if depth == 0 => break,
+                Token::Symbol('}') => {
+                    depth -= 1;
+                    "}".to_owned()
+                }
+                Token::Symbol(symbol) => symbol.to_string(),
+                Token::Ident(ident) => ident,
+                Token::Int(value) => value.to_string(),
+                Token::Float(value) => value.to_string(),
+                Token::Str(bytes) => format!("\"{}\"", c_escape(&bytes)),
+                Token::Comment(_) => unreachable!(),
+            };
+            text.push(part);
+        }
+        Ok(text.join(" "))
+    }
+
+    /// Parses `[name = value, ...]` if present.
+    fn option_list(&mut self) -> Result<Vec<ProtoOption>, ParseError> {
+        let mut options = Vec::new();
+        if self.eat_symbol('[')? {
+            loop {
+                options.push(self.option()?);
+                if !self.eat_symbol(',')? {
+                    break;
+                }
+            }
+            self.expect_symbol(']')?;
+        }
+        Ok(options)
+    }
+
+    fn message(&mut self, path: &[i32]) -> Result<DescriptorProto, ParseError> {
+        let location = self.start_location(path);
+        self.expect_keyword("message")?;
+        let name = self.ident()?;
+        self.expect_symbol('{')?;
+        self.trailing_comment(location);
+        let mut message = DescriptorProto {
+            name: Some(name),
+            ..Default::default()
+        };
+        self.message_body(&mut message, path)?;
+        self.end_location(location);
+        Ok(message)
+    }
+
+    /// Parses the body of a message after the opening brace, up to and including the closing
+    /// brace.
+    fn message_body(
+        &mut self,
+        message: &mut DescriptorProto,
+        path: &[i32],
+    ) -> Result<(), ParseError> {
+        let child = |field: i32, index: usize| -> Vec<i32> {
+            let mut child = path.to_vec();
+            child.extend([field, index as i32]);
+            child
+        };
+
+        loop {
+            if self.eat_symbol('}')? {
+                break;
+            }
+            if self.eat_symbol(';')? {
+                continue;
+            }
+            if self.peek_ident("message") {
+                let path = child(3, message.nested_type.len());
+                let nested = self.message(&path)?;
+                message.nested_type.push(nested);
+            } else if self.peek_ident("enum") {
+                let path = child(4, message.enum_type.len());
+                let nested = self.enumeration(&path)?;
+                message.enum_type.push(nested);
+            } else if self.peek_ident("extend") {
+                self.next()?;
+                self.extend(path, 6, &mut message.extension, 3, &mut message.nested_type)?;
+            } else if self.peek_ident("option") {
+                self.next()?;
+                let option = self.option_statement()?;
+                let options = message.options.get_or_insert_with(Default::default);
+                self.check(set_message_option(options, option))?;
+            } else if self.peek_ident("extensions") {
+                self.next()?;
+                self.extension_ranges(message)?;
+            } else if self.peek_ident("reserved") {
+                self.next()?;
+                let (ranges, names) = self.reserved(MAX_FIELD_NUMBER)?;
+                message
+                    .reserved_range
+                    .extend(ranges.into_iter().map(|(start, end)| ReservedRange {
+                        start: Some(start),
+                        end: Some(end + 1),
+                    }));
+                message.reserved_name.extend(names);
+            } else if self.peek_ident("oneof") {
+                self.oneof(message, path)?;
+            } else if self.peek_ident("map") && self.peek_nth(1) == Some(&Token::Symbol('<')) {
+                let path = child(2, message.field.len());
+                let (field, entry) = self.map_field(&path)?;
+                message.field.push(field);
+                message.nested_type.push(entry);
+            } else {
+                let path = child(2, message.field.len());
+                let group_path = child(3, message.nested_type.len());
+                let (field, group) = self.field(FieldKind::Message, &path, &group_path)?;
+                message.field.push(field);
+                message.nested_type.extend(group);
+            }
+        }
+
+        // Like protoc, add a synthetic oneof for each proto3 optional field, after the
+        // declared oneofs.
+        for index in 0..message.field.len() {
+            if !message.field[index].proto3_optional() {
+                continue;
+            }
+            let mut name = format!("_{}", message.field[index].name());
+            while message.field.iter().any(|f| f.name() == name)
+                || message.oneof_decl.iter().any(|o| o.name() == name)
+            {
+                name.insert(0, 'X');
+            }
+            message.field[index].oneof_index = Some(message.oneof_decl.len() as i32);
+            message.oneof_decl.push(OneofDescriptorProto {
+                name: Some(name),
+                options: None,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Parses an optional field label.
+    fn label(&mut self) -> Result<Option<Label>, ParseError> {
+        let label = match self.peek() {
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "optional" => Label::Optional,
+                "required" => Label::Required,
+                "repeated" => Label::Repeated,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        // A field may have a type named like a label, as in `optional optional = 1;`.
+        if matches!(self.peek_nth(2), Some(Token::Symbol('='))) {
+            return Ok(None);
+        }
+        self.next()?;
+        Ok(Some(label))
+    }
+
+    /// Parses a field, or a group with its message type.
+    fn field(
+        &mut self,
+        kind: FieldKind,
+        path: &[i32],
+        group_path: &[i32],
+    ) -> Result<(FieldDescriptorProto, Option<DescriptorProto>), ParseError> {
+        let location = self.start_location(path);
+        let label = self.label()?;
+        match (kind, label) {
+            (FieldKind::Oneof, Some(_)) => {
+                return Err(self.error("fields in oneofs must not have labels"))
+            }
+            (FieldKind::Message | FieldKind::Extension, None) if !self.proto3 => {
+                return Err(self.error("expected \"required\", \"optional\", or \"repeated\""))
+            }
+            _ => {}
+        }
+
+        let mut field = FieldDescriptorProto::default();
+        match label {
+            Some(Label::Required) if self.proto3 => {
+                return Err(self.error("required fields are not allowed in proto3"))
+            }
+            Some(Label::Optional) if self.proto3 && kind == FieldKind::Message => {
+                field.label = Some(Label::Optional as i32);
+                field.proto3_optional = Some(true);
+            }
+            Some(label) => field.label = Some(label as i32),
+            None => field.label = Some(Label::Optional as i32),
+        }
+
+        let is_group =
+            self.peek_ident("group") && matches!(self.peek_nth(1), Some(Token::Ident(_)));
+        if is_group {
+            if self.proto3 {
+                return Err(self.error("groups are not supported in proto3"));
+            }
+            self.next()?;
+            let name = self.ident()?;
+            field.name = Some(name.to_lowercase());
+            field.r#type = Some(Type::Group as i32);
+            field.type_name = Some(name);
+        } else {
+            let type_name = self.type_name()?;
+            match scalar_type(&type_name) {
+                Some(ty) => field.r#type = Some(ty as i32),
+                None => field.type_name = Some(type_name),
+            }
+            field.name = Some(self.ident()?);
+        }
+
+        self.expect_symbol('=')?;
+        field.number = Some(self.field_number()?);
+        for option in self.option_list()? {
+            self.field_option(&mut field, option)?;
+        }
+        if field.json_name.is_none() {
+            field.json_name = Some(json_name(field.name()));
+        }
+
+        if is_group {
+            self.expect_symbol('{')?;
+            self.trailing_comment(location);
+            let group_location = self.start_location(group_path);
+            let mut group = DescriptorProto {
+                name: field.type_name.clone(),
+                ..Default::default()
+            };
+            self.message_body(&mut group, group_path)?;
+            self.end_location(group_location);
+            self.end_location(location);
+            Ok((field, Some(group)))
+        } else {
+            self.expect_symbol(';')?;
+            self.end_location(location);
+            self.trailing_comment(location);
+            Ok((field, None))
+        }
+    }
+
+    fn field_option(
+        &mut self,
+        field: &mut FieldDescriptorProto,
+        option: ProtoOption,
+    ) -> Result<(), ParseError> {
+        match option.builtin_name() {
+            Some("default") => {
+                if field.label() == Label::Repeated {
+                    return Err(self.error("repeated fields can't have default values"));
+                }
+                if self.proto3 {
+                    return Err(self.error("explicit default values are not allowed in proto3"));
+                }
+                let value = match (
+                    option.value,
+                    field.r#type.and_then(|t| Type::try_from(t).ok()),
+                ) {
+                    (Value::Str(bytes), Some(Type::Bytes)) => c_escape(&bytes),
+                    (Value::Str(bytes), Some(Type::String)) => String::from_utf8(bytes)
+                        .map_err(|_| self.error("default value is not UTF-8"))?,
+                    (Value::Int(negative, value), Some(_)) => {
+                        format!("{}{value}", if negative { "-" } else { "" })
+                    }
+                    (Value::Float(value), Some(Type::Float | Type::Double)) => value.to_string(),
+                    (Value::Ident(ident), _) => ident,
+                    (_, _) => return Err(self.error("invalid default value")),
+                };
+                field.default_value = Some(value);
+            }
+            Some("json_name") => {
+                field.json_name = Some(self.check(option.string())?);
+            }
+            _ => {
+                let options = field.options.get_or_insert_with(Default::default);
+                self.check(set_field_option(options, option))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn map_field(
+        &mut self,
+        path: &[i32],
+    ) -> Result<(FieldDescriptorProto, DescriptorProto), ParseError> {
+        let location = self.start_location(path);
+        self.expect_keyword("map")?;
+        self.expect_symbol('<')?;
+        let key_type = self.type_name()?;
+        let key_type = match scalar_type(&key_type) {
+            Some(Type::Double | Type::Float | Type::Bytes) | None => {
+                return Err(self.error(format!("invalid map key type: {key_type}")))
+            }
+            Some(ty) => ty,
+        };
+        self.expect_symbol(',')?;
+        let value_type = self.type_name()?;
+        self.expect_symbol('>')?;
+        let name = self.ident()?;
+        self.expect_symbol('=')?;
+        let number = self.field_number()?;
+
+        let entry_name = format!("{}Entry", map_entry_name(&name));
+        let mut value = FieldDescriptorProto {
+            name: Some("value".to_owned()),
+            number: Some(2),
+            label: Some(Label::Optional as i32),
+            json_name: Some("value".to_owned()),
+            ..Default::default()
+        };
+        match scalar_type(&value_type) {
+            Some(ty) => value.r#type = Some(ty as i32),
+            None => value.type_name = Some(value_type),
+        }
+        let entry = DescriptorProto {
+            name: Some(entry_name.clone()),
+            field: vec![
+                FieldDescriptorProto {
+                    name: Some("key".to_owned()),
+                    number: Some(1),
+                    label: Some(Label::Optional as i32),
+                    r#type: Some(key_type as i32),
+                    json_name: Some("key".to_owned()),
+                    ..Default::default()
+                },
+                value,
+            ],
+            options: Some(MessageOptions {
+                map_entry: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let mut field = FieldDescriptorProto {
+            json_name: Some(json_name(&name)),
+            name: Some(name),
+            number: Some(number),
+            label: Some(Label::Repeated as i32),
+            type_name: Some(entry_name),
+            ..Default::default()
+        };
+        for option in self.option_list()? {
+            self.field_option(&mut field, option)?;
+        }
+        self.expect_symbol(';')?;
+        self.end_location(location);
+        self.trailing_comment(location);
+        Ok((field, entry))
+    }
+
+    fn oneof(&mut self, message: &mut DescriptorProto, path: &[i32]) -> Result<(), ParseError> {
+        let index = message.oneof_decl.len();
+        let mut oneof_path = path.to_vec();
+        oneof_path.extend([8, index as i32]);
+
+        let location = self.start_location(&oneof_path);
+        self.expect_keyword("oneof")?;
+        let mut oneof = OneofDescriptorProto {
+            name: Some(self.ident()?),
+            options: None,
+        };
+        self.expect_symbol('{')?;
+        self.trailing_comment(location);
+        message.oneof_decl.push(OneofDescriptorProto::default());
+
+        loop {
+            if self.eat_symbol('}')? {
+                break;
+            }
+            if self.eat_symbol(';')? {
+                continue;
+            }
+            if self.eat_ident("option")? {
+                let option = self.option_statement()?;
+                let options: &mut OneofOptions = oneof.options.get_or_insert_with(Default::default);
+                options.uninterpreted_option.push(option.uninterpreted());
+                continue;
+            }
+            let mut field_path = path.to_vec();
+            field_path.extend([2, message.field.len() as i32]);
+            let mut group_path = path.to_vec();
+            group_path.extend([3, message.nested_type.len() as i32]);
+            let (mut field, group) = self.field(FieldKind::Oneof, &field_path, &group_path)?;
+            field.oneof_index = Some(index as i32);
+            message.field.push(field);
+            message.nested_type.extend(group);
+        }
+
+        self.end_location(location);
+        message.oneof_decl[index] = oneof;
+        Ok(())
+    }
+
+    /// Parses an `extend` block after the `extend` keyword.
+    ///
+    /// The extension fields are appended to `extensions`, which is field `extension_field` of
+    /// the parent at `path`. Groups are appended to `messages`, which is field `message_field`.
+    fn extend(
+        &mut self,
+        path: &[i32],
+        extension_field: i32,
+        extensions: &mut Vec<FieldDescriptorProto>,
+        message_field: i32,
+        messages: &mut Vec<DescriptorProto>,
+    ) -> Result<(), ParseError> {
+        let extendee = self.type_name()?;
+        self.expect_symbol('{')?;
+        loop {
+            if self.eat_symbol('}')? {
+                break;
+            }
+            if self.eat_symbol(';')? {
+                continue;
+            }
+            let mut field_path = path.to_vec();
+            field_path.extend([extension_field, extensions.len() as i32]);
+            let mut group_path = path.to_vec();
+            group_path.extend([message_field, messages.len() as i32]);
+            let (mut field, group) = self.field(FieldKind::Extension, &field_path, &group_path)?;
+            field.extendee = Some(extendee.clone());
+            extensions.push(field);
+            messages.extend(group);
+        }
+        Ok(())
+    }
+
+    fn extension_ranges(&mut self, message: &mut DescriptorProto) -> Result<(), ParseError> {
+        let mut ranges = Vec::new();
+        loop {
+            let start = self.field_number()?;
+            let end = if self.eat_ident("to")? {
+                self.field_number()?
+            } else {
+                start
+            };
+            ranges.push(ExtensionRange {
+                start: Some(start),
+                end: Some(end + 1),
+                options: None,
+            });
+            if !self.eat_symbol(',')? {
+                break;
+            }
+        }
+        let options = self.option_list()?;
+        if !options.is_empty() {
+            let options = ExtensionRangeOptions {
+                uninterpreted_option: options
+                    .into_iter()
+                    .map(ProtoOption::uninterpreted)
+                    .collect(),
+                ..Default::default()
+            };
+            for range in &mut ranges {
+                range.options = Some(options.clone());
+            }
+        }
+        self.expect_symbol(';')?;
+        message.extension_range.extend(ranges);
+        Ok(())
+    }
+
+    /// Parses a `reserved` statement after the keyword into inclusive ranges and names.
+    ///
+    /// `max` is the value of the `max` keyword.
+    fn reserved(&mut self, max: i32) -> Result<Reserved, ParseError> {
+        let mut ranges = Vec::new();
+        let mut names = Vec::new();
+        if matches!(self.peek(), Some(Token::Str(_))) {
+            loop {
+                names.push(self.string()?);
+                if !self.eat_symbol(',')? {
+                    break;
+                }
+            }
+        } else {
+            loop {
+                let start = self.int()?;
+                let end = if self.eat_ident("to")? {
+                    if self.eat_ident("max")? {
+                        max
+                    } else {
+                        self.int()?
+                    }
+                } else {
+                    start
+                };
+                ranges.push((start, end));
+                if !self.eat_symbol(',')? {
+                    break;
+                }
+            }
+        }
+        self.expect_symbol(';')?;
+        Ok((ranges, names))
+    }
+
+    fn enumeration(&mut self, path: &[i32]) -> Result<EnumDescriptorProto, ParseError> {
+        let location = self.start_location(path);
+        self.expect_keyword("enum")?;
+        let mut enumeration = EnumDescriptorProto {
+            name: Some(self.ident()?),
+            ..Default::default()
+        };
+        self.expect_symbol('{')?;
+        self.trailing_comment(location);
+
+        loop {
+            if self.eat_symbol('}')? {
+                break;
+            }
+            if self.eat_symbol(';')? {
+                continue;
+            }
+            if self.peek_ident("option") && self.peek_nth(1) != Some(&Token::Symbol('=')) {
+                self.next()?;
+                let option = self.option_statement()?;
+                let options = enumeration.options.get_or_insert_with(Default::default);
+                self.check(set_enum_option(options, option))?;
+            } else if self.peek_ident("reserved") && self.peek_nth(1) != Some(&Token::Symbol('=')) {
+                self.next()?;
+                let (ranges, names) = self.reserved(i32::MAX)?;
+                enumeration
+                    .reserved_range
+                    .extend(ranges.into_iter().map(|(start, end)| EnumReservedRange {
+                        start: Some(start),
+                        end: Some(end),
+                    }));
+                enumeration.reserved_name.extend(names);
+            } else {
+                let mut value_path = path.to_vec();
+                value_path.extend([2, enumeration.value.len() as i32]);
+                let location = self.start_location(&value_path);
+                let mut value = EnumValueDescriptorProto {
+                    name: Some(self.ident()?),
+                    ..Default::default()
+                };
+                self.expect_symbol('=')?;
+                value.number = Some(self.int()?);
+                for option in self.option_list()? {
+                    let options = value.options.get_or_insert_with(Default::default);
+                    self.check(set_enum_value_option(options, option))?;
+                }
+                self.expect_symbol(';')?;
+                self.end_location(location);
+                self.trailing_comment(location);
+                enumeration.value.push(value);
+            }
+        }
+
+        if enumeration.value.is_empty() {
+            return Err(self.error(format!(
+                "enum {} must define at least one value",
+                enumeration.name()
+            )));
+        }
+        self.end_location(location);
+        Ok(enumeration)
+    }
+
+    fn service(&mut self, path: &[i32]) -> Result<ServiceDescriptorProto, ParseError> {
+        let location = self.start_location(path);
+        self.expect_keyword("service")?;
+        let mut service = ServiceDescriptorProto {
+            name: Some(self.ident()?),
+            ..Default::default()
+        };
+        self.expect_symbol('{')?;
+        self.trailing_comment(location);
+
+        loop {
+            if self.eat_symbol('}')? {
+                break;
+            }
+            if self.eat_symbol(';')? {
+                continue;
+            }
+            if self.eat_ident("option")? {
+                let option = self.option_statement()?;
+                let options = service.options.get_or_insert_with(Default::default);
+                self.check(set_service_option(options, option))?;
+                continue;
+            }
+
+            let mut method_path = path.to_vec();
+            method_path.extend([2, service.method.len() as i32]);
+            let location = self.start_location(&method_path);
+            self.expect_keyword("rpc")?;
+            let mut method = MethodDescriptorProto {
+                name: Some(self.ident()?),
+                ..Default::default()
+            };
+            self.expect_symbol('(')?;
+            if self.peek_ident("stream") && matches!(self.peek_nth(1), Some(Token::Ident(_))) {
+                self.next()?;
+                method.client_streaming = Some(true);
+            }
+            method.input_type = Some(self.type_name()?);
+            self.expect_symbol(')')?;
+            self.expect_keyword("returns")?;
+            self.expect_symbol('(')?;
+            if self.peek_ident("stream") && matches!(self.peek_nth(1), Some(Token::Ident(_))) {
+                self.next()?;
+                method.server_streaming = Some(true);
+            }
+            method.output_type = Some(self.type_name()?);
+            self.expect_symbol(')')?;
+
+            if self.eat_symbol('{')? {
+                self.trailing_comment(location);
+                loop {
+                    if self.eat_symbol('}')? {
+                        break;
+                    }
+                    if self.eat_symbol(';')? {
+                        continue;
+                    }
+                    self.expect_keyword("option")?;
+                    let option = self.option_statement()?;
+                    let options = method.options.get_or_insert_with(Default::default);
+                    self.check(set_method_option(options, option))?;
+                }
+                self.end_location(location);
+            } else {
+                self.expect_symbol(';')?;
+                self.end_location(location);
+                self.trailing_comment(location);
+            }
+            service.method.push(method);
+        }
+
+        self.end_location(location);
+        Ok(service)
+    }
+}
+
+fn scalar_type(name: &str) -> Option<Type> {
+    Some(match name {
+        "double" => Type::Double,
+        "float" => Type::Float,
+        "int64" => Type::Int64,
+        "uint64" => Type::Uint64,
+        "int32" => Type::Int32,
+        "fixed64" => Type::Fixed64,
+        "fixed32" => Type::Fixed32,
+        "bool" => Type::Bool,
+        "string" => Type::String,
+        "bytes" => Type::Bytes,
+        "uint32" => Type::Uint32,
+        "sfixed32" => Type::Sfixed32,
+        "sfixed64" => Type::Sfixed64,
+        "sint32" => Type::Sint32,
+        "sint64" => Type::Sint64,
+        _ => return None,
+    })
+}
+
+/// Returns the default JSON name of a field, as computed by protoc.
+fn json_name(name: &str) -> String {
+    let mut json_name = String::with_capacity(name.len());
+    let mut capitalize_next = false;
+    for c in name.chars() {
+        if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            json_name.push(c.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            json_name.push(c);
+        }
+    }
+    json_name
+}
+
+/// Returns the name of the entry message of a map field, without the `Entry` suffix.
+fn map_entry_name(field_name: &str) -> String {
+    let mut name = String::with_capacity(field_name.len());
+    let mut capitalize_next = true;
+    for c in field_name.chars() {
+        if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            name.push(c.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
+
+/// Escapes bytes like protoc does for the default values of `bytes` fields.
+fn c_escape(bytes: &[u8]) -> String {
+    let mut escaped = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        match byte {
+            b'\n' => escaped.push_str("\\n"),
+            b'\r' => escaped.push_str("\\r"),
+            b'\t' => escaped.push_str("\\t"),
+            b'"' => escaped.push_str("\\\""),
+            b'\'' => escaped.push_str("\\'"),
+            b'\\' => escaped.push_str("\\\\"),
+            0x20..=0x7e => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("\\{byte:03o}")),
+        }
+    }
+    escaped
+}
+
+fn set_file_option(options: &mut FileOptions, option: ProtoOption) -> Result<(), String> {
+    match option.builtin_name() {
+        Some("java_package") => options.java_package = Some(option.string()?),
+        Some("java_outer_classname") => options.java_outer_classname = Some(option.string()?),
+        Some("java_multiple_files") => options.java_multiple_files = Some(option.bool()?),
+        Some("go_package") => options.go_package = Some(option.string()?),
+        Some("optimize_for") => {
+            options.optimize_for =
+                Some(option.enumeration(|name| {
+                    OptimizeMode::from_str_name(name).map(|mode| mode as i32)
+                })?)
+        }
+        Some("cc_enable_arenas") => options.cc_enable_arenas = Some(option.bool()?),
+        Some("deprecated") => options.deprecated = Some(option.bool()?),
+        Some("objc_class_prefix") => options.objc_class_prefix = Some(option.string()?),
+        Some("csharp_namespace") => options.csharp_namespace = Some(option.string()?),
+        Some("swift_prefix") => options.swift_prefix = Some(option.string()?),
+        Some("php_namespace") => options.php_namespace = Some(option.string()?),
+        Some("ruby_package") => options.ruby_package = Some(option.string()?),
+        _ => options.uninterpreted_option.push(option.uninterpreted()),
+    }
+    Ok(())
+}
+
+fn set_message_option(options: &mut MessageOptions, option: ProtoOption) -> Result<(), String> {
+    match option.builtin_name() {
+        Some("deprecated") => options.deprecated = Some(option.bool()?),
+        Some("message_set_wire_format") => options.message_set_wire_format = Some(option.bool()?),
+        Some("no_standard_descriptor_accessor") => {
+            options.no_standard_descriptor_accessor = Some(option.bool()?)
+        }
+        Some("map_entry") => return Err("map_entry should not be set explicitly".to_owned()),
+        _ => options.uninterpreted_option.push(option.uninterpreted()),
+    }
+    Ok(())
+}
+
+fn set_field_option(options: &mut FieldOptions, option: ProtoOption) -> Result<(), String> {
+    match option.builtin_name() {
+        Some("packed") => options.packed = Some(option.bool()?),
+        Some("deprecated") => options.deprecated = Some(option.bool()?),
+        Some("lazy") => options.lazy = Some(option.bool()?),
+        Some("weak") => options.weak = Some(option.bool()?),
+        Some("debug_redact") => options.debug_redact = Some(option.bool()?),
+        Some("ctype") => {
+            options.ctype = Some(
+                option.enumeration(|name| CType::from_str_name(name).map(|ctype| ctype as i32))?,
+            )
+        }
+        Some("jstype") => {
+            options.jstype = Some(
+                option
+                    .enumeration(|name| JsType::from_str_name(name).map(|jstype| jstype as i32))?,
+            )
+        }
+        _ => options.uninterpreted_option.push(option.uninterpreted()),
+    }
+    Ok(())
+}
+
+fn set_enum_option(options: &mut EnumOptions, option: ProtoOption) -> Result<(), String> {
+    match option.builtin_name() {
+        Some("allow_alias") => options.allow_alias = Some(option.bool()?),
+        Some("deprecated") => options.deprecated = Some(option.bool()?),
+        _ => options.uninterpreted_option.push(option.uninterpreted()),
+    }
+    Ok(())
+}
+
+fn set_enum_value_option(
+    options: &mut EnumValueOptions,
+    option: ProtoOption,
+) -> Result<(), String> {
+    match option.builtin_name() {
+        Some("deprecated") => options.deprecated = Some(option.bool()?),
+        Some("debug_redact") => options.debug_redact = Some(option.bool()?),
+        _ => options.uninterpreted_option.push(option.uninterpreted()),
+    }
+    Ok(())
+}
+
+fn set_service_option(options: &mut ServiceOptions, option: ProtoOption) -> Result<(), String> {
+    match option.builtin_name() {
+        Some("deprecated") => options.deprecated = Some(option.bool()?),
+        _ => options.uninterpreted_option.push(option.uninterpreted()),
+    }
+    Ok(())
+}
+
+fn set_method_option(options: &mut MethodOptions, option: ProtoOption) -> Result<(), String> {
+    match option.builtin_name() {
+        Some("deprecated") => options.deprecated = Some(option.bool()?),
+        Some("idempotency_level") => {
+            options.idempotency_level = Some(option.enumeration(|name| {
+                IdempotencyLevel::from_str_name(name).map(|level| level as i32)
+            })?)
+        }
+        _ => options.uninterpreted_option.push(option.uninterpreted()),
+    }
+    Ok(())
+}
@@ -0,0 +1,213 @@
+//! Resolution of the type references of parsed files, following the scoping rules of protoc.
+
+use std::collections::HashMap;
+
+use prost_types::field_descriptor_proto::Type;
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Symbol {
+    Package,
+    Message,
+    Enum,
+    Service,
+}
+
+/// Resolves the type references of the files, which must include all of their imports.
+///
+/// Type names are replaced by their fully qualified names, and the type of fields referring to
+/// messages or enums is set.
+pub(super) fn resolve(files: &mut [FileDescriptorProto]) -> Result<(), String> {
+    let mut symbols = HashMap::new();
+    for file in files.iter() {
+        add_symbols(&mut symbols, file)?;
+    }
+
+    for file in files.iter_mut() {
+        let resolver = Resolver {
+            symbols: &symbols,
+            file_name: file.name().to_owned(),
+        };
+        let package = file.package().to_owned();
+        for message in &mut file.message_type {
+            resolver.message(&package, message)?;
+        }
+        for extension in &mut file.extension {
+            resolver.field(&package, extension)?;
+        }
+        for service in &mut file.service {
+            let scope = join(&package, service.name());
+            for method in &mut service.method {
+                for type_name in [&mut method.input_type, &mut method.output_type]
+                    .into_iter()
+                    .flatten()
+                {
+                    *type_name = resolver.message_type(&scope, type_name)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn join(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{scope}.{name}")
+    }
+}
+
+fn add_symbols(
+    symbols: &mut HashMap<String, Symbol>,
+    file: &FileDescriptorProto,
+) -> Result<(), String> {
+    let mut package = String::new();
+    for part in file.package().split('.').filter(|part| !part.is_empty()) {
+        package = join(&package, part);
+        let symbol = symbols.entry(package.clone()).or_insert(Symbol::Package);
+        if *symbol != Symbol::Package {
+            return Err(format!(
+                "{}: \"{package}\" is already defined as a type",
+                file.name()
+            ));
+        }
+    }
+
+    let mut add = |name: String, symbol: Symbol| {
+        if symbols.insert(name.clone(), symbol).is_some() {
+            Err(format!("{}: \"{name}\" is already defined", file.name()))
+        } else {
+            Ok(())
+        }
+    };
+
+    fn add_message(
+        add: &mut impl FnMut(String, Symbol) -> Result<(), String>,
+        scope: &str,
+        message: &DescriptorProto,
+    ) -> Result<(), String> {
+        let name = join(scope, message.name());
+        add(name.clone(), Symbol::Message)?;
+        for nested in &message.nested_type {
+            add_message(add, &name, nested)?;
+        }
+        for enumeration in &message.enum_type {
+            add_enum(add, &name, enumeration)?;
+        }
+        Ok(())
+    }
+
+    fn add_enum(
+        add: &mut impl FnMut(String, Symbol) -> Result<(), String>,
+        scope: &str,
+        enumeration: &EnumDescriptorProto,
+    ) -> Result<(), String> {
+        add(join(scope, enumeration.name()), Symbol::Enum)
+    }
+
+    for message in &file.message_type {
+        add_message(&mut add, &package, message)?;
+    }
+    for enumeration in &file.enum_type {
+        add_enum(&mut add, &package, enumeration)?;
+    }
+    for service in &file.service {
+        add(join(&package, service.name()), Symbol::Service)?;
+    }
+    Ok(())
+}
+
+struct Resolver<'a> {
+    symbols: &'a HashMap<String, Symbol>,
+    file_name: String,
+}
+
+impl Resolver<'_> {
+    fn message(&self, scope: &str, message: &mut DescriptorProto) -> Result<(), String> {
+        let scope = join(scope, message.name());
+        for field in message.field.iter_mut().chain(&mut message.extension) {
+            self.field(&scope, field)?;
+        }
+        for nested in &mut message.nested_type {
+            self.message(&scope, nested)?;
+        }
+        Ok(())
+    }
+
+    fn field(&self, scope: &str, field: &mut FieldDescriptorProto) -> Result<(), String> {
+        if let Some(type_name) = &field.type_name {
+            let (name, symbol) = self.lookup(scope, type_name)?;
+            // The type of groups is already set.
+            match symbol {
+                Symbol::Message => {
+                    field.r#type.get_or_insert(Type::Message as i32);
+                }
+                Symbol::Enum if field.r#type.is_none() => field.r#type = Some(Type::Enum as i32),
+                _ => {
+                    return Err(format!(
+                        "{}: \"{type_name}\" is not a message or enum type",
+                        self.file_name
+                    ))
+                }
+            }
+            field.type_name = Some(name);
+        }
+        if let Some(extendee) = &field.extendee {
+            field.extendee = Some(self.message_type(scope, extendee)?);
+        }
+        Ok(())
+    }
+
+    fn message_type(&self, scope: &str, type_name: &str) -> Result<String, String> {
+        match self.lookup(scope, type_name)? {
+            (name, Symbol::Message) => Ok(name),
+            _ => Err(format!(
+                "{}: \"{type_name}\" is not a message type",
+                self.file_name
+            )),
+        }
+    }
+
+    /// Looks up a type name from a scope, returning its fully qualified name with a leading dot.
+    ///
+    /// Like protoc, the first component of a relative name is looked up in the scope and each of
+    /// its parents in turn, and the rest of the name is resolved relative to where it was found.
+    fn lookup(&self, scope: &str, type_name: &str) -> Result<(String, Symbol), String> {
+        let not_defined = || format!("{}: \"{type_name}\" is not defined", self.file_name);
+
+        if let Some(name) = type_name.strip_prefix('.') {
+            return self
+                .symbols
+                .get(name)
+                .map(|&symbol| (type_name.to_owned(), symbol))
+                .ok_or_else(not_defined);
+        }
+
+        let first = type_name.split('.').next().unwrap_or_default();
+        let mut scope = scope;
+        loop {
+            match self.symbols.get(&join(scope, first)) {
+                Some(Symbol::Message | Symbol::Enum) if first == type_name => {
+                    let name = join(scope, type_name);
+                    return Ok((format!(".{name}"), self.symbols[&name]));
+                }
+                Some(Symbol::Message | Symbol::Package) if first != type_name => {
+                    let name = join(scope, type_name);
+                    return self
+                        .symbols
+                        .get(&name)
+                        .map(|&symbol| (format!(".{name}"), symbol))
+                        .ok_or_else(not_defined);
+                }
+                _ => {}
+            }
+            if scope.is_empty() {
+                return Err(not_defined());
+            }
+            scope = scope.rfind('.').map_or("", |index| &scope[..index]);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::ident::{to_snake, to_upper_camel};
+use crate::{Service, ServiceGenerator};
+
+/// A service generator which generates transport-agnostic service definitions.
+///
+/// For each service `Foo`, a unit struct `Foo` implementing `prost::service::Service` is
+/// generated, together with a module `foo` holding a unit struct for each method, which implements
+/// `prost::service::ServiceMethod`. These carry the method names, the gRPC paths and the request
+/// and response message types, so that RPC frameworks can serve and call the methods without
+/// providing their own [`ServiceGenerator`].
+///
+/// # Examples
+///
+/// ```rust
+/// # let mut config = prost_build::Config::new();
+/// config.service_generator(Box::new(prost_build::ServiceDefinitionGenerator::new()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServiceDefinitionGenerator {
+    prost_path: String,
+}
+
+impl ServiceDefinitionGenerator {
+    /// Creates a service generator which refers to the `prost` crate as `::prost`.
+    pub fn new() -> ServiceDefinitionGenerator {
+        ServiceDefinitionGenerator {
+            prost_path: "::prost".to_string(),
+        }
+    }
+
+    /// Configures the path of the `prost` crate in the generated code, which should match
+    /// [`Config::prost_path`](crate::Config::prost_path).
+    pub fn prost_path(mut self, path: impl Into<String>) -> ServiceDefinitionGenerator {
+        self.prost_path = path.into();
+        self
+    }
+}
+
+impl Default for ServiceDefinitionGenerator {
+    fn default() -> ServiceDefinitionGenerator {
+        ServiceDefinitionGenerator::new()
+    }
+}
+
+impl ServiceGenerator for ServiceDefinitionGenerator {
+    fn generate(&mut self, service: Service, buf: &mut String) {
+        let prost_path = &self.prost_path;
+        let name = &service.name;
+        let module = to_snake(name);
+        let full_name = if service.package.is_empty() {
+            service.proto_name.clone()
+        } else {
+            format!("{}.{}", service.package, service.proto_name)
+        };
+
+        if service.comments.leading.is_empty() {
+            buf.push_str(&format!("/// The `{full_name}` service.\n"));
+        }
+        service.comments.append_with_indent(0, buf);
+        buf.push_str(&format!(
+            "#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+            pub struct {name};
+            impl {prost_path}::service::Service for {name} {{
+                const DESCRIPTOR: {prost_path}::service::ServiceDescriptor = {prost_path}::service::ServiceDescriptor {{
+                    name: {proto_name:?},
+                    package: {package:?},
+                    full_name: {full_name:?},
+                    methods: &[\n",
+            proto_name = service.proto_name,
+            package = service.package,
+        ));
+        for method in &service.methods {
+            buf.push_str(&format!(
+                "<{module}::{} as {prost_path}::service::ServiceMethod>::DESCRIPTOR,\n",
+                to_upper_camel(&method.proto_name)
+            ));
+        }
+        buf.push_str("],\n};\n}\n");
+
+        buf.push_str(&format!(
+            "/// Methods of the [`{name}`] service.
+            pub mod {module} {{\n"
+        ));
+        for method in &service.methods {
+            if method.comments.leading.is_empty() {
+                buf.push_str(&format!("/// The `{}` method.\n", method.proto_name));
+            }
+            method.comments.append_with_indent(1, buf);
+            buf.push_str(&format!(
+                "#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+                pub struct {method_name};
+                impl {prost_path}::service::ServiceMethod for {method_name} {{
+                    type Service = super::{name};
+                    type Request = {request};
+                    type Response = {response};
+                    const DESCRIPTOR: {prost_path}::service::MethodDescriptor = {prost_path}::service::MethodDescriptor {{
+                        name: {proto_name:?},
+                        path: {path:?},
+                        client_streaming: {client_streaming},
+                        server_streaming: {server_streaming},
+                    }};
+                }}\n",
+                method_name = to_upper_camel(&method.proto_name),
+                request = super_path(&method.input_type),
+                response = super_path(&method.output_type),
+                proto_name = method.proto_name,
+                path = format!("/{full_name}/{}", method.proto_name),
+                client_streaming = method.client_streaming,
+                server_streaming = method.server_streaming,
+            ));
+        }
+        buf.push_str("}\n");
+    }
+}
+
+/// Resolves a type path relative to the service module from within its methods module.
+fn super_path(path: &str) -> String {
+    if path.starts_with("::") || path.starts_with("crate::") || path == "()" {
+        path.to_string()
+    } else {
+        format!("super::{path}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Comments, Method};
+
+    fn method(name: &str, input_type: &str, output_type: &str) -> Method {
+        Method {
+            name: to_snake(name),
+            proto_name: name.to_string(),
+            comments: Comments::default(),
+            input_type: input_type.to_string(),
+            output_type: output_type.to_string(),
+            input_proto_type: String::new(),
+            output_proto_type: String::new(),
+            options: Default::default(),
+            client_streaming: false,
+            server_streaming: true,
+        }
+    }
+
+    #[test]
+    fn generate_definitions() {
+        let service = Service {
+            name: "Greeter".to_string(),
+            proto_name: "Greeter".to_string(),
+            package: "helloworld".to_string(),
+            comments: Comments::default(),
+            methods: vec![
+                method("SayHello", "HelloRequest", "::other::HelloReply"),
+                method("Ping", "()", "()"),
+                method(
+                    "say_goodbye",
+                    "super::GoodbyeRequest",
+                    "crate::GoodbyeReply",
+                ),
+            ],
+            options: Default::default(),
+        };
+        let mut buf = String::new();
+        ServiceDefinitionGenerator::new()
+            .prost_path("::custom::prost")
+            .generate(service, &mut buf);
+
+        assert!(buf.contains("pub struct Greeter;"));
+        assert!(buf.contains("impl ::custom::prost::service::Service for Greeter {"));
+        assert!(buf.contains("full_name: \"helloworld.Greeter\","));
+        assert!(buf.contains(
+            "<greeter::SayHello as ::custom::prost::service::ServiceMethod>::DESCRIPTOR,"
+        ));
+        assert!(buf.contains("pub mod greeter {"));
+        assert!(buf.contains("type Request = super::HelloRequest;"));
+        assert!(buf.contains("type Response = ::other::HelloReply;"));
+        assert!(buf.contains("path: \"/helloworld.Greeter/SayHello\","));
+        assert!(buf.contains("type Request = ();"));
+        assert!(buf.contains("pub struct SayGoodbye;"));
+        assert!(buf.contains("type Request = super::super::GoodbyeRequest;"));
+        assert!(buf.contains("type Response = crate::GoodbyeReply;"));
+        assert!(buf.contains("path: \"/helloworld.Greeter/say_goodbye\","));
+        assert!(buf.contains("server_streaming: true,"));
+    }
+
+    #[test]
+    fn generate_without_package() {
+        let service = Service {
+            name: "Echo".to_string(),
+            proto_name: "Echo".to_string(),
+            package: String::new(),
+            comments: Comments::default(),
+            methods: vec![method("Say", "Message", "Message")],
+            options: Default::default(),
+        };
+        let mut buf = String::new();
+        ServiceDefinitionGenerator::new().generate(service, &mut buf);
+
+        assert!(buf.contains("impl ::prost::service::Service for Echo {"));
+        assert!(buf.contains("full_name: \"Echo\","));
+        assert!(buf.contains("path: \"/Echo/Say\","));
+    }
+}
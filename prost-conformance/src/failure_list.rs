@@ -0,0 +1,144 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::Report;
+
+/// A list of the conformance tests which are expected to fail.
+///
+/// The list is a text file with the name of one test per line. Lines starting with `#` are
+/// comments, and anything after a `#` on a line with a test name is ignored, so a test may be
+/// annotated with the reason it fails. Comments are preserved when the list is updated.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FailureList {
+    lines: Vec<String>,
+}
+
+impl FailureList {
+    /// Creates an empty failure list.
+    pub fn new() -> FailureList {
+        FailureList::default()
+    }
+
+    /// Parses a failure list.
+    pub fn parse(contents: &str) -> FailureList {
+        FailureList {
+            lines: contents.lines().map(str::to_owned).collect(),
+        }
+    }
+
+    /// Reads a failure list from a file.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<FailureList> {
+        fs::read_to_string(path).map(|contents| FailureList::parse(&contents))
+    }
+
+    /// Writes the failure list to a file.
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// Returns the names of the tests in the list.
+    pub fn tests(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().filter_map(|line| test_name(line))
+    }
+
+    /// Returns `true` if the test is in the list.
+    pub fn contains(&self, test: &str) -> bool {
+        self.tests().any(|name| name == test)
+    }
+
+    /// Appends a test to the list, and returns `true` if it was not already in the list.
+    pub fn insert(&mut self, test: &str) -> bool {
+        if self.contains(test) {
+            return false;
+        }
+        self.lines.push(test.to_owned());
+        true
+    }
+
+    /// Removes a test from the list, and returns `true` if it was in the list.
+    pub fn remove(&mut self, test: &str) -> bool {
+        let len = self.lines.len();
+        self.lines.retain(|line| test_name(line) != Some(test));
+        self.lines.len() != len
+    }
+
+    /// Updates the list with the results of a run: the tests which failed unexpectedly are added,
+    /// and the tests which succeeded even though they were listed are removed.
+    pub fn update(&mut self, report: &Report) {
+        for test in report.unexpected_failures() {
+            self.insert(test);
+        }
+        for test in report.unexpected_successes() {
+            self.remove(test);
+        }
+    }
+}
+
+impl fmt::Display for FailureList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the name of the test on a line of a failure list, if any.
+pub(crate) fn test_name(line: &str) -> Option<&str> {
+    let name = match line.find('#') {
+        Some(index) => &line[..index],
+        None => line,
+    }
+    .trim();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LIST: &str = "\
+# Unknown fields are not preserved.
+Required.Proto3.ProtobufInput.UnknownVarint.ProtobufOutput
+Recommended.Proto3.JsonInput.Nan.JsonOutput  # NaN is not supported.
+
+Required.Proto2.ProtobufInput.UnknownVarint.ProtobufOutput
+";
+
+    #[test]
+    fn parse() {
+        let list = FailureList::parse(LIST);
+        assert_eq!(
+            list.tests().collect::<Vec<_>>(),
+            [
+                "Required.Proto3.ProtobufInput.UnknownVarint.ProtobufOutput",
+                "Recommended.Proto3.JsonInput.Nan.JsonOutput",
+                "Required.Proto2.ProtobufInput.UnknownVarint.ProtobufOutput",
+            ]
+        );
+        assert!(list.contains("Recommended.Proto3.JsonInput.Nan.JsonOutput"));
+        assert!(!list.contains("Unknown fields are not preserved."));
+        assert_eq!(list.to_string(), LIST);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut list = FailureList::parse(LIST);
+        assert!(list.remove("Recommended.Proto3.JsonInput.Nan.JsonOutput"));
+        assert!(!list.remove("Recommended.Proto3.JsonInput.Nan.JsonOutput"));
+        assert!(list.insert("Required.Proto3.JsonInput.Any.JsonOutput"));
+        assert!(!list.insert("Required.Proto3.JsonInput.Any.JsonOutput"));
+        assert_eq!(
+            list.to_string(),
+            "\
+# Unknown fields are not preserved.
+Required.Proto3.ProtobufInput.UnknownVarint.ProtobufOutput
+
+Required.Proto2.ProtobufInput.UnknownVarint.ProtobufOutput
+Required.Proto3.JsonInput.Any.JsonOutput
+"
+        );
+    }
+}
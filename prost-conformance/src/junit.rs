@@ -0,0 +1,98 @@
+use std::fmt::Write;
+
+use crate::Report;
+
+/// Renders a report as a JUnit XML test suite.
+pub(crate) fn render(report: &Report, suite: &str) -> String {
+    let failures = report.unexpected_failures.len() + report.unexpected_successes.len();
+    let skipped = report.skipped + report.expected_failures.len();
+    let tests = report.successes
+        + report.skipped
+        + report.expected_failures.len()
+        + report.unexpected_failures.len();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let suite = escape(suite);
+    let _ = writeln!(
+        xml,
+        "<testsuite name=\"{suite}\" tests=\"{tests}\" failures=\"{failures}\" skipped=\"{skipped}\">"
+    );
+    for test in &report.unexpected_failures {
+        test_case(
+            &mut xml,
+            &suite,
+            test,
+            "<failure message=\"unexpected failure\"/>",
+        );
+    }
+    for test in &report.unexpected_successes {
+        test_case(
+            &mut xml,
+            &suite,
+            test,
+            "<failure message=\"succeeded, but is listed in the failure list\"/>",
+        );
+    }
+    for test in &report.expected_failures {
+        test_case(
+            &mut xml,
+            &suite,
+            test,
+            "<skipped message=\"expected failure\"/>",
+        );
+    }
+    xml.push_str("</testsuite>\n");
+    xml
+}
+
+fn test_case(xml: &mut String, suite: &str, test: &str, result: &str) {
+    let _ = writeln!(
+        xml,
+        "  <testcase classname=\"{suite}\" name=\"{}\">{result}</testcase>",
+        escape(test)
+    );
+}
+
+/// Escapes a string for use in an XML attribute.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn render_report() {
+        let report = Report {
+            passed: false,
+            successes: 10,
+            skipped: 2,
+            expected_failures: vec!["Required.Proto3.JsonInput.Nan".to_owned()],
+            unexpected_failures: vec!["Required.Proto3.JsonInput.Any".to_owned()],
+            unexpected_successes: vec!["Required.Proto2.ProtobufInput.Unknown".to_owned()],
+        };
+        assert_eq!(
+            render(&report, "prost <json>"),
+            "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<testsuite name=\"prost &lt;json&gt;\" tests=\"14\" failures=\"2\" skipped=\"3\">
+  <testcase classname=\"prost &lt;json&gt;\" name=\"Required.Proto3.JsonInput.Any\"><failure message=\"unexpected failure\"/></testcase>
+  <testcase classname=\"prost &lt;json&gt;\" name=\"Required.Proto2.ProtobufInput.Unknown\"><failure message=\"succeeded, but is listed in the failure list\"/></testcase>
+  <testcase classname=\"prost &lt;json&gt;\" name=\"Required.Proto3.JsonInput.Nan\"><skipped message=\"expected failure\"/></testcase>
+</testsuite>
+"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! A harness for the Protobuf conformance test suite.
+//!
+//! The `conformance-test-runner` of the Protobuf project sends test cases to a testee binary,
+//! which decodes each request, converts the payload to the requested output format, and writes
+//! back a response. The runner compares the responses with the expected output.
+//!
+//! This crate drives the runner against any testee, so that each codec built on `prost`, such as
+//! the JSON and text format implementations, can be tested with the same harness:
+//!
+//! - [`serve`] implements the framing of the requests and responses for the testee.
+//! - [`Runner`] runs the test suite against a testee and returns a [`Report`].
+//! - [`FailureList`] manages the list of tests which are expected to fail.
+//! - [`Report::to_junit`] renders the results as JUnit XML for CI systems.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use prost_conformance::{FailureList, Runner};
+//!
+//! let report = Runner::new("conformance-test-runner")
+//!     .enforce_recommended(true)
+//!     .failure_list("failing_tests.txt")
+//!     .run("target/debug/conformance")?;
+//! report.write_junit("prost", "target/conformance.xml")?;
+//!
+//! if !report.passed() {
+//!     // Accept the current results as the new baseline.
+//!     let mut failures = FailureList::read("failing_tests.txt")?;
+//!     failures.update(&report);
+//!     failures.write("failing_tests.txt")?;
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+mod failure_list;
+mod junit;
+mod runner;
+mod testee;
+
+pub use crate::failure_list::FailureList;
+pub use crate::runner::{Report, Runner};
+pub use crate::testee::serve;
@@ -0,0 +1,234 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::failure_list::test_name;
+use crate::{junit, FailureList};
+
+/// Runs the `conformance-test-runner` against a testee binary.
+#[derive(Clone, Debug)]
+pub struct Runner {
+    program: PathBuf,
+    failure_list: Option<PathBuf>,
+    text_format_failure_list: Option<PathBuf>,
+    enforce_recommended: bool,
+    maximum_edition: Option<String>,
+}
+
+impl Runner {
+    /// Creates a runner which executes the `conformance-test-runner` at `program`.
+    pub fn new(program: impl Into<PathBuf>) -> Runner {
+        Runner {
+            program: program.into(),
+            failure_list: None,
+            text_format_failure_list: None,
+            enforce_recommended: false,
+            maximum_edition: None,
+        }
+    }
+
+    /// Sets the list of binary and JSON tests which are expected to fail.
+    pub fn failure_list(mut self, path: impl Into<PathBuf>) -> Self {
+        self.failure_list = Some(path.into());
+        self
+    }
+
+    /// Sets the list of text format tests which are expected to fail.
+    pub fn text_format_failure_list(mut self, path: impl Into<PathBuf>) -> Self {
+        self.text_format_failure_list = Some(path.into());
+        self
+    }
+
+    /// Fail the run if a recommended test fails, rather than only if a required test fails.
+    pub fn enforce_recommended(mut self, enabled: bool) -> Self {
+        self.enforce_recommended = enabled;
+        self
+    }
+
+    /// Sets the latest edition whose tests are run, such as `2023`.
+    pub fn maximum_edition(mut self, edition: impl Into<String>) -> Self {
+        self.maximum_edition = Some(edition.into());
+        self
+    }
+
+    /// Runs the test suite against the testee binary at `testee`.
+    ///
+    /// The output of the runner is forwarded to stderr. An error is only returned if the runner
+    /// could not be executed; test failures are reported by the returned [`Report`].
+    pub fn run(&self, testee: impl AsRef<Path>) -> io::Result<Report> {
+        // The runner writes the names of the unexpected failures and successes to files in the
+        // output directory.
+        let output_dir = tempfile::tempdir()?;
+        let mut output_dir_arg = OsString::from(output_dir.path());
+        output_dir_arg.push("/");
+
+        let mut command = Command::new(&self.program);
+        if self.enforce_recommended {
+            command.arg("--enforce_recommended");
+        }
+        if let Some(ref path) = self.failure_list {
+            command.arg("--failure_list").arg(path);
+        }
+        if let Some(ref path) = self.text_format_failure_list {
+            command.arg("--text_format_failure_list").arg(path);
+        }
+        if let Some(ref edition) = self.maximum_edition {
+            command.arg("--maximum_edition").arg(edition);
+        }
+        command
+            .arg("--output_dir")
+            .arg(output_dir_arg)
+            .arg(testee.as_ref());
+
+        let output = command.output()?;
+        let mut stderr = io::stderr().lock();
+        stderr.write_all(&output.stdout)?;
+        stderr.write_all(&output.stderr)?;
+
+        let mut expected_failures = Vec::new();
+        for path in [&self.failure_list, &self.text_format_failure_list]
+            .into_iter()
+            .flatten()
+        {
+            expected_failures.extend(FailureList::read(path)?.tests().map(str::to_owned));
+        }
+
+        let log = String::from_utf8_lossy(&output.stdout) + String::from_utf8_lossy(&output.stderr);
+        let mut report = Report {
+            passed: output.status.success(),
+            unexpected_failures: read_tests(&output_dir.path().join("failing_tests.txt"))?,
+            unexpected_successes: read_tests(&output_dir.path().join("succeeding_tests.txt"))?,
+            ..Report::default()
+        };
+        report.expected_failures = expected_failures
+            .into_iter()
+            .filter(|test| !report.unexpected_successes.contains(test))
+            .collect();
+        if let Some((successes, skipped)) = parse_summary(&log) {
+            report.successes = successes;
+            report.skipped = skipped;
+        }
+        Ok(report)
+    }
+}
+
+/// The results of a conformance test run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub(crate) passed: bool,
+    pub(crate) successes: usize,
+    pub(crate) skipped: usize,
+    pub(crate) expected_failures: Vec<String>,
+    pub(crate) unexpected_failures: Vec<String>,
+    pub(crate) unexpected_successes: Vec<String>,
+}
+
+impl Report {
+    /// Returns `true` if the run passed: every test either succeeded, was skipped, or failed as
+    /// expected.
+    pub fn passed(&self) -> bool {
+        self.passed
+    }
+
+    /// Returns the number of tests which succeeded.
+    pub fn successes(&self) -> usize {
+        self.successes
+    }
+
+    /// Returns the number of tests which the testee skipped.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Returns the names of the tests which failed, as listed in the failure lists.
+    pub fn expected_failures(&self) -> &[String] {
+        &self.expected_failures
+    }
+
+    /// Returns the names of the tests which failed, but are not listed in a failure list.
+    pub fn unexpected_failures(&self) -> &[String] {
+        &self.unexpected_failures
+    }
+
+    /// Returns the names of the tests which succeeded, even though they are listed in a failure
+    /// list.
+    pub fn unexpected_successes(&self) -> &[String] {
+        &self.unexpected_successes
+    }
+
+    /// Renders the report as a JUnit XML test suite named `suite`.
+    ///
+    /// The runner only reports the names of failing tests, so tests which succeeded are counted
+    /// by the test suite, but are not listed as test cases.
+    pub fn to_junit(&self, suite: &str) -> String {
+        junit::render(self, suite)
+    }
+
+    /// Writes the report as a JUnit XML test suite named `suite` to a file.
+    pub fn write_junit(&self, suite: &str, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_junit(suite))
+    }
+}
+
+/// Reads the test names from a file written by the runner, which is absent if there are none.
+fn read_tests(path: &Path) -> io::Result<Vec<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents
+            .lines()
+            .filter_map(test_name)
+            .map(str::to_owned)
+            .collect()),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error),
+    }
+}
+
+/// Parses the numbers of successful and skipped tests from the summary line of the runner, such
+/// as `CONFORMANCE SUITE PASSED: 2040 successes, 0 skipped, 3 expected failures, 0 unexpected
+/// failures.`
+fn parse_summary(log: &str) -> Option<(usize, usize)> {
+    let line = log
+        .lines()
+        .rev()
+        .find(|line| line.contains("CONFORMANCE SUITE"))?;
+    let (_, counts) = line.split_once(':')?;
+    let mut successes = None;
+    let mut skipped = None;
+    for count in counts.split(',') {
+        let mut words = count.split_whitespace();
+        let value = words.next()?.parse().ok()?;
+        match words.next()?.trim_end_matches('.') {
+            "successes" => successes = Some(value),
+            "skipped" => skipped = Some(value),
+            _ => (),
+        }
+    }
+    Some((successes?, skipped?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn summary() {
+        let log = "\
+CONFORMANCE TEST BEGIN ====================================
+
+CONFORMANCE SUITE PASSED: 2040 successes, 12 skipped, 3 expected failures, 0 unexpected failures.
+";
+        assert_eq!(parse_summary(log), Some((2040, 12)));
+        assert_eq!(parse_summary("CONFORMANCE SUITE FAILED"), None);
+        assert_eq!(parse_summary(""), None);
+    }
+
+    #[test]
+    fn missing_runner() {
+        let error = Runner::new("/nonexistent/conformance-test-runner")
+            .run("/nonexistent/testee")
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,67 @@
+use std::io::{self, ErrorKind, Read, Write};
+
+/// Serves conformance test requests until the runner closes the input.
+///
+/// Each request is read from stdin and passed to `handle` as an encoded `ConformanceRequest`.
+/// `handle` returns the encoded `ConformanceResponse`, which is written to stdout.
+pub fn serve<F>(handle: F) -> io::Result<()>
+where
+    F: FnMut(&[u8]) -> Vec<u8>,
+{
+    serve_io(io::stdin().lock(), io::stdout().lock(), handle)
+}
+
+/// Serves requests from `input`, writing the responses to `output`.
+///
+/// Requests and responses are prefixed with their length as a little-endian `u32`.
+fn serve_io<F>(mut input: impl Read, mut output: impl Write, mut handle: F) -> io::Result<()>
+where
+    F: FnMut(&[u8]) -> Vec<u8>,
+{
+    let mut request = Vec::new();
+    loop {
+        let mut len = [0; 4];
+        match input.read_exact(&mut len) {
+            Ok(()) => (),
+            // No more test cases.
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+            Err(error) => return Err(error),
+        }
+
+        request.resize(u32::from_le_bytes(len) as usize, 0);
+        input.read_exact(&mut request)?;
+
+        let response = handle(&request);
+        let len = u32::try_from(response.len())
+            .map_err(|_| io::Error::new(ErrorKind::InvalidData, "response is too large"))?;
+        output.write_all(&len.to_le_bytes())?;
+        output.write_all(&response)?;
+        output.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn frames_requests_and_responses() {
+        let input = [2, 0, 0, 0, b'a', b'b', 0, 0, 0, 0];
+        let mut output = Vec::new();
+        serve_io(&input[..], &mut output, |request| {
+            let mut response = request.to_vec();
+            response.reverse();
+            response.push(b'!');
+            response
+        })
+        .unwrap();
+        assert_eq!(output, [3, 0, 0, 0, b'b', b'a', b'!', 1, 0, 0, 0, b'!']);
+    }
+
+    #[test]
+    fn truncated_request() {
+        let input = [2, 0, 0, 0, b'a'];
+        let error = serve_io(&input[..], Vec::new(), |request| request.to_vec()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::{bail, Error};
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::punctuated::Punctuated;
-use syn::{Expr, ExprLit, Ident, Lit, Meta, MetaNameValue, Path, Token};
+use syn::{Expr, ExprLit, Ident, Lit, Meta, MetaNameValue, Path, Token, Type};
 
 use crate::field::{scalar, set_option, tag_attr};
 
@@ -10,6 +10,8 @@ use crate::field::{scalar, set_option, tag_attr};
 pub enum MapTy {
     HashMap,
     BTreeMap,
+    /// A custom map type, which is iterated by reference and extended with entries.
+    Custom,
 }
 
 impl MapTy {
@@ -17,6 +19,7 @@ impl MapTy {
         match s {
             "map" | "hash_map" => Some(MapTy::HashMap),
             "btree_map" => Some(MapTy::BTreeMap),
+            "custom_map" => Some(MapTy::Custom),
             _ => None,
         }
     }
@@ -25,13 +28,16 @@ impl MapTy {
         match *self {
             MapTy::HashMap => Ident::new("hash_map", Span::call_site()),
             MapTy::BTreeMap => Ident::new("btree_map", Span::call_site()),
+            MapTy::Custom => Ident::new("custom_map", Span::call_site()),
         }
     }
 
-    fn lib(&self) -> TokenStream {
+    /// Returns the map type of the field for the Debug wrapper, or `None` for a custom map type.
+    fn debug_type(&self) -> Option<TokenStream> {
         match self {
-            MapTy::HashMap => quote! { std },
-            MapTy::BTreeMap => quote! { prost::alloc },
+            MapTy::HashMap => Some(quote!(::std::collections::HashMap)),
+            MapTy::BTreeMap => Some(quote!(::prost::alloc::collections::BTreeMap)),
+            MapTy::Custom => None,
         }
     }
 }
@@ -42,6 +48,9 @@ fn fake_scalar(ty: scalar::Ty) -> scalar::Field {
         ty,
         kind,
         tag: 0, // Not used here
+        required: false,
+        convert: false,
+        presence_bit: None,
     }
 }
 
@@ -125,16 +134,57 @@ impl Field {
 
     /// Returns a statement which encodes the map field.
     pub fn encode(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let module = self.map_ty.module();
+        self.encode_with(
+            prost_path,
+            ident,
+            quote!(#prost_path::encoding::#module::encode),
+            quote!(#prost_path::encoding::#module::encode_with_default),
+            quote!(#prost_path::encoding::message::encode),
+        )
+    }
+
+    /// Returns a statement which encodes the map field with the deterministic encoding, which
+    /// encodes the entries in the order of their keys.
+    pub fn encode_deterministic(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let (encode, encode_with_default) = match self.map_ty {
+            MapTy::BTreeMap => (
+                quote!(#prost_path::encoding::btree_map::encode),
+                quote!(#prost_path::encoding::btree_map::encode_with_default),
+            ),
+            MapTy::HashMap | MapTy::Custom => (
+                quote!(#prost_path::encoding::custom_map::encode_sorted),
+                quote!(#prost_path::encoding::custom_map::encode_sorted_with_default),
+            ),
+        };
+        self.encode_with(
+            prost_path,
+            ident,
+            encode,
+            encode_with_default,
+            quote!(#prost_path::encoding::message::encode_deterministic),
+        )
+    }
+
+    /// Returns a statement which encodes the map field with the map encode functions `encode`
+    /// and `encode_with_default`, and the message encode function `message_encode`.
+    fn encode_with(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        encode: TokenStream,
+        encode_with_default: TokenStream,
+        message_encode: TokenStream,
+    ) -> TokenStream {
         let tag = self.tag;
         let key_mod = self.key_ty.module();
         let ke = quote!(#prost_path::encoding::#key_mod::encode);
         let kl = quote!(#prost_path::encoding::#key_mod::encoded_len);
-        let module = self.map_ty.module();
         match &self.value_ty {
             ValueTy::Scalar(scalar::Ty::Enumeration(ty)) => {
                 let default = quote!(#ty::default() as i32);
                 quote! {
-                    #prost_path::encoding::#module::encode_with_default(
+                    #encode_with_default(
                         #ke,
                         #kl,
                         #prost_path::encoding::int32::encode,
@@ -151,7 +201,7 @@ impl Field {
                 let ve = quote!(#prost_path::encoding::#val_mod::encode);
                 let vl = quote!(#prost_path::encoding::#val_mod::encoded_len);
                 quote! {
-                    #prost_path::encoding::#module::encode(
+                    #encode(
                         #ke,
                         #kl,
                         #ve,
@@ -163,10 +213,10 @@ impl Field {
                 }
             }
             ValueTy::Message => quote! {
-                #prost_path::encoding::#module::encode(
+                #encode(
                     #ke,
                     #kl,
-                    #prost_path::encoding::message::encode,
+                    #message_encode,
                     #prost_path::encoding::message::encoded_len,
                     #tag,
                     &#ident,
@@ -182,6 +232,11 @@ impl Field {
         let key_mod = self.key_ty.module();
         let km = quote!(#prost_path::encoding::#key_mod::merge);
         let module = self.map_ty.module();
+        // Custom maps are generic over the map type, so the map must not be a `&mut &mut M`.
+        let map = match self.map_ty {
+            MapTy::Custom => quote!(&mut *#ident),
+            _ => quote!(&mut #ident),
+        };
         match &self.value_ty {
             ValueTy::Scalar(scalar::Ty::Enumeration(ty)) => {
                 let default = quote!(#ty::default() as i32);
@@ -190,7 +245,7 @@ impl Field {
                         #km,
                         #prost_path::encoding::int32::merge,
                         #default,
-                        &mut #ident,
+                        #map,
                         buf,
                         ctx,
                     )
@@ -199,13 +254,13 @@ impl Field {
             ValueTy::Scalar(value_ty) => {
                 let val_mod = value_ty.module();
                 let vm = quote!(#prost_path::encoding::#val_mod::merge);
-                quote!(#prost_path::encoding::#module::merge(#km, #vm, &mut #ident, buf, ctx))
+                quote!(#prost_path::encoding::#module::merge(#km, #vm, #map, buf, ctx))
             }
             ValueTy::Message => quote! {
                 #prost_path::encoding::#module::merge(
                     #km,
                     #prost_path::encoding::message::merge,
-                    &mut #ident,
+                    #map,
                     buf,
                     ctx,
                 )
@@ -248,8 +303,177 @@ impl Field {
         }
     }
 
+    /// Returns a constant expression which evaluates to the default value of the field, given
+    /// the Rust type of the field.
+    pub fn const_default(&self, ty: &Type) -> Result<TokenStream, Error> {
+        match self.map_ty {
+            MapTy::BTreeMap => Ok(quote!(<#ty>::new())),
+            _ => bail!("only btree_map fields have a constant default"),
+        }
+    }
+
     pub fn clear(&self, ident: TokenStream) -> TokenStream {
-        quote!(#ident.clear())
+        match self.map_ty {
+            MapTy::Custom => quote!(#ident = ::core::default::Default::default()),
+            _ => quote!(#ident.clear()),
+        }
+    }
+
+    /// Returns a statement which clears the sensitive fields of the message values. The values
+    /// of custom map types are not visited, since they are only iterated by reference.
+    pub fn redact(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        match (&self.map_ty, &self.value_ty) {
+            (MapTy::HashMap | MapTy::BTreeMap, ValueTy::Message) => quote! {
+                for (_, value) in &mut #ident {
+                    #prost_path::Message::redact(value);
+                }
+            },
+            _ => TokenStream::new(),
+        }
+    }
+
+    /// Returns a statement which merges the map field `other` into the field, replacing the
+    /// entries with the same keys.
+    pub fn merge_from(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        other: TokenStream,
+    ) -> TokenStream {
+        let value = match self.value_ty {
+            ValueTy::Scalar(_) => quote!(::core::clone::Clone::clone(value)),
+            ValueTy::Message => quote! {{
+                let mut merged = ::core::default::Default::default();
+                #prost_path::Message::merge_from(&mut merged, value);
+                merged
+            }},
+        };
+        quote! {
+            for (key, value) in &#other {
+                ::core::iter::Extend::extend(
+                    &mut #ident,
+                    ::core::iter::once((::core::clone::Clone::clone(key), #value)),
+                );
+            }
+        }
+    }
+
+    /// Returns a statement which appends the paths of the missing required fields of the message
+    /// values of the map to `missing`.
+    pub fn missing_required_fields(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        name: &str,
+    ) -> TokenStream {
+        match self.value_ty {
+            ValueTy::Scalar(_) => TokenStream::new(),
+            ValueTy::Message => quote! {
+                for (key, value) in &#ident {
+                    #prost_path::encoding::message::collect_missing_required_fields(
+                        value,
+                        prefix,
+                        ::core::format_args!("{}[{:?}]", #name, key),
+                        missing,
+                    );
+                }
+            },
+        }
+    }
+
+    /// Returns a statement which adds the JSON representation of the map to `object`.
+    pub fn json_encode(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let json = quote!(#prost_path::json::encoding);
+        let value_to_json = match &self.value_ty {
+            ValueTy::Scalar(scalar::Ty::Enumeration(ty)) => {
+                let enumeration = scalar::json_enumeration(prost_path, ty);
+                quote!(::core::result::Result::Ok(#enumeration.to_json(*value)))
+            }
+            _ => quote!(#json::JsonField::to_json(value, options)),
+        };
+        quote! {
+            if #json::emit_default_values(options)
+                || ::core::iter::IntoIterator::into_iter(&#ident).next().is_some()
+            {
+                object.push((name.into(), #json::encode_map(&#ident, |value| #value_to_json)?));
+            }
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of merging the JSON `value` into the
+    /// map.
+    pub fn json_merge(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let json = quote!(#prost_path::json::encoding);
+        let value_from_json = match &self.value_ty {
+            ValueTy::Scalar(scalar::Ty::Enumeration(ty)) => {
+                let enumeration = scalar::json_enumeration(prost_path, ty);
+                quote!(#enumeration.from_json(value, options))
+            }
+            _ => quote!(#json::map_value(value, options)),
+        };
+        quote!(#json::merge_map(&mut #ident, value, |value| #value_from_json))
+    }
+
+    /// Returns a statement which inserts arbitrary entries generated from `u` into the map, with
+    /// message values up to the recursion limit.
+    pub fn arbitrary(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let arbitrary = quote!(#prost_path::encoding::arbitrary);
+        let key_ty = self.key_ty.rust_type(prost_path);
+        let key = quote!(<#key_ty as #prost_path::arbitrary::Arbitrary>::arbitrary(u)?);
+        match &self.value_ty {
+            ValueTy::Scalar(value_ty) => {
+                let value = value_ty.arbitrary(prost_path);
+                quote! {
+                    let entries = #arbitrary::repeated(u, |u| {
+                        ::core::result::Result::Ok((#key, #value))
+                    })?;
+                    ::core::iter::Extend::extend(&mut #ident, entries);
+                }
+            }
+            ValueTy::Message => quote! {
+                let entries = #arbitrary::nested(u, |u| {
+                    #arbitrary::repeated(u, |u| {
+                        ::core::result::Result::Ok((
+                            #key,
+                            #prost_path::arbitrary::Arbitrary::arbitrary(u)?,
+                        ))
+                    })
+                })?;
+                if let ::core::option::Option::Some(entries) = entries {
+                    ::core::iter::Extend::extend(&mut #ident, entries);
+                }
+            },
+        }
+    }
+
+    /// Returns a statement which appends the text format representation of the map entries to
+    /// `message`.
+    pub fn text_encode(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let text = quote!(#prost_path::text_format::encoding);
+        let value_to_text = match &self.value_ty {
+            ValueTy::Scalar(scalar::Ty::Enumeration(ty)) => {
+                let enumeration = scalar::text_enumeration(prost_path, ty);
+                quote!(#enumeration.to_text(*value))
+            }
+            _ => quote!(#text::TextField::to_text_value(value)),
+        };
+        quote! {
+            #text::encode_map(message, name, &#ident, |value| #value_to_text);
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of merging the text format map entry
+    /// `value` into the map.
+    pub fn text_merge(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let text = quote!(#prost_path::text_format::encoding);
+        let value_from_text = match &self.value_ty {
+            ValueTy::Scalar(scalar::Ty::Enumeration(ty)) => {
+                let enumeration = scalar::text_enumeration(prost_path, ty);
+                quote!(#enumeration.from_text(value))
+            }
+            _ => quote!(#text::TextField::from_text_value(value)),
+        };
+        quote!(#text::merge_map(&mut #ident, value, |value| #value_from_text))
     }
 
     /// Returns methods to embed in the message.
@@ -297,16 +521,10 @@ impl Field {
     /// The Debug tries to convert any enumerations met into the variants if possible, instead of
     /// outputting the raw numbers.
     pub fn debug(&self, prost_path: &Path, wrapper_name: TokenStream) -> TokenStream {
-        let type_name = match self.map_ty {
-            MapTy::HashMap => Ident::new("HashMap", Span::call_site()),
-            MapTy::BTreeMap => Ident::new("BTreeMap", Span::call_site()),
-        };
-
         // A fake field for generating the debug wrapper
         let key_wrapper = fake_scalar(self.key_ty.clone()).debug(prost_path, quote!(KeyWrapper));
         let key = self.key_ty.rust_type(prost_path);
         let value_wrapper = self.value_ty.debug(prost_path);
-        let libname = self.map_ty.lib();
         let fmt = quote! {
             fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
                 #key_wrapper
@@ -318,29 +536,39 @@ impl Field {
                 builder.finish()
             }
         };
-        match &self.value_ty {
-            ValueTy::Scalar(ty) => {
-                if let scalar::Ty::Bytes(_) = *ty {
-                    return quote! {
-                        struct #wrapper_name<'a>(&'a dyn ::core::fmt::Debug);
-                        impl<'a> ::core::fmt::Debug for #wrapper_name<'a> {
-                            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-                                self.0.fmt(f)
-                            }
-                        }
-                    };
+        let forward = quote! {
+            struct #wrapper_name<'a>(&'a dyn ::core::fmt::Debug);
+            impl<'a> ::core::fmt::Debug for #wrapper_name<'a> {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    self.0.fmt(f)
                 }
-
+            }
+        };
+        match (&self.value_ty, self.map_ty.debug_type()) {
+            (ValueTy::Scalar(scalar::Ty::Bytes(_)), _) => forward,
+            (ValueTy::Scalar(ty), Some(map_ty)) => {
                 let value = ty.rust_type(prost_path);
                 quote! {
-                    struct #wrapper_name<'a>(&'a ::#libname::collections::#type_name<#key, #value>);
+                    struct #wrapper_name<'a>(&'a #map_ty<#key, #value>);
                     impl<'a> ::core::fmt::Debug for #wrapper_name<'a> {
                         #fmt
                     }
                 }
             }
-            ValueTy::Message => quote! {
-                struct #wrapper_name<'a, V: 'a>(&'a ::#libname::collections::#type_name<#key, V>);
+            (ValueTy::Scalar(ty), None) => {
+                let value = ty.rust_type(prost_path);
+                quote! {
+                    struct #wrapper_name<'a, M>(&'a M);
+                    impl<'a, M> ::core::fmt::Debug for #wrapper_name<'a, M>
+                    where
+                        &'a M: ::core::iter::IntoIterator<Item = (&'a #key, &'a #value)>,
+                    {
+                        #fmt
+                    }
+                }
+            }
+            (ValueTy::Message, Some(map_ty)) => quote! {
+                struct #wrapper_name<'a, V: 'a>(&'a #map_ty<#key, V>);
                 impl<'a, V> ::core::fmt::Debug for #wrapper_name<'a, V>
                 where
                     V: ::core::fmt::Debug + 'a,
@@ -348,6 +576,8 @@ impl Field {
                     #fmt
                 }
             },
+            // Message values are formatted as they are, so the map can be formatted as it is.
+            (ValueTy::Message, None) => forward,
         }
     }
 }
@@ -366,7 +596,7 @@ fn key_ty_from_str(s: &str) -> Result<scalar::Ty, Error> {
         | scalar::Ty::Sfixed32
         | scalar::Ty::Sfixed64
         | scalar::Ty::Bool
-        | scalar::Ty::String => Ok(ty),
+        | scalar::Ty::String(..) => Ok(ty),
         _ => bail!("invalid map key type: {s}"),
     }
 }
@@ -1,7 +1,7 @@
 use anyhow::{bail, Error};
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
-use syn::{Meta, Path};
+use syn::{Meta, Path, Type};
 
 use crate::field::{set_bool, set_option, tag_attr, word_attr, Label};
 
@@ -9,6 +9,12 @@ use crate::field::{set_bool, set_option, tag_attr, word_attr, Label};
 pub struct Field {
     pub label: Label,
     pub tag: u32,
+    /// Whether the field is a required field whose presence is tracked, i.e.
+    /// `#[prost(required, presence)]`. Such fields are stored as optional fields.
+    pub required: bool,
+    /// Whether the field is a `Lazy` message, i.e. `#[prost(lazy)]`, which is decoded on first
+    /// access.
+    pub lazy: bool,
 }
 
 impl Field {
@@ -17,6 +23,8 @@ impl Field {
         let mut label = None;
         let mut tag = None;
         let mut boxed = false;
+        let mut presence = false;
+        let mut lazy = false;
 
         let mut unknown_attrs = Vec::new();
 
@@ -25,6 +33,10 @@ impl Field {
                 set_bool(&mut message, "duplicate message attribute")?;
             } else if word_attr("boxed", attr) {
                 set_bool(&mut boxed, "duplicate boxed attribute")?;
+            } else if word_attr("presence", attr) {
+                set_bool(&mut presence, "duplicate presence attributes")?;
+            } else if word_attr("lazy", attr) {
+                set_bool(&mut lazy, "duplicate lazy attributes")?;
             } else if let Some(t) = tag_attr(attr)? {
                 set_option(&mut tag, t, "duplicate tag attributes")?;
             } else if let Some(l) = Label::from_attr(attr) {
@@ -50,15 +62,26 @@ impl Field {
             None => bail!("message field is missing a tag attribute"),
         };
 
+        let label = match (label, presence) {
+            (Some(Label::Required), true) => Label::Optional,
+            (_, true) => bail!("presence attribute may only be applied to required message fields"),
+            (label, false) => label.unwrap_or(Label::Optional),
+        };
+
         Ok(Some(Field {
-            label: label.unwrap_or(Label::Optional),
+            label,
             tag,
+            required: presence,
+            lazy,
         }))
     }
 
     pub fn new_oneof(attrs: &[Meta]) -> Result<Option<Field>, Error> {
         if let Some(mut field) = Field::new(attrs, None)? {
-            if let Some(attr) = attrs.iter().find(|attr| Label::from_attr(attr).is_some()) {
+            if let Some(attr) = attrs
+                .iter()
+                .find(|attr| Label::from_attr(attr).is_some() || word_attr("lazy", attr))
+            {
                 bail!(
                     "invalid attribute for oneof field: {}",
                     attr.path().into_token_stream()
@@ -72,37 +95,55 @@ impl Field {
     }
 
     pub fn encode(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        self.encode_with(quote!(#prost_path::encoding::message::encode), ident)
+    }
+
+    /// Returns a statement which encodes the field with the deterministic encoding.
+    pub fn encode_deterministic(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        self.encode_with(
+            quote!(#prost_path::encoding::message::encode_deterministic),
+            ident,
+        )
+    }
+
+    /// Returns a statement which encodes the field with the encode function `encode`.
+    fn encode_with(&self, encode: TokenStream, ident: TokenStream) -> TokenStream {
         let tag = self.tag;
         match self.label {
             Label::Optional => quote! {
-                if let Some(ref msg) = #ident {
-                    #prost_path::encoding::message::encode(#tag, msg, buf);
+                if let ::core::option::Option::Some(ref msg) = #ident {
+                    #encode(#tag, msg, buf);
                 }
             },
             Label::Required => quote! {
-                #prost_path::encoding::message::encode(#tag, &#ident, buf);
+                #encode(#tag, &#ident, buf);
             },
             Label::Repeated => quote! {
                 for msg in &#ident {
-                    #prost_path::encoding::message::encode(#tag, msg, buf);
+                    #encode(#tag, msg, buf);
                 }
             },
         }
     }
 
     pub fn merge(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let module = if self.lazy {
+            quote!(lazy)
+        } else {
+            quote!(message)
+        };
         match self.label {
             Label::Optional => quote! {
-                #prost_path::encoding::message::merge(wire_type,
+                #prost_path::encoding::#module::merge(wire_type,
                                                  #ident.get_or_insert_with(::core::default::Default::default),
                                                  buf,
                                                  ctx)
             },
             Label::Required => quote! {
-                #prost_path::encoding::message::merge(wire_type, #ident, buf, ctx)
+                #prost_path::encoding::#module::merge(wire_type, #ident, buf, ctx)
             },
             Label::Repeated => quote! {
-                #prost_path::encoding::message::merge_repeated(wire_type, #ident, buf, ctx)
+                #prost_path::encoding::#module::merge_repeated(wire_type, #ident, buf, ctx)
             },
         }
     }
@@ -122,6 +163,120 @@ impl Field {
         }
     }
 
+    /// Returns a statement which encodes the field, reading the lengths of the nested messages
+    /// from `cache`.
+    pub fn encode_cached(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let tag = self.tag;
+        match self.label {
+            Label::Optional => quote! {
+                if let ::core::option::Option::Some(ref msg) = #ident {
+                    #prost_path::encoding::message::encode_cached(#tag, msg, buf, cache);
+                }
+            },
+            Label::Required => quote! {
+                #prost_path::encoding::message::encode_cached(#tag, &#ident, buf, cache);
+            },
+            Label::Repeated => quote! {
+                for msg in &#ident {
+                    #prost_path::encoding::message::encode_cached(#tag, msg, buf, cache);
+                }
+            },
+        }
+    }
+
+    /// Returns an expression which evaluates to the encoded length of the field, and records the
+    /// lengths of the nested messages in `cache`.
+    pub fn encoded_len_cached(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let tag = self.tag;
+        match self.label {
+            Label::Optional => quote! {
+                #ident.as_ref().map_or(0, |msg| #prost_path::encoding::message::encoded_len_cached(#tag, msg, cache))
+            },
+            Label::Required => quote! {
+                #prost_path::encoding::message::encoded_len_cached(#tag, &#ident, cache)
+            },
+            Label::Repeated => quote! {
+                #prost_path::encoding::message::encoded_len_repeated_cached(#tag, &#ident, cache)
+            },
+        }
+    }
+
+    /// Returns a statement which adds the JSON representation of the field to `object`.
+    pub fn json_encode(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let json = quote!(#prost_path::json::encoding);
+        match self.label {
+            Label::Optional => quote! {
+                if let ::core::option::Option::Some(ref msg) = #ident {
+                    object.push((name.into(), #json::JsonField::to_json(msg, options)?));
+                }
+            },
+            Label::Required => quote! {
+                object.push((name.into(), #json::JsonField::to_json(&#ident, options)?));
+            },
+            Label::Repeated => quote! {
+                if #json::emit_default_values(options) || !#ident.is_empty() {
+                    object.push((name.into(), #json::encode_repeated(&#ident, options)?));
+                }
+            },
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of merging the JSON `value` into the
+    /// field.
+    pub fn json_merge(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let merge_fn = match self.label {
+            Label::Optional => quote!(merge_optional),
+            Label::Required => quote!(merge),
+            Label::Repeated => quote!(merge_repeated),
+        };
+        quote!(#prost_path::json::encoding::#merge_fn(&mut #ident, value, options))
+    }
+
+    /// Returns a statement which appends the text format representation of the field to
+    /// `message`.
+    pub fn text_encode(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let text = quote!(#prost_path::text_format::encoding);
+        match self.label {
+            Label::Optional => quote! {
+                if let ::core::option::Option::Some(ref msg) = #ident {
+                    message.push((name.into(), #text::TextField::to_text_value(msg)));
+                }
+            },
+            Label::Required => quote! {
+                message.push((name.into(), #text::TextField::to_text_value(&#ident)));
+            },
+            Label::Repeated => quote! {
+                for msg in &#ident {
+                    message.push((name.into(), #text::TextField::to_text_value(msg)));
+                }
+            },
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of merging the text format `value`
+    /// into the field.
+    pub fn text_merge(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        let merge_fn = match self.label {
+            Label::Optional => quote!(merge_optional),
+            Label::Required => quote!(merge),
+            Label::Repeated => quote!(merge_repeated),
+        };
+        quote!(#prost_path::text_format::encoding::#merge_fn(&mut #ident, value))
+    }
+
+    /// Returns a constant expression which evaluates to the default value of the field, given
+    /// the Rust type of the field.
+    pub fn const_default(&self, ty: &Type) -> Result<TokenStream, Error> {
+        if self.lazy {
+            bail!("lazy message fields have no constant default");
+        }
+        Ok(match self.label {
+            Label::Optional => quote!(::core::option::Option::None),
+            Label::Required => quote!(<#ty>::DEFAULT),
+            Label::Repeated => quote!(<#ty>::new()),
+        })
+    }
+
     pub fn clear(&self, ident: TokenStream) -> TokenStream {
         match self.label {
             Label::Optional => quote!(#ident = ::core::option::Option::None),
@@ -129,4 +284,91 @@ impl Field {
             Label::Repeated => quote!(#ident.clear()),
         }
     }
+
+    /// Returns a statement which clears the sensitive fields of the nested messages.
+    pub fn redact(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        match self.label {
+            Label::Optional => quote! {
+                if let ::core::option::Option::Some(ref mut msg) = #ident {
+                    #prost_path::Message::redact(msg);
+                }
+            },
+            Label::Required => quote!(#prost_path::Message::redact(&mut #ident);),
+            Label::Repeated => quote! {
+                for msg in &mut #ident {
+                    #prost_path::Message::redact(msg);
+                }
+            },
+        }
+    }
+
+    /// Returns a statement which merges the field `other` into the field.
+    pub fn merge_from(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        other: TokenStream,
+    ) -> TokenStream {
+        match self.label {
+            Label::Optional => quote! {
+                if let ::core::option::Option::Some(ref other) = #other {
+                    #prost_path::Message::merge_from(
+                        #ident.get_or_insert_with(::core::default::Default::default),
+                        other,
+                    );
+                }
+            },
+            Label::Required => quote! {
+                #prost_path::Message::merge_from(&mut #ident, &#other);
+            },
+            Label::Repeated => quote! {
+                for other in &#other {
+                    let mut value = ::core::default::Default::default();
+                    #prost_path::Message::merge_from(&mut value, other);
+                    ::core::iter::Extend::extend(&mut #ident, ::core::iter::once(value));
+                }
+            },
+        }
+    }
+
+    /// Returns a statement which appends the paths of the missing required fields of the field,
+    /// and of the messages it holds, to `missing`.
+    pub fn missing_required_fields(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        name: &str,
+    ) -> TokenStream {
+        let message = quote!(#prost_path::encoding::message);
+        match self.label {
+            Label::Optional if self.required => quote! {
+                match #ident {
+                    ::core::option::Option::Some(ref msg) => {
+                        #message::collect_missing_required_fields(msg, prefix, #name, missing);
+                    }
+                    ::core::option::Option::None => {
+                        #message::missing_required_field(prefix, #name, missing);
+                    }
+                }
+            },
+            Label::Optional => quote! {
+                if let ::core::option::Option::Some(ref msg) = #ident {
+                    #message::collect_missing_required_fields(msg, prefix, #name, missing);
+                }
+            },
+            Label::Required => quote! {
+                #message::collect_missing_required_fields(&#ident, prefix, #name, missing);
+            },
+            Label::Repeated => quote! {
+                for (index, msg) in #ident.iter().enumerate() {
+                    #message::collect_missing_required_fields(
+                        msg,
+                        prefix,
+                        ::core::format_args!("{}[{}]", #name, index),
+                        missing,
+                    );
+                }
+            },
+        }
+    }
 }
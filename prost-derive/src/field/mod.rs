@@ -3,16 +3,20 @@ mod map;
 mod message;
 mod oneof;
 mod scalar;
+mod with;
 
 use std::fmt;
 use std::slice;
 
-use anyhow::{bail, Error};
-use proc_macro2::TokenStream;
+use anyhow::{bail, Context, Error};
+use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::punctuated::Punctuated;
 use syn::Path;
-use syn::{Attribute, Expr, ExprLit, Lit, LitBool, LitInt, Meta, MetaNameValue, Token};
+use syn::{
+    Attribute, Expr, ExprLit, GenericArgument, Ident, Lit, LitBool, LitInt, Meta, MetaNameValue,
+    PathArguments, Token, Type,
+};
 
 #[derive(Clone)]
 pub enum Field {
@@ -26,6 +30,8 @@ pub enum Field {
     Oneof(oneof::Field),
     /// A group field.
     Group(group::Field),
+    /// A field encoded by the functions of a module.
+    With(with::Field),
 }
 
 impl Field {
@@ -34,7 +40,7 @@ impl Field {
     /// If the meta items are invalid, an error will be returned.
     /// If the field should be ignored, `None` is returned.
     pub fn new(attrs: Vec<Attribute>, inferred_tag: Option<u32>) -> Result<Option<Field>, Error> {
-        let attrs = prost_attrs(attrs)?;
+        let attrs = field_attrs(attrs)?;
 
         // TODO: check for ignore attribute.
 
@@ -48,6 +54,8 @@ impl Field {
             Field::Oneof(field)
         } else if let Some(field) = group::Field::new(&attrs, inferred_tag)? {
             Field::Group(field)
+        } else if let Some(field) = with::Field::new(&attrs, inferred_tag)? {
+            Field::With(field)
         } else {
             bail!("no type attribute");
         };
@@ -60,7 +68,7 @@ impl Field {
     /// If the meta items are invalid, an error will be returned.
     /// If the field should be ignored, `None` is returned.
     pub fn new_oneof(attrs: Vec<Attribute>) -> Result<Option<Field>, Error> {
-        let attrs = prost_attrs(attrs)?;
+        let attrs = field_attrs(attrs)?;
 
         // TODO: check for ignore attribute.
 
@@ -86,6 +94,7 @@ impl Field {
             Field::Map(ref map) => vec![map.tag],
             Field::Oneof(ref oneof) => oneof.tags.clone(),
             Field::Group(ref group) => vec![group.tag],
+            Field::With(ref with) => vec![with.tag],
         }
     }
 
@@ -97,6 +106,20 @@ impl Field {
             Field::Map(ref map) => map.encode(prost_path, ident),
             Field::Oneof(ref oneof) => oneof.encode(ident),
             Field::Group(ref group) => group.encode(prost_path, ident),
+            Field::With(ref with) => with.encode(ident),
+        }
+    }
+
+    /// Returns a statement which encodes the field with the deterministic encoding, which
+    /// encodes map entries in the order of their keys.
+    pub fn encode_deterministic(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.encode(prost_path, ident),
+            Field::Message(ref message) => message.encode_deterministic(prost_path, ident),
+            Field::Map(ref map) => map.encode_deterministic(prost_path, ident),
+            Field::Oneof(ref oneof) => oneof.encode_deterministic(ident),
+            Field::Group(ref group) => group.encode_deterministic(prost_path, ident),
+            Field::With(ref with) => with.encode(ident),
         }
     }
 
@@ -109,6 +132,7 @@ impl Field {
             Field::Map(ref map) => map.merge(prost_path, ident),
             Field::Oneof(ref oneof) => oneof.merge(ident),
             Field::Group(ref group) => group.merge(prost_path, ident),
+            Field::With(ref with) => with.merge(ident),
         }
     }
 
@@ -120,6 +144,114 @@ impl Field {
             Field::Message(ref msg) => msg.encoded_len(prost_path, ident),
             Field::Oneof(ref oneof) => oneof.encoded_len(ident),
             Field::Group(ref group) => group.encoded_len(prost_path, ident),
+            Field::With(ref with) => with.encoded_len(ident),
+        }
+    }
+
+    /// Returns a statement which encodes the field, reading the lengths of nested messages from
+    /// `cache`.
+    ///
+    /// Only message fields use the cache. Other fields compute the lengths of their nested
+    /// messages, if any, as they are encoded.
+    pub fn encode_cached(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        match *self {
+            Field::Message(ref message) => message.encode_cached(prost_path, ident),
+            _ => self.encode(prost_path, ident),
+        }
+    }
+
+    /// Returns an expression which evaluates to the encoded length of the field, and records the
+    /// lengths of nested messages in `cache`.
+    pub fn encoded_len_cached(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        match *self {
+            Field::Message(ref message) => message.encoded_len_cached(prost_path, ident),
+            _ => self.encoded_len(prost_path, ident),
+        }
+    }
+
+    /// Returns a statement which merges the field `other` into the field, following the Protobuf
+    /// merge semantics.
+    pub fn merge_from(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        other: TokenStream,
+    ) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.merge_from(prost_path, ident, other),
+            Field::Message(ref message) => message.merge_from(prost_path, ident, other),
+            Field::Map(ref map) => map.merge_from(prost_path, ident, other),
+            Field::Oneof(ref oneof) => oneof.merge_from(ident, other),
+            Field::Group(ref group) => group.merge_from(prost_path, ident, other),
+            Field::With(ref with) => with.merge_from(prost_path, ident, other),
+        }
+    }
+
+    /// Returns a statement which appends the paths of the missing required fields of the field,
+    /// and of the messages it holds, to `missing`. `name` is the Protobuf name of the field.
+    pub fn missing_required_fields(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        name: &str,
+    ) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.missing_required_fields(prost_path, ident, name),
+            Field::Message(ref message) => message.missing_required_fields(prost_path, ident, name),
+            Field::Map(ref map) => map.missing_required_fields(prost_path, ident, name),
+            Field::Oneof(ref oneof) => oneof.missing_required_fields(ident),
+            Field::Group(ref group) => group.missing_required_fields(prost_path, ident, name),
+            Field::With(_) => TokenStream::new(),
+        }
+    }
+
+    /// Records the name of the message type of a group field, given the Rust type of the field.
+    pub fn set_rust_type(&mut self, ty: &Type) {
+        if let Field::Group(ref mut group) = *self {
+            group.type_name = message_type_name(ty);
+        }
+    }
+
+    /// Returns the text format name of the field. Group fields are named by their message type,
+    /// while other fields use their Protobuf name, `proto_name`.
+    pub fn text_name(&self, proto_name: &str) -> String {
+        match *self {
+            Field::Group(ref group) => group.type_name.as_deref().unwrap_or(proto_name).to_owned(),
+            _ => proto_name.to_owned(),
+        }
+    }
+
+    /// Returns the index of the presence bit of the field, if it is an optional scalar field whose
+    /// presence is tracked in the presence bits of the message.
+    pub fn presence_bit(&self) -> Option<u32> {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.presence_bit,
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the field is a string scalar field, which holds the bytes of invalid
+    /// values as unknown fields under `Utf8Policy::Bytes`.
+    pub fn is_string(&self) -> bool {
+        matches!(*self, Field::Scalar(ref scalar) if matches!(scalar.ty, scalar::Ty::String(..)))
+    }
+
+    /// Returns `true` if the field is a repeated scalar field.
+    pub fn is_repeated_scalar(&self) -> bool {
+        matches!(
+            *self,
+            Field::Scalar(ref scalar)
+                if matches!(scalar.kind, scalar::Kind::Repeated | scalar::Kind::Packed)
+        )
+    }
+
+    /// Returns `true` if the field is a required field whose presence is tracked.
+    pub fn is_required_with_presence(&self) -> bool {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.required,
+            Field::Message(ref message) => message.required,
+            Field::Group(ref group) => group.required,
+            Field::Map(..) | Field::Oneof(..) | Field::With(..) => false,
         }
     }
 
@@ -131,16 +263,141 @@ impl Field {
             Field::Map(ref map) => map.clear(ident),
             Field::Oneof(ref oneof) => oneof.clear(ident),
             Field::Group(ref group) => group.clear(ident),
+            Field::With(ref with) => with.clear(ident),
+        }
+    }
+
+    /// Returns a statement which clears the sensitive fields of the messages held by the field.
+    pub fn redact(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        match *self {
+            Field::Scalar(_) | Field::With(_) => TokenStream::new(),
+            Field::Message(ref message) => message.redact(prost_path, ident),
+            Field::Map(ref map) => map.redact(prost_path, ident),
+            Field::Oneof(ref oneof) => oneof.redact(ident),
+            Field::Group(ref group) => group.redact(prost_path, ident),
         }
     }
 
-    pub fn default(&self, prost_path: &Path) -> TokenStream {
+    /// Returns a statement which adds the JSON representation of the field to `object`, using
+    /// the member name bound to `name`.
+    pub fn json_encode(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
         match *self {
-            Field::Scalar(ref scalar) => scalar.default(prost_path),
+            Field::Scalar(ref scalar) => scalar.json_encode(prost_path, ident),
+            Field::Message(ref message) => message.json_encode(prost_path, ident),
+            Field::Map(ref map) => map.json_encode(prost_path, ident),
+            Field::Oneof(ref oneof) => oneof.json_encode(ident),
+            Field::Group(ref group) => group.json_encode(prost_path, ident),
+            Field::With(ref with) => with.unsupported("the JSON mapping"),
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of merging the JSON `value` into the
+    /// field. For oneof fields, the result is `false` if the member `key` does not belong to the
+    /// oneof.
+    pub fn json_merge(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.json_merge(prost_path, ident),
+            Field::Message(ref message) => message.json_merge(prost_path, ident),
+            Field::Map(ref map) => map.json_merge(prost_path, ident),
+            Field::Oneof(ref oneof) => oneof.json_merge(ident),
+            Field::Group(ref group) => group.json_merge(prost_path, ident),
+            Field::With(ref with) => with.unsupported("the JSON mapping"),
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of converting the JSON `value` of a
+    /// oneof variant, or `None` if the variant is unset.
+    pub fn json_merge_oneof(&self, prost_path: &Path) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.json_merge_oneof(prost_path),
+            _ => quote!(#prost_path::json::encoding::merge_oneof(value, options)),
+        }
+    }
+
+    /// Returns a statement which sets the field to an arbitrary value generated from `u`. Nested
+    /// messages are generated up to the recursion limit, so that they can be decoded.
+    pub fn arbitrary(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.arbitrary(prost_path, ident),
+            Field::Map(ref map) => map.arbitrary(prost_path, ident),
+            Field::With(ref with) => with.unsupported("`Arbitrary`"),
+            Field::Message(..) | Field::Oneof(..) | Field::Group(..) => quote! {
+                let value = #prost_path::encoding::arbitrary::nested(
+                    u,
+                    #prost_path::arbitrary::Arbitrary::arbitrary,
+                )?;
+                if let ::core::option::Option::Some(value) = value {
+                    #ident = value;
+                }
+            },
+        }
+    }
+
+    /// Returns an expression which evaluates to an arbitrary value of a oneof variant generated
+    /// from `u`.
+    pub fn arbitrary_oneof(&self, prost_path: &Path) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.arbitrary_oneof(prost_path),
+            _ => quote!(#prost_path::arbitrary::Arbitrary::arbitrary(u)?),
+        }
+    }
+
+    /// Returns a statement which appends the text format representation of the field to
+    /// `message`, using the field name bound to `name`.
+    pub fn text_encode(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.text_encode(prost_path, ident),
+            Field::Message(ref message) => message.text_encode(prost_path, ident),
+            Field::Map(ref map) => map.text_encode(prost_path, ident),
+            Field::Oneof(ref oneof) => oneof.text_encode(ident),
+            Field::Group(ref group) => group.text_encode(prost_path, ident),
+            Field::With(ref with) => with.unsupported("the text format"),
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of merging the text format `value`
+    /// into the field. For oneof fields, the result is `false` if the field `name` does not
+    /// belong to the oneof.
+    pub fn text_merge(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.text_merge(prost_path, ident),
+            Field::Message(ref message) => message.text_merge(prost_path, ident),
+            Field::Map(ref map) => map.text_merge(prost_path, ident),
+            Field::Oneof(ref oneof) => oneof.text_merge(ident),
+            Field::Group(ref group) => group.text_merge(prost_path, ident),
+            Field::With(ref with) => with.unsupported("the text format"),
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of converting the text format `value`
+    /// of a oneof variant.
+    pub fn text_merge_oneof(&self, prost_path: &Path) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.text_merge_oneof(prost_path),
+            _ => quote!(#prost_path::text_format::encoding::TextField::from_text_value(value)),
+        }
+    }
+
+    pub fn default(&self) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.default(),
             _ => quote!(::core::default::Default::default()),
         }
     }
 
+    /// Returns a constant expression which evaluates to the default value of the field, given
+    /// the Rust type of the field, for messages annotated with `#[prost(const_default)]`.
+    pub fn const_default(&self, ty: &Type) -> Result<TokenStream, Error> {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.const_default(ty),
+            Field::Message(ref message) => message.const_default(ty),
+            Field::Map(ref map) => map.const_default(ty),
+            Field::Oneof(_) => Ok(quote!(::core::option::Option::None)),
+            Field::Group(ref group) => Ok(group.const_default(ty)),
+            Field::With(_) => bail!("with fields have no constant default"),
+        }
+    }
+
     /// Produces the fragment implementing debug for the given field.
     pub fn debug(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
         match *self {
@@ -166,13 +423,379 @@ impl Field {
         }
     }
 
-    pub fn methods(&self, prost_path: &Path, ident: &TokenStream) -> Option<TokenStream> {
-        match *self {
+    pub fn methods(
+        &self,
+        prost_path: &Path,
+        ident: &TokenStream,
+        presence_bits: Option<&PresenceBits>,
+    ) -> Option<TokenStream> {
+        if let (Some(bit), Some(bits), Field::Scalar(scalar)) =
+            (self.presence_bit(), presence_bits, self)
+        {
+            return Some(scalar.presence_methods(
+                prost_path,
+                ident,
+                bits.is_set(quote!(self), bit),
+                bits.set(quote!(self), bit),
+                bits.unset(quote!(self), bit),
+            ));
+        }
+
+        let methods = match *self {
             Field::Scalar(ref scalar) => scalar.methods(ident),
             Field::Map(ref map) => map.methods(prost_path, ident),
             _ => None,
+        };
+        if !self.is_required_with_presence() {
+            return methods;
         }
+
+        let ident_str = ident.to_string();
+        let ident_str = ident_str.trim_start_matches("r#");
+        let has = Ident::new(&format!("has_{ident_str}"), Span::call_site());
+        let has_doc = format!("Returns `true` if the required field `{ident_str}` is set.");
+        let clear = Ident::new(&format!("clear_{ident_str}"), Span::call_site());
+        let clear_doc = format!("Clears the required field `{ident_str}`, leaving it unset.");
+        Some(quote! {
+            #methods
+
+            #[doc=#has_doc]
+            pub fn #has(&self) -> bool {
+                self.#ident.is_some()
+            }
+
+            #[doc=#clear_doc]
+            pub fn #clear(&mut self) {
+                self.#ident = ::core::option::Option::None;
+            }
+        })
     }
+
+    /// Returns the accessors of the field in a message with `#[prost(accessors)]`, given the Rust
+    /// type of the field and the statement which sets its presence bit, if it has one: a getter
+    /// returning a reference, unless `methods` already returns a getter, a `_mut` method, unless
+    /// the presence of the field must be set along with its value, and a `with_` method.
+    pub fn accessors(
+        &self,
+        ident: &TokenStream,
+        ty: &Type,
+        deprecated: bool,
+        set_bit: Option<TokenStream>,
+    ) -> TokenStream {
+        let ident_str = ident.to_string();
+        let ident_str = ident_str.trim_start_matches("r#");
+        let deprecated = if deprecated {
+            quote!(#[deprecated])
+        } else {
+            quote!()
+        };
+
+        let has_getter = match *self {
+            Field::Scalar(ref scalar) => {
+                let enumeration = matches!(scalar.ty, scalar::Ty::Enumeration(_));
+                if scalar.presence_bit.is_some() {
+                    enumeration
+                } else {
+                    !scalar.convert
+                        && (enumeration || matches!(scalar.kind, scalar::Kind::Optional(_)))
+                }
+            }
+            _ => false,
+        };
+        let get = if has_getter {
+            quote!()
+        } else {
+            let get_doc = format!("Returns a reference to `{ident_str}`.");
+            quote! {
+                #[doc=#get_doc]
+                #deprecated
+                pub fn #ident(&self) -> &#ty {
+                    &self.#ident
+                }
+            }
+        };
+
+        let get_mut = if set_bit.is_some() {
+            quote!()
+        } else {
+            let get_mut = Ident::new(&format!("{ident_str}_mut"), Span::call_site());
+            let get_mut_doc = format!("Returns a mutable reference to `{ident_str}`.");
+            quote! {
+                #[doc=#get_mut_doc]
+                #deprecated
+                pub fn #get_mut(&mut self) -> &mut #ty {
+                    &mut self.#ident
+                }
+            }
+        };
+
+        let with = Ident::new(&format!("with_{ident_str}"), Span::call_site());
+        let with_doc =
+            format!("Sets `{ident_str}` to the provided value, and returns the message.");
+        quote! {
+            #get
+            #get_mut
+
+            #[doc=#with_doc]
+            #deprecated
+            pub fn #with(mut self, value: #ty) -> Self {
+                self.#ident = value;
+                #set_bit
+                self
+            }
+        }
+    }
+}
+
+/// Returns `true` if the field attributes mark the field as the unknown field set of the
+/// message, i.e. `#[prost(unknown_fields)]`.
+pub fn is_unknown_fields(attrs: &[Attribute]) -> Result<bool, Error> {
+    let attrs = prost_attrs(attrs.to_vec())?;
+    if !attrs.iter().any(|attr| word_attr("unknown_fields", attr)) {
+        return Ok(false);
+    }
+    if attrs.len() > 1 {
+        bail!("unknown_fields attribute may not be combined with other attributes");
+    }
+    Ok(true)
+}
+
+/// Returns `true` if the field attributes mark the field as the presence bits of the message,
+/// i.e. `#[prost(presence_bits)]`.
+pub fn is_presence_bits(attrs: &[Attribute]) -> Result<bool, Error> {
+    let attrs = prost_attrs(attrs.to_vec())?;
+    if !attrs.iter().any(|attr| word_attr("presence_bits", attr)) {
+        return Ok(false);
+    }
+    if attrs.len() > 1 {
+        bail!("presence_bits attribute may not be combined with other attributes");
+    }
+    Ok(true)
+}
+
+/// The presence bits of a message, i.e. the `#[prost(presence_bits)]` field: an array of `u32`
+/// words holding the bits which track the presence of the fields with a `presence_bit` attribute.
+#[derive(Debug)]
+pub struct PresenceBits {
+    /// The field holding the bits.
+    pub ident: TokenStream,
+    /// The number of words of the array.
+    pub len: Expr,
+}
+
+impl PresenceBits {
+    /// Returns the presence bits of the field `ident` of type `ty`, which must be an array.
+    pub fn new(ident: TokenStream, ty: &Type) -> Result<PresenceBits, Error> {
+        match *ty {
+            Type::Array(ref array) => Ok(PresenceBits {
+                ident,
+                len: array.len.clone(),
+            }),
+            _ => bail!("presence_bits field must be an array of u32"),
+        }
+    }
+
+    /// Returns an expression which is `true` if the bit is set in the presence bits of `message`.
+    pub fn is_set(&self, message: TokenStream, bit: u32) -> TokenStream {
+        let (word, mask) = Self::word_and_mask(bit);
+        let ident = &self.ident;
+        quote!((#message.#ident[#word] & #mask != 0))
+    }
+
+    /// Returns a statement which sets the bit in the presence bits of `message`.
+    pub fn set(&self, message: TokenStream, bit: u32) -> TokenStream {
+        let (word, mask) = Self::word_and_mask(bit);
+        let ident = &self.ident;
+        quote!(#message.#ident[#word] |= #mask;)
+    }
+
+    /// Returns a statement which unsets the bit in the presence bits of `message`.
+    pub fn unset(&self, message: TokenStream, bit: u32) -> TokenStream {
+        let (word, mask) = Self::word_and_mask(bit);
+        let ident = &self.ident;
+        quote!(#message.#ident[#word] &= !#mask;)
+    }
+
+    /// Returns an expression which evaluates to presence bits without any set bit.
+    pub fn default(&self) -> TokenStream {
+        let len = &self.len;
+        quote!([0; #len])
+    }
+
+    fn word_and_mask(bit: u32) -> (usize, u32) {
+        ((bit / 32) as usize, 1 << (bit % 32))
+    }
+}
+
+/// Returns `true` if the field attributes mark the field as sensitive, i.e. `#[prost(redact)]`.
+pub fn is_redacted(attrs: &[Attribute]) -> Result<bool, Error> {
+    let attrs = prost_attrs(attrs.to_vec())?;
+    Ok(attrs.iter().any(|attr| word_attr("redact", attr)))
+}
+
+/// Returns the JSON name declared for the field, i.e. `#[prost(json_name = "...")]`, if any.
+///
+/// Fields without a declared JSON name use the name returned by [`json_name`].
+pub fn declared_json_name(attrs: &[Attribute]) -> Result<Option<String>, Error> {
+    let attrs = prost_attrs(attrs.to_vec())?;
+    let mut json_name = None;
+    for attr in &attrs {
+        if let Some(name) = str_attr("json_name", attr)? {
+            set_option(&mut json_name, name, "duplicate json_name attributes")?;
+        }
+    }
+    Ok(json_name)
+}
+
+/// Returns the fragment implementing debug for a sensitive field, which hides its value.
+pub fn redacted_debug() -> TokenStream {
+    quote! {
+        {
+            struct Redacted;
+            impl ::core::fmt::Debug for Redacted {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    f.write_str("<redacted>")
+                }
+            }
+            Redacted
+        }
+    }
+}
+
+/// The extension set of a message, i.e. a field annotated with
+/// `#[prost(extension_set = "package.Message", extension_ranges = "100..200, 1000..2000")]`.
+#[derive(Debug)]
+pub struct ExtensionSet {
+    /// The fully qualified Protobuf name of the message type.
+    pub extendee: String,
+    /// The field number ranges reserved for extensions, as `(start, end)` with an exclusive end.
+    pub ranges: Vec<(u32, u32)>,
+}
+
+/// Returns the extension set described by the field attributes, if the field is the extension
+/// set of the message.
+pub fn extension_set(attrs: &[Attribute]) -> Result<Option<ExtensionSet>, Error> {
+    let attrs = prost_attrs(attrs.to_vec())?;
+    let mut extendee = None;
+    let mut ranges = None;
+    let mut unknown_attrs = Vec::new();
+    for attr in &attrs {
+        if let Some(value) = str_attr("extension_set", attr)? {
+            set_option(&mut extendee, value, "duplicate extension_set attributes")?;
+        } else if let Some(value) = str_attr("extension_ranges", attr)? {
+            set_option(
+                &mut ranges,
+                parse_ranges(&value)?,
+                "duplicate extension_ranges attributes",
+            )?;
+        } else {
+            unknown_attrs.push(attr);
+        }
+    }
+
+    let extendee = match extendee {
+        Some(extendee) => extendee,
+        None if ranges.is_some() => bail!("extension_ranges attribute requires extension_set"),
+        None => return Ok(None),
+    };
+    if !unknown_attrs.is_empty() {
+        bail!("extension_set attribute may not be combined with other attributes");
+    }
+    let ranges = match ranges {
+        Some(ranges) => ranges,
+        None => bail!("missing extension_ranges attribute"),
+    };
+    Ok(Some(ExtensionSet { extendee, ranges }))
+}
+
+/// Parses comma separated ranges of field numbers, e.g. `100..200, 1000..2000`.
+fn parse_ranges(value: &str) -> Result<Vec<(u32, u32)>, Error> {
+    value
+        .split(',')
+        .map(|range| {
+            let (start, end) = range
+                .split_once("..")
+                .with_context(|| format!("invalid extension range: {range}"))?;
+            let start = start.trim().parse::<u32>()?;
+            let end = end.trim().parse::<u32>()?;
+            if start >= end {
+                bail!("invalid extension range: {range}");
+            }
+            Ok((start, end))
+        })
+        .collect()
+}
+
+/// Unpacks a `key = "value"` attribute, returning the value.
+/// If the key doesn't match the attribute, `None` is returned.
+fn str_attr(key: &str, attr: &Meta) -> Result<Option<String>, Error> {
+    if !attr.path().is_ident(key) {
+        return Ok(None);
+    }
+    match *attr {
+        Meta::NameValue(MetaNameValue {
+            value:
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(ref lit),
+                    ..
+                }),
+            ..
+        }) => Ok(Some(lit.value())),
+        _ => bail!("invalid {key} attribute"),
+    }
+}
+
+/// Returns the JSON name of a field, which is its name in lowerCamelCase.
+///
+/// This follows `protoc`, which removes underscores and capitalizes the letter following each.
+pub fn json_name(name: &str) -> String {
+    let mut json_name = String::with_capacity(name.len());
+    let mut capitalize_next = false;
+    for c in name.chars() {
+        if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            json_name.push(c.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            json_name.push(c);
+        }
+    }
+    json_name
+}
+
+/// Returns the name of the message type held by a field of type `ty`, looking through the
+/// `Option`, `Vec` and `Box` wrappers.
+fn message_type_name(ty: &Type) -> Option<String> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    match (segment.ident.to_string().as_str(), &segment.arguments) {
+        ("Option" | "Vec" | "Box", PathArguments::AngleBracketed(arguments)) => {
+            arguments.args.iter().find_map(|argument| match argument {
+                GenericArgument::Type(ty) => message_type_name(ty),
+                _ => None,
+            })
+        }
+        (name, _) => Some(name.trim_start_matches("r#").to_owned()),
+    }
+}
+
+/// Returns the field name corresponding to a oneof variant name, which is in UpperCamelCase.
+pub fn oneof_field_name(variant: &str) -> String {
+    let mut name = String::with_capacity(variant.len() + 4);
+    for (i, c) in variant.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        } else {
+            name.push(c);
+        }
+    }
+    name
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -242,6 +865,14 @@ fn prost_attrs(attrs: Vec<Attribute>) -> Result<Vec<Meta>, Error> {
     Ok(result)
 }
 
+/// Get the items of the 'prost' list attribute describing the field, without the `redact`
+/// attribute, which applies to every type of field and is handled by the derives.
+fn field_attrs(attrs: Vec<Attribute>) -> Result<Vec<Meta>, Error> {
+    let mut attrs = prost_attrs(attrs)?;
+    attrs.retain(|attr| !word_attr("redact", attr) && !attr.path().is_ident("json_name"));
+    Ok(attrs)
+}
+
 pub fn set_option<T>(option: &mut Option<T>, value: T, message: &str) -> Result<(), Error>
 where
     T: fmt::Debug,
@@ -62,12 +62,21 @@ impl Field {
     /// Returns a statement which encodes the oneof field.
     pub fn encode(&self, ident: TokenStream) -> TokenStream {
         quote! {
-            if let Some(ref oneof) = #ident {
+            if let ::core::option::Option::Some(ref oneof) = #ident {
                 oneof.encode(buf)
             }
         }
     }
 
+    /// Returns a statement which encodes the oneof field with the deterministic encoding.
+    pub fn encode_deterministic(&self, ident: TokenStream) -> TokenStream {
+        quote! {
+            if let ::core::option::Option::Some(ref oneof) = #ident {
+                oneof.encode_deterministic(buf)
+            }
+        }
+    }
+
     /// Returns an expression which evaluates to the result of decoding the oneof field.
     pub fn merge(&self, ident: TokenStream) -> TokenStream {
         let ty = &self.ty;
@@ -84,6 +93,70 @@ impl Field {
         }
     }
 
+    /// Returns a statement which merges the oneof field `other` into the field.
+    pub fn merge_from(&self, ident: TokenStream, other: TokenStream) -> TokenStream {
+        let ty = &self.ty;
+        quote! {
+            #ty::merge_from(&mut #ident, &#other);
+        }
+    }
+
+    /// Returns a statement which clears the sensitive fields of the oneof field.
+    pub fn redact(&self, ident: TokenStream) -> TokenStream {
+        quote! {
+            if let ::core::option::Option::Some(ref mut oneof) = #ident {
+                oneof.redact();
+            }
+        }
+    }
+
+    /// Returns a statement which appends the paths of the missing required fields of the message
+    /// held by the oneof field to `missing`.
+    pub fn missing_required_fields(&self, ident: TokenStream) -> TokenStream {
+        quote! {
+            if let ::core::option::Option::Some(ref oneof) = #ident {
+                oneof.collect_missing_required_fields(prefix, missing);
+            }
+        }
+    }
+
+    /// Returns a statement which adds the JSON representation of the oneof field to `object`.
+    pub fn json_encode(&self, ident: TokenStream) -> TokenStream {
+        quote! {
+            if let ::core::option::Option::Some(ref oneof) = #ident {
+                oneof.encode_json(&mut object, options)?;
+            }
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of merging the JSON member `key` into
+    /// the oneof field, which is `false` if the member does not belong to the oneof.
+    pub fn json_merge(&self, ident: TokenStream) -> TokenStream {
+        let ty = &self.ty;
+        quote! {
+            #ty::merge_json(&mut #ident, key, value, options)
+        }
+    }
+
+    /// Returns a statement which appends the text format representation of the oneof field to
+    /// `message`.
+    pub fn text_encode(&self, ident: TokenStream) -> TokenStream {
+        quote! {
+            if let ::core::option::Option::Some(ref oneof) = #ident {
+                oneof.encode_text(message);
+            }
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of merging the text format field
+    /// `name` into the oneof field, which is `false` if the field does not belong to the oneof.
+    pub fn text_merge(&self, ident: TokenStream) -> TokenStream {
+        let ty = &self.ty;
+        quote! {
+            #ty::merge_text(&mut #ident, name, value)
+        }
+    }
+
     pub fn clear(&self, ident: TokenStream) -> TokenStream {
         quote!(#ident = ::core::option::Option::None)
     }
@@ -3,9 +3,11 @@ use std::fmt;
 use anyhow::{anyhow, bail, Error};
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens, TokenStreamExt};
-use syn::{parse_str, Expr, ExprLit, Ident, Index, Lit, LitByteStr, Meta, MetaNameValue, Path};
+use syn::{
+    parse_str, Expr, ExprLit, Ident, Index, Lit, LitByteStr, Meta, MetaNameValue, Path, Type,
+};
 
-use crate::field::{bool_attr, set_option, tag_attr, Label};
+use crate::field::{bool_attr, set_bool, set_option, tag_attr, word_attr, Label};
 
 /// A scalar protobuf field.
 #[derive(Clone)]
@@ -13,6 +15,16 @@ pub struct Field {
     pub ty: Ty,
     pub kind: Kind,
     pub tag: u32,
+    /// Whether the field is a required field whose presence is tracked, i.e.
+    /// `#[prost(required, presence)]`. Such fields are stored as optional fields.
+    pub required: bool,
+    /// Whether the field holds a type which converts to and from the Rust type of the scalar,
+    /// i.e. `#[prost(convert)]`.
+    pub convert: bool,
+    /// The index of the bit which tracks the presence of an optional field in the presence bits
+    /// of the message, i.e. `#[prost(optional, presence_bit = "..")]`. Such fields are stored as
+    /// their value.
+    pub presence_bit: Option<u32>,
 }
 
 impl Field {
@@ -22,12 +34,20 @@ impl Field {
         let mut packed = None;
         let mut default = None;
         let mut tag = None;
+        let mut presence = false;
+        let mut convert = false;
+        let mut max_len = None;
+        let mut presence_bit = None;
 
         let mut unknown_attrs = Vec::new();
 
         for attr in attrs {
             if let Some(t) = Ty::from_attr(attr)? {
                 set_option(&mut ty, t, "duplicate type attributes")?;
+            } else if word_attr("presence", attr) {
+                set_bool(&mut presence, "duplicate presence attributes")?;
+            } else if word_attr("convert", attr) {
+                set_bool(&mut convert, "duplicate convert attributes")?;
             } else if let Some(p) = bool_attr("packed", attr)? {
                 set_option(&mut packed, p, "duplicate packed attributes")?;
             } else if let Some(t) = tag_attr(attr)? {
@@ -36,6 +56,10 @@ impl Field {
                 set_option(&mut label, l, "duplicate label attributes")?;
             } else if let Some(d) = DefaultValue::from_attr(attr)? {
                 set_option(&mut default, d, "duplicate default attributes")?;
+            } else if let Some(m) = max_len_attr(attr)? {
+                set_option(&mut max_len, m, "duplicate max_len attributes")?;
+            } else if let Some(b) = presence_bit_attr(attr)? {
+                set_option(&mut presence_bit, b, "duplicate presence_bit attributes")?;
             } else {
                 unknown_attrs.push(attr);
             }
@@ -46,6 +70,22 @@ impl Field {
             None => return Ok(None),
         };
 
+        let ty = match (ty, max_len) {
+            (Ty::String(StringTy::Heapless(_)), Some(max_len)) => {
+                Ty::String(StringTy::Heapless(max_len))
+            }
+            (Ty::Bytes(BytesTy::Heapless(_)), Some(max_len)) => {
+                Ty::Bytes(BytesTy::Heapless(max_len))
+            }
+            (Ty::String(StringTy::Heapless(_)) | Ty::Bytes(BytesTy::Heapless(_)), None) => {
+                bail!("heapless string and bytes fields require a max_len attribute");
+            }
+            (_, Some(_)) => {
+                bail!("max_len attribute may only be applied to heapless string and bytes fields");
+            }
+            (ty, None) => ty,
+        };
+
         if !unknown_attrs.is_empty() {
             bail!(
                 "unknown attribute(s): #[prost({})]",
@@ -63,6 +103,11 @@ impl Field {
             || Ok(DefaultValue::new(&ty)),
             |lit| DefaultValue::from_lit(&ty, lit),
         )?;
+        if let Some(max_len) = ty.max_len() {
+            if default.len() > max_len {
+                bail!("default value exceeds the max_len of {max_len}");
+            }
+        }
 
         let kind = match (label, packed, has_default) {
             (None, Some(true), _)
@@ -76,7 +121,20 @@ impl Field {
             (Some(Label::Repeated), _, true) => {
                 bail!("repeated fields may not have a default value");
             }
+            (label, _, _) if presence && label != Some(Label::Required) => {
+                bail!("presence attribute may only be applied to required fields");
+            }
+            (label, _, _) if presence_bit.is_some() && label != Some(Label::Optional) => {
+                bail!("presence_bit attribute may only be applied to optional fields");
+            }
+            (_, _, _) if presence_bit.is_some() && convert => {
+                bail!("presence_bit attribute may not be combined with the convert attribute");
+            }
+            // The value of a field with a presence bit is always held, and only encoded if the
+            // bit is set.
+            (Some(Label::Optional), _, _) if presence_bit.is_some() => Kind::Required(default),
 
+            (Some(Label::Required), _, _) if presence => Kind::Optional(default),
             (None, _, _) => Kind::Plain(default),
             (Some(Label::Optional), _, _) => Kind::Optional(default),
             (Some(Label::Required), _, _) => Kind::Required(default),
@@ -86,11 +144,27 @@ impl Field {
             (Some(Label::Repeated), _, false) => Kind::Repeated,
         };
 
-        Ok(Some(Field { ty, kind, tag }))
+        Ok(Some(Field {
+            ty,
+            kind,
+            tag,
+            required: presence,
+            convert,
+            presence_bit,
+        }))
     }
 
     pub fn new_oneof(attrs: &[Meta]) -> Result<Option<Field>, Error> {
         if let Some(mut field) = Field::new(attrs, None)? {
+            if field.required {
+                bail!("invalid presence attribute on oneof field");
+            }
+            if field.convert {
+                bail!("invalid convert attribute on oneof field");
+            }
+            if field.presence_bit.is_some() {
+                bail!("invalid presence_bit attribute on oneof field");
+            }
             match field.kind {
                 Kind::Plain(default) => {
                     field.kind = Kind::Required(default);
@@ -106,6 +180,11 @@ impl Field {
     }
 
     pub fn encode(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        if self.convert {
+            let encode = self.unconverted().encode(prost_path, quote!(raw));
+            return self.with_converted(prost_path, ident, encode);
+        }
+
         let module = self.ty.module();
         let encode_fn = match self.kind {
             Kind::Plain(..) | Kind::Optional(..) | Kind::Required(..) => quote!(encode),
@@ -117,9 +196,9 @@ impl Field {
 
         match self.kind {
             Kind::Plain(ref default) => {
-                let default = default.typed();
+                let ne_default = self.ne_default(ident.clone(), default);
                 quote! {
-                    if #ident != #default {
+                    if #ne_default {
                         #encode_fn(#tag, &#ident, buf);
                     }
                 }
@@ -138,6 +217,26 @@ impl Field {
     /// Returns an expression which evaluates to the result of merging a decoded
     /// scalar value into the field.
     pub fn merge(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        if self.convert {
+            let raw_ty = self.converted_type(prost_path);
+            let merge = self.unconverted().merge(prost_path, quote!((&mut raw)));
+            // Repeated values are decoded one at a time, so only the decoded values are converted.
+            return match self.kind {
+                Kind::Repeated | Kind::Packed => quote! {
+                    ({
+                        let mut raw: #raw_ty = ::core::default::Default::default();
+                        let result = #merge;
+                        ::core::iter::Extend::extend(
+                            #ident,
+                            raw.into_iter().map(::core::convert::From::from),
+                        );
+                        result
+                    })
+                },
+                _ => self.merge_converted(prost_path, quote!((*#ident)), merge),
+            };
+        }
+
         let module = self.ty.module();
         let merge_fn = match self.kind {
             Kind::Plain(..) | Kind::Optional(..) | Kind::Required(..) => quote!(merge),
@@ -160,6 +259,11 @@ impl Field {
 
     /// Returns an expression which evaluates to the encoded length of the field.
     pub fn encoded_len(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        if self.convert {
+            let encoded_len = self.unconverted().encoded_len(prost_path, quote!(raw));
+            return self.with_converted(prost_path, ident, encoded_len);
+        }
+
         let module = self.ty.module();
         let encoded_len_fn = match self.kind {
             Kind::Plain(..) | Kind::Optional(..) | Kind::Required(..) => quote!(encoded_len),
@@ -171,9 +275,9 @@ impl Field {
 
         match self.kind {
             Kind::Plain(ref default) => {
-                let default = default.typed();
+                let ne_default = self.ne_default(ident.clone(), default);
                 quote! {
-                    if #ident != #default {
+                    if #ne_default {
                         #encoded_len_fn(#tag, &#ident)
                     } else {
                         0
@@ -190,11 +294,26 @@ impl Field {
     }
 
     pub fn clear(&self, ident: TokenStream) -> TokenStream {
+        if self.convert {
+            return match self.kind {
+                Kind::Repeated | Kind::Packed => quote!(#ident.clear()),
+                _ => {
+                    let default = self.default();
+                    quote!(#ident = #default)
+                }
+            };
+        }
+
         match self.kind {
             Kind::Plain(ref default) | Kind::Required(ref default) => {
                 let default = default.typed();
                 match self.ty {
-                    Ty::String | Ty::Bytes(..) => quote!(#ident.clear()),
+                    // Custom containers, such as `Arc<[u8]>`, may not be cleared in place.
+                    Ty::String(StringTy::Custom(_)) | Ty::Bytes(BytesTy::Custom(_)) => {
+                        let default = self.default();
+                        quote!(#ident = #default)
+                    }
+                    Ty::String(..) | Ty::Bytes(..) => quote!(#ident.clear()),
                     _ => quote!(#ident = #default),
                 }
             }
@@ -203,12 +322,329 @@ impl Field {
         }
     }
 
+    /// Returns an expression which is `true` if the field `ident` differs from its default value.
+    fn ne_default(&self, ident: TokenStream, default: &DefaultValue) -> TokenStream {
+        let default = default.typed();
+        match self.ty.custom_ref_type() {
+            // Custom containers need not implement `PartialEq<&str>` or `PartialEq<&[u8]>`.
+            Some(ref_ty) => quote!(::core::convert::AsRef::<#ref_ty>::as_ref(&#ident) != #default),
+            None => quote!(#ident != #default),
+        }
+    }
+
+    /// Returns a statement which merges the field `other` into the field.
+    /// Returns a statement which appends the path of the field to `missing` if it is a required
+    /// field which is not set.
+    pub fn missing_required_fields(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        name: &str,
+    ) -> TokenStream {
+        if !self.required {
+            return TokenStream::new();
+        }
+        quote! {
+            if #ident.is_none() {
+                #prost_path::encoding::message::missing_required_field(prefix, #name, missing);
+            }
+        }
+    }
+
+    pub fn merge_from(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        other: TokenStream,
+    ) -> TokenStream {
+        match self.kind {
+            Kind::Plain(ref default) if self.convert => {
+                let default = default.typed();
+                let raw_ty = self.converted_type(prost_path);
+                let convert_to = self.convert_to(other.clone());
+                quote! {
+                    if ::core::convert::identity::<#raw_ty>(#convert_to) != #default {
+                        #ident.clone_from(&#other);
+                    }
+                }
+            }
+            Kind::Plain(ref default) => {
+                let ne_default = self.ne_default(other.clone(), default);
+                quote! {
+                    if #ne_default {
+                        #ident.clone_from(&#other);
+                    }
+                }
+            }
+            Kind::Optional(..) => quote! {
+                if #other.is_some() {
+                    #ident.clone_from(&#other);
+                }
+            },
+            Kind::Required(..) => quote!(#ident.clone_from(&#other);),
+            Kind::Repeated | Kind::Packed => quote!(#ident.extend(#other.iter().cloned());),
+        }
+    }
+
     /// Returns an expression which evaluates to the default value of the field.
-    pub fn default(&self, prost_path: &Path) -> TokenStream {
+    pub fn default(&self) -> TokenStream {
         match self.kind {
-            Kind::Plain(ref value) | Kind::Required(ref value) => value.owned(prost_path),
+            Kind::Plain(..) | Kind::Required(..) if self.convert => {
+                let value = self.unconverted().default();
+                quote!(::core::convert::From::from(#value))
+            }
+            Kind::Plain(ref value) | Kind::Required(ref value)
+                if self.ty.max_len().is_some() && value.len() > 0 =>
+            {
+                // The length of the value is checked against the capacity in `Field::new`.
+                quote!(::core::convert::TryFrom::try_from(#value).unwrap())
+            }
+            Kind::Plain(ref value) | Kind::Required(ref value) => value.owned(),
             Kind::Optional(_) => quote!(::core::option::Option::None),
-            Kind::Repeated | Kind::Packed => quote!(#prost_path::alloc::vec::Vec::new()),
+            // Repeated fields are either an `alloc::vec::Vec` or a `heapless::Vec`.
+            Kind::Repeated | Kind::Packed => quote!(::core::default::Default::default()),
+        }
+    }
+
+    /// Returns a constant expression which evaluates to the default value of the field, given
+    /// the Rust type of the field.
+    pub fn const_default(&self, ty: &Type) -> Result<TokenStream, Error> {
+        if self.convert {
+            bail!("converted fields have no constant default");
+        }
+        let value = match self.kind {
+            Kind::Optional(_) => return Ok(quote!(::core::option::Option::None)),
+            Kind::Repeated | Kind::Packed => return Ok(quote!(<#ty>::new())),
+            Kind::Plain(ref value) | Kind::Required(ref value) => value,
+        };
+        let expr = match (&self.ty, value) {
+            (Ty::String(StringTy::Custom(_)) | Ty::Bytes(BytesTy::Custom(_)), _) => {
+                bail!("custom string and bytes types have no constant default")
+            }
+            (Ty::String(_) | Ty::Bytes(_), value) if value.len() == 0 => quote!(<#ty>::new()),
+            (Ty::String(StringTy::Bytes), DefaultValue::String(value)) => {
+                quote!(<#ty>::from_static(#value))
+            }
+            (Ty::Bytes(BytesTy::Bytes), DefaultValue::Bytes(value)) => {
+                let lit = LitByteStr::new(value, Span::call_site());
+                quote!(<#ty>::from_static(#lit))
+            }
+            (Ty::String(_) | Ty::Bytes(_), _) => {
+                bail!("string and bytes fields with a default value need `bytes` types for a constant default")
+            }
+            (Ty::Enumeration(path), DefaultValue::FirstVariant(_)) => quote!(#path::DEFAULT as i32),
+            (_, value) => value.typed(),
+        };
+        Ok(expr)
+    }
+
+    /// Returns a statement which adds the JSON representation of the field to `object`.
+    pub fn json_encode(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        if self.convert {
+            let encode = self.unconverted().json_encode(prost_path, quote!(raw));
+            return self.with_converted(prost_path, ident, encode);
+        }
+
+        let json = quote!(#prost_path::json::encoding);
+        let (to_json, encode_repeated) = match self.ty {
+            Ty::Enumeration(ref ty) => {
+                let enumeration = json_enumeration(prost_path, ty);
+                (
+                    quote!(#enumeration.to_json(#ident)),
+                    quote!(#enumeration.encode_repeated(&#ident)),
+                )
+            }
+            _ => (
+                quote!(#json::JsonField::to_json(&#ident, options)?),
+                quote!(#json::encode_repeated(&#ident, options)?),
+            ),
+        };
+
+        match self.kind {
+            Kind::Plain(ref default) => {
+                let ne_default = self.ne_default(ident.clone(), default);
+                quote! {
+                    if #json::emit_default_values(options) || #ne_default {
+                        object.push((name.into(), #to_json));
+                    }
+                }
+            }
+            Kind::Optional(..) => {
+                let to_json = match self.ty {
+                    Ty::Enumeration(ref ty) => {
+                        let enumeration = json_enumeration(prost_path, ty);
+                        quote!(#enumeration.to_json(*value))
+                    }
+                    _ => quote!(#json::JsonField::to_json(value, options)?),
+                };
+                quote! {
+                    if let ::core::option::Option::Some(ref value) = #ident {
+                        object.push((name.into(), #to_json));
+                    }
+                }
+            }
+            Kind::Required(..) => quote! {
+                object.push((name.into(), #to_json));
+            },
+            Kind::Repeated | Kind::Packed => quote! {
+                if #json::emit_default_values(options) || !#ident.is_empty() {
+                    object.push((name.into(), #encode_repeated));
+                }
+            },
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of merging the JSON `value` into the
+    /// field.
+    pub fn json_merge(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        if self.convert {
+            let merge = self.unconverted().json_merge(prost_path, quote!(raw));
+            return self.merge_converted(prost_path, ident, merge);
+        }
+
+        let json = quote!(#prost_path::json::encoding);
+        let merge_fn = match self.kind {
+            Kind::Plain(..) | Kind::Required(..) => quote!(merge),
+            Kind::Optional(..) => quote!(merge_optional),
+            Kind::Repeated | Kind::Packed => quote!(merge_repeated),
+        };
+        match self.ty {
+            Ty::Enumeration(ref ty) => {
+                let enumeration = json_enumeration(prost_path, ty);
+                quote!(#enumeration.#merge_fn(&mut #ident, value, options))
+            }
+            _ => quote!(#json::#merge_fn(&mut #ident, value, options)),
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of converting the JSON `value` of a
+    /// oneof field, or `None` if the field is unset.
+    pub fn json_merge_oneof(&self, prost_path: &Path) -> TokenStream {
+        match self.ty {
+            Ty::Enumeration(ref ty) => {
+                let enumeration = json_enumeration(prost_path, ty);
+                quote!(#enumeration.merge_oneof(value, options))
+            }
+            _ => quote!(#prost_path::json::encoding::merge_oneof(value, options)),
+        }
+    }
+
+    /// Returns a statement which sets the field to an arbitrary value generated from `u`.
+    pub fn arbitrary(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        if self.convert {
+            return quote!(#ident = #prost_path::arbitrary::Arbitrary::arbitrary(u)?;);
+        }
+
+        let value = self.ty.arbitrary(prost_path);
+        match self.kind {
+            Kind::Plain(..) | Kind::Required(..) => quote!(#ident = #value;),
+            Kind::Optional(..) => quote! {
+                #ident = if u.arbitrary::<bool>()? {
+                    ::core::option::Option::Some(#value)
+                } else {
+                    ::core::option::Option::None
+                };
+            },
+            Kind::Repeated | Kind::Packed => quote! {
+                #ident = #prost_path::encoding::arbitrary::repeated(u, |u| {
+                    ::core::result::Result::Ok(#value)
+                })?;
+            },
+        }
+    }
+
+    /// Returns an expression which evaluates to an arbitrary value of a oneof field generated
+    /// from `u`.
+    pub fn arbitrary_oneof(&self, prost_path: &Path) -> TokenStream {
+        if self.convert {
+            quote!(#prost_path::arbitrary::Arbitrary::arbitrary(u)?)
+        } else {
+            self.ty.arbitrary(prost_path)
+        }
+    }
+
+    /// Returns a statement which appends the text format representation of the field to
+    /// `message`.
+    pub fn text_encode(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        if self.convert {
+            let encode = self.unconverted().text_encode(prost_path, quote!(raw));
+            return self.with_converted(prost_path, ident, encode);
+        }
+
+        let to_text = |value: TokenStream| match self.ty {
+            Ty::Enumeration(ref ty) => {
+                let enumeration = text_enumeration(prost_path, ty);
+                quote!(#enumeration.to_text(#value))
+            }
+            _ => quote!(#prost_path::text_format::encoding::TextField::to_text_value(&#value)),
+        };
+
+        match self.kind {
+            Kind::Plain(ref default) => {
+                let ne_default = self.ne_default(ident.clone(), default);
+                let to_text = to_text(ident.clone());
+                quote! {
+                    if #ne_default {
+                        message.push((name.into(), #to_text));
+                    }
+                }
+            }
+            Kind::Optional(..) => {
+                let to_text = to_text(quote!(*value));
+                quote! {
+                    if let ::core::option::Option::Some(ref value) = #ident {
+                        message.push((name.into(), #to_text));
+                    }
+                }
+            }
+            Kind::Required(..) => {
+                let to_text = to_text(ident);
+                quote! {
+                    message.push((name.into(), #to_text));
+                }
+            }
+            Kind::Repeated | Kind::Packed => {
+                let to_text = to_text(quote!(*value));
+                quote! {
+                    for value in &#ident {
+                        message.push((name.into(), #to_text));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of merging the text format `value`
+    /// into the field.
+    pub fn text_merge(&self, prost_path: &Path, ident: TokenStream) -> TokenStream {
+        if self.convert {
+            let merge = self.unconverted().text_merge(prost_path, quote!(raw));
+            return self.merge_converted(prost_path, ident, merge);
+        }
+
+        let merge_fn = match self.kind {
+            Kind::Plain(..) | Kind::Required(..) => quote!(merge),
+            Kind::Optional(..) => quote!(merge_optional),
+            Kind::Repeated | Kind::Packed => quote!(merge_repeated),
+        };
+        match self.ty {
+            Ty::Enumeration(ref ty) => {
+                let enumeration = text_enumeration(prost_path, ty);
+                quote!(#enumeration.#merge_fn(&mut #ident, value))
+            }
+            _ => quote!(#prost_path::text_format::encoding::#merge_fn(&mut #ident, value)),
+        }
+    }
+
+    /// Returns an expression which evaluates to the result of converting the text format `value`
+    /// of a oneof field.
+    pub fn text_merge_oneof(&self, prost_path: &Path) -> TokenStream {
+        match self.ty {
+            Ty::Enumeration(ref ty) => {
+                let enumeration = text_enumeration(prost_path, ty);
+                quote!(#enumeration.from_text(value))
+            }
+            _ => quote!(#prost_path::text_format::encoding::TextField::from_text_value(value)),
         }
     }
 
@@ -221,8 +657,8 @@ impl Field {
                     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
                         let res: ::core::result::Result<#ty, _> = ::core::convert::TryFrom::try_from(*self.0);
                         match res {
-                            Err(_) => ::core::fmt::Debug::fmt(&self.0, f),
-                            Ok(en) => ::core::fmt::Debug::fmt(&en, f),
+                            ::core::result::Result::Err(_) => ::core::fmt::Debug::fmt(&self.0, f),
+                            ::core::result::Result::Ok(en) => ::core::fmt::Debug::fmt(&en, f),
                         }
                     }
                 }
@@ -237,6 +673,14 @@ impl Field {
 
     /// Returns a fragment for formatting the field `ident` in `Debug`.
     pub fn debug(&self, prost_path: &Path, wrapper_name: TokenStream) -> TokenStream {
+        if self.convert {
+            // Converted fields are formatted with the `Debug` implementation of their type.
+            return quote! {
+                #[allow(non_snake_case)]
+                fn #wrapper_name<T>(v: T) -> T { v }
+            };
+        }
+
         let wrapper = self.debug_inner(quote!(Inner));
         let inner_ty = self.ty.rust_type(prost_path);
         match self.kind {
@@ -252,7 +696,7 @@ impl Field {
             },
             Kind::Repeated | Kind::Packed => {
                 quote! {
-                    struct #wrapper_name<'a>(&'a #prost_path::alloc::vec::Vec<#inner_ty>);
+                    struct #wrapper_name<'a>(&'a [#inner_ty]);
                     impl<'a> ::core::fmt::Debug for #wrapper_name<'a> {
                         fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
                             let mut vec_builder = f.debug_list();
@@ -270,6 +714,10 @@ impl Field {
 
     /// Returns methods to embed in the message.
     pub fn methods(&self, ident: &TokenStream) -> Option<TokenStream> {
+        if self.convert {
+            return None;
+        }
+
         let mut ident_str = ident.to_string();
         if ident_str.starts_with("r#") {
             ident_str = ident_str.split_off(2);
@@ -344,7 +792,10 @@ impl Field {
                         }
                         #[doc=#push_doc]
                         pub fn #push(&mut self, value: #ty) {
-                            self.#ident.push(value as i32);
+                            ::core::iter::Extend::extend(
+                                &mut self.#ident,
+                                ::core::iter::once(value as i32),
+                            );
                         }
                     }
                 }
@@ -352,8 +803,11 @@ impl Field {
         } else if let Kind::Optional(ref default) = self.kind {
             let ty = self.ty.rust_ref_type();
 
-            let match_some = if self.ty.is_numeric() {
+            let match_some = if self.ty.is_copy() {
                 quote!(::core::option::Option::Some(val) => val,)
+            } else if let Some(ref_ty) = self.ty.custom_ref_type() {
+                let as_ref = quote!(::core::convert::AsRef::<#ref_ty>::as_ref);
+                quote!(::core::option::Option::Some(ref val) => #as_ref(val),)
             } else {
                 quote!(::core::option::Option::Some(ref val) => &val[..],)
             };
@@ -375,6 +829,232 @@ impl Field {
             None
         }
     }
+
+    /// Returns the methods of a field with a presence bit, given an expression which is `true`
+    /// if the bit is set, and statements which set and unset it.
+    pub fn presence_methods(
+        &self,
+        prost_path: &Path,
+        ident: &TokenStream,
+        is_set: TokenStream,
+        set_bit: TokenStream,
+        unset_bit: TokenStream,
+    ) -> TokenStream {
+        let ident_str = ident.to_string();
+        let ident_str = ident_str.trim_start_matches("r#");
+        let has = Ident::new(&format!("has_{ident_str}"), Span::call_site());
+        let has_doc = format!("Returns `true` if the optional field `{ident_str}` is set.");
+        let set = Ident::new(&format!("set_{ident_str}"), Span::call_site());
+        let clear = Ident::new(&format!("clear_{ident_str}"), Span::call_site());
+        let clear_doc = format!(
+            "Clears the optional field `{ident_str}`, leaving it unset with its default value."
+        );
+        let clear_value = self.clear(quote!(self.#ident));
+
+        let (get, set_doc, value_ty, value) = match (&self.ty, &self.kind) {
+            (Ty::Enumeration(ty), Kind::Required(default)) => {
+                let get_doc = format!(
+                    "Returns the enum value of `{ident_str}`, \
+                     or the default if the field is unset or set to an invalid enum value."
+                );
+                let get = quote! {
+                    #[doc=#get_doc]
+                    pub fn #ident(&self) -> #ty {
+                        ::core::convert::TryFrom::try_from(self.#ident).unwrap_or(#default)
+                    }
+                };
+                let set_doc = format!("Sets `{ident_str}` to the provided enum value.");
+                (get, set_doc, quote!(#ty), quote!(value as i32))
+            }
+            _ => {
+                let set_doc = format!("Sets `{ident_str}` to the provided value.");
+                (
+                    quote!(),
+                    set_doc,
+                    self.ty.rust_type(prost_path),
+                    quote!(value),
+                )
+            }
+        };
+
+        quote! {
+            #get
+
+            #[doc=#set_doc]
+            pub fn #set(&mut self, value: #value_ty) {
+                self.#ident = #value;
+                #set_bit
+            }
+
+            #[doc=#has_doc]
+            pub fn #has(&self) -> bool {
+                #is_set
+            }
+
+            #[doc=#clear_doc]
+            pub fn #clear(&mut self) {
+                #clear_value;
+                #unset_bit
+            }
+        }
+    }
+}
+
+impl Field {
+    /// Returns the field without the `convert` attribute, which holds the Rust type of the scalar.
+    fn unconverted(&self) -> Field {
+        Field {
+            convert: false,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the type of the field without the `convert` attribute.
+    fn converted_type(&self, prost_path: &Path) -> TokenStream {
+        let ty = self.ty.rust_type(prost_path);
+        match self.kind {
+            Kind::Plain(..) | Kind::Required(..) => ty,
+            Kind::Optional(..) => quote!(::core::option::Option<#ty>),
+            Kind::Repeated | Kind::Packed => quote!(#prost_path::alloc::vec::Vec<#ty>),
+        }
+    }
+
+    /// Returns an expression which converts the value of the converted field `ident` into the
+    /// Rust type of the scalar.
+    fn convert_to(&self, ident: TokenStream) -> TokenStream {
+        match self.kind {
+            Kind::Plain(..) | Kind::Required(..) => {
+                quote!(::core::convert::From::from(::core::clone::Clone::clone(&#ident)))
+            }
+            Kind::Optional(..) => {
+                quote!(::core::clone::Clone::clone(&#ident).map(::core::convert::From::from))
+            }
+            Kind::Repeated | Kind::Packed => quote! {
+                #ident.iter().cloned().map(::core::convert::From::from).collect()
+            },
+        }
+    }
+
+    /// Returns an expression which converts `raw`, of the Rust type of the scalar, into the type
+    /// of the converted field.
+    fn convert_from(&self) -> TokenStream {
+        match self.kind {
+            Kind::Plain(..) | Kind::Required(..) => quote!(::core::convert::From::from(raw)),
+            Kind::Optional(..) => quote!(raw.map(::core::convert::From::from)),
+            Kind::Repeated | Kind::Packed => {
+                quote!(raw.into_iter().map(::core::convert::From::from).collect())
+            }
+        }
+    }
+
+    /// Wraps `code`, which uses the field as `raw`, in a block which binds the converted value of
+    /// the field `ident` to `raw`.
+    fn with_converted(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        code: TokenStream,
+    ) -> TokenStream {
+        let raw_ty = self.converted_type(prost_path);
+        let convert_to = self.convert_to(ident);
+        quote! {
+            {
+                let raw: #raw_ty = #convert_to;
+                #code
+            }
+        }
+    }
+
+    /// Wraps `merge`, which merges into the field as `raw`, in a block which converts the field
+    /// `ident` to `raw` and back.
+    fn merge_converted(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        merge: TokenStream,
+    ) -> TokenStream {
+        let raw_ty = self.converted_type(prost_path);
+        let convert_to = self.convert_to(ident.clone());
+        let convert_from = self.convert_from();
+        quote! {
+            ({
+                let mut raw: #raw_ty = #convert_to;
+                let result = #merge;
+                #ident = #convert_from;
+                result
+            })
+        }
+    }
+}
+
+/// Parses the `max_len` attribute, which sets the capacity of heapless `string` and `bytes` fields.
+fn max_len_attr(attr: &Meta) -> Result<Option<usize>, Error> {
+    if !attr.path().is_ident("max_len") {
+        return Ok(None);
+    }
+    match *attr {
+        Meta::NameValue(MetaNameValue {
+            value: Expr::Lit(ref expr),
+            ..
+        }) => match expr.lit {
+            Lit::Str(ref lit) => lit
+                .value()
+                .parse::<usize>()
+                .map_err(Error::from)
+                .map(Option::Some),
+            Lit::Int(ref lit) => Ok(Some(lit.base10_parse()?)),
+            _ => bail!("invalid max_len attribute: {attr:?}"),
+        },
+        _ => bail!("invalid max_len attribute: {attr:?}"),
+    }
+}
+
+fn presence_bit_attr(attr: &Meta) -> Result<Option<u32>, Error> {
+    if !attr.path().is_ident("presence_bit") {
+        return Ok(None);
+    }
+    match *attr {
+        Meta::NameValue(MetaNameValue {
+            value: Expr::Lit(ref expr),
+            ..
+        }) => match expr.lit {
+            Lit::Str(ref lit) => lit
+                .value()
+                .parse::<u32>()
+                .map_err(Error::from)
+                .map(Option::Some),
+            Lit::Int(ref lit) => Ok(Some(lit.base10_parse()?)),
+            _ => bail!("invalid presence_bit attribute: {attr:?}"),
+        },
+        _ => bail!("invalid presence_bit attribute: {attr:?}"),
+    }
+}
+
+/// Returns an expression which evaluates to the JSON name mapping of the enumeration `ty`.
+pub fn json_enumeration(prost_path: &Path, ty: &Path) -> TokenStream {
+    enumeration(quote!(#prost_path::json::encoding), ty)
+}
+
+/// Returns an expression which evaluates to the text format name mapping of the enumeration
+/// `ty`.
+pub fn text_enumeration(prost_path: &Path, ty: &Path) -> TokenStream {
+    enumeration(quote!(#prost_path::text_format::encoding), ty)
+}
+
+/// Returns an expression which evaluates to the `Enumeration` name mapping of `ty`, as defined
+/// in the `encoding` module.
+///
+/// The mapping relies on the `as_str_name` and `from_str_name` methods generated by `prost-build`.
+fn enumeration(encoding: TokenStream, ty: &Path) -> TokenStream {
+    quote! {
+        (#encoding::Enumeration {
+            name: |value| {
+                let result: ::core::result::Result<#ty, _> = ::core::convert::TryFrom::try_from(value);
+                result.ok().map(|value| #ty::as_str_name(&value))
+            },
+            value: |name| #ty::from_str_name(name).map(|value| value as i32),
+        })
+    }
 }
 
 /// A scalar protobuf field type.
@@ -393,15 +1073,56 @@ pub enum Ty {
     Sfixed32,
     Sfixed64,
     Bool,
-    String,
+    String(StringTy),
     Bytes(BytesTy),
     Enumeration(Path),
+    /// A `u128`, which Protobuf does not have, i.e. `uint128` or `uint128 = "fixed64"`.
+    Uint128(Int128Ty),
+    /// An `i128`, which Protobuf does not have, i.e. `int128` or `int128 = "fixed64"`.
+    Int128(Int128Ty),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StringTy {
+    String,
+    Bytes,
+    /// A `heapless::String` with the capacity of the `max_len` attribute.
+    Heapless(usize),
+    /// A container implementing `StringAdapter`, given by its path.
+    Custom(Box<syn::Type>),
+}
+
+impl StringTy {
+    fn try_from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "string" => Ok(StringTy::String),
+            "bytes" => Ok(StringTy::Bytes),
+            // The capacity is set from the `max_len` attribute by `Field::new`.
+            "heapless" => Ok(StringTy::Heapless(0)),
+            _ => parse_str(s)
+                .map(|ty| StringTy::Custom(Box::new(ty)))
+                .map_err(|_| anyhow!("Invalid string type: {s}")),
+        }
+    }
+
+    fn rust_type(&self, prost_path: &Path) -> TokenStream {
+        match self {
+            StringTy::String => quote! { #prost_path::alloc::string::String },
+            StringTy::Bytes => quote! { #prost_path::Utf8Bytes },
+            StringTy::Heapless(max_len) => quote! { #prost_path::heapless::String<#max_len> },
+            StringTy::Custom(ty) => quote! { #ty },
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum BytesTy {
     Vec,
     Bytes,
+    /// A `heapless::Vec<u8, _>` with the capacity of the `max_len` attribute.
+    Heapless(usize),
+    /// A container implementing `BytesAdapter`, given by its path.
+    Custom(Box<syn::Type>),
 }
 
 impl BytesTy {
@@ -409,7 +1130,11 @@ impl BytesTy {
         match s {
             "vec" => Ok(BytesTy::Vec),
             "bytes" => Ok(BytesTy::Bytes),
-            _ => bail!("Invalid bytes type: {s}"),
+            // The capacity is set from the `max_len` attribute by `Field::new`.
+            "heapless" => Ok(BytesTy::Heapless(0)),
+            _ => parse_str(s)
+                .map(|ty| BytesTy::Custom(Box::new(ty)))
+                .map_err(|_| anyhow!("Invalid bytes type: {s}")),
         }
     }
 
@@ -417,6 +1142,27 @@ impl BytesTy {
         match self {
             BytesTy::Vec => quote! { #prost_path::alloc::vec::Vec<u8> },
             BytesTy::Bytes => quote! { #prost_path::bytes::Bytes },
+            BytesTy::Heapless(max_len) => quote! { #prost_path::heapless::Vec<u8, #max_len> },
+            BytesTy::Custom(ty) => quote! { #ty },
+        }
+    }
+}
+
+/// The encoding of a 128-bit integer field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Int128Ty {
+    /// A `bytes` value of 16 big-endian bytes.
+    Bytes,
+    /// A nested message with the high and low 64 bits in two `fixed64` fields.
+    Fixed64,
+}
+
+impl Int128Ty {
+    fn try_from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "bytes" => Ok(Int128Ty::Bytes),
+            "fixed64" => Ok(Int128Ty::Fixed64),
+            _ => bail!("invalid 128-bit integer encoding: {s}"),
         }
     }
 }
@@ -437,8 +1183,19 @@ impl Ty {
             Meta::Path(ref name) if name.is_ident("sfixed32") => Ty::Sfixed32,
             Meta::Path(ref name) if name.is_ident("sfixed64") => Ty::Sfixed64,
             Meta::Path(ref name) if name.is_ident("bool") => Ty::Bool,
-            Meta::Path(ref name) if name.is_ident("string") => Ty::String,
+            Meta::Path(ref name) if name.is_ident("string") => Ty::String(StringTy::String),
             Meta::Path(ref name) if name.is_ident("bytes") => Ty::Bytes(BytesTy::Vec),
+            Meta::Path(ref name) if name.is_ident("uint128") => Ty::Uint128(Int128Ty::Bytes),
+            Meta::Path(ref name) if name.is_ident("int128") => Ty::Int128(Int128Ty::Bytes),
+            Meta::NameValue(MetaNameValue {
+                ref path,
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(ref l),
+                        ..
+                    }),
+                ..
+            }) if path.is_ident("string") => Ty::String(StringTy::try_from_str(&l.value())?),
             Meta::NameValue(MetaNameValue {
                 ref path,
                 value:
@@ -448,6 +1205,24 @@ impl Ty {
                     }),
                 ..
             }) if path.is_ident("bytes") => Ty::Bytes(BytesTy::try_from_str(&l.value())?),
+            Meta::NameValue(MetaNameValue {
+                ref path,
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(ref l),
+                        ..
+                    }),
+                ..
+            }) if path.is_ident("uint128") => Ty::Uint128(Int128Ty::try_from_str(&l.value())?),
+            Meta::NameValue(MetaNameValue {
+                ref path,
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(ref l),
+                        ..
+                    }),
+                ..
+            }) if path.is_ident("int128") => Ty::Int128(Int128Ty::try_from_str(&l.value())?),
             Meta::NameValue(MetaNameValue {
                 ref path,
                 value:
@@ -482,7 +1257,7 @@ impl Ty {
             "sfixed32" => Ty::Sfixed32,
             "sfixed64" => Ty::Sfixed64,
             "bool" => Ty::Bool,
-            "string" => Ty::String,
+            "string" => Ty::String(StringTy::String),
             "bytes" => Ty::Bytes(BytesTy::Vec),
             s if s.len() > enumeration_len && &s[..enumeration_len] == "enumeration" => {
                 let s = &s[enumeration_len..].trim();
@@ -518,21 +1293,42 @@ impl Ty {
             Ty::Sfixed32 => "sfixed32",
             Ty::Sfixed64 => "sfixed64",
             Ty::Bool => "bool",
-            Ty::String => "string",
+            Ty::String(..) => "string",
             Ty::Bytes(..) => "bytes",
             Ty::Enumeration(..) => "enum",
+            Ty::Uint128(..) => "uint128",
+            Ty::Int128(..) => "int128",
         }
     }
 
     // TODO: rename to 'owned_type'.
     pub fn rust_type(&self, prost_path: &Path) -> TokenStream {
         match self {
-            Ty::String => quote!(#prost_path::alloc::string::String),
+            Ty::String(ty) => ty.rust_type(prost_path),
             Ty::Bytes(ty) => ty.rust_type(prost_path),
             _ => self.rust_ref_type(),
         }
     }
 
+    /// Returns an expression which evaluates to an arbitrary value of the type generated from
+    /// `u`. The values of enumerations are variants of the enumeration.
+    pub fn arbitrary(&self, prost_path: &Path) -> TokenStream {
+        let arbitrary = quote!(#prost_path::arbitrary::Arbitrary);
+        match self {
+            Ty::Enumeration(ty) => quote!(i32::from(<#ty as #arbitrary>::arbitrary(u)?)),
+            // `Bytes` does not implement `Arbitrary`.
+            Ty::Bytes(BytesTy::Bytes) => quote! {
+                #prost_path::bytes::Bytes::from(
+                    <#prost_path::alloc::vec::Vec<u8> as #arbitrary>::arbitrary(u)?
+                )
+            },
+            _ => {
+                let ty = self.rust_type(prost_path);
+                quote!(<#ty as #arbitrary>::arbitrary(u)?)
+            }
+        }
+    }
+
     // TODO: rename to 'ref_type'
     pub fn rust_ref_type(&self) -> TokenStream {
         match *self {
@@ -549,22 +1345,54 @@ impl Ty {
             Ty::Sfixed32 => quote!(i32),
             Ty::Sfixed64 => quote!(i64),
             Ty::Bool => quote!(bool),
-            Ty::String => quote!(&str),
+            Ty::String(..) => quote!(&str),
             Ty::Bytes(..) => quote!(&[u8]),
             Ty::Enumeration(..) => quote!(i32),
+            Ty::Uint128(..) => quote!(u128),
+            Ty::Int128(..) => quote!(i128),
         }
     }
 
     pub fn module(&self) -> Ident {
         match *self {
             Ty::Enumeration(..) => Ident::new("int32", Span::call_site()),
+            Ty::Uint128(Int128Ty::Fixed64) => Ident::new("uint128_fixed64", Span::call_site()),
+            Ty::Int128(Int128Ty::Fixed64) => Ident::new("int128_fixed64", Span::call_site()),
             _ => Ident::new(self.as_str(), Span::call_site()),
         }
     }
 
-    /// Returns false if the scalar type is length delimited (i.e., `string` or `bytes`).
+    /// Returns the type a custom `string` or `bytes` container is borrowed as, with `AsRef`.
+    fn custom_ref_type(&self) -> Option<TokenStream> {
+        match self {
+            Ty::String(StringTy::Custom(_)) => Some(quote!(str)),
+            Ty::Bytes(BytesTy::Custom(_)) => Some(quote!([u8])),
+            _ => None,
+        }
+    }
+
+    /// Returns the capacity of a heapless `string` or `bytes` type.
+    pub fn max_len(&self) -> Option<usize> {
+        match *self {
+            Ty::String(StringTy::Heapless(max_len)) | Ty::Bytes(BytesTy::Heapless(max_len)) => {
+                Some(max_len)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns false if the scalar type is length delimited (i.e., `string`, `bytes` or a 128-bit
+    /// integer), which may not be packed.
     pub fn is_numeric(&self) -> bool {
-        !matches!(self, Ty::String | Ty::Bytes(..))
+        !matches!(
+            self,
+            Ty::String(..) | Ty::Bytes(..) | Ty::Uint128(..) | Ty::Int128(..)
+        )
+    }
+
+    /// Returns true if the Rust type of the scalar type is `Copy`.
+    pub fn is_copy(&self) -> bool {
+        !matches!(self, Ty::String(..) | Ty::Bytes(..))
     }
 }
 
@@ -604,10 +1432,14 @@ pub enum DefaultValue {
     I64(i64),
     U32(u32),
     U64(u64),
+    I128(i128),
+    U128(u128),
     Bool(bool),
     String(String),
     Bytes(Vec<u8>),
     Enumeration(TokenStream),
+    /// The first variant of an enumeration, for fields without a default attribute.
+    FirstVariant(Path),
     Path(Path),
 }
 
@@ -633,6 +1465,9 @@ impl DefaultValue {
         let is_u32 = *ty == Ty::Uint32 || *ty == Ty::Fixed32;
         let is_u64 = *ty == Ty::Uint64 || *ty == Ty::Fixed64;
 
+        let is_i128 = matches!(ty, Ty::Int128(..));
+        let is_u128 = matches!(ty, Ty::Uint128(..));
+
         let empty_or_is = |expected, actual: &str| expected == actual || actual.is_empty();
 
         let default = match lit {
@@ -648,6 +1483,12 @@ impl DefaultValue {
             Lit::Int(ref lit) if is_u64 && empty_or_is("u64", lit.suffix()) => {
                 DefaultValue::U64(lit.base10_parse()?)
             }
+            Lit::Int(ref lit) if is_i128 && empty_or_is("i128", lit.suffix()) => {
+                DefaultValue::I128(lit.base10_parse()?)
+            }
+            Lit::Int(ref lit) if is_u128 && empty_or_is("u128", lit.suffix()) => {
+                DefaultValue::U128(lit.base10_parse()?)
+            }
 
             Lit::Float(ref lit) if *ty == Ty::Float && empty_or_is("f32", lit.suffix()) => {
                 DefaultValue::F32(lit.base10_parse()?)
@@ -660,10 +1501,8 @@ impl DefaultValue {
             Lit::Int(ref lit) if *ty == Ty::Double => DefaultValue::F64(lit.base10_parse()?),
 
             Lit::Bool(ref lit) if *ty == Ty::Bool => DefaultValue::Bool(lit.value),
-            Lit::Str(ref lit) if *ty == Ty::String => DefaultValue::String(lit.value()),
-            Lit::ByteStr(ref lit)
-                if *ty == Ty::Bytes(BytesTy::Bytes) || *ty == Ty::Bytes(BytesTy::Vec) =>
-            {
+            Lit::Str(ref lit) if matches!(ty, Ty::String(..)) => DefaultValue::String(lit.value()),
+            Lit::ByteStr(ref lit) if matches!(ty, Ty::Bytes(..)) => {
                 DefaultValue::Bytes(lit.value())
             }
 
@@ -727,6 +1566,16 @@ impl DefaultValue {
                             let value: i128 = -lit.base10_parse()?;
                             return Ok(i64::try_from(value).map(DefaultValue::I64)?);
                         }
+                        Lit::Int(ref lit) if is_i128 && empty_or_is("i128", lit.suffix()) => {
+                            // Parse the magnitude into a u128, so that i128::MIN does not overflow.
+                            let magnitude: u128 = lit.base10_parse()?;
+                            return 0i128
+                                .checked_sub_unsigned(magnitude)
+                                .map(DefaultValue::I128)
+                                .ok_or_else(|| {
+                                    anyhow!("default value out of range: -{magnitude}")
+                                });
+                        }
                         Lit::Float(ref lit)
                             if *ty == Ty::Float && empty_or_is("f32", lit.suffix()) =>
                         {
@@ -767,18 +1616,29 @@ impl DefaultValue {
             Ty::Int64 | Ty::Sint64 | Ty::Sfixed64 => DefaultValue::I64(0),
             Ty::Uint32 | Ty::Fixed32 => DefaultValue::U32(0),
             Ty::Uint64 | Ty::Fixed64 => DefaultValue::U64(0),
+            Ty::Int128(..) => DefaultValue::I128(0),
+            Ty::Uint128(..) => DefaultValue::U128(0),
 
             Ty::Bool => DefaultValue::Bool(false),
-            Ty::String => DefaultValue::String(String::new()),
+            Ty::String(..) => DefaultValue::String(String::new()),
             Ty::Bytes(..) => DefaultValue::Bytes(Vec::new()),
-            Ty::Enumeration(ref path) => DefaultValue::Enumeration(quote!(#path::default())),
+            Ty::Enumeration(ref path) => DefaultValue::FirstVariant(path.clone()),
         }
     }
 
-    pub fn owned(&self, prost_path: &Path) -> TokenStream {
+    /// Returns the length of a `string` or `bytes` default value.
+    fn len(&self) -> usize {
+        match *self {
+            DefaultValue::String(ref value) => value.len(),
+            DefaultValue::Bytes(ref value) => value.len(),
+            _ => 0,
+        }
+    }
+
+    pub fn owned(&self) -> TokenStream {
         match *self {
             DefaultValue::String(ref value) if value.is_empty() => {
-                quote!(#prost_path::alloc::string::String::new())
+                quote!(::core::default::Default::default())
             }
             DefaultValue::String(ref value) => quote!(#value.into()),
             DefaultValue::Bytes(ref value) if value.is_empty() => {
@@ -794,7 +1654,7 @@ impl DefaultValue {
     }
 
     pub fn typed(&self) -> TokenStream {
-        if let DefaultValue::Enumeration(_) = *self {
+        if let DefaultValue::Enumeration(_) | DefaultValue::FirstVariant(_) = *self {
             quote!(#self as i32)
         } else {
             quote!(#self)
@@ -811,6 +1671,8 @@ impl ToTokens for DefaultValue {
             DefaultValue::I64(value) => value.to_tokens(tokens),
             DefaultValue::U32(value) => value.to_tokens(tokens),
             DefaultValue::U64(value) => value.to_tokens(tokens),
+            DefaultValue::I128(value) => value.to_tokens(tokens),
+            DefaultValue::U128(value) => value.to_tokens(tokens),
             DefaultValue::Bool(value) => value.to_tokens(tokens),
             DefaultValue::String(ref value) => value.to_tokens(tokens),
             DefaultValue::Bytes(ref value) => {
@@ -818,6 +1680,7 @@ impl ToTokens for DefaultValue {
                 tokens.append_all(quote!(#byte_str as &[u8]));
             }
             DefaultValue::Enumeration(ref value) => value.to_tokens(tokens),
+            DefaultValue::FirstVariant(ref path) => quote!(#path::default()).to_tokens(tokens),
             DefaultValue::Path(ref value) => value.to_tokens(tokens),
         }
     }
@@ -0,0 +1,132 @@
+use anyhow::{bail, Error};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_str, Meta, Path};
+
+use crate::field::{set_option, str_attr, tag_attr};
+
+/// A field which is encoded and decoded by the functions of a module, i.e.
+/// `#[prost(with = "path::to::module", tag = "1")]`.
+///
+/// The module provides the same functions as the modules of `prost::encoding`:
+///
+/// ```rust,ignore
+/// pub fn encode(tag: u32, value: &T, buf: &mut impl BufMut);
+/// pub fn merge(
+///     wire_type: WireType,
+///     value: &mut T,
+///     buf: &mut impl Buf,
+///     ctx: DecodeContext,
+/// ) -> Result<(), DecodeError>;
+/// pub fn encoded_len(tag: u32, value: &T) -> usize;
+/// ```
+#[derive(Clone)]
+pub struct Field {
+    pub module: Path,
+    pub tag: u32,
+}
+
+impl Field {
+    pub fn new(attrs: &[Meta], inferred_tag: Option<u32>) -> Result<Option<Field>, Error> {
+        let mut module = None;
+        let mut tag = None;
+        let mut unknown_attrs = Vec::new();
+
+        for attr in attrs {
+            if let Some(path) = str_attr("with", attr)? {
+                set_option(&mut module, path, "duplicate with attributes")?;
+            } else if let Some(t) = tag_attr(attr)? {
+                set_option(&mut tag, t, "duplicate tag attributes")?;
+            } else {
+                unknown_attrs.push(attr);
+            }
+        }
+
+        let module = match module {
+            Some(module) => parse_str::<Path>(&module)?,
+            None => return Ok(None),
+        };
+
+        if !unknown_attrs.is_empty() {
+            bail!(
+                "unknown attribute(s) for with field: #[prost({})]",
+                quote!(#(#unknown_attrs),*)
+            );
+        }
+
+        let tag = match tag.or(inferred_tag) {
+            Some(tag) => tag,
+            None => bail!("with field is missing a tag attribute"),
+        };
+
+        Ok(Some(Field { module, tag }))
+    }
+
+    /// Returns a statement which encodes the field.
+    pub fn encode(&self, ident: TokenStream) -> TokenStream {
+        let module = &self.module;
+        let tag = self.tag;
+        quote!(#module::encode(#tag, &#ident, buf);)
+    }
+
+    /// Returns an expression which evaluates to the result of merging a decoded value into the
+    /// field.
+    pub fn merge(&self, ident: TokenStream) -> TokenStream {
+        let module = &self.module;
+        quote!(#module::merge(wire_type, #ident, buf, ctx))
+    }
+
+    /// Returns an expression which evaluates to the encoded length of the field.
+    pub fn encoded_len(&self, ident: TokenStream) -> TokenStream {
+        let module = &self.module;
+        let tag = self.tag;
+        quote!(#module::encoded_len(#tag, &#ident))
+    }
+
+    /// Returns a statement which merges the field `other` into the field, by decoding the
+    /// encoding of `other` into it, since the module defines the merge semantics of the field.
+    pub fn merge_from(
+        &self,
+        prost_path: &Path,
+        ident: TokenStream,
+        other: TokenStream,
+    ) -> TokenStream {
+        let module = &self.module;
+        let tag = self.tag;
+        quote! {
+            {
+                let mut encoded = #prost_path::alloc::vec::Vec::new();
+                #module::encode(#tag, &#other, &mut encoded);
+                let mut buf = encoded.as_slice();
+                while !buf.is_empty() {
+                    let (_, wire_type) = #prost_path::encoding::decode_key(&mut buf)
+                        .expect("the encoding of a field is valid");
+                    #module::merge(
+                        wire_type,
+                        &mut #ident,
+                        &mut buf,
+                        #prost_path::encoding::DecodeContext::default(),
+                    )
+                    .expect("the encoding of a field can be merged into the field");
+                }
+            }
+        }
+    }
+
+    /// Returns a statement which clears the field.
+    pub fn clear(&self, ident: TokenStream) -> TokenStream {
+        quote!(#ident = ::core::default::Default::default())
+    }
+
+    /// Returns a statement or expression which fails to compile, for the generated code of the
+    /// optional features of a message, such as the JSON mapping, which the module does not
+    /// provide.
+    pub fn unsupported(&self, feature: &str) -> TokenStream {
+        let module = &self.module;
+        let error = format!(
+            "fields encoded with `{}` do not support {feature}",
+            quote!(#module).to_string().replace(' ', "")
+        );
+        quote!(::core::compile_error! { #error })
+    }
+}
@@ -26,6 +26,14 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
 
     let Attributes {
         skip_debug,
+        json,
+        arbitrary,
+        text_format,
+        text_format_debug,
+        field_mask,
+        proto_cmp,
+        const_default,
+        accessors,
         prost_path,
     } = Attributes::new(input.attrs)?;
 
@@ -57,10 +65,18 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
     };
 
     let mut next_tag: u32 = 1;
+    let mut unknown_fields = None;
+    let mut presence_bits = None;
+    let mut extension_set = None;
+    let mut const_defaults = Vec::new();
+    let mut field_types = Vec::new();
+    let mut redacted = Vec::new();
+    let mut json_names = Vec::new();
     let mut fields = fields
         .into_iter()
         .enumerate()
         .flat_map(|(i, field)| {
+            let field_ty = field.ty;
             let field_ident = field.ident.map(|x| quote!(#x)).unwrap_or_else(|| {
                 let index = Index {
                     index: i as u32,
@@ -68,8 +84,98 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
                 };
                 quote!(#index)
             });
+            match field::is_unknown_fields(&field.attrs) {
+                Ok(true) => {
+                    return set_option(
+                        &mut unknown_fields,
+                        field_ident,
+                        "duplicate unknown_fields attributes",
+                    )
+                    .err()
+                    .map(Err);
+                }
+                Ok(false) => (),
+                Err(err) => {
+                    return Some(Err(
+                        err.context(format!("invalid message field {ident}.{field_ident}"))
+                    ))
+                }
+            }
+            match field::is_presence_bits(&field.attrs) {
+                Ok(true) => {
+                    return field::PresenceBits::new(field_ident.clone(), &field_ty)
+                        .and_then(|bits| {
+                            set_option(
+                                &mut presence_bits,
+                                bits,
+                                "duplicate presence_bits attributes",
+                            )
+                        })
+                        .err()
+                        .map(|err| {
+                            Err(err.context(format!("invalid message field {ident}.{field_ident}")))
+                        });
+                }
+                Ok(false) => (),
+                Err(err) => {
+                    return Some(Err(
+                        err.context(format!("invalid message field {ident}.{field_ident}"))
+                    ))
+                }
+            }
+            match field::extension_set(&field.attrs) {
+                Ok(Some(extensions)) => {
+                    return set_option(
+                        &mut extension_set,
+                        (field_ident, extensions),
+                        "duplicate extension_set attributes",
+                    )
+                    .err()
+                    .map(Err);
+                }
+                Ok(None) => (),
+                Err(err) => {
+                    return Some(Err(
+                        err.context(format!("invalid message field {ident}.{field_ident}"))
+                    ))
+                }
+            }
+            match field::is_redacted(&field.attrs) {
+                Ok(true) => redacted.push(field_ident.to_string()),
+                Ok(false) => (),
+                Err(err) => {
+                    return Some(Err(
+                        err.context(format!("invalid message field {ident}.{field_ident}"))
+                    ))
+                }
+            }
+            match field::declared_json_name(&field.attrs) {
+                Ok(Some(json_name)) => json_names.push((field_ident.to_string(), json_name)),
+                Ok(None) => (),
+                Err(err) => {
+                    return Some(Err(
+                        err.context(format!("invalid message field {ident}.{field_ident}"))
+                    ))
+                }
+            }
+            let deprecated = field
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("deprecated"));
             match Field::new(field.attrs, Some(next_tag)) {
-                Ok(Some(field)) => {
+                Ok(Some(mut field)) => {
+                    field.set_rust_type(&field_ty);
+                    field_types.push((field_ty.clone(), deprecated));
+                    if const_default {
+                        match field.const_default(&field_ty) {
+                            Ok(value) => const_defaults.push(quote!(#field_ident: #value,)),
+                            Err(err) => {
+                                return Some(Err(err.context(format!(
+                                    "invalid message field {ident}.{field_ident}"
+                                ))))
+                            }
+                        }
+                    }
                     next_tag = field.tags().iter().max().map(|t| t + 1).unwrap_or(next_tag);
                     Some(Ok((field_ident, field)))
                 }
@@ -100,30 +206,235 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
         bail!("message {ident} has multiple fields with tag {duplicate_tag}",)
     };
 
+    if presence_bits.is_none() {
+        if let Some((field_ident, _)) = fields
+            .iter()
+            .find(|(_, field)| field.presence_bit().is_some())
+        {
+            bail!("field {ident}.{field_ident} has a presence bit, but {ident} has no presence_bits field");
+        }
+    }
+
+    // Fields with a presence bit are only encoded if their bit is set in the presence bits of
+    // the message.
+    let present = |field: &Field, message: TokenStream| {
+        field
+            .presence_bit()
+            .zip(presence_bits.as_ref())
+            .map(|(bit, bits)| bits.is_set(message, bit))
+    };
+    let set_bit = |field: &Field, message: TokenStream| {
+        field
+            .presence_bit()
+            .zip(presence_bits.as_ref())
+            .map(|(bit, bits)| bits.set(message, bit))
+    };
+    let unset_bit = |field: &Field, message: TokenStream| {
+        field
+            .presence_bit()
+            .zip(presence_bits.as_ref())
+            .map(|(bit, bits)| bits.unset(message, bit))
+    };
+    let if_present = |field: &Field, encode: TokenStream| match present(field, quote!(self)) {
+        Some(present) => quote!(if #present { #encode }),
+        None => encode,
+    };
+    let len_if_present = |field: &Field, len: TokenStream| match present(field, quote!(self)) {
+        Some(present) => quote!((if #present { #len } else { 0 })),
+        None => len,
+    };
+
     let encoded_len = fields
         .iter()
-        .map(|(field_ident, field)| field.encoded_len(&prost_path, quote!(self.#field_ident)));
+        .map(|(field_ident, field)| {
+            len_if_present(
+                field,
+                field.encoded_len(&prost_path, quote!(self.#field_ident)),
+            )
+        })
+        .chain(
+            extension_set
+                .iter()
+                .map(|(field_ident, _)| field_ident)
+                .chain(unknown_fields.iter())
+                .map(|field_ident| quote!(#prost_path::Message::encoded_len(&self.#field_ident))),
+        );
 
+    // Extensions and unknown fields are written after all known fields.
     let encode = fields
         .iter()
-        .map(|(field_ident, field)| field.encode(&prost_path, quote!(self.#field_ident)));
+        .map(|(field_ident, field)| {
+            if_present(field, field.encode(&prost_path, quote!(self.#field_ident)))
+        })
+        .chain(
+            extension_set
+                .iter()
+                .map(|(field_ident, _)| field_ident)
+                .chain(unknown_fields.iter())
+                .map(
+                    |field_ident| quote!(#prost_path::Message::encode_raw(&self.#field_ident, buf);),
+                ),
+        );
+
+    // Messages with message fields record the lengths of the nested messages in a `LengthCache`,
+    // in the same order in which they are encoded. Other messages use the default
+    // implementations, which do not record any lengths.
+    let cached = if fields
+        .iter()
+        .any(|(_, field)| matches!(field, Field::Message(_)))
+    {
+        let encoded_len_cached = fields
+            .iter()
+            .map(|(field_ident, field)| {
+                len_if_present(
+                    field,
+                    field.encoded_len_cached(&prost_path, quote!(self.#field_ident)),
+                )
+            })
+            .chain(
+                extension_set
+                    .iter()
+                    .map(|(field_ident, _)| field_ident)
+                    .chain(unknown_fields.iter())
+                    .map(
+                        |field_ident| quote!(#prost_path::Message::encoded_len(&self.#field_ident)),
+                    ),
+            );
+        let encode_cached = fields
+            .iter()
+            .map(|(field_ident, field)| {
+                if_present(
+                    field,
+                    field.encode_cached(&prost_path, quote!(self.#field_ident)),
+                )
+            })
+            .chain(
+                extension_set
+                    .iter()
+                    .map(|(field_ident, _)| field_ident)
+                    .chain(unknown_fields.iter())
+                    .map(|field_ident| {
+                        quote!(#prost_path::Message::encode_raw(&self.#field_ident, buf);)
+                    }),
+            );
+        quote! {
+            fn encoded_len_cached(&self, cache: &mut #prost_path::LengthCache) -> usize {
+                0 #(+ #encoded_len_cached)*
+            }
+
+            fn encode_raw_cached(
+                &self,
+                buf: &mut impl #prost_path::bytes::BufMut,
+                cache: &mut #prost_path::LengthCache,
+            ) {
+                #(#encode_cached)*
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    // Messages with fields which nest other messages or maps pass the deterministic encoding on
+    // to them. Other messages use the default implementation, which is the regular encoding.
+    let deterministic = if fields
+        .iter()
+        .any(|(_, field)| !matches!(field, Field::Scalar(_)))
+        || extension_set.is_some()
+        || unknown_fields.is_some()
+    {
+        let encode_deterministic = fields
+            .iter()
+            .map(|(field_ident, field)| {
+                if_present(
+                    field,
+                    field.encode_deterministic(&prost_path, quote!(self.#field_ident)),
+                )
+            })
+            .chain(
+                extension_set
+                    .iter()
+                    .map(|(field_ident, _)| field_ident)
+                    .chain(unknown_fields.iter())
+                    .map(|field_ident| {
+                        quote!(#prost_path::Message::encode_raw_deterministic(&self.#field_ident, buf);)
+                    }),
+            );
+        quote! {
+            fn encode_raw_deterministic(&self, buf: &mut impl #prost_path::bytes::BufMut) {
+                #(#encode_deterministic)*
+            }
+        }
+    } else {
+        quote!()
+    };
 
     let merge = fields.iter().map(|(field_ident, field)| {
         let merge = field.merge(&prost_path, quote!(value));
         let tags = field.tags().into_iter().map(|tag| quote!(#tag));
         let tags = Itertools::intersperse(tags, quote!(|));
+        let set_bit = set_bit(field, quote!(self));
 
-        quote! {
-            #(#tags)* => {
-                let mut value = &mut self.#field_ident;
-                #merge.map_err(|mut error| {
-                    error.push(STRUCT_NAME, stringify!(#field_ident));
-                    error
-                })
+        let push_field = quote! {
+            error.push_field(STRUCT_NAME, stringify!(#field_ident), tag);
+        };
+        // Under `Utf8Policy::Bytes`, a string value which is not valid UTF-8 is kept byte for byte
+        // as an unknown field, and the field is left unset.
+        match unknown_fields {
+            Some(ref unknown_fields) if field.is_string() => {
+                let clear = if field.is_repeated_scalar() {
+                    quote!()
+                } else {
+                    let clear = field.clear(quote!(self.#field_ident));
+                    let unset_bit = unset_bit(field, quote!(self));
+                    quote!(#clear; #unset_bit)
+                };
+                quote! {
+                    #(#tags)* => {
+                        #set_bit
+                        let mut value = &mut self.#field_ident;
+                        match #merge {
+                            ::core::result::Result::Err(error) => match error.into_invalid_string_bytes() {
+                                ::core::result::Result::Ok(bytes) => {
+                                    #clear;
+                                    self.#unknown_fields.push(
+                                        tag,
+                                        #prost_path::UnknownFieldValue::LengthDelimited(bytes),
+                                    );
+                                    ::core::result::Result::Ok(())
+                                }
+                                ::core::result::Result::Err(mut error) => {
+                                    #push_field
+                                    ::core::result::Result::Err(error)
+                                }
+                            },
+                            ok => ok,
+                        }
+                    },
+                }
+            }
+            _ => quote! {
+                #(#tags)* => {
+                    #set_bit
+                    let mut value = &mut self.#field_ident;
+                    #merge.map_err(|mut error| {
+                        #push_field
+                        error
+                    })
+                },
             },
         }
     });
 
+    let merge_extension = extension_set.iter().map(|(field_ident, extensions)| {
+        let ranges = extensions.ranges.iter().map(|&(start, end)| {
+            let end = end - 1;
+            quote!(#start..=#end)
+        });
+        quote! {
+            #(#ranges)|* => #prost_path::Message::merge_field(&mut self.#field_ident, tag, wire_type, buf, ctx),
+        }
+    });
+
     let struct_name = if fields.is_empty() {
         quote!()
     } else {
@@ -132,21 +443,112 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
         )
     };
 
+    let skip_field = match unknown_fields {
+        Some(ref field_ident) => quote! {
+            #prost_path::Message::merge_field(&mut self.#field_ident, tag, wire_type, buf, ctx)
+        },
+        None => quote!(#prost_path::encoding::skip_field(wire_type, tag, buf, ctx)),
+    };
+
     let clear = fields
         .iter()
-        .map(|(field_ident, field)| field.clear(quote!(self.#field_ident)));
+        .map(|(field_ident, field)| field.clear(quote!(self.#field_ident)))
+        .chain(
+            extension_set
+                .iter()
+                .map(|(field_ident, _)| field_ident)
+                .chain(unknown_fields.iter())
+                .map(|field_ident| quote!(#prost_path::Message::clear(&mut self.#field_ident))),
+        )
+        .chain(presence_bits.iter().map(|bits| {
+            let field_ident = &bits.ident;
+            let default = bits.default();
+            quote!(self.#field_ident = #default)
+        }));
+
+    // Sensitive fields are cleared, and the other fields clear the sensitive fields of the
+    // messages they hold.
+    let redact = fields.iter().map(|(field_ident, field)| {
+        if redacted.contains(&field_ident.to_string()) {
+            let clear = field.clear(quote!(self.#field_ident));
+            let unset_bit = unset_bit(field, quote!(self));
+            quote!(#clear; #unset_bit)
+        } else {
+            field.redact(&prost_path, quote!(self.#field_ident))
+        }
+    });
+
+    let missing_required_fields = fields.iter().map(|(field_ident, field)| {
+        let proto_name = field_ident.to_string();
+        let proto_name = proto_name.trim_start_matches("r#");
+        field.missing_required_fields(&prost_path, quote!(self.#field_ident), proto_name)
+    });
+
+    let merge_from = fields
+        .iter()
+        .map(|(field_ident, field)| {
+            let merge_from = field.merge_from(
+                &prost_path,
+                quote!(self.#field_ident),
+                quote!(other.#field_ident),
+            );
+            match present(field, quote!(other)) {
+                Some(present) => {
+                    let set_bit = set_bit(field, quote!(self));
+                    quote! {
+                        if #present {
+                            #merge_from
+                            #set_bit
+                        }
+                    }
+                }
+                None => merge_from,
+            }
+        })
+        .chain(
+            extension_set
+                .iter()
+                .map(|(field_ident, _)| field_ident)
+                .chain(unknown_fields.iter())
+                .map(|field_ident| {
+                    quote!(#prost_path::Message::merge_from(&mut self.#field_ident, &other.#field_ident);)
+                }),
+        );
 
     let default = if is_struct {
-        let default = fields.iter().map(|(field_ident, field)| {
-            let value = field.default(&prost_path);
-            quote!(#field_ident: #value,)
-        });
+        let default = fields
+            .iter()
+            .map(|(field_ident, field)| {
+                let value = field.default();
+                quote!(#field_ident: #value,)
+            })
+            .chain(
+                extension_set
+                    .iter()
+                    .map(|(field_ident, _)| field_ident)
+                    .chain(unknown_fields.iter())
+                    .map(|field_ident| quote!(#field_ident: ::core::default::Default::default(),)),
+            )
+            .chain(presence_bits.iter().map(|bits| {
+                let field_ident = &bits.ident;
+                let default = bits.default();
+                quote!(#field_ident: #default,)
+            }));
         quote! {#ident {
             #(#default)*
         }}
     } else {
+        if unknown_fields.is_some() {
+            bail!("unknown_fields is not supported on tuple struct {ident}");
+        }
+        if extension_set.is_some() {
+            bail!("extension_set is not supported on tuple struct {ident}");
+        }
+        if presence_bits.is_some() {
+            bail!("presence_bits is not supported on tuple struct {ident}");
+        }
         let default = fields.iter().map(|(_, field)| {
-            let value = field.default(&prost_path);
+            let value = field.default();
             quote!(#value,)
         });
         quote! {#ident (
@@ -154,9 +556,53 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
         )}
     };
 
+    // The `DEFAULT` constant lists the fields in declaration order, which also works for tuple
+    // structs.
+    let const_default = if const_default {
+        if !generics.params.is_empty() {
+            bail!("const_default is not supported on generic message {ident}");
+        }
+        let sets = extension_set
+            .iter()
+            .map(|(field_ident, _)| quote!(#field_ident: #prost_path::ExtensionSet::new(),))
+            .chain(
+                unknown_fields
+                    .iter()
+                    .map(|field_ident| quote!(#field_ident: #prost_path::UnknownFieldSet::new(),)),
+            )
+            .chain(presence_bits.iter().map(|bits| {
+                let field_ident = &bits.ident;
+                let default = bits.default();
+                quote!(#field_ident: #default,)
+            }));
+        let default_doc = format!(
+            "The default `{ident}`, with the declared defaults of its fields, as a constant."
+        );
+        quote! {
+            impl #ident {
+                #[doc=#default_doc]
+                pub const DEFAULT: Self = #ident {
+                    #(#const_defaults)*
+                    #(#sets)*
+                };
+
+                /// Returns a reference to a static default instance of the message, which is
+                /// never allocated.
+                pub fn default_instance() -> &'static Self {
+                    static DEFAULT: #ident = #ident::DEFAULT;
+                    &DEFAULT
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
     let methods = fields
         .iter()
-        .flat_map(|(field_ident, field)| field.methods(&prost_path, field_ident))
+        .flat_map(|(field_ident, field)| {
+            field.methods(&prost_path, field_ident, presence_bits.as_ref())
+        })
         .collect::<Vec<_>>();
     let methods = if methods.is_empty() {
         quote!()
@@ -182,13 +628,14 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
                 tag: u32,
                 wire_type: #prost_path::encoding::wire_type::WireType,
                 buf: &mut impl #prost_path::bytes::Buf,
-                ctx: #prost_path::encoding::DecodeContext,
+                ctx: #prost_path::encoding::DecodeContext<'_>,
             ) -> ::core::result::Result<(), #prost_path::DecodeError>
             {
                 #struct_name
                 match tag {
                     #(#merge)*
-                    _ => #prost_path::encoding::skip_field(wire_type, tag, buf, ctx),
+                    #(#merge_extension)*
+                    _ => #skip_field,
                 }
             }
 
@@ -197,6 +644,28 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
                 0 #(+ #encoded_len)*
             }
 
+            #cached
+
+            #deterministic
+
+            #[allow(unused_variables)]
+            fn merge_from(&mut self, other: &Self) {
+                #(#merge_from)*
+            }
+
+            #[allow(unused_variables)]
+            fn collect_missing_required_fields(
+                &self,
+                prefix: &str,
+                missing: &mut #prost_path::alloc::vec::Vec<#prost_path::alloc::string::String>,
+            ) {
+                #(#missing_required_fields)*
+            }
+
+            fn redact(&mut self) {
+                #(#redact)*
+            }
+
             fn clear(&mut self) {
                 #(#clear;)*
             }
@@ -207,12 +676,58 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
                 #default
             }
         }
+
+        #const_default
+    };
+    let expanded = match &extension_set {
+        Some((field_ident, extensions)) => {
+            let extendee = &extensions.extendee;
+            quote! {
+                #expanded
+
+                impl #impl_generics #prost_path::Extendable for #ident #ty_generics #where_clause {
+                    const EXTENDEE: &'static str = #extendee;
+
+                    fn extension_set(&self) -> &#prost_path::ExtensionSet {
+                        &self.#field_ident
+                    }
+
+                    fn extension_set_mut(&mut self) -> &mut #prost_path::ExtensionSet {
+                        &mut self.#field_ident
+                    }
+                }
+            }
+        }
+        None => expanded,
     };
+
     let expanded = if skip_debug {
         expanded
+    } else if text_format_debug {
+        if !text_format {
+            bail!("text_format_debug requires the text_format attribute on {ident}");
+        }
+        quote! {
+            #expanded
+
+            impl #impl_generics ::core::fmt::Debug for #ident #ty_generics #where_clause {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    #prost_path::text_format::encoding::debug(self, f)
+                }
+            }
+        }
     } else {
         let debugs = unsorted_fields.iter().map(|(field_ident, field)| {
-            let wrapper = field.debug(&prost_path, quote!(self.#field_ident));
+            let wrapper = if redacted.contains(&field_ident.to_string()) {
+                field::redacted_debug()
+            } else {
+                let debug = field.debug(&prost_path, quote!(self.#field_ident));
+                // Fields with a presence bit are formatted like optional fields.
+                match present(field, quote!(self)) {
+                    Some(present) => quote!(#present.then(|| #debug)),
+                    None => debug,
+                }
+            };
             let call = if is_struct {
                 quote!(builder.field(stringify!(#field_ident), &wrapper))
             } else {
@@ -243,15 +758,472 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
         }
     };
 
+    let expanded = if json {
+        if !is_struct {
+            bail!("json is not supported on tuple struct {ident}");
+        }
+        let json = quote!(#prost_path::json::encoding);
+        let json_name = |field_ident: &TokenStream, proto_name: &str| {
+            let field_ident = field_ident.to_string();
+            json_names
+                .iter()
+                .find(|(ident, _)| *ident == field_ident)
+                .map_or_else(|| field::json_name(proto_name), |(_, name)| name.clone())
+        };
+        let json_encode = fields.iter().map(|(field_ident, field)| {
+            let encode = field.json_encode(&prost_path, quote!(self.#field_ident));
+            if let Field::Oneof(..) = field {
+                return encode;
+            }
+            let encode = if_present(field, encode);
+            let proto_name = field_ident.to_string();
+            let proto_name = proto_name.trim_start_matches("r#");
+            let json_name = json_name(field_ident, proto_name);
+            quote! {
+                {
+                    let name = #json::field_name(options, #json_name, #proto_name);
+                    #encode
+                }
+            }
+        });
+        let (oneofs, non_oneofs): (Vec<_>, Vec<_>) = fields
+            .iter()
+            .partition(|(_, field)| matches!(field, Field::Oneof(..)));
+        let json_merge = non_oneofs.iter().map(|(field_ident, field)| {
+            let merge = field.json_merge(&prost_path, quote!(self.#field_ident));
+            let proto_name = field_ident.to_string();
+            let proto_name = proto_name.trim_start_matches("r#");
+            let json_name = json_name(field_ident, proto_name);
+            let names = if json_name == proto_name {
+                quote!(#proto_name)
+            } else {
+                quote!(#json_name | #proto_name)
+            };
+            let merge = quote! {
+                #merge.map_err(|error| #json::with_field(error, STRUCT_NAME, #proto_name))?
+            };
+            // A `null` value leaves a field with a presence bit unset.
+            match set_bit(field, quote!(self)) {
+                Some(set_bit) => quote! {
+                    #names => {
+                        if !value.is_null() {
+                            #set_bit
+                        }
+                        #merge;
+                    }
+                },
+                None => quote!(#names => #merge,),
+            }
+        });
+        let json_merge_oneofs = oneofs.iter().map(|(field_ident, field)| {
+            let merge = field.json_merge(&prost_path, quote!(self.#field_ident));
+            quote! {
+                if #merge? {
+                    continue;
+                }
+            }
+        });
+        quote! {
+            #expanded
+
+            impl #impl_generics #json::JsonMessage for #ident #ty_generics #where_clause {
+                #[allow(unused_mut, unused_variables)]
+                fn to_json(
+                    &self,
+                    options: &#json::JsonOptions,
+                ) -> ::core::result::Result<#json::JsonValue, #json::JsonError> {
+                    let mut object = #json::JsonObject::new();
+                    #(#json_encode)*
+                    ::core::result::Result::Ok(#json::JsonValue::Object(object))
+                }
+
+                #[allow(unused_variables)]
+                fn merge_json(
+                    &mut self,
+                    value: &#json::JsonValue,
+                    options: &#json::JsonOptions,
+                ) -> ::core::result::Result<(), #json::JsonError> {
+                    const STRUCT_NAME: &'static str = stringify!(#ident);
+                    for (key, value) in #json::object(value, STRUCT_NAME)? {
+                        match key.as_str() {
+                            #(#json_merge)*
+                            _ => {
+                                #(#json_merge_oneofs)*
+                                #json::unknown_field(key, STRUCT_NAME, options)?;
+                            }
+                        }
+                    }
+                    ::core::result::Result::Ok(())
+                }
+            }
+        }
+    } else {
+        expanded
+    };
+
+    let expanded = if text_format {
+        if !is_struct {
+            bail!("text_format is not supported on tuple struct {ident}");
+        }
+        let text = quote!(#prost_path::text_format::encoding);
+        let text_encode = fields.iter().map(|(field_ident, field)| {
+            let encode = field.text_encode(&prost_path, quote!(self.#field_ident));
+            if let Field::Oneof(..) = field {
+                return encode;
+            }
+            let encode = if_present(field, encode);
+            let proto_name = field_ident.to_string();
+            let text_name = field.text_name(proto_name.trim_start_matches("r#"));
+            quote! {
+                {
+                    let name = #text_name;
+                    #encode
+                }
+            }
+        });
+        let (oneofs, non_oneofs): (Vec<_>, Vec<_>) = fields
+            .iter()
+            .partition(|(_, field)| matches!(field, Field::Oneof(..)));
+        let text_merge = non_oneofs.iter().map(|(field_ident, field)| {
+            let merge = field.text_merge(&prost_path, quote!(self.#field_ident));
+            let proto_name = field_ident.to_string();
+            let proto_name = proto_name.trim_start_matches("r#");
+            let text_name = field.text_name(proto_name);
+            // Group fields are also accepted by their lowercase field name.
+            let names = if text_name == proto_name {
+                quote!(#proto_name)
+            } else {
+                quote!(#text_name | #proto_name)
+            };
+            let merge = quote! {
+                #merge.map_err(|error| #text::with_field(error, STRUCT_NAME, #proto_name))?
+            };
+            match set_bit(field, quote!(self)) {
+                Some(set_bit) => quote! {
+                    #names => {
+                        #set_bit
+                        #merge;
+                    }
+                },
+                None => quote!(#names => #merge,),
+            }
+        });
+        let text_merge_oneofs = oneofs.iter().map(|(field_ident, field)| {
+            let merge = field.text_merge(&prost_path, quote!(self.#field_ident));
+            quote! {
+                if #merge.map_err(|error| #text::with_field(error, STRUCT_NAME, name))? {
+                    continue;
+                }
+            }
+        });
+        quote! {
+            #expanded
+
+            impl #impl_generics #text::TextFormatMessage for #ident #ty_generics #where_clause {
+                #[allow(unused_variables)]
+                fn encode_text(&self, message: &mut #text::TextMessage) {
+                    #(#text_encode)*
+                }
+
+                #[allow(unused_variables)]
+                fn merge_text(
+                    &mut self,
+                    message: &#text::TextMessage,
+                ) -> ::core::result::Result<(), #text::TextFormatError> {
+                    const STRUCT_NAME: &'static str = stringify!(#ident);
+                    for (name, value) in message {
+                        match name.as_str() {
+                            #(#text_merge)*
+                            _ => {
+                                #(#text_merge_oneofs)*
+                                return ::core::result::Result::Err(
+                                    #text::unknown_field(name, STRUCT_NAME),
+                                );
+                            }
+                        }
+                    }
+                    ::core::result::Result::Ok(())
+                }
+            }
+        }
+    } else {
+        expanded
+    };
+
+    let expanded = if field_mask {
+        if !is_struct {
+            bail!("field_mask is not supported on tuple struct {ident}");
+        }
+        let field_mask = quote!(#prost_path::field_mask);
+        let (oneofs, non_oneofs): (Vec<_>, Vec<_>) = fields
+            .iter()
+            .partition(|(_, field)| matches!(field, Field::Oneof(..)));
+        let names = non_oneofs
+            .iter()
+            .map(|(field_ident, _)| {
+                let proto_name = field_ident.to_string();
+                proto_name.trim_start_matches("r#").to_owned()
+            })
+            .collect::<Vec<_>>();
+        let oneof_tys = oneofs
+            .iter()
+            .map(|(_, field)| match field {
+                Field::Oneof(oneof) => &oneof.ty,
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        let is_valid_path = non_oneofs.iter().zip(&names).map(|((field_ident, _), name)| {
+            quote! {
+                #name => #field_mask::encoding::is_valid_path(|message: &Self| &message.#field_ident, path)
+            }
+        });
+        let retain = non_oneofs
+            .iter()
+            .zip(&names)
+            .map(|((field_ident, field), name)| {
+                let retain =
+                    quote!(#field_mask::encoding::retain(&mut self.#field_ident, mask.get(#name)););
+                match unset_bit(field, quote!(self)) {
+                    Some(unset_bit) => quote! {
+                        #retain
+                        if mask.get(#name).is_none() {
+                            #unset_bit
+                        }
+                    },
+                    None => retain,
+                }
+            });
+        let retain_oneofs = oneofs.iter().zip(&oneof_tys).map(
+            |((field_ident, _), ty)| quote!(#ty::retain_field_mask(&mut self.#field_ident, mask);),
+        );
+        let merge = non_oneofs.iter().zip(&names).map(|((field_ident, field), name)| {
+            let merge = quote! {
+                #field_mask::encoding::merge(&mut self.#field_ident, &source.#field_ident, mask.get(#name));
+            };
+            match present(field, quote!(source)) {
+                Some(present) => {
+                    let set_bit = set_bit(field, quote!(self));
+                    let unset_bit = unset_bit(field, quote!(self));
+                    quote! {
+                        #merge
+                        if mask.get(#name).is_some() {
+                            if #present {
+                                #set_bit
+                            } else {
+                                #unset_bit
+                            }
+                        }
+                    }
+                }
+                None => merge,
+            }
+        });
+        let merge_oneofs = oneofs.iter().zip(&oneof_tys).map(|((field_ident, _), ty)| {
+            quote!(#ty::merge_field_mask(&mut self.#field_ident, &source.#field_ident, mask);)
+        });
+        let diff = quote!(#prost_path::diff);
+        // Fields with a presence bit are compared as optional values.
+        let diff_fields = non_oneofs.iter().zip(&names).map(|((field_ident, field), name)| {
+            match (present(field, quote!(self)), present(field, quote!(other))) {
+                (Some(present), Some(other_present)) => quote! {
+                    #diff::encoding::diff(
+                        &#present.then(|| ::core::clone::Clone::clone(&self.#field_ident)),
+                        &#other_present.then(|| ::core::clone::Clone::clone(&other.#field_ident)),
+                        #name,
+                        diff,
+                    );
+                },
+                _ => quote!(#diff::encoding::diff(&self.#field_ident, &other.#field_ident, #name, diff);),
+            }
+        });
+        let diff_oneofs = oneofs.iter().zip(&oneof_tys).map(|((field_ident, _), ty)| {
+            quote!(#ty::diff_fields(&self.#field_ident, &other.#field_ident, diff);)
+        });
+        quote! {
+            #expanded
+
+            impl #impl_generics #field_mask::FieldMaskMessage for #ident #ty_generics #where_clause {
+                fn is_valid_path(path: &[&str]) -> bool {
+                    let (name, path) = match path.split_first() {
+                        ::core::option::Option::Some(split) => split,
+                        ::core::option::Option::None => return false,
+                    };
+                    match *name {
+                        #(#is_valid_path,)*
+                        _ => false #(|| #oneof_tys::is_valid_field_mask_path(name, path))*,
+                    }
+                }
+
+                #[allow(unused_variables)]
+                fn retain_fields(&mut self, mask: &#field_mask::FieldMaskTree) {
+                    #(#retain)*
+                    #(#retain_oneofs)*
+                }
+
+                #[allow(unused_variables)]
+                fn merge_fields(&mut self, source: &Self, mask: &#field_mask::FieldMaskTree) {
+                    #(#merge)*
+                    #(#merge_oneofs)*
+                }
+            }
+
+            impl #impl_generics #diff::DiffMessage for #ident #ty_generics #where_clause {
+                #[allow(unused_variables)]
+                fn diff_fields(&self, other: &Self, diff: &mut #diff::MessageDiff) {
+                    #(#diff_fields)*
+                    #(#diff_oneofs)*
+                }
+            }
+        }
+    } else {
+        expanded
+    };
+
+    let expanded = if proto_cmp {
+        let proto_ord = quote!(#prost_path::proto_ord::ProtoOrd);
+        // Each field of a oneof is compared at its own field number.
+        let mut cmps = fields
+            .iter()
+            .flat_map(|(field_ident, field)| match field {
+                Field::Oneof(oneof) => {
+                    let ty = &oneof.ty;
+                    oneof
+                        .tags
+                        .iter()
+                        .map(|&tag| {
+                            let cmp = quote! {
+                                #ty::proto_cmp_field(&self.#field_ident, &other.#field_ident, #tag)
+                            };
+                            (tag, cmp)
+                        })
+                        .collect()
+                }
+                // Fields with a presence bit are compared as optional values.
+                _ => {
+                    let cmp = match (present(field, quote!(self)), present(field, quote!(other))) {
+                        (Some(present), Some(other_present)) => quote! {
+                            #proto_ord::proto_cmp(
+                                &#present.then_some(&self.#field_ident),
+                                &#other_present.then_some(&other.#field_ident),
+                            )
+                        },
+                        _ => quote!(#proto_ord::proto_cmp(&self.#field_ident, &other.#field_ident)),
+                    };
+                    vec![(field.tags()[0], cmp)]
+                }
+            })
+            .collect::<Vec<_>>();
+        cmps.sort_by_key(|(tag, _)| *tag);
+        // Extensions and unknown fields are compared after all known fields.
+        let cmps = cmps.into_iter().map(|(_, cmp)| cmp).chain(
+            extension_set
+                .iter()
+                .map(|(field_ident, _)| field_ident)
+                .chain(unknown_fields.iter())
+                .map(|field_ident| quote!(#proto_ord::proto_cmp(&self.#field_ident, &other.#field_ident))),
+        );
+        quote! {
+            #expanded
+
+            impl #impl_generics #proto_ord for #ident #ty_generics #where_clause {
+                #[allow(deprecated, unused_variables)]
+                fn proto_cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                    #(
+                        let ordering = #cmps;
+                        if ordering.is_ne() {
+                            return ordering;
+                        }
+                    )*
+                    ::core::cmp::Ordering::Equal
+                }
+            }
+        }
+    } else {
+        expanded
+    };
+
+    let expanded = if arbitrary {
+        if !generics.params.is_empty() {
+            bail!("arbitrary is not supported on generic message {ident}");
+        }
+        let arbitrary = quote!(#prost_path::arbitrary);
+        let fields = fields.iter().map(|(field_ident, field)| {
+            let generate = field.arbitrary(&prost_path, quote!(message.#field_ident));
+            // Fields with a presence bit are set by the input, like optional fields.
+            let generate = match set_bit(field, quote!(message)) {
+                Some(set_bit) => quote! {
+                    if u.arbitrary::<bool>()? {
+                        #generate
+                        #set_bit
+                    }
+                },
+                None => generate,
+            };
+            quote!({ #generate })
+        });
+        quote! {
+            #expanded
+
+            impl<'arbitrary> #arbitrary::Arbitrary<'arbitrary> for #ident {
+                fn arbitrary(
+                    u: &mut #arbitrary::Unstructured<'arbitrary>,
+                ) -> #arbitrary::Result<Self> {
+                    let mut message = <Self as ::core::default::Default>::default();
+                    #(#fields)*
+                    ::core::result::Result::Ok(message)
+                }
+            }
+        }
+    } else {
+        expanded
+    };
+
+    // The accessors are generated in declaration order.
+    let accessors = if accessors {
+        if !is_struct {
+            bail!("accessors is not supported on tuple struct {ident}");
+        }
+        let accessors = unsorted_fields.iter().zip(&field_types).map(
+            |((field_ident, field), (field_ty, deprecated))| {
+                field.accessors(
+                    field_ident,
+                    field_ty,
+                    *deprecated,
+                    set_bit(field, quote!(self)),
+                )
+            },
+        );
+        let new_doc = format!("Returns a new `{ident}` with the default values of its fields.");
+        quote! {
+            #[allow(dead_code, deprecated)]
+            impl #impl_generics #ident #ty_generics #where_clause {
+                #[doc=#new_doc]
+                pub fn new() -> Self {
+                    ::core::default::Default::default()
+                }
+
+                #(#accessors)*
+            }
+        }
+    } else {
+        quote!()
+    };
+
     let expanded = quote! {
         #expanded
 
         #methods
+
+        #accessors
     };
 
     Ok(expanded)
 }
 
+/// Derives `prost::Message` for a struct whose fields are annotated with `#[prost(...)]`
+/// attributes.
+///
+/// The attributes are documented in the `derive` module of `prost`.
 #[proc_macro_derive(Message, attributes(prost))]
 pub fn message(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     try_message(input.into()).unwrap().into()
@@ -261,7 +1233,11 @@ fn try_enumeration(input: TokenStream) -> Result<TokenStream, Error> {
     let input: DeriveInput = syn::parse2(input)?;
     let ident = input.ident;
 
-    let Attributes { prost_path, .. } = Attributes::new(input.attrs)?;
+    let Attributes {
+        arbitrary,
+        prost_path,
+        ..
+    } = Attributes::new(input.attrs)?;
 
     let generics = &input.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
@@ -316,12 +1292,20 @@ fn try_enumeration(input: TokenStream) -> Result<TokenStream, Error> {
         .iter()
         .map(|(variant, value, deprecated)| quote!(#value => ::core::result::Result::Ok(#deprecated #ident::#variant)));
 
+    let default_doc = format!("The default `{ident}`, which is its first variant.");
     let is_valid_doc = format!("Returns `true` if `value` is a variant of `{ident}`.");
     let from_i32_doc =
         format!("Converts an `i32` to a `{ident}`, or `None` if `value` is not a valid variant.");
+    let try_from_i32_doc = format!(
+        "Converts an `i32` to a `{ident}`, or an `UnknownEnumValue` error if `value` is not a valid variant."
+    );
 
     let expanded = quote! {
         impl #impl_generics #ident #ty_generics #where_clause {
+            #[doc=#default_doc]
+            #default_deprecated
+            pub const DEFAULT: Self = #ident::#default;
+
             #[doc=#is_valid_doc]
             pub fn is_valid(value: i32) -> bool {
                 match value {
@@ -330,6 +1314,11 @@ fn try_enumeration(input: TokenStream) -> Result<TokenStream, Error> {
                 }
             }
 
+            #[doc=#try_from_i32_doc]
+            pub fn try_from_i32(value: i32) -> ::core::result::Result<#ident, #prost_path::UnknownEnumValue> {
+                <#ident as ::core::convert::TryFrom<i32>>::try_from(value)
+            }
+
             #[deprecated = "Use the TryFrom<i32> implementation instead"]
             #[doc=#from_i32_doc]
             pub fn from_i32(value: i32) -> ::core::option::Option<#ident> {
@@ -362,11 +1351,50 @@ fn try_enumeration(input: TokenStream) -> Result<TokenStream, Error> {
                 }
             }
         }
+
+        impl #impl_generics ::core::convert::From::<#ident #ty_generics> for #prost_path::OpenEnum<#ident #ty_generics> #where_clause {
+            fn from(value: #ident #ty_generics) -> Self {
+                #prost_path::OpenEnum::Known(value)
+            }
+        }
+    };
+
+    let expanded = if arbitrary {
+        if !generics.params.is_empty() {
+            bail!("arbitrary is not supported on generic enumeration {ident}");
+        }
+        let arbitrary = quote!(#prost_path::arbitrary);
+        let count = variants.len();
+        // Arbitrary values are variants of the enumeration, so that arbitrary enumeration fields
+        // hold known values.
+        let variants = variants.iter().enumerate().map(
+            |(index, (variant, _, deprecated))| quote!(#deprecated #index => #ident::#variant),
+        );
+        quote! {
+            #expanded
+
+            impl<'arbitrary> #arbitrary::Arbitrary<'arbitrary> for #ident {
+                fn arbitrary(
+                    u: &mut #arbitrary::Unstructured<'arbitrary>,
+                ) -> #arbitrary::Result<Self> {
+                    ::core::result::Result::Ok(match u.choose_index(#count)? {
+                        #(#variants,)*
+                        _ => ::core::unreachable!(),
+                    })
+                }
+            }
+        }
+    } else {
+        expanded
     };
 
     Ok(expanded)
 }
 
+/// Derives the conversions of a Protobuf enumeration for a fieldless enum with explicit
+/// discriminants.
+///
+/// The attributes are documented in the `derive` module of `prost`.
 #[proc_macro_derive(Enumeration, attributes(prost))]
 pub fn enumeration(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     try_enumeration(input.into()).unwrap().into()
@@ -379,7 +1407,13 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
 
     let Attributes {
         skip_debug,
+        json,
+        arbitrary,
+        text_format,
+        field_mask,
+        proto_cmp,
         prost_path,
+        ..
     } = Attributes::new(input.attrs)?;
 
     let variants = match input.data {
@@ -393,6 +1427,9 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
 
     // Map the variants into 'fields'.
     let mut fields: Vec<(Ident, Field, Option<TokenStream>)> = Vec::new();
+    let mut tys = Vec::new();
+    let mut redacted = Vec::new();
+    let mut json_names = Vec::new();
     for Variant {
         attrs,
         ident: variant_ident,
@@ -415,8 +1452,18 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
         } else {
             None
         };
+        tys.push(variant_fields[0].ty.clone());
+        if field::is_redacted(&attrs)? {
+            redacted.push(variant_ident.clone());
+        }
+        if let Some(json_name) = field::declared_json_name(&attrs)? {
+            json_names.push((variant_ident.clone(), json_name));
+        }
         match Field::new_oneof(attrs)? {
-            Some(field) => fields.push((variant_ident, field, deprecated_attr)),
+            Some(mut field) => {
+                field.set_rust_type(&variant_fields[0].ty);
+                fields.push((variant_ident, field, deprecated_attr))
+            }
             None => bail!("invalid oneof variant: oneof variants may not be ignored"),
         }
     }
@@ -439,6 +1486,11 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
         quote!(#deprecated #ident::#variant_ident(ref value) => { #encode })
     });
 
+    let encode_deterministic = fields.iter().map(|(variant_ident, field, deprecated)| {
+        let encode = field.encode_deterministic(&prost_path, quote!(*value));
+        quote!(#deprecated #ident::#variant_ident(ref value) => { #encode })
+    });
+
     let merge = fields.iter().map(|(variant_ident, field, deprecated)| {
         let tag = field.tags()[0];
         let merge = field.merge(&prost_path, quote!(value));
@@ -459,6 +1511,67 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
         quote!(#deprecated #ident::#variant_ident(ref value) => #encoded_len)
     });
 
+    let missing_required_fields = fields.iter().map(|(variant_ident, field, deprecated)| {
+        let proto_name = field::oneof_field_name(&variant_ident.to_string());
+        match field {
+            Field::Message(..) | Field::Group(..) => quote! {
+                #deprecated
+                #ident::#variant_ident(ref value) => {
+                    #prost_path::encoding::message::collect_missing_required_fields(
+                        value,
+                        prefix,
+                        #proto_name,
+                        missing,
+                    );
+                }
+            },
+            _ => quote!(#deprecated #ident::#variant_ident(..) => ()),
+        }
+    });
+
+    let redact = fields.iter().map(|(variant_ident, field, deprecated)| {
+        if redacted.contains(variant_ident) {
+            return quote! {
+                #deprecated
+                #ident::#variant_ident(ref mut value) => {
+                    *value = ::core::default::Default::default();
+                }
+            };
+        }
+        match field {
+            Field::Message(..) | Field::Group(..) => quote! {
+                #deprecated
+                #ident::#variant_ident(ref mut value) => #prost_path::Message::redact(value)
+            },
+            _ => quote!(#deprecated #ident::#variant_ident(..) => ()),
+        }
+    });
+
+    let merge_from = fields.iter().map(|(variant_ident, field, deprecated)| {
+        let value = match field {
+            Field::Message(..) | Field::Group(..) => quote! {
+                if let ::core::option::Option::Some(#ident::#variant_ident(value)) = field {
+                    #prost_path::Message::merge_from(value, other);
+                } else {
+                    let mut value = ::core::default::Default::default();
+                    #prost_path::Message::merge_from(&mut value, other);
+                    *field = ::core::option::Option::Some(#deprecated #ident::#variant_ident(value));
+                }
+            },
+            _ => quote! {
+                *field = ::core::option::Option::Some(
+                    #deprecated #ident::#variant_ident(::core::clone::Clone::clone(other)),
+                );
+            },
+        };
+        quote! {
+            #deprecated
+            ::core::option::Option::Some(#ident::#variant_ident(other)) => {
+                #value
+            }
+        }
+    });
+
     let expanded = quote! {
         impl #impl_generics #ident #ty_generics #where_clause {
             /// Encodes the message to a buffer.
@@ -468,13 +1581,20 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
                 }
             }
 
+            /// Encodes the message to a buffer with the deterministic encoding.
+            pub fn encode_deterministic(&self, buf: &mut impl #prost_path::bytes::BufMut) {
+                match *self {
+                    #(#encode_deterministic,)*
+                }
+            }
+
             /// Decodes an instance of the message from a buffer, and merges it into self.
             pub fn merge(
                 field: &mut ::core::option::Option<#ident #ty_generics>,
                 tag: u32,
                 wire_type: #prost_path::encoding::wire_type::WireType,
                 buf: &mut impl #prost_path::bytes::Buf,
-                ctx: #prost_path::encoding::DecodeContext,
+                ctx: #prost_path::encoding::DecodeContext<'_>,
             ) -> ::core::result::Result<(), #prost_path::DecodeError>
             {
                 match tag {
@@ -490,14 +1610,361 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
                     #(#encoded_len,)*
                 }
             }
+
+            /// Appends the paths of the missing required fields of the message held by the
+            /// oneof field, prefixed with `prefix`, to `missing`.
+            #[allow(unused_variables, clippy::ptr_arg)]
+            pub fn collect_missing_required_fields(
+                &self,
+                prefix: &str,
+                missing: &mut #prost_path::alloc::vec::Vec<#prost_path::alloc::string::String>,
+            ) {
+                match *self {
+                    #(#missing_required_fields,)*
+                }
+            }
+
+            /// Clears the value of the oneof field if it is sensitive, or the sensitive fields of
+            /// the message held by the oneof field.
+            pub fn redact(&mut self) {
+                match *self {
+                    #(#redact,)*
+                }
+            }
+
+            /// Merges the oneof field `other` into `field`, replacing the set field unless both
+            /// are the same message field, which is merged.
+            pub fn merge_from(
+                field: &mut ::core::option::Option<#ident #ty_generics>,
+                other: &::core::option::Option<#ident #ty_generics>,
+            ) {
+                match other {
+                    #(#merge_from,)*
+                    ::core::option::Option::None => (),
+                }
+            }
+        }
+
+    };
+    let expanded = if json {
+        let json = quote!(#prost_path::json::encoding);
+        let names = fields
+            .iter()
+            .map(|(variant_ident, _, _)| {
+                let proto_name = field::oneof_field_name(&variant_ident.to_string());
+                let json_name = json_names
+                    .iter()
+                    .find(|(ident, _)| ident == variant_ident)
+                    .map_or_else(|| field::json_name(&proto_name), |(_, name)| name.clone());
+                (proto_name, json_name)
+            })
+            .collect::<Vec<_>>();
+        let json_encode = fields.iter().zip(&names).map(
+            |((variant_ident, field, deprecated), (proto_name, json_name))| {
+                let encode = field.json_encode(&prost_path, quote!(*value));
+                quote! {
+                    #deprecated
+                    #ident::#variant_ident(ref value) => {
+                        let name = #json::field_name(options, #json_name, #proto_name);
+                        #encode
+                    }
+                }
+            },
+        );
+        let json_merge = fields.iter().zip(&names).map(
+            |((variant_ident, field, deprecated), (proto_name, json_name))| {
+                let merge = field.json_merge_oneof(&prost_path);
+                let names = if json_name == proto_name {
+                    quote!(#proto_name)
+                } else {
+                    quote!(#json_name | #proto_name)
+                };
+                quote! {
+                    #names => {
+                        if let ::core::option::Option::Some(value) = #merge? {
+                            *field = ::core::option::Option::Some(#deprecated #ident::#variant_ident(value));
+                        }
+                    }
+                }
+            },
+        );
+        quote! {
+            #expanded
+
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// Adds the JSON representation of the oneof field to `object`.
+                pub fn encode_json(
+                    &self,
+                    object: &mut #json::JsonObject,
+                    options: &#json::JsonOptions,
+                ) -> ::core::result::Result<(), #json::JsonError> {
+                    match *self {
+                        #(#json_encode,)*
+                    }
+                    ::core::result::Result::Ok(())
+                }
+
+                /// Merges the JSON member `name` into the oneof field, returning `false` if the
+                /// member does not belong to the oneof.
+                pub fn merge_json(
+                    field: &mut ::core::option::Option<#ident #ty_generics>,
+                    name: &str,
+                    value: &#json::JsonValue,
+                    options: &#json::JsonOptions,
+                ) -> ::core::result::Result<bool, #json::JsonError> {
+                    match name {
+                        #(#json_merge,)*
+                        _ => return ::core::result::Result::Ok(false),
+                    }
+                    ::core::result::Result::Ok(true)
+                }
+            }
+        }
+    } else {
+        expanded
+    };
+    let expanded = if text_format {
+        let text = quote!(#prost_path::text_format::encoding);
+        let text_encode = fields.iter().map(|(variant_ident, field, deprecated)| {
+            let encode = field.text_encode(&prost_path, quote!(*value));
+            let text_name = field.text_name(&field::oneof_field_name(&variant_ident.to_string()));
+            quote! {
+                #deprecated
+                #ident::#variant_ident(ref value) => {
+                    let name = #text_name;
+                    #encode
+                }
+            }
+        });
+        let text_merge = fields.iter().map(|(variant_ident, field, deprecated)| {
+            let merge = field.text_merge_oneof(&prost_path);
+            let proto_name = field::oneof_field_name(&variant_ident.to_string());
+            let text_name = field.text_name(&proto_name);
+            let names = if text_name == proto_name {
+                quote!(#proto_name)
+            } else {
+                quote!(#text_name | #proto_name)
+            };
+            quote! {
+                #names => {
+                    *field = ::core::option::Option::Some(#deprecated #ident::#variant_ident(#merge?));
+                }
+            }
+        });
+        quote! {
+            #expanded
+
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// Appends the text format representation of the oneof field to `message`.
+                pub fn encode_text(&self, message: &mut #text::TextMessage) {
+                    match *self {
+                        #(#text_encode,)*
+                    }
+                }
+
+                /// Merges the text format field `name` into the oneof field, returning `false`
+                /// if the field does not belong to the oneof.
+                pub fn merge_text(
+                    field: &mut ::core::option::Option<#ident #ty_generics>,
+                    name: &str,
+                    value: &#text::TextValue,
+                ) -> ::core::result::Result<bool, #text::TextFormatError> {
+                    match name {
+                        #(#text_merge,)*
+                        _ => return ::core::result::Result::Ok(false),
+                    }
+                    ::core::result::Result::Ok(true)
+                }
+            }
+        }
+    } else {
+        expanded
+    };
+    let expanded = if field_mask {
+        let field_mask = quote!(#prost_path::field_mask);
+        let names = fields
+            .iter()
+            .map(|(variant_ident, _, _)| field::oneof_field_name(&variant_ident.to_string()))
+            .collect::<Vec<_>>();
+        let is_valid_path = tys.iter().zip(&names).map(|(ty, name)| {
+            quote! {
+                #name => path.is_empty() || <#ty as #field_mask::FieldMaskField>::is_valid_path(path)
+            }
+        });
+        let retain = fields
+            .iter()
+            .zip(&names)
+            .map(|((variant_ident, _, deprecated), name)| {
+                quote! {
+                    #deprecated
+                    ::core::option::Option::Some(#ident::#variant_ident(value)) => {
+                        #field_mask::encoding::retain_oneof(value, mask.get(#name))
+                    }
+                }
+            });
+        let merge = fields.iter().zip(&names).map(|((variant_ident, _, deprecated), name)| {
+            quote! {
+                if let ::core::option::Option::Some(node) = mask.get(#name) {
+                    match (&mut *field, source) {
+                        #deprecated
+                        (
+                            ::core::option::Option::Some(#ident::#variant_ident(value)),
+                            ::core::option::Option::Some(#ident::#variant_ident(source)),
+                        ) => #field_mask::encoding::merge(value, source, ::core::option::Option::Some(node)),
+                        #deprecated
+                        (_, ::core::option::Option::Some(#ident::#variant_ident(source))) => {
+                            let mut value = ::core::default::Default::default();
+                            #field_mask::encoding::merge(&mut value, source, ::core::option::Option::Some(node));
+                            *field = ::core::option::Option::Some(#deprecated #ident::#variant_ident(value));
+                        }
+                        #deprecated
+                        (::core::option::Option::Some(#ident::#variant_ident(value)), _) => {
+                            if node.is_leaf() {
+                                *field = ::core::option::Option::None;
+                            } else {
+                                let source = ::core::default::Default::default();
+                                #field_mask::encoding::merge(value, &source, ::core::option::Option::Some(node));
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        });
+        let diff = quote!(#prost_path::diff);
+        let diff_variants =
+            fields
+                .iter()
+                .zip(&names)
+                .map(|((variant_ident, _, deprecated), name)| {
+                    quote! {
+                        #deprecated
+                        (
+                            ::core::option::Option::Some(#ident::#variant_ident(value)),
+                            ::core::option::Option::Some(#ident::#variant_ident(other)),
+                        ) => #diff::encoding::diff(value, other, #name, diff)
+                    }
+                });
+        let variant_names = fields
+            .iter()
+            .zip(&names)
+            .map(|((variant_ident, _, deprecated), name)| {
+                quote!(#deprecated #ident::#variant_ident(_) => #name)
+            });
+        quote! {
+            #expanded
+
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// Returns `true` if the field mask path `name.path` refers to a field of the
+                /// oneof, or to one of its sub-fields.
+                pub fn is_valid_field_mask_path(name: &str, path: &[&str]) -> bool {
+                    match name {
+                        #(#is_valid_path,)*
+                        _ => false,
+                    }
+                }
+
+                /// Clears the oneof field if its set field is not selected by the mask.
+                pub fn retain_field_mask(
+                    field: &mut ::core::option::Option<#ident #ty_generics>,
+                    mask: &#field_mask::FieldMaskTree,
+                ) {
+                    let retain = match field {
+                        #(#retain,)*
+                        ::core::option::Option::None => true,
+                    };
+                    if !retain {
+                        *field = ::core::option::Option::None;
+                    }
+                }
+
+                /// Replaces the fields of the oneof which are selected by the mask with those of
+                /// `source`.
+                pub fn merge_field_mask(
+                    field: &mut ::core::option::Option<#ident #ty_generics>,
+                    source: &::core::option::Option<#ident #ty_generics>,
+                    mask: &#field_mask::FieldMaskTree,
+                ) {
+                    #(#merge)*
+                }
+
+                /// Adds the paths of the fields of the oneof which differ from those of `other`
+                /// to the diff. If different fields of the oneof are set, both differ.
+                pub fn diff_fields(
+                    field: &::core::option::Option<#ident #ty_generics>,
+                    other: &::core::option::Option<#ident #ty_generics>,
+                    diff: &mut #diff::MessageDiff,
+                ) {
+                    match (field, other) {
+                        #(#diff_variants,)*
+                        _ => {
+                            for value in field.iter().chain(other) {
+                                diff.add(match value {
+                                    #(#variant_names,)*
+                                });
+                            }
+                        }
+                    }
+                }
+            }
         }
+    } else {
+        expanded
+    };
+    let expanded = if proto_cmp {
+        let proto_ord = quote!(#prost_path::proto_ord::ProtoOrd);
+        let cmps = fields.iter().map(|(variant_ident, field, deprecated)| {
+            let tag = field.tags()[0];
+            quote! {
+                #deprecated
+                #tag => #proto_ord::proto_cmp(
+                    &match field {
+                        ::core::option::Option::Some(#ident::#variant_ident(value)) => ::core::option::Option::Some(value),
+                        _ => ::core::option::Option::None,
+                    },
+                    &match other {
+                        ::core::option::Option::Some(#ident::#variant_ident(value)) => ::core::option::Option::Some(value),
+                        _ => ::core::option::Option::None,
+                    },
+                )
+            }
+        });
+        quote! {
+            #expanded
 
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// Compares the fields of the oneofs with the field number `tag` in the Protobuf
+                /// order of `prost::proto_ord`, as fields which are set only if they are the
+                /// field of the oneof which is set.
+                #[doc(hidden)]
+                pub fn proto_cmp_field(
+                    field: &::core::option::Option<#ident #ty_generics>,
+                    other: &::core::option::Option<#ident #ty_generics>,
+                    tag: u32,
+                ) -> ::core::cmp::Ordering {
+                    match tag {
+                        #(#cmps,)*
+                        _ => ::core::cmp::Ordering::Equal,
+                    }
+                }
+            }
+        }
+    } else {
+        expanded
     };
     let expanded = if skip_debug {
         expanded
     } else {
         let debug = fields.iter().map(|(variant_ident, field, deprecated)| {
-            let wrapper = field.debug(&prost_path, quote!(*value));
+            let wrapper = if redacted.contains(variant_ident) {
+                let redacted = field::redacted_debug();
+                quote!({
+                    let _ = value;
+                    #redacted
+                })
+            } else {
+                field.debug(&prost_path, quote!(*value))
+            };
             quote!(#deprecated #ident::#variant_ident(ref value) => {
                 let wrapper = #wrapper;
                 f.debug_tuple(stringify!(#variant_ident))
@@ -518,9 +1985,44 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
         }
     };
 
+    let expanded = if arbitrary {
+        if !generics.params.is_empty() {
+            bail!("arbitrary is not supported on generic oneof {ident}");
+        }
+        let arbitrary = quote!(#prost_path::arbitrary);
+        let variants =
+            fields
+                .iter()
+                .enumerate()
+                .map(|(index, (variant_ident, field, deprecated))| {
+                    let value = field.arbitrary_oneof(&prost_path);
+                    quote!(#deprecated #index => #ident::#variant_ident(#value))
+                });
+        let count = fields.len();
+        quote! {
+            #expanded
+
+            impl<'arbitrary> #arbitrary::Arbitrary<'arbitrary> for #ident {
+                fn arbitrary(
+                    u: &mut #arbitrary::Unstructured<'arbitrary>,
+                ) -> #arbitrary::Result<Self> {
+                    ::core::result::Result::Ok(match u.choose_index(#count)? {
+                        #(#variants,)*
+                        _ => ::core::unreachable!(),
+                    })
+                }
+            }
+        }
+    } else {
+        expanded
+    };
+
     Ok(expanded)
 }
 
+/// Derives the encoding of a Protobuf oneof for an enum whose variants each hold one field.
+///
+/// The attributes are documented in the `derive` module of `prost`.
 #[proc_macro_derive(Oneof, attributes(prost))]
 pub fn oneof(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     try_oneof(input.into()).unwrap().into()
@@ -575,6 +2077,14 @@ fn get_prost_path(attrs: &[Meta]) -> Result<Path, Error> {
 
 struct Attributes {
     skip_debug: bool,
+    json: bool,
+    arbitrary: bool,
+    text_format: bool,
+    text_format_debug: bool,
+    field_mask: bool,
+    proto_cmp: bool,
+    const_default: bool,
+    accessors: bool,
     prost_path: Path,
 }
 
@@ -584,10 +2094,42 @@ impl Attributes {
         let skip_debug = attrs.iter().any(|a| a.parse_args::<skip_debug>().is_ok());
 
         let attrs = prost_attrs(attrs)?;
+        let json = attrs
+            .iter()
+            .any(|attr| matches!(attr, Meta::Path(path) if path.is_ident("json")));
+        let arbitrary = attrs
+            .iter()
+            .any(|attr| matches!(attr, Meta::Path(path) if path.is_ident("arbitrary")));
+        let text_format = attrs
+            .iter()
+            .any(|attr| matches!(attr, Meta::Path(path) if path.is_ident("text_format")));
+        let text_format_debug = attrs
+            .iter()
+            .any(|attr| matches!(attr, Meta::Path(path) if path.is_ident("text_format_debug")));
+        let field_mask = attrs
+            .iter()
+            .any(|attr| matches!(attr, Meta::Path(path) if path.is_ident("field_mask")));
+        let proto_cmp = attrs
+            .iter()
+            .any(|attr| matches!(attr, Meta::Path(path) if path.is_ident("proto_cmp")));
+        let const_default = attrs
+            .iter()
+            .any(|attr| matches!(attr, Meta::Path(path) if path.is_ident("const_default")));
+        let accessors = attrs
+            .iter()
+            .any(|attr| matches!(attr, Meta::Path(path) if path.is_ident("accessors")));
         let prost_path = get_prost_path(&attrs)?;
 
         Ok(Self {
             skip_debug,
+            json,
+            arbitrary,
+            text_format,
+            text_format_debug,
+            field_mask,
+            proto_cmp,
+            const_default,
+            accessors,
             prost_path,
         })
     }
@@ -616,6 +2158,183 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_rejects_duplicate_extension_set() {
+        let output = try_message(quote!(
+            struct Invalid {
+                #[prost(extension_set = "test.Invalid", extension_ranges = "100..200")]
+                a: ::prost::ExtensionSet,
+                #[prost(extension_set = "test.Invalid", extension_ranges = "200..300")]
+                b: ::prost::ExtensionSet,
+            }
+        ));
+        assert!(output
+            .expect_err("did not reject duplicate extension_set")
+            .to_string()
+            .starts_with("duplicate extension_set attributes"));
+    }
+
+    #[test]
+    fn test_rejects_duplicate_unknown_fields() {
+        let output = try_message(quote!(
+            struct Invalid {
+                #[prost(unknown_fields)]
+                a: ::prost::UnknownFieldSet,
+                #[prost(unknown_fields)]
+                b: ::prost::UnknownFieldSet,
+            }
+        ));
+        assert!(output
+            .expect_err("did not reject duplicate unknown_fields")
+            .to_string()
+            .starts_with("duplicate unknown_fields attributes"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_presence_bits() {
+        let output = try_message(quote!(
+            struct Invalid {
+                #[prost(int32, optional, presence_bit = "0", tag = "1")]
+                a: i32,
+            }
+        ));
+        assert_eq!(
+            output
+                .expect_err("did not reject presence bit without presence_bits")
+                .to_string(),
+            "field Invalid.a has a presence bit, but Invalid has no presence_bits field"
+        );
+
+        let output = try_message(quote!(
+            struct Invalid {
+                #[prost(int32, presence_bit = "0", tag = "1")]
+                a: i32,
+                #[prost(presence_bits)]
+                bits: [u32; 1],
+            }
+        ));
+        assert_eq!(
+            format!(
+                "{:#}",
+                output.expect_err("did not reject presence bit on field without presence")
+            ),
+            "invalid message field Invalid.a: presence_bit attribute may only be applied to optional fields"
+        );
+
+        let output = try_message(quote!(
+            struct Invalid {
+                #[prost(presence_bits)]
+                bits: u32,
+            }
+        ));
+        assert_eq!(
+            format!(
+                "{:#}",
+                output.expect_err("did not reject presence_bits which are not an array")
+            ),
+            "invalid message field Invalid.bits: presence_bits field must be an array of u32"
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_heapless_fields() {
+        let output = try_message(quote!(
+            struct Invalid {
+                #[prost(string = "heapless", tag = "1")]
+                a: ::prost::heapless::String<8>,
+            }
+        ));
+        assert!(format!(
+            "{:#}",
+            output.expect_err("did not reject heapless field without max_len")
+        )
+        .contains("require a max_len attribute"));
+
+        let output = try_message(quote!(
+            struct Invalid {
+                #[prost(string, max_len = "8", tag = "1")]
+                a: String,
+            }
+        ));
+        assert!(format!(
+            "{:#}",
+            output.expect_err("did not reject max_len on non-heapless field")
+        )
+        .contains("max_len attribute may only be applied"));
+
+        let output = try_message(quote!(
+            struct Invalid {
+                #[prost(string = "heapless", max_len = "2", tag = "1", default = "abc")]
+                a: ::prost::heapless::String<2>,
+            }
+        ));
+        assert!(format!(
+            "{:#}",
+            output.expect_err("did not reject default exceeding max_len")
+        )
+        .contains("default value exceeds the max_len of 2"));
+    }
+
+    #[test]
+    fn test_rejects_fields_without_const_default() {
+        let output = try_message(quote!(
+            #[prost(const_default)]
+            struct Invalid {
+                #[prost(map = "string, string", tag = "1")]
+                a: ::std::collections::HashMap<String, String>,
+            }
+        ));
+        assert!(format!(
+            "{:#}",
+            output.expect_err("did not reject hash map field with const_default")
+        )
+        .contains("only btree_map fields have a constant default"));
+
+        let output = try_message(quote!(
+            #[prost(const_default)]
+            struct Invalid {
+                #[prost(string, tag = "1", default = "abc")]
+                a: String,
+            }
+        ));
+        assert!(format!(
+            "{:#}",
+            output.expect_err("did not reject string default with const_default")
+        )
+        .contains("need `bytes` types for a constant default"));
+    }
+
+    #[test]
+    fn test_rejects_accessors_on_tuple_struct() {
+        let output = try_message(quote!(
+            #[prost(accessors)]
+            struct Invalid(#[prost(string, tag = "1")] String);
+        ));
+        assert_eq!(
+            output
+                .expect_err("did not reject accessors on tuple struct")
+                .to_string(),
+            "accessors is not supported on tuple struct Invalid"
+        );
+    }
+
+    #[test]
+    fn test_rejects_text_format_debug_without_text_format() {
+        let output = try_message(quote!(
+            #[prost(text_format_debug)]
+            struct Invalid {
+                #[prost(string, tag = "1")]
+                a: String,
+            }
+        ));
+        assert_eq!(
+            output
+                .expect_err("did not reject text_format_debug without text_format")
+                .to_string(),
+            "text_format_debug requires the text_format attribute on Invalid"
+        );
+    }
+
     #[test]
     fn test_rejects_colliding_oneof_variants() {
         let output = try_oneof(quote!(
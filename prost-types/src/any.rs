@@ -1,5 +1,7 @@
 use super::*;
 
+use prost::alloc::boxed::Box;
+
 impl Any {
     /// Serialize the given message type `M` as [`Any`].
     pub fn from_msg<M>(msg: &M) -> Result<Self, EncodeError>
@@ -35,6 +37,40 @@ impl Any {
             expected_type_url,
         ))
     }
+
+    /// Serialize a message whose type is only known at runtime as [`Any`].
+    ///
+    /// The type URL is looked up in the registry, and defaults to
+    /// `type.googleapis.com/<full name>` for types which are not in the registry.
+    pub fn from_msg_with_registry<M, R>(msg: &M, registry: &R) -> Result<Self, EncodeError>
+    where
+        M: AnyMessage + ?Sized,
+        R: TypeRegistry + ?Sized,
+    {
+        let full_name = msg.message_name();
+        let type_url = registry
+            .type_url(&full_name)
+            .unwrap_or_else(|| format!("type.googleapis.com/{full_name}"));
+        Ok(Any {
+            type_url,
+            value: msg.encode_message(),
+        })
+    }
+
+    /// Decode a message of the type named by the type URL from [`Any`], looking the type up in
+    /// the registry.
+    ///
+    /// Only the last segment of the type URL, which is the fully qualified name of the type, is
+    /// used to find the type, so any domain is accepted. The result can be downcast to the
+    /// concrete message type.
+    pub fn to_msg_dyn<R>(&self, registry: &R) -> Result<Box<dyn AnyMessage>, DecodeError>
+    where
+        R: TypeRegistry + ?Sized,
+    {
+        TypeUrl::new(&self.type_url)
+            .and_then(|type_url| registry.decode(type_url.full_name, &self.value))
+            .unwrap_or_else(|| Err(DecodeError::new_unknown_type_url(&self.type_url)))
+    }
 }
 
 impl Name for Any {
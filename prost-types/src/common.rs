@@ -0,0 +1,336 @@
+use super::*;
+use crate::r#type::*;
+
+/// The range of valid years of a [`Date`], excluding 0 for a date without a year.
+const DATE_YEAR_MAX: i32 = 9999;
+
+/// A year in which February has 29 days, to validate the dates which have no year.
+const LEAP_YEAR: i64 = 2000;
+
+impl Date {
+    /// Creates a full date, failing if it is not a valid date of the Gregorian calendar in the
+    /// years 1 to 9999.
+    pub fn new(year: i32, month: i32, day: i32) -> Result<Date, DateError> {
+        let date = Date { year, month, day };
+        if year == 0 || month == 0 || day == 0 || !date.is_valid() {
+            return Err(DateError::InvalidDate);
+        }
+        Ok(date)
+    }
+
+    /// Returns `true` if the date is a valid whole or partial date.
+    ///
+    /// A valid date is one of the following, where the month and day must exist in the given
+    /// year, or in a leap year if the year is 0:
+    ///
+    /// * A full date, with non-zero year, month, and day values.
+    /// * A month and day, with a zero year.
+    /// * A year on its own, with a zero month and a zero day.
+    /// * A year and month, with a zero day.
+    pub fn is_valid(&self) -> bool {
+        if !(0..=DATE_YEAR_MAX).contains(&self.year)
+            || !(0..=12).contains(&self.month)
+            || !(0..=31).contains(&self.day)
+        {
+            return false;
+        }
+        match (self.year, self.month, self.day) {
+            // A date with neither a year nor a month, or a day without a month.
+            (0, 0, _) | (_, 0, 1..) => false,
+            (_, _, 0) => true,
+            (year, month, day) => {
+                let year = if year == 0 {
+                    LEAP_YEAR
+                } else {
+                    i64::from(year)
+                };
+                day <= i32::from(datetime::days_in_month(year, month as u8))
+            }
+        }
+    }
+
+    /// Returns `true` if the date is a valid full date, with non-zero year, month, and day values.
+    pub fn is_full_date(&self) -> bool {
+        self.year != 0 && self.month != 0 && self.day != 0 && self.is_valid()
+    }
+}
+
+impl TimeOfDay {
+    /// Returns `true` if the time of day is valid.
+    ///
+    /// The hours must be from 0 to 23, the minutes from 0 to 59, the seconds from 0 to 60, to
+    /// allow leap seconds, and the nanoseconds from 0 to 999,999,999. The time "24:00:00" is
+    /// also valid, for scenarios like business closing time.
+    pub fn is_valid(&self) -> bool {
+        if self.hours == 24 {
+            return self.minutes == 0 && self.seconds == 0 && self.nanos == 0;
+        }
+        (0..24).contains(&self.hours)
+            && (0..60).contains(&self.minutes)
+            && (0..=60).contains(&self.seconds)
+            && (0..=NANOS_MAX).contains(&self.nanos)
+    }
+}
+
+impl LatLng {
+    /// Returns `true` if the latitude is in the range [-90.0, +90.0] and the longitude is in the
+    /// range [-180.0, +180.0].
+    pub fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.latitude) && (-180.0..=180.0).contains(&self.longitude)
+    }
+}
+
+impl Money {
+    /// Returns `true` if the currency code is three uppercase letters, as defined in ISO 4217,
+    /// the nanos are from -999,999,999 to +999,999,999, and the units and nanos do not have
+    /// opposite signs.
+    pub fn is_valid(&self) -> bool {
+        self.currency_code.len() == 3
+            && self.currency_code.bytes().all(|b| b.is_ascii_uppercase())
+            && (-NANOS_MAX..=NANOS_MAX).contains(&self.nanos)
+            && !(self.units > 0 && self.nanos < 0)
+            && !(self.units < 0 && self.nanos > 0)
+    }
+}
+
+/// A date handling error.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DateError {
+    /// Indicates that a [`Date`] is not a valid date.
+    InvalidDate,
+
+    /// Indicates failure to convert a [`Date`] which is not a full date, i.e. which has a zero
+    /// year, month, or day.
+    PartialDate,
+
+    /// Indicates failure to convert a date outside of the years 1 to 9999 to a [`Date`].
+    OutOfRange,
+}
+
+impl fmt::Display for DateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateError::InvalidDate => write!(f, "invalid date"),
+            DateError::PartialDate => write!(f, "failed to convert partial date"),
+            DateError::OutOfRange => write!(f, "failed to convert date out of range"),
+        }
+    }
+}
+
+impl core::error::Error for DateError {}
+
+impl Date {
+    /// Returns the year, month, and day of a full date, for the conversions to other date types.
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    fn to_full_date(self) -> Result<(i32, u32, u32), DateError> {
+        if !self.is_valid() {
+            return Err(DateError::InvalidDate);
+        }
+        if !self.is_full_date() {
+            return Err(DateError::PartialDate);
+        }
+        Ok((self.year, self.month as u32, self.day as u32))
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono {
+    use ::chrono::{Datelike, NaiveDate};
+
+    use super::*;
+
+    /// Converts a `chrono::NaiveDate`, failing if it is not in the years 1 to 9999.
+    impl TryFrom<NaiveDate> for Date {
+        type Error = DateError;
+
+        fn try_from(value: NaiveDate) -> Result<Date, DateError> {
+            if !(1..=DATE_YEAR_MAX).contains(&value.year()) {
+                return Err(DateError::OutOfRange);
+            }
+            Ok(Date {
+                year: value.year(),
+                month: value.month() as i32,
+                day: value.day() as i32,
+            })
+        }
+    }
+
+    /// Converts to a `chrono::NaiveDate`, failing if the date is not a valid full date.
+    impl TryFrom<Date> for NaiveDate {
+        type Error = DateError;
+
+        fn try_from(value: Date) -> Result<NaiveDate, DateError> {
+            let (year, month, day) = value.to_full_date()?;
+            NaiveDate::from_ymd_opt(year, month, day).ok_or(DateError::InvalidDate)
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_crate {
+    use ::time::Month;
+
+    use super::*;
+
+    /// Converts a `time::Date`, failing if it is not in the years 1 to 9999.
+    impl TryFrom<::time::Date> for Date {
+        type Error = DateError;
+
+        fn try_from(value: ::time::Date) -> Result<Date, DateError> {
+            if !(1..=DATE_YEAR_MAX).contains(&value.year()) {
+                return Err(DateError::OutOfRange);
+            }
+            Ok(Date {
+                year: value.year(),
+                month: i32::from(u8::from(value.month())),
+                day: i32::from(value.day()),
+            })
+        }
+    }
+
+    /// Converts to a `time::Date`, failing if the date is not a valid full date.
+    impl TryFrom<Date> for ::time::Date {
+        type Error = DateError;
+
+        fn try_from(value: Date) -> Result<::time::Date, DateError> {
+            let (year, month, day) = value.to_full_date()?;
+            let month = Month::try_from(month as u8).map_err(|_| DateError::InvalidDate)?;
+            ::time::Date::from_calendar_date(year, month, day as u8)
+                .map_err(|_| DateError::InvalidDate)
+        }
+    }
+}
+
+macro_rules! impl_type_name {
+    ($t:ty, $name:literal) => {
+        impl Name for $t {
+            const PACKAGE: &'static str = TYPE_PACKAGE;
+            const NAME: &'static str = $name;
+
+            fn type_url() -> String {
+                type_url_for::<Self>()
+            }
+        }
+    };
+}
+
+impl_type_name!(Date, "Date");
+impl_type_name!(TimeOfDay, "TimeOfDay");
+impl_type_name!(LatLng, "LatLng");
+impl_type_name!(Money, "Money");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_is_valid() {
+        assert!(Date::new(2024, 2, 29).is_ok());
+        assert_eq!(Date::new(2023, 2, 29), Err(DateError::InvalidDate));
+        assert_eq!(Date::new(1900, 2, 29), Err(DateError::InvalidDate));
+        assert_eq!(Date::new(2023, 4, 31), Err(DateError::InvalidDate));
+        assert_eq!(Date::new(0, 4, 30), Err(DateError::InvalidDate));
+
+        let partial = |year, month, day| Date { year, month, day };
+        // A month and day, e.g. an anniversary, which may be a leap day.
+        assert!(partial(0, 2, 29).is_valid());
+        assert!(!partial(0, 2, 30).is_valid());
+        // A year on its own, and a year and month.
+        assert!(partial(2024, 0, 0).is_valid());
+        assert!(partial(2024, 12, 0).is_valid());
+        assert!(!partial(2024, 12, 0).is_full_date());
+        // A day without a month, or neither a year nor a month.
+        assert!(!partial(2024, 0, 5).is_valid());
+        assert!(!partial(0, 0, 5).is_valid());
+        assert!(!Date::default().is_valid());
+        // Out of range fields.
+        assert!(!partial(10000, 1, 1).is_valid());
+        assert!(!partial(-1, 1, 1).is_valid());
+        assert!(!partial(2024, 13, 1).is_valid());
+        assert!(!partial(2024, 1, 32).is_valid());
+    }
+
+    #[test]
+    fn time_of_day_is_valid() {
+        let time = |hours, minutes, seconds, nanos| TimeOfDay {
+            hours,
+            minutes,
+            seconds,
+            nanos,
+        };
+        assert!(time(0, 0, 0, 0).is_valid());
+        assert!(time(23, 59, 60, NANOS_MAX).is_valid());
+        assert!(time(24, 0, 0, 0).is_valid());
+        assert!(!time(24, 0, 0, 1).is_valid());
+        assert!(!time(12, 60, 0, 0).is_valid());
+        assert!(!time(12, 0, 0, -1).is_valid());
+    }
+
+    #[test]
+    fn lat_lng_is_valid() {
+        let lat_lng = |latitude, longitude| LatLng {
+            latitude,
+            longitude,
+        };
+        assert!(lat_lng(-90.0, 180.0).is_valid());
+        assert!(!lat_lng(90.5, 0.0).is_valid());
+        assert!(!lat_lng(0.0, -180.5).is_valid());
+        assert!(!lat_lng(f64::NAN, 0.0).is_valid());
+    }
+
+    #[test]
+    fn money_is_valid() {
+        let money = |currency_code: &str, units, nanos| Money {
+            currency_code: currency_code.into(),
+            units,
+            nanos,
+        };
+        assert!(money("USD", -1, -750_000_000).is_valid());
+        assert!(money("EUR", 0, 500_000_000).is_valid());
+        assert!(money("EUR", 0, -500_000_000).is_valid());
+        assert!(!money("USD", 1, -750_000_000).is_valid());
+        assert!(!money("USD", -1, 750_000_000).is_valid());
+        assert!(!money("USD", 0, NANOS_PER_SECOND).is_valid());
+        assert!(!money("usd", 1, 0).is_valid());
+        assert!(!money("", 1, 0).is_valid());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn date_chrono_conversions() {
+        let date = Date::new(2024, 2, 29).unwrap();
+        let naive = ::chrono::NaiveDate::try_from(date).unwrap();
+        assert_eq!(
+            naive,
+            ::chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()
+        );
+        assert_eq!(Date::try_from(naive), Ok(date));
+
+        let partial = Date {
+            year: 2024,
+            month: 2,
+            day: 0,
+        };
+        assert_eq!(
+            ::chrono::NaiveDate::try_from(partial),
+            Err(DateError::PartialDate)
+        );
+        assert_eq!(
+            Date::try_from(::chrono::NaiveDate::from_ymd_opt(10000, 1, 1).unwrap()),
+            Err(DateError::OutOfRange)
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn date_time_conversions() {
+        let date = Date::new(1999, 12, 31).unwrap();
+        let time_date = ::time::Date::try_from(date).unwrap();
+        assert_eq!(
+            time_date,
+            ::time::Date::from_calendar_date(1999, ::time::Month::December, 31).unwrap()
+        );
+        assert_eq!(Date::try_from(time_date), Ok(date));
+    }
+}
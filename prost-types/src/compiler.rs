@@ -2,6 +2,7 @@
 /// The version number of protocol compiler.
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[prost(field_mask)]
 pub struct Version {
     #[prost(int32, optional, tag = "1")]
     pub major: ::core::option::Option<i32>,
@@ -17,6 +18,7 @@ pub struct Version {
 /// An encoded CodeGeneratorRequest is written to the plugin's stdin.
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[derive(Clone, PartialEq, ::prost::Message)]
+#[prost(field_mask)]
 pub struct CodeGeneratorRequest {
     /// The .proto files that were explicitly listed on the command-line.  The
     /// code generator should generate code only for these files.  Each file's
@@ -30,6 +32,11 @@ pub struct CodeGeneratorRequest {
     /// they import.  The files will appear in topological order, so each file
     /// appears before any file that imports it.
     ///
+    /// Note: the files listed in files_to_generate will include runtime-retention
+    /// options only, but all other files will include source-retention options.
+    /// The source_file_descriptors field below is available in case you need
+    /// source-retention options for files_to_generate.
+    ///
     /// protoc guarantees that all proto_files will be written after
     /// the fields above, even though this is not technically guaranteed by the
     /// protobuf wire format.  This theoretically could allow a plugin to stream
@@ -42,6 +49,11 @@ pub struct CodeGeneratorRequest {
     /// fully qualified.
     #[prost(message, repeated, tag = "15")]
     pub proto_file: ::prost::alloc::vec::Vec<super::FileDescriptorProto>,
+    /// File descriptors with all options, including source-retention options.
+    /// These descriptors are only provided for the files listed in
+    /// files_to_generate.
+    #[prost(message, repeated, tag = "17")]
+    pub source_file_descriptors: ::prost::alloc::vec::Vec<super::FileDescriptorProto>,
     /// The version number of protocol compiler.
     #[prost(message, optional, tag = "3")]
     pub compiler_version: ::core::option::Option<Version>,
@@ -49,6 +61,7 @@ pub struct CodeGeneratorRequest {
 /// The plugin writes an encoded CodeGeneratorResponse to stdout.
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[derive(Clone, PartialEq, ::prost::Message)]
+#[prost(field_mask)]
 pub struct CodeGeneratorResponse {
     /// Error message.  If non-empty, code generation failed.  The plugin process
     /// should exit with status code zero even if it reports an error in this way.
@@ -64,6 +77,18 @@ pub struct CodeGeneratorResponse {
     /// This is a bitwise "or" of values from the Feature enum.
     #[prost(uint64, optional, tag = "2")]
     pub supported_features: ::core::option::Option<u64>,
+    /// The minimum edition this plugin supports.  This will be treated as an
+    /// Edition enum, but we want to allow unknown values.  It should be specified
+    /// according the edition enum value, *not* the edition number.  Only takes
+    /// effect for plugins that have FEATURE_SUPPORTS_EDITIONS set.
+    #[prost(int32, optional, tag = "3")]
+    pub minimum_edition: ::core::option::Option<i32>,
+    /// The maximum edition this plugin supports.  This will be treated as an
+    /// Edition enum, but we want to allow unknown values.  It should be specified
+    /// according the edition enum value, *not* the edition number.  Only takes
+    /// effect for plugins that have FEATURE_SUPPORTS_EDITIONS set.
+    #[prost(int32, optional, tag = "4")]
+    pub maximum_edition: ::core::option::Option<i32>,
     #[prost(message, repeated, tag = "15")]
     pub file: ::prost::alloc::vec::Vec<code_generator_response::File>,
 }
@@ -72,6 +97,7 @@ pub mod code_generator_response {
     /// Represents a single generated file.
     #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
     #[derive(Clone, PartialEq, ::prost::Message)]
+    #[prost(field_mask)]
     pub struct File {
         /// The file name, relative to the output directory.  The name must not
         /// contain "." or ".." components and must be relative, not be absolute (so,
@@ -151,6 +177,7 @@ pub mod code_generator_response {
     pub enum Feature {
         None = 0,
         Proto3Optional = 1,
+        SupportsEditions = 2,
     }
     impl Feature {
         /// String value of the enum field names used in the ProtoBuf definition.
@@ -161,6 +188,7 @@ pub mod code_generator_response {
             match self {
                 Self::None => "FEATURE_NONE",
                 Self::Proto3Optional => "FEATURE_PROTO3_OPTIONAL",
+                Self::SupportsEditions => "FEATURE_SUPPORTS_EDITIONS",
             }
         }
         /// Creates an enum from field names used in the ProtoBuf definition.
@@ -168,6 +196,7 @@ pub mod code_generator_response {
             match value {
                 "FEATURE_NONE" => Some(Self::None),
                 "FEATURE_PROTO3_OPTIONAL" => Some(Self::Proto3Optional),
+                "FEATURE_SUPPORTS_EDITIONS" => Some(Self::SupportsEditions),
                 _ => None,
             }
         }
@@ -60,3 +60,163 @@ impl From<BTreeMap<String, Value>> for Value {
         value::Kind::StructValue(crate::protobuf::Struct { fields: value }).into()
     }
 }
+
+#[cfg(feature = "serde_json")]
+mod serde_json {
+    use super::*;
+    use crate::protobuf::{ListValue, NullValue, Struct};
+
+    use ::serde_json::{Map, Number};
+
+    /// JSON numbers are converted to `f64`, so integers beyond 2^53 lose precision.
+    impl From<::serde_json::Value> for Value {
+        fn from(value: ::serde_json::Value) -> Self {
+            match value {
+                ::serde_json::Value::Null => value::Kind::NullValue(NullValue::NullValue as i32),
+                ::serde_json::Value::Bool(boolean) => value::Kind::BoolValue(boolean),
+                ::serde_json::Value::Number(number) => {
+                    value::Kind::NumberValue(number.as_f64().unwrap_or(f64::NAN))
+                }
+                ::serde_json::Value::String(string) => value::Kind::StringValue(string),
+                ::serde_json::Value::Array(values) => value::Kind::ListValue(values.into()),
+                ::serde_json::Value::Object(fields) => {
+                    value::Kind::StructValue(Struct::from_json_map(fields))
+                }
+            }
+            .into()
+        }
+    }
+
+    /// The largest integer up to which all integers are exactly representable as `f64`.
+    const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+    /// Converts integral numbers which are exactly representable to JSON integers, so that they
+    /// round-trip unchanged.
+    fn json_number(number: f64) -> Option<Number> {
+        if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&number)
+            && number as i64 as f64 == number
+        {
+            Some(Number::from(number as i64))
+        } else {
+            Number::from_f64(number)
+        }
+    }
+
+    /// A value without a kind, and numbers which are not finite, are converted to JSON `null`.
+    /// Integral numbers are converted to JSON integers.
+    impl From<Value> for ::serde_json::Value {
+        fn from(value: Value) -> Self {
+            match value.kind {
+                None | Some(value::Kind::NullValue(_)) => ::serde_json::Value::Null,
+                Some(value::Kind::NumberValue(number)) => json_number(number)
+                    .map_or(::serde_json::Value::Null, ::serde_json::Value::Number),
+                Some(value::Kind::StringValue(string)) => ::serde_json::Value::String(string),
+                Some(value::Kind::BoolValue(boolean)) => ::serde_json::Value::Bool(boolean),
+                Some(value::Kind::StructValue(fields)) => {
+                    ::serde_json::Value::Object(fields.into())
+                }
+                Some(value::Kind::ListValue(list)) => ::serde_json::Value::Array(list.into()),
+            }
+        }
+    }
+
+    impl Struct {
+        /// Converts a JSON object to a `Struct`.
+        pub fn from_json_map(map: Map<String, ::serde_json::Value>) -> Struct {
+            Struct {
+                fields: map
+                    .into_iter()
+                    .map(|(name, value)| (name, value.into()))
+                    .collect(),
+            }
+        }
+    }
+
+    impl From<Map<String, ::serde_json::Value>> for Struct {
+        fn from(map: Map<String, ::serde_json::Value>) -> Self {
+            Struct::from_json_map(map)
+        }
+    }
+
+    impl From<Struct> for Map<String, ::serde_json::Value> {
+        fn from(value: Struct) -> Self {
+            value
+                .fields
+                .into_iter()
+                .map(|(name, value)| (name, value.into()))
+                .collect()
+        }
+    }
+
+    impl From<Vec<::serde_json::Value>> for ListValue {
+        fn from(values: Vec<::serde_json::Value>) -> Self {
+            ListValue {
+                values: values.into_iter().map(Into::into).collect(),
+            }
+        }
+    }
+
+    impl From<ListValue> for Vec<::serde_json::Value> {
+        fn from(value: ListValue) -> Self {
+            value.values.into_iter().map(Into::into).collect()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use ::serde_json::json;
+        use prost::alloc::vec;
+
+        #[test]
+        fn value_roundtrip() {
+            let json = json!({
+                "name": "prost",
+                "stars": 4000,
+                "ratio": 0.5,
+                "tags": ["protobuf", null, true],
+                "owner": { "login": "tokio-rs" },
+            });
+            let value = Value::from(json.clone());
+            let fields = match value.kind {
+                Some(value::Kind::StructValue(ref object)) => &object.fields,
+                _ => panic!("expected struct: {value:?}"),
+            };
+            assert_eq!(fields["stars"], Value::from(4000));
+            assert_eq!(
+                fields["tags"],
+                Value::from(vec![
+                    Value::from("protobuf"),
+                    value::Kind::NullValue(NullValue::NullValue as i32).into(),
+                    Value::from(true),
+                ])
+            );
+            assert_eq!(::serde_json::Value::from(value), json);
+        }
+
+        #[test]
+        fn struct_from_json_map() {
+            let map = match json!({ "a": 1, "b": { "c": "d" } }) {
+                ::serde_json::Value::Object(map) => map,
+                _ => unreachable!(),
+            };
+            let object = Struct::from_json_map(map.clone());
+            assert_eq!(object.fields["a"], Value::from(1));
+            assert_eq!(Map::from(object.clone()), map);
+            assert_eq!(Struct::from(map), object);
+        }
+
+        #[test]
+        fn non_finite_numbers() {
+            assert_eq!(
+                ::serde_json::Value::from(Value::from(f64::NAN)),
+                ::serde_json::Value::Null
+            );
+            assert_eq!(
+                ::serde_json::Value::from(Value::default()),
+                ::serde_json::Value::Null
+            );
+        }
+    }
+}
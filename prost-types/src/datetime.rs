@@ -180,7 +180,7 @@ impl From<Timestamp> for DateTime {
 }
 
 /// Returns the number of days in the month.
-fn days_in_month(year: i64, month: u8) -> u8 {
+pub(crate) fn days_in_month(year: i64, month: u8) -> u8 {
     const DAYS_IN_MONTH: [u8; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
     let (_, is_leap) = year_to_seconds(year);
     DAYS_IN_MONTH[usize::from(month - 1)] + u8::from(is_leap && month == 2)
@@ -571,6 +571,68 @@ pub(crate) fn parse_duration(s: &str) -> Option<Duration> {
     Some(Duration { seconds, nanos })
 }
 
+/// Parses a timestamp in the exact string format of the [Protobuf JSON mapping][1]: an RFC 3339
+/// date and time with an uppercase `T`, up to nine fractional digits, and either `Z` or an offset
+/// of the form `+HH:MM`.
+///
+/// The timestamp is not checked to be within the range of valid timestamps.
+///
+/// [1]: https://protobuf.dev/programming-guides/proto3/#json
+pub(crate) fn parse_json_timestamp(s: &str) -> Option<Timestamp> {
+    ensure!(s.is_ascii() && s.len() >= 20);
+
+    let (date_time, rest) = s.split_at(19);
+    ensure!(matches_pattern(date_time, b"dddd-dd-ddTdd:dd:dd"));
+
+    let offset = skip_fraction(rest)?;
+    ensure!(
+        offset == "Z"
+            || (matches!(offset.as_bytes().first(), Some(b'+' | b'-'))
+                && matches_pattern(&offset[1..], b"dd:dd"))
+    );
+
+    parse_timestamp(s)
+}
+
+/// Parses a duration in the exact string format of the [Protobuf JSON mapping][1]: an optional
+/// `-`, the seconds, up to nine fractional digits, and an `s` suffix.
+///
+/// The duration is not checked to be within the range of valid durations.
+///
+/// [1]: https://protobuf.dev/programming-guides/proto3/#json
+pub(crate) fn parse_json_duration(s: &str) -> Option<Duration> {
+    ensure!(s.is_ascii());
+
+    let (seconds, rest) = parse_digits(parse_char(s, b'-').unwrap_or(s));
+    ensure!(!seconds.is_empty());
+    ensure!(skip_fraction(rest)? == "s");
+
+    parse_duration(s)
+}
+
+/// Returns `true` if ASCII string `s` matches `pattern`, in which `d` matches any digit and every
+/// other character matches itself.
+fn matches_pattern(s: &str, pattern: &[u8]) -> bool {
+    s.len() == pattern.len()
+        && s.bytes().zip(pattern).all(|(c, &p)| match p {
+            b'd' => c.is_ascii_digit(),
+            _ => c == p,
+        })
+}
+
+/// Skips an optional fraction of one to nine digits from ASCII string `s`, returning the remaining
+/// string.
+fn skip_fraction(s: &str) -> Option<&str> {
+    match parse_char(s, b'.') {
+        Some(s) => {
+            let (digits, s) = parse_digits(s);
+            ensure!((1..=9).contains(&digits.len()));
+            Some(s)
+        }
+        None => Some(s),
+    }
+}
+
 impl TryFrom<DateTime> for Timestamp {
     type Error = TimestampError;
 
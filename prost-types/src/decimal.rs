@@ -0,0 +1,418 @@
+use core::cmp::Ordering;
+use core::option::Option;
+
+use prost::alloc::string::ToString;
+
+use super::*;
+
+/// A representation of a decimal value, such as 2.5, matching [`google.type.Decimal`][1].
+///
+/// The value is held as a string, which is a decimal number with an optional sign, an optional
+/// decimal point and an optional exponent, e.g. `"-2.5"`, `".5"` or `"2.5e-8"`. Decimals are
+/// parsed from strings with [`FromStr`], and created from an integer and a number of decimal
+/// places with [`Decimal::from_parts`]. Arithmetic is exact, with the methods `checked_add`,
+/// `checked_sub` and `checked_mul`, which fail if the result does not fit in the 128-bit integer
+/// of [`Decimal::to_parts`].
+///
+/// The derived `PartialEq` compares the strings, so `"2.5"` and `"2.50"` are not equal.
+/// [`Decimal::cmp_value`] compares the values instead.
+///
+/// [1]: https://github.com/googleapis/googleapis/blob/master/google/type/decimal.proto
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Decimal {
+    /// The decimal value, as a string.
+    #[prost(string, tag = "1")]
+    pub value: String,
+}
+
+impl Decimal {
+    /// The largest scale of a decimal created with [`Decimal::from_parts`] or returned by
+    /// [`Decimal::to_parts`], which bounds the number of zeros written after the decimal point.
+    pub const MAX_SCALE: u32 = 1000;
+
+    /// Creates the decimal `mantissa * 10^-scale`, e.g. `Decimal::from_parts(-25, 1)` is `-2.5`.
+    ///
+    /// Fails with [`DecimalError::OutOfRange`] if the scale is above [`Decimal::MAX_SCALE`].
+    pub fn from_parts(mantissa: i128, scale: u32) -> Result<Decimal, DecimalError> {
+        if scale > Decimal::MAX_SCALE {
+            return Err(DecimalError::OutOfRange);
+        }
+        let digits = mantissa.unsigned_abs().to_string();
+        let sign = if mantissa < 0 { "-" } else { "" };
+        let scale = scale as usize;
+        let value = if scale == 0 {
+            format!("{sign}{digits}")
+        } else if digits.len() > scale {
+            let (integer, fraction) = digits.split_at(digits.len() - scale);
+            format!("{sign}{integer}.{fraction}")
+        } else {
+            let zeros = "0".repeat(scale - digits.len());
+            format!("{sign}0.{zeros}{digits}")
+        };
+        Ok(Decimal { value })
+    }
+
+    /// Returns the integer `mantissa` and the number of decimal places `scale` of the decimal,
+    /// which is `mantissa * 10^-scale`.
+    ///
+    /// The scale is the number of digits after the decimal point, less the exponent, if any.
+    /// Fails if the value is not a valid decimal, if the mantissa does not fit in an `i128`, or if
+    /// the scale is above [`Decimal::MAX_SCALE`].
+    pub fn to_parts(&self) -> Result<(i128, u32), DecimalError> {
+        let (mantissa, exponent) = parse(&self.value)?;
+        let magnitude = u32::try_from(exponent.unsigned_abs()).ok();
+        if mantissa == 0 {
+            Ok((0, 0))
+        } else if exponent >= 0 {
+            let mantissa = magnitude
+                .and_then(pow10)
+                .and_then(|factor| mantissa.checked_mul(factor))
+                .ok_or(DecimalError::OutOfRange)?;
+            Ok((mantissa, 0))
+        } else {
+            let scale = magnitude
+                .filter(|&scale| scale <= Decimal::MAX_SCALE)
+                .ok_or(DecimalError::OutOfRange)?;
+            Ok((mantissa, scale))
+        }
+    }
+
+    /// Returns `true` if the value is a valid decimal, of any magnitude.
+    pub fn is_valid(&self) -> bool {
+        parse(&self.value) != Err(DecimalError::ParseFailure)
+    }
+
+    /// Returns the decimal without trailing zeros after the decimal point nor an exponent, e.g.
+    /// `2.5` for `"2.500"` or `"25e-1"`.
+    pub fn normalized(&self) -> Result<Decimal, DecimalError> {
+        let (mut mantissa, mut scale) = self.to_parts()?;
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        Decimal::from_parts(mantissa, scale)
+    }
+
+    /// Adds two decimals, returning `None` if either is not valid or the result is out of range.
+    pub fn checked_add(&self, rhs: &Decimal) -> Option<Decimal> {
+        let (lhs, rhs, scale) = aligned(self, rhs)?;
+        Decimal::from_parts(lhs.checked_add(rhs)?, scale).ok()
+    }
+
+    /// Subtracts a decimal, returning `None` if either is not valid or the result is out of
+    /// range.
+    pub fn checked_sub(&self, rhs: &Decimal) -> Option<Decimal> {
+        let (lhs, rhs, scale) = aligned(self, rhs)?;
+        Decimal::from_parts(lhs.checked_sub(rhs)?, scale).ok()
+    }
+
+    /// Multiplies two decimals, returning `None` if either is not valid or the result is out of
+    /// range.
+    pub fn checked_mul(&self, rhs: &Decimal) -> Option<Decimal> {
+        let (lhs, lhs_scale) = self.to_parts().ok()?;
+        let (rhs, rhs_scale) = rhs.to_parts().ok()?;
+        Decimal::from_parts(lhs.checked_mul(rhs)?, lhs_scale.checked_add(rhs_scale)?).ok()
+    }
+
+    /// Compares the values of two decimals, returning `None` if either is not valid or out of
+    /// range.
+    pub fn cmp_value(&self, rhs: &Decimal) -> Option<Ordering> {
+        let (lhs, rhs, _) = aligned(self, rhs)?;
+        Some(lhs.cmp(&rhs))
+    }
+}
+
+/// Returns the mantissas of two decimals with the same scale, and that scale.
+fn aligned(lhs: &Decimal, rhs: &Decimal) -> Option<(i128, i128, u32)> {
+    let (lhs, lhs_scale) = lhs.to_parts().ok()?;
+    let (rhs, rhs_scale) = rhs.to_parts().ok()?;
+    let scale = lhs_scale.max(rhs_scale);
+    let lhs = lhs.checked_mul(pow10(scale - lhs_scale)?)?;
+    let rhs = rhs.checked_mul(pow10(scale - rhs_scale)?)?;
+    Some((lhs, rhs, scale))
+}
+
+fn pow10(exponent: u32) -> Option<i128> {
+    10i128.checked_pow(exponent)
+}
+
+/// Parses a decimal value into an integer mantissa and a power of ten.
+///
+/// The syntax is that of `google.type.Decimal`: an optional sign, digits with an optional decimal
+/// point, and an optional exponent of `e` or `E` followed by an optionally signed integer.
+fn parse(value: &str) -> Result<(i128, i64), DecimalError> {
+    let (negative, rest) = match value.as_bytes().first() {
+        Some(b'-') => (true, &value[1..]),
+        Some(b'+') => (false, &value[1..]),
+        _ => (false, value),
+    };
+    let (number, exponent) = match rest.find(['e', 'E']) {
+        Some(index) => (&rest[..index], Some(&rest[index + 1..])),
+        None => (rest, None),
+    };
+    let (integer, fraction) = match number.find('.') {
+        Some(index) => (&number[..index], &number[index + 1..]),
+        None => (number, ""),
+    };
+    if (integer.is_empty() && fraction.is_empty())
+        || !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(DecimalError::ParseFailure);
+    }
+    if let Some(exponent) = exponent {
+        let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(DecimalError::ParseFailure);
+        }
+    }
+
+    // The whole value is valid, so any failure from here on is a value out of range.
+    // Negative mantissas are accumulated as such, so that `i128::MIN` does not overflow.
+    let sign = if negative { -1 } else { 1 };
+    let mut mantissa: i128 = 0;
+    for digit in integer.bytes().chain(fraction.bytes()) {
+        mantissa = mantissa
+            .checked_mul(10)
+            .and_then(|mantissa| mantissa.checked_add(sign * i128::from(digit - b'0')))
+            .ok_or(DecimalError::OutOfRange)?;
+    }
+    let exponent = match exponent {
+        Some(exponent) => exponent
+            .parse::<i32>()
+            .map_err(|_| DecimalError::OutOfRange)?,
+        None => 0,
+    };
+    Ok((mantissa, i64::from(exponent) - fraction.len() as i64))
+}
+
+impl Name for Decimal {
+    const PACKAGE: &'static str = TYPE_PACKAGE;
+    const NAME: &'static str = "Decimal";
+
+    fn type_url() -> String {
+        type_url_for::<Self>()
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = DecimalError;
+
+    /// Parses a decimal, failing if the string is not a valid `google.type.Decimal` value.
+    fn from_str(s: &str) -> Result<Decimal, DecimalError> {
+        if parse(s) == Err(DecimalError::ParseFailure) {
+            return Err(DecimalError::ParseFailure);
+        }
+        Ok(Decimal {
+            value: s.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Decimal {
+        Decimal {
+            value: value.to_string(),
+        }
+    }
+}
+
+/// A decimal handling error.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DecimalError {
+    /// Indicates failure to parse a [`Decimal`] from a string.
+    ParseFailure,
+
+    /// Indicates that a [`Decimal`] does not fit in the 128-bit integer of
+    /// [`Decimal::to_parts`], has a scale above [`Decimal::MAX_SCALE`], or does not fit in the
+    /// decimal type it is converted to.
+    OutOfRange,
+}
+
+impl fmt::Display for DecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecimalError::ParseFailure => write!(f, "failed to parse decimal"),
+            DecimalError::OutOfRange => write!(f, "failed to convert decimal out of range"),
+        }
+    }
+}
+
+impl core::error::Error for DecimalError {}
+
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal {
+    use super::*;
+
+    impl From<::rust_decimal::Decimal> for Decimal {
+        fn from(value: ::rust_decimal::Decimal) -> Decimal {
+            // The scale of a `rust_decimal::Decimal` is at most 28.
+            Decimal::from_parts(value.mantissa(), value.scale()).unwrap()
+        }
+    }
+
+    /// Converts to a `rust_decimal::Decimal`, failing if the decimal is not valid or has more
+    /// than 28 decimal places or a mantissa of more than 96 bits.
+    impl TryFrom<Decimal> for ::rust_decimal::Decimal {
+        type Error = DecimalError;
+
+        fn try_from(value: Decimal) -> Result<::rust_decimal::Decimal, DecimalError> {
+            let (mantissa, scale) = value.to_parts()?;
+            ::rust_decimal::Decimal::try_from_i128_with_scale(mantissa, scale)
+                .map_err(|_| DecimalError::OutOfRange)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_valid() {
+        assert_eq!(decimal("2.5").to_parts(), Ok((25, 1)));
+        assert_eq!(decimal("-2.50").to_parts(), Ok((-250, 2)));
+        assert_eq!(decimal("+.5").to_parts(), Ok((5, 1)));
+        assert_eq!(decimal("5.").to_parts(), Ok((5, 0)));
+        assert_eq!(decimal("2.5e-8").to_parts(), Ok((25, 9)));
+        assert_eq!(decimal("25E+2").to_parts(), Ok((2500, 0)));
+        assert_eq!(decimal("0").to_parts(), Ok((0, 0)));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for value in [
+            "", ".", "-", "1.2.3", "1e", "e5", "1,5", "NaN", "1 ", "--1", "1e+-2",
+        ] {
+            assert_eq!(
+                value.parse::<Decimal>(),
+                Err(DecimalError::ParseFailure),
+                "{value}"
+            );
+        }
+        assert_eq!(decimal("1").checked_mul(&decimal("1e39")), None);
+        assert_eq!(decimal("1e39").to_parts(), Err(DecimalError::OutOfRange));
+        assert!(decimal("1234567890123456789012345678901234567890").is_valid());
+
+        // The exponent is checked even if the mantissa is out of range.
+        let invalid = "1234567890123456789012345678901234567890eZZZ";
+        assert_eq!(invalid.parse::<Decimal>(), Err(DecimalError::ParseFailure));
+        let invalid = Decimal {
+            value: invalid.to_string(),
+        };
+        assert!(!invalid.is_valid());
+        assert_eq!(invalid.to_parts(), Err(DecimalError::ParseFailure));
+    }
+
+    #[test]
+    fn from_parts() {
+        let from_parts = |mantissa, scale| Decimal::from_parts(mantissa, scale).unwrap().value;
+        assert_eq!(from_parts(-25, 1), "-2.5");
+        assert_eq!(from_parts(5, 3), "0.005");
+        assert_eq!(from_parts(-5, 1), "-0.5");
+        assert_eq!(from_parts(100, 0), "100");
+        assert_eq!(
+            Decimal::from_parts(i128::MIN, 0).unwrap().to_parts(),
+            Ok((i128::MIN, 0))
+        );
+        assert_eq!(Decimal::from(-7).value, "-7");
+
+        let smallest = from_parts(1, Decimal::MAX_SCALE);
+        assert_eq!(smallest.len(), Decimal::MAX_SCALE as usize + 2);
+        assert!(smallest.starts_with("0.000") && smallest.ends_with("001"));
+        assert_eq!(
+            Decimal::from_parts(1, Decimal::MAX_SCALE + 1),
+            Err(DecimalError::OutOfRange)
+        );
+        assert_eq!(
+            Decimal::from_parts(1, u32::MAX),
+            Err(DecimalError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn scale_out_of_range() {
+        let tiny = decimal("1e-100000");
+        assert!(tiny.is_valid());
+        assert_eq!(tiny.to_parts(), Err(DecimalError::OutOfRange));
+        assert_eq!(tiny.normalized(), Err(DecimalError::OutOfRange));
+        assert_eq!(tiny.checked_add(&decimal("1")), None);
+        assert_eq!(tiny.checked_mul(&decimal("2")), None);
+
+        // The scale of a product is the sum of the scales.
+        let small = decimal("1e-600");
+        assert_eq!(small.to_parts(), Ok((1, 600)));
+        assert_eq!(small.checked_mul(&small), None);
+        assert_eq!(
+            small.checked_mul(&decimal("1e-400")),
+            Some(decimal("1e-1000").normalized().unwrap())
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(
+            decimal("2.5").checked_add(&decimal("0.25")),
+            Some(decimal("2.75"))
+        );
+        assert_eq!(
+            decimal("2.5").checked_sub(&decimal("3")),
+            Some(decimal("-0.5"))
+        );
+        assert_eq!(
+            decimal("2.5").checked_mul(&decimal("-0.2")),
+            Some(decimal("-0.50"))
+        );
+        assert_eq!(
+            decimal("1e2").checked_add(&decimal("1")),
+            Some(decimal("101"))
+        );
+        let invalid = Decimal {
+            value: "x".to_string(),
+        };
+        assert_eq!(invalid.checked_add(&decimal("1")), None);
+    }
+
+    #[test]
+    fn compare_and_normalize() {
+        assert_ne!(decimal("2.5"), decimal("2.50"));
+        assert_eq!(
+            decimal("2.5").cmp_value(&decimal("2.50")),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            decimal("-3").cmp_value(&decimal("2.5")),
+            Some(Ordering::Less)
+        );
+        assert_eq!(decimal("25e-1").normalized(), Ok(decimal("2.5")));
+        assert_eq!(decimal("2.500").normalized(), Ok(decimal("2.5")));
+        assert_eq!(decimal("100").normalized(), Ok(decimal("100")));
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn rust_decimal_roundtrip() {
+        let value = ::rust_decimal::Decimal::new(-12345, 3);
+        let decimal = Decimal::from(value);
+        assert_eq!(decimal.value, "-12.345");
+        assert_eq!(::rust_decimal::Decimal::try_from(decimal), Ok(value));
+        assert_eq!(
+            ::rust_decimal::Decimal::try_from(Decimal::from_parts(1, 29).unwrap()),
+            Err(DecimalError::OutOfRange)
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use core::option::Option;
+
 use super::*;
 
 impl Duration {
@@ -61,6 +63,98 @@ impl Duration {
         result.normalize();
         result
     }
+
+    /// The smallest valid duration, approximately -10,000 years.
+    pub const MIN: Duration = Duration {
+        seconds: -DURATION_SECONDS_MAX,
+        nanos: -NANOS_MAX,
+    };
+
+    /// The largest valid duration, approximately 10,000 years.
+    pub const MAX: Duration = Duration {
+        seconds: DURATION_SECONDS_MAX,
+        nanos: NANOS_MAX,
+    };
+
+    /// Returns `true` if the duration is normalized and within the range of valid durations,
+    /// [`Duration::MIN`] to [`Duration::MAX`].
+    pub fn is_valid(&self) -> bool {
+        (-DURATION_SECONDS_MAX..=DURATION_SECONDS_MAX).contains(&self.seconds)
+            && (-NANOS_MAX..=NANOS_MAX).contains(&self.nanos)
+            && !(self.seconds < 0 && self.nanos > 0)
+            && !(self.seconds > 0 && self.nanos < 0)
+    }
+
+    /// Parses a duration in exactly the string format of the [Protobuf JSON mapping][1], e.g.
+    /// `"1.5s"` or `"-0.000001s"`.
+    ///
+    /// Unlike [`FromStr`], which truncates any fractional digits beyond nanoseconds, this only
+    /// accepts up to nine fractional digits. Fails with [`DurationError::OutOfRange`] if the
+    /// duration is not within [`Duration::MIN`] to [`Duration::MAX`].
+    ///
+    /// [1]: https://protobuf.dev/programming-guides/proto3/#json
+    pub fn parse_strict(s: &str) -> Result<Duration, DurationError> {
+        let duration = datetime::parse_json_duration(s).ok_or(DurationError::ParseFailure)?;
+        if !duration.is_valid() {
+            return Err(DurationError::OutOfRange);
+        }
+        Ok(duration)
+    }
+
+    /// Adds two durations, returning `None` if the result is not a valid duration.
+    pub fn checked_add(self, rhs: Duration) -> Option<Duration> {
+        Duration::from_nanos(self.as_nanos() + rhs.as_nanos())
+    }
+
+    /// Subtracts a duration, returning `None` if the result is not a valid duration.
+    pub fn checked_sub(self, rhs: Duration) -> Option<Duration> {
+        Duration::from_nanos(self.as_nanos() - rhs.as_nanos())
+    }
+
+    /// Returns the total number of nanoseconds of the duration, which need not be normalized.
+    pub(crate) fn as_nanos(&self) -> i128 {
+        i128::from(self.seconds) * i128::from(NANOS_PER_SECOND) + i128::from(self.nanos)
+    }
+
+    /// Creates a normalized duration from a number of nanoseconds, returning `None` if it is not a
+    /// valid duration.
+    pub(crate) fn from_nanos(nanos: i128) -> Option<Duration> {
+        let duration = Duration {
+            seconds: i64::try_from(nanos / i128::from(NANOS_PER_SECOND)).ok()?,
+            nanos: (nanos % i128::from(NANOS_PER_SECOND)) as i32,
+        };
+        duration.is_valid().then_some(duration)
+    }
+}
+
+impl core::ops::Add for Duration {
+    type Output = Duration;
+
+    /// Adds two durations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is not a valid duration. Use [`Duration::checked_add`] to handle
+    /// overflow.
+    fn add(self, rhs: Duration) -> Duration {
+        self.checked_add(rhs)
+            .expect("overflow when adding durations")
+    }
+}
+
+impl core::ops::Sub for Duration {
+    type Output = Duration;
+
+    /// Subtracts a duration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is not a valid duration. Use [`Duration::checked_sub`] to handle
+    /// overflow.
+    fn sub(self, rhs: Duration) -> Duration {
+        self.checked_sub(rhs)
+            .expect("overflow when subtracting durations")
+    }
 }
 
 impl Name for Duration {
@@ -203,6 +297,30 @@ mod chrono {
     }
 }
 
+#[cfg(feature = "time")]
+mod time_crate {
+    use super::*;
+
+    /// Converts a `time::Duration`, failing if it is not a valid duration.
+    impl TryFrom<::time::Duration> for Duration {
+        type Error = DurationError;
+
+        fn try_from(value: ::time::Duration) -> Result<Duration, DurationError> {
+            Duration::from_nanos(value.whole_nanoseconds()).ok_or(DurationError::OutOfRange)
+        }
+    }
+
+    /// Converts to a `time::Duration`, failing if the duration is not valid.
+    impl TryFrom<Duration> for ::time::Duration {
+        type Error = DurationError;
+
+        fn try_from(value: Duration) -> Result<::time::Duration, DurationError> {
+            let value = Duration::from_nanos(value.as_nanos()).ok_or(DurationError::OutOfRange)?;
+            Ok(::time::Duration::new(value.seconds, value.nanos))
+        }
+    }
+}
+
 #[cfg(kani)]
 mod proofs {
     use super::*;
@@ -341,6 +459,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_duration_parse_strict() {
+        for (s, seconds, nanos) in [
+            ("0s", 0, 0),
+            ("-0s", 0, 0),
+            ("1.5s", 1, 500_000_000),
+            ("3.000001s", 3, 1_000),
+            ("-0.000000001s", 0, -1),
+            ("315576000000.999999999s", 315_576_000_000, 999_999_999),
+            ("-315576000000.999999999s", -315_576_000_000, -999_999_999),
+        ] {
+            assert_eq!(
+                Duration::parse_strict(s),
+                Ok(Duration { seconds, nanos }),
+                "{s}"
+            );
+        }
+
+        assert_eq!(
+            Duration::parse_strict("315576000001s"),
+            Err(DurationError::OutOfRange)
+        );
+        assert_eq!(
+            Duration::parse_strict("-315576000001s"),
+            Err(DurationError::OutOfRange)
+        );
+
+        // Accepted by `FromStr`, but not by the JSON mapping.
+        assert!(Duration::from_str("1.0000000001s").is_ok());
+        for s in [
+            "1.0000000001s",
+            "",
+            "s",
+            "1",
+            "1.s",
+            ".5s",
+            "+1s",
+            "1.5S",
+            " 1s",
+            "--1s",
+        ] {
+            assert_eq!(
+                Duration::parse_strict(s),
+                Err(DurationError::ParseFailure),
+                "{s}"
+            );
+        }
+    }
+
     #[cfg(feature = "std")]
     #[test]
     fn test_format_duration() {
@@ -477,4 +644,99 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn check_duration_arithmetic() {
+        let a = Duration {
+            seconds: 1,
+            nanos: 500_000_000,
+        };
+        let b = Duration {
+            seconds: 2,
+            nanos: 700_000_000,
+        };
+        assert_eq!(
+            a + b,
+            Duration {
+                seconds: 4,
+                nanos: 200_000_000
+            }
+        );
+        assert_eq!(
+            a - b,
+            Duration {
+                seconds: -1,
+                nanos: -200_000_000
+            }
+        );
+        assert_eq!(
+            Duration::MAX.checked_sub(Duration::MAX),
+            Some(Duration::default())
+        );
+        assert_eq!(Duration::MAX.checked_add(a), None);
+        assert_eq!(Duration::MIN.checked_sub(a), None);
+
+        // Operands need not be normalized.
+        let c = Duration {
+            seconds: 1,
+            nanos: -1,
+        };
+        assert_eq!(
+            c.checked_add(Duration::default()),
+            Some(Duration {
+                seconds: 0,
+                nanos: 999_999_999
+            })
+        );
+    }
+
+    #[test]
+    fn check_duration_is_valid() {
+        assert!(Duration::MIN.is_valid());
+        assert!(Duration::MAX.is_valid());
+        assert!(Duration {
+            seconds: -1,
+            nanos: -1
+        }
+        .is_valid());
+        assert!(!Duration {
+            seconds: 1,
+            nanos: -1
+        }
+        .is_valid());
+        assert!(!Duration {
+            seconds: 0,
+            nanos: NANOS_PER_SECOND
+        }
+        .is_valid());
+        assert!(!Duration {
+            seconds: DURATION_SECONDS_MAX + 1,
+            nanos: 0
+        }
+        .is_valid());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn check_duration_time_conversions() {
+        let duration = Duration {
+            seconds: -5,
+            nanos: -250,
+        };
+        let time_duration = ::time::Duration::try_from(duration).unwrap();
+        assert_eq!(time_duration, ::time::Duration::new(-5, -250));
+        assert_eq!(Duration::try_from(time_duration), Ok(duration));
+
+        assert_eq!(
+            Duration::try_from(::time::Duration::MAX),
+            Err(DurationError::OutOfRange)
+        );
+        assert_eq!(
+            ::time::Duration::try_from(Duration {
+                seconds: i64::MAX,
+                nanos: 0
+            }),
+            Err(DurationError::OutOfRange)
+        );
+    }
 }
@@ -0,0 +1,115 @@
+use prost::Message;
+
+use crate::feature_set::{
+    EnumType, FieldPresence, JsonFormat, MessageEncoding, RepeatedFieldEncoding, Utf8Validation,
+};
+use crate::{Edition, FeatureSet, FileDescriptorProto};
+
+impl FileDescriptorProto {
+    /// Returns the edition of the file.
+    ///
+    /// Files with the `proto2` and `proto3` syntaxes, or without a syntax, have the legacy
+    /// [`Edition::Proto2`] and [`Edition::Proto3`] editions. [`Edition::Unknown`] is returned for
+    /// an unknown syntax.
+    pub fn resolved_edition(&self) -> Edition {
+        match self.syntax() {
+            "" | "proto2" => Edition::Proto2,
+            "proto3" => Edition::Proto3,
+            "editions" => self.edition(),
+            _ => Edition::Unknown,
+        }
+    }
+}
+
+impl FeatureSet {
+    /// Returns the default features of an edition, or `None` if the edition is not supported.
+    ///
+    /// The legacy `proto2` and `proto3` editions and edition 2023 are supported.
+    pub fn edition_defaults(edition: Edition) -> Option<FeatureSet> {
+        let (field_presence, enum_type, repeated_field_encoding, utf8_validation, json_format) =
+            match edition {
+                Edition::Proto2 => (
+                    FieldPresence::Explicit,
+                    EnumType::Closed,
+                    RepeatedFieldEncoding::Expanded,
+                    Utf8Validation::None,
+                    JsonFormat::LegacyBestEffort,
+                ),
+                Edition::Proto3 => (
+                    FieldPresence::Implicit,
+                    EnumType::Open,
+                    RepeatedFieldEncoding::Packed,
+                    Utf8Validation::Verify,
+                    JsonFormat::Allow,
+                ),
+                Edition::Edition2023 => (
+                    FieldPresence::Explicit,
+                    EnumType::Open,
+                    RepeatedFieldEncoding::Packed,
+                    Utf8Validation::Verify,
+                    JsonFormat::Allow,
+                ),
+                _ => return None,
+            };
+        Some(FeatureSet {
+            field_presence: Some(field_presence as i32),
+            enum_type: Some(enum_type as i32),
+            repeated_field_encoding: Some(repeated_field_encoding as i32),
+            utf8_validation: Some(utf8_validation as i32),
+            message_encoding: Some(MessageEncoding::LengthPrefixed as i32),
+            json_format: Some(json_format as i32),
+            _extensions: Default::default(),
+        })
+    }
+
+    /// Returns the features of an element whose parent has these features, and which sets the
+    /// features in `overrides`.
+    ///
+    /// Features which are set in `overrides` replace the inherited ones, and language-specific
+    /// feature extensions are merged, as the features of descriptors are resolved by `protoc`.
+    pub fn resolve(&self, overrides: Option<&FeatureSet>) -> FeatureSet {
+        let mut features = self.clone();
+        if let Some(overrides) = overrides {
+            features
+                .merge(overrides.encode_to_vec().as_slice())
+                .expect("features are valid");
+        }
+        features
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolved_edition() {
+        let mut file = FileDescriptorProto::default();
+        assert_eq!(file.resolved_edition(), Edition::Proto2);
+        file.syntax = Some("proto3".into());
+        assert_eq!(file.resolved_edition(), Edition::Proto3);
+        file.syntax = Some("editions".into());
+        file.set_edition(Edition::Edition2023);
+        assert_eq!(file.resolved_edition(), Edition::Edition2023);
+        file.syntax = Some("proto4".into());
+        assert_eq!(file.resolved_edition(), Edition::Unknown);
+    }
+
+    #[test]
+    fn resolve_features() {
+        assert!(FeatureSet::edition_defaults(Edition::Edition2024).is_none());
+
+        let defaults = FeatureSet::edition_defaults(Edition::Edition2023).unwrap();
+        assert_eq!(defaults.field_presence(), FieldPresence::Explicit);
+
+        let mut overrides = FeatureSet::default();
+        overrides.set_field_presence(FieldPresence::Implicit);
+        let resolved = defaults.resolve(Some(&overrides));
+        assert_eq!(resolved.field_presence(), FieldPresence::Implicit);
+        assert_eq!(
+            resolved.repeated_field_encoding(),
+            RepeatedFieldEncoding::Packed
+        );
+        assert_eq!(defaults.resolve(None), defaults);
+    }
+}
@@ -0,0 +1,314 @@
+use prost::alloc::string::String;
+use prost::alloc::vec::Vec;
+use prost::diff::{DiffMessage, MessageDiff};
+use prost::field_mask::{FieldMaskMessage, FieldMaskTree};
+
+use crate::FieldMask;
+
+impl FieldMask {
+    /// Creates a field mask from a list of paths.
+    pub fn new<I, S>(paths: I) -> FieldMask
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        FieldMask {
+            paths: paths.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Returns the mask of the fields which differ between two messages, e.g. to use as the update
+    /// mask of a request which changes `original` to `modified`.
+    ///
+    /// See [`prost::diff::diff`] for how the fields are compared.
+    pub fn diff<T: DiffMessage>(original: &T, modified: &T) -> FieldMask {
+        prost::diff::diff(original, modified).into()
+    }
+
+    /// Returns the paths of the mask as a tree of field names.
+    pub fn to_tree(&self) -> FieldMaskTree {
+        self.paths.iter().collect()
+    }
+
+    /// Returns the mask in canonical form: with its paths sorted, and without paths which are
+    /// covered by other paths.
+    pub fn normalize(&self) -> FieldMask {
+        FieldMask::new(self.to_tree().paths())
+    }
+
+    /// Returns the canonical mask of the paths which are in either mask.
+    pub fn union(&self, other: &FieldMask) -> FieldMask {
+        let mut tree = self.to_tree();
+        tree.union(&other.to_tree());
+        FieldMask::new(tree.paths())
+    }
+
+    /// Returns the canonical mask of the paths which are covered by both masks.
+    ///
+    /// For example, the intersection of `a` and `a.b` is `a.b`.
+    pub fn intersect(&self, other: &FieldMask) -> FieldMask {
+        FieldMask::new(self.to_tree().intersect(&other.to_tree()).paths())
+    }
+
+    /// Returns `true` if every path of the mask refers to a field of the message type `T`.
+    ///
+    /// Paths may descend into singular message fields, but not into repeated or map fields, and
+    /// must use the snake_case Protobuf field names.
+    pub fn is_valid_for<T: FieldMaskMessage>(&self) -> bool {
+        self.paths
+            .iter()
+            .all(|path| T::is_valid_path(&path.split('.').collect::<Vec<_>>()))
+    }
+
+    /// Clears the fields of the message which are not selected by the mask.
+    pub fn apply_to<T: FieldMaskMessage>(&self, message: &mut T) {
+        message.retain_fields(&self.to_tree());
+    }
+
+    /// Replaces the fields of `destination` which are selected by the mask with those of
+    /// `source`, following the update semantics of Google APIs.
+    ///
+    /// Selected fields which are not set in `source` are cleared, and repeated and map fields are
+    /// replaced rather than appended to.
+    pub fn merge<T: FieldMaskMessage>(&self, source: &T, destination: &mut T) {
+        destination.merge_fields(source, &self.to_tree());
+    }
+
+    /// Converts the snake_case paths of the mask to lowerCamelCase, as used by the JSON mapping.
+    ///
+    /// Returns `None` if a path can not be converted back to snake_case unambiguously, which is
+    /// the case if it contains uppercase letters, or an underscore which is not followed by a
+    /// lowercase letter.
+    pub fn to_camel_case(&self) -> Option<FieldMask> {
+        self.paths
+            .iter()
+            .map(|path| snake_to_camel_case(path))
+            .collect::<Option<_>>()
+            .map(|paths| FieldMask { paths })
+    }
+
+    /// Converts the lowerCamelCase paths of the mask to snake_case.
+    ///
+    /// Returns `None` if a path contains an underscore.
+    pub fn to_snake_case(&self) -> Option<FieldMask> {
+        self.paths
+            .iter()
+            .map(|path| camel_to_snake_case(path))
+            .collect::<Option<_>>()
+            .map(|paths| FieldMask { paths })
+    }
+}
+
+impl From<MessageDiff> for FieldMask {
+    fn from(diff: MessageDiff) -> FieldMask {
+        FieldMask::new(diff.into_paths())
+    }
+}
+
+pub(crate) fn snake_to_camel_case(path: &str) -> Option<String> {
+    let mut camel = String::with_capacity(path.len());
+    let mut capitalize_next = false;
+    for c in path.chars() {
+        match c {
+            '_' if !capitalize_next => capitalize_next = true,
+            'a'..='z' if capitalize_next => {
+                camel.push(c.to_ascii_uppercase());
+                capitalize_next = false;
+            }
+            c if !capitalize_next && !c.is_ascii_uppercase() => camel.push(c),
+            _ => return None,
+        }
+    }
+    if capitalize_next {
+        return None;
+    }
+    Some(camel)
+}
+
+pub(crate) fn camel_to_snake_case(path: &str) -> Option<String> {
+    let mut snake = String::with_capacity(path.len() + 4);
+    for c in path.chars() {
+        match c {
+            '_' => return None,
+            'A'..='Z' => {
+                snake.push('_');
+                snake.push(c.to_ascii_lowercase());
+            }
+            c => snake.push(c),
+        }
+    }
+    Some(snake)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use prost::alloc::vec;
+
+    use crate::{DescriptorProto, FieldDescriptorProto, MessageOptions, Value};
+
+    #[test]
+    fn union_and_intersect() {
+        let a = FieldMask::new(["options.deprecated", "name", "field"]);
+        let b = FieldMask::new(["options", "field.name", "reserved_name"]);
+        assert_eq!(
+            a.union(&b).paths,
+            vec!["field", "name", "options", "reserved_name"]
+        );
+        assert_eq!(
+            a.intersect(&b).paths,
+            vec!["field.name", "options.deprecated"]
+        );
+        assert_eq!(
+            FieldMask::new(["b", "a.c", "a", "b"]).normalize().paths,
+            vec!["a", "b"]
+        );
+    }
+
+    #[test]
+    fn is_valid_for() {
+        assert!(FieldMask::new(["name", "options.deprecated", "field"])
+            .is_valid_for::<DescriptorProto>());
+        // Oneof fields are named by the fields of the oneof.
+        assert!(FieldMask::new(["struct_value.fields", "number_value"]).is_valid_for::<Value>());
+        assert!(FieldMask::default().is_valid_for::<DescriptorProto>());
+
+        assert!(!FieldMask::new(["names"]).is_valid_for::<DescriptorProto>());
+        assert!(!FieldMask::new(["name.length"]).is_valid_for::<DescriptorProto>());
+        assert!(!FieldMask::new(["field.name"]).is_valid_for::<DescriptorProto>());
+        assert!(!FieldMask::new(["options."]).is_valid_for::<DescriptorProto>());
+        assert!(!FieldMask::new(["kind"]).is_valid_for::<Value>());
+    }
+
+    fn message() -> DescriptorProto {
+        DescriptorProto {
+            name: Some("Foo".into()),
+            field: vec![FieldDescriptorProto::default()],
+            options: Some(MessageOptions {
+                deprecated: Some(true),
+                map_entry: Some(false),
+                ..Default::default()
+            }),
+            reserved_name: vec!["bar".into()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn apply_to() {
+        let mut message = message();
+        FieldMask::new(["name", "options.deprecated"]).apply_to(&mut message);
+        assert_eq!(
+            message,
+            DescriptorProto {
+                name: Some("Foo".into()),
+                options: Some(MessageOptions {
+                    deprecated: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }
+        );
+
+        let mut value = Value::from(1.5);
+        FieldMask::new(["string_value"]).apply_to(&mut value);
+        assert_eq!(value.kind, None);
+        let mut value = Value::from(1.5);
+        FieldMask::new(["number_value"]).apply_to(&mut value);
+        assert_eq!(value, Value::from(1.5));
+    }
+
+    #[test]
+    fn merge() {
+        let source = DescriptorProto {
+            name: Some("Bar".into()),
+            reserved_name: vec!["baz".into()],
+            options: Some(MessageOptions {
+                map_entry: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let mut destination = message();
+        FieldMask::new(["name", "reserved_name", "field", "options.map_entry"])
+            .merge(&source, &mut destination);
+        assert_eq!(
+            destination,
+            DescriptorProto {
+                name: Some("Bar".into()),
+                reserved_name: vec!["baz".into()],
+                options: Some(MessageOptions {
+                    deprecated: Some(true),
+                    map_entry: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }
+        );
+
+        // Sub-fields of unset messages are cleared.
+        let mut destination = message();
+        FieldMask::new(["options.deprecated"]).merge(&DescriptorProto::default(), &mut destination);
+        assert_eq!(destination.options.unwrap().deprecated, None);
+
+        // Oneof fields replace the set field of the oneof, or clear it.
+        let mut value = Value::from(1.5);
+        FieldMask::new(["string_value"]).merge(&Value::from("a"), &mut value);
+        assert_eq!(value, Value::from("a"));
+        FieldMask::new(["string_value"]).merge(&Value::from(2.0), &mut value);
+        assert_eq!(value.kind, None);
+    }
+
+    #[test]
+    fn diff() {
+        let original = message();
+        assert!(FieldMask::diff(&original, &original).paths.is_empty());
+
+        let mut modified = message();
+        modified.name = None;
+        modified.field.push(FieldDescriptorProto::default());
+        modified.options.as_mut().unwrap().deprecated = Some(false);
+        let mask = FieldMask::diff(&original, &modified);
+        assert_eq!(mask.paths, vec!["name", "field", "options.deprecated"]);
+
+        // Applying the diff to the original gives the modified message.
+        let mut updated = original.clone();
+        mask.merge(&modified, &mut updated);
+        assert_eq!(updated, modified);
+
+        // Unset messages differ as a whole.
+        modified.options = None;
+        assert!(FieldMask::diff(&original, &modified)
+            .paths
+            .contains(&"options".into()));
+
+        // Oneof fields are named by the fields of the oneof.
+        assert_eq!(
+            FieldMask::diff(&Value::from(1.5), &Value::from(2.5)).paths,
+            vec!["number_value"]
+        );
+        assert_eq!(
+            FieldMask::diff(&Value::from(1.5), &Value::from("a")).paths,
+            vec!["number_value", "string_value"]
+        );
+        assert_eq!(
+            FieldMask::diff(&Value::from(1.5), &Value::default()).paths,
+            vec!["number_value"]
+        );
+    }
+
+    #[test]
+    fn case_conversion() {
+        let mask = FieldMask::new(["user.display_name", "photo"]);
+        let camel = mask.to_camel_case().unwrap();
+        assert_eq!(camel.paths, vec!["user.displayName", "photo"]);
+        assert_eq!(camel.to_snake_case().unwrap(), mask);
+
+        assert!(FieldMask::new(["fooBar"]).to_camel_case().is_none());
+        assert!(FieldMask::new(["foo__bar"]).to_camel_case().is_none());
+        assert!(FieldMask::new(["foo_"]).to_camel_case().is_none());
+        assert!(FieldMask::new(["foo_bar"]).to_snake_case().is_none());
+    }
+}
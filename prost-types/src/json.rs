@@ -0,0 +1,324 @@
+//! JSON mapping of the well-known types which have special JSON representations.
+
+use prost::alloc::string::ToString;
+use prost::json::{JsonError, JsonField, JsonMessage, JsonOptions, JsonValue};
+
+use super::*;
+
+fn expect_string<'a>(value: &'a JsonValue, type_name: &str) -> Result<&'a str, JsonError> {
+    match value {
+        JsonValue::String(value) => Ok(value),
+        _ => Err(JsonError::new(format!("expected string for {type_name}"))),
+    }
+}
+
+/// A timestamp is represented as an RFC 3339 string in UTC, e.g. `"1972-01-01T10:00:20.021Z"`.
+impl JsonMessage for Timestamp {
+    fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        if !self.is_valid() {
+            return Err(JsonError::new(format!("timestamp out of range: {self:?}")));
+        }
+        Ok(JsonValue::String(self.to_string()))
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, _options: &JsonOptions) -> Result<(), JsonError> {
+        let value = expect_string(value, "Timestamp")?;
+        *self = Timestamp::parse_strict(value).map_err(|error| match error {
+            TimestampError::OutOfRange => {
+                JsonError::new(format!("timestamp out of range: {value}"))
+            }
+            _ => JsonError::new(format!("invalid timestamp: {value}")),
+        })?;
+        Ok(())
+    }
+}
+
+/// A duration is represented as a string of seconds with an `s` suffix, e.g. `"1.5s"`.
+impl JsonMessage for Duration {
+    fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        if !self.is_valid() {
+            return Err(JsonError::new(format!("duration out of range: {self:?}")));
+        }
+        Ok(JsonValue::String(self.to_string()))
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, _options: &JsonOptions) -> Result<(), JsonError> {
+        let value = expect_string(value, "Duration")?;
+        *self = Duration::parse_strict(value).map_err(|error| match error {
+            DurationError::OutOfRange => JsonError::new(format!("duration out of range: {value}")),
+            _ => JsonError::new(format!("invalid duration: {value}")),
+        })?;
+        Ok(())
+    }
+}
+
+/// A field mask is represented as a comma-separated string of paths, with each path in
+/// lowerCamelCase, e.g. `"user.displayName,photo"`.
+impl JsonMessage for FieldMask {
+    fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        let mut paths = Vec::with_capacity(self.paths.len());
+        for path in &self.paths {
+            let json_path = field_mask::snake_to_camel_case(path).ok_or_else(|| {
+                JsonError::new(format!(
+                    "field mask path can not be represented in JSON: {path}"
+                ))
+            })?;
+            paths.push(json_path);
+        }
+        Ok(JsonValue::String(paths.join(",")))
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, _options: &JsonOptions) -> Result<(), JsonError> {
+        let value = expect_string(value, "FieldMask")?;
+        if value.is_empty() {
+            return Ok(());
+        }
+        for json_path in value.split(',') {
+            let path = field_mask::camel_to_snake_case(json_path)
+                .ok_or_else(|| JsonError::new(format!("invalid field mask path: {json_path}")))?;
+            self.paths.push(path);
+        }
+        Ok(())
+    }
+}
+
+/// A struct is represented as a JSON object.
+impl JsonMessage for Struct {
+    fn to_json(&self, options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        self.fields
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), JsonMessage::to_json(value, options)?)))
+            .collect::<Result<_, _>>()
+            .map(JsonValue::Object)
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError> {
+        match value {
+            JsonValue::Object(members) => {
+                for (name, value) in members {
+                    self.fields
+                        .insert(name.clone(), Value::from_json(value, options)?);
+                }
+                Ok(())
+            }
+            _ => Err(JsonError::new("expected object for Struct")),
+        }
+    }
+}
+
+/// A value is represented as the corresponding JSON value, including `null`.
+impl JsonMessage for Value {
+    fn to_json(&self, options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        Ok(match self.kind {
+            Some(value::Kind::NullValue(_)) => JsonValue::Null,
+            Some(value::Kind::NumberValue(number)) => {
+                if !number.is_finite() {
+                    return Err(JsonError::new(format!(
+                        "Value can not represent number: {number}"
+                    )));
+                }
+                JsonField::to_json(&number, options)?
+            }
+            Some(value::Kind::StringValue(ref string)) => JsonValue::String(string.clone()),
+            Some(value::Kind::BoolValue(boolean)) => JsonValue::Bool(boolean),
+            Some(value::Kind::StructValue(ref object)) => JsonMessage::to_json(object, options)?,
+            Some(value::Kind::ListValue(ref list)) => JsonMessage::to_json(list, options)?,
+            None => return Err(JsonError::new("Value has no kind")),
+        })
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError> {
+        let kind = match value {
+            JsonValue::Null => value::Kind::NullValue(NullValue::NullValue as i32),
+            JsonValue::Bool(boolean) => value::Kind::BoolValue(*boolean),
+            JsonValue::Number(_) => value::Kind::NumberValue(f64::from_json(value, options)?),
+            JsonValue::String(string) => value::Kind::StringValue(string.clone()),
+            JsonValue::Array(_) => value::Kind::ListValue(ListValue::from_json(value, options)?),
+            JsonValue::Object(_) => value::Kind::StructValue(Struct::from_json(value, options)?),
+        };
+        self.kind = Some(kind);
+        Ok(())
+    }
+
+    fn accepts_null() -> bool {
+        true
+    }
+}
+
+/// A list value is represented as a JSON array.
+impl JsonMessage for ListValue {
+    fn to_json(&self, options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        self.values
+            .iter()
+            .map(|value| JsonMessage::to_json(value, options))
+            .collect::<Result<_, _>>()
+            .map(JsonValue::Array)
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError> {
+        match value {
+            JsonValue::Array(values) => {
+                for value in values {
+                    self.values.push(Value::from_json(value, options)?);
+                }
+                Ok(())
+            }
+            _ => Err(JsonError::new("expected array for ListValue")),
+        }
+    }
+}
+
+/// An `Any` is represented as the JSON representation of the packed message, with an additional
+/// `@type` member holding the type URL. The packed message type must be registered with
+/// [`JsonOptions::register`].
+///
+/// If the packed message has a special JSON representation, it is held by a `value` member.
+impl JsonMessage for Any {
+    fn to_json(&self, options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        if self.type_url.is_empty() && self.value.is_empty() {
+            return Ok(JsonValue::Object(Vec::new()));
+        }
+        prost::json::encoding::encode_any(&self.type_url, &self.value, options)
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError> {
+        let (type_url, value) = prost::json::encoding::merge_any(value, options)?;
+        self.type_url = type_url;
+        self.value = value;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::alloc::borrow::ToOwned;
+    use prost::alloc::vec;
+    use prost::json::{from_str, from_str_with_options, to_string, to_string_with_options};
+
+    #[test]
+    fn timestamp() {
+        let timestamp = Timestamp::date_time_nanos(1972, 1, 1, 10, 0, 20, 21_000_000).unwrap();
+        assert_eq!(
+            to_string(&timestamp).unwrap(),
+            r#""1972-01-01T10:00:20.021Z""#
+        );
+        assert_eq!(
+            from_str::<Timestamp>(r#""1972-01-01T10:00:20.021Z""#).unwrap(),
+            timestamp
+        );
+        assert_eq!(
+            from_str::<Timestamp>(r#""1972-01-01T11:00:20.021+01:00""#).unwrap(),
+            timestamp
+        );
+        assert!(from_str::<Timestamp>(r#""10000-01-01T00:00:00Z""#).is_err());
+        assert!(from_str::<Timestamp>(r#""1972-01-01t10:00:20.021z""#).is_err());
+        assert!(from_str::<Timestamp>(r#""1972-01-01""#).is_err());
+        assert!(from_str::<Timestamp>("0").is_err());
+        assert!(to_string(&Timestamp {
+            seconds: TIMESTAMP_SECONDS_MAX + 1,
+            nanos: 0
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn duration() {
+        for (seconds, nanos, json) in [
+            (1, 500_000_000, r#""1.500s""#),
+            (-1, -500_000_000, r#""-1.500s""#),
+            (0, -1_000, r#""-0.000001s""#),
+            (3, 0, r#""3s""#),
+        ] {
+            let duration = Duration { seconds, nanos };
+            assert_eq!(to_string(&duration).unwrap(), json);
+            assert_eq!(from_str::<Duration>(json).unwrap(), duration);
+        }
+        assert!(from_str::<Duration>(r#""315576000001s""#).is_err());
+        assert!(from_str::<Duration>(r#""1""#).is_err());
+        assert!(from_str::<Duration>(r#""1.0000000001s""#).is_err());
+        assert!(to_string(&Duration {
+            seconds: 1,
+            nanos: -1
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn field_mask() {
+        let mask = FieldMask {
+            paths: vec!["user.display_name".into(), "photo".into()],
+        };
+        assert_eq!(to_string(&mask).unwrap(), r#""user.displayName,photo""#);
+        assert_eq!(
+            from_str::<FieldMask>(r#""user.displayName,photo""#).unwrap(),
+            mask
+        );
+        assert_eq!(
+            from_str::<FieldMask>(r#""""#).unwrap(),
+            FieldMask::default()
+        );
+        assert!(to_string(&FieldMask {
+            paths: vec!["fooBar".into()]
+        })
+        .is_err());
+        assert!(from_str::<FieldMask>(r#""foo_bar""#).is_err());
+    }
+
+    #[test]
+    fn struct_and_value() {
+        let json = r#"{"a":null,"b":1.5,"c":"x","d":true,"e":{"f":[]},"g":[1,"2"]}"#;
+        let value = from_str::<Struct>(json).unwrap();
+        assert_eq!(value.fields["b"].kind, Some(value::Kind::NumberValue(1.5)));
+        assert_eq!(
+            value.fields["a"].kind,
+            Some(value::Kind::NullValue(NullValue::NullValue as i32))
+        );
+        assert_eq!(to_string(&value).unwrap(), json);
+
+        assert_eq!(
+            from_str::<Value>("null").unwrap().kind,
+            Some(value::Kind::NullValue(0))
+        );
+        assert!(from_str::<Struct>("[]").is_err());
+        assert!(to_string(&Value::default()).is_err());
+        assert!(to_string(&Value {
+            kind: Some(value::Kind::NumberValue(f64::NAN))
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn any() {
+        let options = JsonOptions::new()
+            .register::<Timestamp>()
+            .register::<Struct>()
+            .register::<FieldMask>();
+
+        let timestamp = Timestamp::date(2000, 1, 1).unwrap();
+        let any = Any::from_msg(&timestamp).unwrap();
+        let json = r#"{"@type":"type.googleapis.com/google.protobuf.Timestamp","value":"2000-01-01T00:00:00Z"}"#;
+        assert_eq!(to_string_with_options(&any, &options).unwrap(), json);
+        assert_eq!(from_str_with_options::<Any>(json, &options).unwrap(), any);
+
+        // Unregistered types can not be converted.
+        assert!(to_string(&any).is_err());
+        assert!(from_str::<Any>(json).is_err());
+
+        let mut object = Struct::default();
+        object.fields.insert(
+            "a".to_owned(),
+            Value {
+                kind: Some(value::Kind::BoolValue(true)),
+            },
+        );
+        let any = Any::from_msg(&object).unwrap();
+        let json = r#"{"@type":"type.googleapis.com/google.protobuf.Struct","value":{"a":true}}"#;
+        assert_eq!(to_string_with_options(&any, &options).unwrap(), json);
+        assert_eq!(from_str_with_options::<Any>(json, &options).unwrap(), any);
+
+        assert_eq!(to_string(&Any::default()).unwrap(), "{}");
+        assert_eq!(from_str::<Any>("{}").unwrap(), Any::default());
+        assert!(from_str::<Any>(r#"{"value":"x"}"#).is_err());
+    }
+}
@@ -33,12 +33,45 @@
 //! let message = any.to_msg::<Timestamp>().unwrap();
 //! ```
 //!
+//! ## Google API Common Types
+//!
+//! Besides the well-known types, this crate includes a few of the types of the [Google APIs][2]
+//! which most gRPC services use, so that they don't need to be compiled from `googleapis`:
+//!
+//! - [`rpc`]: the `google.rpc` package, with [`rpc::Status`], [`rpc::Code`] and the standard
+//!   error details, such as [`rpc::BadRequest`] and [`rpc::RetryInfo`]. A [`rpc::Status`] is
+//!   an error, and [`rpc::Status::into_result`] converts the status of a response to a `Result`.
+//!   Error details are packed into a status with [`rpc::Status::add_detail`], and unpacked with
+//!   [`rpc::Status::details_of`], or with [`rpc::Status::decode_details`] and the registry of
+//!   [`MessageRegistry::with_error_details`].
+//! - [`r#type`]: the `google.type` package, with [`r#type::Date`], [`r#type::TimeOfDay`],
+//!   [`r#type::LatLng`] and [`r#type::Money`], which have `is_valid` methods to check the
+//!   constraints of their definitions. [`Decimal`] is in the crate root.
+//!
+//! ```rust
+//! use prost_types::r#type::Date;
+//! use prost_types::rpc::{Code, Status};
+//!
+//! let date = Date { year: 2023, month: 2, day: 29 };
+//! if !date.is_valid() {
+//!     let status = Status::new(Code::InvalidArgument, "2023 is not a leap year");
+//!     assert_eq!(status.into_result().unwrap_err().code(), Code::InvalidArgument);
+//! }
+//! ```
+//!
 //! ## Feature Flags
 //! - `std`: Enable integration with standard library. Disable this feature for `no_std` support. This feature is enabled by default.
 //! - `arbitrary`: Enable integration with crate `arbitrary`. All types on this crate will implement `trait Arbitrary`.
-//! - `chrono`: Enable integration with crate `chrono`. Time related types implement conversions to/from their `chrono` equivalent.
+//! - `chrono`: Enable integration with crate `chrono`. Time and date related types implement conversions to/from their `chrono` equivalent.
+//! - `time`: Enable integration with crate `time`. Time and date related types implement conversions to/from their `time` equivalent.
+//! - `rust_decimal`: Enable integration with crate `rust_decimal`. `Decimal` converts to/from `rust_decimal::Decimal`.
+//! - `serde_json`: Enable integration with crate `serde_json`. `Value`, `Struct` and `ListValue` convert to/from their `serde_json` equivalents.
+//! - `json`: Enable support for the Protobuf JSON mapping. The well-known types with special JSON representations implement `prost::json::JsonMessage`.
+//! - `text-format`: Enable support for the Protobuf text format. The well-known types implement `prost::text_format::TextFormatMessage`.
+//! - `reflect`: Enable the `reflect` module, which supports message types which are only known at runtime through a `FileDescriptorSet`.
 //!
 //! [1]: https://protobuf.dev/reference/protobuf/google.protobuf/
+//! [2]: https://github.com/googleapis/googleapis
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -47,6 +80,10 @@ pub mod compiler;
 mod datetime;
 #[rustfmt::skip]
 mod protobuf;
+#[rustfmt::skip]
+pub mod rpc;
+#[rustfmt::skip]
+pub mod r#type;
 
 use core::convert::TryFrom;
 use core::fmt;
@@ -67,8 +104,21 @@ pub use protobuf::*;
 const NANOS_PER_SECOND: i32 = 1_000_000_000;
 const NANOS_MAX: i32 = NANOS_PER_SECOND - 1;
 
+/// The range of valid timestamps: 0001-01-01T00:00:00Z to 9999-12-31T23:59:59.999999999Z.
+const TIMESTAMP_SECONDS_MIN: i64 = -62_135_596_800;
+const TIMESTAMP_SECONDS_MAX: i64 = 253_402_300_799;
+
+/// The range of valid durations, approximately +-10,000 years.
+const DURATION_SECONDS_MAX: i64 = 315_576_000_000;
+
 const PACKAGE: &str = "google.protobuf";
 
+/// The package of the status and error details types of Google APIs.
+const RPC_PACKAGE: &str = "google.rpc";
+
+/// The package of the common types of Google APIs.
+const TYPE_PACKAGE: &str = "google.type";
+
 mod any;
 
 mod duration;
@@ -77,7 +127,67 @@ pub use duration::DurationError;
 mod timestamp;
 pub use timestamp::TimestampError;
 
+mod decimal;
+pub use decimal::{Decimal, DecimalError};
+
+mod status;
+
+mod common;
+pub use common::DateError;
+
 mod type_url;
 pub(crate) use type_url::{type_url_for, TypeUrl};
 
+mod field_mask;
+
+mod type_registry;
+pub use type_registry::{AnyMessage, MessageRegistry, TypeRegistry};
+
 mod conversions;
+
+mod editions;
+
+#[cfg(feature = "json")]
+mod json;
+
+#[cfg(feature = "text-format")]
+mod text_format;
+
+#[cfg(feature = "reflect")]
+pub mod reflect;
+
+impl Name for FieldMask {
+    const PACKAGE: &'static str = PACKAGE;
+    const NAME: &'static str = "FieldMask";
+
+    fn type_url() -> String {
+        type_url_for::<Self>()
+    }
+}
+
+impl Name for Struct {
+    const PACKAGE: &'static str = PACKAGE;
+    const NAME: &'static str = "Struct";
+
+    fn type_url() -> String {
+        type_url_for::<Self>()
+    }
+}
+
+impl Name for Value {
+    const PACKAGE: &'static str = PACKAGE;
+    const NAME: &'static str = "Value";
+
+    fn type_url() -> String {
+        type_url_for::<Self>()
+    }
+}
+
+impl Name for ListValue {
+    const PACKAGE: &'static str = PACKAGE;
+    const NAME: &'static str = "ListValue";
+
+    fn type_url() -> String {
+        type_url_for::<Self>()
+    }
+}
@@ -0,0 +1,91 @@
+//! Runtime reflection over Protobuf message types.
+//!
+//! A [`DescriptorPool`] is built from a `FileDescriptorSet`, such as the one written by
+//! `prost-build` when [`Config::file_descriptor_set_path`][1] is set. The pool describes each
+//! message, enum and service type, and a [`DynamicMessage`] of any of its message types can be
+//! encoded, decoded, and inspected without generated code. The well-known types are always
+//! available to the files of a pool.
+//!
+//! ```rust,ignore
+//! use prost::Message;
+//! use prost_types::reflect::{DescriptorPool, DynamicMessage, Value};
+//!
+//! let pool = DescriptorPool::decode(FILE_DESCRIPTOR_SET).unwrap();
+//! let desc = pool.get_message_by_name("my.package.Person").unwrap();
+//!
+//! let mut person = DynamicMessage::new(desc.clone());
+//! person.set_field_by_name("name", Value::String("Ferris".into()));
+//! let bytes = person.encode_to_vec();
+//!
+//! let person = DynamicMessage::decode(desc, bytes.as_slice()).unwrap();
+//! assert_eq!(person.get_field_by_name("name").unwrap().as_str(), Some("Ferris"));
+//! ```
+//!
+//! Message types generated with `Config::enable_reflection` of `prost-build` implement
+//! [`ReflectMessage`], which gives their descriptor and accesses their fields as [`Value`]s, so
+//! that generic code can operate on any generated message:
+//!
+//! ```rust,ignore
+//! use prost_types::reflect::{ReflectMessage, Value};
+//!
+//! fn redact<M: ReflectMessage>(message: &mut M) {
+//!     for field in message.descriptor().fields() {
+//!         if field.name() == "password" {
+//!             message.set_field_by_number(field.number(), Value::String("<redacted>".into()));
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! [1]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.file_descriptor_set_path
+
+use core::fmt;
+
+use prost::alloc::borrow::Cow;
+
+mod descriptor;
+mod dynamic;
+mod message;
+mod value;
+
+pub use descriptor::{
+    Cardinality, DescriptorPool, EnumDescriptor, EnumValueDescriptor, FieldDescriptor, Kind,
+    MessageDescriptor, MethodDescriptor, OneofDescriptor, ServiceDescriptor,
+};
+pub use dynamic::DynamicMessage;
+pub use message::{EmbeddedDescriptorPool, ReflectMessage};
+pub use value::{MapKey, Value};
+
+/// An error building a [`DescriptorPool`].
+///
+/// `DescriptorError` indicates that a file descriptor set could not be decoded, or that the types
+/// it describes are inconsistent, for example because a field refers to an unknown type.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DescriptorError {
+    description: Cow<'static, str>,
+}
+
+impl DescriptorError {
+    fn new(description: impl Into<Cow<'static, str>>) -> DescriptorError {
+        DescriptorError {
+            description: description.into(),
+        }
+    }
+}
+
+impl fmt::Debug for DescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DescriptorError")
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+impl fmt::Display for DescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid descriptor: ")?;
+        f.write_str(&self.description)
+    }
+}
+
+impl core::error::Error for DescriptorError {}
@@ -0,0 +1,1236 @@
+//! Descriptors of message, enum and service types, resolved from file descriptor sets.
+
+use core::fmt;
+
+use prost::alloc::borrow::ToOwned;
+use prost::alloc::collections::BTreeMap;
+use prost::alloc::format;
+use prost::alloc::string::String;
+use prost::alloc::sync::Arc;
+use prost::alloc::vec::Vec;
+use prost::Message;
+
+use super::DescriptorError;
+use crate::feature_set::{FieldPresence, MessageEncoding, RepeatedFieldEncoding};
+use crate::field_descriptor_proto::{Label, Type};
+use crate::{
+    DescriptorProto, EnumDescriptorProto, FeatureSet, FileDescriptorProto, FileDescriptorSet,
+    ServiceDescriptorProto,
+};
+
+/// The encoded file descriptor set of the well-known types, kept up to date by the bootstrap test.
+const WELL_KNOWN_TYPES: &[u8] = include_bytes!("well_known_types.bin");
+
+/// A collection of message, enum and service types, loaded from file descriptor sets.
+///
+/// A pool is cheap to clone, and the descriptors it hands out keep it alive. Adding files to a
+/// pool which has outstanding descriptors leaves those descriptors referring to the pool as it
+/// was before the files were added.
+#[derive(Clone, Default)]
+pub struct DescriptorPool {
+    inner: Arc<PoolInner>,
+}
+
+#[derive(Clone, Default)]
+struct PoolInner {
+    files: Vec<FileDescriptorProto>,
+    messages: Vec<MessageInner>,
+    enums: Vec<EnumInner>,
+    services: Vec<ServiceInner>,
+    names: BTreeMap<String, TypeIndex>,
+}
+
+#[derive(Clone, Copy)]
+enum TypeIndex {
+    Message(usize),
+    Enum(usize),
+    Service(usize),
+}
+
+#[derive(Clone)]
+struct MessageInner {
+    full_name: String,
+    file: usize,
+    fields: Vec<FieldInner>,
+    field_numbers: BTreeMap<u32, usize>,
+    field_names: BTreeMap<String, usize>,
+    oneofs: Vec<OneofInner>,
+    is_map_entry: bool,
+}
+
+#[derive(Clone)]
+struct FieldInner {
+    name: String,
+    json_name: String,
+    number: u32,
+    cardinality: Cardinality,
+    kind: KindIndex,
+    is_group: bool,
+    is_packed: bool,
+    supports_presence: bool,
+    oneof: Option<usize>,
+    default_value: Option<String>,
+}
+
+#[derive(Clone)]
+struct OneofInner {
+    name: String,
+    fields: Vec<usize>,
+}
+
+#[derive(Clone, Copy)]
+enum KindIndex {
+    Double,
+    Float,
+    Int32,
+    Int64,
+    Uint32,
+    Uint64,
+    Sint32,
+    Sint64,
+    Fixed32,
+    Fixed64,
+    Sfixed32,
+    Sfixed64,
+    Bool,
+    String,
+    Bytes,
+    Message(usize),
+    Enum(usize),
+}
+
+#[derive(Clone)]
+struct EnumInner {
+    full_name: String,
+    file: usize,
+    values: Vec<(String, i32)>,
+}
+
+#[derive(Clone)]
+struct ServiceInner {
+    full_name: String,
+    file: usize,
+    methods: Vec<MethodInner>,
+}
+
+#[derive(Clone)]
+struct MethodInner {
+    name: String,
+    input: usize,
+    output: usize,
+    client_streaming: bool,
+    server_streaming: bool,
+}
+
+/// A message type whose fields remain to be resolved, once all type names are known.
+struct PendingMessage<'a> {
+    index: usize,
+    proto: &'a DescriptorProto,
+    features: FeatureSet,
+}
+
+impl DescriptorPool {
+    /// Creates an empty pool.
+    pub fn new() -> DescriptorPool {
+        DescriptorPool::default()
+    }
+
+    /// Creates a pool containing the types of a file descriptor set.
+    pub fn from_file_descriptor_set(
+        file_descriptor_set: FileDescriptorSet,
+    ) -> Result<DescriptorPool, DescriptorError> {
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_set(file_descriptor_set)?;
+        Ok(pool)
+    }
+
+    /// Creates a pool containing the well-known types of the `google/protobuf` directory, such as
+    /// `google.protobuf.Timestamp`, and the types of `google/protobuf/descriptor.proto`.
+    pub fn well_known_types() -> DescriptorPool {
+        DescriptorPool::from_file_descriptor_set(well_known_files())
+            .expect("well-known types are valid")
+    }
+
+    /// Creates a pool from an encoded file descriptor set, such as the output of
+    /// `protoc --include_imports --descriptor_set_out`.
+    pub fn decode(bytes: &[u8]) -> Result<DescriptorPool, DescriptorError> {
+        let mut pool = DescriptorPool::new();
+        pool.add_encoded_file_descriptor_set(bytes)?;
+        Ok(pool)
+    }
+
+    /// Adds the types of an encoded file descriptor set to the pool, such as the
+    /// `FILE_DESCRIPTOR_SET` constants generated with `Config::embed_file_descriptor_sets` of
+    /// `prost-build`.
+    ///
+    /// See [`add_file_descriptor_set`](Self::add_file_descriptor_set) for details.
+    pub fn add_encoded_file_descriptor_set(&mut self, bytes: &[u8]) -> Result<(), DescriptorError> {
+        let file_descriptor_set = FileDescriptorSet::decode(bytes).map_err(|error| {
+            DescriptorError::new(format!("failed to decode file descriptor set: {error}"))
+        })?;
+        self.add_file_descriptor_set(file_descriptor_set)
+    }
+
+    /// Adds the types of a file descriptor set to the pool.
+    ///
+    /// Type names in field definitions must be fully qualified, with a leading `.`, as they are
+    /// in the output of `protoc`. Files which are already in the pool are skipped. The files
+    /// imported by the set must either be in the set or in the pool, except for the files of the
+    /// well-known types, which are added when they are missing. If an error is returned, the pool
+    /// is left unchanged.
+    pub fn add_file_descriptor_set(
+        &mut self,
+        file_descriptor_set: FileDescriptorSet,
+    ) -> Result<(), DescriptorError> {
+        let mut inner = (*self.inner).clone();
+        let mut files = Vec::<FileDescriptorProto>::new();
+        for file in file_descriptor_set.file {
+            if inner
+                .files
+                .iter()
+                .chain(&files)
+                .all(|known| known.name() != file.name())
+            {
+                files.push(file);
+            }
+        }
+
+        // Imports are resolved in a second pass, since files may precede their dependencies.
+        let mut well_known_types = None;
+        let mut index = 0;
+        while index < files.len() {
+            for dependency in files[index].dependency.clone() {
+                if inner
+                    .files
+                    .iter()
+                    .chain(&files)
+                    .any(|known| known.name() == dependency)
+                {
+                    continue;
+                }
+                let file = well_known_types
+                    .get_or_insert_with(well_known_files)
+                    .file
+                    .iter()
+                    .find(|file| file.name() == dependency)
+                    .ok_or_else(|| {
+                        DescriptorError::new(format!(
+                            "file `{}` imports `{dependency}`, which is not in the pool",
+                            files[index].name()
+                        ))
+                    })?;
+                files.push(file.clone());
+            }
+            index += 1;
+        }
+
+        let mut pending = Vec::new();
+        for (index, file) in files.iter().enumerate() {
+            let file_index = inner.files.len() + index;
+            let edition = file.resolved_edition();
+            let features = FeatureSet::edition_defaults(edition)
+                .ok_or_else(|| {
+                    DescriptorError::new(format!(
+                        "file `{}` has the unsupported edition {}",
+                        file.name(),
+                        edition.as_str_name()
+                    ))
+                })?
+                .resolve(file.options.as_ref().and_then(|o| o.features.as_ref()));
+            for message in &file.message_type {
+                inner.add_message(file_index, file.package(), message, &features, &mut pending)?;
+            }
+            for enum_type in &file.enum_type {
+                inner.add_enum(file_index, file.package(), enum_type)?;
+            }
+            for service in &file.service {
+                let full_name = full_name(file.package(), service.name());
+                inner.add_name(&full_name, TypeIndex::Service(inner.services.len()))?;
+                inner.services.push(ServiceInner {
+                    full_name,
+                    file: file_index,
+                    methods: Vec::new(),
+                });
+            }
+        }
+        for message in pending {
+            inner.resolve_fields(message)?;
+        }
+        let services = files.iter().flat_map(|file| &file.service);
+        let first_service = inner.services.len() - services.clone().count();
+        for (index, service) in services.enumerate() {
+            inner.resolve_methods(first_service + index, service)?;
+        }
+
+        inner.files.extend(files);
+        self.inner = Arc::new(inner);
+        Ok(())
+    }
+
+    /// Returns the files which have been added to the pool.
+    pub fn files(&self) -> &[FileDescriptorProto] {
+        &self.inner.files
+    }
+
+    /// Returns the file with the given name, such as `google/protobuf/duration.proto`.
+    pub fn get_file_by_name(&self, name: &str) -> Option<&FileDescriptorProto> {
+        self.inner.files.iter().find(|file| file.name() == name)
+    }
+
+    /// Returns the message type with the given fully qualified name, such as
+    /// `google.protobuf.Duration`.
+    pub fn get_message_by_name(&self, name: &str) -> Option<MessageDescriptor> {
+        match self
+            .inner
+            .names
+            .get(name.strip_prefix('.').unwrap_or(name))?
+        {
+            TypeIndex::Message(index) => Some(MessageDescriptor {
+                pool: self.clone(),
+                index: *index,
+            }),
+            TypeIndex::Enum(_) | TypeIndex::Service(_) => None,
+        }
+    }
+
+    /// Returns the enum type with the given fully qualified name.
+    pub fn get_enum_by_name(&self, name: &str) -> Option<EnumDescriptor> {
+        match self
+            .inner
+            .names
+            .get(name.strip_prefix('.').unwrap_or(name))?
+        {
+            TypeIndex::Enum(index) => Some(EnumDescriptor {
+                pool: self.clone(),
+                index: *index,
+            }),
+            TypeIndex::Message(_) | TypeIndex::Service(_) => None,
+        }
+    }
+
+    /// Returns the service with the given fully qualified name.
+    pub fn get_service_by_name(&self, name: &str) -> Option<ServiceDescriptor> {
+        match self
+            .inner
+            .names
+            .get(name.strip_prefix('.').unwrap_or(name))?
+        {
+            TypeIndex::Service(index) => Some(ServiceDescriptor {
+                pool: self.clone(),
+                index: *index,
+            }),
+            TypeIndex::Message(_) | TypeIndex::Enum(_) => None,
+        }
+    }
+
+    /// Returns all message types in the pool, including map entry types.
+    pub fn all_messages(&self) -> impl ExactSizeIterator<Item = MessageDescriptor> + '_ {
+        (0..self.inner.messages.len()).map(|index| MessageDescriptor {
+            pool: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns all enum types in the pool.
+    pub fn all_enums(&self) -> impl ExactSizeIterator<Item = EnumDescriptor> + '_ {
+        (0..self.inner.enums.len()).map(|index| EnumDescriptor {
+            pool: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns all services in the pool.
+    pub fn all_services(&self) -> impl ExactSizeIterator<Item = ServiceDescriptor> + '_ {
+        (0..self.inner.services.len()).map(|index| ServiceDescriptor {
+            pool: self.clone(),
+            index,
+        })
+    }
+}
+
+/// Decodes the file descriptor set of the well-known types.
+fn well_known_files() -> FileDescriptorSet {
+    FileDescriptorSet::decode(WELL_KNOWN_TYPES).expect("well-known types are valid")
+}
+
+impl PartialEq for DescriptorPool {
+    fn eq(&self, other: &DescriptorPool) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for DescriptorPool {}
+
+impl fmt::Debug for DescriptorPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DescriptorPool")
+            .field(
+                "files",
+                &self
+                    .inner
+                    .files
+                    .iter()
+                    .map(|file| file.name())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+fn full_name(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{scope}.{name}")
+    }
+}
+
+impl PoolInner {
+    fn add_name(&mut self, full_name: &str, index: TypeIndex) -> Result<(), DescriptorError> {
+        if self.names.insert(full_name.to_owned(), index).is_some() {
+            return Err(DescriptorError::new(format!(
+                "duplicate type name `{full_name}`"
+            )));
+        }
+        Ok(())
+    }
+
+    fn add_message<'a>(
+        &mut self,
+        file: usize,
+        scope: &str,
+        proto: &'a DescriptorProto,
+        features: &FeatureSet,
+        pending: &mut Vec<PendingMessage<'a>>,
+    ) -> Result<(), DescriptorError> {
+        let full_name = full_name(scope, proto.name());
+        let index = self.messages.len();
+        self.add_name(&full_name, TypeIndex::Message(index))?;
+        self.messages.push(MessageInner {
+            full_name: full_name.clone(),
+            file,
+            fields: Vec::new(),
+            field_numbers: BTreeMap::new(),
+            field_names: BTreeMap::new(),
+            oneofs: proto
+                .oneof_decl
+                .iter()
+                .map(|oneof| OneofInner {
+                    name: oneof.name().to_owned(),
+                    fields: Vec::new(),
+                })
+                .collect(),
+            is_map_entry: proto
+                .options
+                .as_ref()
+                .is_some_and(|options| options.map_entry()),
+        });
+        let features = features.resolve(proto.options.as_ref().and_then(|o| o.features.as_ref()));
+        pending.push(PendingMessage {
+            index,
+            proto,
+            features: features.clone(),
+        });
+
+        for nested in &proto.nested_type {
+            self.add_message(file, &full_name, nested, &features, pending)?;
+        }
+        for enum_type in &proto.enum_type {
+            self.add_enum(file, &full_name, enum_type)?;
+        }
+        Ok(())
+    }
+
+    fn add_enum(
+        &mut self,
+        file: usize,
+        scope: &str,
+        proto: &EnumDescriptorProto,
+    ) -> Result<(), DescriptorError> {
+        let full_name = full_name(scope, proto.name());
+        self.add_name(&full_name, TypeIndex::Enum(self.enums.len()))?;
+        if proto.value.is_empty() {
+            return Err(DescriptorError::new(format!(
+                "enum `{full_name}` has no values"
+            )));
+        }
+        self.enums.push(EnumInner {
+            full_name,
+            file,
+            values: proto
+                .value
+                .iter()
+                .map(|value| (value.name().to_owned(), value.number()))
+                .collect(),
+        });
+        Ok(())
+    }
+
+    fn resolve_methods(
+        &mut self,
+        index: usize,
+        proto: &ServiceDescriptorProto,
+    ) -> Result<(), DescriptorError> {
+        let mut methods = Vec::with_capacity(proto.method.len());
+        for method in &proto.method {
+            let resolve = |type_name: &str| {
+                let name = type_name.strip_prefix('.').unwrap_or(type_name);
+                match self.names.get(name) {
+                    Some(TypeIndex::Message(index)) => Ok(*index),
+                    _ => Err(DescriptorError::new(format!(
+                        "unknown message type `{name}` in method `{}` of `{}`",
+                        method.name(),
+                        self.services[index].full_name
+                    ))),
+                }
+            };
+            methods.push(MethodInner {
+                name: method.name().to_owned(),
+                input: resolve(method.input_type())?,
+                output: resolve(method.output_type())?,
+                client_streaming: method.client_streaming(),
+                server_streaming: method.server_streaming(),
+            });
+        }
+        self.services[index].methods = methods;
+        Ok(())
+    }
+
+    fn resolve_fields(&mut self, pending: PendingMessage<'_>) -> Result<(), DescriptorError> {
+        let message_name = self.messages[pending.index].full_name.clone();
+        let mut fields = Vec::with_capacity(pending.proto.field.len());
+        for field in &pending.proto.field {
+            let oneof_features = field
+                .oneof_index
+                .and_then(|index| pending.proto.oneof_decl.get(usize::try_from(index).ok()?))
+                .and_then(|oneof| oneof.options.as_ref()?.features.as_ref());
+            let features = pending
+                .features
+                .resolve(oneof_features)
+                .resolve(field.options.as_ref().and_then(|o| o.features.as_ref()));
+            let error = |description: &str| {
+                DescriptorError::new(format!(
+                    "{description} in field `{}` of `{message_name}`",
+                    field.name()
+                ))
+            };
+            let kind = match field.r#type.map(|_| field.r#type()) {
+                Some(Type::Double) => KindIndex::Double,
+                Some(Type::Float) => KindIndex::Float,
+                Some(Type::Int32) => KindIndex::Int32,
+                Some(Type::Int64) => KindIndex::Int64,
+                Some(Type::Uint32) => KindIndex::Uint32,
+                Some(Type::Uint64) => KindIndex::Uint64,
+                Some(Type::Sint32) => KindIndex::Sint32,
+                Some(Type::Sint64) => KindIndex::Sint64,
+                Some(Type::Fixed32) => KindIndex::Fixed32,
+                Some(Type::Fixed64) => KindIndex::Fixed64,
+                Some(Type::Sfixed32) => KindIndex::Sfixed32,
+                Some(Type::Sfixed64) => KindIndex::Sfixed64,
+                Some(Type::Bool) => KindIndex::Bool,
+                Some(Type::String) => KindIndex::String,
+                Some(Type::Bytes) => KindIndex::Bytes,
+                ty @ (Some(Type::Message | Type::Group | Type::Enum) | None) => {
+                    let type_name = field
+                        .type_name()
+                        .strip_prefix('.')
+                        .ok_or_else(|| error("type name is not fully qualified"))?;
+                    match (ty, self.names.get(type_name)) {
+                        (
+                            Some(Type::Message | Type::Group) | None,
+                            Some(TypeIndex::Message(index)),
+                        ) => KindIndex::Message(*index),
+                        (Some(Type::Enum) | None, Some(TypeIndex::Enum(index))) => {
+                            KindIndex::Enum(*index)
+                        }
+                        (_, Some(_)) => return Err(error("mismatched type")),
+                        (_, None) => {
+                            return Err(error(&format!("unknown type `{type_name}`")));
+                        }
+                    }
+                }
+            };
+
+            let number = u32::try_from(field.number())
+                .ok()
+                .filter(|number| (1..=prost::encoding::MAX_TAG).contains(number))
+                .ok_or_else(|| error("invalid field number"))?;
+            let cardinality = match field.label() {
+                Label::Optional if features.field_presence() == FieldPresence::LegacyRequired => {
+                    Cardinality::Required
+                }
+                Label::Optional => Cardinality::Optional,
+                Label::Required => Cardinality::Required,
+                Label::Repeated => Cardinality::Repeated,
+            };
+            let packable = !matches!(
+                kind,
+                KindIndex::String | KindIndex::Bytes | KindIndex::Message(_)
+            );
+            let is_packed = cardinality == Cardinality::Repeated
+                && packable
+                && field
+                    .options
+                    .as_ref()
+                    .and_then(|options| options.packed)
+                    .unwrap_or(features.repeated_field_encoding() == RepeatedFieldEncoding::Packed);
+            // Map entries are always length-delimited, even when the delimited encoding is inherited.
+            let is_group = match kind {
+                _ if field.r#type() == Type::Group => true,
+                KindIndex::Message(index) => {
+                    features.message_encoding() == MessageEncoding::Delimited
+                        && !self.messages[index].is_map_entry
+                        && !self.messages[pending.index].is_map_entry
+                }
+                _ => false,
+            };
+            let proto3_optional = field.proto3_optional();
+            let supports_presence = cardinality != Cardinality::Repeated
+                && (features.field_presence() != FieldPresence::Implicit
+                    || proto3_optional
+                    || field.oneof_index.is_some()
+                    || matches!(kind, KindIndex::Message(_)));
+            let oneof = match field.oneof_index {
+                Some(index) if !proto3_optional => {
+                    let index = usize::try_from(index)
+                        .ok()
+                        .filter(|&index| index < self.messages[pending.index].oneofs.len())
+                        .ok_or_else(|| error("invalid oneof index"))?;
+                    Some(index)
+                }
+                _ => None,
+            };
+
+            fields.push(FieldInner {
+                name: field.name().to_owned(),
+                json_name: field
+                    .json_name
+                    .clone()
+                    .unwrap_or_else(|| json_name(field.name())),
+                number,
+                cardinality,
+                kind,
+                is_group,
+                is_packed,
+                supports_presence,
+                oneof,
+                default_value: field.default_value.clone(),
+            });
+        }
+
+        let message = &mut self.messages[pending.index];
+        if message.is_map_entry {
+            let valid_key = |field: &FieldInner| {
+                field.number == 1
+                    && !matches!(
+                        field.kind,
+                        KindIndex::Double
+                            | KindIndex::Float
+                            | KindIndex::Bytes
+                            | KindIndex::Message(_)
+                            | KindIndex::Enum(_)
+                    )
+            };
+            match &fields[..] {
+                [key, value] if valid_key(key) && value.number == 2 => {}
+                _ => {
+                    return Err(DescriptorError::new(format!(
+                        "invalid map entry `{message_name}`"
+                    )));
+                }
+            }
+        }
+        for (index, field) in fields.iter().enumerate() {
+            if message.field_numbers.insert(field.number, index).is_some() {
+                return Err(DescriptorError::new(format!(
+                    "duplicate field number {} in `{message_name}`",
+                    field.number
+                )));
+            }
+            message.field_names.insert(field.name.clone(), index);
+            if let Some(oneof) = field.oneof {
+                message.oneofs[oneof].fields.push(index);
+            }
+        }
+        // Synthetic oneofs of proto3 optional fields are not exposed.
+        message.oneofs.retain(|oneof| !oneof.fields.is_empty());
+        for (oneof_index, oneof) in message.oneofs.iter().enumerate() {
+            for &field in &oneof.fields {
+                fields[field].oneof = Some(oneof_index);
+            }
+        }
+        message.fields = fields;
+        Ok(())
+    }
+}
+
+/// Returns the default JSON name of a field, which is its name in lowerCamelCase.
+fn json_name(name: &str) -> String {
+    let mut json_name = String::with_capacity(name.len());
+    let mut capitalize_next = false;
+    for c in name.chars() {
+        if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            json_name.push(c.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            json_name.push(c);
+        }
+    }
+    json_name
+}
+
+/// A message type.
+#[derive(Clone, PartialEq, Eq)]
+pub struct MessageDescriptor {
+    pool: DescriptorPool,
+    index: usize,
+}
+
+impl MessageDescriptor {
+    fn inner(&self) -> &MessageInner {
+        &self.pool.inner.messages[self.index]
+    }
+
+    /// Returns the pool which contains the message type.
+    pub fn parent_pool(&self) -> &DescriptorPool {
+        &self.pool
+    }
+
+    /// Returns the file which defines the message type.
+    pub fn parent_file(&self) -> &FileDescriptorProto {
+        &self.pool.inner.files[self.inner().file]
+    }
+
+    /// Returns the fully qualified name of the message type, such as `google.protobuf.Duration`.
+    pub fn full_name(&self) -> &str {
+        &self.inner().full_name
+    }
+
+    /// Returns the name of the message type, without its package and enclosing messages.
+    pub fn name(&self) -> &str {
+        let full_name = self.full_name();
+        full_name.rsplit('.').next().unwrap_or(full_name)
+    }
+
+    /// Returns `true` if the message type is the entry type of a map field.
+    pub fn is_map_entry(&self) -> bool {
+        self.inner().is_map_entry
+    }
+
+    /// Returns the fields of the message type, in declaration order.
+    pub fn fields(&self) -> impl ExactSizeIterator<Item = FieldDescriptor> + '_ {
+        (0..self.inner().fields.len()).map(|index| FieldDescriptor {
+            message: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns the oneofs of the message type, in declaration order.
+    ///
+    /// The synthetic oneofs of proto3 `optional` fields are not included.
+    pub fn oneofs(&self) -> impl ExactSizeIterator<Item = OneofDescriptor> + '_ {
+        (0..self.inner().oneofs.len()).map(|index| OneofDescriptor {
+            message: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns the field with the given number.
+    pub fn get_field(&self, number: u32) -> Option<FieldDescriptor> {
+        self.inner()
+            .field_numbers
+            .get(&number)
+            .map(|&index| FieldDescriptor {
+                message: self.clone(),
+                index,
+            })
+    }
+
+    /// Returns the field with the given name, as it appears in the `.proto` file.
+    pub fn get_field_by_name(&self, name: &str) -> Option<FieldDescriptor> {
+        self.inner()
+            .field_names
+            .get(name)
+            .map(|&index| FieldDescriptor {
+                message: self.clone(),
+                index,
+            })
+    }
+
+    /// Returns the key field of a map entry type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message type is not a map entry type.
+    pub fn map_entry_key_field(&self) -> FieldDescriptor {
+        assert!(
+            self.is_map_entry(),
+            "{} is not a map entry",
+            self.full_name()
+        );
+        self.get_field(1).expect("map entry key field")
+    }
+
+    /// Returns the value field of a map entry type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message type is not a map entry type.
+    pub fn map_entry_value_field(&self) -> FieldDescriptor {
+        assert!(
+            self.is_map_entry(),
+            "{} is not a map entry",
+            self.full_name()
+        );
+        self.get_field(2).expect("map entry value field")
+    }
+}
+
+impl fmt::Debug for MessageDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MessageDescriptor")
+            .field(&self.full_name())
+            .finish()
+    }
+}
+
+/// The cardinality of a field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cardinality {
+    /// A singular field, which may be unset.
+    Optional,
+    /// A singular proto2 field, which must be set.
+    Required,
+    /// A repeated field, including map fields.
+    Repeated,
+}
+
+/// The type of a field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Double,
+    Float,
+    Int32,
+    Int64,
+    Uint32,
+    Uint64,
+    Sint32,
+    Sint64,
+    Fixed32,
+    Fixed64,
+    Sfixed32,
+    Sfixed64,
+    Bool,
+    String,
+    Bytes,
+    /// A message or group type.
+    Message(MessageDescriptor),
+    /// An enum type.
+    Enum(EnumDescriptor),
+}
+
+impl Kind {
+    /// Returns the message type, if this is a message or group type.
+    pub fn as_message(&self) -> Option<&MessageDescriptor> {
+        match self {
+            Kind::Message(message) => Some(message),
+            _ => None,
+        }
+    }
+
+    /// Returns the enum type, if this is an enum type.
+    pub fn as_enum(&self) -> Option<&EnumDescriptor> {
+        match self {
+            Kind::Enum(enum_type) => Some(enum_type),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if repeated fields of this type may use the packed encoding.
+    pub fn is_packable(&self) -> bool {
+        !matches!(self, Kind::String | Kind::Bytes | Kind::Message(_))
+    }
+}
+
+/// A field of a message type.
+#[derive(Clone, PartialEq, Eq)]
+pub struct FieldDescriptor {
+    message: MessageDescriptor,
+    index: usize,
+}
+
+impl FieldDescriptor {
+    fn inner(&self) -> &FieldInner {
+        &self.message.inner().fields[self.index]
+    }
+
+    /// Returns the message type which contains the field.
+    pub fn containing_message(&self) -> &MessageDescriptor {
+        &self.message
+    }
+
+    /// Returns the name of the field, as it appears in the `.proto` file.
+    pub fn name(&self) -> &str {
+        &self.inner().name
+    }
+
+    /// Returns the name of the field in the JSON mapping.
+    pub fn json_name(&self) -> &str {
+        &self.inner().json_name
+    }
+
+    /// Returns the field number.
+    pub fn number(&self) -> u32 {
+        self.inner().number
+    }
+
+    /// Returns the cardinality of the field.
+    pub fn cardinality(&self) -> Cardinality {
+        self.inner().cardinality
+    }
+
+    /// Returns the type of the field. For map fields, this is the map entry type.
+    pub fn kind(&self) -> Kind {
+        let pool = &self.message.pool;
+        match self.inner().kind {
+            KindIndex::Double => Kind::Double,
+            KindIndex::Float => Kind::Float,
+            KindIndex::Int32 => Kind::Int32,
+            KindIndex::Int64 => Kind::Int64,
+            KindIndex::Uint32 => Kind::Uint32,
+            KindIndex::Uint64 => Kind::Uint64,
+            KindIndex::Sint32 => Kind::Sint32,
+            KindIndex::Sint64 => Kind::Sint64,
+            KindIndex::Fixed32 => Kind::Fixed32,
+            KindIndex::Fixed64 => Kind::Fixed64,
+            KindIndex::Sfixed32 => Kind::Sfixed32,
+            KindIndex::Sfixed64 => Kind::Sfixed64,
+            KindIndex::Bool => Kind::Bool,
+            KindIndex::String => Kind::String,
+            KindIndex::Bytes => Kind::Bytes,
+            KindIndex::Message(index) => Kind::Message(MessageDescriptor {
+                pool: pool.clone(),
+                index,
+            }),
+            KindIndex::Enum(index) => Kind::Enum(EnumDescriptor {
+                pool: pool.clone(),
+                index,
+            }),
+        }
+    }
+
+    /// Returns `true` if the field is repeated, and not a map field.
+    pub fn is_list(&self) -> bool {
+        self.cardinality() == Cardinality::Repeated && !self.is_map()
+    }
+
+    /// Returns `true` if the field is a map field.
+    pub fn is_map(&self) -> bool {
+        match self.inner().kind {
+            KindIndex::Message(index) => {
+                self.cardinality() == Cardinality::Repeated
+                    && self.message.pool.inner.messages[index].is_map_entry
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the field is a group, which uses the group wire encoding.
+    pub fn is_group(&self) -> bool {
+        self.inner().is_group
+    }
+
+    /// Returns `true` if the field is repeated and uses the packed encoding.
+    pub fn is_packed(&self) -> bool {
+        self.inner().is_packed
+    }
+
+    /// Returns `true` if the field distinguishes between being unset and being set to its
+    /// default value.
+    pub fn supports_presence(&self) -> bool {
+        self.inner().supports_presence
+    }
+
+    /// Returns the oneof which contains the field, if any.
+    pub fn containing_oneof(&self) -> Option<OneofDescriptor> {
+        self.inner().oneof.map(|index| OneofDescriptor {
+            message: self.message.clone(),
+            index,
+        })
+    }
+
+    /// Returns the explicit default value of a proto2 field, as it appears in the descriptor.
+    pub(crate) fn default_value_text(&self) -> Option<&str> {
+        self.inner().default_value.as_deref()
+    }
+}
+
+impl fmt::Debug for FieldDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FieldDescriptor")
+            .field("message", &self.message.full_name())
+            .field("name", &self.name())
+            .field("number", &self.number())
+            .finish()
+    }
+}
+
+/// A oneof of a message type.
+#[derive(Clone, PartialEq, Eq)]
+pub struct OneofDescriptor {
+    message: MessageDescriptor,
+    index: usize,
+}
+
+impl OneofDescriptor {
+    fn inner(&self) -> &OneofInner {
+        &self.message.inner().oneofs[self.index]
+    }
+
+    /// Returns the message type which contains the oneof.
+    pub fn containing_message(&self) -> &MessageDescriptor {
+        &self.message
+    }
+
+    /// Returns the name of the oneof.
+    pub fn name(&self) -> &str {
+        &self.inner().name
+    }
+
+    /// Returns the fields of the oneof.
+    pub fn fields(&self) -> impl ExactSizeIterator<Item = FieldDescriptor> + '_ {
+        self.inner().fields.iter().map(|&index| FieldDescriptor {
+            message: self.message.clone(),
+            index,
+        })
+    }
+}
+
+impl fmt::Debug for OneofDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OneofDescriptor")
+            .field("message", &self.message.full_name())
+            .field("name", &self.name())
+            .finish()
+    }
+}
+
+/// An enum type.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EnumDescriptor {
+    pool: DescriptorPool,
+    index: usize,
+}
+
+impl EnumDescriptor {
+    fn inner(&self) -> &EnumInner {
+        &self.pool.inner.enums[self.index]
+    }
+
+    /// Returns the pool which contains the enum type.
+    pub fn parent_pool(&self) -> &DescriptorPool {
+        &self.pool
+    }
+
+    /// Returns the file which defines the enum type.
+    pub fn parent_file(&self) -> &FileDescriptorProto {
+        &self.pool.inner.files[self.inner().file]
+    }
+
+    /// Returns the fully qualified name of the enum type.
+    pub fn full_name(&self) -> &str {
+        &self.inner().full_name
+    }
+
+    /// Returns the name of the enum type, without its package and enclosing messages.
+    pub fn name(&self) -> &str {
+        let full_name = self.full_name();
+        full_name.rsplit('.').next().unwrap_or(full_name)
+    }
+
+    /// Returns the values of the enum type, in declaration order.
+    pub fn values(&self) -> impl ExactSizeIterator<Item = EnumValueDescriptor> + '_ {
+        (0..self.inner().values.len()).map(|index| EnumValueDescriptor {
+            parent: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns the default value of the enum type, which is its first value.
+    pub fn default_value(&self) -> EnumValueDescriptor {
+        EnumValueDescriptor {
+            parent: self.clone(),
+            index: 0,
+        }
+    }
+
+    /// Returns the first value with the given number.
+    pub fn get_value(&self, number: i32) -> Option<EnumValueDescriptor> {
+        self.values().find(|value| value.number() == number)
+    }
+
+    /// Returns the value with the given name.
+    pub fn get_value_by_name(&self, name: &str) -> Option<EnumValueDescriptor> {
+        self.values().find(|value| value.name() == name)
+    }
+}
+
+impl fmt::Debug for EnumDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EnumDescriptor")
+            .field(&self.full_name())
+            .finish()
+    }
+}
+
+/// A value of an enum type.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EnumValueDescriptor {
+    parent: EnumDescriptor,
+    index: usize,
+}
+
+impl EnumValueDescriptor {
+    /// Returns the enum type which contains the value.
+    pub fn parent_enum(&self) -> &EnumDescriptor {
+        &self.parent
+    }
+
+    /// Returns the name of the value.
+    pub fn name(&self) -> &str {
+        &self.parent.inner().values[self.index].0
+    }
+
+    /// Returns the number of the value.
+    pub fn number(&self) -> i32 {
+        self.parent.inner().values[self.index].1
+    }
+}
+
+impl fmt::Debug for EnumValueDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnumValueDescriptor")
+            .field("name", &self.name())
+            .field("number", &self.number())
+            .finish()
+    }
+}
+
+/// A service.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ServiceDescriptor {
+    pool: DescriptorPool,
+    index: usize,
+}
+
+impl ServiceDescriptor {
+    fn inner(&self) -> &ServiceInner {
+        &self.pool.inner.services[self.index]
+    }
+
+    /// Returns the pool which contains the service.
+    pub fn parent_pool(&self) -> &DescriptorPool {
+        &self.pool
+    }
+
+    /// Returns the file which defines the service.
+    pub fn parent_file(&self) -> &FileDescriptorProto {
+        &self.pool.inner.files[self.inner().file]
+    }
+
+    /// Returns the fully qualified name of the service.
+    pub fn full_name(&self) -> &str {
+        &self.inner().full_name
+    }
+
+    /// Returns the name of the service, without its package.
+    pub fn name(&self) -> &str {
+        let full_name = self.full_name();
+        full_name.rsplit('.').next().unwrap_or(full_name)
+    }
+
+    /// Returns the methods of the service, in declaration order.
+    pub fn methods(&self) -> impl ExactSizeIterator<Item = MethodDescriptor> + '_ {
+        (0..self.inner().methods.len()).map(|index| MethodDescriptor {
+            service: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns the method with the given name.
+    pub fn get_method_by_name(&self, name: &str) -> Option<MethodDescriptor> {
+        self.methods().find(|method| method.name() == name)
+    }
+}
+
+impl fmt::Debug for ServiceDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ServiceDescriptor")
+            .field(&self.full_name())
+            .finish()
+    }
+}
+
+/// A method of a service.
+#[derive(Clone, PartialEq, Eq)]
+pub struct MethodDescriptor {
+    service: ServiceDescriptor,
+    index: usize,
+}
+
+impl MethodDescriptor {
+    fn inner(&self) -> &MethodInner {
+        &self.service.inner().methods[self.index]
+    }
+
+    /// Returns the service which contains the method.
+    pub fn parent_service(&self) -> &ServiceDescriptor {
+        &self.service
+    }
+
+    /// Returns the name of the method.
+    pub fn name(&self) -> &str {
+        &self.inner().name
+    }
+
+    /// Returns the fully qualified name of the method, such as `my.package.Greeter.SayHello`.
+    pub fn full_name(&self) -> String {
+        format!("{}.{}", self.service.full_name(), self.name())
+    }
+
+    /// Returns the request message type of the method.
+    pub fn input(&self) -> MessageDescriptor {
+        MessageDescriptor {
+            pool: self.service.pool.clone(),
+            index: self.inner().input,
+        }
+    }
+
+    /// Returns the response message type of the method.
+    pub fn output(&self) -> MessageDescriptor {
+        MessageDescriptor {
+            pool: self.service.pool.clone(),
+            index: self.inner().output,
+        }
+    }
+
+    /// Returns `true` if the client sends a stream of requests.
+    pub fn is_client_streaming(&self) -> bool {
+        self.inner().client_streaming
+    }
+
+    /// Returns `true` if the server sends a stream of responses.
+    pub fn is_server_streaming(&self) -> bool {
+        self.inner().server_streaming
+    }
+}
+
+impl fmt::Debug for MethodDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MethodDescriptor")
+            .field(&self.full_name())
+            .finish()
+    }
+}
@@ -0,0 +1,1044 @@
+//! Messages whose type is only known at runtime.
+
+use core::fmt;
+
+use prost::alloc::borrow::Cow;
+use prost::alloc::collections::BTreeMap;
+use prost::alloc::vec::Vec;
+use prost::bytes::{Buf, BufMut};
+use prost::encoding::{
+    self, encode_key, encode_varint, encoded_len_varint, key_len, merge_loop, DecodeContext,
+    WireType,
+};
+use prost::{DecodeError, Message, UnknownFieldSet};
+
+use super::{FieldDescriptor, Kind, MessageDescriptor, Value};
+
+/// A message whose type is described by a [`MessageDescriptor`].
+///
+/// Fields are accessed through their [`FieldDescriptor`]s, or by name or number. The message
+/// implements [`Message`], and is encoded and decoded exactly as a generated type for the same
+/// message type would be. Fields which are not part of the message type are preserved as unknown
+/// fields.
+#[derive(Clone, PartialEq)]
+pub struct DynamicMessage {
+    desc: MessageDescriptor,
+    fields: BTreeMap<u32, Value>,
+    unknown: UnknownFieldSet,
+}
+
+impl DynamicMessage {
+    /// Creates a message of the given type, with all fields unset.
+    pub fn new(desc: MessageDescriptor) -> DynamicMessage {
+        DynamicMessage {
+            desc,
+            fields: BTreeMap::new(),
+            unknown: UnknownFieldSet::new(),
+        }
+    }
+
+    /// Decodes a message of the given type from a buffer.
+    pub fn decode(desc: MessageDescriptor, buf: impl Buf) -> Result<DynamicMessage, DecodeError> {
+        let mut message = DynamicMessage::new(desc);
+        message.merge(buf)?;
+        Ok(message)
+    }
+
+    /// Returns the type of the message.
+    pub fn descriptor(&self) -> &MessageDescriptor {
+        &self.desc
+    }
+
+    /// Returns `true` if the field is set.
+    ///
+    /// Fields without presence are considered set if they hold a value other than their default,
+    /// and repeated fields if they are not empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field does not belong to the message type.
+    pub fn has_field(&self, field: &FieldDescriptor) -> bool {
+        self.check_field(field);
+        match self.fields.get(&field.number()) {
+            Some(value) => field.supports_presence() || !value.is_default_for_field(field),
+            None => false,
+        }
+    }
+
+    /// Returns the value of the field, or its default value if it is not set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field does not belong to the message type.
+    pub fn get_field(&self, field: &FieldDescriptor) -> Cow<'_, Value> {
+        self.check_field(field);
+        match self.fields.get(&field.number()) {
+            Some(value) => Cow::Borrowed(value),
+            None => Cow::Owned(Value::default_value_for_field(field)),
+        }
+    }
+
+    /// Returns a mutable reference to the value of the field, which is set to its default value
+    /// if it is not already set.
+    ///
+    /// Any other field of the same oneof is cleared.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field does not belong to the message type. Setting the value to one which
+    /// is not valid for the field causes a panic when the message is encoded.
+    pub fn get_field_mut(&mut self, field: &FieldDescriptor) -> &mut Value {
+        self.check_field(field);
+        self.clear_oneof(field);
+        self.fields
+            .entry(field.number())
+            .or_insert_with(|| Value::default_value_for_field(field))
+    }
+
+    /// Sets the value of the field.
+    ///
+    /// Any other field of the same oneof is cleared.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field does not belong to the message type, or if the value is not valid for
+    /// the field.
+    pub fn set_field(&mut self, field: &FieldDescriptor, value: Value) {
+        self.check_field(field);
+        assert!(
+            value.is_valid_for_field(field),
+            "invalid value {:?} for field {}.{}",
+            value,
+            self.desc.full_name(),
+            field.name()
+        );
+        self.store(field, value);
+    }
+
+    /// Clears the field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field does not belong to the message type.
+    pub fn clear_field(&mut self, field: &FieldDescriptor) {
+        self.check_field(field);
+        self.fields.remove(&field.number());
+    }
+
+    /// Returns the value of the field with the given name, or `None` if the message type has no
+    /// such field.
+    pub fn get_field_by_name(&self, name: &str) -> Option<Cow<'_, Value>> {
+        let field = self.desc.get_field_by_name(name)?;
+        Some(self.get_field(&field))
+    }
+
+    /// Returns the value of the field with the given number, or `None` if the message type has no
+    /// such field.
+    pub fn get_field_by_number(&self, number: u32) -> Option<Cow<'_, Value>> {
+        let field = self.desc.get_field(number)?;
+        Some(self.get_field(&field))
+    }
+
+    /// Sets the value of the field with the given name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message type has no such field, or if the value is not valid for the field.
+    pub fn set_field_by_name(&mut self, name: &str, value: Value) {
+        let field = self.desc.get_field_by_name(name).unwrap_or_else(|| {
+            panic!("no field named {name} in {}", self.desc.full_name());
+        });
+        self.set_field(&field, value);
+    }
+
+    /// Sets the value of the field with the given number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message type has no such field, or if the value is not valid for the field.
+    pub fn set_field_by_number(&mut self, number: u32, value: Value) {
+        let field = self.desc.get_field(number).unwrap_or_else(|| {
+            panic!("no field number {number} in {}", self.desc.full_name());
+        });
+        self.set_field(&field, value);
+    }
+
+    /// Returns the fields which are set, in field number order.
+    pub fn fields(&self) -> impl Iterator<Item = (FieldDescriptor, &Value)> + '_ {
+        self.fields.iter().filter_map(move |(&number, value)| {
+            let field = self.desc.get_field(number).expect("field of message");
+            self.has_field(&field).then_some((field, value))
+        })
+    }
+
+    /// Returns the fields which were decoded, but are not part of the message type.
+    pub fn unknown_fields(&self) -> &UnknownFieldSet {
+        &self.unknown
+    }
+
+    fn check_field(&self, field: &FieldDescriptor) {
+        assert!(
+            *field.containing_message() == self.desc,
+            "field {}.{} does not belong to {}",
+            field.containing_message().full_name(),
+            field.name(),
+            self.desc.full_name()
+        );
+    }
+
+    fn clear_oneof(&mut self, field: &FieldDescriptor) {
+        if let Some(oneof) = field.containing_oneof() {
+            for sibling in oneof.fields() {
+                if sibling.number() != field.number() {
+                    self.fields.remove(&sibling.number());
+                }
+            }
+        }
+    }
+
+    /// Stores a value known to be valid for the field, dropping default values of fields without
+    /// presence.
+    fn store(&mut self, field: &FieldDescriptor, value: Value) {
+        self.clear_oneof(field);
+        if !field.supports_presence() && value.is_default_for_field(field) {
+            self.fields.remove(&field.number());
+        } else {
+            self.fields.insert(field.number(), value);
+        }
+    }
+}
+
+impl Message for DynamicMessage {
+    fn encode_raw(&self, buf: &mut impl BufMut) {
+        for (field, value) in self.fields() {
+            encode_field(&field, value, buf);
+        }
+        self.unknown.encode_raw(buf);
+    }
+
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        let field = match self.desc.get_field(tag) {
+            Some(field) => field,
+            None => return self.unknown.merge_field(tag, wire_type, buf, ctx),
+        };
+        let kind = field.kind();
+        let value = if field.is_map() {
+            let entry_desc = kind.as_message().expect("map entry").clone();
+            let mut entry = DynamicMessage::new(entry_desc.clone());
+            encoding::message::merge(wire_type, &mut entry, buf, ctx)?;
+            let key = entry
+                .get_field(&entry_desc.map_entry_key_field())
+                .into_owned()
+                .into_map_key()
+                .expect("valid map key");
+            let value = entry
+                .get_field(&entry_desc.map_entry_value_field())
+                .into_owned();
+            let mut entries = match self.fields.remove(&tag) {
+                Some(Value::Map(entries)) => entries,
+                _ => BTreeMap::new(),
+            };
+            entries.insert(key, value);
+            Value::Map(entries)
+        } else if field.is_list() {
+            let mut values = match self.fields.remove(&tag) {
+                Some(Value::List(values)) => values,
+                _ => Vec::new(),
+            };
+            if wire_type == WireType::LengthDelimited && kind.is_packable() {
+                merge_loop(&mut values, buf, ctx, |values, buf, ctx| {
+                    values.push(decode_value(tag, &kind, wire_type_of(&kind), buf, ctx)?);
+                    Ok(())
+                })?;
+            } else {
+                values.push(decode_value(tag, &kind, wire_type, buf, ctx)?);
+            }
+            Value::List(values)
+        } else if let Kind::Message(desc) = &kind {
+            // Singular message fields are merged into their current value.
+            let mut message = match self.fields.remove(&tag) {
+                Some(Value::Message(message)) => message,
+                _ => DynamicMessage::new(desc.clone()),
+            };
+            merge_message(tag, &mut message, wire_type, buf, ctx)?;
+            Value::Message(message)
+        } else {
+            decode_value(tag, &kind, wire_type, buf, ctx)?
+        };
+        self.store(&field, value);
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.fields()
+            .map(|(field, value)| field_len(&field, value))
+            .sum::<usize>()
+            + self.unknown.encoded_len()
+    }
+
+    fn clear(&mut self) {
+        self.fields.clear();
+        self.unknown.clear();
+    }
+}
+
+impl fmt::Debug for DynamicMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut builder = f.debug_struct(self.desc.name());
+        for (field, value) in self.fields() {
+            builder.field(field.name(), value);
+        }
+        if !self.unknown.is_empty() {
+            builder.field("unknown_fields", &self.unknown);
+        }
+        builder.finish()
+    }
+}
+
+/// Returns the wire type of singular values of the given type, other than groups.
+fn wire_type_of(kind: &Kind) -> WireType {
+    match kind {
+        Kind::Double | Kind::Fixed64 | Kind::Sfixed64 => WireType::SixtyFourBit,
+        Kind::Float | Kind::Fixed32 | Kind::Sfixed32 => WireType::ThirtyTwoBit,
+        Kind::String | Kind::Bytes | Kind::Message(_) => WireType::LengthDelimited,
+        _ => WireType::Varint,
+    }
+}
+
+fn merge_message(
+    tag: u32,
+    message: &mut DynamicMessage,
+    wire_type: WireType,
+    buf: &mut impl Buf,
+    ctx: DecodeContext,
+) -> Result<(), DecodeError> {
+    // Groups and length-delimited messages are interchangeable on the wire.
+    if wire_type == WireType::StartGroup {
+        encoding::group::merge(tag, wire_type, message, buf, ctx)
+    } else {
+        encoding::message::merge(wire_type, message, buf, ctx)
+    }
+}
+
+/// Decodes a singular value of the given type.
+fn decode_value(
+    tag: u32,
+    kind: &Kind,
+    wire_type: WireType,
+    buf: &mut impl Buf,
+    ctx: DecodeContext,
+) -> Result<Value, DecodeError> {
+    macro_rules! merge {
+        ($module:ident, $variant:ident) => {{
+            let mut value = Default::default();
+            encoding::$module::merge(wire_type, &mut value, buf, ctx)?;
+            Value::$variant(value)
+        }};
+    }
+
+    Ok(match kind {
+        Kind::Double => merge!(double, F64),
+        Kind::Float => merge!(float, F32),
+        Kind::Int32 => merge!(int32, I32),
+        Kind::Int64 => merge!(int64, I64),
+        Kind::Uint32 => merge!(uint32, U32),
+        Kind::Uint64 => merge!(uint64, U64),
+        Kind::Sint32 => merge!(sint32, I32),
+        Kind::Sint64 => merge!(sint64, I64),
+        Kind::Fixed32 => merge!(fixed32, U32),
+        Kind::Fixed64 => merge!(fixed64, U64),
+        Kind::Sfixed32 => merge!(sfixed32, I32),
+        Kind::Sfixed64 => merge!(sfixed64, I64),
+        Kind::Bool => merge!(bool, Bool),
+        Kind::String => merge!(string, String),
+        Kind::Bytes => merge!(bytes, Bytes),
+        Kind::Enum(_) => merge!(int32, EnumNumber),
+        Kind::Message(desc) => {
+            let mut message = DynamicMessage::new(desc.clone());
+            merge_message(tag, &mut message, wire_type, buf, ctx)?;
+            Value::Message(message)
+        }
+    })
+}
+
+fn encode_field(field: &FieldDescriptor, value: &Value, buf: &mut impl BufMut) {
+    let number = field.number();
+    let kind = field.kind();
+    let group = field.is_group();
+    match value {
+        Value::List(values) if field.is_packed() => {
+            encode_key(number, WireType::LengthDelimited, buf);
+            let len = values
+                .iter()
+                .map(|value| scalar_len(&kind, value))
+                .sum::<usize>();
+            encode_varint(len as u64, buf);
+            for value in values {
+                encode_scalar(&kind, value, buf);
+            }
+        }
+        Value::List(values) => {
+            for value in values {
+                encode_value(number, &kind, group, value, buf);
+            }
+        }
+        Value::Map(entries) => {
+            let entry = kind.as_message().expect("map entry");
+            let key_kind = entry.map_entry_key_field().kind();
+            let value_field = entry.map_entry_value_field();
+            let value_kind = value_field.kind();
+            let value_group = value_field.is_group();
+            for (key, value) in entries {
+                let key = Value::from(key.clone());
+                encode_key(number, WireType::LengthDelimited, buf);
+                let len = value_len(1, &key_kind, false, &key)
+                    + value_len(2, &value_kind, value_group, value);
+                encode_varint(len as u64, buf);
+                encode_value(1, &key_kind, false, &key, buf);
+                encode_value(2, &value_kind, value_group, value, buf);
+            }
+        }
+        value => encode_value(number, &kind, group, value, buf),
+    }
+}
+
+fn field_len(field: &FieldDescriptor, value: &Value) -> usize {
+    let number = field.number();
+    let kind = field.kind();
+    let group = field.is_group();
+    match value {
+        Value::List(values) if field.is_packed() => {
+            let len = values
+                .iter()
+                .map(|value| scalar_len(&kind, value))
+                .sum::<usize>();
+            key_len(number) + encoded_len_varint(len as u64) + len
+        }
+        Value::List(values) => values
+            .iter()
+            .map(|value| value_len(number, &kind, group, value))
+            .sum(),
+        Value::Map(entries) => {
+            let entry = kind.as_message().expect("map entry");
+            let key_kind = entry.map_entry_key_field().kind();
+            let value_field = entry.map_entry_value_field();
+            let value_kind = value_field.kind();
+            let value_group = value_field.is_group();
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    let len = value_len(1, &key_kind, false, &Value::from(key.clone()))
+                        + value_len(2, &value_kind, value_group, value);
+                    key_len(number) + encoded_len_varint(len as u64) + len
+                })
+                .sum()
+        }
+        value => value_len(number, &kind, group, value),
+    }
+}
+
+fn encode_value(number: u32, kind: &Kind, group: bool, value: &Value, buf: &mut impl BufMut) {
+    match value {
+        Value::Message(message) if group => encoding::group::encode(number, message, buf),
+        Value::Message(message) => encoding::message::encode(number, message, buf),
+        Value::String(value) => encoding::string::encode(number, value, buf),
+        Value::Bytes(value) => encoding::bytes::encode(number, value, buf),
+        value => {
+            encode_key(number, wire_type_of(kind), buf);
+            encode_scalar(kind, value, buf);
+        }
+    }
+}
+
+fn value_len(number: u32, kind: &Kind, group: bool, value: &Value) -> usize {
+    match value {
+        Value::Message(message) if group => encoding::group::encoded_len(number, message),
+        Value::Message(message) => encoding::message::encoded_len(number, message),
+        Value::String(value) => encoding::string::encoded_len(number, value),
+        Value::Bytes(value) => encoding::bytes::encoded_len(number, value),
+        value => key_len(number) + scalar_len(kind, value),
+    }
+}
+
+/// Encodes a numeric value, without a key.
+fn encode_scalar(kind: &Kind, value: &Value, buf: &mut impl BufMut) {
+    match (kind, value) {
+        (Kind::Sint32, &Value::I32(value)) => encode_varint(zigzag32(value), buf),
+        (Kind::Sint64, &Value::I64(value)) => encode_varint(zigzag64(value), buf),
+        (Kind::Sfixed32, &Value::I32(value)) => buf.put_i32_le(value),
+        (Kind::Sfixed64, &Value::I64(value)) => buf.put_i64_le(value),
+        (Kind::Fixed32, &Value::U32(value)) => buf.put_u32_le(value),
+        (Kind::Fixed64, &Value::U64(value)) => buf.put_u64_le(value),
+        (_, &Value::F32(value)) => buf.put_f32_le(value),
+        (_, &Value::F64(value)) => buf.put_f64_le(value),
+        (_, value) => encode_varint(varint(value), buf),
+    }
+}
+
+fn scalar_len(kind: &Kind, value: &Value) -> usize {
+    match (kind, value) {
+        (Kind::Sint32, &Value::I32(value)) => encoded_len_varint(zigzag32(value)),
+        (Kind::Sint64, &Value::I64(value)) => encoded_len_varint(zigzag64(value)),
+        (Kind::Sfixed32 | Kind::Fixed32 | Kind::Float, _) => 4,
+        (Kind::Sfixed64 | Kind::Fixed64 | Kind::Double, _) => 8,
+        (_, value) => encoded_len_varint(varint(value)),
+    }
+}
+
+fn varint(value: &Value) -> u64 {
+    match *value {
+        Value::Bool(value) => u64::from(value),
+        Value::I32(value) | Value::EnumNumber(value) => value as u64,
+        Value::I64(value) => value as u64,
+        Value::U32(value) => u64::from(value),
+        Value::U64(value) => value,
+        ref value => panic!("invalid numeric value {value:?}"),
+    }
+}
+
+fn zigzag32(value: i32) -> u64 {
+    ((value << 1) ^ (value >> 31)) as u32 as u64
+}
+
+fn zigzag64(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use prost::alloc::borrow::ToOwned;
+    use prost::alloc::string::ToString;
+    use prost::alloc::vec;
+
+    use crate::feature_set::{FieldPresence, MessageEncoding, RepeatedFieldEncoding};
+    use crate::field_descriptor_proto::{Label, Type};
+    use crate::reflect::{Cardinality, DescriptorPool, MapKey};
+    use crate::{
+        DescriptorProto, Edition, EnumDescriptorProto, EnumValueDescriptorProto, FeatureSet,
+        FieldDescriptorProto, FieldOptions, FileDescriptorProto, FileDescriptorSet, FileOptions,
+        MessageOptions, MethodDescriptorProto, OneofDescriptorProto, ServiceDescriptorProto,
+    };
+
+    fn field(name: &str, number: i32, label: Label, ty: Type) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            number: Some(number),
+            label: Some(label as i32),
+            r#type: Some(ty as i32),
+            ..Default::default()
+        }
+    }
+
+    fn typed_field(name: &str, number: i32, label: Label, type_name: &str) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            number: Some(number),
+            label: Some(label as i32),
+            type_name: Some(type_name.to_owned()),
+            ..Default::default()
+        }
+    }
+
+    /// A proto3 file with a message exercising most field shapes, and a proto2 file which imports
+    /// it.
+    fn pool() -> DescriptorPool {
+        let proto3 = FileDescriptorProto {
+            name: Some("test.proto".to_owned()),
+            package: Some("test".to_owned()),
+            syntax: Some("proto3".to_owned()),
+            message_type: vec![DescriptorProto {
+                name: Some("Message".to_owned()),
+                field: vec![
+                    field("int", 1, Label::Optional, Type::Int32),
+                    field("sint", 2, Label::Optional, Type::Sint64),
+                    field("text", 3, Label::Optional, Type::String),
+                    field("numbers", 4, Label::Repeated, Type::Fixed32),
+                    typed_field("child", 5, Label::Optional, ".test.Message"),
+                    typed_field("color", 6, Label::Optional, ".test.Color"),
+                    typed_field("counts", 7, Label::Repeated, ".test.Message.CountsEntry"),
+                    FieldDescriptorProto {
+                        oneof_index: Some(0),
+                        ..field("a", 8, Label::Optional, Type::Bool)
+                    },
+                    FieldDescriptorProto {
+                        oneof_index: Some(0),
+                        ..field("b", 9, Label::Optional, Type::Bytes)
+                    },
+                    FieldDescriptorProto {
+                        oneof_index: Some(1),
+                        proto3_optional: Some(true),
+                        ..field("maybe", 10, Label::Optional, Type::Double)
+                    },
+                    FieldDescriptorProto {
+                        options: Some(FieldOptions {
+                            packed: Some(false),
+                            ..Default::default()
+                        }),
+                        ..field("unpacked", 11, Label::Repeated, Type::Sint32)
+                    },
+                ],
+                nested_type: vec![DescriptorProto {
+                    name: Some("CountsEntry".to_owned()),
+                    field: vec![
+                        field("key", 1, Label::Optional, Type::String),
+                        field("value", 2, Label::Optional, Type::Uint64),
+                    ],
+                    options: Some(MessageOptions {
+                        map_entry: Some(true),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                oneof_decl: vec![
+                    OneofDescriptorProto {
+                        name: Some("choice".to_owned()),
+                        ..Default::default()
+                    },
+                    OneofDescriptorProto {
+                        name: Some("_maybe".to_owned()),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            enum_type: vec![EnumDescriptorProto {
+                name: Some("Color".to_owned()),
+                value: vec![
+                    EnumValueDescriptorProto {
+                        name: Some("RED".to_owned()),
+                        number: Some(0),
+                        ..Default::default()
+                    },
+                    EnumValueDescriptorProto {
+                        name: Some("GREEN".to_owned()),
+                        number: Some(1),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let proto2 = FileDescriptorProto {
+            name: Some("legacy.proto".to_owned()),
+            package: Some("legacy".to_owned()),
+            dependency: vec!["test.proto".to_owned()],
+            message_type: vec![DescriptorProto {
+                name: Some("Legacy".to_owned()),
+                field: vec![
+                    FieldDescriptorProto {
+                        default_value: Some("42".to_owned()),
+                        ..field("answer", 1, Label::Optional, Type::Int32)
+                    },
+                    FieldDescriptorProto {
+                        default_value: Some("GREEN".to_owned()),
+                        ..typed_field("color", 2, Label::Optional, ".test.Color")
+                    },
+                    field("values", 3, Label::Repeated, Type::Int64),
+                    FieldDescriptorProto {
+                        r#type: Some(Type::Group as i32),
+                        ..typed_field("group", 4, Label::Optional, ".legacy.Legacy")
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        DescriptorPool::from_file_descriptor_set(FileDescriptorSet {
+            file: vec![proto3, proto2],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn descriptors() {
+        let pool = pool();
+        let desc = pool.get_message_by_name("test.Message").unwrap();
+        assert_eq!(desc.name(), "Message");
+        assert_eq!(desc.fields().len(), 11);
+        assert!(pool.get_message_by_name(".test.Message").is_some());
+        assert!(pool.get_message_by_name("test.Color").is_none());
+        assert_eq!(
+            pool.get_enum_by_name("test.Color").unwrap().values().len(),
+            2
+        );
+
+        let numbers = desc.get_field_by_name("numbers").unwrap();
+        assert!(numbers.is_list() && numbers.is_packed());
+        assert!(!desc.get_field(11).unwrap().is_packed());
+        assert!(!desc.get_field(1).unwrap().supports_presence());
+        assert!(desc.get_field(5).unwrap().supports_presence());
+        assert!(desc.get_field(10).unwrap().supports_presence());
+        assert_eq!(desc.get_field(3).unwrap().json_name(), "text");
+
+        let counts = desc.get_field(7).unwrap();
+        assert!(counts.is_map() && !counts.is_list());
+        assert!(counts.kind().as_message().unwrap().is_map_entry());
+
+        // The synthetic oneof of the proto3 optional field is not exposed.
+        let oneofs = desc.oneofs().collect::<Vec<_>>();
+        assert_eq!(oneofs.len(), 1);
+        assert_eq!(oneofs[0].name(), "choice");
+        assert_eq!(
+            oneofs[0].fields().map(|f| f.number()).collect::<Vec<_>>(),
+            [8, 9]
+        );
+        assert!(desc.get_field(10).unwrap().containing_oneof().is_none());
+
+        let legacy = pool.get_message_by_name("legacy.Legacy").unwrap();
+        assert!(!legacy.get_field(3).unwrap().is_packed());
+        assert!(legacy.get_field(4).unwrap().is_group());
+        assert!(legacy.get_field(1).unwrap().supports_presence());
+    }
+
+    #[test]
+    fn invalid_descriptors() {
+        let file = |fields| FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("bad.proto".to_owned()),
+                message_type: vec![DescriptorProto {
+                    name: Some("Bad".to_owned()),
+                    field: fields,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+        let error = |fields| {
+            DescriptorPool::from_file_descriptor_set(file(fields))
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error(vec![typed_field("x", 1, Label::Optional, ".Missing")]),
+            "invalid descriptor: unknown type `Missing` in field `x` of `Bad`"
+        );
+        assert_eq!(
+            error(vec![typed_field("x", 1, Label::Optional, "Bad")]),
+            "invalid descriptor: type name is not fully qualified in field `x` of `Bad`"
+        );
+        assert_eq!(
+            error(vec![
+                field("x", 1, Label::Optional, Type::Bool),
+                field("y", 1, Label::Optional, Type::Bool),
+            ]),
+            "invalid descriptor: duplicate field number 1 in `Bad`"
+        );
+        assert_eq!(
+            error(vec![field("x", 0, Label::Optional, Type::Bool)]),
+            "invalid descriptor: invalid field number in field `x` of `Bad`"
+        );
+
+        // A failed addition leaves the pool unchanged.
+        let mut pool = pool();
+        assert!(pool
+            .add_file_descriptor_set(file(vec![typed_field("x", 1, Label::Optional, ".Missing")]))
+            .is_err());
+        assert!(pool.get_message_by_name("Bad").is_none());
+        assert_eq!(pool.files().len(), 2);
+    }
+
+    #[test]
+    fn services_and_imports() {
+        let file = FileDescriptorProto {
+            name: Some("service.proto".to_owned()),
+            package: Some("test".to_owned()),
+            syntax: Some("proto3".to_owned()),
+            dependency: vec!["google/protobuf/timestamp.proto".to_owned()],
+            message_type: vec![DescriptorProto {
+                name: Some("Request".to_owned()),
+                field: vec![typed_field(
+                    "at",
+                    1,
+                    Label::Optional,
+                    ".google.protobuf.Timestamp",
+                )],
+                ..Default::default()
+            }],
+            service: vec![ServiceDescriptorProto {
+                name: Some("Clock".to_owned()),
+                method: vec![MethodDescriptorProto {
+                    name: Some("Watch".to_owned()),
+                    input_type: Some(".test.Request".to_owned()),
+                    output_type: Some(".google.protobuf.Timestamp".to_owned()),
+                    server_streaming: Some(true),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // The imported well-known types are added to the pool.
+        let pool = DescriptorPool::from_file_descriptor_set(FileDescriptorSet {
+            file: vec![file.clone()],
+        })
+        .unwrap();
+        assert_eq!(pool.files().len(), 2);
+        let timestamp = pool
+            .get_message_by_name("google.protobuf.Timestamp")
+            .unwrap();
+        assert_eq!(
+            timestamp.parent_file().name(),
+            "google/protobuf/timestamp.proto"
+        );
+        assert!(pool.get_file_by_name("service.proto").is_some());
+        assert!(pool.get_file_by_name("google/protobuf/any.proto").is_none());
+
+        let service = pool.get_service_by_name(".test.Clock").unwrap();
+        assert_eq!(service.name(), "Clock");
+        assert_eq!(service.parent_file().name(), "service.proto");
+        assert_eq!(pool.all_services().len(), 1);
+        assert!(pool.get_message_by_name("test.Clock").is_none());
+        let method = service.get_method_by_name("Watch").unwrap();
+        assert_eq!(method.full_name(), "test.Clock.Watch");
+        assert_eq!(method.input().full_name(), "test.Request");
+        assert_eq!(method.output(), timestamp);
+        assert!(!method.is_client_streaming() && method.is_server_streaming());
+
+        // Other imports must be provided.
+        let file = FileDescriptorProto {
+            dependency: vec!["missing.proto".to_owned()],
+            ..file
+        };
+        assert_eq!(
+            DescriptorPool::from_file_descriptor_set(FileDescriptorSet { file: vec![file] })
+                .unwrap_err()
+                .to_string(),
+            "invalid descriptor: file `service.proto` imports `missing.proto`, which is not in \
+             the pool"
+        );
+
+        let pool = DescriptorPool::well_known_types();
+        assert!(pool.get_message_by_name("google.protobuf.Any").is_some());
+        assert!(pool
+            .get_message_by_name("google.protobuf.BoolValue")
+            .is_some());
+        assert!(pool
+            .get_message_by_name("google.protobuf.FileDescriptorSet")
+            .is_some());
+        assert!(pool.get_enum_by_name("google.protobuf.NullValue").is_some());
+    }
+
+    #[test]
+    fn field_access() {
+        let desc = pool().get_message_by_name("test.Message").unwrap();
+        let mut message = DynamicMessage::new(desc.clone());
+        let int = desc.get_field(1).unwrap();
+        assert!(!message.has_field(&int));
+        assert_eq!(*message.get_field(&int), Value::I32(0));
+
+        message.set_field(&int, Value::I32(5));
+        assert!(message.has_field(&int));
+        assert_eq!(message.get_field_by_name("int").unwrap().as_i32(), Some(5));
+
+        // Fields without presence are unset by their default value.
+        message.set_field(&int, Value::I32(0));
+        assert!(!message.has_field(&int));
+
+        message.set_field_by_name("a", Value::Bool(true));
+        message.set_field_by_name("b", Value::Bytes(b"x"[..].into()));
+        assert!(!message.has_field(&desc.get_field(8).unwrap()));
+        assert!(message.has_field(&desc.get_field(9).unwrap()));
+
+        message.set_field_by_number(10, Value::F64(0.0));
+        assert!(message.has_field(&desc.get_field(10).unwrap()));
+
+        message
+            .get_field_mut(&desc.get_field(4).unwrap())
+            .as_list_mut()
+            .unwrap()
+            .push(Value::U32(3));
+        assert_eq!(
+            message
+                .fields()
+                .map(|(field, _)| field.name().to_owned())
+                .collect::<Vec<_>>(),
+            ["numbers", "b", "maybe"]
+        );
+
+        message.clear_field(&desc.get_field(9).unwrap());
+        assert_eq!(message.fields().count(), 2);
+        message.clear();
+        assert_eq!(message.fields().count(), 0);
+        assert!(message.get_field_by_name("missing").is_none());
+
+        let legacy = pool().get_message_by_name("legacy.Legacy").unwrap();
+        let message = DynamicMessage::new(legacy);
+        assert_eq!(*message.get_field_by_number(1).unwrap(), Value::I32(42));
+        assert_eq!(
+            *message.get_field_by_number(2).unwrap(),
+            Value::EnumNumber(1)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid value")]
+    fn set_invalid_value() {
+        let desc = pool().get_message_by_name("test.Message").unwrap();
+        DynamicMessage::new(desc).set_field_by_name("int", Value::I64(1));
+    }
+
+    #[test]
+    fn encode_and_decode() {
+        let desc = pool().get_message_by_name("test.Message").unwrap();
+        let mut child = DynamicMessage::new(desc.clone());
+        child.set_field_by_name("text", Value::String("child".to_string()));
+
+        let mut message = DynamicMessage::new(desc.clone());
+        message.set_field_by_name("int", Value::I32(-1));
+        message.set_field_by_name("sint", Value::I64(-2));
+        message.set_field_by_name("numbers", Value::List(vec![Value::U32(1), Value::U32(2)]));
+        message.set_field_by_name("child", Value::Message(child));
+        message.set_field_by_name("color", Value::EnumNumber(7));
+        message.set_field_by_name(
+            "counts",
+            Value::Map(
+                [(MapKey::String("x".to_string()), Value::U64(0))]
+                    .into_iter()
+                    .collect(),
+            ),
+        );
+        message.set_field_by_name("unpacked", Value::List(vec![Value::I32(-1)]));
+
+        let bytes = message.encode_to_vec();
+        assert_eq!(bytes.len(), message.encoded_len());
+        assert_eq!(
+            bytes,
+            [
+                0x08, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, // int
+                0x10, 0x03, // sint
+                0x22, 0x08, 1, 0, 0, 0, 2, 0, 0, 0, // numbers
+                0x2a, 0x07, 0x1a, 0x05, b'c', b'h', b'i', b'l', b'd', // child
+                0x30, 0x07, // color
+                0x3a, 0x05, 0x0a, 0x01, b'x', 0x10, 0x00, // counts
+                0x58, 0x01, // unpacked
+            ]
+        );
+        assert_eq!(
+            DynamicMessage::decode(desc.clone(), bytes.as_slice()).unwrap(),
+            message
+        );
+
+        // Repeated fields accept both encodings, and unknown fields are preserved.
+        let bytes = [0x25, 3, 0, 0, 0, 0x5a, 0x01, 0x02, 0xa0, 0x06, 0x01];
+        let decoded = DynamicMessage::decode(desc.clone(), &bytes[..]).unwrap();
+        assert_eq!(
+            *decoded.get_field_by_name("numbers").unwrap(),
+            Value::List(vec![Value::U32(3)])
+        );
+        assert_eq!(
+            *decoded.get_field_by_name("unpacked").unwrap(),
+            Value::List(vec![Value::I32(1)])
+        );
+        assert_eq!(decoded.unknown_fields().len(), 1);
+        assert_eq!(
+            decoded.encode_to_vec(),
+            [0x22, 4, 3, 0, 0, 0, 0x58, 0x02, 0xa0, 0x06, 0x01]
+        );
+
+        // Singular messages are merged, and the last member of a oneof wins.
+        let bytes = [
+            0x2a, 0x02, 0x08, 0x01, 0x2a, 0x02, 0x10, 0x02, 0x40, 0x01, 0x4a, 0x00,
+        ];
+        let decoded = DynamicMessage::decode(desc.clone(), &bytes[..]).unwrap();
+        let child = decoded.get_field_by_name("child").unwrap();
+        let child = child.as_message().unwrap();
+        assert_eq!(child.get_field_by_name("int").unwrap().as_i32(), Some(1));
+        assert_eq!(child.get_field_by_name("sint").unwrap().as_i64(), Some(1));
+        assert!(!decoded.has_field(&desc.get_field(8).unwrap()));
+        assert!(decoded.has_field(&desc.get_field(9).unwrap()));
+
+        assert!(DynamicMessage::decode(desc, &[0x08, 0x80][..]).is_err());
+    }
+
+    #[test]
+    fn groups() {
+        let desc = pool().get_message_by_name("legacy.Legacy").unwrap();
+        let mut inner = DynamicMessage::new(desc.clone());
+        inner.set_field_by_name("answer", Value::I32(0));
+        let mut message = DynamicMessage::new(desc.clone());
+        message.set_field_by_name("group", Value::Message(inner));
+        message.set_field_by_name("values", Value::List(vec![Value::I64(1)]));
+
+        let bytes = message.encode_to_vec();
+        assert_eq!(bytes, [0x18, 0x01, 0x23, 0x08, 0x00, 0x24]);
+        assert_eq!(bytes.len(), message.encoded_len());
+        assert_eq!(
+            DynamicMessage::decode(desc, bytes.as_slice()).unwrap(),
+            message
+        );
+    }
+
+    #[test]
+    fn editions() {
+        let features = |f: fn(&mut FeatureSet)| {
+            let mut features = FeatureSet::default();
+            f(&mut features);
+            Some(FieldOptions {
+                features: Some(features),
+                ..Default::default()
+            })
+        };
+        let file = FileDescriptorProto {
+            name: Some("editions.proto".to_owned()),
+            package: Some("editions".to_owned()),
+            syntax: Some("editions".to_owned()),
+            edition: Some(Edition::Edition2023 as i32),
+            options: Some(FileOptions {
+                features: Some(FeatureSet {
+                    repeated_field_encoding: Some(RepeatedFieldEncoding::Expanded as i32),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            message_type: vec![DescriptorProto {
+                name: Some("Message".to_owned()),
+                field: vec![
+                    field("explicit", 1, Label::Optional, Type::Int32),
+                    FieldDescriptorProto {
+                        options: features(|f| f.set_field_presence(FieldPresence::Implicit)),
+                        ..field("implicit", 2, Label::Optional, Type::Int32)
+                    },
+                    FieldDescriptorProto {
+                        options: features(|f| f.set_field_presence(FieldPresence::LegacyRequired)),
+                        ..field("required", 3, Label::Optional, Type::Int32)
+                    },
+                    field("expanded", 4, Label::Repeated, Type::Int32),
+                    FieldDescriptorProto {
+                        r#type: Some(Type::Message as i32),
+                        options: features(|f| f.set_message_encoding(MessageEncoding::Delimited)),
+                        ..typed_field("delimited", 5, Label::Optional, ".editions.Message")
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let pool = DescriptorPool::from_file_descriptor_set(FileDescriptorSet { file: vec![file] })
+            .unwrap();
+        let desc = pool.get_message_by_name("editions.Message").unwrap();
+        assert!(desc.get_field(1).unwrap().supports_presence());
+        assert!(!desc.get_field(2).unwrap().supports_presence());
+        assert_eq!(
+            desc.get_field(3).unwrap().cardinality(),
+            Cardinality::Required
+        );
+        assert!(!desc.get_field(4).unwrap().is_packed());
+        assert!(desc.get_field(5).unwrap().is_group());
+
+        let mut message = DynamicMessage::new(desc.clone());
+        message.set_field_by_name("explicit", Value::I32(0));
+        message.set_field_by_name("implicit", Value::I32(0));
+        message.set_field_by_name("delimited", Value::Message(DynamicMessage::new(desc)));
+        assert_eq!(message.encode_to_vec(), [0x08, 0x00, 0x2b, 0x2c]);
+    }
+}
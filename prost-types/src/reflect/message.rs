@@ -0,0 +1,133 @@
+//! Reflection over generated message types.
+
+use core::fmt;
+
+use prost::alloc::borrow::Cow;
+use prost::Message;
+
+use super::{DescriptorPool, DynamicMessage, MessageDescriptor, Value};
+
+/// A message type which describes itself with a [`MessageDescriptor`].
+///
+/// This trait is implemented by the message types generated with `Config::enable_reflection` of
+/// `prost-build`, and by [`DynamicMessage`], so that generic code, such as middleware redacting or
+/// filtering fields, can inspect and modify the fields of any message by number.
+///
+/// The fields of a generated message are accessed by transcoding the message to a
+/// [`DynamicMessage`] of its type, so each access encodes the message. Code which accesses many
+/// fields should call [`transcode_to_dynamic`](Self::transcode_to_dynamic) once instead.
+pub trait ReflectMessage: Message + Sized {
+    /// Returns the descriptor of the message type.
+    fn descriptor(&self) -> MessageDescriptor;
+
+    /// Returns a [`DynamicMessage`] of the message type holding the fields of this message.
+    fn transcode_to_dynamic(&self) -> DynamicMessage {
+        DynamicMessage::decode(self.descriptor(), self.encode_to_vec().as_slice())
+            .expect("a message should decode as its own type")
+    }
+
+    /// Returns the value of the field with the given number, or `None` if the message type has no
+    /// such field.
+    ///
+    /// Fields which are not set return their default value.
+    fn get_field_by_number(&self, number: u32) -> Option<Value> {
+        self.transcode_to_dynamic()
+            .get_field_by_number(number)
+            .map(Cow::into_owned)
+    }
+
+    /// Sets the value of the field with the given number.
+    ///
+    /// Any other field of the same oneof is cleared.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message type has no such field, or if the value is not valid for the field.
+    fn set_field_by_number(&mut self, number: u32, value: Value) {
+        let mut message = self.transcode_to_dynamic();
+        message.set_field_by_number(number, value);
+        self.clear();
+        self.merge(message.encode_to_vec().as_slice())
+            .expect("a dynamic message should decode as its own type");
+    }
+}
+
+impl ReflectMessage for DynamicMessage {
+    fn descriptor(&self) -> MessageDescriptor {
+        self.descriptor().clone()
+    }
+
+    fn transcode_to_dynamic(&self) -> DynamicMessage {
+        self.clone()
+    }
+
+    fn get_field_by_number(&self, number: u32) -> Option<Value> {
+        self.get_field_by_number(number).map(Cow::into_owned)
+    }
+
+    fn set_field_by_number(&mut self, number: u32, value: Value) {
+        self.set_field_by_number(number, value)
+    }
+}
+
+/// A [`DescriptorPool`] built from an encoded file descriptor set embedded in the program.
+///
+/// `prost-build` generates a `DESCRIPTOR_POOL` static of this type in each package module when
+/// reflection is enabled, from which the generated [`ReflectMessage`] implementations take their
+/// descriptors. With the `std` feature the pool is decoded once, on first use, and without it
+/// the pool is decoded on each call to [`get`](Self::get).
+pub struct EmbeddedDescriptorPool {
+    encoded: &'static [u8],
+    #[cfg(feature = "std")]
+    pool: std::sync::OnceLock<DescriptorPool>,
+}
+
+impl EmbeddedDescriptorPool {
+    /// Creates a pool from an encoded `FileDescriptorSet`.
+    pub const fn new(encoded: &'static [u8]) -> EmbeddedDescriptorPool {
+        EmbeddedDescriptorPool {
+            encoded,
+            #[cfg(feature = "std")]
+            pool: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Returns the pool.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the embedded file descriptor set is invalid.
+    pub fn get(&self) -> DescriptorPool {
+        #[cfg(feature = "std")]
+        {
+            self.pool.get_or_init(|| self.decode()).clone()
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            self.decode()
+        }
+    }
+
+    /// Returns the descriptor of the message type with the given fully-qualified name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the embedded file descriptor set is invalid, or does not describe the message.
+    pub fn get_message(&self, name: &str) -> MessageDescriptor {
+        self.get()
+            .get_message_by_name(name)
+            .unwrap_or_else(|| panic!("no message {name} in the embedded descriptor pool"))
+    }
+
+    fn decode(&self) -> DescriptorPool {
+        DescriptorPool::decode(self.encoded).expect("embedded file descriptor set is valid")
+    }
+}
+
+impl fmt::Debug for EmbeddedDescriptorPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmbeddedDescriptorPool")
+            .field("len", &self.encoded.len())
+            .finish_non_exhaustive()
+    }
+}
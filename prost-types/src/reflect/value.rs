@@ -0,0 +1,424 @@
+//! Values of dynamic message fields.
+
+use prost::alloc::collections::BTreeMap;
+use prost::alloc::string::String;
+use prost::alloc::vec::Vec;
+use prost::bytes::Bytes;
+
+use super::{DynamicMessage, FieldDescriptor, Kind};
+
+/// The value of a field of a [`DynamicMessage`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A `bool` value.
+    Bool(bool),
+    /// An `int32`, `sint32` or `sfixed32` value.
+    I32(i32),
+    /// An `int64`, `sint64` or `sfixed64` value.
+    I64(i64),
+    /// A `uint32` or `fixed32` value.
+    U32(u32),
+    /// A `uint64` or `fixed64` value.
+    U64(u64),
+    /// A `float` value.
+    F32(f32),
+    /// A `double` value.
+    F64(f64),
+    /// A `string` value.
+    String(String),
+    /// A `bytes` value.
+    Bytes(Bytes),
+    /// The number of an enum value, which may not be a known value of the enum type.
+    EnumNumber(i32),
+    /// A message or group value.
+    Message(DynamicMessage),
+    /// The elements of a repeated field.
+    List(Vec<Value>),
+    /// The entries of a map field.
+    Map(BTreeMap<MapKey, Value>),
+}
+
+/// The key of a map field entry.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MapKey {
+    /// A `bool` key.
+    Bool(bool),
+    /// An `int32`, `sint32` or `sfixed32` key.
+    I32(i32),
+    /// An `int64`, `sint64` or `sfixed64` key.
+    I64(i64),
+    /// A `uint32` or `fixed32` key.
+    U32(u32),
+    /// A `uint64` or `fixed64` key.
+    U64(u64),
+    /// A `string` key.
+    String(String),
+}
+
+impl Value {
+    /// Returns the default value of a field.
+    ///
+    /// This is an empty list or map for repeated fields, the explicit default of a proto2 field if
+    /// it has one, and the default value of the field type otherwise.
+    pub fn default_value_for_field(field: &FieldDescriptor) -> Value {
+        if field.is_map() {
+            Value::Map(BTreeMap::new())
+        } else if field.is_list() {
+            Value::List(Vec::new())
+        } else {
+            let kind = field.kind();
+            field
+                .default_value_text()
+                .and_then(|text| parse_default(&kind, text))
+                .unwrap_or_else(|| Value::default_value(&kind))
+        }
+    }
+
+    /// Returns the default value of a singular field of the given type.
+    pub fn default_value(kind: &Kind) -> Value {
+        match kind {
+            Kind::Double => Value::F64(0.0),
+            Kind::Float => Value::F32(0.0),
+            Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => Value::I32(0),
+            Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => Value::I64(0),
+            Kind::Uint32 | Kind::Fixed32 => Value::U32(0),
+            Kind::Uint64 | Kind::Fixed64 => Value::U64(0),
+            Kind::Bool => Value::Bool(false),
+            Kind::String => Value::String(String::new()),
+            Kind::Bytes => Value::Bytes(Bytes::new()),
+            Kind::Message(message) => Value::Message(DynamicMessage::new(message.clone())),
+            Kind::Enum(enum_type) => Value::EnumNumber(enum_type.default_value().number()),
+        }
+    }
+
+    /// Returns `true` if the value is the default value of a field, which is never encoded for
+    /// fields without presence.
+    pub(crate) fn is_default_for_field(&self, field: &FieldDescriptor) -> bool {
+        match self {
+            Value::List(values) => values.is_empty(),
+            Value::Map(entries) => entries.is_empty(),
+            Value::Message(_) => false,
+            value => *value == Value::default_value_for_field(field),
+        }
+    }
+
+    /// Returns `true` if the value can be held by a field.
+    pub fn is_valid_for_field(&self, field: &FieldDescriptor) -> bool {
+        let kind = field.kind();
+        match self {
+            Value::List(values) if field.is_list() => {
+                values.iter().all(|value| value.is_valid(&kind))
+            }
+            Value::Map(entries) if field.is_map() => {
+                let entry = kind.as_message().expect("map entry");
+                let key_kind = entry.map_entry_key_field().kind();
+                let value_kind = entry.map_entry_value_field().kind();
+                entries
+                    .iter()
+                    .all(|(key, value)| key.is_valid(&key_kind) && value.is_valid(&value_kind))
+            }
+            _ if field.is_list() || field.is_map() => false,
+            value => value.is_valid(&kind),
+        }
+    }
+
+    /// Returns `true` if the value can be held by a singular field of the given type.
+    pub fn is_valid(&self, kind: &Kind) -> bool {
+        match (self, kind) {
+            (Value::Message(message), Kind::Message(desc)) => message.descriptor() == desc,
+            _ => matches!(
+                (self, kind),
+                (Value::Bool(_), Kind::Bool)
+                    | (Value::I32(_), Kind::Int32 | Kind::Sint32 | Kind::Sfixed32)
+                    | (Value::I64(_), Kind::Int64 | Kind::Sint64 | Kind::Sfixed64)
+                    | (Value::U32(_), Kind::Uint32 | Kind::Fixed32)
+                    | (Value::U64(_), Kind::Uint64 | Kind::Fixed64)
+                    | (Value::F32(_), Kind::Float)
+                    | (Value::F64(_), Kind::Double)
+                    | (Value::String(_), Kind::String)
+                    | (Value::Bytes(_), Kind::Bytes)
+                    | (Value::EnumNumber(_), Kind::Enum(_))
+            ),
+        }
+    }
+
+    /// Returns the value if it is a `bool`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is an `i32`.
+    pub fn as_i32(&self) -> Option<i32> {
+        match *self {
+            Value::I32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is an `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::I64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `u32`.
+    pub fn as_u32(&self) -> Option<u32> {
+        match *self {
+            Value::U32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `u64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::U64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is an `f32`.
+    pub fn as_f32(&self) -> Option<f32> {
+        match *self {
+            Value::F32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is an `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::F64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a byte string.
+    pub fn as_bytes(&self) -> Option<&Bytes> {
+        match self {
+            Value::Bytes(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is an enum number.
+    pub fn as_enum_number(&self) -> Option<i32> {
+        match *self {
+            Value::EnumNumber(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a message.
+    pub fn as_message(&self) -> Option<&DynamicMessage> {
+        match self {
+            Value::Message(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a message, mutably.
+    pub fn as_message_mut(&mut self) -> Option<&mut DynamicMessage> {
+        match self {
+            Value::Message(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements if the value is a list.
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements if the value is a list, mutably.
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if the value is a map.
+    pub fn as_map(&self) -> Option<&BTreeMap<MapKey, Value>> {
+        match self {
+            Value::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if the value is a map, mutably.
+    pub fn as_map_mut(&mut self) -> Option<&mut BTreeMap<MapKey, Value>> {
+        match self {
+            Value::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Converts the value to a map key, if it has a valid map key type.
+    pub fn into_map_key(self) -> Option<MapKey> {
+        match self {
+            Value::Bool(value) => Some(MapKey::Bool(value)),
+            Value::I32(value) => Some(MapKey::I32(value)),
+            Value::I64(value) => Some(MapKey::I64(value)),
+            Value::U32(value) => Some(MapKey::U32(value)),
+            Value::U64(value) => Some(MapKey::U64(value)),
+            Value::String(value) => Some(MapKey::String(value)),
+            _ => None,
+        }
+    }
+}
+
+impl MapKey {
+    /// Returns `true` if the key can be held by a map key field of the given type.
+    pub fn is_valid(&self, kind: &Kind) -> bool {
+        Value::from(self.clone()).is_valid(kind)
+    }
+}
+
+impl From<MapKey> for Value {
+    fn from(key: MapKey) -> Value {
+        match key {
+            MapKey::Bool(value) => Value::Bool(value),
+            MapKey::I32(value) => Value::I32(value),
+            MapKey::I64(value) => Value::I64(value),
+            MapKey::U32(value) => Value::U32(value),
+            MapKey::U64(value) => Value::U64(value),
+            MapKey::String(value) => Value::String(value),
+        }
+    }
+}
+
+impl From<DynamicMessage> for Value {
+    fn from(message: DynamicMessage) -> Value {
+        Value::Message(message)
+    }
+}
+
+/// Parses the explicit default value of a proto2 field, as it appears in the descriptor.
+fn parse_default(kind: &Kind, text: &str) -> Option<Value> {
+    Some(match kind {
+        Kind::Double => Value::F64(parse_float(text)?),
+        Kind::Float => Value::F32(parse_float(text)?),
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => Value::I32(text.parse().ok()?),
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => Value::I64(text.parse().ok()?),
+        Kind::Uint32 | Kind::Fixed32 => Value::U32(text.parse().ok()?),
+        Kind::Uint64 | Kind::Fixed64 => Value::U64(text.parse().ok()?),
+        Kind::Bool => Value::Bool(text.parse().ok()?),
+        Kind::String => Value::String(text.into()),
+        Kind::Bytes => Value::Bytes(unescape(text)?.into()),
+        Kind::Enum(enum_type) => Value::EnumNumber(enum_type.get_value_by_name(text)?.number()),
+        Kind::Message(_) => return None,
+    })
+}
+
+fn parse_float<T>(text: &str) -> Option<T>
+where
+    T: core::str::FromStr,
+{
+    match text {
+        "inf" | "-inf" | "nan" => text.parse().ok(),
+        _ if text.starts_with(|c: char| c == '-' || c == '.' || c.is_ascii_digit()) => {
+            text.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Unescapes the default value of a `bytes` field, which `protoc` writes with C-style escapes.
+fn unescape(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut input = text.bytes().peekable();
+    while let Some(byte) = input.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        let escaped = match input.next()? {
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b'f' => 0x0c,
+            b'v' => 0x0b,
+            b'?' => b'?',
+            c @ (b'\\' | b'\'' | b'"') => c,
+            b'x' => {
+                let mut value = 0u8;
+                for _ in 0..2 {
+                    match input.peek().and_then(|&c| (c as char).to_digit(16)) {
+                        Some(digit) => {
+                            value = value * 16 + digit as u8;
+                            input.next();
+                        }
+                        None => break,
+                    }
+                }
+                value
+            }
+            c @ b'0'..=b'7' => {
+                let mut value = u32::from(c - b'0');
+                for _ in 0..2 {
+                    match input.peek() {
+                        Some(&c @ b'0'..=b'7') => {
+                            value = value * 8 + u32::from(c - b'0');
+                            input.next();
+                        }
+                        _ => break,
+                    }
+                }
+                u8::try_from(value).ok()?
+            }
+            _ => return None,
+        };
+        bytes.push(escaped);
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescape_bytes() {
+        assert_eq!(unescape("abc").unwrap(), b"abc");
+        assert_eq!(unescape(r#"\001\x7f\n\\\""#).unwrap(), b"\x01\x7f\n\\\"");
+        assert_eq!(unescape(r"\377").unwrap(), b"\xff");
+        assert!(unescape(r"\400").is_none());
+        assert!(unescape("\\").is_none());
+    }
+
+    #[test]
+    fn parse_defaults() {
+        assert_eq!(parse_default(&Kind::Int32, "-5"), Some(Value::I32(-5)));
+        assert_eq!(parse_default(&Kind::Fixed64, "7"), Some(Value::U64(7)));
+        assert_eq!(parse_default(&Kind::Bool, "true"), Some(Value::Bool(true)));
+        assert_eq!(
+            parse_default(&Kind::Double, "-inf"),
+            Some(Value::F64(f64::NEG_INFINITY))
+        );
+        assert_eq!(parse_default(&Kind::Float, "1.5"), Some(Value::F32(1.5)));
+        assert_eq!(
+            parse_default(&Kind::String, "a\\b"),
+            Some(Value::String("a\\b".into()))
+        );
+        assert_eq!(parse_default(&Kind::Int32, "x"), None);
+    }
+}
@@ -0,0 +1,356 @@
+use core::option::Option;
+
+use prost::alloc::boxed::Box;
+
+use super::*;
+use crate::rpc::*;
+
+impl Status {
+    /// Creates a status with the given code and message, and no details.
+    pub fn new(code: Code, message: impl Into<String>) -> Status {
+        Status {
+            code: code as i32,
+            message: message.into(),
+            details: Vec::new(),
+        }
+    }
+
+    /// Returns the code of the status, or [`Code::Unknown`] if the code is not a known
+    /// `google.rpc.Code` value.
+    pub fn code(&self) -> Code {
+        Code::try_from(self.code).unwrap_or(Code::Unknown)
+    }
+
+    /// Returns `true` if the code of the status is [`Code::Ok`].
+    pub fn is_ok(&self) -> bool {
+        self.code == Code::Ok as i32
+    }
+
+    /// Converts the status to a `Result`, which is `Err` with the status unless its code is
+    /// [`Code::Ok`].
+    ///
+    /// This converts the status of a response into an error which can be propagated with `?`:
+    ///
+    /// ```rust
+    /// # use prost_types::rpc::{Code, Status};
+    /// let status = Status::new(Code::NotFound, "no such book");
+    /// let error = status.into_result().unwrap_err();
+    /// assert_eq!(error.to_string(), "NOT_FOUND: no such book");
+    /// ```
+    pub fn into_result(self) -> Result<(), Status> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Packs a message, such as one of the error details of `google.rpc`, into an [`Any`] and
+    /// adds it to the details of the status.
+    pub fn add_detail<M>(&mut self, detail: &M) -> Result<(), EncodeError>
+    where
+        M: Name,
+    {
+        self.details.push(Any::from_msg(detail)?);
+        Ok(())
+    }
+
+    /// Packs a message into an [`Any`] and adds it to the details of the status, returning the
+    /// status.
+    ///
+    /// ```rust
+    /// # use prost_types::rpc::{bad_request, BadRequest, Code, RetryInfo, Status};
+    /// let bad_request = BadRequest {
+    ///     field_violations: vec![bad_request::FieldViolation {
+    ///         field: "book.title".into(),
+    ///         description: "the title must not be empty".into(),
+    ///         ..Default::default()
+    ///     }],
+    /// };
+    /// let status = Status::new(Code::InvalidArgument, "invalid book")
+    ///     .with_detail(&bad_request)
+    ///     .unwrap();
+    ///
+    /// let mut details = status.details_of::<BadRequest>();
+    /// assert_eq!(details.next().unwrap().unwrap(), bad_request);
+    /// assert!(details.next().is_none());
+    /// assert!(status.details_of::<RetryInfo>().next().is_none());
+    /// ```
+    pub fn with_detail<M>(mut self, detail: &M) -> Result<Status, EncodeError>
+    where
+        M: Name,
+    {
+        self.add_detail(detail)?;
+        Ok(self)
+    }
+
+    /// Returns an iterator over the details of type `M`, in order, decoding each of them.
+    ///
+    /// Details of other types are skipped. A detail is of type `M` if the last segment of its
+    /// type URL is the fully qualified name of `M`, so any domain is accepted.
+    pub fn details_of<M>(&self) -> impl Iterator<Item = Result<M, DecodeError>> + '_
+    where
+        M: Default + Name,
+    {
+        let full_name = M::full_name();
+        self.details
+            .iter()
+            .filter(move |detail| {
+                TypeUrl::new(&detail.type_url)
+                    .is_some_and(|type_url| type_url.full_name == full_name)
+            })
+            .map(|detail| M::decode(detail.value.as_slice()))
+    }
+
+    /// Returns the first detail of type `M`, or `None` if the status has no detail of type `M`.
+    pub fn first_detail_of<M>(&self) -> Option<Result<M, DecodeError>>
+    where
+        M: Default + Name,
+    {
+        self.details_of().next()
+    }
+
+    /// Returns an iterator over all of the details, in order, decoding each of them with the
+    /// message type of the registry which its type URL names.
+    ///
+    /// [`MessageRegistry::with_error_details`] returns a registry of the error details of
+    /// `google.rpc`. Decoding a detail whose type is not in the registry fails.
+    pub fn decode_details<'a, R>(
+        &'a self,
+        registry: &'a R,
+    ) -> impl Iterator<Item = Result<Box<dyn AnyMessage>, DecodeError>> + 'a
+    where
+        R: TypeRegistry + ?Sized,
+    {
+        self.details
+            .iter()
+            .map(move |detail| detail.to_msg_dyn(registry))
+    }
+}
+
+impl MessageRegistry {
+    /// Creates a registry of the standard error details of `google.rpc`, which are the message
+    /// types that the details of a [`Status`] usually contain.
+    ///
+    /// More message types can be registered with [`MessageRegistry::register`].
+    pub fn with_error_details() -> MessageRegistry {
+        MessageRegistry::new()
+            .register::<ErrorInfo>()
+            .register::<RetryInfo>()
+            .register::<DebugInfo>()
+            .register::<QuotaFailure>()
+            .register::<PreconditionFailure>()
+            .register::<BadRequest>()
+            .register::<RequestInfo>()
+            .register::<ResourceInfo>()
+            .register::<Help>()
+            .register::<LocalizedMessage>()
+    }
+}
+
+impl From<Code> for Status {
+    /// Creates a status with the given code, an empty message, and no details.
+    fn from(code: Code) -> Status {
+        Status::new(code, String::new())
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match Code::try_from(self.code) {
+            Ok(code) => write!(f, "{code}")?,
+            Err(_) => write!(f, "code {}", self.code)?,
+        }
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for Status {}
+
+impl Code {
+    /// Returns the HTTP status code which the code maps to, as documented for each code.
+    pub fn http_status(&self) -> u16 {
+        match self {
+            Code::Ok => 200,
+            Code::Cancelled => 499,
+            Code::Unknown => 500,
+            Code::InvalidArgument => 400,
+            Code::DeadlineExceeded => 504,
+            Code::NotFound => 404,
+            Code::AlreadyExists => 409,
+            Code::PermissionDenied => 403,
+            Code::Unauthenticated => 401,
+            Code::ResourceExhausted => 429,
+            Code::FailedPrecondition => 400,
+            Code::Aborted => 409,
+            Code::OutOfRange => 400,
+            Code::Unimplemented => 501,
+            Code::Internal => 500,
+            Code::Unavailable => 503,
+            Code::DataLoss => 500,
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    /// Formats the code as its name in the Protobuf definition, e.g. `NOT_FOUND`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str_name())
+    }
+}
+
+macro_rules! impl_rpc_name {
+    ($t:ty, $name:literal) => {
+        impl Name for $t {
+            const PACKAGE: &'static str = RPC_PACKAGE;
+            const NAME: &'static str = $name;
+
+            fn type_url() -> String {
+                type_url_for::<Self>()
+            }
+        }
+    };
+}
+
+impl_rpc_name!(Status, "Status");
+impl_rpc_name!(ErrorInfo, "ErrorInfo");
+impl_rpc_name!(RetryInfo, "RetryInfo");
+impl_rpc_name!(DebugInfo, "DebugInfo");
+impl_rpc_name!(QuotaFailure, "QuotaFailure");
+impl_rpc_name!(PreconditionFailure, "PreconditionFailure");
+impl_rpc_name!(BadRequest, "BadRequest");
+impl_rpc_name!(RequestInfo, "RequestInfo");
+impl_rpc_name!(ResourceInfo, "ResourceInfo");
+impl_rpc_name!(Help, "Help");
+impl_rpc_name!(LocalizedMessage, "LocalizedMessage");
+
+#[cfg(test)]
+mod tests {
+    use prost::alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn status_code() {
+        let status = Status::new(Code::PermissionDenied, "denied");
+        assert_eq!(status.code, 7);
+        assert_eq!(status.code(), Code::PermissionDenied);
+        assert!(!status.is_ok());
+
+        let status = Status {
+            code: 100,
+            ..Status::default()
+        };
+        assert_eq!(status.code(), Code::Unknown);
+
+        assert!(Status::default().is_ok());
+        assert_eq!(Status::from(Code::Ok).into_result(), Ok(()));
+    }
+
+    #[test]
+    fn status_display() {
+        let status = Status::new(Code::InvalidArgument, "bad title");
+        assert_eq!(status.to_string(), "INVALID_ARGUMENT: bad title");
+        assert_eq!(Status::from(Code::Aborted).to_string(), "ABORTED");
+
+        let status = Status {
+            code: 100,
+            message: "teapot".to_string(),
+            details: Vec::new(),
+        };
+        assert_eq!(status.to_string(), "code 100: teapot");
+    }
+
+    #[test]
+    fn code_display() {
+        assert_eq!(Code::Unauthenticated.to_string(), "UNAUTHENTICATED");
+        assert_eq!(Code::ResourceExhausted.http_status(), 429);
+        assert_eq!(i32::from(Code::DataLoss), 15);
+    }
+
+    #[test]
+    fn details_of() {
+        let retry_info = RetryInfo {
+            retry_delay: Some(Duration {
+                seconds: 3,
+                nanos: 0,
+            }),
+        };
+        let first = LocalizedMessage {
+            locale: "en-US".to_string(),
+            message: "Try again later".to_string(),
+        };
+        let second = LocalizedMessage {
+            locale: "fr-CH".to_string(),
+            message: "Réessayez plus tard".to_string(),
+        };
+        let mut status = Status::new(Code::Unavailable, "overloaded")
+            .with_detail(&first)
+            .unwrap()
+            .with_detail(&retry_info)
+            .unwrap();
+        status.add_detail(&second).unwrap();
+        assert_eq!(status.details.len(), 3);
+
+        let messages = status
+            .details_of::<LocalizedMessage>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(messages, [first, second]);
+        assert_eq!(
+            status.first_detail_of::<RetryInfo>().unwrap().unwrap(),
+            retry_info
+        );
+        assert!(status.first_detail_of::<BadRequest>().is_none());
+
+        // Any domain is accepted, but the value must be valid.
+        status.details.push(Any {
+            type_url: "example.com/google.rpc.RetryInfo".to_string(),
+            value: vec![0x0a],
+        });
+        let mut details = status.details_of::<RetryInfo>();
+        assert!(details.next().unwrap().is_ok());
+        assert!(details.next().unwrap().is_err());
+        assert!(details.next().is_none());
+    }
+
+    #[test]
+    fn decode_details() {
+        let debug_info = DebugInfo {
+            stack_entries: vec!["main".to_string()],
+            detail: "panicked".to_string(),
+        };
+        let mut status = Status::new(Code::Internal, "")
+            .with_detail(&debug_info)
+            .unwrap()
+            .with_detail(&Duration::default())
+            .unwrap();
+
+        let registry = MessageRegistry::with_error_details();
+        let mut details = status.decode_details(&registry);
+        let detail = details.next().unwrap().unwrap();
+        assert_eq!(detail.downcast_ref::<DebugInfo>(), Some(&debug_info));
+        // `Duration` is not an error detail.
+        assert!(details.next().unwrap().is_err());
+        assert!(details.next().is_none());
+        drop(details);
+
+        status.details.pop();
+        let registry = MessageRegistry::with_error_details().register::<Duration>();
+        assert!(status
+            .decode_details(&registry)
+            .all(|detail| detail.is_ok()));
+    }
+
+    #[test]
+    fn error_details_type_urls() {
+        assert_eq!(
+            BadRequest::type_url(),
+            "type.googleapis.com/google.rpc.BadRequest"
+        );
+        assert_eq!(Status::full_name(), "google.rpc.Status");
+    }
+}
@@ -0,0 +1,253 @@
+//! Text format of the well-known types.
+//!
+//! The well-known types have no special text format representation, so these implementations
+//! match what `#[prost(text_format)]` would derive for them.
+
+use prost::alloc::string::String;
+use prost::text_format::encoding::{self, Enumeration};
+use prost::text_format::{TextField, TextFormatError, TextFormatMessage, TextMessage, TextValue};
+
+use crate::{value, Any, Duration, FieldMask, ListValue, NullValue, Struct, Timestamp, Value};
+
+/// Appends a singular field to `message`, unless it has its default value.
+fn encode_plain<T>(message: &mut TextMessage, name: &str, value: &T)
+where
+    T: TextField + Default + PartialEq,
+{
+    if *value != T::default() {
+        message.push((name.into(), value.to_text_value()));
+    }
+}
+
+/// Merges `message` into `target` using `merge_field` for each field, reporting unknown fields as
+/// belonging to the message type `type_name`.
+fn merge_fields<T>(
+    target: &mut T,
+    message: &TextMessage,
+    type_name: &str,
+    mut merge_field: impl FnMut(&mut T, &str, &TextValue) -> Option<Result<(), TextFormatError>>,
+) -> Result<(), TextFormatError> {
+    for (name, value) in message {
+        match merge_field(target, name, value) {
+            Some(result) => result.map_err(|error| encoding::with_field(error, type_name, name))?,
+            None => return Err(encoding::unknown_field(name, type_name)),
+        }
+    }
+    Ok(())
+}
+
+macro_rules! plain_message {
+    ($ty:ident { $($field:ident),* }) => {
+        impl TextFormatMessage for $ty {
+            fn encode_text(&self, message: &mut TextMessage) {
+                $(encode_plain(message, stringify!($field), &self.$field);)*
+            }
+
+            fn merge_text(&mut self, message: &TextMessage) -> Result<(), TextFormatError> {
+                merge_fields(self, message, stringify!($ty), |this, name, value| match name {
+                    $(stringify!($field) => Some(encoding::merge(&mut this.$field, value)),)*
+                    _ => None,
+                })
+            }
+        }
+    };
+}
+
+plain_message!(Timestamp { seconds, nanos });
+plain_message!(Duration { seconds, nanos });
+plain_message!(Any { type_url, value });
+
+impl TextFormatMessage for FieldMask {
+    fn encode_text(&self, message: &mut TextMessage) {
+        for path in &self.paths {
+            message.push(("paths".into(), path.to_text_value()));
+        }
+    }
+
+    fn merge_text(&mut self, message: &TextMessage) -> Result<(), TextFormatError> {
+        merge_fields(self, message, "FieldMask", |this, name, value| match name {
+            "paths" => Some(encoding::merge_repeated(&mut this.paths, value)),
+            _ => None,
+        })
+    }
+}
+
+impl TextFormatMessage for Struct {
+    fn encode_text(&self, message: &mut TextMessage) {
+        encoding::encode_map(message, "fields", &self.fields, |value| {
+            value.to_text_value()
+        });
+    }
+
+    fn merge_text(&mut self, message: &TextMessage) -> Result<(), TextFormatError> {
+        merge_fields(self, message, "Struct", |this, name, value| match name {
+            "fields" => Some(encoding::merge_map(
+                &mut this.fields,
+                value,
+                Value::from_text_value,
+            )),
+            _ => None,
+        })
+    }
+}
+
+const NULL_VALUE: Enumeration = Enumeration {
+    name: |value| {
+        NullValue::try_from(value)
+            .ok()
+            .map(|value| value.as_str_name())
+    },
+    value: |name| NullValue::from_str_name(name).map(|value| value as i32),
+};
+
+impl TextFormatMessage for Value {
+    fn encode_text(&self, message: &mut TextMessage) {
+        let (name, value) = match &self.kind {
+            Some(value::Kind::NullValue(value)) => ("null_value", NULL_VALUE.to_text(*value)),
+            Some(value::Kind::NumberValue(value)) => ("number_value", value.to_text_value()),
+            Some(value::Kind::StringValue(value)) => ("string_value", value.to_text_value()),
+            Some(value::Kind::BoolValue(value)) => ("bool_value", value.to_text_value()),
+            Some(value::Kind::StructValue(value)) => ("struct_value", value.to_text_value()),
+            Some(value::Kind::ListValue(value)) => ("list_value", value.to_text_value()),
+            None => return,
+        };
+        message.push((name.into(), value));
+    }
+
+    fn merge_text(&mut self, message: &TextMessage) -> Result<(), TextFormatError> {
+        merge_fields(self, message, "Value", |this, name, value| {
+            let kind = match name {
+                "null_value" => NULL_VALUE.from_text(value).map(value::Kind::NullValue),
+                "number_value" => f64::from_text_value(value).map(value::Kind::NumberValue),
+                "string_value" => String::from_text_value(value).map(value::Kind::StringValue),
+                "bool_value" => bool::from_text_value(value).map(value::Kind::BoolValue),
+                "struct_value" => Struct::from_text_value(value).map(value::Kind::StructValue),
+                "list_value" => ListValue::from_text_value(value).map(value::Kind::ListValue),
+                _ => return None,
+            };
+            Some(kind.map(|kind| this.kind = Some(kind)))
+        })
+    }
+}
+
+impl TextFormatMessage for ListValue {
+    fn encode_text(&self, message: &mut TextMessage) {
+        for value in &self.values {
+            message.push(("values".into(), value.to_text_value()));
+        }
+    }
+
+    fn merge_text(&mut self, message: &TextMessage) -> Result<(), TextFormatError> {
+        merge_fields(self, message, "ListValue", |this, name, value| match name {
+            "values" => Some(encoding::merge_repeated(&mut this.values, value)),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use prost::alloc::string::ToString;
+    use prost::alloc::vec;
+
+    #[test]
+    fn timestamp() {
+        let timestamp = Timestamp {
+            seconds: 1,
+            nanos: 0,
+        };
+        assert_eq!(timestamp.to_text_format(), "seconds: 1\n");
+        assert_eq!(
+            Timestamp::from_text_format("seconds: 1 nanos: 2").unwrap(),
+            Timestamp {
+                seconds: 1,
+                nanos: 2
+            }
+        );
+        assert_eq!(
+            Timestamp::from_text_format("seconds: x")
+                .unwrap_err()
+                .to_string(),
+            "failed to process Protobuf text format: Timestamp.seconds: invalid integer: x"
+        );
+        assert!(Timestamp::from_text_format("minutes: 1").is_err());
+    }
+
+    #[test]
+    fn struct_and_value() {
+        let value = Value {
+            kind: Some(value::Kind::StructValue(Struct {
+                fields: [
+                    (
+                        "list".to_string(),
+                        Value {
+                            kind: Some(value::Kind::ListValue(ListValue {
+                                values: vec![
+                                    Value {
+                                        kind: Some(value::Kind::NullValue(0)),
+                                    },
+                                    Value {
+                                        kind: Some(value::Kind::NumberValue(1.5)),
+                                    },
+                                ],
+                            })),
+                        },
+                    ),
+                    (
+                        "text".to_string(),
+                        Value {
+                            kind: Some(value::Kind::StringValue("a".to_string())),
+                        },
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+            })),
+        };
+        let text = value.to_text_format();
+        assert_eq!(
+            text,
+            concat!(
+                "struct_value {\n",
+                "  fields {\n",
+                "    key: \"list\"\n",
+                "    value {\n",
+                "      list_value {\n",
+                "        values {\n",
+                "          null_value: NULL_VALUE\n",
+                "        }\n",
+                "        values {\n",
+                "          number_value: 1.5\n",
+                "        }\n",
+                "      }\n",
+                "    }\n",
+                "  }\n",
+                "  fields {\n",
+                "    key: \"text\"\n",
+                "    value {\n",
+                "      string_value: \"a\"\n",
+                "    }\n",
+                "  }\n",
+                "}\n",
+            )
+        );
+        assert_eq!(Value::from_text_format(&text).unwrap(), value);
+    }
+
+    #[test]
+    fn field_mask_and_any() {
+        let mask = FieldMask {
+            paths: vec!["a.b".to_string(), "c".to_string()],
+        };
+        let text = mask.to_text_format();
+        assert_eq!(text, "paths: \"a.b\"\npaths: \"c\"\n");
+        assert_eq!(FieldMask::from_text_format(&text).unwrap(), mask);
+
+        let any = Any::from_msg(&mask).unwrap();
+        let text = any.to_text_format();
+        assert!(text.starts_with("type_url: \"type.googleapis.com/google.protobuf.FieldMask\"\n"));
+        assert_eq!(Any::from_text_format(&text).unwrap(), any);
+    }
+}
@@ -1,3 +1,5 @@
+use core::option::Option;
+
 use super::*;
 
 impl Timestamp {
@@ -73,6 +75,61 @@ impl Timestamp {
         result
     }
 
+    /// The earliest valid timestamp, 0001-01-01T00:00:00Z.
+    pub const MIN: Timestamp = Timestamp {
+        seconds: TIMESTAMP_SECONDS_MIN,
+        nanos: 0,
+    };
+
+    /// The latest valid timestamp, 9999-12-31T23:59:59.999999999Z.
+    pub const MAX: Timestamp = Timestamp {
+        seconds: TIMESTAMP_SECONDS_MAX,
+        nanos: NANOS_MAX,
+    };
+
+    /// Returns `true` if the timestamp is normalized and within the range of valid timestamps,
+    /// [`Timestamp::MIN`] to [`Timestamp::MAX`].
+    pub fn is_valid(&self) -> bool {
+        (TIMESTAMP_SECONDS_MIN..=TIMESTAMP_SECONDS_MAX).contains(&self.seconds)
+            && (0..NANOS_PER_SECOND).contains(&self.nanos)
+    }
+
+    /// Adds a duration to the timestamp, returning `None` if the result is not a valid timestamp.
+    pub fn checked_add(self, duration: Duration) -> Option<Timestamp> {
+        Timestamp::from_nanos(self.as_nanos() + duration.as_nanos())
+    }
+
+    /// Subtracts a duration from the timestamp, returning `None` if the result is not a valid
+    /// timestamp.
+    pub fn checked_sub(self, duration: Duration) -> Option<Timestamp> {
+        Timestamp::from_nanos(self.as_nanos() - duration.as_nanos())
+    }
+
+    /// Returns the duration from `earlier` to this timestamp, which is negative if `earlier` is
+    /// later than this timestamp.
+    ///
+    /// Returns `None` if the result is not a valid duration, which is only possible if either
+    /// timestamp is not valid.
+    pub fn duration_since(self, earlier: Timestamp) -> Option<Duration> {
+        Duration::from_nanos(self.as_nanos() - earlier.as_nanos())
+    }
+
+    /// Returns the number of nanoseconds since the Unix epoch, for a timestamp which need not be
+    /// normalized.
+    fn as_nanos(&self) -> i128 {
+        i128::from(self.seconds) * i128::from(NANOS_PER_SECOND) + i128::from(self.nanos)
+    }
+
+    /// Creates a normalized timestamp from the number of nanoseconds since the Unix epoch,
+    /// returning `None` if it is not a valid timestamp.
+    fn from_nanos(nanos: i128) -> Option<Timestamp> {
+        let timestamp = Timestamp {
+            seconds: i64::try_from(nanos.div_euclid(i128::from(NANOS_PER_SECOND))).ok()?,
+            nanos: nanos.rem_euclid(i128::from(NANOS_PER_SECOND)) as i32,
+        };
+        timestamp.is_valid().then_some(timestamp)
+    }
+
     /// Creates a new `Timestamp` at the start of the provided UTC date.
     pub fn date(year: i64, month: u8, day: u8) -> Result<Timestamp, TimestampError> {
         Timestamp::date_time_nanos(year, month, day, 0, 0, 0, 0)
@@ -112,6 +169,53 @@ impl Timestamp {
 
         Timestamp::try_from(date_time)
     }
+
+    /// Parses a timestamp in exactly the string format of the [Protobuf JSON mapping][1], e.g.
+    /// `"1972-01-01T10:00:20.021Z"` or `"1972-01-01T11:00:20.021+01:00"`.
+    ///
+    /// Unlike [`FromStr`], which also accepts dates without a time, lowercase separators and
+    /// offsets without a colon, this only accepts RFC 3339 date times with up to nine fractional
+    /// digits. Fails with [`TimestampError::OutOfRange`] if the timestamp is not within
+    /// [`Timestamp::MIN`] to [`Timestamp::MAX`].
+    ///
+    /// [1]: https://protobuf.dev/programming-guides/proto3/#json
+    pub fn parse_strict(s: &str) -> Result<Timestamp, TimestampError> {
+        let timestamp = datetime::parse_json_timestamp(s).ok_or(TimestampError::ParseFailure)?;
+        if !timestamp.is_valid() {
+            return Err(TimestampError::OutOfRange);
+        }
+        Ok(timestamp)
+    }
+}
+
+impl core::ops::Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    /// Adds a duration to the timestamp.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is not a valid timestamp. Use [`Timestamp::checked_add`] to handle
+    /// overflow.
+    fn add(self, duration: Duration) -> Timestamp {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to timestamp")
+    }
+}
+
+impl core::ops::Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    /// Subtracts a duration from the timestamp.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is not a valid timestamp. Use [`Timestamp::checked_sub`] to handle
+    /// overflow.
+    fn sub(self, duration: Duration) -> Timestamp {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from timestamp")
+    }
 }
 
 impl Name for Timestamp {
@@ -164,6 +268,10 @@ pub enum TimestampError {
 
     /// Indicates an error when constructing a timestamp due to invalid date or time data.
     InvalidDateTime,
+
+    /// Indicates that a date and time could not be converted to or from a [`Timestamp`] because it
+    /// is outside the range of valid timestamps, [`Timestamp::MIN`] to [`Timestamp::MAX`].
+    OutOfRange,
 }
 
 impl fmt::Display for TimestampError {
@@ -181,6 +289,9 @@ impl fmt::Display for TimestampError {
             TimestampError::InvalidDateTime => {
                 write!(f, "invalid date or time")
             }
+            TimestampError::OutOfRange => {
+                write!(f, "timestamp out of range")
+            }
         }
     }
 }
@@ -228,6 +339,66 @@ impl fmt::Display for Timestamp {
     }
 }
 
+#[cfg(feature = "chrono")]
+mod chrono {
+    use ::chrono::{DateTime, Utc};
+
+    use super::*;
+
+    /// Converts a `chrono::DateTime<Utc>`, failing if it is not a valid timestamp.
+    impl TryFrom<DateTime<Utc>> for Timestamp {
+        type Error = TimestampError;
+
+        fn try_from(value: DateTime<Utc>) -> Result<Timestamp, TimestampError> {
+            // Leap seconds, whose subsecond nanoseconds exceed one second, roll over into the
+            // following second.
+            let nanos = i128::from(value.timestamp()) * i128::from(NANOS_PER_SECOND)
+                + i128::from(value.timestamp_subsec_nanos());
+            Timestamp::from_nanos(nanos).ok_or(TimestampError::OutOfRange)
+        }
+    }
+
+    /// Converts to a `chrono::DateTime<Utc>`, failing if the timestamp is not valid.
+    impl TryFrom<Timestamp> for DateTime<Utc> {
+        type Error = TimestampError;
+
+        fn try_from(value: Timestamp) -> Result<DateTime<Utc>, TimestampError> {
+            let value =
+                Timestamp::from_nanos(value.as_nanos()).ok_or(TimestampError::OutOfRange)?;
+            DateTime::from_timestamp(value.seconds, value.nanos as u32)
+                .ok_or(TimestampError::OutOfRange)
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_crate {
+    use ::time::OffsetDateTime;
+
+    use super::*;
+
+    /// Converts a `time::OffsetDateTime`, failing if it is not a valid timestamp.
+    impl TryFrom<OffsetDateTime> for Timestamp {
+        type Error = TimestampError;
+
+        fn try_from(value: OffsetDateTime) -> Result<Timestamp, TimestampError> {
+            Timestamp::from_nanos(value.unix_timestamp_nanos()).ok_or(TimestampError::OutOfRange)
+        }
+    }
+
+    /// Converts to a `time::OffsetDateTime` in UTC, failing if the timestamp is not valid.
+    impl TryFrom<Timestamp> for OffsetDateTime {
+        type Error = TimestampError;
+
+        fn try_from(value: Timestamp) -> Result<OffsetDateTime, TimestampError> {
+            let value =
+                Timestamp::from_nanos(value.as_nanos()).ok_or(TimestampError::OutOfRange)?;
+            OffsetDateTime::from_unix_timestamp_nanos(value.as_nanos())
+                .map_err(|_| TimestampError::OutOfRange)
+        }
+    }
+}
+
 #[cfg(kani)]
 mod proofs {
     use super::*;
@@ -412,6 +583,183 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_timestamp_arithmetic() {
+        let timestamp = Timestamp::date_time(2000, 1, 1, 0, 0, 0).unwrap();
+        let duration = Duration {
+            seconds: -1,
+            nanos: -500_000_000,
+        };
+        let earlier = timestamp + duration;
+        assert_eq!(
+            earlier,
+            Timestamp::date_time_nanos(1999, 12, 31, 23, 59, 58, 500_000_000).unwrap()
+        );
+        assert_eq!(earlier - duration, timestamp);
+        assert_eq!(
+            timestamp.duration_since(earlier),
+            Some(duration_neg(duration))
+        );
+        assert_eq!(earlier.duration_since(timestamp), Some(duration));
+
+        assert_eq!(
+            Timestamp::MAX.duration_since(Timestamp::MIN),
+            Some(Duration {
+                seconds: TIMESTAMP_SECONDS_MAX - TIMESTAMP_SECONDS_MIN,
+                nanos: NANOS_MAX
+            })
+        );
+        assert_eq!(
+            Timestamp::MAX.checked_add(Duration {
+                seconds: 0,
+                nanos: 1
+            }),
+            None
+        );
+        assert_eq!(Timestamp::MIN.checked_sub(Duration::MAX), None);
+        assert!(!Timestamp {
+            seconds: TIMESTAMP_SECONDS_MIN - 1,
+            nanos: 0
+        }
+        .is_valid());
+        assert!(!Timestamp {
+            seconds: 0,
+            nanos: -1
+        }
+        .is_valid());
+    }
+
+    fn duration_neg(duration: Duration) -> Duration {
+        Duration {
+            seconds: -duration.seconds,
+            nanos: -duration.nanos,
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn check_timestamp_chrono_conversions() {
+        use ::chrono::{DateTime, NaiveDate, Utc};
+
+        let timestamp = Timestamp::date_time_nanos(1969, 7, 20, 20, 17, 40, 123).unwrap();
+        let date_time = DateTime::<Utc>::try_from(timestamp).unwrap();
+        assert_eq!(date_time.timestamp(), timestamp.seconds);
+        assert_eq!(date_time.timestamp_subsec_nanos(), 123);
+        assert_eq!(Timestamp::try_from(date_time), Ok(timestamp));
+
+        // A leap second rolls over into the following second.
+        let leap_second = NaiveDate::from_ymd_opt(2016, 12, 31)
+            .unwrap()
+            .and_hms_nano_opt(23, 59, 59, 1_500_000_000)
+            .unwrap()
+            .and_utc();
+        assert_eq!(
+            Timestamp::try_from(leap_second),
+            Timestamp::date_time_nanos(2017, 1, 1, 0, 0, 0, 500_000_000)
+        );
+
+        assert_eq!(
+            Timestamp::try_from(DateTime::<Utc>::MAX_UTC),
+            Err(TimestampError::OutOfRange)
+        );
+        assert_eq!(
+            DateTime::<Utc>::try_from(Timestamp {
+                seconds: i64::MIN,
+                nanos: 0
+            }),
+            Err(TimestampError::OutOfRange)
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn check_timestamp_time_conversions() {
+        use ::time::OffsetDateTime;
+
+        let timestamp = Timestamp::date_time_nanos(1969, 7, 20, 20, 17, 40, 123).unwrap();
+        let date_time = OffsetDateTime::try_from(timestamp).unwrap();
+        assert_eq!(date_time.unix_timestamp(), timestamp.seconds);
+        assert_eq!(date_time.nanosecond(), 123);
+        assert_eq!(Timestamp::try_from(date_time), Ok(timestamp));
+
+        assert_eq!(
+            OffsetDateTime::try_from(Timestamp::MAX).map(Timestamp::try_from),
+            Ok(Ok(Timestamp::MAX))
+        );
+        assert_eq!(
+            OffsetDateTime::try_from(Timestamp {
+                seconds: TIMESTAMP_SECONDS_MAX + 1,
+                nanos: 0
+            }),
+            Err(TimestampError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn check_timestamp_parse_strict() {
+        let timestamp = Timestamp::date_time_nanos(1972, 1, 1, 10, 0, 20, 21_000_000).unwrap();
+        for s in [
+            "1972-01-01T10:00:20.021Z",
+            "1972-01-01T10:00:20.021000000Z",
+            "1972-01-01T11:00:20.021+01:00",
+            "1971-12-31T23:30:20.021-10:30",
+        ] {
+            assert_eq!(Timestamp::parse_strict(s), Ok(timestamp), "{s}");
+            assert_eq!(s.parse::<Timestamp>(), Ok(timestamp), "{s}");
+        }
+
+        assert_eq!(
+            Timestamp::parse_strict("0001-01-01T00:00:00Z"),
+            Ok(Timestamp::MIN)
+        );
+        assert_eq!(
+            Timestamp::parse_strict("9999-12-31T23:59:59.999999999Z"),
+            Ok(Timestamp::MAX)
+        );
+        assert_eq!(
+            Timestamp::parse_strict("0000-12-31T23:59:59Z"),
+            Err(TimestampError::OutOfRange)
+        );
+        assert_eq!(
+            Timestamp::parse_strict("0001-01-01T00:00:00+00:01"),
+            Err(TimestampError::OutOfRange)
+        );
+
+        // Accepted by `FromStr`, but not by the JSON mapping.
+        for s in [
+            "1972-01-01",
+            "1972-01-01t10:00:20Z",
+            "1972-01-01 10:00:20Z",
+            "1972-01-01T10:00:20z",
+            "1972-01-01T10:00:20",
+            "1972-01-01T10:00:20+0100",
+            "1972-01-01T10:00:20+01",
+            "1972-01-01T10:00:20.0210000001Z",
+            "+10000-01-01T00:00:00Z",
+        ] {
+            assert!(s.parse::<Timestamp>().is_ok(), "{s}");
+            assert_eq!(
+                Timestamp::parse_strict(s),
+                Err(TimestampError::ParseFailure),
+                "{s}"
+            );
+        }
+
+        for s in [
+            "",
+            "1972-01-01T10:00:20.Z",
+            "1972-01-01T10:00:20.021",
+            "1972-1-01T10:00:20Z",
+            "1972-01-01T10:00:20+01:00Z",
+        ] {
+            assert_eq!(
+                Timestamp::parse_strict(s),
+                Err(TimestampError::ParseFailure),
+                "{s}"
+            );
+        }
+    }
+
     #[cfg(feature = "arbitrary")]
     #[test]
     fn check_timestamp_implements_arbitrary() {
@@ -0,0 +1,93 @@
+// This file is @generated by prost-build.
+/// Represents a whole or partial calendar date, such as a birthday. The time of
+/// day and time zone are either specified elsewhere or are insignificant. The
+/// date is relative to the Gregorian Calendar. This can represent one of the
+/// following:
+///
+/// * A full date, with non-zero year, month, and day values.
+/// * A month and day, with a zero year (for example, an anniversary).
+/// * A year on its own, with a zero month and a zero day.
+/// * A year and month, with a zero day (for example, a credit card expiration
+///   date).
+///
+/// Related types:
+///
+/// * \[google.type.TimeOfDay\]\[google.type.TimeOfDay\]
+/// * \[google.protobuf.Timestamp\]\[google.protobuf.Timestamp\]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+#[prost(field_mask)]
+pub struct Date {
+    /// Year of the date. Must be from 1 to 9999, or 0 to specify a date without
+    /// a year.
+    #[prost(int32, tag = "1")]
+    pub year: i32,
+    /// Month of a year. Must be from 1 to 12, or 0 to specify a year without a
+    /// month and day.
+    #[prost(int32, tag = "2")]
+    pub month: i32,
+    /// Day of a month. Must be from 1 to 31 and valid for the year and month, or 0
+    /// to specify a year by itself or a year and month where the day isn't
+    /// significant.
+    #[prost(int32, tag = "3")]
+    pub day: i32,
+}
+/// An object that represents a latitude/longitude pair. This is expressed as a
+/// pair of doubles to represent degrees latitude and degrees longitude. Unless
+/// specified otherwise, this object must conform to the
+/// WGS84 standard. Values must be within normalized ranges.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+#[prost(field_mask)]
+pub struct LatLng {
+    /// The latitude in degrees. It must be in the range \[-90.0, +90.0\].
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    /// The longitude in degrees. It must be in the range \[-180.0, +180.0\].
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+}
+/// Represents an amount of money with its currency type.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[prost(field_mask)]
+pub struct Money {
+    /// The three-letter currency code defined in ISO 4217.
+    #[prost(string, tag = "1")]
+    pub currency_code: ::prost::alloc::string::String,
+    /// The whole units of the amount.
+    /// For example if `currencyCode` is `"USD"`, then 1 unit is one US dollar.
+    #[prost(int64, tag = "2")]
+    pub units: i64,
+    /// Number of nano (10^-9) units of the amount.
+    /// The value must be between -999,999,999 and +999,999,999 inclusive.
+    /// If `units` is positive, `nanos` must be positive or zero.
+    /// If `units` is zero, `nanos` can be positive, zero, or negative.
+    /// If `units` is negative, `nanos` must be negative or zero.
+    /// For example $-1.75 is represented as `units`=-1 and `nanos`=-750,000,000.
+    #[prost(int32, tag = "3")]
+    pub nanos: i32,
+}
+/// Represents a time of day. The date and time zone are either not significant
+/// or are specified elsewhere. An API may choose to allow leap seconds. Related
+/// types are \[google.type.Date\]\[google.type.Date\] and
+/// `google.protobuf.Timestamp`.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+#[prost(field_mask)]
+pub struct TimeOfDay {
+    /// Hours of day in 24 hour format. Should be from 0 to 23. An API may choose
+    /// to allow the value "24:00:00" for scenarios like business closing time.
+    #[prost(int32, tag = "1")]
+    pub hours: i32,
+    /// Minutes of hour of day. Must be from 0 to 59.
+    #[prost(int32, tag = "2")]
+    pub minutes: i32,
+    /// Seconds of minutes of the time. Must normally be from 0 to 59. An API may
+    /// allow the value 60 if it allows leap-seconds.
+    #[prost(int32, tag = "3")]
+    pub seconds: i32,
+    /// Fractions of seconds in nanoseconds. Must be from 0 to 999,999,999.
+    #[prost(int32, tag = "4")]
+    pub nanos: i32,
+}
@@ -0,0 +1,298 @@
+//! Registries of the message types which may be packed into an [`Any`], for unpacking messages
+//! whose type is only known at runtime.
+
+use core::fmt;
+
+use prost::alloc::boxed::Box;
+use prost::alloc::collections::BTreeMap;
+use prost::alloc::string::String;
+use prost::alloc::vec::Vec;
+use prost::{DecodeError, Message, Name};
+
+#[cfg(doc)]
+use crate::Any;
+
+/// A message whose type is only known at runtime, as packed into or unpacked from an [`Any`].
+///
+/// `AnyMessage` is implemented for all message types which implement [`Name`], and, with the
+/// `reflect` feature, for [`DynamicMessage`](crate::reflect::DynamicMessage). A boxed
+/// `dyn AnyMessage` can be downcast to its concrete type.
+pub trait AnyMessage: core::any::Any + fmt::Debug + Send + Sync {
+    /// Returns the fully qualified name of the message type, such as `google.protobuf.Duration`.
+    fn message_name(&self) -> String;
+
+    /// Encodes the message to a newly allocated buffer.
+    fn encode_message(&self) -> Vec<u8>;
+
+    /// Converts a reference to the message into a reference to `core::any::Any`.
+    fn as_any(&self) -> &dyn core::any::Any;
+
+    /// Converts the boxed message into a boxed `core::any::Any`.
+    fn into_any(self: Box<Self>) -> Box<dyn core::any::Any>;
+}
+
+impl<M> AnyMessage for M
+where
+    M: Message + Name + fmt::Debug + 'static,
+{
+    fn message_name(&self) -> String {
+        M::full_name()
+    }
+
+    fn encode_message(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn core::any::Any> {
+        self
+    }
+}
+
+impl dyn AnyMessage {
+    /// Returns `true` if the message is of type `M`.
+    pub fn is<M: AnyMessage>(&self) -> bool {
+        self.as_any().is::<M>()
+    }
+
+    /// Returns a reference to the message if it is of type `M`.
+    pub fn downcast_ref<M: AnyMessage>(&self) -> Option<&M> {
+        self.as_any().downcast_ref()
+    }
+
+    /// Converts the message to type `M`, or returns it unchanged if it is of another type.
+    pub fn downcast<M: AnyMessage>(self: Box<Self>) -> Result<Box<M>, Box<dyn AnyMessage>> {
+        if self.is::<M>() {
+            Ok(self.into_any().downcast().expect("message type"))
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// A collection of message types, which resolves the type URLs of [`Any`] messages.
+///
+/// [`MessageRegistry`] holds Rust message types, and, with the `reflect` feature,
+/// [`DescriptorPool`](crate::reflect::DescriptorPool) resolves the message types it describes to
+/// dynamic messages. Other sources of message types, such as a schema registry, may implement
+/// the trait as well.
+pub trait TypeRegistry {
+    /// Returns the type URL of the message type with the given fully qualified name, or `None` if
+    /// the type is not in the registry.
+    fn type_url(&self, full_name: &str) -> Option<String>;
+
+    /// Decodes a message of the type with the given fully qualified name, or returns `None` if the
+    /// type is not in the registry.
+    fn decode(
+        &self,
+        full_name: &str,
+        buf: &[u8],
+    ) -> Option<Result<Box<dyn AnyMessage>, DecodeError>>;
+}
+
+/// A [`TypeRegistry`] of Rust message types.
+///
+/// ```rust
+/// use prost_types::{Any, Duration, MessageRegistry, Timestamp};
+///
+/// let registry = MessageRegistry::new()
+///     .register::<Duration>()
+///     .register::<Timestamp>();
+///
+/// let any = Any::from_msg(&Duration { seconds: 5, nanos: 0 }).unwrap();
+/// let message = any.to_msg_dyn(&registry).unwrap();
+/// assert_eq!(message.downcast_ref::<Duration>().unwrap().seconds, 5);
+/// ```
+#[derive(Clone, Default)]
+pub struct MessageRegistry {
+    types: BTreeMap<String, RegisteredType>,
+}
+
+type DecodeFn = fn(&[u8]) -> Result<Box<dyn AnyMessage>, DecodeError>;
+
+#[derive(Clone)]
+struct RegisteredType {
+    type_url: String,
+    decode: DecodeFn,
+}
+
+impl MessageRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> MessageRegistry {
+        MessageRegistry::default()
+    }
+
+    /// Registers the message type `M`, whose type URL is [`Name::type_url`].
+    ///
+    /// Registering a type whose full name is already registered replaces the previous
+    /// registration.
+    pub fn register<M>(mut self) -> Self
+    where
+        M: Message + Name + Default + fmt::Debug + 'static,
+    {
+        self.types.insert(
+            M::full_name(),
+            RegisteredType {
+                type_url: M::type_url(),
+                decode: |buf| Ok(Box::new(M::decode(buf)?)),
+            },
+        );
+        self
+    }
+
+    /// Returns `true` if the message type with the given fully qualified name is registered.
+    pub fn contains(&self, full_name: &str) -> bool {
+        self.types.contains_key(full_name)
+    }
+}
+
+impl TypeRegistry for MessageRegistry {
+    fn type_url(&self, full_name: &str) -> Option<String> {
+        self.types.get(full_name).map(|ty| ty.type_url.clone())
+    }
+
+    fn decode(
+        &self,
+        full_name: &str,
+        buf: &[u8],
+    ) -> Option<Result<Box<dyn AnyMessage>, DecodeError>> {
+        self.types.get(full_name).map(|ty| (ty.decode)(buf))
+    }
+}
+
+impl fmt::Debug for MessageRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.types.keys()).finish()
+    }
+}
+
+#[cfg(feature = "reflect")]
+mod reflect {
+    use super::*;
+
+    use prost::alloc::format;
+
+    use crate::reflect::{DescriptorPool, DynamicMessage};
+
+    impl AnyMessage for DynamicMessage {
+        fn message_name(&self) -> String {
+            self.descriptor().full_name().into()
+        }
+
+        fn encode_message(&self) -> Vec<u8> {
+            self.encode_to_vec()
+        }
+
+        fn as_any(&self) -> &dyn core::any::Any {
+            self
+        }
+
+        fn into_any(self: Box<Self>) -> Box<dyn core::any::Any> {
+            self
+        }
+    }
+
+    /// The message types of a pool have `type.googleapis.com/` type URLs, and are decoded as
+    /// [`DynamicMessage`]s.
+    impl TypeRegistry for DescriptorPool {
+        fn type_url(&self, full_name: &str) -> Option<String> {
+            let desc = self.get_message_by_name(full_name)?;
+            Some(format!("type.googleapis.com/{}", desc.full_name()))
+        }
+
+        fn decode(
+            &self,
+            full_name: &str,
+            buf: &[u8],
+        ) -> Option<Result<Box<dyn AnyMessage>, DecodeError>> {
+            let desc = self.get_message_by_name(full_name)?;
+            Some(
+                DynamicMessage::decode(desc, buf)
+                    .map(|message| Box::new(message) as Box<dyn AnyMessage>),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use prost::alloc::string::ToString;
+
+    use crate::{Any, Duration, FieldMask, Timestamp};
+
+    #[test]
+    fn message_registry() {
+        let registry = MessageRegistry::new()
+            .register::<Duration>()
+            .register::<Timestamp>();
+        assert!(registry.contains("google.protobuf.Duration"));
+        assert!(!registry.contains("google.protobuf.FieldMask"));
+
+        let timestamp = Timestamp::date(2000, 1, 1).unwrap();
+        let any = Any::from_msg_with_registry(&timestamp, &registry).unwrap();
+        assert_eq!(any, Any::from_msg(&timestamp).unwrap());
+
+        let message = any.to_msg_dyn(&registry).unwrap();
+        assert_eq!(message.message_name(), "google.protobuf.Timestamp");
+        assert!(message.is::<Timestamp>() && !message.is::<Duration>());
+        let message = message.downcast::<Duration>().unwrap_err();
+        assert_eq!(*message.downcast::<Timestamp>().unwrap(), timestamp);
+
+        // Type URLs with other domains resolve to the same type.
+        let any = Any {
+            type_url: "example.com/types/google.protobuf.Duration".into(),
+            ..Any::from_msg(&Duration::default()).unwrap()
+        };
+        assert!(any.to_msg_dyn(&registry).unwrap().is::<Duration>());
+
+        // A boxed message can be packed again.
+        let message: Box<dyn AnyMessage> = Box::new(timestamp);
+        assert_eq!(
+            Any::from_msg_with_registry(&*message, &registry).unwrap(),
+            Any::from_msg(&timestamp).unwrap()
+        );
+
+        let any = Any::from_msg(&FieldMask::default()).unwrap();
+        assert_eq!(
+            any.to_msg_dyn(&registry).unwrap_err().to_string(),
+            "failed to decode Protobuf message: unknown type URL: \
+             \"type.googleapis.com/google.protobuf.FieldMask\""
+        );
+        let any = Any {
+            type_url: "google.protobuf.Duration".into(),
+            value: Vec::new(),
+        };
+        assert!(any.to_msg_dyn(&registry).is_err());
+    }
+
+    #[cfg(feature = "reflect")]
+    #[test]
+    fn descriptor_pool_registry() {
+        use crate::reflect::{DescriptorPool, DynamicMessage};
+
+        let pool = DescriptorPool::well_known_types();
+        let duration = Duration {
+            seconds: 3,
+            nanos: 0,
+        };
+        let message = Any::from_msg(&duration)
+            .unwrap()
+            .to_msg_dyn(&pool)
+            .unwrap()
+            .downcast::<DynamicMessage>()
+            .unwrap();
+        assert_eq!(
+            message.get_field_by_name("seconds").unwrap().as_i64(),
+            Some(3)
+        );
+        assert_eq!(
+            Any::from_msg_with_registry(&*message, &pool).unwrap(),
+            Any::from_msg(&duration).unwrap()
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::hint::black_box;
+
+use criterion::{Criterion, Throughput};
+use prost::Message;
+
+#[derive(Clone, PartialEq, Message)]
+struct Leaf {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+    #[prost(string, tag = "2")]
+    name: String,
+    #[prost(bytes = "vec", tag = "3")]
+    payload: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Node {
+    #[prost(message, repeated, tag = "1")]
+    leaves: Vec<Leaf>,
+    #[prost(uint32, repeated, tag = "2")]
+    values: Vec<u32>,
+    #[prost(map = "string, int64", tag = "3")]
+    labels: HashMap<String, i64>,
+    #[prost(message, optional, boxed, tag = "4")]
+    child: Option<Box<Node>>,
+}
+
+fn node(depth: u32) -> Node {
+    Node {
+        leaves: (0..16)
+            .map(|id| Leaf {
+                id,
+                name: format!("leaf-{id}"),
+                payload: vec![id as u8; 32],
+            })
+            .collect(),
+        values: (0..64).map(|value| value << 10).collect(),
+        labels: (0..8)
+            .map(|label| (format!("label-{label}"), i64::from(label)))
+            .collect(),
+        child: depth.checked_sub(1).map(|depth| Box::new(node(depth))),
+    }
+}
+
+fn benchmark_decode(criterion: &mut Criterion, name: &str, message: Node) {
+    let buf = message.encode_to_vec();
+    let options = prost::DecodeOptions::new()
+        .max_len(1 << 20)
+        .max_repeated_len(1 << 10);
+
+    criterion
+        .benchmark_group(format!("decode/{name}"))
+        .throughput(Throughput::Bytes(buf.len() as u64))
+        .bench_function("default", |b| {
+            b.iter(|| black_box(Node::decode(black_box(buf.as_slice())).unwrap()))
+        })
+        .bench_function("options", |b| {
+            b.iter(|| {
+                black_box(Node::decode_with_options(black_box(buf.as_slice()), &options).unwrap())
+            })
+        });
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+
+    // Benchmark decoding a message with repeated, packed and map fields.
+    benchmark_decode(&mut criterion, "flat", node(0));
+
+    // Benchmark decoding eight levels of nested messages, which passes the context down.
+    benchmark_decode(&mut criterion, "nested", node(8));
+
+    criterion.final_summary();
+}
@@ -3,7 +3,7 @@ use std::mem;
 use bytes::Buf;
 use criterion::{Criterion, Throughput};
 use prost::encoding::varint::{decode_varint, encode_varint, encoded_len_varint};
-use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 
 fn benchmark_varint(criterion: &mut Criterion, name: &str, mut values: Vec<u64>) {
     // Shuffle the values in a stable order.
@@ -95,5 +95,26 @@ fn main() {
             .collect(),
     );
 
+    // Benchmark encoding and decoding 10,000 varints of random width, from 1 to 10 bytes, which
+    // defeats branch prediction of the varint length.
+    let mut rng = StdRng::seed_from_u64(0);
+    benchmark_varint(
+        &mut criterion,
+        "random",
+        (0..10_000)
+            .map(|_| {
+                let width = rng.random_range(1..=10u32);
+                let min = if width == 1 {
+                    0
+                } else {
+                    1 << ((width - 1) * 7)
+                };
+                // 10-byte varints are the values with the top bit set.
+                let max = 1u64.checked_shl(width * 7).map_or(u64::MAX, |max| max - 1);
+                rng.random_range(min..=max)
+            })
+            .collect(),
+    );
+
     criterion.final_summary();
 }
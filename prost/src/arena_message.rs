@@ -0,0 +1,74 @@
+use bumpalo::Bump;
+use bytes::Buf;
+
+use crate::encoding::wire_type::WireType;
+use crate::encoding::{decode_key, DecodeContext};
+use crate::DecodeError;
+
+/// A Protocol Buffers message decoded into a [`Bump`] arena.
+///
+/// Arena messages are generated by `prost-build` for the messages selected with
+/// [`Config::arena_messages`][1]. The `string` and `bytes` fields of an arena message are
+/// `&str` and `&[u8]` slices, and its repeated and map fields are
+/// [`bumpalo::collections::Vec`]s, all allocated in the arena. Decoding an arena message does not
+/// use the global allocator, and the memory of every message decoded into an arena is released
+/// at once when the arena is reset or dropped.
+///
+/// [1]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.arena_messages
+pub trait ArenaMessage<'bump>: Sized {
+    /// Creates an empty message in the arena.
+    fn new_in(arena: &'bump Bump) -> Self;
+
+    /// Decodes a field from a buffer, and merges it into `self`.
+    ///
+    /// Meant to be used only by `ArenaMessage` implementations.
+    #[doc(hidden)]
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        arena: &'bump Bump,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>;
+
+    /// Decodes a message from a buffer into the arena.
+    ///
+    /// The entire buffer will be consumed.
+    fn decode_in(buf: impl Buf, arena: &'bump Bump) -> Result<Self, DecodeError> {
+        let mut message = Self::new_in(arena);
+        message.merge_in(buf, arena).map(|_| message)
+    }
+
+    /// Decodes a message from a buffer into the arena, and merges it into `self`.
+    ///
+    /// The entire buffer will be consumed.
+    fn merge_in(&mut self, mut buf: impl Buf, arena: &'bump Bump) -> Result<(), DecodeError> {
+        let ctx = DecodeContext::default();
+        while buf.has_remaining() {
+            let (tag, wire_type) = decode_key(&mut buf)?;
+            self.merge_field(tag, wire_type, &mut buf, arena, ctx.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'bump, M> ArenaMessage<'bump> for &'bump mut M
+where
+    M: ArenaMessage<'bump>,
+{
+    fn new_in(arena: &'bump Bump) -> Self {
+        arena.alloc(M::new_in(arena))
+    }
+
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        arena: &'bump Bump,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        (**self).merge_field(tag, wire_type, buf, arena, ctx)
+    }
+}
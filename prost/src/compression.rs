@@ -0,0 +1,350 @@
+//! Compressed framing of messages, compatible with the message framing of gRPC.
+//!
+//! A frame is a one-byte flag, which is `1` if the message is compressed and `0` otherwise,
+//! followed by the length of the message as a four-byte big-endian integer, and the encoded,
+//! possibly compressed, message. As in gRPC, the compression algorithm is not part of the frame:
+//! the writer and the reader agree on it beforehand, e.g. with the `grpc-encoding` header, and a
+//! frame whose flag is `0` is read as is, whatever the algorithm.
+//!
+//! [`Message::encode_length_delimited_compressed`] and
+//! [`Message::decode_length_delimited_compressed`] write and read a frame. The `gzip` and `zstd`
+//! features enable the respective algorithms.
+//!
+//! A small compressed frame can expand into a huge message, so the decompressed message is
+//! limited to [`DEFAULT_MAX_MESSAGE_SIZE`], or to the
+//! [`max_message_size`](crate::DecodeOptions::max_message_size) of the options given to
+//! [`Message::decode_length_delimited_compressed_with_options`]. Decompression stops as soon as
+//! the limit is exceeded.
+//!
+//! ```rust
+//! # #[cfg(feature = "gzip")]
+//! # {
+//! use prost::compression::Compression;
+//! use prost::Message;
+//!
+//! let mut buf = Vec::new();
+//! let message = "log line ".repeat(100);
+//! message.encode_length_delimited_compressed(Compression::Gzip, &mut buf)?;
+//! assert_eq!(buf[0], 1);
+//! assert!(buf.len() < message.len());
+//!
+//! let decoded = String::decode_length_delimited_compressed(buf.as_slice(), Compression::Gzip)?;
+//! assert_eq!(decoded, message);
+//! # }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`Message::encode_length_delimited_compressed`]: crate::Message::encode_length_delimited_compressed
+//! [`Message::decode_length_delimited_compressed`]: crate::Message::decode_length_delimited_compressed
+//! [`Message::decode_length_delimited_compressed_with_options`]: crate::Message::decode_length_delimited_compressed_with_options
+
+use std::io::{Read, Write};
+
+use bytes::{Buf, BufMut};
+
+use crate::error::DecodeErrorKind;
+use crate::{DecodeError, DecodeOptions, EncodeError};
+
+/// The length of the flag and the length which precede the message in a frame.
+const HEADER_LEN: usize = 5;
+
+/// The maximum size in bytes of the message of a frame, once decompressed, when no options are
+/// given. This is the default maximum message size of gRPC, 4 MiB.
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// A compression algorithm of the messages of frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Compression {
+    /// Messages are not compressed, and frames whose flag is `1` fail to decode.
+    None,
+    /// Messages are compressed with gzip.
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// Messages are compressed with Zstandard.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the name of the algorithm in the `grpc-encoding` and `grpc-accept-encoding`
+    /// headers, e.g. `gzip`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::None => "identity",
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => "gzip",
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Returns the algorithm with the given name in the `grpc-encoding` header, or `None` if the
+    /// algorithm is not known or not enabled.
+    pub fn from_name(name: &str) -> Option<Compression> {
+        match name {
+            "identity" => Some(Compression::None),
+            #[cfg(feature = "gzip")]
+            "gzip" => Some(Compression::Gzip),
+            #[cfg(feature = "zstd")]
+            "zstd" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Compresses an encoded message, returning `None` if the algorithm does not compress.
+    fn compress(&self, encoded: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Compression::None => None,
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder
+                    .write_all(encoded)
+                    .and_then(|()| encoder.finish())
+                    .map(Some)
+                    .expect("compressing to a Vec does not fail")
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Some(
+                zstd::stream::encode_all(encoded, zstd::DEFAULT_COMPRESSION_LEVEL)
+                    .expect("compressing to a Vec does not fail"),
+            ),
+        }
+    }
+
+    /// Decompresses the message of a frame whose flag is `1`, failing if the decompressed
+    /// message is longer than `limit`.
+    fn decompress(&self, compressed: &[u8], limit: usize) -> Result<Vec<u8>, DecodeError> {
+        // Reading one byte past the limit tells a message of exactly `limit` bytes from a longer
+        // one, without decompressing more of it.
+        let take = (limit as u64).saturating_add(1);
+        let mut decompressed = Vec::new();
+        let result = match self {
+            Compression::None => {
+                return Err(DecodeErrorKind::InvalidCompressionFlag { flag: 1 }.into())
+            }
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => flate2::read::GzDecoder::new(compressed)
+                .take(take)
+                .read_to_end(&mut decompressed),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::stream::Decoder::new(compressed)
+                .and_then(|decoder| decoder.take(take).read_to_end(&mut decompressed)),
+        };
+        match result {
+            Ok(_) if decompressed.len() > limit => {
+                Err(DecodeErrorKind::MessageSizeLimitExceeded.into())
+            }
+            Ok(_) => Ok(decompressed),
+            Err(error) => Err(DecodeErrorKind::Decompression {
+                algorithm: self.name(),
+                description: error.to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Writes a frame of an encoded message, compressed with `compression`.
+pub(crate) fn encode_frame(
+    encoded: Vec<u8>,
+    compression: Compression,
+    buf: &mut impl BufMut,
+) -> Result<(), EncodeError> {
+    let (flag, message) = match compression.compress(&encoded) {
+        Some(compressed) => (1, compressed),
+        None => (0, encoded),
+    };
+    let required = HEADER_LEN + message.len();
+    let remaining = buf.remaining_mut();
+    if required > remaining {
+        return Err(EncodeError::new(required, remaining));
+    }
+    buf.put_u8(flag);
+    buf.put_u32(u32::try_from(message.len()).expect("frames are less than 4 GiB"));
+    buf.put_slice(&message);
+    Ok(())
+}
+
+/// Reads a frame, returning its encoded message, which is decompressed with `compression` if
+/// the flag of the frame is `1`. Fails if the message is longer than the `max_message_size` of
+/// `options`.
+pub(crate) fn decode_frame(
+    mut buf: impl Buf,
+    compression: Compression,
+    options: &DecodeOptions,
+) -> Result<Vec<u8>, DecodeError> {
+    if buf.remaining() < HEADER_LEN {
+        return Err(DecodeErrorKind::BufferUnderflow.into());
+    }
+    let flag = buf.get_u8();
+    let len = buf.get_u32() as usize;
+    if buf.remaining() < len {
+        return Err(DecodeErrorKind::BufferUnderflow.into());
+    }
+    let limit = options.max_message_size;
+    if flag == 0 && len > limit {
+        return Err(DecodeErrorKind::MessageSizeLimitExceeded.into());
+    }
+    let mut message = vec![0; len];
+    buf.copy_to_slice(&mut message);
+    match flag {
+        0 => Ok(message),
+        1 => compression.decompress(&message, limit),
+        flag => Err(DecodeErrorKind::InvalidCompressionFlag { flag }.into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Message;
+
+    fn frames() -> Vec<Compression> {
+        vec![
+            Compression::None,
+            #[cfg(feature = "gzip")]
+            Compression::Gzip,
+            #[cfg(feature = "zstd")]
+            Compression::Zstd,
+        ]
+    }
+
+    #[test]
+    fn compressed_roundtrip() {
+        let message = "the quick brown fox ".repeat(50);
+        for compression in frames() {
+            let mut buf = Vec::new();
+            message
+                .encode_length_delimited_compressed(compression, &mut buf)
+                .unwrap();
+            assert_eq!(buf[0], u8::from(compression != Compression::None));
+            assert_eq!(
+                u32::from_be_bytes(buf[1..5].try_into().unwrap()) as usize,
+                buf.len() - HEADER_LEN
+            );
+            assert_eq!(
+                Compression::from_name(compression.name()),
+                Some(compression)
+            );
+
+            let decoded =
+                String::decode_length_delimited_compressed(buf.as_slice(), compression).unwrap();
+            assert_eq!(decoded, message);
+        }
+    }
+
+    #[test]
+    fn uncompressed_frames_are_read_as_is() {
+        // A frame as written by gRPC, with the flag `0`.
+        let mut buf = vec![0, 0, 0, 0, 3];
+        buf.extend_from_slice(&150u32.encode_to_vec());
+        assert_eq!(buf.len(), 8);
+        for compression in frames() {
+            assert_eq!(
+                u32::decode_length_delimited_compressed(buf.as_slice(), compression),
+                Ok(150)
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_frames() {
+        let error =
+            u32::decode_length_delimited_compressed(&[0, 0, 0][..], Compression::None).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to decode Protobuf message: buffer underflow"
+        );
+
+        let error =
+            u32::decode_length_delimited_compressed(&[0, 0, 0, 0, 1][..], Compression::None)
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to decode Protobuf message: buffer underflow"
+        );
+
+        let error =
+            u32::decode_length_delimited_compressed(&[2, 0, 0, 0, 0][..], Compression::None)
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to decode Protobuf message: invalid compression flag: 2"
+        );
+
+        let error =
+            u32::decode_length_delimited_compressed(&[1, 0, 0, 0, 0][..], Compression::None)
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to decode Protobuf message: invalid compression flag: 1"
+        );
+
+        let mut buf = [0; 8];
+        let error = 150u32
+            .encode_length_delimited_compressed(Compression::None, &mut &mut buf[..7])
+            .unwrap_err();
+        assert_eq!(error.required_capacity(), 8);
+    }
+
+    #[test]
+    fn decompressed_size_is_limited() {
+        // A highly compressible message, which would expand a few KiB into more than the default
+        // limit.
+        let message = vec![0u8; DEFAULT_MAX_MESSAGE_SIZE + 1];
+        let len = message.encoded_len();
+        for compression in frames() {
+            let mut buf = Vec::new();
+            message
+                .encode_length_delimited_compressed(compression, &mut buf)
+                .unwrap();
+            if compression != Compression::None {
+                assert!(buf.len() < 64 * 1024, "{compression:?}: {}", buf.len());
+            }
+
+            let error = Vec::<u8>::decode_length_delimited_compressed(buf.as_slice(), compression)
+                .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "failed to decode Protobuf message: message size limit exceeded"
+            );
+
+            let options = DecodeOptions::new().max_message_size(len - 1);
+            let error = Vec::<u8>::decode_length_delimited_compressed_with_options(
+                buf.as_slice(),
+                compression,
+                &options,
+            )
+            .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "failed to decode Protobuf message: message size limit exceeded"
+            );
+
+            let options = DecodeOptions::new().max_message_size(len);
+            let decoded = Vec::<u8>::decode_length_delimited_compressed_with_options(
+                buf.as_slice(),
+                compression,
+                &options,
+            )
+            .unwrap();
+            assert_eq!(decoded, message);
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn corrupt_compressed_message() {
+        let error = u32::decode_length_delimited_compressed(
+            &[1, 0, 0, 0, 3, 1, 2, 3][..],
+            Compression::Gzip,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("failed to decode Protobuf message: failed to decompress gzip message:"));
+    }
+}
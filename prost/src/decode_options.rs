@@ -0,0 +1,415 @@
+/// Limits applied while decoding a message.
+///
+/// The defaults only limit the recursion depth, to 100 nested messages, and the memory reserved
+/// up front for packed repeated fields, to 64 KiB per field. Servers decoding
+/// untrusted input can tighten the limits per endpoint and pass the options to
+/// [`Message::decode_with_options`](crate::Message::decode_with_options).
+///
+/// # Examples
+///
+/// ```rust
+/// use prost::{DecodeOptions, Message};
+///
+/// let options = DecodeOptions::new()
+///     .recursion_limit(16)
+///     .max_message_size(4096)
+///     .max_len(1024)
+///     .max_repeated_len(64);
+/// let value = u32::decode_with_options(&[0x08, 0x96, 0x01][..], &options)?;
+/// assert_eq!(value, 150);
+/// # Ok::<(), prost::DecodeError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct DecodeOptions {
+    pub(crate) utf8_policy: Utf8Policy,
+    pub(crate) recursion_limit: u32,
+    pub(crate) max_message_size: usize,
+    pub(crate) max_len: usize,
+    pub(crate) max_repeated_len: usize,
+    pub(crate) max_preallocation: usize,
+}
+
+/// The default maximum number of bytes reserved up front for the elements of a packed field.
+const MAX_PREALLOCATION: usize = 64 * 1024;
+
+impl Default for DecodeOptions {
+    fn default() -> DecodeOptions {
+        DecodeOptions::DEFAULT
+    }
+}
+
+impl DecodeOptions {
+    /// The default limits, which the methods of `Message` without options decode with.
+    pub(crate) const DEFAULT: DecodeOptions = DecodeOptions {
+        recursion_limit: crate::RECURSION_LIMIT,
+        max_message_size: usize::MAX,
+        max_len: usize::MAX,
+        max_repeated_len: usize::MAX,
+        max_preallocation: MAX_PREALLOCATION,
+        utf8_policy: Utf8Policy::Strict,
+    };
+
+    /// Creates options with the default limits.
+    pub fn new() -> DecodeOptions {
+        DecodeOptions::default()
+    }
+
+    /// Sets the maximum depth of nested messages, groups and map entries.
+    ///
+    /// The limit is ignored if the `no-recursion-limit` feature is enabled.
+    pub fn recursion_limit(mut self, limit: u32) -> Self {
+        self.recursion_limit = limit;
+        self
+    }
+
+    /// Sets the maximum size in bytes of the message and of each length-delimited value in it.
+    pub fn max_message_size(mut self, limit: usize) -> Self {
+        self.max_message_size = limit;
+        self
+    }
+
+    /// Sets the maximum declared length in bytes of each length-delimited field: strings, bytes,
+    /// nested messages, packed repeated fields, map entries and unknown fields.
+    ///
+    /// The length prefix is checked as soon as it is read, before anything is allocated for the
+    /// field, so a hostile prefix claiming gigabytes fails fast with an error for which
+    /// [`DecodeError::is_length_limit_exceeded`](crate::DecodeError::is_length_limit_exceeded)
+    /// returns `true`. Unlike [`max_message_size`](DecodeOptions::max_message_size), the limit
+    /// does not apply to the top-level message, nor to its length prefix when the message is
+    /// decoded with [`decode_length_delimited_with_options`].
+    ///
+    /// [`decode_length_delimited_with_options`]: crate::Message::decode_length_delimited_with_options
+    pub fn max_len(mut self, limit: usize) -> Self {
+        self.max_len = limit;
+        self
+    }
+
+    /// Sets the maximum number of elements of each repeated field, including `HashMap` and
+    /// `BTreeMap` map fields.
+    pub fn max_repeated_len(mut self, limit: usize) -> Self {
+        self.max_repeated_len = limit;
+        self
+    }
+
+    /// Sets the maximum number of bytes reserved up front for the elements of each packed
+    /// repeated field. The default is 64 KiB.
+    ///
+    /// When decoding a packed field, the elements are reserved at once rather than as they are
+    /// decoded. The number of elements reserved is derived from the bytes actually remaining in
+    /// the input, never from a declared count, and is capped by this limit and by
+    /// [`max_repeated_len`](DecodeOptions::max_repeated_len). Fields with more elements grow as
+    /// usual, so the limit only trades allocations for memory which a small input can claim.
+    pub fn max_preallocation(mut self, limit: usize) -> Self {
+        self.max_preallocation = limit;
+        self
+    }
+
+    /// Sets how string fields whose value is not valid UTF-8 are decoded. The default is
+    /// [`Utf8Policy::Strict`].
+    pub fn utf8_policy(mut self, policy: Utf8Policy) -> Self {
+        self.utf8_policy = policy;
+        self
+    }
+}
+
+/// How string fields whose value is not valid UTF-8 are decoded.
+///
+/// Other Protobuf implementations, and files using the `utf8_validation = NONE` feature of
+/// editions, may write such values. A Rust string must be valid UTF-8, so the lenient policies
+/// convert or set aside such values instead of failing the whole message. Valid values are
+/// decoded as is whatever the policy. Borrowed `&str` fields, which point into the input, are
+/// always decoded strictly.
+///
+/// # Examples
+///
+/// ```rust
+/// use prost::{DecodeOptions, Message, Utf8Policy};
+///
+/// let buf = [0x0a, 0x03, b'c', 0xe9, b'!'];
+/// assert!(String::decode(&buf[..]).is_err());
+///
+/// let options = DecodeOptions::new().utf8_policy(Utf8Policy::Lossy);
+/// let value = String::decode_with_options(&buf[..], &options)?;
+/// assert_eq!(value, "c\u{fffd}!");
+/// # Ok::<(), prost::DecodeError>(())
+/// ```
+///
+/// Under [`Utf8Policy::Bytes`], the original bytes of the value are kept in the unknown fields
+/// of the message:
+///
+/// ```rust
+/// use prost::{DecodeOptions, Message, UnknownFieldSet, UnknownFieldValue, Utf8Policy};
+///
+/// #[derive(Clone, PartialEq, Message)]
+/// struct Record {
+///     #[prost(string, tag = "1")]
+///     name: String,
+///     #[prost(unknown_fields)]
+///     unknown: UnknownFieldSet,
+/// }
+///
+/// let buf = [0x0a, 0x03, b'c', 0xe9, b'!'];
+/// let options = DecodeOptions::new().utf8_policy(Utf8Policy::Bytes);
+/// let record = Record::decode_with_options(&buf[..], &options)?;
+/// assert_eq!(record.name, "");
+/// let field = record.unknown.iter().next().unwrap();
+/// assert_eq!(field.tag, 1);
+/// assert_eq!(field.value, UnknownFieldValue::LengthDelimited(b"c\xe9!".to_vec()));
+///
+/// // The value is re-encoded byte for byte.
+/// assert_eq!(record.encode_to_vec(), buf);
+/// # Ok::<(), prost::DecodeError>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Utf8Policy {
+    /// The value fails to decode with a `DecodeError`.
+    #[default]
+    Strict,
+    /// Each invalid sequence of the value is replaced with U+FFFD REPLACEMENT CHARACTER, as by
+    /// `String::from_utf8_lossy`. The original bytes of the value are lost.
+    Lossy,
+    /// The value is treated as bytes: it is kept byte for byte as an unknown field of the message
+    /// holding the string field, which is left unset, and is re-encoded after the known fields.
+    ///
+    /// Only string fields declared directly in a message which keeps its unknown fields, i.e.
+    /// has a `#[prost(unknown_fields)]` field, can hold bytes. Other values, such as the keys
+    /// and values of map fields, fields of a oneof and strings decoded on their own, fail to
+    /// decode as under [`Utf8Policy::Strict`], so that no byte is silently lost.
+    Bytes,
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(not(feature = "std"))]
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::encoding::{int32, message, uint64, DecodeContext, WireType};
+    use crate::{Message, UnknownFieldSet, UnknownFieldValue};
+
+    #[test]
+    fn default_options() {
+        let value = u32::decode_with_options(&[0x08, 0x96, 0x01][..], &DecodeOptions::new());
+        assert_eq!(value, Ok(150));
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-recursion-limit"))]
+    fn recursion_limit() {
+        let mut fields = UnknownFieldSet::new();
+        for _ in 0..3 {
+            let mut outer = UnknownFieldSet::new();
+            outer.push(1, UnknownFieldValue::Group(fields));
+            fields = outer;
+        }
+        let buf = fields.encode_to_vec();
+
+        let options = DecodeOptions::new().recursion_limit(3);
+        assert_eq!(
+            UnknownFieldSet::decode_with_options(buf.as_slice(), &options),
+            Ok(fields)
+        );
+        let options = DecodeOptions::new().recursion_limit(2);
+        assert_eq!(
+            UnknownFieldSet::decode_with_options(buf.as_slice(), &options)
+                .unwrap_err()
+                .to_string(),
+            "failed to decode Protobuf message: recursion limit reached"
+        );
+    }
+
+    #[test]
+    fn max_message_size() {
+        let mut fields = UnknownFieldSet::new();
+        fields.push(1, UnknownFieldValue::LengthDelimited(vec![0; 8]));
+        let buf = fields.encode_to_vec();
+        assert_eq!(buf.len(), 10);
+
+        let options = DecodeOptions::new().max_message_size(10);
+        assert!(UnknownFieldSet::decode_with_options(buf.as_slice(), &options).is_ok());
+        let options = DecodeOptions::new().max_message_size(9);
+        assert_eq!(
+            UnknownFieldSet::decode_with_options(buf.as_slice(), &options)
+                .unwrap_err()
+                .to_string(),
+            "failed to decode Protobuf message: message size limit exceeded"
+        );
+
+        let delimited = fields.encode_length_delimited_to_vec();
+        let options = DecodeOptions::new().max_message_size(10);
+        assert!(UnknownFieldSet::decode_length_delimited_with_options(
+            delimited.as_slice(),
+            &options
+        )
+        .is_ok());
+        let options = DecodeOptions::new().max_message_size(9);
+        assert!(UnknownFieldSet::decode_length_delimited_with_options(
+            delimited.as_slice(),
+            &options
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn max_len() {
+        let mut fields = UnknownFieldSet::new();
+        fields.push(1, UnknownFieldValue::LengthDelimited(vec![0; 8]));
+        fields.push(2, UnknownFieldValue::Varint(1));
+        let buf = fields.encode_to_vec();
+
+        let options = DecodeOptions::new().max_len(8);
+        assert_eq!(
+            UnknownFieldSet::decode_with_options(buf.as_slice(), &options),
+            Ok(fields.clone())
+        );
+        let options = DecodeOptions::new().max_len(7);
+        let error = UnknownFieldSet::decode_with_options(buf.as_slice(), &options).unwrap_err();
+        assert!(error.is_length_limit_exceeded());
+        assert_eq!(
+            error.to_string(),
+            "failed to decode Protobuf message: length-delimited field of 8 bytes exceeds the \
+             length limit of 7 bytes"
+        );
+
+        // A hostile length prefix fails on the limit rather than on the missing bytes.
+        let hostile = [0x0a, 0x80, 0x80, 0x80, 0x80, 0x08];
+        let error = String::decode_with_options(&hostile[..], &options).unwrap_err();
+        assert!(error.is_length_limit_exceeded());
+        let error = String::decode(&hostile[..]).unwrap_err();
+        assert!(!error.is_length_limit_exceeded());
+
+        // The length prefix of a top-level length-delimited message is not a field.
+        let delimited = fields.encode_length_delimited_to_vec();
+        assert_eq!(delimited[0], 12);
+        let options = DecodeOptions::new().max_len(8);
+        assert_eq!(
+            UnknownFieldSet::decode_length_delimited_with_options(delimited.as_slice(), &options),
+            Ok(fields)
+        );
+        let options = DecodeOptions::new().max_len(7);
+        let error =
+            UnknownFieldSet::decode_length_delimited_with_options(delimited.as_slice(), &options)
+                .unwrap_err();
+        assert!(error.is_length_limit_exceeded());
+    }
+
+    #[test]
+    fn max_preallocation() {
+        // A packed field of 1,000 one-byte varints.
+        let mut packed = vec![0xe8, 0x07];
+        packed.extend_from_slice(&[0x01; 1000]);
+
+        let options = DecodeOptions::new();
+        let ctx = DecodeContext::new(&options);
+        let mut values = Vec::<u64>::new();
+        uint64::merge_repeated(
+            WireType::LengthDelimited,
+            &mut values,
+            &mut &packed[..],
+            ctx,
+        )
+        .unwrap();
+        assert_eq!(values.len(), 1000);
+        assert!(values.capacity() >= 1000);
+
+        // The reservation is capped by the limit, but the field still decodes.
+        let options = DecodeOptions::new().max_preallocation(80);
+        let ctx = DecodeContext::new(&options);
+        let mut values = Vec::<u64>::new();
+        ctx.reserve_packed(&mut values, 1000, 1);
+        assert!((10..1000).contains(&values.capacity()));
+        uint64::merge_repeated(
+            WireType::LengthDelimited,
+            &mut values,
+            &mut &packed[..],
+            ctx,
+        )
+        .unwrap();
+        assert_eq!(values.len(), 1000);
+
+        // The reservation is capped by the remaining input, whatever the declared length.
+        let options = DecodeOptions::new();
+        let ctx = DecodeContext::new(&options);
+        let mut values = Vec::<u64>::new();
+        let hostile = [0x80, 0x80, 0x80, 0x80, 0x08, 0x01];
+        assert!(uint64::merge_repeated(
+            WireType::LengthDelimited,
+            &mut values,
+            &mut &hostile[..],
+            ctx
+        )
+        .is_err());
+        assert_eq!(values.capacity(), 0);
+
+        let options = DecodeOptions::new().max_repeated_len(4);
+        let ctx = DecodeContext::new(&options);
+        let mut values = Vec::<u64>::new();
+        ctx.reserve_packed(&mut values, 1000, 1);
+        assert!((4..1000).contains(&values.capacity()));
+    }
+
+    #[test]
+    fn utf8_policy() {
+        let buf = [0x0a, 0x03, 0xff, b'a', 0xc3];
+        let decode = |policy| {
+            let options = DecodeOptions::new().utf8_policy(policy);
+            String::decode_with_options(&buf[..], &options)
+        };
+        assert_eq!(
+            decode(Utf8Policy::Strict).unwrap_err().to_string(),
+            "failed to decode Protobuf message: invalid string value: data is not UTF-8 encoded"
+        );
+        assert_eq!(decode(Utf8Policy::Lossy).unwrap(), "\u{fffd}a\u{fffd}");
+        // A string decoded on its own has no unknown fields to keep the bytes in.
+        assert_eq!(
+            decode(Utf8Policy::Bytes).unwrap_err().to_string(),
+            "failed to decode Protobuf message: invalid string value: data is not UTF-8 encoded"
+        );
+
+        // Valid values are decoded as is.
+        let options = DecodeOptions::new().utf8_policy(Utf8Policy::Lossy);
+        let buf = "señal".to_string().encode_to_vec();
+        assert_eq!(
+            String::decode_with_options(buf.as_slice(), &options).unwrap(),
+            "señal"
+        );
+    }
+
+    #[test]
+    fn max_repeated_len() {
+        let options = DecodeOptions::new().max_repeated_len(2);
+        let ctx = DecodeContext::new(&options);
+
+        let mut values = Vec::new();
+        let packed = [0x03, 0x01, 0x02, 0x03];
+        let error = int32::merge_repeated(
+            WireType::LengthDelimited,
+            &mut values,
+            &mut &packed[..],
+            ctx.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to decode Protobuf message: repeated field length limit exceeded"
+        );
+        assert_eq!(values, [1, 2]);
+
+        let mut messages = Vec::<()>::new();
+        let mut merge = || {
+            message::merge_repeated(
+                WireType::LengthDelimited,
+                &mut messages,
+                &mut &[0x00][..],
+                ctx.clone(),
+            )
+        };
+        assert!(merge().is_ok());
+        assert!(merge().is_ok());
+        assert!(merge().is_err());
+        assert_eq!(messages.len(), 2);
+    }
+}
@@ -0,0 +1,324 @@
+//! Defining messages in Rust, without `.proto` files.
+//!
+//! `prost-build` generates Rust types annotated with `#[derive(Message)]`, `#[derive(Oneof)]` and
+//! `#[derive(Enumeration)]`, but the derives and their `#[prost(...)]` attributes are a public
+//! API of their own. A Rust-first project can use them to define messages which are
+//! wire-compatible with a Protobuf schema, without a `.proto` file or a build script.
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use prost::{Enumeration, Message, Oneof};
+//!
+//! #[derive(Clone, PartialEq, Message)]
+//! pub struct Order {
+//!     #[prost(uint64, tag = "1")]
+//!     pub id: u64,
+//!     #[prost(string, tag = "2")]
+//!     pub customer: String,
+//!     #[prost(message, repeated, tag = "3")]
+//!     pub items: Vec<Item>,
+//!     #[prost(enumeration = "Status", tag = "4")]
+//!     pub status: i32,
+//!     #[prost(map = "string, string", tag = "5")]
+//!     pub labels: HashMap<String, String>,
+//!     #[prost(oneof = "Payment", tags = "6, 7")]
+//!     pub payment: Option<Payment>,
+//!     #[prost(message, optional, tag = "8")]
+//!     pub note: Option<Note>,
+//! }
+//!
+//! #[derive(Clone, PartialEq, Message)]
+//! pub struct Item {
+//!     #[prost(string, tag = "1")]
+//!     pub sku: String,
+//!     #[prost(uint32, tag = "2")]
+//!     pub quantity: u32,
+//! }
+//!
+//! #[derive(Clone, PartialEq, Message)]
+//! pub struct Note {
+//!     #[prost(string, tag = "1")]
+//!     pub text: String,
+//! }
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+//! #[repr(i32)]
+//! pub enum Status {
+//!     Pending = 0,
+//!     Shipped = 1,
+//! }
+//!
+//! #[derive(Clone, PartialEq, Oneof)]
+//! pub enum Payment {
+//!     #[prost(string, tag = "6")]
+//!     Voucher(String),
+//!     #[prost(message, tag = "7")]
+//!     Card(Card),
+//! }
+//!
+//! #[derive(Clone, PartialEq, Message)]
+//! pub struct Card {
+//!     #[prost(string, tag = "1")]
+//!     pub number: String,
+//! }
+//!
+//! let order = Order {
+//!     id: 7,
+//!     customer: "Ferris".to_string(),
+//!     items: vec![Item {
+//!         sku: "crab-01".to_string(),
+//!         quantity: 2,
+//!     }],
+//!     status: Status::Shipped as i32,
+//!     labels: [("gift".to_string(), "yes".to_string())].into(),
+//!     payment: Some(Payment::Voucher("SPRING".to_string())),
+//!     note: None,
+//! };
+//!
+//! let encoded = order.encode_to_vec();
+//! assert_eq!(&encoded[..2], [0x08, 0x07]);
+//! assert_eq!(Order::decode(encoded.as_slice()).unwrap(), order);
+//! assert_eq!(order.status(), Status::Shipped);
+//! ```
+//!
+//! `#[derive(Message)]` and `#[derive(Enumeration)]` implement `Default`, and `#[derive(Message)]`
+//! and `#[derive(Oneof)]` implement `Debug` unless the type is annotated with
+//! `#[prost(skip_debug)]`, so these traits must not be derived as well.
+//!
+//! A message, oneof or enumeration annotated with `#[prost(arbitrary)]` implements
+//! `arbitrary::Arbitrary`, with the `arbitrary` feature. Enumeration fields then hold variants of
+//! their enumeration, and nested messages are generated up to the recursion limit of decoding.
+//!
+//! A message annotated with `#[prost(const_default)]` also gets a `DEFAULT` constant with the
+//! value of `Default::default()`, and a `default_instance()` method returning a reference to a
+//! static default message. Every field must then have a default which is a constant expression,
+//! which excludes `HashMap` fields and `String` or `Vec<u8>` fields with a non-empty default.
+//!
+//! A message annotated with `#[prost(accessors)]` gets a `new()` constructor, and for each field
+//! `foo` a `foo()` getter returning a reference, unless the field already has a getter, a
+//! `foo_mut()` method and a `with_foo(value)` method which sets the field and returns the message.
+//! This allows a message to be `#[non_exhaustive]`, and still be used from other crates.
+//!
+//! A message annotated with `#[prost(proto_cmp)]` implements [`ProtoOrd`](crate::proto_ord::ProtoOrd),
+//! ordering messages by field number, then by value. The types of all of its fields must
+//! implement `ProtoOrd`, and its oneofs must be annotated with `#[prost(proto_cmp)]` as well.
+//!
+//! # Field attributes
+//!
+//! Every field of a message has a `#[prost(...)]` attribute with its Protobuf type and its field
+//! number, the `tag`. If the tag is omitted, the field takes the tag following the one of the
+//! previous field, or `1` for the first field.
+//!
+//! | Attribute                                   | Protobuf type             | Rust type                         |
+//! |---------------------------------------------|---------------------------|-----------------------------------|
+//! | `double`, `float`                           | `double`, `float`         | `f64`, `f32`                      |
+//! | `int32`, `sint32`, `sfixed32`               | `int32`, ...              | `i32`                             |
+//! | `int64`, `sint64`, `sfixed64`               | `int64`, ...              | `i64`                             |
+//! | `uint32`, `fixed32`                         | `uint32`, `fixed32`       | `u32`                             |
+//! | `uint64`, `fixed64`                         | `uint64`, `fixed64`       | `u64`                             |
+//! | `bool`                                      | `bool`                    | `bool`                            |
+//! | `string`                                    | `string`                  | `String`                          |
+//! | `bytes = "vec"`, `bytes = "bytes"`          | `bytes`                   | `Vec<u8>`, [`bytes::Bytes`]       |
+//! | `enumeration = "Path"`                      | an enum                   | `i32`                             |
+//! | `message`                                   | a message                 | `Option<M>`                       |
+//! | `map = "key, value"`, `btree_map = "..."`   | `map<key, value>`         | `HashMap<K, V>`, `BTreeMap<K, V>` |
+//! | `oneof = "Path", tags = "..."`              | a `oneof`                 | `Option<Path>`                    |
+//! | `group`                                     | a proto2 group            | `Option<M>`                       |
+//! | `uint128`, `int128`                         | `bytes` or a message      | `u128`, `i128`                    |
+//!
+//! A `bytes` field may also hold any other container implementing
+//! [`BytesAdapter`](crate::BytesAdapter), given by its path, e.g.
+//! `bytes = "std::sync::Arc<[u8]>"`, and a `string` field any string type implementing
+//! [`StringAdapter`](crate::StringAdapter), e.g. `string = "my_crate::ShortString"`.
+//!
+//! Protobuf has no 128-bit integers, so `uint128` and `int128` fields are encoded as a `bytes`
+//! value of 16 big-endian bytes, or with `uint128 = "fixed64"` and `int128 = "fixed64"` as a
+//! nested message with the high and low 64 bits in the `fixed64` fields 1 and 2. The latter is
+//! readable by other implementations as a message such as:
+//!
+//! ```proto
+//! message UInt128 {
+//!   fixed64 high = 1;
+//!   fixed64 low = 2;
+//! }
+//! ```
+//!
+//! Repeated 128-bit integer fields are never packed, and they may not be map keys or values.
+//!
+//! A field of any other type, implementing `Default`, is encoded and decoded by the functions of
+//! a module given with `with = "path"`, as serde's `with` attribute. The module provides
+//! `encode`, `merge` and `encoded_len` functions with the signatures of the modules of
+//! [`encoding`](crate::encoding), which it may implement with them:
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! mod millis {
+//!     use prost::bytes::{Buf, BufMut};
+//!     use prost::encoding::{uint64, DecodeContext, WireType};
+//!     use prost::DecodeError;
+//!     use std::time::Duration;
+//!
+//!     pub fn encode(tag: u32, value: &Duration, buf: &mut impl BufMut) {
+//!         if !value.is_zero() {
+//!             uint64::encode(tag, &(value.as_millis() as u64), buf);
+//!         }
+//!     }
+//!
+//!     pub fn merge(
+//!         wire_type: WireType,
+//!         value: &mut Duration,
+//!         buf: &mut impl Buf,
+//!         ctx: DecodeContext,
+//!     ) -> Result<(), DecodeError> {
+//!         let mut millis = 0;
+//!         uint64::merge(wire_type, &mut millis, buf, ctx)?;
+//!         *value = Duration::from_millis(millis);
+//!         Ok(())
+//!     }
+//!
+//!     pub fn encoded_len(tag: u32, value: &Duration) -> usize {
+//!         if value.is_zero() {
+//!             0
+//!         } else {
+//!             uint64::encoded_len(tag, &(value.as_millis() as u64))
+//!         }
+//!     }
+//! }
+//!
+//! #[derive(Clone, PartialEq, prost::Message)]
+//! pub struct Request {
+//!     #[prost(with = "millis", tag = "1")]
+//!     pub timeout: Duration,
+//! }
+//!
+//! let request = Request { timeout: Duration::from_millis(1500) };
+//! assert_eq!(prost::Message::encode_to_vec(&request), [0x08, 0xdc, 0x0b]);
+//! ```
+//!
+//! Such fields are not supported in oneofs, nor by the JSON mapping, the text format and
+//! `Arbitrary`. [`Message::merge_from`](crate::Message::merge_from) merges them by decoding the
+//! encoding of the other field, so that the module defines their merge semantics.
+//!
+//! The scalar types take a label, which changes the Rust type of the field:
+//!
+//! - no label: a field without presence, of type `T`, which is not encoded if it is the default
+//!   value, as proto3 fields;
+//! - `optional`: a field with presence, of type `Option<T>`;
+//! - `required`: a proto2 required field, of type `T`, which is always encoded;
+//! - `repeated`: a repeated field, of type `Vec<T>`. Repeated numeric fields are packed, unless
+//!   annotated with `packed = "false"`.
+//!
+//! Scalar fields may have a proto2 default value, e.g. `default = "42"`, which is the value of the
+//! field in the `Default` message, and which the generated getter of an `optional` field returns
+//! when the field is not set. Message fields take the `repeated` label to become a `Vec<M>`, and
+//! `boxed` to become an `Option<Box<M>>`.
+//!
+//! Any field or oneof variant may also be marked as sensitive with `redact`. The derived `Debug`
+//! implementation then prints `<redacted>` instead of its value, and
+//! [`Message::redact`](crate::Message::redact) clears it.
+//!
+//! The JSON name of a field or oneof variant, which is its name in lowerCamelCase by default, is
+//! set with `json_name = "..."`. `prost-build` adds it to fields declared with the `json_name`
+//! option, and the JSON mapping derived with `#[prost(json)]` uses it.
+//!
+//! The presence of an `optional` scalar field may also be tracked by a bit instead of an
+//! `Option`, with `presence_bit = "..."`, the index of the bit. The field is then of type `T`,
+//! holding its default value when unset, and the bits are held by a field of type `[u32; N]`
+//! annotated with `#[prost(presence_bits)]`. The derive generates `set_`, `has_` and `clear_`
+//! methods for such fields, which must be used to change their presence:
+//!
+//! ```rust
+//! #[derive(Clone, PartialEq, prost::Message)]
+//! pub struct Settings {
+//!     #[prost(int32, optional, presence_bit = "0", tag = "1")]
+//!     pub volume: i32,
+//!     #[prost(presence_bits)]
+//!     pub presence: [u32; 1],
+//! }
+//!
+//! let mut settings = Settings::default();
+//! assert!(!settings.has_volume());
+//! settings.set_volume(0);
+//! assert!(settings.has_volume());
+//! assert_eq!(prost::Message::encode_to_vec(&settings), [0x08, 0x00]);
+//! ```
+//!
+//! The values of a map are scalar types, `enumeration(Path)` or `message`. The variants of a
+//! `#[derive(Oneof)]` enum each hold one field, with the same attributes as the fields of a message
+//! but without a label, and the `tags` of the oneof field list the tags of all variants.
+//!
+//! # Enumerations
+//!
+//! `#[derive(Enumeration)]` applies to fieldless enums whose variants all have explicit
+//! discriminants. Enumeration fields hold the `i32` value of the enum, so that unknown values
+//! are preserved; the derive generates a getter and setter for each field, which convert between
+//! the enum and its value.
+//!
+//! # Errors
+//!
+//! Invalid attributes are reported at compile time. The Rust type of a field must match its
+//! attribute:
+//!
+//! ```compile_fail
+//! #[derive(Clone, PartialEq, prost::Message)]
+//! struct Mismatch {
+//!     #[prost(int32, tag = "1")]
+//!     count: String,
+//! }
+//! ```
+//!
+//! Field numbers must be unique, including the tags of oneof fields:
+//!
+//! ```compile_fail
+//! #[derive(Clone, PartialEq, prost::Message)]
+//! struct Duplicate {
+//!     #[prost(int32, tag = "1")]
+//!     a: i32,
+//!     #[prost(string, tag = "1")]
+//!     b: String,
+//! }
+//! ```
+//!
+//! Every field needs a type attribute:
+//!
+//! ```compile_fail
+//! #[derive(Clone, PartialEq, prost::Message)]
+//! struct Untyped {
+//!     #[prost(tag = "1")]
+//!     a: i32,
+//! }
+//! ```
+//!
+//! Map fields must match both their key and value types:
+//!
+//! ```compile_fail
+//! use std::collections::HashMap;
+//!
+//! #[derive(Clone, PartialEq, prost::Message)]
+//! struct Labels {
+//!     #[prost(map = "string, int32", tag = "1")]
+//!     labels: HashMap<String, String>,
+//! }
+//! ```
+//!
+//! Oneof variants hold exactly one field:
+//!
+//! ```compile_fail
+//! #[derive(Clone, PartialEq, prost::Oneof)]
+//! enum Kind {
+//!     #[prost(int32, tag = "1")]
+//!     Pair(i32, i32),
+//! }
+//! ```
+//!
+//! Enumeration variants need a discriminant:
+//!
+//! ```compile_fail
+//! #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+//! enum Status {
+//!     Pending,
+//!     Shipped,
+//! }
+//! ```
@@ -0,0 +1,246 @@
+//! Support for comparing messages field by field.
+//!
+//! [`diff`] compares two messages of the same type and returns the paths of the fields which
+//! differ, in the same form as the paths of a field mask. Such a diff can be used to build the
+//! update mask of an update request from the original and the modified message. The traits of
+//! this module are implemented by `#[derive(Message)]` for message types annotated with
+//! `#[prost(field_mask)]`.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::field_mask::FieldMaskTree;
+
+/// The paths of the fields which differ between two messages.
+///
+/// A path descends into a singular message field which is set in both messages, and names every
+/// other field as a whole. The paths are in the order of the fields of the message types.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MessageDiff {
+    paths: Vec<String>,
+    prefix: String,
+}
+
+impl MessageDiff {
+    /// Returns `true` if the messages have no differing fields.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Returns the paths of the differing fields.
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    /// Returns `true` if the field with the dot-separated path, or one of its sub-fields, differs.
+    pub fn contains(&self, path: &str) -> bool {
+        self.paths.iter().any(|diff| {
+            diff.strip_prefix(path)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    }
+
+    /// Returns the paths of the differing fields as a tree of field names.
+    pub fn to_tree(&self) -> FieldMaskTree {
+        self.paths.iter().collect()
+    }
+
+    /// Returns the paths of the differing fields.
+    pub fn into_paths(self) -> Vec<String> {
+        self.paths
+    }
+
+    /// Adds the path of the field with the given name, relative to the message being compared.
+    pub fn add(&mut self, name: &str) {
+        let len = self.push(name);
+        self.paths.push(self.prefix.clone());
+        self.prefix.truncate(len);
+    }
+
+    fn push(&mut self, name: &str) -> usize {
+        let len = self.prefix.len();
+        if len > 0 {
+            self.prefix.push('.');
+        }
+        self.prefix.push_str(name);
+        len
+    }
+}
+
+/// Compares two messages, and returns the paths of the fields which differ.
+///
+/// Fields are compared with `PartialEq`, so a float field holding `NaN` always differs. Differences
+/// in unknown fields and extensions are not reported.
+pub fn diff<M: DiffMessage>(message: &M, other: &M) -> MessageDiff {
+    let mut diff = MessageDiff::default();
+    message.diff_fields(other, &mut diff);
+    diff
+}
+
+/// A Protobuf message which may be compared field by field.
+///
+/// This trait is implemented by `#[derive(Message)]` for message types annotated with
+/// `#[prost(field_mask)]`. Field paths use the Protobuf names of the fields, in snake_case.
+pub trait DiffMessage {
+    /// Adds the paths of the fields which differ from those of `other` to the diff.
+    fn diff_fields(&self, other: &Self, diff: &mut MessageDiff);
+}
+
+impl<M> DiffMessage for alloc::boxed::Box<M>
+where
+    M: DiffMessage,
+{
+    fn diff_fields(&self, other: &Self, diff: &mut MessageDiff) {
+        (**self).diff_fields(&**other, diff)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<M> DiffMessage for alloc::sync::Arc<M>
+where
+    M: DiffMessage,
+{
+    fn diff_fields(&self, other: &Self, diff: &mut MessageDiff) {
+        (**self).diff_fields(&**other, diff)
+    }
+}
+
+/// A value which may be held by a field of a message supporting diffs.
+///
+/// Values which are not messages have no sub-fields, so the default implementation compares them
+/// as a whole. This is implemented for the Rust types of the Protobuf scalar types, repeated and
+/// map fields, and for all [`DiffMessage`] types.
+pub trait DiffField: PartialEq {
+    /// Adds the paths of the sub-fields which differ from those of `other` to the diff, and
+    /// returns `true`, or returns `false` if the value can only differ as a whole.
+    fn diff_fields(&self, other: &Self, diff: &mut MessageDiff) -> bool {
+        let _ = (other, diff);
+        false
+    }
+}
+
+impl<M> DiffField for M
+where
+    M: DiffMessage + PartialEq,
+{
+    fn diff_fields(&self, other: &Self, diff: &mut MessageDiff) -> bool {
+        DiffMessage::diff_fields(self, other, diff);
+        true
+    }
+}
+
+/// Optional values differ as a whole if only one of them is set.
+impl<T> DiffField for Option<T>
+where
+    T: DiffField,
+{
+    fn diff_fields(&self, other: &Self, diff: &mut MessageDiff) -> bool {
+        match (self, other) {
+            (Some(value), Some(other)) => value.diff_fields(other, diff),
+            _ => false,
+        }
+    }
+}
+
+/// Lazy messages are compared by their sub-fields once both are decoded.
+#[cfg(feature = "std")]
+impl<M> DiffField for crate::Lazy<M>
+where
+    M: DiffField + crate::Message + Default,
+{
+    fn diff_fields(&self, other: &Self, diff: &mut MessageDiff) -> bool {
+        match (self.get(), other.get()) {
+            (Ok(value), Ok(other)) => value.diff_fields(other, diff),
+            _ => false,
+        }
+    }
+}
+
+macro_rules! leaf_fields {
+    ($($ty:ty),* $(,)?) => {
+        $(impl DiffField for $ty {})*
+    };
+}
+
+leaf_fields!(
+    bool,
+    i32,
+    i64,
+    u32,
+    u64,
+    f32,
+    f64,
+    (),
+    String,
+    bytes::Bytes,
+    crate::Utf8Bytes,
+);
+
+impl<T: PartialEq> DiffField for Vec<T> {}
+
+impl<K: PartialEq, V: PartialEq> DiffField for BTreeMap<K, V> {}
+
+#[cfg(feature = "std")]
+impl<K, V, S> DiffField for std::collections::HashMap<K, V, S>
+where
+    K: Eq + core::hash::Hash,
+    V: PartialEq,
+    S: core::hash::BuildHasher,
+{
+}
+
+#[doc(hidden)]
+pub mod encoding {
+    use super::*;
+
+    /// Adds the path of a differing field, or the paths of its differing sub-fields.
+    pub fn diff<T>(value: &T, other: &T, name: &str, diff: &mut MessageDiff)
+    where
+        T: DiffField,
+    {
+        if value == other {
+            return;
+        }
+        let len = diff.push(name);
+        let paths = diff.paths.len();
+        // A message which differs only in its unknown fields differs as a whole.
+        if !value.diff_fields(other, diff) || diff.paths.len() == paths {
+            diff.paths.push(diff.prefix.clone());
+        }
+        diff.prefix.truncate(len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+
+    #[test]
+    fn diff_paths() {
+        let mut diff = MessageDiff::default();
+        encoding::diff(&1, &1, "a", &mut diff);
+        assert!(diff.is_empty());
+
+        encoding::diff(&Some(1), &None, "a", &mut diff);
+        encoding::diff(&vec![1], &vec![1, 2], "b", &mut diff);
+        diff.add("c");
+        assert_eq!(diff.paths(), ["a", "b", "c"]);
+        assert!(diff.contains("a"));
+        assert!(!diff.contains("d"));
+        assert_eq!(diff.to_tree().paths(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn diff_contains() {
+        let mut diff = MessageDiff::default();
+        diff.add("user.name");
+        assert!(diff.contains("user"));
+        assert!(diff.contains("user.name"));
+        assert!(!diff.contains("use"));
+        assert!(!diff.contains("user.name_prefix"));
+        assert!(!diff.contains("user.email"));
+    }
+}
@@ -5,7 +5,9 @@
 //!
 //! This module is `pub`, but is only for prost internal use. The `prost-derive` crate needs access for its `Message` implementations.
 
+use alloc::borrow::Cow;
 use alloc::collections::BTreeMap;
+use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
 use core::mem;
@@ -15,7 +17,11 @@ use ::bytes::{Buf, BufMut, Bytes};
 
 use crate::error::DecodeErrorKind;
 use crate::DecodeError;
+use crate::DecodeOptions;
+use crate::LengthCache;
 use crate::Message;
+use crate::Utf8Bytes;
+use crate::Utf8Policy;
 
 pub mod varint;
 pub use varint::{decode_varint, encode_varint, encoded_len_varint};
@@ -28,34 +34,53 @@ pub use length_delimiter::{
 pub mod wire_type;
 pub use wire_type::{check_wire_type, WireType};
 
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+#[cfg(feature = "arena")]
+pub mod arena;
+pub mod borrowed;
+#[cfg(feature = "heapless")]
+mod heapless;
+
 /// Additional information passed to every decode/merge function.
 ///
 /// The context should be passed by value and can be freely cloned. When passing
 /// to a function which is decoding a nested object, then use `enter_recursion`.
+///
+/// The limits are read from the [`DecodeOptions`] the message is decoded with, which the context
+/// borrows rather than copies, so the context stays as small as the recursion count.
 #[derive(Clone, Debug)]
-#[cfg_attr(feature = "no-recursion-limit", derive(Default))]
-pub struct DecodeContext {
+pub struct DecodeContext<'a> {
     /// How many times we can recurse in the current decode stack before we hit
     /// the recursion limit.
     ///
-    /// The recursion limit is defined by `RECURSION_LIMIT` and cannot be
-    /// customized. The recursion limit can be ignored by building the Prost
+    /// The recursion limit defaults to `RECURSION_LIMIT` and can be customized with
+    /// `DecodeOptions`. The recursion limit can be ignored by building the Prost
     /// crate with the `no-recursion-limit` feature.
     #[cfg(not(feature = "no-recursion-limit"))]
     recurse_count: u32,
+    /// The limits of the decode.
+    options: &'a DecodeOptions,
+}
+
+impl Default for DecodeContext<'_> {
+    #[inline]
+    fn default() -> Self {
+        DecodeContext::new(&DecodeOptions::DEFAULT)
+    }
 }
 
-#[cfg(not(feature = "no-recursion-limit"))]
-impl Default for DecodeContext {
+impl<'a> DecodeContext<'a> {
+    /// Creates a context for decoding a message with the limits of `options`.
     #[inline]
-    fn default() -> DecodeContext {
+    pub(crate) fn new(options: &'a DecodeOptions) -> Self {
         DecodeContext {
-            recurse_count: crate::RECURSION_LIMIT,
+            #[cfg(not(feature = "no-recursion-limit"))]
+            recurse_count: options.recursion_limit,
+            options,
         }
     }
-}
 
-impl DecodeContext {
     /// Call this function before recursively decoding.
     ///
     /// There is no `exit` function since this function creates a new `DecodeContext`
@@ -63,16 +88,17 @@ impl DecodeContext {
     // at the previous level of recursion.
     #[cfg(not(feature = "no-recursion-limit"))]
     #[inline]
-    pub(crate) fn enter_recursion(&self) -> DecodeContext {
+    pub(crate) fn enter_recursion(&self) -> Self {
         DecodeContext {
             recurse_count: self.recurse_count - 1,
+            options: self.options,
         }
     }
 
     #[cfg(feature = "no-recursion-limit")]
     #[inline]
-    pub(crate) fn enter_recursion(&self) -> DecodeContext {
-        DecodeContext {}
+    pub(crate) fn enter_recursion(&self) -> Self {
+        self.clone()
     }
 
     /// Checks whether the recursion limit has been reached in the stack of
@@ -95,6 +121,77 @@ impl DecodeContext {
     pub(crate) fn limit_reached(&self) -> Result<(), DecodeError> {
         Ok(())
     }
+
+    /// Checks that a length-delimited value of `len` bytes does not exceed the message size
+    /// limit.
+    #[inline]
+    pub(crate) fn check_message_size(&self, len: u64) -> Result<(), DecodeError> {
+        if len > self.options.max_message_size as u64 {
+            Err(DecodeErrorKind::MessageSizeLimitExceeded.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that the declared length of a length-delimited field does not exceed the length
+    /// limit, before the length is compared with the remaining input.
+    #[inline]
+    pub(crate) fn check_len(&self, len: u64) -> Result<(), DecodeError> {
+        if len > self.options.max_len as u64 {
+            Err(DecodeErrorKind::LengthLimitExceeded {
+                len,
+                limit: self.options.max_len,
+            }
+            .into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reserves capacity for the elements of a packed field of `len` bytes, whose elements are
+    /// at least `min_width` bytes long.
+    ///
+    /// The length has already been checked against the remaining input, and the number of
+    /// elements reserved is also capped by the repeated field length limit and by the
+    /// preallocation limit, so a tiny input can't reserve a huge amount of memory.
+    #[inline]
+    pub(crate) fn reserve_packed<T>(&self, values: &mut T, len: usize, min_width: usize)
+    where
+        T: RepeatedAdapter,
+    {
+        let item_size = mem::size_of::<T::Item>().max(1);
+        let additional = (len / min_width)
+            .min(self.options.max_repeated_len.saturating_sub(values.len()))
+            .min(self.options.max_preallocation / item_size);
+        values.reserve(additional);
+    }
+
+    /// Converts the value of a string field which is not valid UTF-8 according to the UTF-8
+    /// policy, failing if the policy is strict.
+    ///
+    /// Under the bytes policy, the error holds the value so that the message can keep it as an
+    /// unknown field.
+    #[cold]
+    pub(crate) fn invalid_utf8(&self, value: &[u8]) -> Result<String, DecodeError> {
+        match self.options.utf8_policy {
+            Utf8Policy::Strict => Err(DecodeErrorKind::InvalidString.into()),
+            Utf8Policy::Lossy => Ok(String::from_utf8_lossy(value).into_owned()),
+            Utf8Policy::Bytes => Err(DecodeErrorKind::InvalidStringBytes {
+                value: value.to_vec(),
+            }
+            .into()),
+        }
+    }
+
+    /// Checks that another element may be appended to a repeated field holding `len` elements.
+    #[inline]
+    pub(crate) fn check_repeated_len(&self, len: usize) -> Result<(), DecodeError> {
+        if len >= self.options.max_repeated_len {
+            Err(DecodeErrorKind::RepeatedLengthLimitExceeded.into())
+        } else {
+            Ok(())
+        }
+    }
 }
 
 pub const MIN_TAG: u32 = 1;
@@ -140,19 +237,43 @@ pub fn merge_loop<T, M, B>(
     value: &mut T,
     buf: &mut B,
     ctx: DecodeContext,
-    mut merge: M,
+    merge: M,
 ) -> Result<(), DecodeError>
 where
     M: FnMut(&mut T, &mut B, DecodeContext) -> Result<(), DecodeError>,
     B: Buf,
 {
+    let len = decode_delimited_len(buf, &ctx)?;
+    merge_delimited(len, value, buf, ctx, merge)
+}
+
+/// Reads the length prefix of a length-delimited value, checking it against the limits of the
+/// context and the remaining input.
+#[inline]
+fn decode_delimited_len(buf: &mut impl Buf, ctx: &DecodeContext) -> Result<usize, DecodeError> {
     let len = decode_varint(buf)?;
-    let remaining = buf.remaining();
-    if len > remaining as u64 {
+    ctx.check_len(len)?;
+    ctx.check_message_size(len)?;
+    if len > buf.remaining() as u64 {
         return Err(DecodeErrorKind::BufferUnderflow.into());
     }
+    Ok(len as usize)
+}
 
-    let limit = remaining - len as usize;
+/// Decodes values until the `len` bytes following a length prefix are exhausted.
+#[inline]
+fn merge_delimited<T, M, B>(
+    len: usize,
+    value: &mut T,
+    buf: &mut B,
+    ctx: DecodeContext,
+    mut merge: M,
+) -> Result<(), DecodeError>
+where
+    M: FnMut(&mut T, &mut B, DecodeContext) -> Result<(), DecodeError>,
+    B: Buf,
+{
+    let limit = buf.remaining() - len;
     while buf.remaining() > limit {
         merge(value, buf, ctx.clone())?;
     }
@@ -163,6 +284,27 @@ where
     Ok(())
 }
 
+/// Helper function which decodes the elements of a packed repeated field, reserving capacity
+/// for them up front.
+///
+/// `min_width` is the minimum encoded length of an element, which is 1 for varints.
+fn merge_packed<T, M, B>(
+    values: &mut T,
+    min_width: usize,
+    buf: &mut B,
+    ctx: DecodeContext,
+    merge: M,
+) -> Result<(), DecodeError>
+where
+    T: RepeatedAdapter,
+    M: FnMut(&mut T, &mut B, DecodeContext) -> Result<(), DecodeError>,
+    B: Buf,
+{
+    let len = decode_delimited_len(buf, &ctx)?;
+    ctx.reserve_packed(values, len, min_width);
+    merge_delimited(len, values, buf, ctx, merge)
+}
+
 pub fn skip_field(
     wire_type: WireType,
     tag: u32,
@@ -174,7 +316,11 @@ pub fn skip_field(
         WireType::Varint => decode_varint(buf).map(|_| 0)?,
         WireType::ThirtyTwoBit => 4,
         WireType::SixtyFourBit => 8,
-        WireType::LengthDelimited => decode_varint(buf)?,
+        WireType::LengthDelimited => {
+            let len = decode_varint(buf)?;
+            ctx.check_len(len)?;
+            len
+        }
         WireType::StartGroup => loop {
             let (inner_tag, inner_wire_type) = decode_key(buf)?;
             match inner_wire_type {
@@ -213,29 +359,30 @@ macro_rules! encode_repeated {
 macro_rules! merge_repeated_numeric {
     ($ty:ty,
      $wire_type:expr,
+     $min_width:expr,
      $merge:ident,
      $merge_repeated:ident) => {
         pub fn $merge_repeated(
             wire_type: WireType,
-            values: &mut Vec<$ty>,
+            values: &mut impl RepeatedAdapter<Item = $ty>,
             buf: &mut impl Buf,
             ctx: DecodeContext,
         ) -> Result<(), DecodeError> {
             if wire_type == WireType::LengthDelimited {
                 // Packed.
-                merge_loop(values, buf, ctx, |values, buf, ctx| {
+                merge_packed(values, $min_width, buf, ctx, |values, buf, ctx| {
+                    ctx.check_repeated_len(values.len())?;
                     let mut value = Default::default();
                     $merge($wire_type, &mut value, buf, ctx)?;
-                    values.push(value);
-                    Ok(())
+                    values.push(value)
                 })
             } else {
                 // Unpacked.
                 check_wire_type($wire_type, wire_type)?;
+                ctx.check_repeated_len(values.len())?;
                 let mut value = Default::default();
                 $merge(wire_type, &mut value, buf, ctx)?;
-                values.push(value);
-                Ok(())
+                values.push(value)
             }
         }
     };
@@ -288,7 +435,7 @@ macro_rules! varint {
                 }
             }
 
-            merge_repeated_numeric!($ty, WireType::Varint, merge, merge_repeated);
+            merge_repeated_numeric!($ty, WireType::Varint, 1, merge, merge_repeated);
 
             #[inline]
             pub fn encoded_len(tag: u32, $to_uint64_value: &$ty) -> usize {
@@ -418,7 +565,7 @@ macro_rules! fixed_width {
                 }
             }
 
-            merge_repeated_numeric!($ty, $wire_type, merge, merge_repeated);
+            merge_repeated_numeric!($ty, $wire_type, $width, merge, merge_repeated);
 
             #[inline]
             pub fn encoded_len(tag: u32, _: &$ty) -> usize {
@@ -519,6 +666,175 @@ fixed_width!(
     get_i64_le
 );
 
+/// Macro which emits the modules of a 128-bit integer type, which Protobuf does not have.
+///
+/// The `$bytes` module encodes the integer as a `bytes` value of 16 big-endian bytes, in two's
+/// complement for signed integers. The `$fixed64` module encodes it as a nested message, with the
+/// high and low 64 bits in the `fixed64` fields 1 and 2.
+macro_rules! int128 {
+    ($ty:ty, $bytes:ident, $fixed64:ident) => {
+        pub mod $bytes {
+            use crate::encoding::*;
+
+            pub fn encode(tag: u32, value: &$ty, buf: &mut impl BufMut) {
+                encode_key(tag, WireType::LengthDelimited, buf);
+                encode_varint(16, buf);
+                buf.put_slice(&value.to_be_bytes());
+            }
+
+            pub fn merge(
+                wire_type: WireType,
+                value: &mut $ty,
+                buf: &mut impl Buf,
+                ctx: DecodeContext,
+            ) -> Result<(), DecodeError> {
+                check_wire_type(WireType::LengthDelimited, wire_type)?;
+                let len = decode_varint(buf)?;
+                ctx.check_len(len)?;
+                if len > buf.remaining() as u64 {
+                    return Err(DecodeErrorKind::BufferUnderflow.into());
+                }
+                if len != 16 {
+                    return Err(DecodeErrorKind::InvalidInt128 { len }.into());
+                }
+                let mut bytes = [0; 16];
+                buf.copy_to_slice(&mut bytes);
+                *value = <$ty>::from_be_bytes(bytes);
+                Ok(())
+            }
+
+            encode_repeated!($ty);
+
+            pub fn merge_repeated(
+                wire_type: WireType,
+                values: &mut impl RepeatedAdapter<Item = $ty>,
+                buf: &mut impl Buf,
+                ctx: DecodeContext,
+            ) -> Result<(), DecodeError> {
+                check_wire_type(WireType::LengthDelimited, wire_type)?;
+                ctx.check_repeated_len(values.len())?;
+                let mut value = 0;
+                merge(wire_type, &mut value, buf, ctx)?;
+                values.push(value)
+            }
+
+            #[inline]
+            pub fn encoded_len(tag: u32, _: &$ty) -> usize {
+                key_len(tag) + 17
+            }
+
+            #[inline]
+            pub fn encoded_len_repeated(tag: u32, values: &[$ty]) -> usize {
+                (key_len(tag) + 17) * values.len()
+            }
+
+            #[cfg(test)]
+            mod test {
+                use proptest::prelude::*;
+
+                use super::super::test::{check_collection_type, check_type};
+                use super::*;
+
+                proptest! {
+                    #[test]
+                    fn check(value: $ty, tag in MIN_TAG..=MAX_TAG) {
+                        check_type(value, tag, WireType::LengthDelimited,
+                                   encode, merge, encoded_len)?;
+                    }
+                    #[test]
+                    fn check_repeated(value: Vec<$ty>, tag in MIN_TAG..=MAX_TAG) {
+                        check_collection_type(value, tag, WireType::LengthDelimited,
+                                              encode_repeated, merge_repeated,
+                                              encoded_len_repeated)?;
+                    }
+                }
+            }
+        }
+
+        pub mod $fixed64 {
+            use crate::encoding::*;
+
+            pub fn encode(tag: u32, value: &$ty, buf: &mut impl BufMut) {
+                let value = *value as u128;
+                encode_key(tag, WireType::LengthDelimited, buf);
+                encode_varint(18, buf);
+                fixed64::encode(1, &((value >> 64) as u64), buf);
+                fixed64::encode(2, &(value as u64), buf);
+            }
+
+            pub fn merge(
+                wire_type: WireType,
+                value: &mut $ty,
+                buf: &mut impl Buf,
+                ctx: DecodeContext,
+            ) -> Result<(), DecodeError> {
+                check_wire_type(WireType::LengthDelimited, wire_type)?;
+                // A half which is not encoded is zero, as a field of a nested message.
+                let mut halves = (0, 0);
+                merge_loop(&mut halves, buf, ctx, |(high, low), buf, ctx| {
+                    let (tag, wire_type) = decode_key(buf)?;
+                    match tag {
+                        1 => fixed64::merge(wire_type, high, buf, ctx),
+                        2 => fixed64::merge(wire_type, low, buf, ctx),
+                        _ => skip_field(wire_type, tag, buf, ctx),
+                    }
+                })?;
+                *value = (u128::from(halves.0) << 64 | u128::from(halves.1)) as $ty;
+                Ok(())
+            }
+
+            encode_repeated!($ty);
+
+            pub fn merge_repeated(
+                wire_type: WireType,
+                values: &mut impl RepeatedAdapter<Item = $ty>,
+                buf: &mut impl Buf,
+                ctx: DecodeContext,
+            ) -> Result<(), DecodeError> {
+                check_wire_type(WireType::LengthDelimited, wire_type)?;
+                ctx.check_repeated_len(values.len())?;
+                let mut value = 0;
+                merge(wire_type, &mut value, buf, ctx)?;
+                values.push(value)
+            }
+
+            #[inline]
+            pub fn encoded_len(tag: u32, _: &$ty) -> usize {
+                key_len(tag) + 19
+            }
+
+            #[inline]
+            pub fn encoded_len_repeated(tag: u32, values: &[$ty]) -> usize {
+                (key_len(tag) + 19) * values.len()
+            }
+
+            #[cfg(test)]
+            mod test {
+                use proptest::prelude::*;
+
+                use super::super::test::{check_collection_type, check_type};
+                use super::*;
+
+                proptest! {
+                    #[test]
+                    fn check(value: $ty, tag in MIN_TAG..=MAX_TAG) {
+                        check_type(value, tag, WireType::LengthDelimited,
+                                   encode, merge, encoded_len)?;
+                    }
+                    #[test]
+                    fn check_repeated(value: Vec<$ty>, tag in MIN_TAG..=MAX_TAG) {
+                        check_collection_type(value, tag, WireType::LengthDelimited,
+                                              encode_repeated, merge_repeated,
+                                              encoded_len_repeated)?;
+                    }
+                }
+            }
+        }
+    };
+}
+int128!(u128, uint128, uint128_fixed64);
+int128!(i128, int128, int128_fixed64);
+
 /// Macro which emits encoding functions for a length-delimited type.
 macro_rules! length_delimited {
     ($ty:ty) => {
@@ -526,15 +842,15 @@ macro_rules! length_delimited {
 
         pub fn merge_repeated(
             wire_type: WireType,
-            values: &mut Vec<$ty>,
+            values: &mut impl RepeatedAdapter<Item = $ty>,
             buf: &mut impl Buf,
             ctx: DecodeContext,
         ) -> Result<(), DecodeError> {
             check_wire_type(WireType::LengthDelimited, wire_type)?;
+            ctx.check_repeated_len(values.len())?;
             let mut value = Default::default();
             merge(wire_type, &mut value, buf, ctx)?;
-            values.push(value);
-            Ok(())
+            values.push(value)
         }
 
         #[inline]
@@ -554,18 +870,143 @@ macro_rules! length_delimited {
     };
 }
 
-pub mod string {
-    use super::*;
+/// A container holding the value of a `string` field.
+///
+/// This is implemented for [`String`], `Cow<'static, str>` and [`Utf8Bytes`], and may be
+/// implemented for other string types, such as small strings which store short values inline, so
+/// that messages with many short strings decode without allocating. Such a type is used for a field
+/// with `#[prost(string = "path::to::Type")]`, or with `Config::string_type` in `prost-build`, and
+/// must also implement `AsRef<str>`, `From<&str>`, `Clone`, `PartialEq` and `Debug`.
+///
+/// ```
+/// use prost::{Message, StringAdapter};
+///
+/// /// A string of up to 16 bytes, stored inline.
+/// #[derive(Clone, Debug, Default, PartialEq)]
+/// struct Tag {
+///     len: u8,
+///     bytes: [u8; 16],
+/// }
+///
+/// impl AsRef<str> for Tag {
+///     fn as_ref(&self) -> &str {
+///         core::str::from_utf8(&self.bytes[..self.len as usize]).unwrap()
+///     }
+/// }
+///
+/// impl From<&str> for Tag {
+///     fn from(value: &str) -> Tag {
+///         let mut tag = Tag::default();
+///         tag.replace_with(value);
+///         tag
+///     }
+/// }
+///
+/// impl StringAdapter for Tag {
+///     fn as_str(&self) -> &str {
+///         self.as_ref()
+///     }
+///
+///     fn capacity(&self) -> usize {
+///         self.bytes.len()
+///     }
+///
+///     fn replace_with(&mut self, value: &str) {
+///         self.bytes[..value.len()].copy_from_slice(value.as_bytes());
+///         self.len = value.len() as u8;
+///     }
+/// }
+///
+/// #[derive(Clone, PartialEq, Message)]
+/// struct Labels {
+///     #[prost(string = "Tag", repeated, tag = "1")]
+///     tags: Vec<Tag>,
+/// }
+///
+/// let labels = Labels { tags: vec![Tag::from("red"), Tag::from("round")] };
+/// assert_eq!(Labels::decode(labels.encode_to_vec().as_slice()).unwrap(), labels);
+/// ```
+pub trait StringAdapter: Default + Sized + 'static {
+    /// Returns the string.
+    fn as_str(&self) -> &str;
+
+    /// The maximum length in bytes of the strings the container can hold. A longer value fails
+    /// to decode with a `DecodeError`.
+    fn capacity(&self) -> usize {
+        usize::MAX
+    }
 
-    pub fn encode(tag: u32, value: &String, buf: &mut impl BufMut) {
-        encode_key(tag, WireType::LengthDelimited, buf);
-        encode_varint(value.len() as u64, buf);
-        buf.put_slice(value.as_bytes());
+    /// Replaces the contents of this container with the string, which is no longer than
+    /// [`capacity`](Self::capacity).
+    fn replace_with(&mut self, value: &str);
+
+    /// Returns the length of the string in bytes.
+    fn len(&self) -> usize {
+        self.as_str().len()
     }
 
-    pub fn merge(
+    /// Returns `true` if the string is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Replaces the string with a length-delimited value read from the buffer.
+    ///
+    /// The default implementation validates the value in place if it is contiguous in the
+    /// buffer, so that it is copied once, into the container. The string is cleared on error.
+    #[doc(hidden)]
+    fn merge(
+        &mut self,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        *self = Self::default();
+        check_wire_type(WireType::LengthDelimited, wire_type)?;
+        let len = decode_varint(buf)?;
+        ctx.check_len(len)?;
+        if len > buf.remaining() as u64 {
+            return Err(DecodeErrorKind::BufferUnderflow.into());
+        }
+        let len = len as usize;
+        if len > self.capacity() {
+            return Err(DecodeErrorKind::CapacityExceeded.into());
+        }
+
+        if buf.chunk().len() >= len {
+            if let Ok(value) = str::from_utf8(&buf.chunk()[..len]) {
+                self.replace_with(value);
+                buf.advance(len);
+                return Ok(());
+            }
+        }
+        let mut value = Vec::with_capacity(len);
+        value.put(buf.take(len));
+        let value = match String::from_utf8(value) {
+            Ok(value) => value,
+            Err(error) => ctx.invalid_utf8(error.as_bytes())?,
+        };
+        if value.len() > self.capacity() {
+            return Err(DecodeErrorKind::CapacityExceeded.into());
+        }
+        self.replace_with(&value);
+        Ok(())
+    }
+}
+
+impl StringAdapter for String {
+    fn as_str(&self) -> &str {
+        self
+    }
+
+    fn replace_with(&mut self, value: &str) {
+        self.clear();
+        self.push_str(value);
+    }
+
+    fn merge(
+        &mut self,
         wire_type: WireType,
-        value: &mut String,
         buf: &mut impl Buf,
         ctx: DecodeContext,
     ) -> Result<(), DecodeError> {
@@ -591,20 +1032,100 @@ pub mod string {
                 }
             }
 
-            let drop_guard = DropGuard(value.as_mut_vec());
-            bytes::merge_one_copy(wire_type, drop_guard.0, buf, ctx)?;
+            let drop_guard = DropGuard(self.as_mut_vec());
+            bytes::merge_one_copy(wire_type, drop_guard.0, buf, ctx.clone())?;
             match str::from_utf8(drop_guard.0) {
                 Ok(_) => {
                     // Success; do not clear the bytes.
                     mem::forget(drop_guard);
                     Ok(())
                 }
-                Err(_) => Err(DecodeErrorKind::InvalidString.into()),
+                Err(_) => {
+                    let value = ctx.invalid_utf8(drop_guard.0)?;
+                    drop(drop_guard);
+                    *self = value;
+                    Ok(())
+                }
             }
         }
     }
+}
 
-    length_delimited!(String);
+/// A borrowed string is replaced by an owned one when the field is decoded, and an owned string
+/// is decoded in place like a `String`.
+impl StringAdapter for Cow<'static, str> {
+    fn as_str(&self) -> &str {
+        self
+    }
+
+    fn replace_with(&mut self, value: &str) {
+        match self {
+            Cow::Owned(string) => string.replace_with(value),
+            Cow::Borrowed(_) => *self = Cow::Owned(value.into()),
+        }
+    }
+
+    fn merge(
+        &mut self,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        if let Cow::Borrowed(_) = self {
+            *self = Cow::Owned(String::new());
+        }
+        StringAdapter::merge(self.to_mut(), wire_type, buf, ctx)
+    }
+}
+
+impl StringAdapter for Utf8Bytes {
+    fn as_str(&self) -> &str {
+        self
+    }
+
+    fn replace_with(&mut self, value: &str) {
+        *self = Utf8Bytes::copy_from_str(value);
+    }
+
+    fn merge(
+        &mut self,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        // The string is cleared on error, as for `String`. When `buf` is `Bytes`, the value shares
+        // its memory instead of being copied.
+        self.clear();
+        let mut value = Bytes::new();
+        bytes::merge(wire_type, &mut value, buf, ctx.clone())?;
+        *self = match Utf8Bytes::try_from(value.clone()) {
+            Ok(value) => value,
+            Err(_) => Utf8Bytes::from(ctx.invalid_utf8(&value)?),
+        };
+        Ok(())
+    }
+}
+
+pub mod string {
+    use super::*;
+
+    pub fn encode(tag: u32, value: &impl StringAdapter, buf: &mut impl BufMut) {
+        let value = value.as_str();
+        encode_key(tag, WireType::LengthDelimited, buf);
+        encode_varint(value.len() as u64, buf);
+        buf.put_slice(value.as_bytes());
+    }
+
+    pub fn merge(
+        wire_type: WireType,
+        value: &mut impl StringAdapter,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        value.merge(wire_type, buf, ctx)
+    }
+
+    length_delimited!(impl StringAdapter);
 
     #[cfg(test)]
     mod test {
@@ -616,8 +1137,25 @@ pub mod string {
         proptest! {
             #[test]
             fn check(value: String, tag in MIN_TAG..=MAX_TAG) {
-                super::test::check_type(value, tag, WireType::LengthDelimited,
-                                        encode, merge, encoded_len)?;
+                super::test::check_type::<String, String>(value, tag, WireType::LengthDelimited,
+                                                          encode, merge, encoded_len)?;
+            }
+            #[test]
+            fn check_utf8_bytes(value: String, tag in MIN_TAG..=MAX_TAG) {
+                let value = Utf8Bytes::from(value);
+                super::test::check_type::<Utf8Bytes, Utf8Bytes>(value, tag, WireType::LengthDelimited,
+                                                                encode, merge, encoded_len)?;
+            }
+            #[test]
+            fn check_custom(value: String, tag in MIN_TAG..=MAX_TAG) {
+                super::test::check_type::<Custom, Custom>(Custom(value), tag, WireType::LengthDelimited,
+                                                          encode, merge, encoded_len)?;
+            }
+            #[test]
+            fn check_cow(value: String, tag in MIN_TAG..=MAX_TAG) {
+                super::test::check_type::<Cow<'static, str>, Cow<'static, str>>(Cow::Owned(value), tag,
+                                                                              WireType::LengthDelimited,
+                                                                              encode, merge, encoded_len)?;
             }
             #[test]
             fn check_repeated(value: Vec<String>, tag in MIN_TAG..=MAX_TAG) {
@@ -626,32 +1164,167 @@ pub mod string {
                                                    encoded_len_repeated)?;
             }
         }
+
+        /// A string type using the default `merge` of `StringAdapter`.
+        #[derive(Debug, Default, PartialEq)]
+        struct Custom(String);
+
+        impl StringAdapter for Custom {
+            fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            fn replace_with(&mut self, value: &str) {
+                self.0 = value.into();
+            }
+        }
+
+        #[test]
+        fn merge_borrowed_cow() {
+            let mut value = Cow::Borrowed("old");
+            let mut buf = &b"\x03abc"[..];
+            merge(
+                WireType::LengthDelimited,
+                &mut value,
+                &mut buf,
+                DecodeContext::default(),
+            )
+            .unwrap();
+            assert!(matches!(value, Cow::Owned(ref value) if value == "abc"));
+        }
+
+        #[test]
+        fn merge_custom_from_chunks() {
+            let mut value = Custom("old".into());
+            let mut buf = (&b"\x03ab"[..]).chain(&b"c"[..]);
+            merge(
+                WireType::LengthDelimited,
+                &mut value,
+                &mut buf,
+                DecodeContext::default(),
+            )
+            .unwrap();
+            assert_eq!(value.as_str(), "abc");
+
+            let mut buf = &b"\x02\xff\xfe"[..];
+            merge(
+                WireType::LengthDelimited,
+                &mut value,
+                &mut buf,
+                DecodeContext::default(),
+            )
+            .unwrap_err();
+            assert_eq!(value.as_str(), "");
+        }
+    }
+}
+
+/// A container holding the value of a `bytes` field.
+///
+/// This is implemented for `Vec<u8>`, [`Bytes`], `Box<[u8]>`, `Arc<[u8]>` and `Cow<'static, [u8]>`,
+/// and may be implemented for other containers, such as small vectors with inline storage. Such a
+/// type is used for a field with `#[prost(bytes = "path::to::Type")]`, or with `Config::bytes_type`
+/// in `prost-build`, and must also implement `AsRef<[u8]>`, `Clone`, `PartialEq` and `Debug`.
+///
+/// ```
+/// use prost::bytes::{Buf, BufMut};
+/// use prost::{BytesAdapter, Message};
+///
+/// #[derive(Clone, Debug, Default, PartialEq)]
+/// struct Blob(Vec<u8>);
+///
+/// impl AsRef<[u8]> for Blob {
+///     fn as_ref(&self) -> &[u8] {
+///         &self.0
+///     }
+/// }
+///
+/// impl BytesAdapter for Blob {
+///     fn len(&self) -> usize {
+///         self.0.len()
+///     }
+///
+///     fn replace_with(&mut self, buf: impl Buf) {
+///         self.0.clear();
+///         self.0.put(buf);
+///     }
+///
+///     fn append_to(&self, buf: &mut impl BufMut) {
+///         buf.put_slice(&self.0);
+///     }
+/// }
+///
+/// #[derive(Clone, PartialEq, Message)]
+/// struct Upload {
+///     #[prost(bytes = "Blob", tag = "1")]
+///     data: Blob,
+/// }
+///
+/// let upload = Upload { data: Blob(b"data".to_vec()) };
+/// assert_eq!(Upload::decode(upload.encode_to_vec().as_slice()).unwrap(), upload);
+/// ```
+pub trait BytesAdapter: Default + Sized + 'static {
+    /// Returns the number of bytes.
+    fn len(&self) -> usize;
+
+    /// The maximum number of bytes the container can hold. A longer value fails to decode with
+    /// a `DecodeError`.
+    fn capacity(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Replaces the contents of this container with the contents of the buffer.
+    fn replace_with(&mut self, buf: impl Buf);
+
+    /// Appends the contents of this container to the buffer.
+    fn append_to(&self, buf: &mut impl BufMut);
+
+    /// Returns `true` if the container holds no bytes.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 }
 
-pub trait BytesAdapter: sealed::BytesAdapter {}
+/// A collection of the values of a repeated field.
+pub trait RepeatedAdapter: sealed::RepeatedAdapter {}
 
 mod sealed {
-    use super::{Buf, BufMut};
+    use super::DecodeError;
 
-    pub trait BytesAdapter: Default + Sized + 'static {
-        fn len(&self) -> usize;
+    pub trait RepeatedAdapter {
+        type Item;
 
-        /// Replace contents of this buffer with the contents of another buffer.
-        fn replace_with(&mut self, buf: impl Buf);
+        /// The number of values.
+        fn len(&self) -> usize;
 
-        /// Appends this buffer to the (contents of) other buffer.
-        fn append_to(&self, buf: &mut impl BufMut);
+        /// Appends a decoded value.
+        fn push(&mut self, value: Self::Item) -> Result<(), DecodeError>;
 
-        fn is_empty(&self) -> bool {
-            self.len() == 0
-        }
+        /// Reserves capacity for at least `additional` more values, if the collection grows.
+        fn reserve(&mut self, _additional: usize) {}
     }
 }
 
-impl BytesAdapter for Bytes {}
+impl<T> RepeatedAdapter for Vec<T> {}
+
+impl<T> sealed::RepeatedAdapter for Vec<T> {
+    type Item = T;
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn push(&mut self, value: T) -> Result<(), DecodeError> {
+        Vec::push(self, value);
+        Ok(())
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+}
 
-impl sealed::BytesAdapter for Bytes {
+impl BytesAdapter for Bytes {
     fn len(&self) -> usize {
         Buf::remaining(self)
     }
@@ -665,9 +1338,7 @@ impl sealed::BytesAdapter for Bytes {
     }
 }
 
-impl BytesAdapter for Vec<u8> {}
-
-impl sealed::BytesAdapter for Vec<u8> {
+impl BytesAdapter for Vec<u8> {
     fn len(&self) -> usize {
         Vec::len(self)
     }
@@ -683,6 +1354,56 @@ impl sealed::BytesAdapter for Vec<u8> {
     }
 }
 
+impl BytesAdapter for alloc::boxed::Box<[u8]> {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn replace_with(&mut self, buf: impl Buf) {
+        let mut value = Vec::with_capacity(buf.remaining());
+        value.put(buf);
+        *self = value.into_boxed_slice();
+    }
+
+    fn append_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(self)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl BytesAdapter for alloc::sync::Arc<[u8]> {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn replace_with(&mut self, mut buf: impl Buf) {
+        *self = buf.copy_to_bytes(buf.remaining()).as_ref().into();
+    }
+
+    fn append_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(self)
+    }
+}
+
+/// A borrowed value is replaced by an owned one when the field is decoded, and an owned value is
+/// decoded in place like a `Vec<u8>`.
+impl BytesAdapter for Cow<'static, [u8]> {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn replace_with(&mut self, buf: impl Buf) {
+        if let Cow::Borrowed(_) = self {
+            *self = Cow::Owned(Vec::new());
+        }
+        self.to_mut().replace_with(buf)
+    }
+
+    fn append_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(self)
+    }
+}
+
 pub mod bytes {
     use crate::error::DecodeErrorKind;
 
@@ -698,14 +1419,18 @@ pub mod bytes {
         wire_type: WireType,
         value: &mut impl BytesAdapter,
         buf: &mut impl Buf,
-        _ctx: DecodeContext,
+        ctx: DecodeContext,
     ) -> Result<(), DecodeError> {
         check_wire_type(WireType::LengthDelimited, wire_type)?;
         let len = decode_varint(buf)?;
+        ctx.check_len(len)?;
         if len > buf.remaining() as u64 {
             return Err(DecodeErrorKind::BufferUnderflow.into());
         }
         let len = len as usize;
+        if len > value.capacity() {
+            return Err(DecodeErrorKind::CapacityExceeded.into());
+        }
 
         // Clear the existing value. This follows from the following rule in the encoding guide[1]:
         //
@@ -727,14 +1452,18 @@ pub mod bytes {
         wire_type: WireType,
         value: &mut impl BytesAdapter,
         buf: &mut impl Buf,
-        _ctx: DecodeContext,
+        ctx: DecodeContext,
     ) -> Result<(), DecodeError> {
         check_wire_type(WireType::LengthDelimited, wire_type)?;
         let len = decode_varint(buf)?;
+        ctx.check_len(len)?;
         if len > buf.remaining() as u64 {
             return Err(DecodeErrorKind::BufferUnderflow.into());
         }
         let len = len as usize;
+        if len > value.capacity() {
+            return Err(DecodeErrorKind::CapacityExceeded.into());
+        }
 
         // If we must copy, make sure to copy only once.
         value.replace_with(buf.take(len));
@@ -745,6 +1474,9 @@ pub mod bytes {
 
     #[cfg(test)]
     mod test {
+        use alloc::boxed::Box;
+        use alloc::sync::Arc;
+
         use proptest::prelude::*;
 
         use super::super::test::{check_collection_type, check_type};
@@ -764,6 +1496,27 @@ pub mod bytes {
                                                         encode, merge, encoded_len)?;
             }
 
+            #[test]
+            fn check_boxed_slice(value: Vec<u8>, tag in MIN_TAG..=MAX_TAG) {
+                let value = Box::<[u8]>::from(value);
+                super::test::check_type::<Box<[u8]>, Box<[u8]>>(value, tag, WireType::LengthDelimited,
+                                                                encode, merge, encoded_len)?;
+            }
+
+            #[test]
+            fn check_arc_slice(value: Vec<u8>, tag in MIN_TAG..=MAX_TAG) {
+                let value = Arc::<[u8]>::from(value);
+                super::test::check_type::<Arc<[u8]>, Arc<[u8]>>(value, tag, WireType::LengthDelimited,
+                                                                encode, merge, encoded_len)?;
+            }
+
+            #[test]
+            fn check_cow(value: Vec<u8>, tag in MIN_TAG..=MAX_TAG) {
+                super::test::check_type::<Cow<'static, [u8]>, Cow<'static, [u8]>>(Cow::Owned(value), tag,
+                                                                                WireType::LengthDelimited,
+                                                                                encode, merge, encoded_len)?;
+            }
+
             #[test]
             fn check_repeated_vec(value: Vec<Vec<u8>>, tag in MIN_TAG..=MAX_TAG) {
                 super::test::check_collection_type(value, tag, WireType::LengthDelimited,
@@ -773,7 +1526,7 @@ pub mod bytes {
 
             #[test]
             fn check_repeated_bytes(value: Vec<Vec<u8>>, tag in MIN_TAG..=MAX_TAG) {
-                let value = value.into_iter().map(Bytes::from).collect();
+                let value: Vec<Bytes> = value.into_iter().map(Bytes::from).collect();
                 super::test::check_collection_type(value, tag, WireType::LengthDelimited,
                                                    encode_repeated, merge_repeated,
                                                    encoded_len_repeated)?;
@@ -817,6 +1570,36 @@ pub mod message {
         )
     }
 
+    /// Merges a length-delimited message which is decoded on its own rather than as a field, so
+    /// that its length prefix is only limited by the message size limit of the context, and not
+    /// by its length limit.
+    pub(crate) fn merge_top_level<M, B>(
+        msg: &mut M,
+        buf: &mut B,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: Message,
+        B: Buf,
+    {
+        ctx.limit_reached()?;
+        let len = decode_varint(buf)?;
+        ctx.check_message_size(len)?;
+        if len > buf.remaining() as u64 {
+            return Err(DecodeErrorKind::BufferUnderflow.into());
+        }
+        merge_delimited(
+            len as usize,
+            msg,
+            buf,
+            ctx.enter_recursion(),
+            |msg: &mut M, buf: &mut B, ctx| {
+                let (tag, wire_type) = decode_key(buf)?;
+                msg.merge_field(tag, wire_type, buf, ctx)
+            },
+        )
+    }
+
     pub fn encode_repeated<M>(tag: u32, messages: &[M], buf: &mut impl BufMut)
     where
         M: Message,
@@ -828,7 +1611,7 @@ pub mod message {
 
     pub fn merge_repeated<M>(
         wire_type: WireType,
-        messages: &mut Vec<M>,
+        messages: &mut impl RepeatedAdapter<Item = M>,
         buf: &mut impl Buf,
         ctx: DecodeContext,
     ) -> Result<(), DecodeError>
@@ -836,10 +1619,13 @@ pub mod message {
         M: Message + Default,
     {
         check_wire_type(WireType::LengthDelimited, wire_type)?;
+        ctx.check_repeated_len(messages.len())?;
         let mut msg = M::default();
-        merge(WireType::LengthDelimited, &mut msg, buf, ctx)?;
-        messages.push(msg);
-        Ok(())
+        merge(WireType::LengthDelimited, &mut msg, buf, ctx).map_err(|mut error| {
+            error.push_index(messages.len());
+            error
+        })?;
+        messages.push(msg)
     }
 
     #[inline]
@@ -863,6 +1649,113 @@ pub mod message {
                 .map(|len| len + encoded_len_varint(len as u64))
                 .sum::<usize>()
     }
+
+    /// Encodes a nested message with the deterministic encoding of
+    /// [`Message::encode_deterministic`].
+    pub fn encode_deterministic<M>(tag: u32, msg: &M, buf: &mut impl BufMut)
+    where
+        M: Message,
+    {
+        encode_key(tag, WireType::LengthDelimited, buf);
+        encode_varint(msg.encoded_len() as u64, buf);
+        msg.encode_raw_deterministic(buf);
+    }
+
+    /// Encodes a nested message with the length recorded by [`encoded_len_cached`].
+    pub fn encode_cached<M>(tag: u32, msg: &M, buf: &mut impl BufMut, cache: &mut LengthCache)
+    where
+        M: Message,
+    {
+        encode_key(tag, WireType::LengthDelimited, buf);
+        encode_varint(cache.next_len() as u64, buf);
+        msg.encode_raw_cached(buf, cache);
+    }
+
+    /// Returns the encoded length of a nested message, and records it in `cache` before the
+    /// lengths of its own nested messages, in the order in which [`encode_cached`] reads them.
+    pub fn encoded_len_cached<M>(tag: u32, msg: &M, cache: &mut LengthCache) -> usize
+    where
+        M: Message,
+    {
+        let index = cache.reserve();
+        let len = msg.encoded_len_cached(cache);
+        cache.set(index, len);
+        key_len(tag) + encoded_len_varint(len as u64) + len
+    }
+
+    pub fn encoded_len_repeated_cached<M>(
+        tag: u32,
+        messages: &[M],
+        cache: &mut LengthCache,
+    ) -> usize
+    where
+        M: Message,
+    {
+        messages
+            .iter()
+            .map(|msg| encoded_len_cached(tag, msg, cache))
+            .sum()
+    }
+
+    /// Appends the path of a required field which is not set to `missing`.
+    pub fn missing_required_field(prefix: &str, name: &str, missing: &mut Vec<String>) {
+        missing.push(format!("{prefix}{name}"));
+    }
+
+    /// Appends the paths of the missing required fields of the nested message in the field
+    /// `name`, which may include an index or key, to `missing`.
+    pub fn collect_missing_required_fields<M>(
+        msg: &M,
+        prefix: &str,
+        name: impl core::fmt::Display,
+        missing: &mut Vec<String>,
+    ) where
+        M: Message,
+    {
+        msg.collect_missing_required_fields(&format!("{prefix}{name}."), missing);
+    }
+}
+
+/// Functions for message fields which are decoded on first access, see [`Lazy`](crate::Lazy).
+///
+/// Lazy fields are encoded with the functions of the [`message`] module.
+#[cfg(feature = "std")]
+pub mod lazy {
+    use super::*;
+    use crate::Lazy;
+
+    pub fn merge<M>(
+        wire_type: WireType,
+        value: &mut Lazy<M>,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: Message + Default,
+    {
+        check_wire_type(WireType::LengthDelimited, wire_type)?;
+        let len = decode_varint(buf)?;
+        ctx.check_len(len)?;
+        if len > buf.remaining() as u64 {
+            return Err(DecodeErrorKind::BufferUnderflow.into());
+        }
+        value.merge_encoded(buf.copy_to_bytes(len as usize))
+    }
+
+    pub fn merge_repeated<M>(
+        wire_type: WireType,
+        values: &mut impl RepeatedAdapter<Item = Lazy<M>>,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: Message + Default,
+    {
+        ctx.check_repeated_len(values.len())?;
+        let mut value = Lazy::default();
+        merge(wire_type, &mut value, buf, ctx)?;
+        values.push(value)
+    }
 }
 
 pub mod group {
@@ -879,6 +1772,16 @@ pub mod group {
         encode_key(tag, WireType::EndGroup, buf);
     }
 
+    /// Encodes a group with the deterministic encoding of [`Message::encode_deterministic`].
+    pub fn encode_deterministic<M>(tag: u32, msg: &M, buf: &mut impl BufMut)
+    where
+        M: Message,
+    {
+        encode_key(tag, WireType::StartGroup, buf);
+        msg.encode_raw_deterministic(buf);
+        encode_key(tag, WireType::EndGroup, buf);
+    }
+
     pub fn merge<M>(
         tag: u32,
         wire_type: WireType,
@@ -917,7 +1820,7 @@ pub mod group {
     pub fn merge_repeated<M>(
         tag: u32,
         wire_type: WireType,
-        messages: &mut Vec<M>,
+        messages: &mut impl RepeatedAdapter<Item = M>,
         buf: &mut impl Buf,
         ctx: DecodeContext,
     ) -> Result<(), DecodeError>
@@ -925,10 +1828,13 @@ pub mod group {
         M: Message + Default,
     {
         check_wire_type(WireType::StartGroup, wire_type)?;
+        ctx.check_repeated_len(messages.len())?;
         let mut msg = M::default();
-        merge(tag, WireType::StartGroup, &mut msg, buf, ctx)?;
-        messages.push(msg);
-        Ok(())
+        merge(tag, WireType::StartGroup, &mut msg, buf, ctx).map_err(|mut error| {
+            error.push_index(messages.len());
+            error
+        })?;
+        messages.push(msg)
     }
 
     #[inline]
@@ -1077,6 +1983,7 @@ macro_rules! map {
             KM: Fn(WireType, &mut K, &mut B, DecodeContext) -> Result<(), DecodeError>,
             VM: Fn(WireType, &mut V, &mut B, DecodeContext) -> Result<(), DecodeError>,
         {
+            ctx.check_repeated_len(values.len())?;
             let mut key = Default::default();
             let mut val = val_default;
             ctx.limit_reached()?;
@@ -1145,6 +2052,298 @@ pub mod btree_map {
     map!(BTreeMap);
 }
 
+/// Functions for map fields with a custom map type, see `Config::map_type` of `prost-build`.
+///
+/// Any map type can be used which is iterated by reference as `(&K, &V)` pairs, and which entries
+/// are inserted into with `Extend<(K, V)>`, such as `indexmap::IndexMap`.
+pub mod custom_map {
+    use crate::encoding::*;
+
+    /// Generic protobuf map encode function.
+    pub fn encode<M, K, V, B, KE, KL, VE, VL>(
+        key_encode: KE,
+        key_encoded_len: KL,
+        val_encode: VE,
+        val_encoded_len: VL,
+        tag: u32,
+        values: &M,
+        buf: &mut B,
+    ) where
+        for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Default + PartialEq,
+        V: Default + PartialEq,
+        B: BufMut,
+        KE: Fn(u32, &K, &mut B),
+        KL: Fn(u32, &K) -> usize,
+        VE: Fn(u32, &V, &mut B),
+        VL: Fn(u32, &V) -> usize,
+    {
+        encode_with_default(
+            key_encode,
+            key_encoded_len,
+            val_encode,
+            val_encoded_len,
+            &V::default(),
+            tag,
+            values,
+            buf,
+        )
+    }
+
+    /// Generic protobuf map merge function.
+    pub fn merge<M, K, V, B, KM, VM>(
+        key_merge: KM,
+        val_merge: VM,
+        values: &mut M,
+        buf: &mut B,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: Extend<(K, V)>,
+        K: Default,
+        V: Default,
+        B: Buf,
+        KM: Fn(WireType, &mut K, &mut B, DecodeContext) -> Result<(), DecodeError>,
+        VM: Fn(WireType, &mut V, &mut B, DecodeContext) -> Result<(), DecodeError>,
+    {
+        merge_with_default(key_merge, val_merge, V::default(), values, buf, ctx)
+    }
+
+    /// Generic protobuf map encoded length function.
+    pub fn encoded_len<M, K, V, KL, VL>(
+        key_encoded_len: KL,
+        val_encoded_len: VL,
+        tag: u32,
+        values: &M,
+    ) -> usize
+    where
+        for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Default + PartialEq,
+        V: Default + PartialEq,
+        KL: Fn(u32, &K) -> usize,
+        VL: Fn(u32, &V) -> usize,
+    {
+        encoded_len_with_default(key_encoded_len, val_encoded_len, &V::default(), tag, values)
+    }
+
+    /// Generic protobuf map encode function with an overridden value default.
+    ///
+    /// This is necessary because enumeration values can have a default value other
+    /// than 0 in proto2.
+    pub fn encode_with_default<M, K, V, B, KE, KL, VE, VL>(
+        key_encode: KE,
+        key_encoded_len: KL,
+        val_encode: VE,
+        val_encoded_len: VL,
+        val_default: &V,
+        tag: u32,
+        values: &M,
+        buf: &mut B,
+    ) where
+        for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Default + PartialEq,
+        V: PartialEq,
+        B: BufMut,
+        KE: Fn(u32, &K, &mut B),
+        KL: Fn(u32, &K) -> usize,
+        VE: Fn(u32, &V, &mut B),
+        VL: Fn(u32, &V) -> usize,
+    {
+        encode_entries(
+            key_encode,
+            key_encoded_len,
+            val_encode,
+            val_encoded_len,
+            val_default,
+            tag,
+            values.into_iter(),
+            buf,
+        )
+    }
+
+    /// Generic protobuf map encode function, which encodes the entries in the order of their
+    /// keys.
+    ///
+    /// This is used by the deterministic encoding of maps which are not ordered by their keys.
+    pub fn encode_sorted<M, K, V, B, KE, KL, VE, VL>(
+        key_encode: KE,
+        key_encoded_len: KL,
+        val_encode: VE,
+        val_encoded_len: VL,
+        tag: u32,
+        values: &M,
+        buf: &mut B,
+    ) where
+        for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Default + Ord,
+        V: Default + PartialEq,
+        B: BufMut,
+        KE: Fn(u32, &K, &mut B),
+        KL: Fn(u32, &K) -> usize,
+        VE: Fn(u32, &V, &mut B),
+        VL: Fn(u32, &V) -> usize,
+    {
+        encode_sorted_with_default(
+            key_encode,
+            key_encoded_len,
+            val_encode,
+            val_encoded_len,
+            &V::default(),
+            tag,
+            values,
+            buf,
+        )
+    }
+
+    /// Generic protobuf map encode function with an overridden value default, which encodes the
+    /// entries in the order of their keys.
+    pub fn encode_sorted_with_default<M, K, V, B, KE, KL, VE, VL>(
+        key_encode: KE,
+        key_encoded_len: KL,
+        val_encode: VE,
+        val_encoded_len: VL,
+        val_default: &V,
+        tag: u32,
+        values: &M,
+        buf: &mut B,
+    ) where
+        for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Default + Ord,
+        V: PartialEq,
+        B: BufMut,
+        KE: Fn(u32, &K, &mut B),
+        KL: Fn(u32, &K) -> usize,
+        VE: Fn(u32, &V, &mut B),
+        VL: Fn(u32, &V) -> usize,
+    {
+        let mut entries = values.into_iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(key, _)| *key);
+        encode_entries(
+            key_encode,
+            key_encoded_len,
+            val_encode,
+            val_encoded_len,
+            val_default,
+            tag,
+            entries.into_iter(),
+            buf,
+        )
+    }
+
+    fn encode_entries<'e, K, V, B, KE, KL, VE, VL>(
+        key_encode: KE,
+        key_encoded_len: KL,
+        val_encode: VE,
+        val_encoded_len: VL,
+        val_default: &V,
+        tag: u32,
+        entries: impl Iterator<Item = (&'e K, &'e V)>,
+        buf: &mut B,
+    ) where
+        K: Default + PartialEq + 'e,
+        V: PartialEq + 'e,
+        B: BufMut,
+        KE: Fn(u32, &K, &mut B),
+        KL: Fn(u32, &K) -> usize,
+        VE: Fn(u32, &V, &mut B),
+        VL: Fn(u32, &V) -> usize,
+    {
+        let key_default = K::default();
+        for (key, val) in entries {
+            let skip_key = key == &key_default;
+            let skip_val = val == val_default;
+
+            let len = (if skip_key { 0 } else { key_encoded_len(1, key) })
+                + (if skip_val { 0 } else { val_encoded_len(2, val) });
+
+            encode_key(tag, WireType::LengthDelimited, buf);
+            encode_varint(len as u64, buf);
+            if !skip_key {
+                key_encode(1, key, buf);
+            }
+            if !skip_val {
+                val_encode(2, val, buf);
+            }
+        }
+    }
+
+    /// Generic protobuf map merge function with an overridden value default.
+    ///
+    /// This is necessary because enumeration values can have a default value other
+    /// than 0 in proto2.
+    pub fn merge_with_default<M, K, V, B, KM, VM>(
+        key_merge: KM,
+        val_merge: VM,
+        val_default: V,
+        values: &mut M,
+        buf: &mut B,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: Extend<(K, V)>,
+        K: Default,
+        B: Buf,
+        KM: Fn(WireType, &mut K, &mut B, DecodeContext) -> Result<(), DecodeError>,
+        VM: Fn(WireType, &mut V, &mut B, DecodeContext) -> Result<(), DecodeError>,
+    {
+        let mut key = Default::default();
+        let mut val = val_default;
+        ctx.limit_reached()?;
+        merge_loop(
+            &mut (&mut key, &mut val),
+            buf,
+            ctx.enter_recursion(),
+            |&mut (ref mut key, ref mut val), buf, ctx| {
+                let (tag, wire_type) = decode_key(buf)?;
+                match tag {
+                    1 => key_merge(wire_type, key, buf, ctx),
+                    2 => val_merge(wire_type, val, buf, ctx),
+                    _ => skip_field(wire_type, tag, buf, ctx),
+                }
+            },
+        )?;
+        values.extend(core::iter::once((key, val)));
+
+        Ok(())
+    }
+
+    /// Generic protobuf map encoded length function with an overridden value default.
+    ///
+    /// This is necessary because enumeration values can have a default value other
+    /// than 0 in proto2.
+    pub fn encoded_len_with_default<M, K, V, KL, VL>(
+        key_encoded_len: KL,
+        val_encoded_len: VL,
+        val_default: &V,
+        tag: u32,
+        values: &M,
+    ) -> usize
+    where
+        for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Default + PartialEq,
+        V: PartialEq,
+        KL: Fn(u32, &K) -> usize,
+        VL: Fn(u32, &V) -> usize,
+    {
+        let key_default = K::default();
+        values
+            .into_iter()
+            .map(|(key, val)| {
+                let len = (if key == &key_default {
+                    0
+                } else {
+                    key_encoded_len(1, key)
+                }) + (if val == val_default {
+                    0
+                } else {
+                    val_encoded_len(2, val)
+                });
+                key_len(tag) + encoded_len_varint(len as u64) + len
+            })
+            .sum::<usize>()
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[cfg(not(feature = "std"))]
@@ -1429,6 +2628,39 @@ mod test {
         (Vec<u8>, bytes)
     ]);
 
+    #[cfg(feature = "std")]
+    proptest! {
+        #[test]
+        fn custom_map_matches_btree_map(
+            values: std::collections::BTreeMap<String, i32>,
+            tag in MIN_TAG..=MAX_TAG,
+        ) {
+            let mut expected = Vec::new();
+            crate::encoding::btree_map::encode(string::encode, string::encoded_len,
+                                               int32::encode, int32::encoded_len, tag,
+                                               &values, &mut expected);
+
+            let mut buf = Vec::new();
+            custom_map::encode(string::encode, string::encoded_len, int32::encode,
+                               int32::encoded_len, tag, &values, &mut buf);
+            prop_assert_eq!(&buf, &expected);
+            prop_assert_eq!(
+                custom_map::encoded_len(string::encoded_len, int32::encoded_len, tag, &values),
+                expected.len()
+            );
+
+            let mut decoded = std::collections::BTreeMap::new();
+            let mut buf = buf.as_slice();
+            while buf.has_remaining() {
+                let (_, wire_type) = decode_key(&mut buf)?;
+                check_wire_type(WireType::LengthDelimited, wire_type)?;
+                custom_map::merge(string::merge, int32::merge, &mut decoded, &mut buf,
+                                  DecodeContext::default())?;
+            }
+            prop_assert_eq!(decoded, values);
+        }
+    }
+
     #[test]
     /// `decode_varint` accepts a `Buf`, which can be multiple concatenated buffers.
     /// This test ensures that future optimizations don't break the
@@ -0,0 +1,80 @@
+//! Generation functions for the `Arbitrary` implementations derived with `#[prost(arbitrary)]`.
+//!
+//! Nested messages are generated up to the recursion limit of decoding, so that every arbitrary
+//! message can be decoded again. The nesting depth is tracked per thread, since it can not be
+//! passed through `Arbitrary::arbitrary`.
+
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use ::arbitrary::{Result, Unstructured};
+
+use crate::RECURSION_LIMIT;
+
+std::thread_local! {
+    /// The number of nested messages being generated on this thread.
+    static DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Restores the nesting depth when the generation of a nested message returns, or panics.
+struct DepthGuard(u32);
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(self.0));
+    }
+}
+
+/// Generates the value of a field holding nested messages, or returns `None` if its messages
+/// would exceed the recursion limit.
+pub fn nested<'a, T>(
+    u: &mut Unstructured<'a>,
+    generate: impl FnOnce(&mut Unstructured<'a>) -> Result<T>,
+) -> Result<Option<T>> {
+    let current = DEPTH.with(Cell::get);
+    if current + 1 >= RECURSION_LIMIT {
+        return Ok(None);
+    }
+    let _guard = DepthGuard(current);
+    DEPTH.with(|depth| depth.set(current + 1));
+    generate(u).map(Some)
+}
+
+/// Generates the values of a repeated field, until the input decides to stop or runs out.
+pub fn repeated<'a, T>(
+    u: &mut Unstructured<'a>,
+    mut generate: impl FnMut(&mut Unstructured<'a>) -> Result<T>,
+) -> Result<Vec<T>> {
+    let mut values = Vec::new();
+    while u.arbitrary::<bool>()? {
+        values.push(generate(u)?);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nested_stops_at_recursion_limit() {
+        fn depth(u: &mut Unstructured<'_>) -> Result<u32> {
+            Ok(match nested(u, depth)? {
+                Some(depth) => depth + 1,
+                None => 0,
+            })
+        }
+
+        let mut u = Unstructured::new(&[]);
+        assert_eq!(depth(&mut u).unwrap(), RECURSION_LIMIT - 1);
+        assert_eq!(DEPTH.with(Cell::get), 0);
+    }
+
+    #[test]
+    fn repeated_stops_without_input() {
+        let mut u = Unstructured::new(&[1, 7, 1, 9, 0, 1, 3]);
+        assert_eq!(repeated(&mut u, |u| u.arbitrary::<u8>()).unwrap(), [7, 9]);
+        let mut u = Unstructured::new(&[1, 7, 1]);
+        assert_eq!(repeated(&mut u, |u| u.arbitrary::<u8>()).unwrap(), [7, 0]);
+    }
+}
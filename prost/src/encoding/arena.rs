@@ -0,0 +1,417 @@
+//! Decoding functions for the fields of [`ArenaMessage`]s.
+//!
+//! `string` and `bytes` values are copied into the arena, and repeated values are pushed to
+//! [`Vec`]s allocated in the arena. Scalar fields of arena messages are decoded with the regular
+//! functions of the [`encoding`](super) module.
+
+use ::bytes::Buf;
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+
+use super::{check_wire_type, decode_key, decode_varint, merge_loop, skip_field};
+use super::{DecodeContext, WireType};
+use crate::error::DecodeErrorKind;
+use crate::{ArenaMessage, DecodeError};
+
+/// The types of the fields of arena messages, which are created empty in an arena.
+pub trait NewIn<'bump> {
+    fn new_in(arena: &'bump Bump) -> Self;
+}
+
+macro_rules! new_in_default {
+    ($($ty:ty),*) => {
+        $(
+            impl<'bump> NewIn<'bump> for $ty {
+                fn new_in(_arena: &'bump Bump) -> Self {
+                    Default::default()
+                }
+            }
+        )*
+    };
+}
+
+new_in_default!(bool, i32, i64, u32, u64, f32, f64, &'bump str, &'bump [u8]);
+
+impl<'bump, M> NewIn<'bump> for M
+where
+    M: ArenaMessage<'bump>,
+{
+    fn new_in(arena: &'bump Bump) -> Self {
+        M::new_in(arena)
+    }
+}
+
+/// Copies a length-delimited value from the buffer into the arena.
+fn copy_length_delimited<'bump>(
+    wire_type: WireType,
+    buf: &mut impl Buf,
+    arena: &'bump Bump,
+    ctx: &DecodeContext,
+) -> Result<&'bump [u8], DecodeError> {
+    check_wire_type(WireType::LengthDelimited, wire_type)?;
+    let len = decode_varint(buf)?;
+    ctx.check_len(len)?;
+    if len > buf.remaining() as u64 {
+        return Err(DecodeErrorKind::BufferUnderflow.into());
+    }
+    let value = arena.alloc_slice_fill_copy(len as usize, 0u8);
+    buf.copy_to_slice(value);
+    Ok(value)
+}
+
+/// Merges a value of a repeated scalar field, which is either packed or a single value.
+///
+/// `value_wire_type` is the wire type of an unpacked value, and `merge` is the `merge` function
+/// of the scalar type.
+pub fn merge_repeated_scalar<'bump, T, B, M>(
+    value_wire_type: WireType,
+    merge: M,
+    wire_type: WireType,
+    values: &mut Vec<'bump, T>,
+    buf: &mut B,
+    ctx: DecodeContext,
+) -> Result<(), DecodeError>
+where
+    T: Default,
+    B: Buf,
+    M: Fn(WireType, &mut T, &mut B, DecodeContext) -> Result<(), DecodeError>,
+{
+    if wire_type == WireType::LengthDelimited {
+        // Packed.
+        merge_loop(values, buf, ctx, |values, buf, ctx| {
+            let mut value = T::default();
+            merge(value_wire_type, &mut value, buf, ctx)?;
+            values.push(value);
+            Ok(())
+        })
+    } else {
+        // Unpacked.
+        check_wire_type(value_wire_type, wire_type)?;
+        let mut value = T::default();
+        merge(wire_type, &mut value, buf, ctx)?;
+        values.push(value);
+        Ok(())
+    }
+}
+
+pub mod str {
+    use super::*;
+
+    pub fn merge<'bump>(
+        wire_type: WireType,
+        value: &mut &'bump str,
+        buf: &mut impl Buf,
+        arena: &'bump Bump,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        let bytes = copy_length_delimited(wire_type, buf, arena, &ctx)?;
+        *value = match core::str::from_utf8(bytes) {
+            Ok(value) => value,
+            Err(_) => arena.alloc_str(&ctx.invalid_utf8(bytes)?),
+        };
+        Ok(())
+    }
+
+    pub fn merge_repeated<'bump>(
+        wire_type: WireType,
+        values: &mut Vec<'bump, &'bump str>,
+        buf: &mut impl Buf,
+        arena: &'bump Bump,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        let mut value = "";
+        merge(wire_type, &mut value, buf, arena, ctx)?;
+        values.push(value);
+        Ok(())
+    }
+}
+
+pub mod bytes {
+    use super::*;
+
+    pub fn merge<'bump>(
+        wire_type: WireType,
+        value: &mut &'bump [u8],
+        buf: &mut impl Buf,
+        arena: &'bump Bump,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        *value = copy_length_delimited(wire_type, buf, arena, &ctx)?;
+        Ok(())
+    }
+
+    pub fn merge_repeated<'bump>(
+        wire_type: WireType,
+        values: &mut Vec<'bump, &'bump [u8]>,
+        buf: &mut impl Buf,
+        arena: &'bump Bump,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        values.push(copy_length_delimited(wire_type, buf, arena, &ctx)?);
+        Ok(())
+    }
+}
+
+pub mod message {
+    use super::*;
+
+    pub fn merge<'bump, M, B>(
+        wire_type: WireType,
+        msg: &mut M,
+        buf: &mut B,
+        arena: &'bump Bump,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: ArenaMessage<'bump>,
+        B: Buf,
+    {
+        check_wire_type(WireType::LengthDelimited, wire_type)?;
+        ctx.limit_reached()?;
+        merge_loop(
+            msg,
+            buf,
+            ctx.enter_recursion(),
+            |msg: &mut M, buf: &mut B, ctx| {
+                let (tag, wire_type) = decode_key(buf)?;
+                msg.merge_field(tag, wire_type, buf, arena, ctx)
+            },
+        )
+    }
+
+    pub fn merge_repeated<'bump, M>(
+        wire_type: WireType,
+        messages: &mut Vec<'bump, M>,
+        buf: &mut impl Buf,
+        arena: &'bump Bump,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: ArenaMessage<'bump>,
+    {
+        let mut msg = M::new_in(arena);
+        merge(wire_type, &mut msg, buf, arena, ctx)?;
+        messages.push(msg);
+        Ok(())
+    }
+}
+
+pub mod group {
+    use super::*;
+
+    pub fn merge<'bump, M>(
+        tag: u32,
+        wire_type: WireType,
+        msg: &mut M,
+        buf: &mut impl Buf,
+        arena: &'bump Bump,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: ArenaMessage<'bump>,
+    {
+        check_wire_type(WireType::StartGroup, wire_type)?;
+
+        ctx.limit_reached()?;
+        loop {
+            let (field_tag, field_wire_type) = decode_key(buf)?;
+            if field_wire_type == WireType::EndGroup {
+                if field_tag != tag {
+                    return Err(DecodeErrorKind::UnexpectedEndGroupTag.into());
+                }
+                return Ok(());
+            }
+
+            msg.merge_field(
+                field_tag,
+                field_wire_type,
+                buf,
+                arena,
+                ctx.enter_recursion(),
+            )?;
+        }
+    }
+
+    pub fn merge_repeated<'bump, M>(
+        tag: u32,
+        wire_type: WireType,
+        messages: &mut Vec<'bump, M>,
+        buf: &mut impl Buf,
+        arena: &'bump Bump,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: ArenaMessage<'bump>,
+    {
+        let mut msg = M::new_in(arena);
+        merge(tag, wire_type, &mut msg, buf, arena, ctx)?;
+        messages.push(msg);
+        Ok(())
+    }
+}
+
+pub mod map {
+    use super::*;
+
+    /// Merges a map entry into the entries of a map field.
+    ///
+    /// Arena messages keep map entries in the order they were decoded, including entries with
+    /// duplicate keys.
+    pub fn merge<'bump, K, V, B, KM, VM>(
+        key_merge: KM,
+        val_merge: VM,
+        values: &mut Vec<'bump, (K, V)>,
+        buf: &mut B,
+        arena: &'bump Bump,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        K: NewIn<'bump>,
+        V: NewIn<'bump>,
+        B: Buf,
+        KM: Fn(WireType, &mut K, &mut B, DecodeContext) -> Result<(), DecodeError>,
+        VM: Fn(WireType, &mut V, &mut B, DecodeContext) -> Result<(), DecodeError>,
+    {
+        let mut key = K::new_in(arena);
+        let mut val = V::new_in(arena);
+        ctx.limit_reached()?;
+        merge_loop(
+            &mut (&mut key, &mut val),
+            buf,
+            ctx.enter_recursion(),
+            |&mut (ref mut key, ref mut val), buf, ctx| {
+                let (tag, wire_type) = decode_key(buf)?;
+                match tag {
+                    1 => key_merge(wire_type, key, buf, ctx),
+                    2 => val_merge(wire_type, val, buf, ctx),
+                    _ => skip_field(wire_type, tag, buf, ctx),
+                }
+            },
+        )?;
+        values.push((key, val));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encoding::int32;
+
+    #[test]
+    fn copies_into_arena() {
+        let arena = Bump::new();
+        let input = [0x03, b'a', b'b', b'c', 0x01, 0xff];
+        let mut buf = &input[..];
+
+        let mut value = "";
+        str::merge(
+            WireType::LengthDelimited,
+            &mut value,
+            &mut buf,
+            &arena,
+            DecodeContext::default(),
+        )
+        .unwrap();
+        assert_eq!(value, "abc");
+        assert_ne!(value.as_ptr(), input[1..].as_ptr());
+
+        let mut invalid = "";
+        let mut rest = buf;
+        assert!(str::merge(
+            WireType::LengthDelimited,
+            &mut invalid,
+            &mut rest,
+            &arena,
+            DecodeContext::default()
+        )
+        .is_err());
+
+        let mut values = Vec::new_in(&arena);
+        bytes::merge_repeated(
+            WireType::LengthDelimited,
+            &mut values,
+            &mut buf,
+            &arena,
+            DecodeContext::default(),
+        )
+        .unwrap();
+        assert_eq!(values, [&[0xff][..]]);
+        assert!(buf.is_empty());
+
+        let mut buf = &[0x05, 0xff][..];
+        let mut truncated: &[u8] = &[];
+        assert!(bytes::merge(
+            WireType::LengthDelimited,
+            &mut truncated,
+            &mut buf,
+            &arena,
+            DecodeContext::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn repeated_scalars() {
+        let arena = Bump::new();
+        // A packed run of 1, 2 and 150, followed by an unpacked 3.
+        let input = [0x04, 0x01, 0x02, 0x96, 0x01, 0x03];
+        let mut buf = &input[..];
+        let mut values = Vec::new_in(&arena);
+        merge_repeated_scalar(
+            WireType::Varint,
+            int32::merge,
+            WireType::LengthDelimited,
+            &mut values,
+            &mut buf,
+            DecodeContext::default(),
+        )
+        .unwrap();
+        merge_repeated_scalar(
+            WireType::Varint,
+            int32::merge,
+            WireType::Varint,
+            &mut values,
+            &mut buf,
+            DecodeContext::default(),
+        )
+        .unwrap();
+        assert_eq!(values, [1, 2, 150, 3]);
+
+        assert!(merge_repeated_scalar(
+            WireType::Varint,
+            int32::merge,
+            WireType::SixtyFourBit,
+            &mut values,
+            &mut &[0; 8][..],
+            DecodeContext::default(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn map_entries() {
+        let arena = Bump::new();
+        // Two entries for the key "k", and a default key with the value 3.
+        let input = [
+            0x05, 0x0a, 0x01, b'k', 0x10, 0x01, 0x05, 0x0a, 0x01, b'k', 0x10, 0x02, 0x02, 0x10,
+            0x03,
+        ];
+        let mut buf = &input[..];
+        let mut values: Vec<(&str, i32)> = Vec::new_in(&arena);
+        while !buf.is_empty() {
+            map::merge(
+                |wire_type, key: &mut &str, buf: &mut &[u8], ctx| {
+                    str::merge(wire_type, key, buf, &arena, ctx)
+                },
+                int32::merge,
+                &mut values,
+                &mut buf,
+                &arena,
+                DecodeContext::default(),
+            )
+            .unwrap();
+        }
+        assert_eq!(values, [("k", 1), ("k", 2), ("", 3)]);
+    }
+}
@@ -0,0 +1,284 @@
+//! Decoding functions for the fields of [`MessageRef`] views.
+//!
+//! `string` and `bytes` values are borrowed from the input slice instead of being copied. Scalar
+//! fields of views are decoded with the regular functions of the [`encoding`](super) module.
+
+use alloc::vec::Vec;
+
+use super::{check_wire_type, decode_key, decode_varint, merge_loop, skip_field};
+use super::{DecodeContext, WireType};
+use crate::error::DecodeErrorKind;
+use crate::{DecodeError, MessageRef};
+
+/// Splits a length-delimited value off the front of the buffer.
+fn split_length_delimited<'a>(
+    wire_type: WireType,
+    buf: &mut &'a [u8],
+    ctx: &DecodeContext,
+) -> Result<&'a [u8], DecodeError> {
+    check_wire_type(WireType::LengthDelimited, wire_type)?;
+    let len = decode_varint(buf)?;
+    ctx.check_len(len)?;
+    if len > buf.len() as u64 {
+        return Err(DecodeErrorKind::BufferUnderflow.into());
+    }
+    let (value, rest) = buf.split_at(len as usize);
+    *buf = rest;
+    Ok(value)
+}
+
+pub mod str {
+    use super::*;
+
+    pub fn merge<'a>(
+        wire_type: WireType,
+        value: &mut &'a str,
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        let bytes = split_length_delimited(wire_type, buf, &ctx)?;
+        *value = core::str::from_utf8(bytes).map_err(|_| DecodeErrorKind::InvalidString)?;
+        Ok(())
+    }
+
+    pub fn merge_repeated<'a>(
+        wire_type: WireType,
+        values: &mut Vec<&'a str>,
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        let mut value = "";
+        merge(wire_type, &mut value, buf, ctx)?;
+        values.push(value);
+        Ok(())
+    }
+}
+
+pub mod bytes {
+    use super::*;
+
+    pub fn merge<'a>(
+        wire_type: WireType,
+        value: &mut &'a [u8],
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        *value = split_length_delimited(wire_type, buf, &ctx)?;
+        Ok(())
+    }
+
+    pub fn merge_repeated<'a>(
+        wire_type: WireType,
+        values: &mut Vec<&'a [u8]>,
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        values.push(split_length_delimited(wire_type, buf, &ctx)?);
+        Ok(())
+    }
+}
+
+pub mod message {
+    use super::*;
+
+    pub fn merge<'a, M>(
+        wire_type: WireType,
+        msg: &mut M,
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: MessageRef<'a>,
+    {
+        check_wire_type(WireType::LengthDelimited, wire_type)?;
+        ctx.limit_reached()?;
+        merge_loop(
+            msg,
+            buf,
+            ctx.enter_recursion(),
+            |msg: &mut M, buf: &mut &'a [u8], ctx| {
+                let (tag, wire_type) = decode_key(buf)?;
+                msg.merge_field(tag, wire_type, buf, ctx)
+            },
+        )
+    }
+
+    pub fn merge_repeated<'a, M>(
+        wire_type: WireType,
+        messages: &mut Vec<M>,
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: MessageRef<'a>,
+    {
+        let mut msg = M::default();
+        merge(wire_type, &mut msg, buf, ctx)?;
+        messages.push(msg);
+        Ok(())
+    }
+}
+
+pub mod group {
+    use super::*;
+
+    pub fn merge<'a, M>(
+        tag: u32,
+        wire_type: WireType,
+        msg: &mut M,
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: MessageRef<'a>,
+    {
+        check_wire_type(WireType::StartGroup, wire_type)?;
+
+        ctx.limit_reached()?;
+        loop {
+            let (field_tag, field_wire_type) = decode_key(buf)?;
+            if field_wire_type == WireType::EndGroup {
+                if field_tag != tag {
+                    return Err(DecodeErrorKind::UnexpectedEndGroupTag.into());
+                }
+                return Ok(());
+            }
+
+            msg.merge_field(field_tag, field_wire_type, buf, ctx.enter_recursion())?;
+        }
+    }
+
+    pub fn merge_repeated<'a, M>(
+        tag: u32,
+        wire_type: WireType,
+        messages: &mut Vec<M>,
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        M: MessageRef<'a>,
+    {
+        let mut msg = M::default();
+        merge(tag, wire_type, &mut msg, buf, ctx)?;
+        messages.push(msg);
+        Ok(())
+    }
+}
+
+pub mod map {
+    use super::*;
+
+    /// Merges a map entry into the entries of a map field.
+    ///
+    /// Views keep map entries in the order they were decoded, including entries with duplicate
+    /// keys. Entries are deduplicated when the view is converted to an owned message, where the
+    /// last entry for a key wins.
+    pub fn merge<'a, K, V, KM, VM>(
+        key_merge: KM,
+        val_merge: VM,
+        values: &mut Vec<(K, V)>,
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>
+    where
+        K: Default,
+        V: Default,
+        KM: Fn(WireType, &mut K, &mut &'a [u8], DecodeContext) -> Result<(), DecodeError>,
+        VM: Fn(WireType, &mut V, &mut &'a [u8], DecodeContext) -> Result<(), DecodeError>,
+    {
+        let mut key = K::default();
+        let mut val = V::default();
+        ctx.limit_reached()?;
+        merge_loop(
+            &mut (&mut key, &mut val),
+            buf,
+            ctx.enter_recursion(),
+            |&mut (ref mut key, ref mut val), buf, ctx| {
+                let (tag, wire_type) = decode_key(buf)?;
+                match tag {
+                    1 => key_merge(wire_type, key, buf, ctx),
+                    2 => val_merge(wire_type, val, buf, ctx),
+                    _ => skip_field(wire_type, tag, buf, ctx),
+                }
+            },
+        )?;
+        values.push((key, val));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encoding::int32;
+
+    #[test]
+    fn borrows_from_input() {
+        let input = [0x03, b'a', b'b', b'c', 0x01, 0xff];
+        let mut buf = &input[..];
+
+        let mut value = "";
+        str::merge(
+            WireType::LengthDelimited,
+            &mut value,
+            &mut buf,
+            DecodeContext::default(),
+        )
+        .unwrap();
+        assert_eq!(value, "abc");
+        assert_eq!(value.as_ptr(), input[1..].as_ptr());
+
+        let mut invalid = "";
+        let mut rest = buf;
+        assert!(str::merge(
+            WireType::LengthDelimited,
+            &mut invalid,
+            &mut rest,
+            DecodeContext::default()
+        )
+        .is_err());
+
+        let mut values = Vec::new();
+        bytes::merge_repeated(
+            WireType::LengthDelimited,
+            &mut values,
+            &mut buf,
+            DecodeContext::default(),
+        )
+        .unwrap();
+        assert_eq!(values, [&[0xff][..]]);
+        assert!(buf.is_empty());
+
+        let mut buf = &[0x05, 0xff][..];
+        let mut truncated: &[u8] = &[];
+        assert!(bytes::merge(
+            WireType::LengthDelimited,
+            &mut truncated,
+            &mut buf,
+            DecodeContext::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn map_entries() {
+        // Two entries for the key "k", and a default key with the value 3.
+        let input = [
+            0x05, 0x0a, 0x01, b'k', 0x10, 0x01, 0x05, 0x0a, 0x01, b'k', 0x10, 0x02, 0x02, 0x10,
+            0x03,
+        ];
+        let mut buf = &input[..];
+        let mut values: Vec<(&str, i32)> = Vec::new();
+        while !buf.is_empty() {
+            map::merge(
+                str::merge,
+                int32::merge,
+                &mut values,
+                &mut buf,
+                DecodeContext::default(),
+            )
+            .unwrap();
+        }
+        assert_eq!(values, [("k", 1), ("k", 2), ("", 3)]);
+    }
+}
@@ -0,0 +1,165 @@
+//! Encoding of the fixed-capacity `heapless` types used by heapless messages.
+//!
+//! A value which does not fit into the capacity of its field fails to decode with a
+//! `DecodeError`, rather than being truncated.
+
+use ::bytes::{Buf, BufMut};
+use heapless::{String, Vec};
+
+use super::{bytes, sealed, BytesAdapter, RepeatedAdapter, StringAdapter};
+use crate::encoding::{DecodeContext, WireType};
+use crate::error::DecodeErrorKind;
+use crate::DecodeError;
+
+impl<const N: usize> StringAdapter for String<N> {
+    fn as_str(&self) -> &str {
+        self
+    }
+
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn replace_with(&mut self, value: &str) {
+        // The length has been checked against the capacity by the caller.
+        self.clear();
+        self.push_str(value).expect("string exceeds the capacity");
+    }
+
+    fn merge(
+        &mut self,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        // The string is cleared on error, as for `alloc::string::String`.
+        self.clear();
+        let mut value = Vec::<u8, N>::new();
+        bytes::merge_one_copy(wire_type, &mut value, buf, ctx.clone())?;
+        match core::str::from_utf8(&value) {
+            Ok(value) => self.replace_with(value),
+            Err(_) => {
+                let value = ctx.invalid_utf8(&value)?;
+                if value.len() > N {
+                    return Err(DecodeErrorKind::CapacityExceeded.into());
+                }
+                self.replace_with(&value);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> BytesAdapter for Vec<u8, N> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn replace_with(&mut self, mut buf: impl Buf) {
+        // The length has been checked against the capacity by the caller.
+        self.clear();
+        while buf.has_remaining() {
+            let chunk = buf.chunk();
+            let len = chunk.len().min(N - self.as_slice().len());
+            self.extend_from_slice(&chunk[..len])
+                .expect("bytes exceed the capacity");
+            buf.advance(len);
+        }
+    }
+
+    fn append_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(self)
+    }
+}
+
+impl<T, const N: usize> RepeatedAdapter for Vec<T, N> {}
+
+impl<T, const N: usize> sealed::RepeatedAdapter for Vec<T, N> {
+    type Item = T;
+
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn push(&mut self, value: T) -> Result<(), DecodeError> {
+        Vec::push(self, value).map_err(|_| DecodeErrorKind::CapacityExceeded.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(not(feature = "std"))]
+    use alloc::string::ToString;
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::encoding::test::check_type;
+    use crate::encoding::{int32, message, string, MAX_TAG, MIN_TAG};
+
+    proptest! {
+        #[test]
+        fn check_string(value in "[a-z\u{e9}]{0,8}", tag in MIN_TAG..=MAX_TAG) {
+            let value = String::<16>::try_from(value.as_str()).unwrap();
+            check_type::<String<16>, String<16>>(value, tag, WireType::LengthDelimited,
+                                                 string::encode, string::merge,
+                                                 string::encoded_len)?;
+        }
+
+        #[test]
+        fn check_bytes(value: alloc::vec::Vec<u8>, tag in MIN_TAG..=MAX_TAG) {
+            let value = Vec::<u8, 512>::from_slice(&value[..value.len().min(512)]).unwrap();
+            check_type::<Vec<u8, 512>, Vec<u8, 512>>(value, tag, WireType::LengthDelimited,
+                                                     bytes::encode, bytes::merge,
+                                                     bytes::encoded_len)?;
+        }
+    }
+
+    #[test]
+    fn capacity_exceeded() {
+        let mut buf = alloc::vec::Vec::new();
+        string::encode(1, &alloc::string::String::from("too long"), &mut buf);
+        let mut value = String::<4>::try_from("abc").unwrap();
+        let mut slice = &buf[1..];
+        let error = string::merge(
+            WireType::LengthDelimited,
+            &mut value,
+            &mut slice,
+            DecodeContext::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to decode Protobuf message: capacity of fixed-size field exceeded"
+        );
+        assert!(value.is_empty());
+
+        let mut values = Vec::<i32, 2>::new();
+        let packed = [0x03, 0x01, 0x02, 0x03];
+        let mut slice = &packed[..];
+        assert!(int32::merge_repeated(
+            WireType::LengthDelimited,
+            &mut values,
+            &mut slice,
+            DecodeContext::default(),
+        )
+        .is_err());
+
+        let mut messages = Vec::<(), 1>::new();
+        let merge = |messages: &mut Vec<(), 1>| {
+            message::merge_repeated(
+                WireType::LengthDelimited,
+                messages,
+                &mut &[0x00][..],
+                DecodeContext::default(),
+            )
+        };
+        assert!(merge(&mut messages).is_ok());
+        assert!(merge(&mut messages).is_err());
+        assert_eq!(messages.len(), 1);
+    }
+}
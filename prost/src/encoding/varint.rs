@@ -5,6 +5,12 @@ use ::bytes::{Buf, BufMut};
 
 use crate::{error::DecodeErrorKind, DecodeError};
 
+#[cfg(all(
+    feature = "simd-varint",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod simd;
+
 /// Encodes an integer value into LEB128 variable length format, and writes it to the buffer.
 /// The buffer must have enough remaining space (maximum 10 bytes).
 #[inline]
@@ -46,6 +52,15 @@ pub fn decode_varint(buf: &mut impl Buf) -> Result<u64, DecodeError> {
         buf.advance(1);
         Ok(u64::from(byte))
     } else if len > 10 || bytes[len - 1] < 0x80 {
+        #[cfg(all(
+            feature = "simd-varint",
+            any(target_arch = "x86_64", target_arch = "aarch64")
+        ))]
+        if len >= simd::CHUNK_LEN {
+            let (value, advance) = simd::decode_varint_chunk(bytes)?;
+            buf.advance(advance);
+            return Ok(value);
+        }
         let (value, advance) = decode_varint_slice(bytes)?;
         buf.advance(advance);
         Ok(value)
@@ -205,6 +220,19 @@ mod test {
             let roundtrip_value =
                 decode_varint_slow(&mut encoded_copy).expect("slow decoding failed");
             assert_eq!(value, roundtrip_value);
+
+            #[cfg(all(
+                feature = "simd-varint",
+                any(target_arch = "x86_64", target_arch = "aarch64")
+            ))]
+            {
+                let mut padded = encoded.to_vec();
+                padded.resize(simd::CHUNK_LEN, 0xFF);
+                let (roundtrip_value, advance) =
+                    simd::decode_varint_chunk(&padded).expect("SIMD decoding failed");
+                assert_eq!(value, roundtrip_value);
+                assert_eq!(encoded.len(), advance);
+            }
         }
 
         check(2u64.pow(0) - 1, &[0x00]);
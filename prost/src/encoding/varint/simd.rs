@@ -0,0 +1,129 @@
+//! Varint decoding using SIMD instructions, enabled by the `simd-varint` feature.
+//!
+//! A single 16 byte vector load and comparison finds the last byte of the varint, instead of
+//! testing the continuation bit of each byte in turn. The 7-bit groups of the first 8 bytes are
+//! then packed together with a fixed sequence of shifts and masks. Only SSE2 and NEON
+//! instructions are used, which are always available on `x86_64` and `aarch64`.
+
+use crate::{error::DecodeErrorKind, DecodeError};
+
+/// The number of bytes read by a vector load. Callers must provide at least this many bytes.
+pub(super) const CHUNK_LEN: usize = 16;
+
+/// Decodes a LEB128-encoded variable length integer from the slice, returning the value and the
+/// number of bytes read.
+///
+/// The caller must ensure that `bytes.len() >= CHUNK_LEN`.
+#[inline(always)]
+pub(super) fn decode_varint_chunk(bytes: &[u8]) -> Result<(u64, usize), DecodeError> {
+    assert!(bytes.len() >= CHUNK_LEN);
+
+    // Safety: the slice holds at least `CHUNK_LEN` bytes.
+    let len = unsafe { varint_len(bytes.as_ptr()) };
+    if len > 10 {
+        return Err(DecodeErrorKind::InvalidVarint.into());
+    }
+
+    // Safety: the slice holds at least `CHUNK_LEN` bytes.
+    let low = u64::from_le(unsafe { bytes.as_ptr().cast::<u64>().read_unaligned() });
+    if len < 8 {
+        return Ok((pack_groups(low & ((1 << (len * 8)) - 1)), len));
+    }
+    let mut value = pack_groups(low);
+
+    if len > 8 {
+        value |= u64::from(bytes[8] & 0x7F) << 56;
+    }
+    if len > 9 {
+        // Check for u64::MAX overflow. See [`ConsumeVarint`][1] for details.
+        // [1]: https://github.com/protocolbuffers/protobuf-go/blob/v1.27.1/encoding/protowire/wire.go#L358
+        let byte = bytes[9];
+        if byte >= 0x02 {
+            return Err(DecodeErrorKind::InvalidVarint.into());
+        }
+        value |= u64::from(byte) << 63;
+    }
+    Ok((value, len))
+}
+
+/// Packs the low 7 bits of each byte of `value` into the low 56 bits of the result, in order.
+#[inline]
+fn pack_groups(value: u64) -> u64 {
+    let value = value & 0x7F7F_7F7F_7F7F_7F7F;
+    // Pairs of 7-bit groups into 14-bit groups in each 16-bit lane.
+    let value = (value & 0x007F_007F_007F_007F) | ((value & 0x7F00_7F00_7F00_7F00) >> 1);
+    // Pairs of 14-bit groups into 28-bit groups in each 32-bit lane.
+    let value = (value & 0x0000_3FFF_0000_3FFF) | ((value & 0x3FFF_0000_3FFF_0000) >> 2);
+    // The two 28-bit groups into the final 56-bit value.
+    (value & 0x0000_0000_0FFF_FFFF) | ((value & 0x0FFF_FFFF_0000_0000) >> 4)
+}
+
+/// Returns the length of the varint starting at `ptr`, or a value greater than 10 if none of
+/// the first 16 bytes ends a varint.
+///
+/// ## Safety
+///
+/// `ptr` must be valid for reads of `CHUNK_LEN` bytes.
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+unsafe fn varint_len(ptr: *const u8) -> usize {
+    use core::arch::x86_64::{__m128i, _mm_loadu_si128, _mm_movemask_epi8};
+
+    let chunk = _mm_loadu_si128(ptr.cast::<__m128i>());
+    // Bit `i` of the mask is the continuation bit of byte `i`.
+    let continuation = _mm_movemask_epi8(chunk) as u32;
+    (!continuation).trailing_zeros() as usize + 1
+}
+
+/// Returns the length of the varint starting at `ptr`, or a value greater than 10 if none of
+/// the first 16 bytes ends a varint.
+///
+/// ## Safety
+///
+/// `ptr` must be valid for reads of `CHUNK_LEN` bytes.
+#[cfg(target_arch = "aarch64")]
+#[inline]
+unsafe fn varint_len(ptr: *const u8) -> usize {
+    use core::arch::aarch64::{
+        vcltq_u8, vdupq_n_u8, vget_lane_u64, vld1q_u8, vreinterpret_u64_u8, vreinterpretq_u16_u8,
+        vshrn_n_u16,
+    };
+
+    let chunk = vld1q_u8(ptr);
+    // Bytes without the continuation bit are set to 0xFF.
+    let last = vcltq_u8(chunk, vdupq_n_u8(0x80));
+    // Narrow each byte to a nibble, as NEON has no equivalent of `movemask`.
+    let nibbles = vshrn_n_u16::<4>(vreinterpretq_u16_u8(last));
+    let mask = vget_lane_u64::<0>(vreinterpret_u64_u8(nibbles));
+    (mask.trailing_zeros() / 4) as usize + 1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pack_groups() {
+        assert_eq!(super::pack_groups(0), 0);
+        assert_eq!(super::pack_groups(0xFFFF_FFFF_FFFF_FFFF), (1 << 56) - 1);
+        assert_eq!(super::pack_groups(0x02AC), 300);
+        assert_eq!(
+            super::pack_groups(0x0102_0304_0506_0708),
+            (1 << 49) | (2 << 42) | (3 << 35) | (4 << 28) | (5 << 21) | (6 << 14) | (7 << 7) | 8
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        // Continuation bits in all 16 bytes.
+        assert!(decode_varint_chunk(&[0xFF; CHUNK_LEN]).is_err());
+        // Continuation bits in 10 bytes.
+        let mut bytes = [0xFF; CHUNK_LEN];
+        bytes[10] = 0;
+        assert!(decode_varint_chunk(&bytes).is_err());
+        // u64::MAX + 1.
+        let mut bytes = [0; CHUNK_LEN];
+        bytes[..10].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02]);
+        assert!(decode_varint_chunk(&bytes).is_err());
+    }
+}
@@ -8,7 +8,7 @@ use alloc::boxed::Box;
 use alloc::string::String;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
-use core::fmt;
+use core::{fmt, mem};
 
 /// A Protobuf message decoding error.
 ///
@@ -28,6 +28,16 @@ struct Inner {
     /// message type and field where decoding failed. The stack contains an
     /// entry per level of nesting.
     stack: Vec<(&'static str, &'static str)>,
+    /// The field number, and the element index of repeated message fields, of each entry of
+    /// `stack`.
+    #[cfg(feature = "rich-errors")]
+    fields: Vec<(u32, Option<usize>)>,
+    /// The element index of the repeated message field which is pushed next.
+    #[cfg(feature = "rich-errors")]
+    index: Option<usize>,
+    /// The offset in the input buffer at which decoding stopped.
+    #[cfg(feature = "rich-errors")]
+    offset: Option<usize>,
 }
 
 impl DecodeError {
@@ -63,21 +73,170 @@ impl DecodeError {
         .into()
     }
 
+    /// Creates a new `DecodeError` with a DecodeErrorKind::UnknownTypeUrl.
+    ///
+    /// Must only be used by `prost_types::Any` implementation.
+    #[doc(hidden)]
+    #[cold]
+    pub fn new_unknown_type_url(type_url: impl Into<String>) -> DecodeError {
+        DecodeErrorKind::UnknownTypeUrl {
+            type_url: type_url.into(),
+        }
+        .into()
+    }
+
+    /// Returns `true` if decoding failed because the declared length of a length-delimited field
+    /// exceeds the [`max_len`](crate::DecodeOptions::max_len) limit.
+    ///
+    /// Unlike other decode errors, this usually means that the input was well-formed but too
+    /// large for the configured quota, which servers may want to report differently.
+    pub fn is_length_limit_exceeded(&self) -> bool {
+        matches!(
+            self.inner.description,
+            DecodeErrorKind::LengthLimitExceeded { .. }
+        )
+    }
+
+    /// Returns the value of the string field which failed to decode, if it is not valid UTF-8
+    /// and is to be kept as bytes under [`Utf8Policy::Bytes`](crate::Utf8Policy::Bytes), or
+    /// the error otherwise.
+    ///
+    /// Meant to be used only by `Message` implementations.
+    #[doc(hidden)]
+    pub fn into_invalid_string_bytes(mut self) -> Result<Vec<u8>, DecodeError> {
+        match self.inner.description {
+            DecodeErrorKind::InvalidStringBytes { ref mut value } => Ok(mem::take(value)),
+            _ => Err(self),
+        }
+    }
+
+    /// Creates a new `DecodeError` with a DecodeErrorKind::MissingRequiredFields.
+    #[cold]
+    pub(crate) fn new_missing_required_fields(fields: Vec<String>) -> DecodeError {
+        DecodeErrorKind::MissingRequiredFields { fields }.into()
+    }
+
     /// Pushes a (message, field) name location pair on to the location stack.
     ///
     /// Meant to be used only by `Message` implementations.
     #[doc(hidden)]
     pub fn push(&mut self, message: &'static str, field: &'static str) {
+        self.push_field(message, field, 0);
+    }
+
+    /// Pushes a (message, field) name location pair, and the field number, on to the location
+    /// stack.
+    ///
+    /// Meant to be used only by `Message` implementations.
+    #[doc(hidden)]
+    #[cfg_attr(not(feature = "rich-errors"), allow(unused_variables))]
+    pub fn push_field(&mut self, message: &'static str, field: &'static str, number: u32) {
         self.inner.stack.push((message, field));
+        #[cfg(feature = "rich-errors")]
+        {
+            let index = self.inner.index.take();
+            self.inner.fields.push((number, index));
+        }
+    }
+
+    /// Records the element index of the repeated message field in which decoding failed.
+    ///
+    /// The index is attached to the field which is pushed next.
+    #[cfg_attr(not(feature = "rich-errors"), allow(unused_variables))]
+    pub(crate) fn push_index(&mut self, index: usize) {
+        #[cfg(feature = "rich-errors")]
+        {
+            self.inner.index = Some(index);
+        }
+    }
+
+    /// Records the offset in the input buffer at which decoding stopped, unless an offset is
+    /// already recorded.
+    #[cfg_attr(not(feature = "rich-errors"), allow(unused_variables, unused_mut))]
+    pub(crate) fn at_offset(mut self, offset: usize) -> DecodeError {
+        #[cfg(feature = "rich-errors")]
+        {
+            self.inner.offset.get_or_insert(offset);
+        }
+        self
+    }
+}
+
+#[cfg(feature = "rich-errors")]
+impl DecodeError {
+    /// Returns the offset in the input buffer at which decoding stopped.
+    ///
+    /// This is the number of bytes which were consumed from the buffer passed to
+    /// [`Message::decode`](crate::Message::decode) or a related method, so the malformed data
+    /// ends at or just before the offset. It is `None` if the error did not originate from
+    /// decoding a buffer.
+    pub fn offset(&self) -> Option<usize> {
+        self.inner.offset
+    }
+
+    /// Returns the path of the field in which decoding failed, from the outermost message to
+    /// the innermost, such as `outer.inner[3].name`.
+    ///
+    /// Elements of repeated message fields are identified by their index. The path is empty if
+    /// decoding failed outside of a known field.
+    pub fn path(&self) -> String {
+        use core::fmt::Write;
+
+        let mut path = String::new();
+        for (i, &(_, field)) in self.inner.stack.iter().enumerate().rev() {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(field);
+            if let Some((_, Some(index))) = self.inner.fields.get(i) {
+                let _ = write!(path, "[{index}]");
+            }
+        }
+        path
+    }
+
+    /// Returns the field numbers of the [`path`](DecodeError::path), from the outermost message
+    /// to the innermost.
+    pub fn field_numbers(&self) -> Vec<u32> {
+        self.inner
+            .fields
+            .iter()
+            .rev()
+            .map(|&(number, _)| number)
+            .collect()
+    }
+
+    /// Returns the wire type which the field was expected to have, if decoding failed because
+    /// of an unexpected wire type.
+    pub fn expected_wire_type(&self) -> Option<WireType> {
+        match self.inner.description {
+            DecodeErrorKind::UnexpectedWireType { expected, .. } => Some(expected),
+            _ => None,
+        }
+    }
+
+    /// Returns the wire type which the field actually had, if decoding failed because of an
+    /// unexpected wire type.
+    pub fn actual_wire_type(&self) -> Option<WireType> {
+        match self.inner.description {
+            DecodeErrorKind::UnexpectedWireType { actual, .. } => Some(actual),
+            _ => None,
+        }
     }
 }
 
 impl fmt::Debug for DecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("DecodeError")
+        let mut debug = f.debug_struct("DecodeError");
+        debug
             .field("description", &self.inner.description)
-            .field("stack", &self.inner.stack)
-            .finish()
+            .field("stack", &self.inner.stack);
+        #[cfg(feature = "rich-errors")]
+        debug
+            .field("path", &self.path())
+            .field("field_numbers", &self.field_numbers())
+            .field("offset", &self.inner.offset);
+        debug.finish()
     }
 }
 
@@ -87,7 +246,20 @@ impl fmt::Display for DecodeError {
         for &(message, field) in &self.inner.stack {
             write!(f, "{message}.{field}: ")?;
         }
-        write!(f, "{}", self.inner.description)
+        write!(f, "{}", self.inner.description)?;
+        #[cfg(feature = "rich-errors")]
+        if f.alternate() {
+            if let Some(offset) = self.inner.offset {
+                write!(f, " (at offset {offset}")?;
+                if !self.inner.stack.is_empty() {
+                    write!(f, ", in field {}", self.path())?;
+                }
+                f.write_str(")")?;
+            } else if !self.inner.stack.is_empty() {
+                write!(f, " (in field {})", self.path())?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -97,6 +269,12 @@ impl From<DecodeErrorKind> for DecodeError {
             inner: Box::new(Inner {
                 description,
                 stack: Vec::new(),
+                #[cfg(feature = "rich-errors")]
+                fields: Vec::new(),
+                #[cfg(feature = "rich-errors")]
+                index: None,
+                #[cfg(feature = "rich-errors")]
+                offset: None,
             }),
         }
     }
@@ -126,12 +304,37 @@ pub(crate) enum DecodeErrorKind {
     BufferUnderflow,
     /// Delimited length exceeded
     DelimitedLengthExceeded,
+    /// A message exceeds the maximum message size
+    MessageSizeLimitExceeded,
+    /// A length-delimited field declares a length which exceeds the length limit
+    LengthLimitExceeded { len: u64, limit: usize },
+    /// A repeated field exceeds the maximum number of elements
+    RepeatedLengthLimitExceeded,
+    /// A value exceeds the capacity of a fixed-size field
+    CapacityExceeded,
     /// Unexpected end group tag
     UnexpectedEndGroupTag,
     /// Invalid string value: data is not UTF-8 encoded
     InvalidString,
+    /// Invalid string value, kept as bytes under `Utf8Policy::Bytes`
+    InvalidStringBytes { value: Vec<u8> },
+    /// Invalid 128-bit integer: the value is not 16 bytes long
+    InvalidInt128 { len: u64 },
+    /// Invalid compression flag of a compressed frame
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    InvalidCompressionFlag { flag: u8 },
+    /// The message of a compressed frame failed to decompress
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    Decompression {
+        algorithm: &'static str,
+        description: String,
+    },
     /// Unexpected type URL
     UnexpectedTypeUrl { actual: String, expected: String },
+    /// Type URL of an unknown message type
+    UnknownTypeUrl { type_url: String },
+    /// Required fields are not set
+    MissingRequiredFields { fields: Vec<String> },
     /// A textual description of a problem
     Other { description: Cow<'static, str> },
 }
@@ -153,13 +356,38 @@ impl fmt::Display for DecodeErrorKind {
             }
             Self::BufferUnderflow => write!(f, "buffer underflow"),
             Self::DelimitedLengthExceeded => write!(f, "delimited length exceeded"),
+            Self::MessageSizeLimitExceeded => write!(f, "message size limit exceeded"),
+            Self::LengthLimitExceeded { len, limit } => write!(
+                f,
+                "length-delimited field of {len} bytes exceeds the length limit of {limit} bytes"
+            ),
+            Self::RepeatedLengthLimitExceeded => {
+                write!(f, "repeated field length limit exceeded")
+            }
+            Self::CapacityExceeded => write!(f, "capacity of fixed-size field exceeded"),
             Self::UnexpectedEndGroupTag => write!(f, "unexpected end group tag"),
-            Self::InvalidString => {
+            Self::InvalidString | Self::InvalidStringBytes { .. } => {
                 write!(f, "invalid string value: data is not UTF-8 encoded")
             }
+            Self::InvalidInt128 { len } => {
+                write!(f, "invalid 128-bit integer: expected 16 bytes, found {len}")
+            }
+            #[cfg(any(feature = "gzip", feature = "zstd"))]
+            Self::InvalidCompressionFlag { flag } => write!(f, "invalid compression flag: {flag}"),
+            #[cfg(any(feature = "gzip", feature = "zstd"))]
+            Self::Decompression {
+                algorithm,
+                description,
+            } => write!(f, "failed to decompress {algorithm} message: {description}"),
             Self::UnexpectedTypeUrl { actual, expected } => {
                 write!(f, "unexpected type URL.type_url: expected type URL: \"{expected}\" (got: \"{actual}\")")
             }
+            Self::UnknownTypeUrl { type_url } => {
+                write!(f, "unknown type URL: \"{type_url}\"")
+            }
+            Self::MissingRequiredFields { fields } => {
+                write!(f, "missing required fields: {}", fields.join(", "))
+            }
             Self::Other { description } => {
                 write!(f, "{description}")
             }
@@ -226,11 +454,57 @@ impl From<EncodeError> for std::io::Error {
     }
 }
 
+/// An error indicating that a message builder is missing a required field.
+///
+/// Builders are generated by `prost-build` when [`Config::generate_builders`][1] is set.
+///
+/// [1]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.generate_builders
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BuildError {
+    message: &'static str,
+    field: &'static str,
+}
+
+impl BuildError {
+    /// Creates a new `BuildError` for a required field which has not been set.
+    ///
+    /// Meant to be used only by generated builders.
+    #[doc(hidden)]
+    pub fn missing_field(message: &'static str, field: &'static str) -> BuildError {
+        BuildError { message, field }
+    }
+
+    /// Returns the fully qualified Protobuf name of the message.
+    pub fn message(&self) -> &'static str {
+        self.message
+    }
+
+    /// Returns the Protobuf name of the missing field.
+    pub fn field(&self) -> &'static str {
+        self.field
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to build Protobuf message {}: missing required field {}",
+            self.message, self.field
+        )
+    }
+}
+
+impl core::error::Error for BuildError {}
+
 /// An error indicating that an unknown enumeration value was encountered.
 ///
 /// The Protobuf spec mandates that enumeration value sets are ‘open’, so this
 /// error's value represents an integer value unrecognized by the
 /// presently used enum definition.
+///
+/// This is the error of the `TryFrom<i32>` implementations and the `try_from_i32` methods
+/// generated by the `Enumeration` derive, and holds the value which failed to convert.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct UnknownEnumValue(pub i32);
 
@@ -242,6 +516,23 @@ impl fmt::Display for UnknownEnumValue {
 
 impl core::error::Error for UnknownEnumValue {}
 
+/// An error indicating that a string is not the name of a value of an enumeration.
+///
+/// This is the error of the `FromStr` implementations generated by `prost-build` with
+/// [`Config::enum_display_from_str`][1], and holds the string which failed to parse.
+///
+/// [1]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.enum_display_from_str
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownEnumName(pub String);
+
+impl fmt::Display for UnknownEnumName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown enumeration name {:?}", self.0)
+    }
+}
+
+impl core::error::Error for UnknownEnumName {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -258,6 +549,25 @@ mod test {
         );
     }
 
+    #[cfg(feature = "rich-errors")]
+    #[test]
+    fn test_push_field() {
+        let mut decode_error = DecodeError::from(DecodeErrorKind::InvalidVarint);
+        decode_error.push_field("Inner", "name", 2);
+        decode_error.push_index(3);
+        decode_error.push_field("Middle", "inner", 5);
+        decode_error.push_field("Outer", "outer", 1);
+        let decode_error = decode_error.at_offset(12).at_offset(20);
+
+        assert_eq!(decode_error.path(), "outer.inner[3].name");
+        assert_eq!(decode_error.field_numbers(), [1, 5, 2]);
+        assert_eq!(decode_error.offset(), Some(12));
+        assert_eq!(
+            format!("{decode_error:#}"),
+            "failed to decode Protobuf message: Inner.name: Middle.inner: Outer.outer: invalid varint (at offset 12, in field outer.inner[3].name)"
+        );
+    }
+
     #[cfg(feature = "std")]
     #[test]
     fn test_into_std_io_error() {
@@ -0,0 +1,383 @@
+//! Support for Protobuf extensions.
+//!
+//! An extension is a field which is declared outside of the message it belongs to, in one of the
+//! field number ranges the message reserves with an `extensions` statement. `prost-build`
+//! generates an [`Extension`] constant for each declared extension, and, when
+//! [`Config::preserve_extensions`][1] is set, stores the extensions of a message in an
+//! [`ExtensionSet`] field. Such messages implement [`Extendable`], which provides access to the
+//! extensions by their constants.
+//!
+//! Extensions are kept in their encoded form, and are only decoded when they are accessed.
+//!
+//! [1]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.preserve_extensions
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use ::bytes::{Buf, BufMut};
+
+use crate::encoding::{decode_key, DecodeContext, WireType};
+use crate::{DecodeError, Message, UnknownFieldSet};
+
+type EncodeFn<T> = fn(u32, &T, &mut Vec<u8>);
+type MergeFn<T> = fn(u32, WireType, &mut T, &mut &[u8], DecodeContext) -> Result<(), DecodeError>;
+
+/// An extension field of the message type `extendee`, holding values of type `T`.
+///
+/// Repeated extensions hold a `Vec` of their element type.
+pub struct Extension<T> {
+    extendee: &'static str,
+    name: &'static str,
+    number: u32,
+    encode: EncodeFn<T>,
+    merge: MergeFn<T>,
+}
+
+impl<T> Extension<T> {
+    /// Creates an extension.
+    ///
+    /// `extendee` and `name` are the fully qualified Protobuf names of the extended message type
+    /// and of the extension, without a leading dot. `encode` writes a value, including its key,
+    /// and `merge` merges a single field with the given tag and wire type into a value.
+    pub const fn new(
+        extendee: &'static str,
+        name: &'static str,
+        number: u32,
+        encode: EncodeFn<T>,
+        merge: MergeFn<T>,
+    ) -> Extension<T> {
+        Extension {
+            extendee,
+            name,
+            number,
+            encode,
+            merge,
+        }
+    }
+
+    /// Returns the fully qualified name of the extended message type.
+    pub fn extendee(&self) -> &'static str {
+        self.extendee
+    }
+
+    /// Returns the fully qualified name of the extension.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the field number of the extension.
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+}
+
+impl<T> Clone for Extension<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Extension<T> {}
+
+impl<T> fmt::Debug for Extension<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extension")
+            .field("extendee", &self.extendee)
+            .field("name", &self.name)
+            .field("number", &self.number)
+            .finish()
+    }
+}
+
+/// The extension fields of a message, in their encoded form.
+///
+/// Fields are kept in the order they were decoded or set, and are written after the other fields
+/// of the message.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ExtensionSet {
+    fields: UnknownFieldSet,
+}
+
+impl ExtensionSet {
+    /// Creates an empty set of extensions.
+    pub const fn new() -> ExtensionSet {
+        ExtensionSet {
+            fields: UnknownFieldSet::new(),
+        }
+    }
+
+    /// Returns `true` if the set contains no extension fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns the encoded extension fields.
+    pub fn fields(&self) -> &UnknownFieldSet {
+        &self.fields
+    }
+
+    /// Returns `true` if the extension is set.
+    pub fn contains<T>(&self, extension: &Extension<T>) -> bool {
+        self.fields
+            .iter()
+            .any(|field| field.tag == extension.number)
+    }
+
+    /// Decodes the value of the extension, or returns `None` if it is not set.
+    ///
+    /// As for regular fields, the last value of a singular extension wins, singular message
+    /// values are merged, and repeated values are concatenated.
+    pub fn get<T>(&self, extension: &Extension<T>) -> Result<Option<T>, DecodeError>
+    where
+        T: Default,
+    {
+        let mut buf = Vec::new();
+        for field in self.fields.iter() {
+            if field.tag == extension.number {
+                field.encode(&mut buf);
+            }
+        }
+        if buf.is_empty() {
+            return Ok(None);
+        }
+
+        let mut value = T::default();
+        let mut buf = buf.as_slice();
+        while buf.has_remaining() {
+            let (tag, wire_type) = decode_key(&mut buf)?;
+            (extension.merge)(
+                tag,
+                wire_type,
+                &mut value,
+                &mut buf,
+                DecodeContext::default(),
+            )?;
+        }
+        Ok(Some(value))
+    }
+
+    /// Sets the value of the extension, replacing any previous value.
+    pub fn set<T>(&mut self, extension: &Extension<T>, value: T) {
+        self.remove(extension);
+
+        let mut buf = Vec::new();
+        (extension.encode)(extension.number, &value, &mut buf);
+        let mut buf = buf.as_slice();
+        while buf.has_remaining() {
+            let (tag, wire_type) = decode_key(&mut buf).expect("valid extension encoding");
+            self.fields
+                .merge_field(tag, wire_type, &mut buf, DecodeContext::default())
+                .expect("valid extension encoding");
+        }
+    }
+
+    /// Removes the extension.
+    pub fn remove<T>(&mut self, extension: &Extension<T>) {
+        self.fields.retain(|field| field.tag != extension.number);
+    }
+}
+
+impl Message for ExtensionSet {
+    fn encode_raw(&self, buf: &mut impl BufMut) {
+        self.fields.encode_raw(buf)
+    }
+
+    fn encode_raw_deterministic(&self, buf: &mut impl BufMut) {
+        self.fields.encode_raw_deterministic(buf)
+    }
+
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        self.fields.merge_field(tag, wire_type, buf, ctx)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.fields.encoded_len()
+    }
+
+    fn merge_from(&mut self, other: &Self) {
+        self.fields.merge_from(&other.fields)
+    }
+
+    fn clear(&mut self) {
+        self.fields.clear()
+    }
+}
+
+/// Extension data is opaque to the message, so arbitrary messages have no extensions.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ExtensionSet {
+    fn arbitrary(_: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(ExtensionSet::default())
+    }
+}
+
+/// A message type which stores its extensions in an [`ExtensionSet`].
+///
+/// This trait is implemented by `#[derive(Message)]` for message types with a field annotated
+/// with `#[prost(extension_set = "...")]`.
+pub trait Extendable: Message {
+    /// The fully qualified Protobuf name of the message type, without a leading dot.
+    const EXTENDEE: &'static str;
+
+    /// Returns the extensions of the message.
+    fn extension_set(&self) -> &ExtensionSet;
+
+    /// Returns the extensions of the message, mutably.
+    fn extension_set_mut(&mut self) -> &mut ExtensionSet;
+
+    /// Returns `true` if the extension is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the extension does not extend this message type.
+    fn has_extension<T>(&self, extension: &Extension<T>) -> bool
+    where
+        Self: Sized,
+    {
+        check_extendee::<Self, T>(extension);
+        self.extension_set().contains(extension)
+    }
+
+    /// Decodes the value of the extension, or returns `None` if it is not set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the extension does not extend this message type.
+    fn get_extension<T>(&self, extension: &Extension<T>) -> Result<Option<T>, DecodeError>
+    where
+        Self: Sized,
+        T: Default,
+    {
+        check_extendee::<Self, T>(extension);
+        self.extension_set().get(extension)
+    }
+
+    /// Sets the value of the extension, replacing any previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the extension does not extend this message type.
+    fn set_extension<T>(&mut self, extension: &Extension<T>, value: T)
+    where
+        Self: Sized,
+    {
+        check_extendee::<Self, T>(extension);
+        self.extension_set_mut().set(extension, value)
+    }
+
+    /// Clears the extension.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the extension does not extend this message type.
+    fn clear_extension<T>(&mut self, extension: &Extension<T>)
+    where
+        Self: Sized,
+    {
+        check_extendee::<Self, T>(extension);
+        self.extension_set_mut().remove(extension)
+    }
+}
+
+fn check_extendee<M, T>(extension: &Extension<T>)
+where
+    M: Extendable,
+{
+    assert!(
+        extension.extendee == M::EXTENDEE,
+        "extension {} extends {}, not {}",
+        extension.name,
+        extension.extendee,
+        M::EXTENDEE
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::String;
+    use alloc::vec;
+
+    use super::*;
+    use crate::encoding::{group, int32, message, string};
+
+    const SCORE: Extension<i32> = Extension::new(
+        "test.Base",
+        "test.score",
+        100,
+        int32::encode,
+        |_, wire_type, value, buf, ctx| int32::merge(wire_type, value, buf, ctx),
+    );
+
+    const TAGS: Extension<Vec<String>> = Extension::new(
+        "test.Base",
+        "test.tags",
+        101,
+        |tag, value, buf| string::encode_repeated(tag, value, buf),
+        |_, wire_type, value, buf, ctx| string::merge_repeated(wire_type, value, buf, ctx),
+    );
+
+    const NESTED: Extension<ExtensionSet> = Extension::new(
+        "test.Base",
+        "test.nested",
+        102,
+        message::encode,
+        |_, wire_type, value, buf, ctx| message::merge(wire_type, value, buf, ctx),
+    );
+
+    const GROUP: Extension<ExtensionSet> = Extension::new(
+        "test.Base",
+        "test.group",
+        103,
+        group::encode,
+        |tag, wire_type, value, buf, ctx| group::merge(tag, wire_type, value, buf, ctx),
+    );
+
+    #[test]
+    fn set_and_get() {
+        let mut set = ExtensionSet::new();
+        assert_eq!(set.get(&SCORE), Ok(None));
+        assert!(!set.contains(&SCORE));
+
+        set.set(&SCORE, -5);
+        set.set(&TAGS, vec!["a".into(), "b".into()]);
+        assert!(set.contains(&SCORE));
+        assert_eq!(set.get(&SCORE), Ok(Some(-5)));
+        assert_eq!(set.get(&TAGS), Ok(Some(vec!["a".into(), "b".into()])));
+
+        set.set(&SCORE, 7);
+        assert_eq!(set.get(&SCORE), Ok(Some(7)));
+        assert_eq!(set.fields().len(), 3);
+
+        let mut nested = ExtensionSet::new();
+        nested.set(&SCORE, 1);
+        set.set(&NESTED, nested.clone());
+        set.set(&GROUP, nested.clone());
+        assert_eq!(set.get(&NESTED), Ok(Some(nested.clone())));
+        assert_eq!(set.get(&GROUP), Ok(Some(nested)));
+
+        set.remove(&TAGS);
+        assert_eq!(set.get(&TAGS), Ok(None));
+
+        let decoded = ExtensionSet::decode(set.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, set);
+    }
+
+    #[test]
+    fn decoded_values() {
+        // The last value of a singular field wins, and repeated values are concatenated.
+        let bytes = [0xa0, 0x06, 0x01, 0xa0, 0x06, 0x02, 0xaa, 0x06, 0x01, b'x'];
+        let set = ExtensionSet::decode(&bytes[..]).unwrap();
+        assert_eq!(set.get(&SCORE), Ok(Some(2)));
+        assert_eq!(set.get(&TAGS), Ok(Some(vec!["x".into()])));
+
+        // Values with the wrong wire type are reported when they are accessed.
+        let set = ExtensionSet::decode(&[0xa5, 0x06, 0, 0, 0, 0][..]).unwrap();
+        assert!(set.get(&SCORE).is_err());
+    }
+}
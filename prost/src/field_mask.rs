@@ -0,0 +1,396 @@
+//! Support for applying field masks to messages.
+//!
+//! A field mask is a set of field paths, such as `user.display_name`, which selects some of the
+//! fields of a message. Field masks are used by update and read requests to specify the fields
+//! which should be changed or returned. The well-known `google.protobuf.FieldMask` type of
+//! `prost-types` provides the user-facing operations; this module contains the traits which are
+//! implemented by `#[derive(Message)]` for message types annotated with `#[prost(field_mask)]`.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A set of field paths, arranged as a tree of field names.
+///
+/// Each node of the tree maps the names of the selected fields of a message to the nodes of their
+/// sub-fields. A field whose node has no children is selected as a whole. Paths which are
+/// covered by another path of the tree, such as `a.b` and `a`, are merged when added.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FieldMaskTree {
+    fields: BTreeMap<String, FieldMaskTree>,
+}
+
+impl FieldMaskTree {
+    /// Creates an empty tree, which selects no fields.
+    pub fn new() -> FieldMaskTree {
+        FieldMaskTree::default()
+    }
+
+    /// Adds a dot-separated field path to the tree.
+    pub fn add_path(&mut self, path: &str) {
+        if !path.is_empty() {
+            self.add(&path.split('.').collect::<Vec<_>>());
+        }
+    }
+
+    fn add(&mut self, names: &[&str]) {
+        let (name, names) = match names.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+        match self.fields.get_mut(*name) {
+            // The field is already selected as a whole.
+            Some(node) if node.is_leaf() => (),
+            Some(node) if names.is_empty() => node.fields.clear(),
+            Some(node) => node.add(names),
+            None => {
+                let mut node = FieldMaskTree::new();
+                node.add(names);
+                self.fields.insert((*name).into(), node);
+            }
+        }
+    }
+
+    /// Returns the node of the field with the given name, or `None` if the field is not selected.
+    pub fn get(&self, name: &str) -> Option<&FieldMaskTree> {
+        self.fields.get(name)
+    }
+
+    /// Returns `true` if the node has no children.
+    ///
+    /// The root of a tree without children selects no fields, and any other node without
+    /// children selects its field as a whole.
+    pub fn is_leaf(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns the paths of the tree in canonical form: sorted, and without paths which are
+    /// covered by other paths.
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        self.collect_paths(&mut String::new(), &mut paths);
+        paths
+    }
+
+    fn collect_paths(&self, prefix: &mut String, paths: &mut Vec<String>) {
+        for (name, node) in &self.fields {
+            let len = prefix.len();
+            if !prefix.is_empty() {
+                prefix.push('.');
+            }
+            prefix.push_str(name);
+            if node.is_leaf() {
+                paths.push(prefix.clone());
+            } else {
+                node.collect_paths(prefix, paths);
+            }
+            prefix.truncate(len);
+        }
+    }
+
+    /// Adds the paths of another tree to this tree.
+    pub fn union(&mut self, other: &FieldMaskTree) {
+        for path in other.paths() {
+            self.add_path(&path);
+        }
+    }
+
+    /// Returns the tree of the paths which are selected by both this tree and `other`.
+    pub fn intersect(&self, other: &FieldMaskTree) -> FieldMaskTree {
+        let mut result = FieldMaskTree::new();
+        for (name, node) in &self.fields {
+            let other_node = match other.fields.get(name) {
+                Some(other_node) => other_node,
+                None => continue,
+            };
+            let node = if node.is_leaf() {
+                other_node.clone()
+            } else if other_node.is_leaf() {
+                node.clone()
+            } else {
+                let node = node.intersect(other_node);
+                if node.is_leaf() {
+                    continue;
+                }
+                node
+            };
+            result.fields.insert(name.clone(), node);
+        }
+        result
+    }
+}
+
+impl<S> FromIterator<S> for FieldMaskTree
+where
+    S: AsRef<str>,
+{
+    fn from_iter<I: IntoIterator<Item = S>>(paths: I) -> Self {
+        let mut tree = FieldMaskTree::new();
+        for path in paths {
+            tree.add_path(path.as_ref());
+        }
+        tree
+    }
+}
+
+/// A Protobuf message whose fields may be selected by a field mask.
+///
+/// This trait is implemented by `#[derive(Message)]` for message types annotated with
+/// `#[prost(field_mask)]`. Field paths use the Protobuf names of the fields, in snake_case.
+pub trait FieldMaskMessage {
+    /// Returns `true` if the path, split into field names, refers to a field of the message.
+    ///
+    /// Paths may descend into singular message fields, but not into repeated or map fields.
+    fn is_valid_path(path: &[&str]) -> bool
+    where
+        Self: Sized;
+
+    /// Clears the fields of the message which are not selected by the mask.
+    fn retain_fields(&mut self, mask: &FieldMaskTree);
+
+    /// Replaces the fields of the message which are selected by the mask with those of `source`.
+    ///
+    /// Selected fields which are not set in `source` are cleared, and repeated and map fields are
+    /// replaced rather than appended to, following the update semantics of Google APIs.
+    fn merge_fields(&mut self, source: &Self, mask: &FieldMaskTree)
+    where
+        Self: Sized;
+}
+
+impl<M> FieldMaskMessage for alloc::boxed::Box<M>
+where
+    M: FieldMaskMessage,
+{
+    fn is_valid_path(path: &[&str]) -> bool {
+        M::is_valid_path(path)
+    }
+
+    fn retain_fields(&mut self, mask: &FieldMaskTree) {
+        (**self).retain_fields(mask)
+    }
+
+    fn merge_fields(&mut self, source: &Self, mask: &FieldMaskTree) {
+        (**self).merge_fields(&**source, mask)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<M> FieldMaskMessage for alloc::sync::Arc<M>
+where
+    M: FieldMaskMessage + Clone,
+{
+    fn is_valid_path(path: &[&str]) -> bool {
+        M::is_valid_path(path)
+    }
+
+    fn retain_fields(&mut self, mask: &FieldMaskTree) {
+        alloc::sync::Arc::make_mut(self).retain_fields(mask)
+    }
+
+    fn merge_fields(&mut self, source: &Self, mask: &FieldMaskTree) {
+        alloc::sync::Arc::make_mut(self).merge_fields(&**source, mask)
+    }
+}
+
+/// A value which may be held by a field of a message supporting field masks.
+///
+/// Values which are not messages have no sub-fields, so the default implementations treat them as
+/// a whole. This is implemented for the Rust types of the Protobuf scalar types, repeated and map
+/// fields, and for all [`FieldMaskMessage`] types.
+pub trait FieldMaskField {
+    /// Returns `true` if the non-empty path refers to a sub-field of the value.
+    fn is_valid_path(path: &[&str]) -> bool {
+        let _ = path;
+        false
+    }
+
+    /// Clears the sub-fields of the value which are not selected by the non-empty mask.
+    fn retain_fields(&mut self, mask: &FieldMaskTree) {
+        let _ = mask;
+    }
+
+    /// Replaces the sub-fields of the value which are selected by the non-empty mask.
+    fn merge_fields(&mut self, source: &Self, mask: &FieldMaskTree) {
+        let _ = (source, mask);
+    }
+}
+
+impl<M> FieldMaskField for M
+where
+    M: FieldMaskMessage,
+{
+    fn is_valid_path(path: &[&str]) -> bool {
+        M::is_valid_path(path)
+    }
+
+    fn retain_fields(&mut self, mask: &FieldMaskTree) {
+        FieldMaskMessage::retain_fields(self, mask)
+    }
+
+    fn merge_fields(&mut self, source: &Self, mask: &FieldMaskTree) {
+        FieldMaskMessage::merge_fields(self, source, mask)
+    }
+}
+
+impl<T> FieldMaskField for Option<T>
+where
+    T: FieldMaskField + Default,
+{
+    fn is_valid_path(path: &[&str]) -> bool {
+        T::is_valid_path(path)
+    }
+
+    fn retain_fields(&mut self, mask: &FieldMaskTree) {
+        if let Some(value) = self {
+            value.retain_fields(mask);
+        }
+    }
+
+    fn merge_fields(&mut self, source: &Self, mask: &FieldMaskTree) {
+        match source {
+            Some(source) => self
+                .get_or_insert_with(Default::default)
+                .merge_fields(source, mask),
+            None => {
+                if let Some(value) = self {
+                    value.merge_fields(&T::default(), mask);
+                }
+            }
+        }
+    }
+}
+
+/// The sub-fields of lazy messages are selected once they are decoded. Lazy messages which fail
+/// to decode are left unchanged.
+#[cfg(feature = "std")]
+impl<M> FieldMaskField for crate::Lazy<M>
+where
+    M: FieldMaskField + crate::Message + Default,
+{
+    fn is_valid_path(path: &[&str]) -> bool {
+        M::is_valid_path(path)
+    }
+
+    fn retain_fields(&mut self, mask: &FieldMaskTree) {
+        if let Ok(value) = self.get_mut() {
+            value.retain_fields(mask);
+        }
+    }
+
+    fn merge_fields(&mut self, source: &Self, mask: &FieldMaskTree) {
+        if let (Ok(value), Ok(source)) = (self.get_mut(), source.get()) {
+            FieldMaskField::merge_fields(value, source, mask);
+        }
+    }
+}
+
+macro_rules! leaf_fields {
+    ($($ty:ty),* $(,)?) => {
+        $(impl FieldMaskField for $ty {})*
+    };
+}
+
+leaf_fields!(
+    bool,
+    i32,
+    i64,
+    u32,
+    u64,
+    f32,
+    f64,
+    (),
+    String,
+    bytes::Bytes,
+    crate::Utf8Bytes,
+);
+
+impl<T> FieldMaskField for Vec<T> {}
+
+impl<K, V> FieldMaskField for BTreeMap<K, V> {}
+
+#[cfg(feature = "std")]
+impl<K, V, S> FieldMaskField for std::collections::HashMap<K, V, S> {}
+
+#[doc(hidden)]
+pub mod encoding {
+    use super::*;
+
+    /// Returns `true` if the remaining path after a field name is valid for the field.
+    pub fn is_valid_path<M, T>(_field: fn(&M) -> &T, path: &[&str]) -> bool
+    where
+        T: FieldMaskField,
+    {
+        path.is_empty() || T::is_valid_path(path)
+    }
+
+    /// Clears a field which is not selected, or the sub-fields which are not selected.
+    pub fn retain<T>(field: &mut T, node: Option<&FieldMaskTree>)
+    where
+        T: FieldMaskField + Default,
+    {
+        match node {
+            None => *field = T::default(),
+            Some(node) if node.is_leaf() => (),
+            Some(node) => field.retain_fields(node),
+        }
+    }
+
+    /// Returns `false` if a oneof field is not selected, and otherwise clears the sub-fields
+    /// which are not selected.
+    pub fn retain_oneof<T>(field: &mut T, node: Option<&FieldMaskTree>) -> bool
+    where
+        T: FieldMaskField,
+    {
+        match node {
+            None => false,
+            Some(node) => {
+                if !node.is_leaf() {
+                    field.retain_fields(node);
+                }
+                true
+            }
+        }
+    }
+
+    /// Replaces a selected field, or the selected sub-fields, with those of `source`.
+    pub fn merge<T>(field: &mut T, source: &T, node: Option<&FieldMaskTree>)
+    where
+        T: FieldMaskField + Clone,
+    {
+        match node {
+            None => (),
+            Some(node) if node.is_leaf() => field.clone_from(source),
+            Some(node) => field.merge_fields(source, node),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+
+    #[test]
+    fn tree_paths() {
+        let tree: FieldMaskTree = ["b.c", "a", "b.d.e", "a.x", "b.d", ""].iter().collect();
+        assert_eq!(tree.paths(), vec!["a", "b.c", "b.d"]);
+        assert!(tree.get("a").unwrap().is_leaf());
+        assert!(!tree.get("b").unwrap().is_leaf());
+        assert!(tree.get("c").is_none());
+        assert!(FieldMaskTree::new().paths().is_empty());
+    }
+
+    #[test]
+    fn tree_union_and_intersect() {
+        let mut a: FieldMaskTree = ["a.b", "c", "d.e"].iter().collect();
+        let b: FieldMaskTree = ["a", "c.x", "d.f", "g"].iter().collect();
+
+        assert_eq!(a.intersect(&b).paths(), vec!["a.b", "c.x"]);
+        assert_eq!(b.intersect(&a).paths(), vec!["a.b", "c.x"]);
+        assert!(a.intersect(&FieldMaskTree::new()).is_leaf());
+
+        a.union(&b);
+        assert_eq!(a.paths(), vec!["a", "c", "d.e", "d.f", "g"]);
+    }
+}
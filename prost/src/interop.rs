@@ -0,0 +1,115 @@
+//! Conversion between `prost` messages and messages generated by [`rust-protobuf`][1].
+//!
+//! A codebase migrating between the two runtimes can hold both kinds of message types for the
+//! same Protobuf messages. [`ProtobufInterop`] converts a `prost` message to and from its
+//! `rust-protobuf` counterpart through the binary encoding, after checking that both types have
+//! the same fully qualified name:
+//!
+//! ```rust,ignore
+//! use prost::interop::ProtobufInterop;
+//!
+//! let person: protos::person::Person = prost_person.to_protobuf()?;
+//! let prost_person = Person::from_protobuf(&person)?;
+//! ```
+//!
+//! The `prost` message type must implement [`Name`], which `prost-build` generates with
+//! [`Config::enable_type_names`][2]. Fields which are unknown to the target type are kept or
+//! dropped according to its runtime, as when decoding the encoding of any other message.
+//!
+//! [1]: https://docs.rs/protobuf
+//! [2]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.enable_type_names
+
+use alloc::borrow::Cow;
+use alloc::format;
+use core::fmt;
+
+use rust_protobuf::MessageFull;
+
+use crate::{Message, Name};
+
+/// An error converting between a `prost` message and a `rust-protobuf` message.
+///
+/// `InteropError` indicates that the message types have different names, or that one runtime
+/// failed to encode or decode the message.
+#[derive(Clone, PartialEq, Eq)]
+pub struct InteropError {
+    description: Cow<'static, str>,
+}
+
+impl InteropError {
+    #[cold]
+    fn new(description: impl Into<Cow<'static, str>>) -> InteropError {
+        InteropError {
+            description: description.into(),
+        }
+    }
+}
+
+impl fmt::Debug for InteropError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InteropError")
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+impl fmt::Display for InteropError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to convert between prost and rust-protobuf: ")?;
+        f.write_str(&self.description)
+    }
+}
+
+impl core::error::Error for InteropError {}
+
+/// Conversion of a `prost` message to and from a `rust-protobuf` message of the same type.
+///
+/// This trait is implemented for every `prost` message which implements [`Name`] and
+/// [`Default`].
+pub trait ProtobufInterop: Message + Name + Default {
+    /// Converts the message to the `rust-protobuf` message type `P`.
+    ///
+    /// Fails if `P` is not the same Protobuf message type, or if `rust-protobuf` can not decode
+    /// the encoded message.
+    fn to_protobuf<P: MessageFull>(&self) -> Result<P, InteropError> {
+        check_names::<Self, P>()?;
+        P::parse_from_bytes(&self.encode_to_vec()).map_err(|error| {
+            InteropError::new(format!(
+                "rust-protobuf failed to decode the message: {error}"
+            ))
+        })
+    }
+
+    /// Converts a `rust-protobuf` message of type `P` to this message type.
+    ///
+    /// Fails if `P` is not the same Protobuf message type, if `rust-protobuf` can not encode the
+    /// message, such as when a proto2 required field is missing, or if `prost` can not decode it.
+    fn from_protobuf<P: MessageFull>(message: &P) -> Result<Self, InteropError> {
+        check_names::<Self, P>()?;
+        let bytes = message.write_to_bytes().map_err(|error| {
+            InteropError::new(format!(
+                "rust-protobuf failed to encode the message: {error}"
+            ))
+        })?;
+        Self::decode(bytes.as_slice()).map_err(|error| {
+            InteropError::new(format!("prost failed to decode the message: {error}"))
+        })
+    }
+}
+
+impl<M: Message + Name + Default> ProtobufInterop for M {}
+
+/// Checks that the `prost` message type `M` and the `rust-protobuf` message type `P` have the
+/// same fully qualified name.
+fn check_names<M: Name, P: MessageFull>() -> Result<(), InteropError> {
+    let descriptor = P::descriptor();
+    let name = M::full_name();
+    if name == descriptor.full_name() {
+        Ok(())
+    } else {
+        Err(InteropError::new(format!(
+            "the prost message `{name}` is not the rust-protobuf message `{}`",
+            descriptor.full_name()
+        )))
+    }
+}
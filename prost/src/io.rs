@@ -0,0 +1,217 @@
+//! Length-delimited framing of messages over asynchronous readers and writers.
+//!
+//! Each message is preceded by its encoded length as a varint, as with
+//! [`Message::encode_length_delimited`], which is the framing commonly used to send a sequence of
+//! messages over a socket. The [`tokio`] and [`futures`] modules provide the same API for the I/O
+//! traits of the respective crates, behind the `tokio` and `futures` features:
+//!
+//! * `AsyncMessageExt` encodes and decodes a single length-delimited message, and is implemented
+//!   for all messages.
+//! * `FramedMessageStream` adapts a reader into a [`Stream`](futures_core::Stream) of messages.
+//!
+//! ```rust,ignore
+//! use prost::io::tokio::{AsyncMessageExt, FramedMessageStream};
+//!
+//! request.encode_length_delimited_async(&mut socket).await?;
+//! let response = Response::decode_length_delimited_async(&mut socket).await?;
+//!
+//! let mut events = FramedMessageStream::<_, Event>::new(socket);
+//! while let Some(event) = events.next().await {
+//!     handle(event?);
+//! }
+//! ```
+
+use std::future::poll_fn;
+use std::io;
+use std::task::{ready, Context, Poll};
+
+use bytes::{Buf, BytesMut};
+
+use crate::{decode_length_delimiter, Message};
+
+#[cfg(feature = "futures")]
+pub mod futures;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+
+/// The maximum length of an encoded length delimiter.
+const MAX_DELIMITER_LEN: usize = 10;
+
+/// The number of bytes which are read at once, when the length of the data is not known yet, or
+/// is larger.
+const READ_SIZE: usize = 8 * 1024;
+
+/// Writes the whole buffer with `poll_write`.
+async fn write_all<F>(mut buf: &[u8], mut poll_write: F) -> io::Result<()>
+where
+    F: FnMut(&mut Context<'_>, &[u8]) -> Poll<io::Result<usize>>,
+{
+    poll_fn(|cx| {
+        while !buf.is_empty() {
+            let n = ready!(poll_write(cx, buf))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            buf = &buf[n..];
+        }
+        Poll::Ready(Ok(()))
+    })
+    .await
+}
+
+/// Fills the whole buffer with `poll_read`.
+async fn read_exact<F>(mut buf: &mut [u8], mut poll_read: F) -> io::Result<()>
+where
+    F: FnMut(&mut Context<'_>, &mut [u8]) -> Poll<io::Result<usize>>,
+{
+    poll_fn(|cx| {
+        while !buf.is_empty() {
+            let n = ready!(poll_read(cx, buf))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+            }
+            buf = &mut std::mem::take(&mut buf)[n..];
+        }
+        Poll::Ready(Ok(()))
+    })
+    .await
+}
+
+/// Encodes a length-delimited message with `poll_write`.
+async fn encode_length_delimited<M, F>(message: &M, poll_write: F) -> io::Result<()>
+where
+    M: Message,
+    F: FnMut(&mut Context<'_>, &[u8]) -> Poll<io::Result<usize>>,
+{
+    write_all(&message.encode_length_delimited_to_vec(), poll_write).await
+}
+
+/// Decodes a length-delimited message with `poll_read`.
+///
+/// Exactly the bytes of the message are read. The message is read in chunks, so that a corrupt
+/// length does not allocate more memory than the data which is actually received.
+async fn decode_length_delimited<M, F>(mut poll_read: F) -> io::Result<M>
+where
+    M: Message + Default,
+    F: FnMut(&mut Context<'_>, &mut [u8]) -> Poll<io::Result<usize>>,
+{
+    let mut delimiter = [0; MAX_DELIMITER_LEN];
+    let mut delimiter_len = 0;
+    while delimiter_len < MAX_DELIMITER_LEN {
+        read_exact(
+            &mut delimiter[delimiter_len..=delimiter_len],
+            &mut poll_read,
+        )
+        .await?;
+        delimiter_len += 1;
+        if delimiter[delimiter_len - 1] < 0x80 {
+            break;
+        }
+    }
+    let len = decode_length_delimiter(&delimiter[..delimiter_len])?;
+
+    let mut buf = Vec::new();
+    while buf.len() < len {
+        let start = buf.len();
+        buf.resize(len.min(start + READ_SIZE.max(start)), 0);
+        read_exact(&mut buf[start..], &mut poll_read).await?;
+    }
+    Ok(M::decode(buf.as_slice())?)
+}
+
+/// The buffered input of a `FramedMessageStream`.
+#[derive(Debug, Default)]
+struct FrameBuffer {
+    buf: BytesMut,
+}
+
+impl FrameBuffer {
+    /// Decodes the next message with the data read by `poll_read`.
+    ///
+    /// Returns `None` when `poll_read` reaches the end of the input at a message boundary.
+    fn poll_next<M, F>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut poll_read: F,
+    ) -> Poll<Option<io::Result<M>>>
+    where
+        M: Message + Default,
+        F: FnMut(&mut Context<'_>, &mut [u8]) -> Poll<io::Result<usize>>,
+    {
+        loop {
+            if let Some(message) = self.decode_frame() {
+                return Poll::Ready(Some(message));
+            }
+
+            let len = self.buf.len();
+            self.buf.resize(len + READ_SIZE, 0);
+            let result = poll_read(cx, &mut self.buf[len..]);
+            let n = match result {
+                Poll::Ready(Ok(n)) => n,
+                _ => 0,
+            };
+            self.buf.truncate(len + n);
+
+            match ready!(result) {
+                Ok(0) if self.buf.is_empty() => return Poll::Ready(None),
+                Ok(0) => {
+                    self.buf.clear();
+                    return Poll::Ready(Some(Err(io::ErrorKind::UnexpectedEof.into())));
+                }
+                Ok(_) => (),
+                Err(error) => return Poll::Ready(Some(Err(error))),
+            }
+        }
+    }
+
+    /// Decodes the first message of the buffer, or returns `None` if it is not complete yet.
+    fn decode_frame<M>(&mut self) -> Option<io::Result<M>>
+    where
+        M: Message + Default,
+    {
+        let mut frame = &self.buf[..];
+        let len = match decode_length_delimiter(&mut frame) {
+            Ok(len) => len,
+            // The delimiter is incomplete.
+            Err(_) if self.buf.len() < MAX_DELIMITER_LEN => return None,
+            Err(error) => {
+                self.buf.clear();
+                return Some(Err(error.into()));
+            }
+        };
+        if frame.len() < len {
+            return None;
+        }
+
+        let delimiter_len = self.buf.len() - frame.len();
+        self.buf.advance(delimiter_len);
+        let frame = self.buf.split_to(len).freeze();
+        Some(M::decode(frame).map_err(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod test_util {
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    /// Polls the future until it completes.
+    pub fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+}
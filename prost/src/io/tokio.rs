@@ -0,0 +1,230 @@
+//! Length-delimited framing of messages over the I/O traits of [`tokio`](::tokio).
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use futures_core::Stream;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use super::FrameBuffer;
+use crate::Message;
+
+/// Encodes and decodes length-delimited messages with asynchronous writers and readers.
+///
+/// This trait is implemented for all messages.
+pub trait AsyncMessageExt: Message + Sized {
+    /// Encodes the message with a length-delimiter to the writer.
+    ///
+    /// The writer is not flushed.
+    fn encode_length_delimited_async<W>(
+        &self,
+        writer: &mut W,
+    ) -> impl Future<Output = io::Result<()>>
+    where
+        W: AsyncWrite + Unpin;
+
+    /// Decodes a length-delimited message from the reader.
+    ///
+    /// Exactly the bytes of the message are read, so the reader can be used to decode the
+    /// following messages. Decoding errors are returned as [`io::ErrorKind::InvalidData`] errors.
+    fn decode_length_delimited_async<R>(reader: &mut R) -> impl Future<Output = io::Result<Self>>
+    where
+        R: AsyncRead + Unpin,
+        Self: Default;
+}
+
+impl<M> AsyncMessageExt for M
+where
+    M: Message,
+{
+    async fn encode_length_delimited_async<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        super::encode_length_delimited(self, |cx, buf| Pin::new(&mut *writer).poll_write(cx, buf))
+            .await
+    }
+
+    async fn decode_length_delimited_async<R>(reader: &mut R) -> io::Result<M>
+    where
+        R: AsyncRead + Unpin,
+        M: Default,
+    {
+        super::decode_length_delimited(|cx, buf| poll_read(&mut *reader, cx, buf)).await
+    }
+}
+
+/// A [`Stream`] of the length-delimited messages read from an [`AsyncRead`].
+///
+/// The stream ends when the reader reaches the end of its input after a message, and yields an
+/// [`io::ErrorKind::UnexpectedEof`] error if the input ends within a message.
+pub struct FramedMessageStream<R, M> {
+    reader: R,
+    frames: FrameBuffer,
+    message: PhantomData<fn() -> M>,
+}
+
+impl<R, M> FramedMessageStream<R, M> {
+    /// Creates a stream of the messages read from `reader`.
+    pub fn new(reader: R) -> FramedMessageStream<R, M> {
+        FramedMessageStream {
+            reader,
+            frames: FrameBuffer::default(),
+            message: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Reading from the reader directly may corrupt the stream of messages.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Returns the underlying reader.
+    ///
+    /// Data which has been read from the reader, but has not been decoded yet, is lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R, M> Stream for FramedMessageStream<R, M>
+where
+    R: AsyncRead + Unpin,
+    M: Message + Default,
+{
+    type Item = io::Result<M>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<io::Result<M>>> {
+        let this = self.get_mut();
+        let reader = &mut this.reader;
+        this.frames
+            .poll_next(cx, |cx, buf| poll_read(&mut *reader, cx, buf))
+    }
+}
+
+impl<R, M> fmt::Debug for FramedMessageStream<R, M>
+where
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FramedMessageStream")
+            .field("reader", &self.reader)
+            .field("frames", &self.frames)
+            .finish()
+    }
+}
+
+fn poll_read<R>(reader: &mut R, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = ReadBuf::new(buf);
+    ready!(Pin::new(reader).poll_read(cx, &mut buf))?;
+    Poll::Ready(Ok(buf.filled().len()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::future::poll_fn;
+
+    use super::*;
+    use crate::io::test_util::block_on;
+
+    /// A reader which returns one byte at a time, and is pending before each byte.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        pending: bool,
+    }
+
+    impl AsyncRead for Trickle<'_> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            self.pending = !self.pending;
+            if self.pending {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            if let Some((&byte, rest)) = self.data.split_first() {
+                buf.put_slice(&[byte]);
+                self.data = rest;
+            }
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn next<R>(stream: &mut FramedMessageStream<R, String>) -> Option<io::Result<String>>
+    where
+        R: AsyncRead + Unpin,
+    {
+        block_on(poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)))
+    }
+
+    #[test]
+    fn encode_and_decode() {
+        let mut buf = Vec::new();
+        block_on(async {
+            "hello"
+                .to_string()
+                .encode_length_delimited_async(&mut buf)
+                .await
+                .unwrap();
+            String::new()
+                .encode_length_delimited_async(&mut buf)
+                .await
+                .unwrap();
+        });
+        assert_eq!(buf, b"\x07\x0a\x05hello\x00");
+
+        let mut reader = Trickle {
+            data: &buf,
+            pending: false,
+        };
+        block_on(async {
+            let first = String::decode_length_delimited_async(&mut reader).await;
+            assert_eq!(first.unwrap(), "hello");
+            let second = String::decode_length_delimited_async(&mut reader).await;
+            assert_eq!(second.unwrap(), "");
+            let end = String::decode_length_delimited_async(&mut reader).await;
+            assert_eq!(end.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        });
+
+        let error = block_on(String::decode_length_delimited_async(
+            &mut &b"\x02\x0a\x05"[..],
+        ));
+        assert_eq!(error.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn framed_stream() {
+        let buf = b"\x07\x0a\x05hello\x00\x03\x0a\x01!";
+        let mut stream = FramedMessageStream::new(Trickle {
+            data: buf,
+            pending: false,
+        });
+        assert_eq!(next(&mut stream).unwrap().unwrap(), "hello");
+        assert_eq!(next(&mut stream).unwrap().unwrap(), "");
+        assert_eq!(next(&mut stream).unwrap().unwrap(), "!");
+        assert!(next(&mut stream).is_none());
+
+        let mut stream = FramedMessageStream::new(&buf[..3]);
+        assert_eq!(
+            next(&mut stream).unwrap().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        assert!(next(&mut stream).is_none());
+    }
+}
@@ -0,0 +1,342 @@
+//! Support for the [Protobuf JSON mapping][1].
+//!
+//! Messages opt in to the JSON mapping with the `#[prost(json)]` attribute, which `prost-build`
+//! emits when [`Config::enable_json`][2] is set. The derived [`JsonMessage`] implementation
+//! converts between the message and a [`JsonValue`], and [`to_string`] and [`from_str`] convert
+//! to and from JSON text.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! #[derive(Clone, PartialEq, prost::Message)]
+//! #[prost(json)]
+//! struct Person {
+//!     #[prost(string, tag = "1")]
+//!     display_name: String,
+//!     #[prost(int64, tag = "2")]
+//!     id: i64,
+//! }
+//!
+//! let person = Person { display_name: "Ferris".into(), id: 7 };
+//! let json = prost::json::to_string(&person).unwrap();
+//! assert_eq!(json, r#"{"displayName":"Ferris","id":"7"}"#);
+//! assert_eq!(prost::json::from_str::<Person>(&json).unwrap(), person);
+//! # }
+//! ```
+//!
+//! Enumeration fields are mapped to their Protobuf value names, which requires the inherent
+//! `as_str_name` and `from_str_name` methods generated by `prost-build`. The well-known types
+//! with special JSON representations implement [`JsonMessage`] in `prost-types`, behind its
+//! `json` feature.
+//!
+//! [1]: https://protobuf.dev/programming-guides/json/
+//! [2]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.enable_json
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Message, Name};
+
+#[doc(hidden)]
+pub mod encoding;
+mod scalar;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod value;
+
+pub use scalar::JsonMapKey;
+pub use value::{JsonObject, JsonValue};
+
+/// A Protobuf JSON mapping error.
+///
+/// `JsonError` indicates that the input is not valid JSON, or that it does not conform to the
+/// JSON mapping of the target message type.
+#[derive(Clone, PartialEq, Eq)]
+pub struct JsonError {
+    description: Cow<'static, str>,
+}
+
+impl JsonError {
+    /// Creates a new `JsonError` with a description of the problem.
+    ///
+    /// Meant to be used only by `JsonMessage` implementations.
+    #[doc(hidden)]
+    #[cold]
+    pub fn new(description: impl Into<Cow<'static, str>>) -> JsonError {
+        JsonError {
+            description: description.into(),
+        }
+    }
+}
+
+impl fmt::Debug for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonError")
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to process Protobuf JSON: ")?;
+        f.write_str(&self.description)
+    }
+}
+
+impl core::error::Error for JsonError {}
+
+/// Options controlling the conversion between messages and JSON.
+///
+/// The defaults match the canonical encoding of the Protobuf JSON mapping: fields with default
+/// values are omitted, field names are written in lowerCamelCase, and unknown fields are rejected
+/// while parsing.
+#[derive(Clone, Debug, Default)]
+pub struct JsonOptions {
+    emit_default_values: bool,
+    use_proto_field_names: bool,
+    ignore_unknown_fields: bool,
+    types: Vec<JsonType>,
+}
+
+/// A message type which may be packed into a `google.protobuf.Any`.
+#[derive(Clone, Debug)]
+struct JsonType {
+    full_name: String,
+    to_json: fn(&[u8], &JsonOptions) -> Result<JsonValue, JsonError>,
+    from_json: fn(&JsonValue, &JsonOptions) -> Result<Vec<u8>, JsonError>,
+}
+
+impl JsonOptions {
+    /// Creates options for the canonical JSON encoding.
+    pub fn new() -> JsonOptions {
+        JsonOptions::default()
+    }
+
+    /// Emit fields which are set to their default value, rather than omitting them.
+    ///
+    /// Singular message fields and oneof fields which are unset are never emitted.
+    pub fn emit_default_values(mut self, enabled: bool) -> Self {
+        self.emit_default_values = enabled;
+        self
+    }
+
+    /// Emit field names as they appear in the `.proto` file, rather than in lowerCamelCase.
+    ///
+    /// Both forms are always accepted when parsing.
+    pub fn use_proto_field_names(mut self, enabled: bool) -> Self {
+        self.use_proto_field_names = enabled;
+        self
+    }
+
+    /// Ignore fields and enum values which are not known to the message type when parsing,
+    /// rather than failing.
+    pub fn ignore_unknown_fields(mut self, enabled: bool) -> Self {
+        self.ignore_unknown_fields = enabled;
+        self
+    }
+
+    /// Registers a message type which may appear packed in a `google.protobuf.Any`.
+    ///
+    /// The JSON representation of an `Any` embeds the packed message, so its type must be known
+    /// in order to convert it. Registering a type whose full name is already registered replaces
+    /// the previous registration.
+    pub fn register<M>(mut self) -> Self
+    where
+        M: JsonField + Name + Default,
+    {
+        let full_name = M::full_name();
+        self.types.retain(|ty| ty.full_name != full_name);
+        self.types.push(JsonType {
+            full_name,
+            to_json: |buf, options| {
+                let message =
+                    M::decode(buf).map_err(|error| JsonError::new(alloc::format!("{error}")))?;
+                message.to_json(options)
+            },
+            from_json: |value, options| Ok(M::from_json(value, options)?.encode_to_vec()),
+        });
+        self
+    }
+
+    /// Returns the registration for the message type identified by `type_url`.
+    fn find_type(&self, type_url: &str) -> Result<&JsonType, JsonError> {
+        let full_name = match type_url.rfind('/') {
+            Some(index) => &type_url[index + 1..],
+            None => type_url,
+        };
+        self.types
+            .iter()
+            .find(|ty| ty.full_name == full_name)
+            .ok_or_else(|| {
+                JsonError::new(alloc::format!(
+                    "type URL `{type_url}` does not refer to a registered message type"
+                ))
+            })
+    }
+}
+
+/// A Protobuf message which supports the JSON mapping.
+///
+/// This trait is implemented by `#[derive(Message)]` for message types annotated with
+/// `#[prost(json)]`.
+pub trait JsonMessage {
+    /// Converts the message to its JSON representation.
+    fn to_json(&self, options: &JsonOptions) -> Result<JsonValue, JsonError>;
+
+    /// Merges the fields of a JSON representation of the message into `self`.
+    fn merge_json(&mut self, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError>;
+
+    /// Returns `true` if JSON `null` is a value of the message, rather than an absent field.
+    ///
+    /// This only holds for `google.protobuf.Value`.
+    #[doc(hidden)]
+    fn accepts_null() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+}
+
+impl<M> JsonMessage for Box<M>
+where
+    M: JsonMessage,
+{
+    fn to_json(&self, options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        (**self).to_json(options)
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError> {
+        (**self).merge_json(value, options)
+    }
+
+    fn accepts_null() -> bool {
+        M::accepts_null()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<M> JsonMessage for alloc::sync::Arc<M>
+where
+    M: JsonMessage + Clone,
+{
+    fn to_json(&self, options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        (**self).to_json(options)
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError> {
+        alloc::sync::Arc::make_mut(self).merge_json(value, options)
+    }
+
+    fn accepts_null() -> bool {
+        M::accepts_null()
+    }
+}
+
+/// A value which may be held by a field of a message supporting the JSON mapping.
+///
+/// This is implemented for the Rust types of the Protobuf scalar types, which also represent the
+/// well-known wrapper types such as `google.protobuf.Int64Value`, and for all [`JsonMessage`]
+/// types.
+pub trait JsonField: Sized {
+    /// Converts the value to its JSON representation.
+    fn to_json(&self, options: &JsonOptions) -> Result<JsonValue, JsonError>;
+
+    /// Merges a JSON representation of the value into `self`.
+    fn merge_json(&mut self, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError>;
+
+    /// Converts a JSON representation to a new value.
+    fn from_json(value: &JsonValue, options: &JsonOptions) -> Result<Self, JsonError>
+    where
+        Self: Default,
+    {
+        let mut field = Self::default();
+        field.merge_json(value, options)?;
+        Ok(field)
+    }
+
+    /// Returns `true` if JSON `null` is a value of the type, rather than an absent field.
+    #[doc(hidden)]
+    fn accepts_null() -> bool {
+        false
+    }
+}
+
+impl<M> JsonField for M
+where
+    M: JsonMessage + Message,
+{
+    fn to_json(&self, options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        JsonMessage::to_json(self, options)
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError> {
+        JsonMessage::merge_json(self, value, options)
+    }
+
+    fn accepts_null() -> bool {
+        <M as JsonMessage>::accepts_null()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M> JsonField for crate::Lazy<M>
+where
+    M: JsonField + Message + Default,
+{
+    fn to_json(&self, options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        let message = self
+            .get()
+            .map_err(|error| JsonError::new(alloc::string::ToString::to_string(&error)))?;
+        message.to_json(options)
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError> {
+        let message = self
+            .get_mut()
+            .map_err(|error| JsonError::new(alloc::string::ToString::to_string(&error)))?;
+        message.merge_json(value, options)
+    }
+
+    fn accepts_null() -> bool {
+        M::accepts_null()
+    }
+}
+
+/// Converts a message to a JSON string, using the canonical encoding.
+pub fn to_string<M>(message: &M) -> Result<String, JsonError>
+where
+    M: JsonMessage,
+{
+    to_string_with_options(message, &JsonOptions::default())
+}
+
+/// Converts a message to a JSON string.
+pub fn to_string_with_options<M>(message: &M, options: &JsonOptions) -> Result<String, JsonError>
+where
+    M: JsonMessage,
+{
+    Ok(alloc::format!("{}", message.to_json(options)?))
+}
+
+/// Parses a message from a JSON string, using the default options.
+pub fn from_str<M>(s: &str) -> Result<M, JsonError>
+where
+    M: JsonMessage + Default,
+{
+    from_str_with_options(s, &JsonOptions::default())
+}
+
+/// Parses a message from a JSON string.
+pub fn from_str_with_options<M>(s: &str, options: &JsonOptions) -> Result<M, JsonError>
+where
+    M: JsonMessage + Default,
+{
+    let value = s.parse::<JsonValue>()?;
+    let mut message = M::default();
+    message.merge_json(&value, options)?;
+    Ok(message)
+}
@@ -0,0 +1,362 @@
+//! Utility functions and types for the JSON mapping of derived messages.
+//!
+//! Meant to be used only from `Message` implementations.
+
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub use super::{
+    JsonError, JsonField, JsonMapKey, JsonMessage, JsonObject, JsonOptions, JsonValue,
+};
+
+/// The well-known types whose JSON representation is not a plain object, and which are therefore
+/// wrapped in a `value` member when packed into an `Any`.
+const SPECIAL_TYPES: &[&str] = &[
+    "google.protobuf.Any",
+    "google.protobuf.BoolValue",
+    "google.protobuf.BytesValue",
+    "google.protobuf.DoubleValue",
+    "google.protobuf.Duration",
+    "google.protobuf.FieldMask",
+    "google.protobuf.FloatValue",
+    "google.protobuf.Int32Value",
+    "google.protobuf.Int64Value",
+    "google.protobuf.ListValue",
+    "google.protobuf.StringValue",
+    "google.protobuf.Struct",
+    "google.protobuf.Timestamp",
+    "google.protobuf.UInt32Value",
+    "google.protobuf.UInt64Value",
+    "google.protobuf.Value",
+];
+
+/// Returns the name to emit for a field.
+pub fn field_name(
+    options: &JsonOptions,
+    json_name: &'static str,
+    proto_name: &'static str,
+) -> &'static str {
+    if options.use_proto_field_names {
+        proto_name
+    } else {
+        json_name
+    }
+}
+
+/// Returns `true` if fields set to their default value should be emitted.
+pub fn emit_default_values(options: &JsonOptions) -> bool {
+    options.emit_default_values
+}
+
+/// Returns the members of the JSON representation of a message.
+pub fn object<'a>(value: &'a JsonValue, message: &str) -> Result<&'a JsonObject, JsonError> {
+    match value {
+        JsonValue::Object(members) => Ok(members),
+        _ => Err(JsonError::new(format!(
+            "expected object for {message}, found {}",
+            value.kind()
+        ))),
+    }
+}
+
+/// Handles a member of a message's JSON representation which does not match any field.
+pub fn unknown_field(name: &str, message: &str, options: &JsonOptions) -> Result<(), JsonError> {
+    if options.ignore_unknown_fields {
+        Ok(())
+    } else {
+        Err(JsonError::new(format!(
+            "unknown field `{name}` in {message}"
+        )))
+    }
+}
+
+/// Adds a message and field name to an error.
+pub fn with_field(error: JsonError, message: &str, field: &str) -> JsonError {
+    JsonError::new(format!("{message}.{field}: {}", error.description))
+}
+
+/// Merges a singular field. `null` leaves the field unchanged.
+pub fn merge<T>(field: &mut T, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError>
+where
+    T: JsonField,
+{
+    if value.is_null() && !T::accepts_null() {
+        return Ok(());
+    }
+    field.merge_json(value, options)
+}
+
+/// Merges an optional field. `null` leaves the field unchanged.
+pub fn merge_optional<T>(
+    field: &mut Option<T>,
+    value: &JsonValue,
+    options: &JsonOptions,
+) -> Result<(), JsonError>
+where
+    T: JsonField + Default,
+{
+    if value.is_null() && !T::accepts_null() {
+        return Ok(());
+    }
+    field
+        .get_or_insert_with(Default::default)
+        .merge_json(value, options)
+}
+
+/// Merges a repeated field, appending the elements of a JSON array.
+pub fn merge_repeated<T>(
+    field: &mut Vec<T>,
+    value: &JsonValue,
+    options: &JsonOptions,
+) -> Result<(), JsonError>
+where
+    T: JsonField + Default,
+{
+    for value in array(value)? {
+        if value.is_null() && !T::accepts_null() {
+            return Err(JsonError::new("null is not a valid repeated field element"));
+        }
+        field.push(T::from_json(value, options)?);
+    }
+    Ok(())
+}
+
+/// Converts the value of a oneof field, returning `None` for `null`.
+pub fn merge_oneof<T>(value: &JsonValue, options: &JsonOptions) -> Result<Option<T>, JsonError>
+where
+    T: JsonField + Default,
+{
+    if value.is_null() && !T::accepts_null() {
+        return Ok(None);
+    }
+    T::from_json(value, options).map(Some)
+}
+
+/// Converts the value of a map entry.
+pub fn map_value<V>(value: &JsonValue, options: &JsonOptions) -> Result<Option<V>, JsonError>
+where
+    V: JsonField + Default,
+{
+    if value.is_null() && !V::accepts_null() {
+        return Err(JsonError::new("null is not a valid map value"));
+    }
+    V::from_json(value, options).map(Some)
+}
+
+/// Converts the elements of a repeated field.
+pub fn encode_repeated<T>(values: &[T], options: &JsonOptions) -> Result<JsonValue, JsonError>
+where
+    T: JsonField,
+{
+    values
+        .iter()
+        .map(|value| value.to_json(options))
+        .collect::<Result<_, _>>()
+        .map(JsonValue::Array)
+}
+
+/// Converts the entries of a map field, using `value_to_json` to convert each value.
+pub fn encode_map<'a, K, V>(
+    map: impl IntoIterator<Item = (&'a K, &'a V)>,
+    mut value_to_json: impl FnMut(&V) -> Result<JsonValue, JsonError>,
+) -> Result<JsonValue, JsonError>
+where
+    K: JsonMapKey + 'a,
+    V: 'a,
+{
+    map.into_iter()
+        .map(|(key, value)| Ok((key.to_json_key(), value_to_json(value)?)))
+        .collect::<Result<_, _>>()
+        .map(JsonValue::Object)
+}
+
+/// Merges the members of a JSON object into a map field, using `value_from_json` to convert each
+/// value. Entries for which `value_from_json` returns `None` are skipped.
+pub fn merge_map<K, V, M>(
+    map: &mut M,
+    value: &JsonValue,
+    mut value_from_json: impl FnMut(&JsonValue) -> Result<Option<V>, JsonError>,
+) -> Result<(), JsonError>
+where
+    K: JsonMapKey,
+    M: Extend<(K, V)>,
+{
+    let members = match value {
+        JsonValue::Null => return Ok(()),
+        JsonValue::Object(members) => members,
+        _ => {
+            return Err(JsonError::new(format!(
+                "expected object for map field, found {}",
+                value.kind()
+            )))
+        }
+    };
+    for (key, value) in members {
+        let key = K::from_json_key(key)?;
+        if let Some(value) = value_from_json(value)? {
+            map.extend(core::iter::once((key, value)));
+        }
+    }
+    Ok(())
+}
+
+fn array(value: &JsonValue) -> Result<&[JsonValue], JsonError> {
+    match value {
+        JsonValue::Null => Ok(&[]),
+        JsonValue::Array(values) => Ok(values),
+        _ => Err(JsonError::new(format!(
+            "expected array for repeated field, found {}",
+            value.kind()
+        ))),
+    }
+}
+
+/// The mapping between the values of an enumeration and their Protobuf names.
+#[derive(Clone, Copy)]
+pub struct Enumeration {
+    /// Returns the name of a value, or `None` if the value is unknown.
+    pub name: fn(i32) -> Option<&'static str>,
+    /// Returns the value of a name, or `None` if the name is unknown.
+    pub value: fn(&str) -> Option<i32>,
+}
+
+impl Enumeration {
+    /// Converts an enumeration value to its name, or to a number if the value is unknown.
+    pub fn to_json(&self, value: i32) -> JsonValue {
+        match (self.name)(value) {
+            Some(name) => JsonValue::String(name.to_owned()),
+            None => JsonValue::Number(format!("{value}")),
+        }
+    }
+
+    /// Converts a name or number to an enumeration value. Returns `None` if the name is unknown
+    /// and unknown values are ignored.
+    pub fn from_json(
+        &self,
+        value: &JsonValue,
+        options: &JsonOptions,
+    ) -> Result<Option<i32>, JsonError> {
+        match value {
+            JsonValue::String(name) => match (self.value)(name) {
+                Some(value) => Ok(Some(value)),
+                None if options.ignore_unknown_fields => Ok(None),
+                None => Err(JsonError::new(format!("unknown enum value: {name}"))),
+            },
+            JsonValue::Number(_) => i32::from_json(value, options).map(Some),
+            _ => Err(JsonError::new(format!(
+                "expected enum value, found {}",
+                value.kind()
+            ))),
+        }
+    }
+
+    /// Merges a singular enumeration field. `null` leaves the field unchanged.
+    pub fn merge(
+        &self,
+        field: &mut i32,
+        value: &JsonValue,
+        options: &JsonOptions,
+    ) -> Result<(), JsonError> {
+        if let Some(value) = self.merge_oneof(value, options)? {
+            *field = value;
+        }
+        Ok(())
+    }
+
+    /// Merges an optional enumeration field. `null` leaves the field unchanged.
+    pub fn merge_optional(
+        &self,
+        field: &mut Option<i32>,
+        value: &JsonValue,
+        options: &JsonOptions,
+    ) -> Result<(), JsonError> {
+        if let Some(value) = self.merge_oneof(value, options)? {
+            *field = Some(value);
+        }
+        Ok(())
+    }
+
+    /// Merges a repeated enumeration field, appending the elements of a JSON array.
+    pub fn merge_repeated(
+        &self,
+        field: &mut Vec<i32>,
+        value: &JsonValue,
+        options: &JsonOptions,
+    ) -> Result<(), JsonError> {
+        for value in array(value)? {
+            field.extend(self.from_json(value, options)?);
+        }
+        Ok(())
+    }
+
+    /// Converts the value of a oneof enumeration field, returning `None` for `null`.
+    pub fn merge_oneof(
+        &self,
+        value: &JsonValue,
+        options: &JsonOptions,
+    ) -> Result<Option<i32>, JsonError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        self.from_json(value, options)
+    }
+
+    /// Converts the elements of a repeated enumeration field.
+    pub fn encode_repeated(&self, values: &[i32]) -> JsonValue {
+        JsonValue::Array(values.iter().map(|&value| self.to_json(value)).collect())
+    }
+}
+
+/// Converts the packed message of a `google.protobuf.Any` to JSON.
+pub fn encode_any(
+    type_url: &str,
+    value: &[u8],
+    options: &JsonOptions,
+) -> Result<JsonValue, JsonError> {
+    let ty = options.find_type(type_url)?;
+    let type_member = ("@type".to_owned(), JsonValue::String(type_url.to_owned()));
+    let value = (ty.to_json)(value, options)?;
+    let members = match value {
+        JsonValue::Object(mut members) if !SPECIAL_TYPES.contains(&ty.full_name.as_str()) => {
+            members.insert(0, type_member);
+            members
+        }
+        value => Vec::from([type_member, ("value".to_owned(), value)]),
+    };
+    Ok(JsonValue::Object(members))
+}
+
+/// Converts the JSON representation of a `google.protobuf.Any` to the type URL and the encoded
+/// packed message.
+pub fn merge_any(value: &JsonValue, options: &JsonOptions) -> Result<(String, Vec<u8>), JsonError> {
+    let members = object(value, "Any")?;
+    let type_url = match value.get("@type") {
+        Some(JsonValue::String(type_url)) => type_url,
+        Some(_) => return Err(JsonError::new("`@type` of Any must be a string")),
+        None if members.is_empty() => return Ok((String::new(), Vec::new())),
+        None => return Err(JsonError::new("missing `@type` in Any")),
+    };
+    let ty = options.find_type(type_url)?;
+    let value = if SPECIAL_TYPES.contains(&ty.full_name.as_str()) {
+        for (name, _) in members {
+            if name != "@type" && name != "value" {
+                unknown_field(name, "Any", options)?;
+            }
+        }
+        match value.get("value") {
+            Some(value) => value.clone(),
+            None => return Ok((type_url.clone(), Vec::new())),
+        }
+    } else {
+        JsonValue::Object(
+            members
+                .iter()
+                .filter(|(name, _)| name != "@type")
+                .cloned()
+                .collect(),
+        )
+    };
+    Ok((type_url.clone(), (ty.from_json)(&value, options)?))
+}
@@ -0,0 +1,428 @@
+//! JSON representations of the Protobuf scalar types.
+//!
+//! The Rust types of the scalar types also represent the well-known wrapper types, such as
+//! `google.protobuf.Int64Value`, whose JSON representation is that of the wrapped scalar.
+
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use ::bytes::Bytes;
+
+use super::{JsonError, JsonField, JsonOptions, JsonValue};
+use crate::Utf8Bytes;
+
+/// A type which may be used as the key of a map field.
+///
+/// JSON object member names are always strings, so map keys are converted to and from their
+/// string form.
+pub trait JsonMapKey: Sized {
+    /// Converts the key to a JSON object member name.
+    fn to_json_key(&self) -> String;
+
+    /// Converts a JSON object member name to a key.
+    fn from_json_key(key: &str) -> Result<Self, JsonError>;
+}
+
+fn unexpected(expected: &str, value: &JsonValue) -> JsonError {
+    JsonError::new(format!("expected {expected}, found {}", value.kind()))
+}
+
+/// Parses an integer from its JSON representation, which is either a number or a string.
+///
+/// Numbers in exponent or fractional notation are accepted, as long as they have an integral
+/// value.
+fn parse_integer<T>(value: &JsonValue) -> Result<T, JsonError>
+where
+    T: TryFrom<i128> + core::str::FromStr,
+{
+    let text = match value {
+        JsonValue::Number(text) | JsonValue::String(text) => text.as_str(),
+        _ => return Err(unexpected("integer", value)),
+    };
+    let invalid = || JsonError::new(format!("invalid integer: {text}"));
+    // Rust's number parsing is more lenient than JSON, e.g. it accepts a leading `+`.
+    if !text.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    // Parsing the type itself covers the 128-bit integers, which `i128` does not all hold.
+    if let Ok(integer) = text.parse::<T>() {
+        return Ok(integer);
+    }
+    let integer = match text.parse::<i128>() {
+        Ok(integer) => integer,
+        Err(_) => {
+            let float = text.parse::<f64>().map_err(|_| invalid())?;
+            // The cast saturates, so only integral values in range convert back losslessly.
+            let integer = float as i128;
+            if integer as f64 != float || integer == i128::MAX || integer == i128::MIN {
+                return Err(invalid());
+            }
+            integer
+        }
+    };
+    T::try_from(integer).map_err(|_| JsonError::new(format!("integer out of range: {text}")))
+}
+
+fn parse_float(value: &JsonValue) -> Result<f64, JsonError> {
+    let text = match value {
+        JsonValue::Number(text) => text.as_str(),
+        JsonValue::String(text) => match text.as_str() {
+            "NaN" => return Ok(f64::NAN),
+            "Infinity" => return Ok(f64::INFINITY),
+            "-Infinity" => return Ok(f64::NEG_INFINITY),
+            text if text.starts_with(|c: char| c == '-' || c.is_ascii_digit()) => text,
+            _ => return Err(JsonError::new(format!("invalid number: {text}"))),
+        },
+        _ => return Err(unexpected("number", value)),
+    };
+    let float = text
+        .parse::<f64>()
+        .map_err(|_| JsonError::new(format!("invalid number: {text}")))?;
+    if float.is_infinite() {
+        return Err(JsonError::new(format!("number out of range: {text}")));
+    }
+    Ok(float)
+}
+
+fn float_to_json(value: f64, display: impl ToString) -> JsonValue {
+    if value.is_nan() {
+        JsonValue::String("NaN".to_owned())
+    } else if value == f64::INFINITY {
+        JsonValue::String("Infinity".to_owned())
+    } else if value == f64::NEG_INFINITY {
+        JsonValue::String("-Infinity".to_owned())
+    } else {
+        JsonValue::Number(display.to_string())
+    }
+}
+
+macro_rules! integer {
+    ($ty:ty, $quoted:expr) => {
+        impl JsonField for $ty {
+            fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+                // 64-bit and 128-bit integers are quoted, since JSON parsers commonly use doubles
+                // for numbers.
+                Ok(if $quoted {
+                    JsonValue::String(self.to_string())
+                } else {
+                    JsonValue::Number(self.to_string())
+                })
+            }
+
+            fn merge_json(
+                &mut self,
+                value: &JsonValue,
+                _options: &JsonOptions,
+            ) -> Result<(), JsonError> {
+                *self = parse_integer(value)?;
+                Ok(())
+            }
+        }
+
+        impl JsonMapKey for $ty {
+            fn to_json_key(&self) -> String {
+                self.to_string()
+            }
+
+            fn from_json_key(key: &str) -> Result<Self, JsonError> {
+                key.parse()
+                    .map_err(|_| JsonError::new(format!("invalid map key: {key}")))
+            }
+        }
+    };
+}
+
+integer!(i32, false);
+integer!(u32, false);
+integer!(i64, true);
+integer!(u64, true);
+integer!(i128, true);
+integer!(u128, true);
+
+impl JsonField for f64 {
+    fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        Ok(float_to_json(*self, self))
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, _options: &JsonOptions) -> Result<(), JsonError> {
+        *self = parse_float(value)?;
+        Ok(())
+    }
+}
+
+impl JsonField for f32 {
+    fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        Ok(float_to_json(f64::from(*self), self))
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, _options: &JsonOptions) -> Result<(), JsonError> {
+        let float = parse_float(value)?;
+        if float.is_finite() && (float > f64::from(f32::MAX) || float < f64::from(f32::MIN)) {
+            return Err(JsonError::new(format!("number out of range: {float}")));
+        }
+        *self = float as f32;
+        Ok(())
+    }
+}
+
+impl JsonField for bool {
+    fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::Bool(*self))
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, _options: &JsonOptions) -> Result<(), JsonError> {
+        match value {
+            JsonValue::Bool(value) => {
+                *self = *value;
+                Ok(())
+            }
+            _ => Err(unexpected("boolean", value)),
+        }
+    }
+}
+
+impl JsonMapKey for bool {
+    fn to_json_key(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_json_key(key: &str) -> Result<Self, JsonError> {
+        match key {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(JsonError::new(format!("invalid map key: {key}"))),
+        }
+    }
+}
+
+impl JsonField for String {
+    fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::String(self.clone()))
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, _options: &JsonOptions) -> Result<(), JsonError> {
+        match value {
+            JsonValue::String(value) => {
+                self.clone_from(value);
+                Ok(())
+            }
+            _ => Err(unexpected("string", value)),
+        }
+    }
+}
+
+impl JsonMapKey for String {
+    fn to_json_key(&self) -> String {
+        self.clone()
+    }
+
+    fn from_json_key(key: &str) -> Result<Self, JsonError> {
+        Ok(key.to_owned())
+    }
+}
+
+impl JsonField for Utf8Bytes {
+    fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::String(self.as_str().to_owned()))
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, _options: &JsonOptions) -> Result<(), JsonError> {
+        match value {
+            JsonValue::String(value) => {
+                *self = value.clone().into();
+                Ok(())
+            }
+            _ => Err(unexpected("string", value)),
+        }
+    }
+}
+
+impl JsonField for Vec<u8> {
+    fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::String(base64_encode(self)))
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, _options: &JsonOptions) -> Result<(), JsonError> {
+        match value {
+            JsonValue::String(value) => {
+                *self = base64_decode(value)?;
+                Ok(())
+            }
+            _ => Err(unexpected("base64 string", value)),
+        }
+    }
+}
+
+impl JsonField for Bytes {
+    fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::String(base64_encode(self)))
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, _options: &JsonOptions) -> Result<(), JsonError> {
+        match value {
+            JsonValue::String(value) => {
+                *self = base64_decode(value)?.into();
+                Ok(())
+            }
+            _ => Err(unexpected("base64 string", value)),
+        }
+    }
+}
+
+/// `google.protobuf.Empty` is represented as an empty object.
+impl JsonField for () {
+    fn to_json(&self, _options: &JsonOptions) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::Object(Vec::new()))
+    }
+
+    fn merge_json(&mut self, value: &JsonValue, options: &JsonOptions) -> Result<(), JsonError> {
+        match value {
+            JsonValue::Object(members) => match members.first() {
+                Some((name, _)) if !options.ignore_unknown_fields => {
+                    Err(JsonError::new(format!("unknown field `{name}` in Empty")))
+                }
+                _ => Ok(()),
+            },
+            _ => Err(unexpected("object", value)),
+        }
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes with the standard base64 alphabet, with padding.
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let indices = [
+            b[0] >> 2,
+            (b[0] & 0x03) << 4 | b[1] >> 4,
+            (b[1] & 0x0f) << 2 | b[2] >> 6,
+            b[2] & 0x3f,
+        ];
+        for (i, &index) in indices.iter().enumerate() {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Decodes base64 in either the standard or the URL-safe alphabet, with or without padding.
+fn base64_decode(s: &str) -> Result<Vec<u8>, JsonError> {
+    let invalid = || JsonError::new("invalid base64 string");
+    let data = s.trim_end_matches('=');
+    if s.len() - data.len() > 2 || (data.len() != s.len() && s.len() % 4 != 0) {
+        return Err(invalid());
+    }
+    if data.len() % 4 == 1 {
+        return Err(invalid());
+    }
+    let mut decoded = Vec::with_capacity(data.len() * 3 / 4);
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    for byte in data.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return Err(invalid()),
+        };
+        accumulator = accumulator << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((accumulator >> bits) as u8);
+            accumulator &= (1 << bits) - 1;
+        }
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn json(s: &str) -> JsonValue {
+        s.parse().unwrap()
+    }
+
+    fn from_json<T: JsonField + Default>(s: &str) -> Result<T, JsonError> {
+        T::from_json(&json(s), &JsonOptions::default())
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(from_json::<i32>("-12"), Ok(-12));
+        assert_eq!(from_json::<i32>("\"-12\""), Ok(-12));
+        assert_eq!(from_json::<i32>("1e2"), Ok(100));
+        assert_eq!(from_json::<u64>("\"18446744073709551615\""), Ok(u64::MAX));
+        assert!(from_json::<i32>("2147483648").is_err());
+        assert!(from_json::<u32>("-1").is_err());
+        assert!(from_json::<i64>("1.5").is_err());
+        assert!(from_json::<i64>("\" 1\"").is_err());
+        assert!(from_json::<i64>("true").is_err());
+
+        let options = JsonOptions::default();
+        assert_eq!(5i32.to_json(&options), Ok(JsonValue::Number("5".into())));
+        assert_eq!(5i64.to_json(&options), Ok(JsonValue::String("5".into())));
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(from_json::<f64>("1.5"), Ok(1.5));
+        assert_eq!(from_json::<f64>("\"-Infinity\""), Ok(f64::NEG_INFINITY));
+        assert!(from_json::<f64>("\"NaN\"").unwrap().is_nan());
+        assert!(from_json::<f64>("1e400").is_err());
+        assert!(from_json::<f32>("1e39").is_err());
+
+        let options = JsonOptions::default();
+        assert_eq!(
+            0.1f32.to_json(&options),
+            Ok(JsonValue::Number("0.1".into()))
+        );
+        assert_eq!(
+            f64::NAN.to_json(&options),
+            Ok(JsonValue::String("NaN".into()))
+        );
+    }
+
+    #[test]
+    fn base64() {
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"\xfb\xff", "+/8="),
+        ] {
+            assert_eq!(base64_encode(bytes), encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), bytes);
+        }
+        assert_eq!(base64_decode("-_8").unwrap(), b"\xfb\xff");
+        assert!(base64_decode("Zg=").is_err());
+        assert!(base64_decode("Z").is_err());
+        assert!(base64_decode("Zm9v!").is_err());
+    }
+
+    #[test]
+    fn map_keys() {
+        assert_eq!(i64::from_json_key("-3"), Ok(-3));
+        assert_eq!(bool::from_json_key("true"), Ok(true));
+        assert!(bool::from_json_key("1").is_err());
+        assert!(u32::from_json_key("x").is_err());
+    }
+}
@@ -0,0 +1,563 @@
+//! Serde adapters for the fields of messages generated with [`Config::enable_serde`][1].
+//!
+//! The generated messages derive `Serialize` and `Deserialize`, and fields whose serde
+//! representation differs from the [Protobuf JSON mapping][2] are annotated with
+//! `#[serde(with = "...")]` and one of the adapters of this module:
+//!
+//! - [`Scalar`] converts 64-bit integers, floating point numbers and bytes through their
+//!   [`JsonField`] implementation, so that 64-bit integers are quoted, non-finite floats are
+//!   strings, and bytes are base64 encoded.
+//! - [`Enumeration`] converts the `i32` value of an enumeration field to the name of its
+//!   variant, and accepts both names and numbers.
+//! - [`Optional`], [`Repeated`] and [`MapValues`] apply an adapter to the values of optional,
+//!   repeated and map fields.
+//! - [`Message`] converts a whole message through its [`JsonMessage`] implementation. It
+//!   implements the serde traits of messages whose optional fields hold their presence in
+//!   presence bits, which a derived implementation can not see.
+//!
+//! [`JsonValue`] implements `Serialize` and `Deserialize` as well.
+//!
+//! [1]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.enable_serde
+//! [2]: https://protobuf.dev/programming-guides/json/
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use ::serde::de::{self, DeserializeOwned, MapAccess, SeqAccess, Visitor};
+use ::serde::ser::{SerializeMap, SerializeSeq};
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{JsonField, JsonMessage, JsonOptions, JsonValue};
+
+/// Converts a field value between its Rust type `T` and its serde representation.
+pub trait Adapter<T> {
+    /// Serializes a field value.
+    fn serialize<S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+
+    /// Deserializes a field value.
+    fn deserialize<'de, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+/// Implements the inherent `serialize` and `deserialize` functions called by
+/// `#[serde(with = "...")]`, which forward to the `Adapter` implementation.
+macro_rules! with_functions {
+    ($ty:ident $(<$param:ident>)?) => {
+        impl$(<$param>)? $ty$(<$param>)? {
+            /// Serializes a field value, for use with `#[serde(with = "...")]`.
+            pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                Self: Adapter<T>,
+                S: Serializer,
+            {
+                <Self as Adapter<T>>::serialize(value, serializer)
+            }
+
+            /// Deserializes a field value, for use with `#[serde(with = "...")]`.
+            pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+            where
+                Self: Adapter<T>,
+                D: Deserializer<'de>,
+            {
+                <Self as Adapter<T>>::deserialize(deserializer)
+            }
+        }
+    };
+}
+
+/// Serializes a value with an adapter.
+struct Adapted<'a, A, T>(&'a T, PhantomData<A>);
+
+impl<A, T> Serialize for Adapted<'_, A, T>
+where
+    A: Adapter<T>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        A::serialize(self.0, serializer)
+    }
+}
+
+/// Deserializes a value with an adapter.
+struct Unadapted<A, T>(T, PhantomData<A>);
+
+impl<'de, A, T> Deserialize<'de> for Unadapted<A, T>
+where
+    A: Adapter<T>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        A::deserialize(deserializer).map(|value| Unadapted(value, PhantomData))
+    }
+}
+
+/// The adapter of the scalar types whose JSON representation is not their serde representation,
+/// which converts values through their [`JsonField`] implementation.
+pub struct Scalar;
+
+with_functions!(Scalar);
+
+impl<T> Adapter<T> for Scalar
+where
+    T: JsonField + Default,
+{
+    fn serialize<S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        value
+            .to_json(&JsonOptions::default())
+            .map_err(::serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+
+    fn deserialize<'de, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = JsonValue::deserialize(deserializer)?;
+        T::from_json(&value, &JsonOptions::default()).map_err(de::Error::custom)
+    }
+}
+
+/// The adapter of enumeration fields, which maps values to the names of the variants of `E`.
+///
+/// `E` is serialized with its `Serialize` implementation, which `prost-build` derives with the
+/// Protobuf names of the variants. Unknown values are serialized as numbers.
+pub struct Enumeration<E>(PhantomData<E>);
+
+with_functions!(Enumeration<E>);
+
+impl<E> Adapter<i32> for Enumeration<E>
+where
+    E: Serialize + DeserializeOwned + TryFrom<i32> + Into<i32>,
+{
+    fn serialize<S>(value: &i32, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match E::try_from(*value) {
+            Ok(value) => value.serialize(serializer),
+            Err(_) => serializer.serialize_i32(*value),
+        }
+    }
+
+    fn deserialize<'de, D>(deserializer: D) -> Result<i32, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct EnumerationVisitor<E>(PhantomData<E>);
+
+        impl<E> Visitor<'_> for EnumerationVisitor<E>
+        where
+            E: DeserializeOwned + Into<i32>,
+        {
+            type Value = i32;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("an enum value name or number")
+            }
+
+            fn visit_str<Err>(self, value: &str) -> Result<i32, Err>
+            where
+                Err: de::Error,
+            {
+                E::deserialize(de::value::StrDeserializer::<Err>::new(value)).map(Into::into)
+            }
+
+            fn visit_i64<Err>(self, value: i64) -> Result<i32, Err>
+            where
+                Err: de::Error,
+            {
+                i32::try_from(value)
+                    .map_err(|_| Err::invalid_value(de::Unexpected::Signed(value), &self))
+            }
+
+            fn visit_u64<Err>(self, value: u64) -> Result<i32, Err>
+            where
+                Err: de::Error,
+            {
+                i32::try_from(value)
+                    .map_err(|_| Err::invalid_value(de::Unexpected::Unsigned(value), &self))
+            }
+        }
+
+        deserializer.deserialize_any(EnumerationVisitor::<E>(PhantomData))
+    }
+}
+
+/// The adapter of messages, which converts them through their [`JsonMessage`] implementation.
+pub struct Message;
+
+with_functions!(Message);
+
+impl<M> Adapter<M> for Message
+where
+    M: JsonMessage + Default,
+{
+    fn serialize<S>(value: &M, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        value
+            .to_json(&JsonOptions::default())
+            .map_err(::serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+
+    fn deserialize<'de, D>(deserializer: D) -> Result<M, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = JsonValue::deserialize(deserializer)?;
+        let mut message = M::default();
+        message
+            .merge_json(&value, &JsonOptions::default())
+            .map_err(de::Error::custom)?;
+        Ok(message)
+    }
+}
+
+/// The adapter of optional fields, which applies the adapter `A` to present values.
+pub struct Optional<A>(PhantomData<A>);
+
+with_functions!(Optional<A>);
+
+impl<A, T> Adapter<Option<T>> for Optional<A>
+where
+    A: Adapter<T>,
+{
+    fn serialize<S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer.serialize_some(&Adapted::<A, T>(value, PhantomData)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize<'de, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Option::<Unadapted<A, T>>::deserialize(deserializer)?;
+        Ok(value.map(|value| value.0))
+    }
+}
+
+/// The adapter of repeated fields, which applies the adapter `A` to each value.
+///
+/// JSON `null` is deserialized as an empty field.
+pub struct Repeated<A>(PhantomData<A>);
+
+with_functions!(Repeated<A>);
+
+impl<A, T, C> Adapter<C> for Repeated<A>
+where
+    A: Adapter<T>,
+    for<'a> &'a C: IntoIterator<Item = &'a T>,
+    C: Default + Extend<T>,
+{
+    fn serialize<S>(values: &C, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(None)?;
+        for value in values {
+            seq.serialize_element(&Adapted::<A, T>(value, PhantomData))?;
+        }
+        seq.end()
+    }
+
+    fn deserialize<'de, D>(deserializer: D) -> Result<C, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RepeatedVisitor<A, T, C>(PhantomData<(A, T, C)>);
+
+        impl<'de, A, T, C> Visitor<'de> for RepeatedVisitor<A, T, C>
+        where
+            A: Adapter<T>,
+            C: Default + Extend<T>,
+        {
+            type Value = C;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("an array")
+            }
+
+            fn visit_unit<E>(self) -> Result<C, E> {
+                Ok(C::default())
+            }
+
+            fn visit_seq<S>(self, mut seq: S) -> Result<C, S::Error>
+            where
+                S: SeqAccess<'de>,
+            {
+                let mut values = C::default();
+                while let Some(value) = seq.next_element::<Unadapted<A, T>>()? {
+                    values.extend(core::iter::once(value.0));
+                }
+                Ok(values)
+            }
+        }
+
+        deserializer.deserialize_any(RepeatedVisitor::<A, T, C>(PhantomData))
+    }
+}
+
+/// The adapter of map fields, which applies the adapter `A` to each map value.
+///
+/// JSON `null` is deserialized as an empty field.
+pub struct MapValues<A>(PhantomData<A>);
+
+with_functions!(MapValues<A>);
+
+impl<A, K, V, M> Adapter<M> for MapValues<A>
+where
+    A: Adapter<V>,
+    K: Serialize + DeserializeOwned,
+    for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+    M: Default + Extend<(K, V)>,
+{
+    fn serialize<S>(map: &M, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut entries = serializer.serialize_map(None)?;
+        for (key, value) in map {
+            entries.serialize_entry(key, &Adapted::<A, V>(value, PhantomData))?;
+        }
+        entries.end()
+    }
+
+    fn deserialize<'de, D>(deserializer: D) -> Result<M, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct MapVisitor<A, K, V, M>(PhantomData<(A, K, V, M)>);
+
+        impl<'de, A, K, V, M> Visitor<'de> for MapVisitor<A, K, V, M>
+        where
+            A: Adapter<V>,
+            K: DeserializeOwned,
+            M: Default + Extend<(K, V)>,
+        {
+            type Value = M;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("an object")
+            }
+
+            fn visit_unit<E>(self) -> Result<M, E> {
+                Ok(M::default())
+            }
+
+            fn visit_map<Access>(self, mut access: Access) -> Result<M, Access::Error>
+            where
+                Access: MapAccess<'de>,
+            {
+                let mut map = M::default();
+                while let Some((key, value)) = access.next_entry::<K, Unadapted<A, V>>()? {
+                    map.extend(core::iter::once((key, value.0)));
+                }
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_any(MapVisitor::<A, K, V, M>(PhantomData))
+    }
+}
+
+/// Returns `true` if a value is the default value of its type.
+///
+/// Generated messages skip serializing fields without presence which have their default value,
+/// like the Protobuf JSON mapping.
+pub fn is_default<T>(value: &T) -> bool
+where
+    T: Default + PartialEq,
+{
+    *value == T::default()
+}
+
+impl Serialize for JsonValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            JsonValue::Null => serializer.serialize_unit(),
+            JsonValue::Bool(value) => serializer.serialize_bool(*value),
+            // Numbers are kept in their textual form, which serde has no representation for.
+            JsonValue::Number(text) => {
+                if let Ok(value) = text.parse::<i64>() {
+                    serializer.serialize_i64(value)
+                } else if let Ok(value) = text.parse::<u64>() {
+                    serializer.serialize_u64(value)
+                } else {
+                    let value = text
+                        .parse::<f64>()
+                        .map_err(|_| ::serde::ser::Error::custom("invalid number"))?;
+                    serializer.serialize_f64(value)
+                }
+            }
+            JsonValue::String(value) => serializer.serialize_str(value),
+            JsonValue::Array(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            JsonValue::Object(members) => {
+                let mut map = serializer.serialize_map(Some(members.len()))?;
+                for (name, value) in members {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D>(deserializer: D) -> Result<JsonValue, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct JsonValueVisitor;
+
+        impl<'de> Visitor<'de> for JsonValueVisitor {
+            type Value = JsonValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a JSON value")
+            }
+
+            fn visit_unit<E>(self) -> Result<JsonValue, E> {
+                Ok(JsonValue::Null)
+            }
+
+            fn visit_none<E>(self) -> Result<JsonValue, E> {
+                Ok(JsonValue::Null)
+            }
+
+            fn visit_some<D>(self, deserializer: D) -> Result<JsonValue, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                JsonValue::deserialize(deserializer)
+            }
+
+            fn visit_bool<E>(self, value: bool) -> Result<JsonValue, E> {
+                Ok(JsonValue::Bool(value))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<JsonValue, E> {
+                Ok(JsonValue::Number(value.to_string()))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<JsonValue, E> {
+                Ok(JsonValue::Number(value.to_string()))
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<JsonValue, E> {
+                Ok(JsonValue::Number(value.to_string()))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<JsonValue, E> {
+                Ok(JsonValue::String(value.into()))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<JsonValue, E> {
+                Ok(JsonValue::String(value))
+            }
+
+            fn visit_seq<S>(self, mut seq: S) -> Result<JsonValue, S::Error>
+            where
+                S: SeqAccess<'de>,
+            {
+                let mut values = Vec::new();
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(JsonValue::Array(values))
+            }
+
+            fn visit_map<Access>(self, mut access: Access) -> Result<JsonValue, Access::Error>
+            where
+                Access: MapAccess<'de>,
+            {
+                let mut members = Vec::new();
+                while let Some(member) = access.next_entry()? {
+                    members.push(member);
+                }
+                Ok(JsonValue::Object(members))
+            }
+        }
+
+        deserializer.deserialize_any(JsonValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct Fields {
+        #[serde(with = "Scalar")]
+        int64: i64,
+        #[serde(with = "Optional::<Scalar>")]
+        bytes: Option<Vec<u8>>,
+        #[serde(with = "Repeated::<Scalar>")]
+        floats: Vec<f32>,
+    }
+
+    #[test]
+    fn scalars() {
+        let fields = Fields {
+            int64: -7,
+            bytes: Some(vec![0xfb, 0xff]),
+            floats: vec![1.5, f32::NEG_INFINITY],
+        };
+        let json = serde_json::to_string(&fields).unwrap();
+        assert_eq!(
+            json,
+            r#"{"int64":"-7","bytes":"+/8=","floats":[1.5,"-Infinity"]}"#
+        );
+        assert_eq!(serde_json::from_str::<Fields>(&json).unwrap(), fields);
+
+        let fields: Fields =
+            serde_json::from_str(r#"{"int64":3,"bytes":null,"floats":null}"#).unwrap();
+        assert_eq!(
+            fields,
+            Fields {
+                int64: 3,
+                ..Fields::default()
+            }
+        );
+        assert!(serde_json::from_str::<Fields>(r#"{"int64":"x"}"#).is_err());
+    }
+
+    #[test]
+    fn json_value() {
+        let json = r#"{"a":[null,true,-1,18446744073709551615,0.5,"s"],"b":{}}"#;
+        let value: JsonValue = serde_json::from_str(json).unwrap();
+        assert_eq!(value, json.parse::<JsonValue>().unwrap());
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+    }
+}
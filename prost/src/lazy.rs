@@ -0,0 +1,292 @@
+//! A message field which is decoded on first access.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use std::sync::OnceLock;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::encoding::{DecodeContext, WireType};
+use crate::{DecodeError, Message};
+
+/// A message which is kept encoded until it is first accessed.
+///
+/// `Lazy` is used for message fields configured with [`Config::lazy`][1]. Decoding the outer
+/// message only copies the bytes of such fields, or shares them if the outer message is decoded
+/// from a [`Bytes`] buffer, and the nested message is decoded by the first call to
+/// [`get`](Lazy::get) or [`get_mut`](Lazy::get_mut). A field which was not modified through
+/// `get_mut` is encoded by writing out its original bytes, without encoding the message again.
+///
+/// Errors in the encoded bytes of a lazy field are only reported when it is accessed.
+///
+/// [1]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.lazy
+#[derive(Clone)]
+pub struct Lazy<M> {
+    /// The encoded message, unless it was modified since it was decoded.
+    encoded: Option<Bytes>,
+    /// The decoded message, once it is accessed.
+    decoded: OnceLock<M>,
+}
+
+impl<M> Lazy<M> {
+    /// Creates a lazy field holding a decoded message.
+    pub fn new(message: M) -> Lazy<M> {
+        Lazy {
+            encoded: None,
+            decoded: OnceLock::from(message),
+        }
+    }
+
+    /// Creates a lazy field holding an encoded message, which is decoded on first access.
+    pub fn from_encoded(encoded: Bytes) -> Lazy<M> {
+        Lazy {
+            encoded: Some(encoded),
+            decoded: OnceLock::new(),
+        }
+    }
+
+    /// Returns the encoded message, unless it was modified since it was decoded.
+    pub fn encoded(&self) -> Option<&Bytes> {
+        self.encoded.as_ref()
+    }
+
+    /// Returns `true` if the message has been decoded.
+    pub fn is_decoded(&self) -> bool {
+        self.decoded.get().is_some()
+    }
+}
+
+impl<M> Lazy<M>
+where
+    M: Message + Default,
+{
+    /// Returns the message, decoding it if it was not accessed before.
+    pub fn get(&self) -> Result<&M, DecodeError> {
+        if let Some(message) = self.decoded.get() {
+            return Ok(message);
+        }
+        let message = M::decode(self.encoded.clone().unwrap_or_default())?;
+        Ok(self.decoded.get_or_init(|| message))
+    }
+
+    /// Returns the message for modification, decoding it if it was not accessed before.
+    ///
+    /// The field is encoded from the message afterwards, rather than from its original bytes.
+    pub fn get_mut(&mut self) -> Result<&mut M, DecodeError> {
+        if self.decoded.get().is_none() {
+            let message = M::decode(self.encoded.clone().unwrap_or_default())?;
+            self.decoded = OnceLock::from(message);
+        }
+        self.encoded = None;
+        Ok(self.decoded.get_mut().expect("message is decoded"))
+    }
+
+    /// Returns the message, decoding it if it was not accessed before.
+    pub fn into_inner(self) -> Result<M, DecodeError> {
+        match self.decoded.into_inner() {
+            Some(message) => Ok(message),
+            None => M::decode(self.encoded.unwrap_or_default()),
+        }
+    }
+
+    /// Merges an encoded message into the field.
+    ///
+    /// If the field holds unmodified encoded bytes, the bytes are appended to them, which is
+    /// equivalent to merging the decoded messages.
+    pub(crate) fn merge_encoded(&mut self, encoded: Bytes) -> Result<(), DecodeError> {
+        match self.encoded {
+            Some(ref mut existing) if existing.is_empty() => *existing = encoded,
+            Some(ref mut existing) => {
+                let mut merged = BytesMut::with_capacity(existing.len() + encoded.len());
+                merged.put_slice(existing);
+                merged.put_slice(&encoded);
+                *existing = merged.freeze();
+            }
+            None => return self.get_mut()?.merge(encoded),
+        }
+        self.decoded = OnceLock::new();
+        Ok(())
+    }
+}
+
+impl<M> Default for Lazy<M> {
+    fn default() -> Lazy<M> {
+        Lazy::from_encoded(Bytes::new())
+    }
+}
+
+impl<M> From<M> for Lazy<M> {
+    fn from(message: M) -> Lazy<M> {
+        Lazy::new(message)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, M> arbitrary::Arbitrary<'a> for Lazy<M>
+where
+    M: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        M::arbitrary(u).map(Lazy::new)
+    }
+}
+
+impl<M> Message for Lazy<M>
+where
+    M: Message + Default,
+{
+    fn encode_raw(&self, buf: &mut impl BufMut) {
+        match (&self.encoded, self.decoded.get()) {
+            (Some(encoded), _) => buf.put_slice(encoded),
+            (None, Some(message)) => message.encode_raw(buf),
+            (None, None) => unreachable!("lazy field holds neither bytes nor a message"),
+        }
+    }
+
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        self.get_mut()?.merge_field(tag, wire_type, buf, ctx)
+    }
+
+    fn encoded_len(&self) -> usize {
+        match (&self.encoded, self.decoded.get()) {
+            (Some(encoded), _) => encoded.len(),
+            (None, Some(message)) => message.encoded_len(),
+            (None, None) => unreachable!("lazy field holds neither bytes nor a message"),
+        }
+    }
+
+    /// Decodes a message from the buffer lazily: the buffer is only copied into the field.
+    fn merge(&mut self, mut buf: impl Buf) -> Result<(), DecodeError> {
+        let encoded = buf.copy_to_bytes(buf.remaining());
+        self.merge_encoded(encoded)
+    }
+
+    fn merge_from(&mut self, other: &Self) {
+        let encoded = match other.encoded {
+            Some(ref encoded) => encoded.clone(),
+            None => other.encode_to_vec().into(),
+        };
+        self.merge_encoded(encoded)
+            .expect("failed to merge an encoded message");
+    }
+
+    fn collect_missing_required_fields(&self, prefix: &str, missing: &mut Vec<String>) {
+        if let Ok(message) = self.get() {
+            message.collect_missing_required_fields(prefix, missing);
+        }
+    }
+
+    fn redact(&mut self) {
+        if let Ok(message) = self.get_mut() {
+            message.redact();
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = Lazy::default();
+    }
+}
+
+/// Lazy fields are equal if their encoded bytes are equal, or if their decoded messages are equal.
+impl<M> PartialEq for Lazy<M>
+where
+    M: Message + Default + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        if let (Some(encoded), Some(other_encoded)) = (&self.encoded, &other.encoded) {
+            if encoded == other_encoded {
+                return true;
+            }
+        }
+        match (self.get(), other.get()) {
+            (Ok(message), Ok(other_message)) => message == other_message,
+            _ => false,
+        }
+    }
+}
+
+impl<M> Eq for Lazy<M> where M: Message + Default + Eq {}
+
+impl<M> Hash for Lazy<M>
+where
+    M: Message + Default + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.get() {
+            Ok(message) => message.hash(state),
+            Err(_) => self.encoded.hash(state),
+        }
+    }
+}
+
+/// Formats the decoded message, or the encoded bytes if they cannot be decoded.
+impl<M> fmt::Debug for Lazy<M>
+where
+    M: Message + Default + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get() {
+            Ok(message) => message.fmt(f),
+            Err(_) => f.debug_tuple("Lazy").field(&self.encoded).finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn decodes_on_access() {
+        let encoded = "hello".to_string().encode_to_vec();
+        let lazy = Lazy::<String>::decode(encoded.as_slice()).unwrap();
+        assert!(!lazy.is_decoded());
+        assert_eq!(lazy.get().unwrap(), "hello");
+        assert!(lazy.is_decoded());
+        assert_eq!(lazy.encoded().unwrap(), &encoded[..]);
+        assert_eq!(lazy.into_inner().unwrap(), "hello");
+    }
+
+    #[test]
+    fn reencodes_when_modified() {
+        let mut lazy = Lazy::<String>::decode(&[0x0a, 0x01, b'a'][..]).unwrap();
+        assert_eq!(lazy.encode_to_vec(), [0x0a, 0x01, b'a']);
+
+        lazy.get_mut().unwrap().push('b');
+        assert!(lazy.encoded().is_none());
+        assert_eq!(lazy.encoded_len(), 4);
+        assert_eq!(lazy.encode_to_vec(), [0x0a, 0x02, b'a', b'b']);
+    }
+
+    #[test]
+    fn merges_encoded_bytes() {
+        let mut lazy = Lazy::<String>::decode(&[0x0a, 0x01, b'a'][..]).unwrap();
+        lazy.merge(&[0x0a, 0x01, b'b'][..]).unwrap();
+        assert_eq!(lazy.get().unwrap(), "b");
+
+        lazy.merge_from(&Lazy::new("c".to_string()));
+        assert_eq!(lazy, Lazy::new("c".to_string()));
+
+        lazy.clear();
+        assert_eq!(lazy.get().unwrap(), "");
+    }
+
+    #[test]
+    fn reports_errors_on_access() {
+        let mut lazy = Lazy::<String>::decode(&[0x0a, 0x05, b'a'][..]).unwrap();
+        assert!(lazy.get().is_err());
+        assert!(lazy.get_mut().is_err());
+        assert!(lazy.clone().into_inner().is_err());
+        assert_eq!(lazy.encode_to_vec(), [0x0a, 0x05, b'a']);
+    }
+}
@@ -0,0 +1,90 @@
+//! A side table of the encoded lengths of nested messages.
+
+use alloc::vec::Vec;
+
+/// The encoded lengths of the nested messages of a message, computed by
+/// [`Message::encoded_len_cached`](crate::Message::encoded_len_cached).
+///
+/// [`Message::encode`](crate::Message::encode) writes the length of each nested message before
+/// the message itself, and computes it by walking the nested message, so the fields of a message
+/// nested `n` levels deep are visited `n` times. [`Message::encode_cached`] instead records the
+/// lengths of all nested messages in a `LengthCache` in a single pass, and reads them back while
+/// encoding.
+///
+/// A cache can be reused for encoding several messages, which reuses its allocation.
+///
+/// [`Message::encode_cached`]: crate::Message::encode_cached
+#[derive(Clone, Debug, Default)]
+pub struct LengthCache {
+    /// The lengths of the nested messages, in the order in which they are encoded.
+    lengths: Vec<usize>,
+    /// The index of the length of the next nested message to encode.
+    next: usize,
+}
+
+impl LengthCache {
+    /// Creates an empty cache.
+    pub fn new() -> LengthCache {
+        LengthCache::default()
+    }
+
+    /// Removes all lengths from the cache, keeping its allocation.
+    pub fn clear(&mut self) {
+        self.lengths.clear();
+        self.next = 0;
+    }
+
+    /// Returns the number of cached lengths.
+    pub fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    /// Returns `true` if the cache holds no lengths.
+    pub fn is_empty(&self) -> bool {
+        self.lengths.is_empty()
+    }
+
+    /// Reserves the entry of a nested message, whose length is set once its own nested messages
+    /// have been recorded, and returns its index.
+    pub(crate) fn reserve(&mut self) -> usize {
+        self.lengths.push(0);
+        self.lengths.len() - 1
+    }
+
+    /// Sets the length of the nested message of a reserved entry.
+    pub(crate) fn set(&mut self, index: usize, len: usize) {
+        self.lengths[index] = len;
+    }
+
+    /// Returns the length of the next nested message to encode.
+    ///
+    /// Panics if the cache does not match the encoded message, which is only possible if a
+    /// `Message` implementation encodes its nested messages in another order than it computes
+    /// their lengths.
+    pub(crate) fn next_len(&mut self) -> usize {
+        let len = self.lengths[self.next];
+        self.next += 1;
+        len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reserve_and_read_back() {
+        let mut cache = LengthCache::new();
+        let outer = cache.reserve();
+        let inner = cache.reserve();
+        cache.set(inner, 3);
+        cache.set(outer, 5);
+        assert_eq!(cache.len(), 2);
+
+        assert_eq!(cache.next_len(), 5);
+        assert_eq!(cache.next_len(), 3);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
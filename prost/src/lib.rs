@@ -9,24 +9,98 @@ pub extern crate alloc;
 // Re-export the bytes crate for use within derived code.
 pub use bytes;
 
+// Re-export the arbitrary crate for use within derived `Arbitrary` implementations.
+#[cfg(feature = "arbitrary")]
+pub use arbitrary;
+
+// Re-export the bumpalo crate for use within generated arena messages.
+#[cfg(feature = "arena")]
+pub use bumpalo;
+
+// Re-export the heapless crate for use within generated heapless messages.
+#[cfg(feature = "heapless")]
+pub use heapless;
+
+// Re-export the protobuf crate, whose messages the interop module converts.
+#[cfg(feature = "rust-protobuf")]
+pub use rust_protobuf;
+
+// Re-export the serde crate for use within generated serde implementations.
+#[cfg(feature = "serde")]
+pub use serde;
+
+#[cfg(feature = "arena")]
+mod arena_message;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub mod compression;
+mod decode_options;
+#[cfg(feature = "derive")]
+pub mod derive;
+pub mod diff;
 mod error;
+mod extension;
+pub mod field_mask;
+#[cfg(feature = "rust-protobuf")]
+pub mod interop;
+#[cfg(any(feature = "futures", feature = "tokio"))]
+pub mod io;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "std")]
+mod lazy;
+mod length_cache;
 mod message;
+mod message_dyn;
+mod message_ref;
 mod name;
+pub mod observe;
+mod open_enum;
+#[cfg(feature = "std")]
+mod pool;
+pub mod proto_ord;
+pub mod service;
+#[cfg(feature = "std")]
+pub mod stream;
+mod streaming;
+#[cfg(feature = "text-format")]
+pub mod text_format;
+pub mod total_order;
+mod trace;
 mod types;
+mod unknown;
+mod utf8_bytes;
+#[cfg(feature = "validate")]
+pub mod validate;
+pub mod wire;
 
 #[doc(hidden)]
 pub mod encoding;
 
+#[cfg(feature = "arena")]
+pub use crate::arena_message::ArenaMessage;
+pub use crate::decode_options::{DecodeOptions, Utf8Policy};
 pub use crate::encoding::length_delimiter::{
     decode_length_delimiter, encode_length_delimiter, length_delimiter_len,
 };
-pub use crate::error::{DecodeError, EncodeError, UnknownEnumValue};
+pub use crate::encoding::{BytesAdapter, StringAdapter};
+pub use crate::error::{BuildError, DecodeError, EncodeError, UnknownEnumName, UnknownEnumValue};
+pub use crate::extension::{Extendable, Extension, ExtensionSet};
+#[cfg(feature = "std")]
+pub use crate::lazy::Lazy;
+pub use crate::length_cache::LengthCache;
 pub use crate::message::Message;
+pub use crate::message_dyn::MessageDyn;
+pub use crate::message_ref::MessageRef;
 pub use crate::name::Name;
+pub use crate::open_enum::OpenEnum;
+#[cfg(feature = "std")]
+pub use crate::pool::{MessagePool, Pooled};
+pub use crate::streaming::StreamingDecoder;
+pub use crate::unknown::{UnknownField, UnknownFieldSet, UnknownFieldValue};
+pub use crate::utf8_bytes::Utf8Bytes;
 
 // See `encoding::DecodeContext` for more info.
 // 100 is the default recursion limit in the C++ implementation.
-#[cfg(not(feature = "no-recursion-limit"))]
 const RECURSION_LIMIT: u32 = 100;
 
 // Re-export #[derive(Message, Enumeration, Oneof)].
@@ -38,5 +112,4 @@ const RECURSION_LIMIT: u32 = 100;
 #[macro_use]
 extern crate prost_derive;
 #[cfg(feature = "derive")]
-#[doc(hidden)]
 pub use prost_derive::*;
@@ -1,15 +1,23 @@
 #[cfg(not(feature = "std"))]
 use alloc::boxed::Box;
 #[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
+use core::mem::MaybeUninit;
+
 use bytes::{Buf, BufMut};
 
 use crate::encoding::varint::{encode_varint, encoded_len_varint};
 use crate::encoding::wire_type::WireType;
-use crate::encoding::{decode_key, message, DecodeContext};
+use crate::encoding::{decode_key, message, skip_field, DecodeContext};
+use crate::observe::{DecodeObserver, EncodeObserver, FieldEvent, ObservedBuf, Stopwatch};
+use crate::trace;
 use crate::DecodeError;
+use crate::DecodeOptions;
 use crate::EncodeError;
+use crate::LengthCache;
 
 /// A Protocol Buffers message.
 pub trait Message: Send + Sync {
@@ -40,6 +48,46 @@ pub trait Message: Send + Sync {
     /// Returns the encoded length of the message without a length delimiter.
     fn encoded_len(&self) -> usize;
 
+    /// Encodes the message to a buffer with the deterministic encoding of
+    /// [`encode_deterministic`](Message::encode_deterministic).
+    ///
+    /// This method will panic if the buffer has insufficient capacity.
+    ///
+    /// Meant to be used only by `Message` implementations.
+    #[doc(hidden)]
+    fn encode_raw_deterministic(&self, buf: &mut impl BufMut)
+    where
+        Self: Sized,
+    {
+        self.encode_raw(buf)
+    }
+
+    /// Returns the encoded length of the message without a length delimiter, and records the
+    /// encoded lengths of its nested messages in `cache`.
+    ///
+    /// `#[derive(Message)]` implements this for messages with message fields, so that the length
+    /// of each nested message is computed once. The default implementation returns
+    /// [`encoded_len`](Message::encoded_len) without recording any lengths.
+    fn encoded_len_cached(&self, cache: &mut LengthCache) -> usize {
+        let _ = cache;
+        self.encoded_len()
+    }
+
+    /// Encodes the message to a buffer, reading the lengths of its nested messages from `cache`,
+    /// as recorded by [`encoded_len_cached`](Message::encoded_len_cached).
+    ///
+    /// This method will panic if the buffer has insufficient capacity.
+    ///
+    /// Meant to be used only by `Message` implementations.
+    #[doc(hidden)]
+    fn encode_raw_cached(&self, buf: &mut impl BufMut, cache: &mut LengthCache)
+    where
+        Self: Sized,
+    {
+        let _ = cache;
+        self.encode_raw(buf)
+    }
+
     /// Encodes the message to a buffer.
     ///
     /// An error will be returned if the buffer does not have sufficient capacity.
@@ -53,21 +101,157 @@ pub trait Message: Send + Sync {
             return Err(EncodeError::new(required, remaining));
         }
 
-        self.encode_raw(buf);
+        trace::encode::<Self, _>(required, || self.encode_raw(buf));
+        Ok(())
+    }
+
+    /// Encodes the message to a buffer, computing the length of each nested message once.
+    ///
+    /// [`encode`](Message::encode) computes the length of a nested message whenever it is
+    /// encoded, including as part of the length of each enclosing message, which is quadratic in
+    /// the depth of deeply nested messages. This method instead records the lengths in `cache`
+    /// first, and clears the cache beforehand, so a cache can be reused across messages.
+    ///
+    /// An error will be returned if the buffer does not have sufficient capacity.
+    fn encode_cached(
+        &self,
+        buf: &mut impl BufMut,
+        cache: &mut LengthCache,
+    ) -> Result<(), EncodeError>
+    where
+        Self: Sized,
+    {
+        cache.clear();
+        let required = self.encoded_len_cached(cache);
+        let remaining = buf.remaining_mut();
+        if required > remaining {
+            return Err(EncodeError::new(required, remaining));
+        }
+
+        self.encode_raw_cached(buf, cache);
         Ok(())
     }
 
     /// Encodes the message to a newly allocated buffer.
     fn encode_to_vec(&self) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let len = self.encoded_len();
+        let mut buf = Vec::with_capacity(len);
+
+        trace::encode::<Self, _>(len, || self.encode_raw(&mut buf));
+        buf
+    }
+
+    /// Encodes the message to the beginning of a slice, and returns the number of bytes written.
+    ///
+    /// This allows encoding to pre-allocated memory, such as a DMA or shared-memory buffer,
+    /// without a [`BufMut`] which grows. An error will be returned if the slice is too short.
+    fn encode_to_slice(&self, buf: &mut [u8]) -> Result<usize, EncodeError>
+    where
+        Self: Sized,
+    {
+        let len = self.encoded_len();
+        if len > buf.len() {
+            return Err(EncodeError::new(len, buf.len()));
+        }
+
+        self.encode_raw(&mut &mut buf[..len]);
+        Ok(len)
+    }
+
+    /// Encodes the message to the beginning of a slice of uninitialized memory, and returns the
+    /// part of the slice which holds the encoded message.
+    ///
+    /// An error will be returned if the slice is too short.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message does not write as many bytes as its [`encoded_len`], which is only
+    /// possible with an incorrect `Message` implementation.
+    ///
+    /// [`encoded_len`]: Message::encoded_len
+    fn encode_to_uninit<'a>(
+        &self,
+        buf: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a mut [u8], EncodeError>
+    where
+        Self: Sized,
+    {
+        let len = self.encoded_len();
+        if len > buf.len() {
+            return Err(EncodeError::new(len, buf.len()));
+        }
+
+        let buf = &mut buf[..len];
+        let mut remaining = &mut *buf;
+        self.encode_raw(&mut remaining);
+        assert!(
+            remaining.is_empty(),
+            "the message wrote fewer bytes than its encoded length"
+        );
+        // SAFETY: `encode_raw` has written to all `len` bytes of `buf`, since the remaining part
+        // of the slice is empty, so they are initialized.
+        Ok(unsafe { &mut *(buf as *mut [MaybeUninit<u8>] as *mut [u8]) })
+    }
+
+    /// Encodes the message to a buffer deterministically.
+    ///
+    /// The entries of map fields are encoded in the order of their keys, and unknown fields and
+    /// extensions in the order of their field numbers, so that equal messages always have the
+    /// same encoding. The encoding is only deterministic for a given build of a message type: it
+    /// is not canonical across versions of a schema, or across Protobuf implementations. Lazy
+    /// fields which were not modified are encoded as they were decoded.
+    ///
+    /// An error will be returned if the buffer does not have sufficient capacity.
+    fn encode_deterministic(&self, buf: &mut impl BufMut) -> Result<(), EncodeError>
+    where
+        Self: Sized,
+    {
+        let required = self.encoded_len();
+        let remaining = buf.remaining_mut();
+        if required > remaining {
+            return Err(EncodeError::new(required, remaining));
+        }
+
+        self.encode_raw_deterministic(buf);
+        Ok(())
+    }
+
+    /// Encodes the message deterministically to a newly allocated buffer.
+    ///
+    /// See [`encode_deterministic`](Message::encode_deterministic) for the guarantees of the
+    /// deterministic encoding.
+    fn encode_to_vec_deterministic(&self) -> Vec<u8>
     where
         Self: Sized,
     {
         let mut buf = Vec::with_capacity(self.encoded_len());
 
-        self.encode_raw(&mut buf);
+        self.encode_raw_deterministic(&mut buf);
         buf
     }
 
+    /// Returns the digest of the deterministic encoding of the message, computed with the hash
+    /// function `H`.
+    ///
+    /// Equal messages have equal digests, so the digest can be used as a content address or a
+    /// cache key. See [`encode_deterministic`](Message::encode_deterministic) for the guarantees
+    /// of the deterministic encoding.
+    ///
+    /// ```rust,ignore
+    /// let key = message.canonical_digest::<sha2::Sha256>();
+    /// ```
+    #[cfg(feature = "digest")]
+    fn canonical_digest<H>(&self) -> digest::Output<H>
+    where
+        H: digest::Digest,
+        Self: Sized,
+    {
+        H::digest(self.encode_to_vec_deterministic())
+    }
+
     /// Encodes the message with a length-delimiter to a buffer.
     ///
     /// An error will be returned if the buffer does not have sufficient capacity.
@@ -82,7 +266,7 @@ pub trait Message: Send + Sync {
             return Err(EncodeError::new(required, remaining));
         }
         encode_varint(len as u64, buf);
-        self.encode_raw(buf);
+        trace::encode::<Self, _>(len, || self.encode_raw(buf));
         Ok(())
     }
 
@@ -95,10 +279,55 @@ pub trait Message: Send + Sync {
         let mut buf = Vec::with_capacity(len + encoded_len_varint(len as u64));
 
         encode_varint(len as u64, &mut buf);
-        self.encode_raw(&mut buf);
+        trace::encode::<Self, _>(len, || self.encode_raw(&mut buf));
         buf
     }
 
+    /// Encodes the message to a buffer in a frame compatible with the message framing of gRPC,
+    /// compressed with `compression`.
+    ///
+    /// The frame is a one-byte compressed flag, the length of the message as a four-byte
+    /// big-endian integer, and the message. See the [`compression`](crate::compression) module.
+    ///
+    /// An error will be returned if the buffer does not have sufficient capacity.
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    fn encode_length_delimited_compressed(
+        &self,
+        compression: crate::compression::Compression,
+        buf: &mut impl BufMut,
+    ) -> Result<(), EncodeError>
+    where
+        Self: Sized,
+    {
+        crate::compression::encode_frame(self.encode_to_vec(), compression, buf)
+    }
+
+    /// Encodes the message to a buffer, and reports every field of the message to `observer`.
+    ///
+    /// Each field is reported once it is written to `buf`, with the time spent on it. See the
+    /// [`observe`](crate::observe) module.
+    ///
+    /// An error will be returned if the buffer does not have sufficient capacity.
+    fn encode_observed(
+        &self,
+        buf: &mut impl BufMut,
+        observer: &mut impl EncodeObserver,
+    ) -> Result<(), EncodeError>
+    where
+        Self: Sized,
+    {
+        let required = self.encoded_len();
+        let remaining = buf.remaining_mut();
+        if required > remaining {
+            return Err(EncodeError::new(required, remaining));
+        }
+
+        let stopwatch = Stopwatch::start();
+        self.encode_raw(&mut ObservedBuf::new(buf, observer));
+        observer.message_encoded(required, stopwatch.elapsed());
+        Ok(())
+    }
+
     /// Decodes an instance of the message from a buffer.
     ///
     /// The entire buffer will be consumed.
@@ -110,6 +339,50 @@ pub trait Message: Send + Sync {
         Self::merge(&mut message, &mut buf).map(|_| message)
     }
 
+    /// Decodes an instance of the message from a buffer, with the limits of `options`.
+    ///
+    /// The entire buffer will be consumed.
+    fn decode_with_options(mut buf: impl Buf, options: &DecodeOptions) -> Result<Self, DecodeError>
+    where
+        Self: Default,
+    {
+        let mut message = Self::default();
+        Self::merge_with_options(&mut message, &mut buf, options).map(|_| message)
+    }
+
+    /// Decodes the fields with the field numbers in `fields` from a buffer, and skips all other
+    /// fields.
+    ///
+    /// The fields which are not selected are skipped without being decoded, which makes it cheap
+    /// to read a few fields of a large message. They are left at their default values, and are
+    /// not preserved as unknown fields. A field of a oneof is selected by its own field number.
+    ///
+    /// The entire buffer will be consumed.
+    fn decode_fields(mut buf: impl Buf, fields: &[u32]) -> Result<Self, DecodeError>
+    where
+        Self: Default,
+    {
+        let mut message = Self::default();
+        Self::merge_fields(&mut message, &mut buf, fields).map(|_| message)
+    }
+
+    /// Decodes an instance of the message from a buffer, and reports every field of the message
+    /// to `observer`.
+    ///
+    /// See the [`observe`](crate::observe) module.
+    ///
+    /// The entire buffer will be consumed.
+    fn decode_observed(
+        mut buf: impl Buf,
+        observer: &mut impl DecodeObserver,
+    ) -> Result<Self, DecodeError>
+    where
+        Self: Default,
+    {
+        let mut message = Self::default();
+        Self::merge_observed(&mut message, &mut buf, observer).map(|_| message)
+    }
+
     /// Decodes a length-delimited instance of the message from the buffer.
     fn decode_length_delimited(buf: impl Buf) -> Result<Self, DecodeError>
     where
@@ -120,39 +393,323 @@ pub trait Message: Send + Sync {
         Ok(message)
     }
 
+    /// Decodes a length-delimited instance of the message from the buffer, with the limits of
+    /// `options`.
+    fn decode_length_delimited_with_options(
+        buf: impl Buf,
+        options: &DecodeOptions,
+    ) -> Result<Self, DecodeError>
+    where
+        Self: Default,
+    {
+        let mut message = Self::default();
+        message.merge_length_delimited_with_options(buf, options)?;
+        Ok(message)
+    }
+
+    /// Decodes an instance of the message from a frame compatible with the message framing of
+    /// gRPC, which is decompressed with `compression` if its compressed flag is set.
+    ///
+    /// The message is limited to
+    /// [`DEFAULT_MAX_MESSAGE_SIZE`](crate::compression::DEFAULT_MAX_MESSAGE_SIZE) bytes once
+    /// decompressed. See the [`compression`](crate::compression) module.
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    fn decode_length_delimited_compressed(
+        buf: impl Buf,
+        compression: crate::compression::Compression,
+    ) -> Result<Self, DecodeError>
+    where
+        Self: Default,
+    {
+        let options =
+            DecodeOptions::new().max_message_size(crate::compression::DEFAULT_MAX_MESSAGE_SIZE);
+        Self::decode_length_delimited_compressed_with_options(buf, compression, &options)
+    }
+
+    /// Decodes an instance of the message from a frame compatible with the message framing of
+    /// gRPC, like [`decode_length_delimited_compressed`], applying the limits of `options`.
+    ///
+    /// The message is limited to the [`max_message_size`](DecodeOptions::max_message_size) of
+    /// `options` once decompressed, and decompression stops as soon as the limit is exceeded.
+    /// Frames from untrusted sources should be decoded with a limit.
+    ///
+    /// [`decode_length_delimited_compressed`]: Message::decode_length_delimited_compressed
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    fn decode_length_delimited_compressed_with_options(
+        buf: impl Buf,
+        compression: crate::compression::Compression,
+        options: &DecodeOptions,
+    ) -> Result<Self, DecodeError>
+    where
+        Self: Default,
+    {
+        let encoded = crate::compression::decode_frame(buf, compression, options)?;
+        Self::decode_with_options(encoded.as_slice(), options)
+    }
+
+    /// Decodes an instance of the message from a buffer into `self`, replacing its fields.
+    ///
+    /// `self` is [cleared](Message::clear) before the message is merged into it, so the result
+    /// is the same as assigning the result of [`decode`](Message::decode), except that the
+    /// allocations of the fields of `self` are reused. If decoding fails, `self` holds the fields
+    /// which were decoded before the error.
+    ///
+    /// The entire buffer will be consumed.
+    fn decode_into(&mut self, buf: impl Buf) -> Result<(), DecodeError>
+    where
+        Self: Sized,
+    {
+        self.clear();
+        self.merge(buf)
+    }
+
+    /// Decodes a length-delimited instance of the message from the buffer into `self`,
+    /// replacing its fields.
+    ///
+    /// See [`decode_into`](Message::decode_into).
+    fn decode_length_delimited_into(&mut self, buf: impl Buf) -> Result<(), DecodeError>
+    where
+        Self: Sized,
+    {
+        self.clear();
+        self.merge_length_delimited(buf)
+    }
+
     /// Decodes an instance of the message from a buffer, and merges it into `self`.
     ///
+    /// The fields of `self` are kept, and the decoded fields are merged into them as if the
+    /// encoding of `self` were followed by the buffer: singular scalar fields which are present
+    /// in the buffer overwrite those of `self`, singular message fields are merged recursively,
+    /// and the elements of repeated fields and the entries of maps are added, replacing the
+    /// entries with the same keys. A oneof field replaces a different field of the same oneof.
+    /// Use [`decode_into`](Message::decode_into) to replace the fields of `self` instead.
+    ///
     /// The entire buffer will be consumed.
     fn merge(&mut self, mut buf: impl Buf) -> Result<(), DecodeError>
     where
         Self: Sized,
     {
         let ctx = DecodeContext::default();
-        while buf.has_remaining() {
-            let (tag, wire_type) = decode_key(&mut buf)?;
-            self.merge_field(tag, wire_type, &mut buf, ctx.clone())?;
-        }
+        trace::decode::<Self, _>(buf.remaining(), || {
+            merge_at_offset(&mut buf, |buf| {
+                while buf.has_remaining() {
+                    let (tag, wire_type) = decode_key(buf)?;
+                    self.merge_field(tag, wire_type, buf, ctx.clone())?;
+                }
+                Ok(())
+            })
+        })
+    }
+
+    /// Decodes an instance of the message from a buffer, with the limits of `options`, and
+    /// merges it into `self`.
+    ///
+    /// The entire buffer will be consumed.
+    fn merge_with_options(
+        &mut self,
+        mut buf: impl Buf,
+        options: &DecodeOptions,
+    ) -> Result<(), DecodeError>
+    where
+        Self: Sized,
+    {
+        let ctx = DecodeContext::new(options);
+        trace::decode::<Self, _>(buf.remaining(), || {
+            ctx.check_message_size(buf.remaining() as u64)?;
+            merge_at_offset(&mut buf, |buf| {
+                while buf.has_remaining() {
+                    let (tag, wire_type) = decode_key(buf)?;
+                    self.merge_field(tag, wire_type, buf, ctx.clone())?;
+                }
+                Ok(())
+            })
+        })
+    }
+
+    /// Decodes the fields with the field numbers in `fields` from a buffer, merges them into
+    /// `self`, and skips all other fields.
+    ///
+    /// See [`decode_fields`](Message::decode_fields) for how the other fields are handled.
+    ///
+    /// The entire buffer will be consumed.
+    fn merge_fields(&mut self, mut buf: impl Buf, fields: &[u32]) -> Result<(), DecodeError>
+    where
+        Self: Sized,
+    {
+        let ctx = DecodeContext::default();
+        merge_at_offset(&mut buf, |buf| {
+            while buf.has_remaining() {
+                let (tag, wire_type) = decode_key(buf)?;
+                if fields.contains(&tag) {
+                    self.merge_field(tag, wire_type, buf, ctx.clone())?;
+                } else {
+                    skip_field(wire_type, tag, buf, ctx.clone())?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Decodes an instance of the message from a buffer, merges it into `self`, and reports
+    /// every field of the message to `observer`.
+    ///
+    /// See the [`observe`](crate::observe) module.
+    ///
+    /// The entire buffer will be consumed.
+    fn merge_observed(
+        &mut self,
+        mut buf: impl Buf,
+        observer: &mut impl DecodeObserver,
+    ) -> Result<(), DecodeError>
+    where
+        Self: Sized,
+    {
+        let ctx = DecodeContext::default();
+        let len = buf.remaining();
+        let stopwatch = Stopwatch::start();
+        merge_at_offset(&mut buf, |buf| {
+            while buf.has_remaining() {
+                let start = buf.remaining();
+                let field = Stopwatch::start();
+                let (tag, wire_type) = decode_key(buf)?;
+                self.merge_field(tag, wire_type, buf, ctx.clone())?;
+                observer.field_decoded(&FieldEvent {
+                    number: tag,
+                    wire_type,
+                    len: start - buf.remaining(),
+                    elapsed: field.elapsed(),
+                });
+            }
+            Ok(())
+        })?;
+        observer.message_decoded(len, stopwatch.elapsed());
         Ok(())
     }
 
     /// Decodes a length-delimited instance of the message from buffer, and
     /// merges it into `self`.
+    ///
+    /// The fields of `self` are kept, as with [`merge`](Message::merge). Use
+    /// [`decode_length_delimited_into`](Message::decode_length_delimited_into) to replace them
+    /// instead.
     fn merge_length_delimited(&mut self, mut buf: impl Buf) -> Result<(), DecodeError>
     where
         Self: Sized,
     {
-        message::merge(
-            WireType::LengthDelimited,
-            self,
-            &mut buf,
-            DecodeContext::default(),
-        )
+        trace::decode::<Self, _>(buf.remaining(), || {
+            merge_at_offset(&mut buf, |buf| {
+                message::merge(
+                    WireType::LengthDelimited,
+                    self,
+                    buf,
+                    DecodeContext::default(),
+                )
+            })
+        })
+    }
+
+    /// Decodes a length-delimited instance of the message from buffer, with the limits of
+    /// `options`, and merges it into `self`.
+    fn merge_length_delimited_with_options(
+        &mut self,
+        mut buf: impl Buf,
+        options: &DecodeOptions,
+    ) -> Result<(), DecodeError>
+    where
+        Self: Sized,
+    {
+        trace::decode::<Self, _>(buf.remaining(), || {
+            merge_at_offset(&mut buf, |buf| {
+                message::merge_top_level(self, buf, DecodeContext::new(options))
+            })
+        })
     }
 
+    /// Merges the fields of `other` into `self`, following the Protobuf merge semantics.
+    ///
+    /// Singular scalar fields which are set in `other` overwrite those of `self`, singular message
+    /// fields are merged recursively, and the elements of repeated fields are appended. Map
+    /// entries of `other` replace the entries of `self` with the same key. This is equivalent to
+    /// decoding the encoded `other` into `self` with [`Message::merge`].
+    ///
+    /// `#[derive(Message)]` implements this field by field. The default implementation encodes
+    /// `other` and merges it into `self`, and panics if decoding fails, which is only possible if
+    /// `other` is nested deeper than the recursion limit.
+    fn merge_from(&mut self, other: &Self)
+    where
+        Self: Sized,
+    {
+        let buf = other.encode_to_vec();
+        self.merge(buf.as_slice())
+            .expect("failed to merge an encoded message");
+    }
+
+    /// Returns `true` if all required fields of the message and of its nested messages are set.
+    fn is_initialized(&self) -> bool {
+        self.missing_required_fields().is_empty()
+    }
+
+    /// Returns the paths of the required fields of the message and of its nested messages which
+    /// are not set, such as `name`, `options.id` or `items[0].id`.
+    ///
+    /// Only required fields whose presence is tracked, i.e. `#[prost(required, presence)]` fields,
+    /// can be missing. Other required fields always hold a value.
+    fn missing_required_fields(&self) -> Vec<String> {
+        let mut missing = Vec::new();
+        self.collect_missing_required_fields("", &mut missing);
+        missing
+    }
+
+    /// Returns an error listing the missing required fields if the message is not initialized.
+    ///
+    /// Decoding does not check required fields, so this should be called after decoding a message
+    /// whose required fields must be set.
+    fn check_initialized(&self) -> Result<(), DecodeError> {
+        let missing = self.missing_required_fields();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(DecodeError::new_missing_required_fields(missing))
+        }
+    }
+
+    /// Appends the paths of the missing required fields to `missing`, prefixing them with
+    /// `prefix`.
+    ///
+    /// Meant to be used only by `Message` implementations.
+    #[doc(hidden)]
+    fn collect_missing_required_fields(&self, prefix: &str, missing: &mut Vec<String>) {
+        let _ = (prefix, missing);
+    }
+
+    /// Clears the sensitive fields of the message and of its nested messages, so that the message
+    /// can be logged or stored without them.
+    ///
+    /// Sensitive fields are annotated with `#[prost(redact)]`, which `prost-build` adds to fields
+    /// with the `debug_redact` option. The `Debug` implementation of `#[derive(Message)]` prints
+    /// them as `<redacted>`. The default implementation does nothing.
+    fn redact(&mut self) {}
+
     /// Clears the message, resetting all fields to their default.
+    ///
+    /// The derived implementation keeps the capacity of strings, byte strings, repeated fields
+    /// and maps, so that a message which is cleared and merged into again reuses their
+    /// allocations. Singular message and oneof fields are reset to `None`. See [`MessagePool`]
+    /// for reusing messages across decodes.
+    ///
+    /// [`MessagePool`]: crate::MessagePool
     fn clear(&mut self);
 }
 
+/// Runs `merge` on `buf`, and records the number of bytes it consumed in the error if it fails.
+fn merge_at_offset<B: Buf>(
+    buf: &mut B,
+    merge: impl FnOnce(&mut B) -> Result<(), DecodeError>,
+) -> Result<(), DecodeError> {
+    let len = buf.remaining();
+    merge(buf).map_err(|error| error.at_offset(len - buf.remaining()))
+}
+
 impl<M> Message for Box<M>
 where
     M: Message,
@@ -172,14 +729,132 @@ where
     fn encoded_len(&self) -> usize {
         (**self).encoded_len()
     }
+    fn encoded_len_cached(&self, cache: &mut LengthCache) -> usize {
+        (**self).encoded_len_cached(cache)
+    }
+    fn encode_raw_cached(&self, buf: &mut impl BufMut, cache: &mut LengthCache) {
+        (**self).encode_raw_cached(buf, cache)
+    }
+    fn encode_raw_deterministic(&self, buf: &mut impl BufMut) {
+        (**self).encode_raw_deterministic(buf)
+    }
+    fn merge_from(&mut self, other: &Self) {
+        (**self).merge_from(other)
+    }
+    fn collect_missing_required_fields(&self, prefix: &str, missing: &mut Vec<String>) {
+        (**self).collect_missing_required_fields(prefix, missing)
+    }
+    fn redact(&mut self) {
+        (**self).redact()
+    }
     fn clear(&mut self) {
         (**self).clear()
     }
 }
 
+/// A shared message is copied before it is modified, if it is not the only reference to the
+/// message, so decoding into a shared message never changes the other references.
+#[cfg(target_has_atomic = "ptr")]
+impl<M> Message for alloc::sync::Arc<M>
+where
+    M: Message + Clone,
+{
+    fn encode_raw(&self, buf: &mut impl BufMut) {
+        (**self).encode_raw(buf)
+    }
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        alloc::sync::Arc::make_mut(self).merge_field(tag, wire_type, buf, ctx)
+    }
+    fn encoded_len(&self) -> usize {
+        (**self).encoded_len()
+    }
+    fn encoded_len_cached(&self, cache: &mut LengthCache) -> usize {
+        (**self).encoded_len_cached(cache)
+    }
+    fn encode_raw_cached(&self, buf: &mut impl BufMut, cache: &mut LengthCache) {
+        (**self).encode_raw_cached(buf, cache)
+    }
+    fn encode_raw_deterministic(&self, buf: &mut impl BufMut) {
+        (**self).encode_raw_deterministic(buf)
+    }
+    fn merge_from(&mut self, other: &Self) {
+        alloc::sync::Arc::make_mut(self).merge_from(other)
+    }
+    fn collect_missing_required_fields(&self, prefix: &str, missing: &mut Vec<String>) {
+        (**self).collect_missing_required_fields(prefix, missing)
+    }
+    fn redact(&mut self) {
+        alloc::sync::Arc::make_mut(self).redact()
+    }
+    fn clear(&mut self) {
+        alloc::sync::Arc::make_mut(self).clear()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     const _MESSAGE_IS_OBJECT_SAFE: Option<&dyn Message> = None;
+
+    #[test]
+    fn encode_to_slice() {
+        let message = String::from("hello");
+        let mut buf = [0xff; 10];
+        assert_eq!(message.encode_to_slice(&mut buf), Ok(7));
+        assert_eq!(&buf[..7], message.encode_to_vec().as_slice());
+        assert_eq!(buf[7..], [0xff; 3]);
+
+        let error = message.encode_to_slice(&mut buf[..6]).unwrap_err();
+        assert_eq!(error.required_capacity(), 7);
+        assert_eq!(error.remaining(), 6);
+    }
+
+    #[test]
+    fn decode_into_replaces_fields() {
+        let mut message = String::with_capacity(64);
+        message.push_str("hello");
+        message.merge(&[][..]).unwrap();
+        assert_eq!(message, "hello");
+
+        message.decode_into(&[][..]).unwrap();
+        assert_eq!(message, "");
+        assert_eq!(message.capacity(), 64);
+
+        let encoded = String::from("world").encode_length_delimited_to_vec();
+        message.push_str("hello");
+        message
+            .decode_length_delimited_into(encoded.as_slice())
+            .unwrap();
+        assert_eq!(message, "world");
+        assert_eq!(message.capacity(), 64);
+    }
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    fn shared_message_is_copied_on_merge() {
+        let encoded = String::from("world").encode_to_vec();
+        let shared = alloc::sync::Arc::new(String::from("hello"));
+        let mut message = shared.clone();
+        message.merge(encoded.as_slice()).unwrap();
+        assert_eq!(*shared, "hello");
+        assert_eq!(*message, "world");
+        assert_eq!(message.encode_to_vec(), encoded);
+    }
+
+    #[test]
+    fn encode_to_uninit() {
+        let message = String::from("hello");
+        let mut buf = [MaybeUninit::uninit(); 10];
+        let encoded = message.encode_to_uninit(&mut buf).unwrap();
+        assert_eq!(encoded, message.encode_to_vec().as_slice());
+
+        assert!(message.encode_to_uninit(&mut buf[..6]).is_err());
+    }
 }
@@ -0,0 +1,169 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut};
+
+use crate::encoding::wire_type::WireType;
+use crate::encoding::DecodeContext;
+use crate::{DecodeError, EncodeError, Message};
+
+/// A Protocol Buffers message which can be encoded and decoded as a trait object.
+///
+/// The encoding and decoding methods of [`Message`] are generic over the buffer, so they are not
+/// available on `dyn Message`. `MessageDyn` provides them with `dyn Buf` and `dyn BufMut`
+/// buffers instead, and is implemented for every message, so that heterogeneous collections of
+/// messages, such as the messages of a plugin system, can be encoded and decoded as
+/// `Box<dyn MessageDyn>` without knowing their types. The object-safe methods of [`Message`],
+/// such as [`encoded_len`](Message::encoded_len) and [`clear`](Message::clear), are also
+/// available on `dyn MessageDyn`.
+///
+/// The methods are suffixed with `_dyn`, so that they do not conflict with the methods of
+/// [`Message`] on message types.
+///
+/// ```
+/// use prost::MessageDyn;
+///
+/// let messages: Vec<Box<dyn MessageDyn>> = vec![Box::new(7_u64), Box::new(String::from("seven"))];
+/// let mut encoded = Vec::new();
+/// for message in &messages {
+///     message.encode_length_delimited_dyn(&mut encoded).unwrap();
+/// }
+///
+/// let mut decoded: Vec<Box<dyn MessageDyn>> = vec![Box::new(0_u64), Box::new(String::new())];
+/// let mut buf = encoded.as_slice();
+/// for message in &mut decoded {
+///     message.merge_length_delimited_dyn(&mut buf).unwrap();
+/// }
+/// assert_eq!(decoded[1].encode_to_vec_dyn(), messages[1].encode_to_vec_dyn());
+/// ```
+pub trait MessageDyn: Message {
+    /// Decodes a field from a buffer, and merges it into `self`.
+    ///
+    /// Meant to be used only by `MessageDyn` implementations.
+    #[doc(hidden)]
+    fn merge_field_dyn(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut dyn Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>;
+
+    /// Encodes the message to a buffer, like [`Message::encode`].
+    ///
+    /// An error will be returned if the buffer does not have sufficient capacity.
+    fn encode_dyn(&self, buf: &mut dyn BufMut) -> Result<(), EncodeError>;
+
+    /// Encodes the message to a newly allocated buffer, like [`Message::encode_to_vec`].
+    fn encode_to_vec_dyn(&self) -> Vec<u8>;
+
+    /// Encodes the message with a length-delimiter to a buffer, like
+    /// [`Message::encode_length_delimited`].
+    ///
+    /// An error will be returned if the buffer does not have sufficient capacity.
+    fn encode_length_delimited_dyn(&self, buf: &mut dyn BufMut) -> Result<(), EncodeError>;
+
+    /// Decodes an instance of the message from a buffer, and merges it into `self`, like
+    /// [`Message::merge`].
+    ///
+    /// The entire buffer will be consumed.
+    fn merge_dyn(&mut self, buf: &mut dyn Buf) -> Result<(), DecodeError>;
+
+    /// Decodes a length-delimited instance of the message from a buffer, and merges it into
+    /// `self`, like [`Message::merge_length_delimited`].
+    fn merge_length_delimited_dyn(&mut self, buf: &mut dyn Buf) -> Result<(), DecodeError>;
+}
+
+impl<M> MessageDyn for M
+where
+    M: Message,
+{
+    fn merge_field_dyn(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        mut buf: &mut dyn Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        self.merge_field(tag, wire_type, &mut buf, ctx)
+    }
+
+    fn encode_dyn(&self, mut buf: &mut dyn BufMut) -> Result<(), EncodeError> {
+        self.encode(&mut buf)
+    }
+
+    fn encode_to_vec_dyn(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+
+    fn encode_length_delimited_dyn(&self, mut buf: &mut dyn BufMut) -> Result<(), EncodeError> {
+        self.encode_length_delimited(&mut buf)
+    }
+
+    fn merge_dyn(&mut self, buf: &mut dyn Buf) -> Result<(), DecodeError> {
+        self.merge(buf)
+    }
+
+    fn merge_length_delimited_dyn(&mut self, buf: &mut dyn Buf) -> Result<(), DecodeError> {
+        self.merge_length_delimited(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::boxed::Box;
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn encode_dyn() {
+        let messages: Vec<Box<dyn MessageDyn>> = vec![
+            Box::new(String::from("hello")),
+            Box::new(42_i32),
+            Box::new(()),
+        ];
+
+        for message in &messages {
+            let mut buf = Vec::new();
+            message.encode_dyn(&mut buf).unwrap();
+            assert_eq!(buf, message.encode_to_vec_dyn());
+            assert_eq!(buf.len(), message.encoded_len());
+        }
+
+        let mut buf = [0; 3];
+        let error = messages[0].encode_dyn(&mut &mut buf[..]).unwrap_err();
+        assert_eq!(error.required_capacity(), 7);
+    }
+
+    #[test]
+    fn merge_dyn() {
+        let mut buf = Vec::new();
+        String::from("hello")
+            .encode_length_delimited(&mut buf)
+            .unwrap();
+        42_i32.encode_length_delimited(&mut buf).unwrap();
+
+        let mut messages: Vec<Box<dyn MessageDyn>> = vec![Box::new(String::new()), Box::new(0_i32)];
+        let mut slice = buf.as_slice();
+        for message in &mut messages {
+            message.merge_length_delimited_dyn(&mut slice).unwrap();
+        }
+        assert!(slice.is_empty());
+        assert_eq!(
+            messages[0].encode_to_vec_dyn(),
+            String::from("hello").encode_to_vec()
+        );
+        assert_eq!(messages[1].encode_to_vec_dyn(), 42_i32.encode_to_vec());
+
+        messages[1].clear();
+        messages[1]
+            .merge_dyn(&mut &*42_i32.encode_to_vec())
+            .unwrap();
+        assert_eq!(messages[1].encode_to_vec_dyn(), 42_i32.encode_to_vec());
+    }
+}
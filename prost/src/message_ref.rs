@@ -0,0 +1,98 @@
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::encoding::wire_type::WireType;
+use crate::encoding::{decode_key, DecodeContext};
+use crate::{DecodeError, Message};
+
+/// A borrowed view of a Protocol Buffers message.
+///
+/// Views are generated by `prost-build` for the messages selected with
+/// [`Config::message_refs`][1]. The `string` and `bytes` fields of a view are `&str` and `&[u8]`
+/// slices of the buffer the view was decoded from, so decoding a view does not copy them. A view
+/// is converted into its owned message with [`MessageRef::to_owned_message`].
+///
+/// [1]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.message_refs
+pub trait MessageRef<'a>: Default + Sized {
+    /// The owned message type.
+    type Owned: Message;
+
+    /// Decodes a field from a buffer, and merges it into `self`.
+    ///
+    /// Meant to be used only by `MessageRef` implementations.
+    #[doc(hidden)]
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError>;
+
+    /// Copies the view into a new owned message.
+    fn to_owned_message(&self) -> Self::Owned;
+
+    /// Decodes a view of a message from a buffer.
+    ///
+    /// The entire buffer will be consumed.
+    fn decode(buf: &'a [u8]) -> Result<Self, DecodeError> {
+        let mut message = Self::default();
+        message.merge(buf).map(|_| message)
+    }
+
+    /// Decodes a view of a message from a buffer, and merges it into `self`.
+    ///
+    /// The entire buffer will be consumed.
+    fn merge(&mut self, mut buf: &'a [u8]) -> Result<(), DecodeError> {
+        let ctx = DecodeContext::default();
+        while !buf.is_empty() {
+            let (tag, wire_type) = decode_key(&mut buf)?;
+            self.merge_field(tag, wire_type, &mut buf, ctx.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, M> MessageRef<'a> for Box<M>
+where
+    M: MessageRef<'a>,
+{
+    type Owned = Box<M::Owned>;
+
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        (**self).merge_field(tag, wire_type, buf, ctx)
+    }
+
+    fn to_owned_message(&self) -> Box<M::Owned> {
+        Box::new((**self).to_owned_message())
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<'a, M> MessageRef<'a> for alloc::sync::Arc<M>
+where
+    M: MessageRef<'a> + Clone,
+    M::Owned: Clone,
+{
+    type Owned = alloc::sync::Arc<M::Owned>;
+
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut &'a [u8],
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        alloc::sync::Arc::make_mut(self).merge_field(tag, wire_type, buf, ctx)
+    }
+
+    fn to_owned_message(&self) -> alloc::sync::Arc<M::Owned> {
+        alloc::sync::Arc::new((**self).to_owned_message())
+    }
+}
@@ -0,0 +1,490 @@
+//! Instrumentation of message encoding and decoding.
+//!
+//! An [`EncodeObserver`] or [`DecodeObserver`] is passed to a single call of
+//! [`Message::encode_observed`] or [`Message::decode_observed`], and is notified of every field of
+//! the message with its size and, with the `std` feature, the time spent on it. [`FieldStats`]
+//! implements both traits and totals the events per field number, which is enough to find the
+//! fields which dominate the payload size; other implementations can forward the events to a
+//! metrics system.
+//!
+//! ```
+//! use prost::observe::FieldStats;
+//! use prost::Message;
+//!
+//! #[derive(Clone, PartialEq, Message)]
+//! struct Log {
+//!     #[prost(string, tag = "1")]
+//!     host: String,
+//!     #[prost(bytes = "vec", repeated, tag = "2")]
+//!     lines: Vec<Vec<u8>>,
+//! }
+//!
+//! let log = Log {
+//!     host: "example.com".to_string(),
+//!     lines: vec![vec![0; 100], vec![0; 200]],
+//! };
+//!
+//! let mut stats = FieldStats::default();
+//! let mut buf = Vec::new();
+//! log.encode_observed(&mut buf, &mut stats).unwrap();
+//!
+//! let lines = stats.get(2).unwrap();
+//! assert_eq!(lines.count, 2);
+//! assert_eq!(lines.bytes, 305);
+//! assert_eq!(stats.largest(), Some(2));
+//! ```
+//!
+//! Only the fields of the observed message are reported, not the fields of its nested messages.
+//!
+//! [`Message::encode_observed`]: crate::Message::encode_observed
+//! [`Message::decode_observed`]: crate::Message::decode_observed
+
+use alloc::collections::BTreeMap;
+use core::cmp::min;
+use core::mem;
+use core::time::Duration;
+
+use bytes::buf::UninitSlice;
+use bytes::BufMut;
+
+pub use crate::encoding::WireType;
+
+/// A field which was encoded or decoded.
+///
+/// A repeated field which is not packed is reported once per element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldEvent {
+    /// The field number.
+    pub number: u32,
+    /// The wire type of the field.
+    pub wire_type: WireType,
+    /// The number of bytes of the field, including its key.
+    pub len: usize,
+    /// The time spent on the field, if it was measured.
+    ///
+    /// Fields are measured with the `std` feature. The time of an encoded field is the time since
+    /// the previous field was written, so it includes computing the lengths of nested messages.
+    pub elapsed: Option<Duration>,
+}
+
+/// Observes the fields written by [`Message::encode_observed`](crate::Message::encode_observed).
+pub trait EncodeObserver {
+    /// Called for every field of the encoded message, in the order of the encoding.
+    fn field_encoded(&mut self, field: &FieldEvent);
+
+    /// Called once the message is encoded, with its length and, with the `std` feature, the time
+    /// spent on encoding it.
+    fn message_encoded(&mut self, len: usize, elapsed: Option<Duration>) {
+        let _ = (len, elapsed);
+    }
+}
+
+/// Observes the fields read by [`Message::decode_observed`](crate::Message::decode_observed).
+pub trait DecodeObserver {
+    /// Called for every field of the decoded message, in the order of the input.
+    fn field_decoded(&mut self, field: &FieldEvent);
+
+    /// Called once the message is decoded, with its length and, with the `std` feature, the time
+    /// spent on decoding it.
+    fn message_decoded(&mut self, len: usize, elapsed: Option<Duration>) {
+        let _ = (len, elapsed);
+    }
+}
+
+impl<O: EncodeObserver + ?Sized> EncodeObserver for &mut O {
+    fn field_encoded(&mut self, field: &FieldEvent) {
+        (**self).field_encoded(field)
+    }
+
+    fn message_encoded(&mut self, len: usize, elapsed: Option<Duration>) {
+        (**self).message_encoded(len, elapsed)
+    }
+}
+
+impl<O: DecodeObserver + ?Sized> DecodeObserver for &mut O {
+    fn field_decoded(&mut self, field: &FieldEvent) {
+        (**self).field_decoded(field)
+    }
+
+    fn message_decoded(&mut self, len: usize, elapsed: Option<Duration>) {
+        (**self).message_decoded(len, elapsed)
+    }
+}
+
+/// The totals of a field number in [`FieldStats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FieldTotals {
+    /// The number of times the field occurred.
+    pub count: usize,
+    /// The number of bytes of all occurrences of the field, including their keys.
+    pub bytes: usize,
+    /// The measured time spent on all occurrences of the field.
+    pub elapsed: Duration,
+}
+
+/// An observer which totals the counts, sizes and times of the fields per field number.
+///
+/// The same `FieldStats` can observe any number of calls, to aggregate the fields of many
+/// messages of a type.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldStats {
+    fields: BTreeMap<u32, FieldTotals>,
+    messages: usize,
+    bytes: usize,
+}
+
+impl FieldStats {
+    /// Returns the totals of the field with the field number `number`, if it occurred.
+    pub fn get(&self, number: u32) -> Option<&FieldTotals> {
+        self.fields.get(&number)
+    }
+
+    /// Returns an iterator over the field numbers and the totals of the fields which occurred,
+    /// in field number order.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &FieldTotals)> + '_ {
+        self.fields.iter().map(|(&number, totals)| (number, totals))
+    }
+
+    /// Returns the field number of the field with the most bytes.
+    pub fn largest(&self) -> Option<u32> {
+        self.iter()
+            .max_by_key(|(_, totals)| totals.bytes)
+            .map(|(number, _)| number)
+    }
+
+    /// Returns the number of observed messages.
+    pub fn messages(&self) -> usize {
+        self.messages
+    }
+
+    /// Returns the number of bytes of the observed messages.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    fn add(&mut self, field: &FieldEvent) {
+        let totals = self.fields.entry(field.number).or_default();
+        totals.count += 1;
+        totals.bytes += field.len;
+        totals.elapsed += field.elapsed.unwrap_or_default();
+    }
+}
+
+impl EncodeObserver for FieldStats {
+    fn field_encoded(&mut self, field: &FieldEvent) {
+        self.add(field);
+    }
+
+    fn message_encoded(&mut self, len: usize, _elapsed: Option<Duration>) {
+        self.messages += 1;
+        self.bytes += len;
+    }
+}
+
+impl DecodeObserver for FieldStats {
+    fn field_decoded(&mut self, field: &FieldEvent) {
+        self.add(field);
+    }
+
+    fn message_decoded(&mut self, len: usize, _elapsed: Option<Duration>) {
+        self.messages += 1;
+        self.bytes += len;
+    }
+}
+
+/// Measures elapsed time with the `std` feature, and nothing without it.
+pub(crate) struct Stopwatch {
+    #[cfg(feature = "std")]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    pub(crate) fn start() -> Stopwatch {
+        Stopwatch {
+            #[cfg(feature = "std")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn elapsed(&self) -> Option<Duration> {
+        Some(self.start.elapsed())
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn elapsed(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// A buffer which writes to `buf` and reports every top-level field written to it to
+/// `observer`, once the last byte of the field is written.
+pub(crate) struct ObservedBuf<'a, B, O> {
+    buf: &'a mut B,
+    observer: &'a mut O,
+    /// The chunk returned by `chunk_mut`, which is copied to `buf` by `advance_mut`.
+    scratch: [u8; 16],
+    state: State,
+    varint: u64,
+    shift: u32,
+    /// The depth of the groups of the current field.
+    depth: usize,
+    number: u32,
+    wire_type: WireType,
+    len: usize,
+    stopwatch: Stopwatch,
+}
+
+/// The part of a field which the next written byte belongs to.
+#[derive(Clone, Copy)]
+enum State {
+    Key,
+    Varint,
+    Len,
+    Bytes(usize),
+}
+
+impl<'a, B: BufMut, O: EncodeObserver> ObservedBuf<'a, B, O> {
+    pub(crate) fn new(buf: &'a mut B, observer: &'a mut O) -> Self {
+        ObservedBuf {
+            buf,
+            observer,
+            scratch: [0; 16],
+            state: State::Key,
+            varint: 0,
+            shift: 0,
+            depth: 0,
+            number: 0,
+            wire_type: WireType::Varint,
+            len: 0,
+            stopwatch: Stopwatch::start(),
+        }
+    }
+
+    fn observe(&mut self, mut src: &[u8]) {
+        while let Some(&byte) = src.first() {
+            if let State::Bytes(remaining) = self.state {
+                let cnt = min(remaining, src.len());
+                self.len += cnt;
+                src = &src[cnt..];
+                if cnt == remaining {
+                    self.value_written();
+                } else {
+                    self.state = State::Bytes(remaining - cnt);
+                }
+                continue;
+            }
+
+            self.len += 1;
+            src = &src[1..];
+            if self.shift < 64 {
+                self.varint |= u64::from(byte & 0x7F) << self.shift;
+                self.shift += 7;
+            }
+            if byte < 0x80 {
+                let value = mem::take(&mut self.varint);
+                self.shift = 0;
+                self.varint_written(value);
+            }
+        }
+    }
+
+    fn varint_written(&mut self, value: u64) {
+        match self.state {
+            State::Key => {
+                let Ok(wire_type) = WireType::try_from(value & 0x07) else {
+                    return self.value_written();
+                };
+                if self.depth == 0 {
+                    self.number = (value >> 3) as u32;
+                    self.wire_type = wire_type;
+                }
+                self.state = match wire_type {
+                    WireType::Varint => State::Varint,
+                    WireType::SixtyFourBit => State::Bytes(8),
+                    WireType::ThirtyTwoBit => State::Bytes(4),
+                    WireType::LengthDelimited => State::Len,
+                    WireType::StartGroup => {
+                        self.depth += 1;
+                        State::Key
+                    }
+                    WireType::EndGroup => {
+                        self.depth = self.depth.saturating_sub(1);
+                        return self.value_written();
+                    }
+                };
+            }
+            State::Len if value > 0 => self.state = State::Bytes(value as usize),
+            State::Varint | State::Len | State::Bytes(_) => self.value_written(),
+        }
+    }
+
+    fn value_written(&mut self) {
+        self.state = State::Key;
+        if self.depth > 0 {
+            return;
+        }
+        self.observer.field_encoded(&FieldEvent {
+            number: self.number,
+            wire_type: self.wire_type,
+            len: mem::take(&mut self.len),
+            elapsed: self.stopwatch.elapsed(),
+        });
+        self.stopwatch = Stopwatch::start();
+    }
+}
+
+// SAFETY: the chunk is the initialized `scratch`, and `advance_mut` writes the first `cnt` bytes
+// of it to `buf`, which checks its own capacity.
+unsafe impl<B: BufMut, O: EncodeObserver> BufMut for ObservedBuf<'_, B, O> {
+    fn remaining_mut(&self) -> usize {
+        self.buf.remaining_mut()
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        let scratch = self.scratch;
+        self.put_slice(&scratch[..cnt]);
+    }
+
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        let len = min(self.scratch.len(), self.buf.remaining_mut());
+        UninitSlice::new(&mut self.scratch[..len])
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        self.buf.put_slice(src);
+        self.observe(src);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{encode_key, encode_varint};
+    use crate::Message;
+    use alloc::vec::Vec;
+
+    #[derive(Default)]
+    struct Events(Vec<(u32, usize)>);
+
+    impl EncodeObserver for Events {
+        fn field_encoded(&mut self, field: &FieldEvent) {
+            self.0.push((field.number, field.len));
+        }
+    }
+
+    impl DecodeObserver for Events {
+        fn field_decoded(&mut self, field: &FieldEvent) {
+            self.0.push((field.number, field.len));
+        }
+    }
+
+    fn encoded() -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_key(1, WireType::Varint, &mut buf);
+        encode_varint(300, &mut buf);
+        encode_key(2, WireType::LengthDelimited, &mut buf);
+        encode_varint(3, &mut buf);
+        buf.extend_from_slice(b"abc");
+        encode_key(2, WireType::LengthDelimited, &mut buf);
+        encode_varint(0, &mut buf);
+        buf
+    }
+
+    #[test]
+    fn observe_encode_and_decode() {
+        let message = crate::UnknownFieldSet::decode(encoded().as_slice()).unwrap();
+
+        let mut encode = Events::default();
+        let mut buf = Vec::new();
+        message.encode_observed(&mut buf, &mut encode).unwrap();
+        assert_eq!(buf, encoded());
+        assert_eq!(encode.0, [(1, 3), (2, 5), (2, 2)]);
+
+        let mut decode = Events::default();
+        let decoded = crate::UnknownFieldSet::decode_observed(buf.as_slice(), &mut decode).unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(decode.0, encode.0);
+    }
+
+    #[test]
+    fn observe_fixed_and_group_fields() {
+        let mut buf = Vec::new();
+        encode_key(3, WireType::ThirtyTwoBit, &mut buf);
+        buf.extend_from_slice(&[0x80; 4]);
+        encode_key(4, WireType::SixtyFourBit, &mut buf);
+        buf.extend_from_slice(&[0x80; 8]);
+        encode_key(5, WireType::StartGroup, &mut buf);
+        encode_key(1, WireType::Varint, &mut buf);
+        encode_varint(u64::MAX, &mut buf);
+        encode_key(2, WireType::StartGroup, &mut buf);
+        encode_key(2, WireType::EndGroup, &mut buf);
+        encode_key(5, WireType::EndGroup, &mut buf);
+        let message = crate::UnknownFieldSet::decode(buf.as_slice()).unwrap();
+
+        let mut encode = Events::default();
+        let mut encoded = Vec::new();
+        message.encode_observed(&mut encoded, &mut encode).unwrap();
+        assert_eq!(encoded, buf);
+        assert_eq!(encode.0, [(3, 5), (4, 9), (5, 15)]);
+    }
+
+    #[test]
+    fn observe_chunk_writes() {
+        // `BufMut::put` writes through `chunk_mut` rather than `put_slice`.
+        let mut events = Events::default();
+        let mut buf = Vec::new();
+        ObservedBuf::new(&mut buf, &mut events).put(encoded().as_slice());
+        assert_eq!(buf, encoded());
+        assert_eq!(events.0, [(1, 3), (2, 5), (2, 2)]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn encoded_fields_are_timed() {
+        struct Elapsed(Vec<Option<Duration>>);
+
+        impl EncodeObserver for Elapsed {
+            fn field_encoded(&mut self, field: &FieldEvent) {
+                self.0.push(field.elapsed);
+            }
+        }
+
+        let message = crate::UnknownFieldSet::decode(encoded().as_slice()).unwrap();
+        let mut elapsed = Elapsed(Vec::new());
+        message
+            .encode_observed(&mut Vec::new(), &mut elapsed)
+            .unwrap();
+        assert_eq!(elapsed.0.len(), 3);
+        assert!(elapsed.0.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn encode_observed_insufficient_capacity() {
+        let message = crate::UnknownFieldSet::decode(encoded().as_slice()).unwrap();
+        let mut events = Events::default();
+        let mut buf = [0; 4];
+        assert!(message
+            .encode_observed(&mut &mut buf[..], &mut events)
+            .is_err());
+        assert!(events.0.is_empty());
+    }
+
+    #[test]
+    fn field_stats() {
+        let message = crate::UnknownFieldSet::decode(encoded().as_slice()).unwrap();
+
+        let mut stats = FieldStats::default();
+        let mut buf = Vec::new();
+        message.encode_observed(&mut buf, &mut stats).unwrap();
+        crate::UnknownFieldSet::decode_observed(encoded().as_slice(), &mut stats).unwrap();
+
+        assert_eq!(stats.messages(), 2);
+        assert_eq!(stats.bytes(), 20);
+        assert_eq!(stats.get(1).unwrap().count, 2);
+        assert_eq!(stats.get(2).unwrap().count, 4);
+        assert_eq!(stats.get(2).unwrap().bytes, 14);
+        assert_eq!(stats.get(3), None);
+        assert_eq!(stats.largest(), Some(2));
+    }
+}
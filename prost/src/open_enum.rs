@@ -0,0 +1,154 @@
+//! An enumeration field which preserves unknown values.
+
+/// The value of an enumeration field, which is either a variant of the enumeration `E` or an
+/// unknown value.
+///
+/// `OpenEnum` is used for enumeration fields configured with [`Config::open_enums`][1], instead
+/// of the `i32` of other enumeration fields. Matching on it is type-safe, while values which are
+/// not variants of `E`, such as variants added by a newer version of the schema, are kept as
+/// [`Unknown`](OpenEnum::Unknown) and encoded again unchanged.
+///
+/// `OpenEnum` converts from `E`, and from and into `i32`.
+///
+/// [1]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.open_enums
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OpenEnum<E> {
+    /// A variant of `E`.
+    Known(E),
+    /// A value which is not a variant of `E`.
+    Unknown(i32),
+}
+
+impl<E> OpenEnum<E> {
+    /// Returns the variant of `E`, or `None` if the value is unknown.
+    pub fn known(self) -> Option<E> {
+        match self {
+            OpenEnum::Known(value) => Some(value),
+            OpenEnum::Unknown(_) => None,
+        }
+    }
+
+    /// Returns `true` if the value is a variant of `E`.
+    pub fn is_known(&self) -> bool {
+        matches!(self, OpenEnum::Known(_))
+    }
+}
+
+impl<E> Default for OpenEnum<E>
+where
+    E: Default,
+{
+    fn default() -> OpenEnum<E> {
+        OpenEnum::Known(E::default())
+    }
+}
+
+impl<E> From<i32> for OpenEnum<E>
+where
+    E: TryFrom<i32>,
+{
+    fn from(value: i32) -> OpenEnum<E> {
+        match E::try_from(value) {
+            Ok(value) => OpenEnum::Known(value),
+            Err(_) => OpenEnum::Unknown(value),
+        }
+    }
+}
+
+impl<E> From<OpenEnum<E>> for i32
+where
+    E: Into<i32>,
+{
+    fn from(value: OpenEnum<E>) -> i32 {
+        match value {
+            OpenEnum::Known(value) => value.into(),
+            OpenEnum::Unknown(value) => value,
+        }
+    }
+}
+
+// `From<E>` is implemented for each enumeration by `#[derive(Enumeration)]`, since a blanket
+// implementation would overlap with `From<i32>`.
+
+#[cfg(feature = "serde")]
+impl<E> serde::Serialize for OpenEnum<E>
+where
+    E: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            OpenEnum::Known(value) => value.serialize(serializer),
+            OpenEnum::Unknown(value) => serializer.serialize_i32(*value),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, E> serde::Deserialize<'de> for OpenEnum<E>
+where
+    E: serde::Serialize + serde::de::DeserializeOwned + TryFrom<i32> + Into<i32>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<OpenEnum<E>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        crate::json::serde::Enumeration::<E>::deserialize(deserializer).map(OpenEnum::from)
+    }
+}
+
+/// Arbitrary open enums are variants of `E`, like the values of arbitrary closed enums.
+#[cfg(feature = "arbitrary")]
+impl<'a, E> arbitrary::Arbitrary<'a> for OpenEnum<E>
+where
+    E: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        E::arbitrary(u).map(OpenEnum::Known)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    enum Color {
+        #[default]
+        Red = 0,
+        Green = 1,
+    }
+
+    impl TryFrom<i32> for Color {
+        type Error = ();
+
+        fn try_from(value: i32) -> Result<Color, ()> {
+            match value {
+                0 => Ok(Color::Red),
+                1 => Ok(Color::Green),
+                _ => Err(()),
+            }
+        }
+    }
+
+    impl From<Color> for i32 {
+        fn from(value: Color) -> i32 {
+            value as i32
+        }
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(OpenEnum::<Color>::default(), OpenEnum::Known(Color::Red));
+        assert_eq!(OpenEnum::<Color>::from(1), OpenEnum::Known(Color::Green));
+        assert_eq!(OpenEnum::<Color>::from(7), OpenEnum::Unknown(7));
+        assert_eq!(i32::from(OpenEnum::Known(Color::Green)), 1);
+        assert_eq!(i32::from(OpenEnum::<Color>::Unknown(7)), 7);
+
+        assert_eq!(OpenEnum::Known(Color::Green).known(), Some(Color::Green));
+        assert_eq!(OpenEnum::<Color>::Unknown(7).known(), None);
+        assert!(!OpenEnum::<Color>::Unknown(7).is_known());
+    }
+}
@@ -0,0 +1,216 @@
+//! A pool of messages which are reused across decodes.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use bytes::Buf;
+
+use crate::{DecodeError, Message};
+
+/// A pool of cleared messages, which keep the capacity of their strings, byte strings, repeated
+/// fields and maps.
+///
+/// A server decoding many messages of the same type can decode them into pooled messages, so
+/// that their fields reuse the allocations of previous messages instead of allocating on every
+/// request. [`decode`](MessagePool::decode) takes a message from the pool and merges the
+/// encoded message into it, and the returned [`Pooled`] message goes back to the pool when it is
+/// dropped.
+///
+/// Messages are cleared with [`Message::clear`] when they return to the pool. Singular message
+/// and oneof fields are reset to `None`, so only the allocations held directly by the fields
+/// of a message are reused.
+///
+/// ```rust
+/// use prost::{Message, MessagePool};
+///
+/// // `Vec<u8>` is the message type of `google.protobuf.BytesValue`.
+/// let pool = MessagePool::<Vec<u8>>::new(16);
+/// let encoded = b"hello".to_vec().encode_to_vec();
+///
+/// let message = pool.decode(encoded.as_slice()).unwrap();
+/// assert_eq!(*message, b"hello");
+/// drop(message);
+///
+/// // The next message reuses the allocation of the previous one.
+/// let message = pool.get();
+/// assert!(message.is_empty());
+/// assert!(message.capacity() >= 5);
+/// ```
+pub struct MessagePool<M> {
+    messages: Mutex<Vec<M>>,
+    capacity: usize,
+}
+
+impl<M: Message + Default> MessagePool<M> {
+    /// Creates an empty pool, which keeps at most `capacity` unused messages.
+    pub fn new(capacity: usize) -> MessagePool<M> {
+        MessagePool {
+            messages: Mutex::new(Vec::new()),
+            capacity,
+        }
+    }
+
+    /// Takes a cleared message from the pool, or creates a default message if the pool is
+    /// empty.
+    pub fn get(&self) -> Pooled<'_, M> {
+        let message = self.lock().pop().unwrap_or_default();
+        Pooled {
+            message: ManuallyDrop::new(message),
+            pool: self,
+        }
+    }
+
+    /// Decodes a message from `buf` into a message from the pool.
+    ///
+    /// If decoding fails, the message goes back to the pool.
+    pub fn decode(&self, buf: impl Buf) -> Result<Pooled<'_, M>, DecodeError> {
+        let mut message = self.get();
+        message.merge(buf)?;
+        Ok(message)
+    }
+
+    /// Decodes a length-delimited message from `buf` into a message from the pool.
+    ///
+    /// If decoding fails, the message goes back to the pool.
+    pub fn decode_length_delimited(&self, buf: impl Buf) -> Result<Pooled<'_, M>, DecodeError> {
+        let mut message = self.get();
+        message.merge_length_delimited(buf)?;
+        Ok(message)
+    }
+
+    /// Clears `message` and adds it to the pool, unless the pool is full.
+    pub fn put(&self, mut message: M) {
+        message.clear();
+        let mut messages = self.lock();
+        if messages.len() < self.capacity {
+            messages.push(message);
+        }
+    }
+
+    /// Returns the number of unused messages in the pool.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if the pool holds no unused messages.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns the maximum number of unused messages the pool keeps.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<M>> {
+        // The messages are only pushed and popped, so they are consistent after a panic.
+        self.messages.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<M> fmt::Debug for MessagePool<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let len = self
+            .messages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len();
+        f.debug_struct("MessagePool")
+            .field("len", &len)
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+/// A message taken from a [`MessagePool`], which goes back to the pool when it is dropped.
+pub struct Pooled<'a, M: Message + Default> {
+    message: ManuallyDrop<M>,
+    pool: &'a MessagePool<M>,
+}
+
+impl<M: Message + Default> Pooled<'_, M> {
+    /// Takes the message out of the pool for good.
+    pub fn into_inner(self) -> M {
+        let mut this = ManuallyDrop::new(self);
+        // SAFETY: `this` is not dropped, so the message is taken out exactly once.
+        unsafe { ManuallyDrop::take(&mut this.message) }
+    }
+}
+
+impl<M: Message + Default> Deref for Pooled<'_, M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        &self.message
+    }
+}
+
+impl<M: Message + Default> DerefMut for Pooled<'_, M> {
+    fn deref_mut(&mut self) -> &mut M {
+        &mut self.message
+    }
+}
+
+impl<M: Message + Default> Drop for Pooled<'_, M> {
+    fn drop(&mut self) {
+        // SAFETY: the message is not accessed after it is taken out.
+        let message = unsafe { ManuallyDrop::take(&mut self.message) };
+        self.pool.put(message);
+    }
+}
+
+impl<M: Message + Default + fmt::Debug> fmt::Debug for Pooled<'_, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+
+    #[test]
+    fn messages_are_cleared_and_reused() {
+        let pool = MessagePool::<String>::new(1);
+        let mut message = pool.get();
+        message.push_str("a long enough message");
+        let capacity = message.capacity();
+        drop(message);
+        assert_eq!(pool.len(), 1);
+
+        let message = pool.get();
+        assert!(message.is_empty());
+        assert_eq!(message.capacity(), capacity);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn pool_keeps_at_most_capacity_messages() {
+        let pool = MessagePool::<String>::new(1);
+        let first = pool.get();
+        let second = pool.get();
+        drop(first);
+        drop(second);
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn detached_messages_do_not_return() {
+        let pool = MessagePool::<String>::new(1);
+        let message = pool.decode(&b"\x0a\x02hi"[..]).unwrap().into_inner();
+        assert_eq!(message, "hi");
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn failed_decodes_return_the_message() {
+        let pool = MessagePool::<String>::new(1);
+        pool.decode(&b"\x0a\x05hi"[..]).unwrap_err();
+        assert_eq!(pool.len(), 1);
+    }
+}
@@ -0,0 +1,362 @@
+//! Support for ordering messages by their Protobuf fields.
+//!
+//! [`ProtoOrd`] orders messages by field number, then by value, so that sorted containers and
+//! deduplication of messages agree with any runtime which orders messages by the same rules,
+//! rather than depending on the declaration order of the Rust fields. It is implemented by
+//! `#[derive(Message)]` for messages annotated with `#[prost(proto_cmp)]`, which `prost-build`
+//! generates with `Config::proto_cmp`.
+//!
+//! Two messages are compared field by field, in ascending order of field number, and the first
+//! field which differs decides the order:
+//!
+//! - Scalars are compared by value: integers numerically, `false` before `true`, and floats by
+//!   [`f64::total_cmp`], so `-0.0` is less than `0.0` and `NaN` equals itself. Enumeration
+//!   fields are compared by their numeric value.
+//! - Strings and bytes are compared byte by byte, a prefix being less than the longer value.
+//! - A field with presence which is unset is less than one which is set. Fields without presence
+//!   are compared by value, so an unset field equals one set to its default.
+//! - Each field of a oneof is compared at its own field number, as a field with presence which
+//!   is set only if it is the field of the oneof which is set.
+//! - Repeated fields are compared element by element, a prefix being less than the longer field.
+//! - Map fields are compared as repeated fields of their entries sorted by key, each entry
+//!   being compared by key, then by value.
+//! - Extensions, then unknown fields, are compared after the known fields. They are sorted by
+//!   field number, keeping the order of fields with the same number, then compared by field
+//!   number, wire type and value, groups being compared like unknown fields.
+//!
+//! ```rust
+//! use prost::proto_ord::ProtoOrd;
+//! use prost::Message;
+//!
+//! #[derive(Clone, PartialEq, Message)]
+//! #[prost(proto_cmp)]
+//! struct Version {
+//!     #[prost(uint32, tag = "2")]
+//!     minor: u32,
+//!     #[prost(uint32, tag = "1")]
+//!     major: u32,
+//! }
+//!
+//! let mut versions = vec![
+//!     Version { major: 2, minor: 0 },
+//!     Version { major: 1, minor: 3 },
+//!     Version { major: 1, minor: 3 },
+//! ];
+//! versions.sort_by(ProtoOrd::proto_cmp);
+//! versions.dedup_by(|a, b| a.proto_cmp(b).is_eq());
+//! assert_eq!(versions, [Version { major: 1, minor: 3 }, Version { major: 2, minor: 0 }]);
+//! ```
+
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::{ExtensionSet, UnknownField, UnknownFieldSet, UnknownFieldValue};
+
+/// The order of values by their Protobuf fields, described in the [module](self) documentation.
+///
+/// This is implemented for the Rust types of the Protobuf field types, and by
+/// `#[derive(Message)]` for messages annotated with `#[prost(proto_cmp)]`. Messages with fields
+/// of other types, such as custom string or bytes types, may implement it for these types.
+pub trait ProtoOrd {
+    /// Compares the values in the Protobuf order.
+    fn proto_cmp(&self, other: &Self) -> Ordering;
+}
+
+macro_rules! impl_ord {
+    ($($ty:ty),* $(,)?) => {
+        $(impl ProtoOrd for $ty {
+            fn proto_cmp(&self, other: &Self) -> Ordering {
+                Ord::cmp(self, other)
+            }
+        })*
+    };
+}
+
+impl_ord!(bool, u8, i32, i64, u32, u64, str, String);
+
+impl ProtoOrd for f32 {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        self.total_cmp(other)
+    }
+}
+
+impl ProtoOrd for f64 {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        self.total_cmp(other)
+    }
+}
+
+impl ProtoOrd for bytes::Bytes {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        self[..].proto_cmp(&other[..])
+    }
+}
+
+impl ProtoOrd for crate::Utf8Bytes {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        (**self).proto_cmp(&**other)
+    }
+}
+
+/// Enumeration values are compared by their numeric value, whether they are known or not.
+impl<E> ProtoOrd for crate::OpenEnum<E>
+where
+    E: Copy + Into<i32>,
+{
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        let value = |value: &Self| match *value {
+            crate::OpenEnum::Known(value) => value.into(),
+            crate::OpenEnum::Unknown(value) => value,
+        };
+        value(self).cmp(&value(other))
+    }
+}
+
+/// An unset value is less than a set one.
+impl<T: ProtoOrd> ProtoOrd for Option<T> {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Some(a), Some(b)) => a.proto_cmp(b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        }
+    }
+}
+
+impl<T: ProtoOrd> ProtoOrd for [T] {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        self.iter()
+            .zip(other)
+            .map(|(a, b)| a.proto_cmp(b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| self.len().cmp(&other.len()))
+    }
+}
+
+impl<T: ProtoOrd> ProtoOrd for Vec<T> {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        self[..].proto_cmp(&other[..])
+    }
+}
+
+impl<T: ProtoOrd + ?Sized> ProtoOrd for &T {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        (**self).proto_cmp(&**other)
+    }
+}
+
+impl<T: ProtoOrd + ?Sized> ProtoOrd for Box<T> {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        (**self).proto_cmp(&**other)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T: ProtoOrd + ?Sized> ProtoOrd for alloc::sync::Arc<T> {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        (**self).proto_cmp(&**other)
+    }
+}
+
+impl<T: ProtoOrd + ToOwned + ?Sized> ProtoOrd for Cow<'_, T> {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        (**self).proto_cmp(&**other)
+    }
+}
+
+/// The entries are iterated in the order of the keys, which is the Protobuf order for the key
+/// types of map fields.
+impl<K: ProtoOrd, V: ProtoOrd> ProtoOrd for BTreeMap<K, V> {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        cmp_entries(self.iter(), other.iter())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: ProtoOrd, V: ProtoOrd, S> ProtoOrd for std::collections::HashMap<K, V, S> {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        let mut entries = self.iter().collect::<Vec<_>>();
+        let mut other = other.iter().collect::<Vec<_>>();
+        entries.sort_by(|(a, _), (b, _)| a.proto_cmp(b));
+        other.sort_by(|(a, _), (b, _)| a.proto_cmp(b));
+        cmp_entries(entries, other)
+    }
+}
+
+/// Compares the entries of two maps, sorted by key, by key, then by value.
+fn cmp_entries<'a, K, V>(
+    entries: impl IntoIterator<Item = (&'a K, &'a V)>,
+    other: impl IntoIterator<Item = (&'a K, &'a V)>,
+) -> Ordering
+where
+    K: ProtoOrd + 'a,
+    V: ProtoOrd + 'a,
+{
+    let mut entries = entries.into_iter();
+    let mut other = other.into_iter();
+    loop {
+        match (entries.next(), other.next()) {
+            (Some((k1, v1)), Some((k2, v2))) => {
+                let ordering = k1.proto_cmp(k2).then_with(|| v1.proto_cmp(v2));
+                if ordering.is_ne() {
+                    return ordering;
+                }
+            }
+            (a, b) => return a.is_some().cmp(&b.is_some()),
+        }
+    }
+}
+
+/// The fields are sorted by field number, keeping the order of fields with the same number.
+impl ProtoOrd for UnknownFieldSet {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        let mut fields = self.iter().collect::<Vec<_>>();
+        let mut other = other.iter().collect::<Vec<_>>();
+        fields.sort_by_key(|field| field.tag);
+        other.sort_by_key(|field| field.tag);
+        fields.proto_cmp(&other)
+    }
+}
+
+impl ProtoOrd for UnknownField {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        self.tag
+            .cmp(&other.tag)
+            .then_with(|| self.value.proto_cmp(&other.value))
+    }
+}
+
+/// Values are ordered by wire type, then fixed width values are compared as unsigned integers,
+/// and length-delimited values byte by byte.
+impl ProtoOrd for UnknownFieldValue {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (UnknownFieldValue::Varint(a), UnknownFieldValue::Varint(b)) => a.cmp(b),
+            (UnknownFieldValue::SixtyFourBit(a), UnknownFieldValue::SixtyFourBit(b)) => {
+                u64::from_le_bytes(*a).cmp(&u64::from_le_bytes(*b))
+            }
+            (UnknownFieldValue::LengthDelimited(a), UnknownFieldValue::LengthDelimited(b)) => {
+                a.proto_cmp(b)
+            }
+            (UnknownFieldValue::Group(a), UnknownFieldValue::Group(b)) => a.proto_cmp(b),
+            (UnknownFieldValue::ThirtyTwoBit(a), UnknownFieldValue::ThirtyTwoBit(b)) => {
+                u32::from_le_bytes(*a).cmp(&u32::from_le_bytes(*b))
+            }
+            (a, b) => (a.wire_type() as u8).cmp(&(b.wire_type() as u8)),
+        }
+    }
+}
+
+/// Extensions are compared like unknown fields.
+impl ProtoOrd for ExtensionSet {
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        self.fields().proto_cmp(other.fields())
+    }
+}
+
+/// Lazy messages are compared once decoded. A message which fails to decode is less than one
+/// which decodes, and two such messages are compared by their encoded bytes.
+#[cfg(feature = "std")]
+impl<M> ProtoOrd for crate::Lazy<M>
+where
+    M: ProtoOrd + crate::Message + Default,
+{
+    fn proto_cmp(&self, other: &Self) -> Ordering {
+        match (self.get(), other.get()) {
+            (Ok(a), Ok(b)) => a.proto_cmp(b),
+            (Ok(_), Err(_)) => Ordering::Greater,
+            (Err(_), Ok(_)) => Ordering::Less,
+            (Err(_), Err(_)) => self.encoded().proto_cmp(&other.encoded()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn scalars() {
+        assert_eq!(1u32.proto_cmp(&2), Ordering::Less);
+        assert_eq!((-1i64).proto_cmp(&0), Ordering::Less);
+        assert_eq!(false.proto_cmp(&true), Ordering::Less);
+        assert_eq!((-0.0f64).proto_cmp(&0.0), Ordering::Less);
+        assert_eq!(f32::NAN.proto_cmp(&f32::NAN), Ordering::Equal);
+        assert_eq!("ab".proto_cmp("b"), Ordering::Less);
+        assert_eq!("a".proto_cmp("ab"), Ordering::Less);
+        assert_eq!(
+            bytes::Bytes::from_static(b"\xff").proto_cmp(&bytes::Bytes::from_static(b"\x00\x00")),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn optional_and_repeated() {
+        assert_eq!(None.proto_cmp(&Some(0)), Ordering::Less);
+        assert_eq!(Some(1).proto_cmp(&Some(0)), Ordering::Greater);
+        assert_eq!(vec![1, 2].proto_cmp(&vec![1, 2, 0]), Ordering::Less);
+        assert_eq!(vec![2].proto_cmp(&vec![1, 2, 0]), Ordering::Greater);
+        assert_eq!(Vec::<u32>::new().proto_cmp(&vec![]), Ordering::Equal);
+    }
+
+    #[test]
+    fn maps() {
+        let a = BTreeMap::from([(1, "x".to_string()), (3, "y".to_string())]);
+        let mut b = a.clone();
+        assert_eq!(a.proto_cmp(&b), Ordering::Equal);
+        // A smaller key is less, whatever the values.
+        b.insert(2, "z".to_string());
+        assert_eq!(a.proto_cmp(&b), Ordering::Greater);
+        b.remove(&2);
+        b.insert(3, "z".to_string());
+        assert_eq!(a.proto_cmp(&b), Ordering::Less);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_maps_are_sorted_by_key() {
+        use std::collections::HashMap;
+
+        let a = (0..64)
+            .map(|key| (key, 0u32))
+            .collect::<HashMap<i32, u32>>();
+        let mut b = (0..64)
+            .rev()
+            .map(|key| (key, 0u32))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(a.proto_cmp(&b), Ordering::Equal);
+        b.insert(63, 1);
+        assert_eq!(a.proto_cmp(&b), Ordering::Less);
+        b.insert(-1, 0);
+        assert_eq!(a.proto_cmp(&b), Ordering::Greater);
+    }
+
+    #[test]
+    fn unknown_fields() {
+        let mut a = UnknownFieldSet::new();
+        a.push(2, UnknownFieldValue::Varint(1));
+        a.push(1, UnknownFieldValue::LengthDelimited(vec![1]));
+        let mut b = UnknownFieldSet::new();
+        b.push(1, UnknownFieldValue::LengthDelimited(vec![1]));
+        b.push(2, UnknownFieldValue::Varint(1));
+        // The fields are compared in field number order.
+        assert_eq!(a.proto_cmp(&b), Ordering::Equal);
+
+        b.push(2, UnknownFieldValue::Varint(0));
+        assert_eq!(a.proto_cmp(&b), Ordering::Less);
+
+        let varint = UnknownFieldValue::Varint(u64::MAX);
+        let fixed = UnknownFieldValue::SixtyFourBit([0; 8]);
+        assert_eq!(varint.proto_cmp(&fixed), Ordering::Less);
+        assert_eq!(
+            UnknownFieldValue::ThirtyTwoBit([0, 1, 0, 0])
+                .proto_cmp(&UnknownFieldValue::ThirtyTwoBit([1, 0, 0, 0])),
+            Ordering::Greater
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Transport-agnostic descriptions of Protobuf services.
+//!
+//! The types in this module are implemented by the code which
+//! `prost_build::ServiceDefinitionGenerator` generates for `service` definitions. They describe
+//! the methods of a service and their request and response message types, which are encoded and
+//! decoded with [`Message`], so that RPC frameworks can route and serve the methods without a
+//! framework specific service generator.
+
+use bytes::Buf;
+
+use crate::{DecodeError, Message};
+
+/// Describes a method of a Protobuf service.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MethodDescriptor {
+    /// The method name as it appears in the .proto file, e.g. `SayHello`.
+    pub name: &'static str,
+    /// The path of the method, e.g. `/helloworld.Greeter/SayHello`, as used by gRPC.
+    pub path: &'static str,
+    /// Whether the client streams multiple request messages.
+    pub client_streaming: bool,
+    /// Whether the server streams multiple response messages.
+    pub server_streaming: bool,
+}
+
+/// Describes a Protobuf service.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ServiceDescriptor {
+    /// The service name as it appears in the .proto file, e.g. `Greeter`.
+    pub name: &'static str,
+    /// The package name of the service, e.g. `helloworld`.
+    pub package: &'static str,
+    /// The fully-qualified name of the service, e.g. `helloworld.Greeter`.
+    pub full_name: &'static str,
+    /// The methods of the service, in the order of the .proto file.
+    pub methods: &'static [MethodDescriptor],
+}
+
+impl ServiceDescriptor {
+    /// Returns the method with the name as it appears in the .proto file.
+    pub fn method(&self, name: &str) -> Option<&'static MethodDescriptor> {
+        self.methods.iter().find(|method| method.name == name)
+    }
+
+    /// Returns the method with the path, e.g. `/helloworld.Greeter/SayHello`.
+    pub fn method_by_path(&self, path: &str) -> Option<&'static MethodDescriptor> {
+        self.methods.iter().find(|method| method.path == path)
+    }
+}
+
+/// A Protobuf service.
+pub trait Service {
+    /// The descriptor of the service.
+    const DESCRIPTOR: ServiceDescriptor;
+}
+
+/// A method of a Protobuf service.
+pub trait ServiceMethod {
+    /// The service which the method belongs to.
+    type Service: Service;
+    /// The message type of the requests.
+    type Request: Message + Default;
+    /// The message type of the responses.
+    type Response: Message + Default;
+
+    /// The descriptor of the method.
+    const DESCRIPTOR: MethodDescriptor;
+
+    /// Decodes a request message from `buf`.
+    fn decode_request(buf: impl Buf) -> Result<Self::Request, DecodeError> {
+        Self::Request::decode(buf)
+    }
+
+    /// Decodes a response message from `buf`.
+    fn decode_response(buf: impl Buf) -> Result<Self::Response, DecodeError> {
+        Self::Response::decode(buf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::{String, ToString};
+
+    use super::*;
+
+    struct Echo;
+
+    impl Service for Echo {
+        const DESCRIPTOR: ServiceDescriptor = ServiceDescriptor {
+            name: "Echo",
+            package: "test",
+            full_name: "test.Echo",
+            methods: &[<Say as ServiceMethod>::DESCRIPTOR],
+        };
+    }
+
+    struct Say;
+
+    impl ServiceMethod for Say {
+        type Service = Echo;
+        type Request = String;
+        type Response = String;
+
+        const DESCRIPTOR: MethodDescriptor = MethodDescriptor {
+            name: "Say",
+            path: "/test.Echo/Say",
+            client_streaming: false,
+            server_streaming: true,
+        };
+    }
+
+    #[test]
+    fn lookup_methods() {
+        let service = Echo::DESCRIPTOR;
+        assert_eq!(service.method("Say"), Some(&Say::DESCRIPTOR));
+        assert_eq!(
+            service.method_by_path("/test.Echo/Say"),
+            Some(&Say::DESCRIPTOR)
+        );
+        assert_eq!(service.method("say"), None);
+        assert_eq!(service.method_by_path("/test.Other/Say"), None);
+    }
+
+    #[test]
+    fn decode_messages() {
+        let buf = "hello".to_string().encode_to_vec();
+        assert_eq!(Say::decode_request(buf.as_slice()).unwrap(), "hello");
+        assert_eq!(Say::decode_response(buf.as_slice()).unwrap(), "hello");
+        assert!(Say::decode_request(&[0x0a, 0x05][..]).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! Reading and writing streams of length-delimited messages with [`Read`] and [`Write`].
+//!
+//! A stream is a sequence of messages, each preceded by its encoded length as a varint, as with
+//! [`Message::encode_length_delimited`]. This is the format of files holding many messages, such
+//! as the delimited streams of the Protobuf Java and C++ libraries.
+//!
+//! ```rust
+//! use prost::stream::{MessageReader, MessageWriter};
+//!
+//! let mut writer = MessageWriter::new(Vec::new());
+//! writer.write(&"first".to_string())?;
+//! writer.write(&"second".to_string())?;
+//! let file = writer.into_inner();
+//!
+//! let reader = MessageReader::<_, String>::new(file.as_slice());
+//! let messages = reader.collect::<Result<Vec<_>, _>>()?;
+//! assert_eq!(messages, ["first", "second"]);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+use crate::{decode_length_delimiter, Message};
+
+/// The maximum length of an encoded length delimiter.
+const MAX_DELIMITER_LEN: usize = 10;
+
+/// Writes length-delimited messages to a [`Write`].
+///
+/// Each message is written with a single call to [`Write::write_all`], so an unbuffered writer
+/// does not need to be wrapped in a [`BufWriter`](std::io::BufWriter).
+#[derive(Debug)]
+pub struct MessageWriter<W> {
+    writer: W,
+}
+
+impl<W> MessageWriter<W>
+where
+    W: Write,
+{
+    /// Creates a writer of messages to `writer`.
+    pub fn new(writer: W) -> MessageWriter<W> {
+        MessageWriter { writer }
+    }
+
+    /// Writes a message with a length-delimiter.
+    pub fn write<M>(&mut self, message: &M) -> io::Result<()>
+    where
+        M: Message,
+    {
+        self.writer
+            .write_all(&message.encode_length_delimited_to_vec())
+    }
+
+    /// Writes all messages of an iterator.
+    pub fn write_all<'a, M, I>(&mut self, messages: I) -> io::Result<()>
+    where
+        M: Message + 'a,
+        I: IntoIterator<Item = &'a M>,
+    {
+        messages
+            .into_iter()
+            .try_for_each(|message| self.write(message))
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Writing to the writer directly may corrupt the stream of messages.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads length-delimited messages from a [`Read`].
+///
+/// Messages are read with [`read`](MessageReader::read), which returns `None` at the end of the
+/// stream, or by iterating over the reader. The stream ends when the reader reaches the end of
+/// its input after a message, and an [`io::ErrorKind::UnexpectedEof`] error is returned if the
+/// input ends within a message. Decoding errors are returned as [`io::ErrorKind::InvalidData`]
+/// errors, after which the iterator ends.
+///
+/// Exactly the bytes of each message are read, one at a time for the length-delimiters, so an
+/// unbuffered reader, such as a [`File`](std::fs::File), should be wrapped in a
+/// [`BufReader`](std::io::BufReader).
+pub struct MessageReader<R, M> {
+    reader: R,
+    buf: Vec<u8>,
+    failed: bool,
+    message: PhantomData<fn() -> M>,
+}
+
+impl<R, M> MessageReader<R, M>
+where
+    R: Read,
+    M: Message + Default,
+{
+    /// Creates a reader of messages from `reader`.
+    pub fn new(reader: R) -> MessageReader<R, M> {
+        MessageReader {
+            reader,
+            buf: Vec::new(),
+            failed: false,
+            message: PhantomData,
+        }
+    }
+
+    /// Reads the next message, or returns `None` at the end of the stream.
+    pub fn read(&mut self) -> io::Result<Option<M>> {
+        let Some(len) = self.read_delimiter()? else {
+            return Ok(None);
+        };
+
+        // The message is read as it is received, so that a corrupt length does not allocate more
+        // memory than the data which is actually read.
+        self.buf.clear();
+        (&mut self.reader)
+            .take(len as u64)
+            .read_to_end(&mut self.buf)?;
+        if self.buf.len() < len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(Some(M::decode(self.buf.as_slice())?))
+    }
+
+    /// Reads a length-delimiter, or returns `None` if the reader is at the end of its input.
+    fn read_delimiter(&mut self) -> io::Result<Option<usize>> {
+        let mut delimiter = [0; MAX_DELIMITER_LEN];
+        let mut delimiter_len = 0;
+        while delimiter_len < MAX_DELIMITER_LEN {
+            match self
+                .reader
+                .read_exact(&mut delimiter[delimiter_len..=delimiter_len])
+            {
+                Ok(()) => (),
+                Err(error)
+                    if error.kind() == io::ErrorKind::UnexpectedEof && delimiter_len == 0 =>
+                {
+                    return Ok(None)
+                }
+                Err(error) => return Err(error),
+            }
+            delimiter_len += 1;
+            if delimiter[delimiter_len - 1] < 0x80 {
+                break;
+            }
+        }
+        Ok(Some(decode_length_delimiter(&delimiter[..delimiter_len])?))
+    }
+}
+
+impl<R, M> MessageReader<R, M> {
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Reading from the reader directly may corrupt the stream of messages.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R, M> Iterator for MessageReader<R, M>
+where
+    R: Read,
+    M: Message + Default,
+{
+    type Item = io::Result<M>;
+
+    fn next(&mut self) -> Option<io::Result<M>> {
+        if self.failed {
+            return None;
+        }
+        let message = self.read().transpose();
+        self.failed = matches!(message, Some(Err(_)));
+        message
+    }
+}
+
+impl<R, M> fmt::Debug for MessageReader<R, M>
+where
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageReader")
+            .field("reader", &self.reader)
+            .field("failed", &self.failed)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let messages = [String::from("first"), String::new(), "x".repeat(300)];
+        let mut writer = MessageWriter::new(Vec::new());
+        writer.write_all(&messages).unwrap();
+        writer.flush().unwrap();
+        let file = writer.into_inner();
+
+        let mut expected = Vec::new();
+        for message in &messages {
+            message.encode_length_delimited(&mut expected).unwrap();
+        }
+        assert_eq!(file, expected);
+
+        let mut reader = MessageReader::<_, String>::new(file.as_slice());
+        for message in &messages {
+            assert_eq!(reader.read().unwrap().as_ref(), Some(message));
+        }
+        assert_eq!(reader.read().unwrap(), None);
+        assert_eq!(reader.read().unwrap(), None);
+
+        let read = MessageReader::<_, String>::new(file.as_slice())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(read, messages);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut file = Vec::new();
+        "first"
+            .to_string()
+            .encode_length_delimited(&mut file)
+            .unwrap();
+        "second"
+            .to_string()
+            .encode_length_delimited(&mut file)
+            .unwrap();
+
+        let mut reader = MessageReader::<_, String>::new(&file[..file.len() - 1]);
+        assert_eq!(reader.next().unwrap().unwrap(), "first");
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(reader.next().is_none());
+
+        // A length-delimiter which is cut off.
+        let mut reader = MessageReader::<_, String>::new(&[0x80][..]);
+        let error = reader.read().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn invalid() {
+        // A length which does not fit in a `u64`.
+        let mut reader = MessageReader::<_, String>::new(&[0xff; 11][..]);
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(reader.next().is_none());
+
+        // A huge length is not allocated up front.
+        let mut reader = MessageReader::<_, String>::new(&[0xff, 0xff, 0xff, 0xff, 0x0f, 0x00][..]);
+        let error = reader.read().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        // Invalid UTF-8.
+        let mut reader = MessageReader::<_, String>::new(&[0x03, 0x0a, 0x01, 0x80][..]);
+        let error = reader.read().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,371 @@
+//! Push-based decoding of messages which arrive in chunks.
+
+use core::mem;
+
+use bytes::{Buf, BytesMut};
+
+use crate::encoding::varint::decode_varint;
+use crate::encoding::wire_type::WireType;
+use crate::encoding::{decode_key, DecodeContext};
+use crate::error::DecodeErrorKind;
+use crate::{decode_length_delimiter, DecodeError, Message};
+
+/// The maximum length of an encoded varint.
+const MAX_VARINT_LEN: usize = 10;
+
+/// Decodes messages from byte chunks, such as the data read from a non-blocking socket.
+///
+/// Chunks are added with [`push`](StreamingDecoder::push), and may split the input at any byte.
+/// The decoder tracks the position in the wire format across chunks, and merges each field into
+/// the message as soon as all of its bytes are received, so only the incomplete field at the end
+/// of the input is buffered, rather than the entire message.
+///
+/// By default the input is a sequence of length-delimited messages, as written by
+/// [`Message::encode_length_delimited`]. An [`unframed`](StreamingDecoder::unframed) decoder
+/// decodes a single message, which ends with the input.
+///
+/// After an error, the decoder discards the buffered input and the partially decoded message.
+///
+/// # Examples
+///
+/// ```rust
+/// use prost::StreamingDecoder;
+///
+/// let mut input = Vec::new();
+/// for value in ["first", "second"] {
+///     prost::Message::encode_length_delimited(&value.to_string(), &mut input).unwrap();
+/// }
+///
+/// let mut decoder = StreamingDecoder::<String>::new();
+/// let mut messages = Vec::new();
+/// for chunk in input.chunks(3) {
+///     decoder.push(chunk);
+///     while let Some(message) = decoder.next_message()? {
+///         messages.push(message);
+///     }
+/// }
+/// assert_eq!(decoder.finish()?, None);
+/// assert_eq!(messages, ["first", "second"]);
+/// # Ok::<(), prost::DecodeError>(())
+/// ```
+#[derive(Debug)]
+pub struct StreamingDecoder<M> {
+    message: M,
+    buf: BytesMut,
+    state: State,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Decoding the length delimiter of the next message.
+    Delimiter,
+    /// Decoding the fields of a length-delimited message, with the number of bytes left.
+    Frame(usize),
+    /// Decoding the fields of an unframed message, which ends with the input.
+    Unframed,
+}
+
+impl<M> StreamingDecoder<M>
+where
+    M: Message + Default,
+{
+    /// Creates a decoder for a sequence of length-delimited messages.
+    pub fn new() -> StreamingDecoder<M> {
+        StreamingDecoder {
+            message: M::default(),
+            buf: BytesMut::new(),
+            state: State::Delimiter,
+        }
+    }
+
+    /// Creates a decoder for a single message without a length delimiter, which is returned by
+    /// [`finish`](StreamingDecoder::finish).
+    pub fn unframed() -> StreamingDecoder<M> {
+        StreamingDecoder {
+            message: M::default(),
+            buf: BytesMut::new(),
+            state: State::Unframed,
+        }
+    }
+
+    /// Appends a chunk of the input.
+    ///
+    /// The chunk is decoded by [`next_message`](StreamingDecoder::next_message).
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Returns the number of bytes which have been pushed, but not merged into a message yet.
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Decodes the pushed input, and returns the next message if it is complete.
+    ///
+    /// Returns `None` once more input is required, so this should be called until it returns
+    /// `None` after each [`push`](StreamingDecoder::push). An unframed decoder merges the complete
+    /// fields of its message and always returns `None`.
+    pub fn next_message(&mut self) -> Result<Option<M>, DecodeError> {
+        let result = self.decode();
+        if result.is_err() {
+            self.reset();
+        }
+        result
+    }
+
+    /// Ends the input.
+    ///
+    /// Returns the message of an unframed decoder. A decoder for length-delimited messages returns
+    /// `None`, after checking that the input ends between messages.
+    pub fn finish(mut self) -> Result<Option<M>, DecodeError> {
+        if let Some(message) = self.next_message()? {
+            return Ok(Some(message));
+        }
+        match self.state {
+            State::Delimiter if self.buf.is_empty() => Ok(None),
+            State::Unframed if self.buf.is_empty() => Ok(Some(self.message)),
+            _ => Err(DecodeErrorKind::BufferUnderflow.into()),
+        }
+    }
+
+    fn decode(&mut self) -> Result<Option<M>, DecodeError> {
+        loop {
+            match self.state {
+                State::Delimiter => {
+                    let Some(len) = varint_len(&self.buf)? else {
+                        return Ok(None);
+                    };
+                    let frame_len = decode_length_delimiter(&self.buf[..len])?;
+                    self.buf.advance(len);
+                    self.state = State::Frame(frame_len);
+                }
+                State::Frame(0) => {
+                    self.state = State::Delimiter;
+                    return Ok(Some(mem::take(&mut self.message)));
+                }
+                State::Frame(remaining) => {
+                    let available = &self.buf[..self.buf.len().min(remaining)];
+                    match field_len(available, DecodeContext::default())? {
+                        Some(len) => {
+                            self.merge_field(len)?;
+                            self.state = State::Frame(remaining - len);
+                        }
+                        // The field exceeds the message.
+                        None if available.len() == remaining => {
+                            return Err(DecodeErrorKind::DelimitedLengthExceeded.into())
+                        }
+                        None => return Ok(None),
+                    }
+                }
+                State::Unframed => match field_len(&self.buf, DecodeContext::default())? {
+                    Some(len) => self.merge_field(len)?,
+                    None => return Ok(None),
+                },
+            }
+        }
+    }
+
+    /// Merges the complete field with the encoded length `len` at the start of the buffer.
+    fn merge_field(&mut self, len: usize) -> Result<(), DecodeError> {
+        let mut field = self.buf.split_to(len).freeze();
+        let (tag, wire_type) = decode_key(&mut field)?;
+        self.message
+            .merge_field(tag, wire_type, &mut field, DecodeContext::default())
+    }
+
+    fn reset(&mut self) {
+        self.message.clear();
+        self.buf.clear();
+        if self.state != State::Unframed {
+            self.state = State::Delimiter;
+        }
+    }
+}
+
+impl<M> Default for StreamingDecoder<M>
+where
+    M: Message + Default,
+{
+    fn default() -> StreamingDecoder<M> {
+        StreamingDecoder::new()
+    }
+}
+
+/// Returns the length of the varint at the start of `buf`, or `None` if it is incomplete.
+fn varint_len(buf: &[u8]) -> Result<Option<usize>, DecodeError> {
+    match buf
+        .iter()
+        .take(MAX_VARINT_LEN)
+        .position(|&byte| byte < 0x80)
+    {
+        Some(position) => Ok(Some(position + 1)),
+        None if buf.len() >= MAX_VARINT_LEN => Err(DecodeErrorKind::InvalidVarint.into()),
+        None => Ok(None),
+    }
+}
+
+/// Returns the encoded length of the field at the start of `buf`, including its key, or `None` if
+/// the field is incomplete.
+fn field_len(buf: &[u8], ctx: DecodeContext) -> Result<Option<usize>, DecodeError> {
+    let Some(key_len) = varint_len(buf)? else {
+        return Ok(None);
+    };
+    let (tag, wire_type) = decode_key(&mut &buf[..key_len])?;
+    let value = &buf[key_len..];
+    let value_len = match wire_type {
+        WireType::Varint => varint_len(value)?,
+        WireType::ThirtyTwoBit => Some(4),
+        WireType::SixtyFourBit => Some(8),
+        WireType::LengthDelimited => match varint_len(value)? {
+            Some(len) => {
+                let data_len = decode_varint(&mut &value[..len])?;
+                Some(
+                    usize::try_from(data_len)
+                        .ok()
+                        .and_then(|data_len| data_len.checked_add(len))
+                        .ok_or(DecodeErrorKind::LengthDelimiterTooLarge)?,
+                )
+            }
+            None => None,
+        },
+        WireType::StartGroup => group_len(tag, value, ctx)?,
+        WireType::EndGroup => return Err(DecodeErrorKind::UnexpectedEndGroupTag.into()),
+    };
+    Ok(value_len
+        .and_then(|len| len.checked_add(key_len))
+        .filter(|&len| len <= buf.len()))
+}
+
+/// Returns the encoded length of the fields of the group with the tag `tag` at the start of `buf`,
+/// including the end group key, or `None` if the group is incomplete.
+fn group_len(tag: u32, buf: &[u8], ctx: DecodeContext) -> Result<Option<usize>, DecodeError> {
+    ctx.limit_reached()?;
+    let mut offset = 0;
+    loop {
+        let rest = &buf[offset..];
+        let Some(key_len) = varint_len(rest)? else {
+            return Ok(None);
+        };
+        let (inner_tag, wire_type) = decode_key(&mut &rest[..key_len])?;
+        if wire_type == WireType::EndGroup {
+            if inner_tag != tag {
+                return Err(DecodeErrorKind::UnexpectedEndGroupTag.into());
+            }
+            return Ok(Some(offset + key_len));
+        }
+        match field_len(rest, ctx.enter_recursion())? {
+            Some(len) => offset += len,
+            None => return Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{UnknownFieldSet, UnknownFieldValue};
+
+    fn fields() -> UnknownFieldSet {
+        let mut fields = UnknownFieldSet::new();
+        fields.push(1, UnknownFieldValue::Varint(300));
+        fields.push(2, UnknownFieldValue::SixtyFourBit([1, 2, 3, 4, 5, 6, 7, 8]));
+        fields.push(3, UnknownFieldValue::LengthDelimited(vec![7; 200]));
+        let mut nested = UnknownFieldSet::new();
+        nested.push(5, UnknownFieldValue::Varint(1));
+        let mut inner = UnknownFieldSet::new();
+        inner.push(7, UnknownFieldValue::ThirtyTwoBit([9, 10, 11, 12]));
+        nested.push(6, UnknownFieldValue::Group(inner));
+        fields.push(4, UnknownFieldValue::Group(nested));
+        fields
+    }
+
+    /// Decodes the input, pushing it in chunks of `chunk_len` bytes.
+    fn decode_chunks(
+        mut decoder: StreamingDecoder<UnknownFieldSet>,
+        input: &[u8],
+        chunk_len: usize,
+    ) -> Result<Vec<UnknownFieldSet>, DecodeError> {
+        let mut messages = Vec::new();
+        for chunk in input.chunks(chunk_len) {
+            decoder.push(chunk);
+            while let Some(message) = decoder.next_message()? {
+                messages.push(message);
+            }
+        }
+        messages.extend(decoder.finish()?);
+        Ok(messages)
+    }
+
+    #[test]
+    fn decode_length_delimited_messages() {
+        let mut input = fields().encode_length_delimited_to_vec();
+        UnknownFieldSet::new()
+            .encode_length_delimited(&mut input)
+            .unwrap();
+        fields().encode_length_delimited(&mut input).unwrap();
+
+        for chunk_len in [1, 2, 3, 7, 64, input.len()] {
+            let messages = decode_chunks(StreamingDecoder::new(), &input, chunk_len).unwrap();
+            assert_eq!(messages, [fields(), UnknownFieldSet::new(), fields()]);
+        }
+    }
+
+    #[test]
+    fn decode_unframed_message() {
+        let input = fields().encode_to_vec();
+        for chunk_len in [1, 5, input.len()] {
+            let messages = decode_chunks(StreamingDecoder::unframed(), &input, chunk_len).unwrap();
+            assert_eq!(messages, [fields()]);
+        }
+
+        let decoder = StreamingDecoder::<String>::unframed();
+        assert_eq!(decoder.finish().unwrap(), Some(String::new()));
+    }
+
+    #[test]
+    fn merge_complete_fields() {
+        let input = fields().encode_to_vec();
+        let mut decoder = StreamingDecoder::<UnknownFieldSet>::unframed();
+        // The varint field, and the key and two bytes of the fixed width field.
+        decoder.push(&input[..6]);
+        assert_eq!(decoder.next_message().unwrap(), None);
+        assert_eq!(decoder.buffered_len(), 3);
+        assert_eq!(decoder.message.len(), 1);
+    }
+
+    #[test]
+    fn decode_errors() {
+        // The input ends within a message.
+        let input = fields().encode_length_delimited_to_vec();
+        assert!(decode_chunks(StreamingDecoder::new(), &input[..input.len() - 1], 4).is_err());
+        let input = fields().encode_to_vec();
+        assert!(decode_chunks(StreamingDecoder::unframed(), &input[..5], 4).is_err());
+
+        // The length-delimited field exceeds the message.
+        let mut decoder = StreamingDecoder::<UnknownFieldSet>::new();
+        decoder.push(&[0x02, 0x0a, 0x05, 0x01, 0x02, 0x03]);
+        assert!(decoder.next_message().is_err());
+
+        // Mismatched end group tag.
+        assert!(decode_chunks(StreamingDecoder::unframed(), &[0x0b, 0x14], 1).is_err());
+        // Invalid length delimiter.
+        assert!(decode_chunks(StreamingDecoder::new(), &[0xff; 11], 1).is_err());
+    }
+
+    #[test]
+    fn reset_after_error() {
+        let mut decoder = StreamingDecoder::<String>::new();
+        // A string with invalid UTF-8.
+        decoder.push(&[0x03, 0x0a, 0x01, 0xff]);
+        assert!(decoder.next_message().is_err());
+        assert_eq!(decoder.buffered_len(), 0);
+
+        decoder.push(&"valid".to_string().encode_length_delimited_to_vec());
+        assert_eq!(decoder.next_message().unwrap(), Some("valid".to_string()));
+        assert_eq!(decoder.finish().unwrap(), None);
+    }
+}
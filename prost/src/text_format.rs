@@ -0,0 +1,226 @@
+//! Support for the [Protobuf text format][1].
+//!
+//! Messages opt in to the text format with the `#[prost(text_format)]` attribute, which
+//! `prost-build` emits when [`Config::enable_text_format`][2] is set. The derived
+//! [`TextFormatMessage`] implementation provides [`to_text_format`] and [`from_text_format`].
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! use prost::text_format::TextFormatMessage;
+//!
+//! #[derive(Clone, PartialEq, prost::Message)]
+//! #[prost(text_format)]
+//! struct Person {
+//!     #[prost(string, tag = "1")]
+//!     display_name: String,
+//!     #[prost(int64, repeated, tag = "2")]
+//!     ids: Vec<i64>,
+//! }
+//!
+//! let person = Person { display_name: "Ferris".into(), ids: vec![7, 8] };
+//! let text = person.to_text_format();
+//! assert_eq!(text, "display_name: \"Ferris\"\nids: 7\nids: 8\n");
+//! assert_eq!(Person::from_text_format("display_name: 'Ferris' ids: [7, 8]").unwrap(), person);
+//! # }
+//! ```
+//!
+//! Messages annotated with `#[prost(text_format_debug)]` as well, which `prost-build` emits when
+//! [`Config::text_format_debug`][3] is set, implement `Debug` with the text format instead of the
+//! derived output: `{:?}` prints the message on a single line, and `{:#?}` with one field per
+//! line. Sensitive fields are cleared with [`Message::redact`] before the message is printed.
+//!
+//! Enumeration fields are written as their Protobuf value names, which requires the inherent
+//! `as_str_name` and `from_str_name` methods generated by `prost-build`. The well-known types
+//! implement [`TextFormatMessage`] in `prost-types`, behind its `text-format` feature.
+//! Messages packed in a `google.protobuf.Any` are written as the raw `type_url` and `value`
+//! fields, rather than in the expanded `[type_url] { ... }` form.
+//!
+//! [1]: https://protobuf.dev/reference/protobuf/textformat-spec/
+//! [2]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.enable_text_format
+//! [3]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.text_format_debug
+//! [`to_text_format`]: TextFormatMessage::to_text_format
+//! [`from_text_format`]: TextFormatMessage::from_text_format
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt;
+
+use crate::Message;
+
+#[doc(hidden)]
+pub mod encoding;
+mod scalar;
+mod value;
+
+pub use value::{TextMessage, TextValue};
+
+/// A Protobuf text format error.
+///
+/// `TextFormatError` indicates that the input is not valid text format, or that it does not
+/// conform to the target message type.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TextFormatError {
+    description: Cow<'static, str>,
+}
+
+impl TextFormatError {
+    /// Creates a new `TextFormatError` with a description of the problem.
+    ///
+    /// Meant to be used only by `TextFormatMessage` implementations.
+    #[doc(hidden)]
+    #[cold]
+    pub fn new(description: impl Into<Cow<'static, str>>) -> TextFormatError {
+        TextFormatError {
+            description: description.into(),
+        }
+    }
+}
+
+impl fmt::Debug for TextFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TextFormatError")
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+impl fmt::Display for TextFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to process Protobuf text format: ")?;
+        f.write_str(&self.description)
+    }
+}
+
+impl core::error::Error for TextFormatError {}
+
+/// A Protobuf message which supports the text format.
+///
+/// This trait is implemented by `#[derive(Message)]` for message types annotated with
+/// `#[prost(text_format)]`.
+pub trait TextFormatMessage {
+    /// Appends the fields of the message which are set to `message`.
+    fn encode_text(&self, message: &mut TextMessage);
+
+    /// Merges the fields of `message` into `self`.
+    fn merge_text(&mut self, message: &TextMessage) -> Result<(), TextFormatError>;
+
+    /// Converts the message to the text format, with one field per line.
+    fn to_text_format(&self) -> String
+    where
+        Self: Sized,
+    {
+        let mut message = TextMessage::new();
+        self.encode_text(&mut message);
+        value::print(&message)
+    }
+
+    /// Parses the text format and merges the fields into `self`.
+    fn merge_text_format(&mut self, s: &str) -> Result<(), TextFormatError>
+    where
+        Self: Sized,
+    {
+        self.merge_text(&value::parse(s)?)
+    }
+
+    /// Parses a new instance of the message from the text format.
+    fn from_text_format(s: &str) -> Result<Self, TextFormatError>
+    where
+        Self: Default + Sized,
+    {
+        let mut message = Self::default();
+        message.merge_text_format(s)?;
+        Ok(message)
+    }
+}
+
+impl<M> TextFormatMessage for Box<M>
+where
+    M: TextFormatMessage,
+{
+    fn encode_text(&self, message: &mut TextMessage) {
+        (**self).encode_text(message)
+    }
+
+    fn merge_text(&mut self, message: &TextMessage) -> Result<(), TextFormatError> {
+        (**self).merge_text(message)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<M> TextFormatMessage for alloc::sync::Arc<M>
+where
+    M: TextFormatMessage + Clone,
+{
+    fn encode_text(&self, message: &mut TextMessage) {
+        (**self).encode_text(message)
+    }
+
+    fn merge_text(&mut self, message: &TextMessage) -> Result<(), TextFormatError> {
+        alloc::sync::Arc::make_mut(self).merge_text(message)
+    }
+}
+
+/// A value which may be held by a field of a message supporting the text format.
+///
+/// This is implemented for the Rust types of the Protobuf scalar types, which also represent the
+/// well-known wrapper types such as `google.protobuf.Int64Value`, and for all
+/// [`TextFormatMessage`] types.
+pub trait TextField: Sized {
+    /// Converts the value to its text format representation.
+    fn to_text_value(&self) -> TextValue;
+
+    /// Merges a text format representation of the value into `self`.
+    fn merge_text_value(&mut self, value: &TextValue) -> Result<(), TextFormatError>;
+
+    /// Converts a text format representation to a new value.
+    fn from_text_value(value: &TextValue) -> Result<Self, TextFormatError>
+    where
+        Self: Default,
+    {
+        let mut field = Self::default();
+        field.merge_text_value(value)?;
+        Ok(field)
+    }
+}
+
+impl<M> TextField for M
+where
+    M: TextFormatMessage + Message,
+{
+    fn to_text_value(&self) -> TextValue {
+        let mut message = TextMessage::new();
+        self.encode_text(&mut message);
+        TextValue::Message(message)
+    }
+
+    fn merge_text_value(&mut self, value: &TextValue) -> Result<(), TextFormatError> {
+        match value {
+            TextValue::Message(message) => self.merge_text(message),
+            _ => Err(TextFormatError::new("expected message")),
+        }
+    }
+}
+
+/// A lazy field whose bytes cannot be decoded is represented by its bytes as a string.
+#[cfg(feature = "std")]
+impl<M> TextField for crate::Lazy<M>
+where
+    M: TextField + Message + Default,
+{
+    fn to_text_value(&self) -> TextValue {
+        match self.get() {
+            Ok(message) => message.to_text_value(),
+            Err(_) => {
+                TextValue::String(self.encoded().map_or_else(Vec::new, |bytes| bytes.to_vec()))
+            }
+        }
+    }
+
+    fn merge_text_value(&mut self, value: &TextValue) -> Result<(), TextFormatError> {
+        let message = self
+            .get_mut()
+            .map_err(|error| TextFormatError::new(alloc::string::ToString::to_string(&error)))?;
+        message.merge_text_value(value)
+    }
+}
@@ -0,0 +1,181 @@
+//! Utility functions and types for the text format of derived messages.
+//!
+//! Meant to be used only from `Message` implementations.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::Message;
+
+pub use super::{TextField, TextFormatError, TextFormatMessage, TextMessage, TextValue};
+
+/// Formats a message in the text format for its `Debug` implementation, on a single line unless
+/// the alternate flag is set. The sensitive fields of the message are cleared before formatting.
+pub fn debug<M>(message: &M, f: &mut fmt::Formatter<'_>) -> fmt::Result
+where
+    M: TextFormatMessage + Message + Clone,
+{
+    let mut message = message.clone();
+    message.redact();
+    let mut text = TextMessage::new();
+    message.encode_text(&mut text);
+    if f.alternate() {
+        f.write_str(&super::value::print(&text))
+    } else {
+        f.write_str(&super::value::print_short(&text))
+    }
+}
+
+/// Returns the error for a field which is not part of a message.
+pub fn unknown_field(name: &str, message: &str) -> TextFormatError {
+    TextFormatError::new(format!("unknown field `{name}` in {message}"))
+}
+
+/// Adds a message and field name to an error.
+pub fn with_field(error: TextFormatError, message: &str, field: &str) -> TextFormatError {
+    TextFormatError::new(format!("{message}.{field}: {}", error.description))
+}
+
+/// Merges a singular field.
+pub fn merge<T>(field: &mut T, value: &TextValue) -> Result<(), TextFormatError>
+where
+    T: TextField,
+{
+    field.merge_text_value(value)
+}
+
+/// Merges an optional field.
+pub fn merge_optional<T>(field: &mut Option<T>, value: &TextValue) -> Result<(), TextFormatError>
+where
+    T: TextField + Default,
+{
+    field
+        .get_or_insert_with(Default::default)
+        .merge_text_value(value)
+}
+
+/// Merges an element of a repeated field.
+pub fn merge_repeated<T>(field: &mut Vec<T>, value: &TextValue) -> Result<(), TextFormatError>
+where
+    T: TextField + Default,
+{
+    field.push(T::from_text_value(value)?);
+    Ok(())
+}
+
+/// Appends the entries of a map field to `message`, using `value_to_text` to convert each value.
+pub fn encode_map<'a, K, V>(
+    message: &mut TextMessage,
+    name: &str,
+    map: impl IntoIterator<Item = (&'a K, &'a V)>,
+    mut value_to_text: impl FnMut(&V) -> TextValue,
+) where
+    K: TextField + 'a,
+    V: 'a,
+{
+    for (key, value) in map {
+        let entry = Vec::from([
+            (String::from("key"), key.to_text_value()),
+            (String::from("value"), value_to_text(value)),
+        ]);
+        message.push((name.into(), TextValue::Message(entry)));
+    }
+}
+
+/// Merges a map entry into a map field, using `value_from_text` to convert the value. Missing keys
+/// and values take their default value.
+pub fn merge_map<K, V, M>(
+    map: &mut M,
+    value: &TextValue,
+    mut value_from_text: impl FnMut(&TextValue) -> Result<V, TextFormatError>,
+) -> Result<(), TextFormatError>
+where
+    K: TextField + Default,
+    V: Default,
+    M: Extend<(K, V)>,
+{
+    let fields = match value {
+        TextValue::Message(fields) => fields,
+        _ => {
+            return Err(TextFormatError::new(format!(
+                "expected message for map entry, found {}",
+                value.kind()
+            )))
+        }
+    };
+    let mut key = K::default();
+    let mut entry_value = V::default();
+    for (name, value) in fields {
+        match name.as_str() {
+            "key" => key.merge_text_value(value)?,
+            "value" => entry_value = value_from_text(value)?,
+            _ => return Err(unknown_field(name, "map entry")),
+        }
+    }
+    map.extend(core::iter::once((key, entry_value)));
+    Ok(())
+}
+
+/// The mapping between the values of an enumeration and their Protobuf names.
+#[derive(Clone, Copy)]
+pub struct Enumeration {
+    /// Returns the name of a value, or `None` if the value is unknown.
+    pub name: fn(i32) -> Option<&'static str>,
+    /// Returns the value of a name, or `None` if the name is unknown.
+    pub value: fn(&str) -> Option<i32>,
+}
+
+impl Enumeration {
+    /// Converts an enumeration value to its name, or to a number if the value is unknown.
+    pub fn to_text(&self, value: i32) -> TextValue {
+        match (self.name)(value) {
+            Some(name) => TextValue::Scalar(name.into()),
+            None => TextValue::Scalar(format!("{value}")),
+        }
+    }
+
+    /// Converts a name or number to an enumeration value.
+    pub fn from_text(&self, value: &TextValue) -> Result<i32, TextFormatError> {
+        match value {
+            TextValue::Scalar(text)
+                if text.starts_with(|c: char| c == '-' || c.is_ascii_digit()) =>
+            {
+                super::scalar::parse_integer(value)
+            }
+            TextValue::Scalar(name) => (self.value)(name)
+                .ok_or_else(|| TextFormatError::new(format!("unknown enum value: {name}"))),
+            _ => Err(TextFormatError::new(format!(
+                "expected enum value, found {}",
+                value.kind()
+            ))),
+        }
+    }
+
+    /// Merges a singular enumeration field.
+    pub fn merge(&self, field: &mut i32, value: &TextValue) -> Result<(), TextFormatError> {
+        *field = self.from_text(value)?;
+        Ok(())
+    }
+
+    /// Merges an optional enumeration field.
+    pub fn merge_optional(
+        &self,
+        field: &mut Option<i32>,
+        value: &TextValue,
+    ) -> Result<(), TextFormatError> {
+        *field = Some(self.from_text(value)?);
+        Ok(())
+    }
+
+    /// Merges an element of a repeated enumeration field.
+    pub fn merge_repeated(
+        &self,
+        field: &mut Vec<i32>,
+        value: &TextValue,
+    ) -> Result<(), TextFormatError> {
+        field.push(self.from_text(value)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+//! Text format representations of the Protobuf scalar types.
+//!
+//! The Rust types of the scalar types also represent the well-known wrapper types, such as
+//! `google.protobuf.Int64Value`, whose text format representation is that of the wrapped scalar.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use ::bytes::Bytes;
+
+use super::{TextField, TextFormatError, TextValue};
+use crate::Utf8Bytes;
+
+fn unexpected(expected: &str, value: &TextValue) -> TextFormatError {
+    TextFormatError::new(format!("expected {expected}, found {}", value.kind()))
+}
+
+fn scalar<'a>(expected: &str, value: &'a TextValue) -> Result<&'a str, TextFormatError> {
+    match value {
+        TextValue::Scalar(text) => Ok(text),
+        _ => Err(unexpected(expected, value)),
+    }
+}
+
+/// Parses an integer in decimal, hexadecimal (`0x` prefix) or octal (`0` prefix) notation.
+pub(crate) fn parse_integer<T>(value: &TextValue) -> Result<T, TextFormatError>
+where
+    T: TryFrom<i128> + TryFrom<u128>,
+{
+    let text = scalar("integer", value)?;
+    let invalid = || TextFormatError::new(format!("invalid integer: {text}"));
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let (radix, digits) = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        (16, hex)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        (8, &digits[1..])
+    } else {
+        (10, digits)
+    };
+    // `from_str_radix` accepts a leading sign, which the text format does not allow here.
+    if !digits.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return Err(invalid());
+    }
+    let magnitude = u128::from_str_radix(digits, radix).map_err(|_| invalid())?;
+    let out_of_range = || TextFormatError::new(format!("integer out of range: {text}"));
+    if negative {
+        let integer = 0i128
+            .checked_sub_unsigned(magnitude)
+            .ok_or_else(out_of_range)?;
+        T::try_from(integer).map_err(|_| out_of_range())
+    } else {
+        T::try_from(magnitude).map_err(|_| out_of_range())
+    }
+}
+
+macro_rules! integer {
+    ($($ty:ty),*) => {$(
+        impl TextField for $ty {
+            fn to_text_value(&self) -> TextValue {
+                TextValue::Scalar(self.to_string())
+            }
+
+            fn merge_text_value(&mut self, value: &TextValue) -> Result<(), TextFormatError> {
+                *self = parse_integer(value)?;
+                Ok(())
+            }
+        }
+    )*};
+}
+
+integer!(i32, i64, i128, u32, u64, u128);
+
+/// Parses a floating point number, which may have an `f` suffix, or be one of the special values
+/// `inf`, `infinity` and `nan` in any case.
+fn parse_float<T>(value: &TextValue) -> Result<T, TextFormatError>
+where
+    T: core::str::FromStr,
+{
+    let text = scalar("number", value)?;
+    let invalid = || TextFormatError::new(format!("invalid number: {text}"));
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let number = if matches!(
+        digits.to_ascii_lowercase().as_str(),
+        "inf" | "infinity" | "nan"
+    ) {
+        text
+    } else if digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        // Rust's number parsing is more lenient than the text format, e.g. it accepts a leading
+        // `+`, hence the check above.
+        text.strip_suffix(['f', 'F']).unwrap_or(text)
+    } else {
+        return Err(invalid());
+    };
+    number.parse().map_err(|_| invalid())
+}
+
+macro_rules! float {
+    ($($ty:ty),*) => {$(
+        impl TextField for $ty {
+            fn to_text_value(&self) -> TextValue {
+                let text = if self.is_nan() {
+                    "nan".to_string()
+                } else if self.is_infinite() {
+                    if *self > 0.0 { "inf" } else { "-inf" }.to_string()
+                } else {
+                    format!("{self:?}")
+                };
+                TextValue::Scalar(text)
+            }
+
+            fn merge_text_value(&mut self, value: &TextValue) -> Result<(), TextFormatError> {
+                *self = parse_float(value)?;
+                Ok(())
+            }
+        }
+    )*};
+}
+
+float!(f32, f64);
+
+impl TextField for bool {
+    fn to_text_value(&self) -> TextValue {
+        TextValue::Scalar(self.to_string())
+    }
+
+    fn merge_text_value(&mut self, value: &TextValue) -> Result<(), TextFormatError> {
+        *self = match scalar("boolean", value)? {
+            "true" | "True" | "t" | "1" => true,
+            "false" | "False" | "f" | "0" => false,
+            text => return Err(TextFormatError::new(format!("invalid boolean: {text}"))),
+        };
+        Ok(())
+    }
+}
+
+impl TextField for String {
+    fn to_text_value(&self) -> TextValue {
+        TextValue::String(self.as_bytes().to_vec())
+    }
+
+    fn merge_text_value(&mut self, value: &TextValue) -> Result<(), TextFormatError> {
+        match value {
+            TextValue::String(bytes) => {
+                *self = String::from_utf8(bytes.clone())
+                    .map_err(|_| TextFormatError::new("invalid UTF-8 in string"))?;
+                Ok(())
+            }
+            _ => Err(unexpected("string", value)),
+        }
+    }
+}
+
+impl TextField for Utf8Bytes {
+    fn to_text_value(&self) -> TextValue {
+        TextValue::String(self.as_bytes().to_vec())
+    }
+
+    fn merge_text_value(&mut self, value: &TextValue) -> Result<(), TextFormatError> {
+        match value {
+            TextValue::String(bytes) => {
+                *self = Utf8Bytes::try_from(Bytes::from(bytes.clone()))
+                    .map_err(|_| TextFormatError::new("invalid UTF-8 in string"))?;
+                Ok(())
+            }
+            _ => Err(unexpected("string", value)),
+        }
+    }
+}
+
+impl TextField for Vec<u8> {
+    fn to_text_value(&self) -> TextValue {
+        TextValue::String(self.clone())
+    }
+
+    fn merge_text_value(&mut self, value: &TextValue) -> Result<(), TextFormatError> {
+        match value {
+            TextValue::String(bytes) => {
+                self.clone_from(bytes);
+                Ok(())
+            }
+            _ => Err(unexpected("string", value)),
+        }
+    }
+}
+
+impl TextField for Bytes {
+    fn to_text_value(&self) -> TextValue {
+        TextValue::String(self.to_vec())
+    }
+
+    fn merge_text_value(&mut self, value: &TextValue) -> Result<(), TextFormatError> {
+        match value {
+            TextValue::String(bytes) => {
+                *self = Bytes::from(bytes.clone());
+                Ok(())
+            }
+            _ => Err(unexpected("string", value)),
+        }
+    }
+}
+
+/// `google.protobuf.Empty`, which has no fields.
+impl TextField for () {
+    fn to_text_value(&self) -> TextValue {
+        TextValue::Message(Vec::new())
+    }
+
+    fn merge_text_value(&mut self, value: &TextValue) -> Result<(), TextFormatError> {
+        match value {
+            TextValue::Message(fields) => match fields.first() {
+                Some((name, _)) => Err(TextFormatError::new(format!(
+                    "unknown field `{name}` in Empty"
+                ))),
+                None => Ok(()),
+            },
+            _ => Err(unexpected("message", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn from_text<T: TextField + Default>(text: &str) -> Result<T, TextFormatError> {
+        T::from_text_value(&TextValue::Scalar(text.to_string()))
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(from_text::<i32>("-12"), Ok(-12));
+        assert_eq!(from_text::<i32>("0x1f"), Ok(31));
+        assert_eq!(from_text::<i32>("-0X1F"), Ok(-31));
+        assert_eq!(from_text::<i32>("017"), Ok(15));
+        assert_eq!(from_text::<i32>("0"), Ok(0));
+        assert_eq!(from_text::<u64>("18446744073709551615"), Ok(u64::MAX));
+        assert_eq!(from_text::<i64>("-9223372036854775808"), Ok(i64::MIN));
+        assert!(from_text::<u32>("-1").is_err());
+        assert!(from_text::<i32>("2147483648").is_err());
+        assert!(from_text::<i32>("1.0").is_err());
+        assert!(from_text::<i32>("--1").is_err());
+        assert!(from_text::<i32>("08").is_err());
+        assert!(from_text::<i32>("FOO").is_err());
+        assert!(i32::from_text_value(&TextValue::String(b"1".to_vec())).is_err());
+        assert_eq!((-7i64).to_text_value(), TextValue::Scalar("-7".to_string()));
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(from_text::<f64>("1.5"), Ok(1.5));
+        assert_eq!(from_text::<f64>("-.5e1"), Ok(-5.0));
+        assert_eq!(from_text::<f32>("2f"), Ok(2.0));
+        assert_eq!(from_text::<f64>("-Infinity"), Ok(f64::NEG_INFINITY));
+        assert_eq!(from_text::<f32>("inf"), Ok(f32::INFINITY));
+        assert!(from_text::<f64>("nan").unwrap().is_nan());
+        assert!(from_text::<f64>("1..0").is_err());
+        assert!(from_text::<f64>("e5").is_err());
+        for (value, text) in [
+            (1.5, "1.5"),
+            (100.0, "100.0"),
+            (1e300, "1e300"),
+            (f64::NEG_INFINITY, "-inf"),
+            (f64::NAN, "nan"),
+        ] {
+            assert_eq!(value.to_text_value(), TextValue::Scalar(text.to_string()));
+        }
+        assert_eq!(0.1f32.to_text_value(), TextValue::Scalar("0.1".to_string()));
+    }
+
+    #[test]
+    fn booleans() {
+        assert_eq!(from_text::<bool>("t"), Ok(true));
+        assert_eq!(from_text::<bool>("False"), Ok(false));
+        assert_eq!(from_text::<bool>("1"), Ok(true));
+        assert!(from_text::<bool>("yes").is_err());
+    }
+
+    #[test]
+    fn strings() {
+        let value = TextValue::String(b"\xff".to_vec());
+        assert!(String::from_text_value(&value).is_err());
+        assert_eq!(Vec::<u8>::from_text_value(&value), Ok(b"\xff".to_vec()));
+        assert_eq!(
+            Bytes::from_text_value(&value),
+            Ok(Bytes::from_static(b"\xff"))
+        );
+        assert!(String::from_text_value(&TextValue::Scalar("a".to_string())).is_err());
+    }
+}
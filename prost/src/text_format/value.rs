@@ -0,0 +1,559 @@
+//! An in-memory text format document, with a parser and a printer.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use super::TextFormatError;
+
+/// The fields of a text format message, in document order.
+///
+/// A repeated field appears once per element; the list syntax `name: [a, b]` is flattened when
+/// parsing.
+pub type TextMessage = Vec<(String, TextValue)>;
+
+/// The value of a field in the text format.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextValue {
+    /// An identifier or a number, as it appears in the document, including any leading `-`.
+    ///
+    /// This holds enumeration value names, booleans, and the special floating point values such
+    /// as `inf`, as well as numbers.
+    Scalar(String),
+    /// A string literal, after concatenating adjacent literals and resolving escape sequences.
+    String(Vec<u8>),
+    /// A message.
+    Message(TextMessage),
+}
+
+/// The maximum nesting depth of messages accepted by the parser.
+const NESTING_LIMIT: u32 = 100;
+
+impl TextValue {
+    /// Returns a short description of the kind of value, for error messages.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            TextValue::Scalar(_) => "scalar",
+            TextValue::String(_) => "string",
+            TextValue::Message(_) => "message",
+        }
+    }
+}
+
+/// Parses the fields of a message from the text format.
+pub(crate) fn parse(s: &str) -> Result<TextMessage, TextFormatError> {
+    let mut parser = Parser {
+        input: s.as_bytes(),
+        pos: 0,
+    };
+    parser.parse_fields(None, NESTING_LIMIT)
+}
+
+/// Prints the fields of a message in the text format, with one field per line.
+pub(crate) fn print(message: &TextMessage) -> String {
+    let mut out = String::new();
+    print_fields(&mut out, message, 0);
+    out
+}
+
+fn print_fields(out: &mut String, message: &TextMessage, indent: usize) {
+    for (name, value) in message {
+        for _ in 0..indent {
+            out.push_str("  ");
+        }
+        out.push_str(name);
+        match value {
+            TextValue::Scalar(value) => {
+                out.push_str(": ");
+                out.push_str(value);
+            }
+            TextValue::String(value) => {
+                out.push_str(": ");
+                write_string(out, value);
+            }
+            TextValue::Message(message) => {
+                out.push_str(" {\n");
+                print_fields(out, message, indent + 1);
+                for _ in 0..indent {
+                    out.push_str("  ");
+                }
+                out.push('}');
+            }
+        }
+        out.push('\n');
+    }
+}
+
+/// Prints the fields of a message in the text format on a single line, with the fields separated
+/// by spaces, as in the short debug strings of other Protobuf runtimes.
+pub(crate) fn print_short(message: &TextMessage) -> String {
+    let mut out = String::new();
+    print_fields_short(&mut out, message);
+    out
+}
+
+fn print_fields_short(out: &mut String, message: &TextMessage) {
+    for (index, (name, value)) in message.iter().enumerate() {
+        if index > 0 {
+            out.push(' ');
+        }
+        out.push_str(name);
+        match value {
+            TextValue::Scalar(value) => {
+                out.push_str(": ");
+                out.push_str(value);
+            }
+            TextValue::String(value) => {
+                out.push_str(": ");
+                write_string(out, value);
+            }
+            TextValue::Message(message) => {
+                out.push_str(" { ");
+                print_fields_short(out, message);
+                if !message.is_empty() {
+                    out.push(' ');
+                }
+                out.push('}');
+            }
+        }
+    }
+}
+
+/// Writes a string literal. Valid UTF-8 is written as is, apart from escapes for quotes,
+/// backslashes and control characters; otherwise, non-ASCII bytes are escaped as well.
+fn write_string(out: &mut String, value: &[u8]) {
+    out.push('"');
+    match core::str::from_utf8(value) {
+        Ok(value) => value.chars().for_each(|c| write_char(out, c)),
+        Err(_) => {
+            for &byte in value {
+                if byte.is_ascii() {
+                    write_char(out, byte as char);
+                } else {
+                    let _ = write!(out, "\\{byte:03o}");
+                }
+            }
+        }
+    }
+    out.push('"');
+}
+
+fn write_char(out: &mut String, c: char) {
+    match c {
+        '"' => out.push_str("\\\""),
+        '\'' => out.push_str("\\'"),
+        '\\' => out.push_str("\\\\"),
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\t' => out.push_str("\\t"),
+        c if c < ' ' || c == '\u{7f}' => {
+            let _ = write!(out, "\\{:03o}", c as u32);
+        }
+        c => out.push(c),
+    }
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, description: &str) -> TextFormatError {
+        let consumed = &self.input[..self.pos];
+        let line = consumed.iter().filter(|&&byte| byte == b'\n').count() + 1;
+        let line_start = consumed
+            .iter()
+            .rposition(|&byte| byte == b'\n')
+            .map_or(0, |index| index + 1);
+        TextFormatError::new(format!(
+            "invalid text format: {description} at line {line}, column {}",
+            self.pos - line_start + 1
+        ))
+    }
+
+    /// Skips whitespace and comments, and returns the next byte.
+    fn peek(&mut self) -> Option<u8> {
+        loop {
+            match self.input.get(self.pos) {
+                Some(b' ' | b'\t' | b'\n' | b'\r' | b'\x0b' | b'\x0c') => self.pos += 1,
+                Some(b'#') => {
+                    while let Some(&byte) = self.input.get(self.pos) {
+                        self.pos += 1;
+                        if byte == b'\n' {
+                            break;
+                        }
+                    }
+                }
+                byte => return byte.copied(),
+            }
+        }
+    }
+
+    /// Consumes the next byte if it is `byte`.
+    fn consume(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Parses fields until `end`, or until the end of the input if `end` is `None`.
+    fn parse_fields(
+        &mut self,
+        end: Option<u8>,
+        depth: u32,
+    ) -> Result<TextMessage, TextFormatError> {
+        let mut message = TextMessage::new();
+        loop {
+            match self.peek() {
+                None if end.is_none() => return Ok(message),
+                None => return Err(self.error("unexpected end of input")),
+                byte if byte == end => {
+                    self.pos += 1;
+                    return Ok(message);
+                }
+                _ => (),
+            }
+
+            let name = self.parse_field_name()?;
+            let colon = self.consume(b':');
+            match self.peek() {
+                Some(b'{' | b'<') => {
+                    let value = self.parse_message(depth)?;
+                    message.push((name, value));
+                }
+                Some(b'[') => {
+                    self.pos += 1;
+                    if !self.consume(b']') {
+                        loop {
+                            let value = match self.peek() {
+                                Some(b'{' | b'<') => self.parse_message(depth)?,
+                                _ if colon => self.parse_scalar()?,
+                                _ => return Err(self.error("expected message")),
+                            };
+                            message.push((name.clone(), value));
+                            if self.consume(b']') {
+                                break;
+                            }
+                            if !self.consume(b',') {
+                                return Err(self.error("expected `,` or `]`"));
+                            }
+                        }
+                    }
+                }
+                _ if colon => {
+                    let value = self.parse_scalar()?;
+                    message.push((name, value));
+                }
+                _ => return Err(self.error("expected `:`")),
+            }
+
+            if !self.consume(b';') {
+                self.consume(b',');
+            }
+        }
+    }
+
+    fn parse_message(&mut self, depth: u32) -> Result<TextValue, TextFormatError> {
+        let end = match self.peek() {
+            Some(b'{') => b'}',
+            _ => b'>',
+        };
+        if depth == 0 {
+            return Err(self.error("nesting limit exceeded"));
+        }
+        self.pos += 1;
+        self.parse_fields(Some(end), depth - 1)
+            .map(TextValue::Message)
+    }
+
+    /// Parses a field name, which is either an identifier or an extension or `Any` type name in
+    /// square brackets.
+    fn parse_field_name(&mut self) -> Result<String, TextFormatError> {
+        if !self.consume(b'[') {
+            return match self.parse_identifier() {
+                Some(name) => Ok(name.into()),
+                None => Err(self.error("expected field name")),
+            };
+        }
+        let mut name = String::from("[");
+        loop {
+            match self.peek() {
+                Some(b']') => break,
+                Some(byte @ (b'.' | b'/')) => {
+                    self.pos += 1;
+                    name.push(byte as char);
+                }
+                _ => match self.parse_identifier() {
+                    Some(part) => name.push_str(part),
+                    None => return Err(self.error("expected type name")),
+                },
+            }
+        }
+        self.pos += 1;
+        name.push(']');
+        Ok(name)
+    }
+
+    fn parse_identifier(&mut self) -> Option<&'a str> {
+        match self.peek() {
+            Some(byte) if byte.is_ascii_alphabetic() || byte == b'_' => (),
+            _ => return None,
+        }
+        let start = self.pos;
+        while let Some(&byte) = self.input.get(self.pos) {
+            if !(byte.is_ascii_alphanumeric() || byte == b'_') {
+                break;
+            }
+            self.pos += 1;
+        }
+        Some(self.slice(start))
+    }
+
+    /// Returns the input from `start` to the current position, which must be ASCII.
+    fn slice(&self, start: usize) -> &'a str {
+        let input = self.input;
+        core::str::from_utf8(&input[start..self.pos]).expect("ASCII token")
+    }
+
+    fn parse_scalar(&mut self) -> Result<TextValue, TextFormatError> {
+        match self.peek() {
+            Some(b'"' | b'\'') => {
+                let mut value = Vec::new();
+                while let Some(quote @ (b'"' | b'\'')) = self.peek() {
+                    self.pos += 1;
+                    self.parse_string(quote, &mut value)?;
+                }
+                Ok(TextValue::String(value))
+            }
+            Some(b'-') => {
+                self.pos += 1;
+                let token = self.parse_token()?;
+                Ok(TextValue::Scalar(format!("-{token}")))
+            }
+            _ => self
+                .parse_token()
+                .map(|token| TextValue::Scalar(token.into())),
+        }
+    }
+
+    /// Parses an identifier or a number.
+    fn parse_token(&mut self) -> Result<&'a str, TextFormatError> {
+        match self.peek() {
+            Some(byte) if byte.is_ascii_digit() || byte == b'.' => (),
+            _ => {
+                return self
+                    .parse_identifier()
+                    .ok_or_else(|| self.error("expected value"))
+            }
+        }
+        let start = self.pos;
+        let hex = self.input[start..].starts_with(b"0x") || self.input[start..].starts_with(b"0X");
+        while let Some(&byte) = self.input.get(self.pos) {
+            // A sign is only part of a number as the sign of an exponent.
+            let exponent_sign = (byte == b'-' || byte == b'+')
+                && !hex
+                && matches!(self.input[self.pos - 1], b'e' | b'E');
+            if !(byte.is_ascii_alphanumeric() || byte == b'.' || byte == b'_' || exponent_sign) {
+                break;
+            }
+            self.pos += 1;
+        }
+        Ok(self.slice(start))
+    }
+
+    /// Parses the remainder of a string literal delimited by `quote`, appending it to `value`.
+    fn parse_string(&mut self, quote: u8, value: &mut Vec<u8>) -> Result<(), TextFormatError> {
+        loop {
+            let byte = match self.input.get(self.pos) {
+                Some(&byte) => byte,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.pos += 1;
+            match byte {
+                byte if byte == quote => return Ok(()),
+                b'\n' => return Err(self.error("newline in string")),
+                b'\\' => self.parse_escape(value)?,
+                byte => value.push(byte),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self, value: &mut Vec<u8>) -> Result<(), TextFormatError> {
+        let byte = match self.input.get(self.pos) {
+            Some(&byte) => byte,
+            None => return Err(self.error("unterminated string")),
+        };
+        self.pos += 1;
+        let escaped = match byte {
+            b'n' => b'\n',
+            b't' => b'\t',
+            b'r' => b'\r',
+            b'a' => b'\x07',
+            b'b' => b'\x08',
+            b'f' => b'\x0c',
+            b'v' => b'\x0b',
+            b'\\' | b'\'' | b'"' | b'?' => byte,
+            b'0'..=b'7' => {
+                self.pos -= 1;
+                let code = self.parse_digits(8, 1, 3)?;
+                u8::try_from(code).map_err(|_| self.error("invalid octal escape"))?
+            }
+            b'x' | b'X' => self.parse_digits(16, 1, 2)? as u8,
+            b'u' | b'U' => {
+                let len = if byte == b'u' { 4 } else { 8 };
+                let code = self.parse_digits(16, len, len)?;
+                let c = char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))?;
+                value.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                return Ok(());
+            }
+            _ => return Err(self.error("invalid escape sequence")),
+        };
+        value.push(escaped);
+        Ok(())
+    }
+
+    /// Parses between `min` and `max` digits in `radix`.
+    fn parse_digits(&mut self, radix: u32, min: usize, max: usize) -> Result<u32, TextFormatError> {
+        let mut code = 0;
+        let mut len = 0;
+        while len < max {
+            match self
+                .input
+                .get(self.pos)
+                .and_then(|&byte| (byte as char).to_digit(radix))
+            {
+                Some(digit) => {
+                    code = code * radix + digit;
+                    self.pos += 1;
+                    len += 1;
+                }
+                None => break,
+            }
+        }
+        if len < min {
+            return Err(self.error("invalid escape sequence"));
+        }
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    fn scalar(value: &str) -> TextValue {
+        TextValue::Scalar(value.to_string())
+    }
+
+    fn field(name: &str, value: TextValue) -> (String, TextValue) {
+        (name.to_string(), value)
+    }
+
+    #[test]
+    fn parse_fields() {
+        let message = parse(
+            r#"
+            # A comment.
+            int: -12 float: 1.5e-3f; enum: FOO,
+            str: "a\n" 'b\'\x41\101\u00e9'
+            nested { inner: 1 } other < >
+            list: [1, -inf] messages [{}, <a: 0x1F>]
+            [type.googleapis.com/foo.Bar]: { }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            message,
+            vec![
+                field("int", scalar("-12")),
+                field("float", scalar("1.5e-3f")),
+                field("enum", scalar("FOO")),
+                field("str", TextValue::String("a\nb'AAé".as_bytes().to_vec())),
+                field(
+                    "nested",
+                    TextValue::Message(vec![field("inner", scalar("1"))])
+                ),
+                field("other", TextValue::Message(vec![])),
+                field("list", scalar("1")),
+                field("list", scalar("-inf")),
+                field("messages", TextValue::Message(vec![])),
+                field(
+                    "messages",
+                    TextValue::Message(vec![field("a", scalar("0x1F"))])
+                ),
+                field("[type.googleapis.com/foo.Bar]", TextValue::Message(vec![])),
+            ]
+        );
+        assert_eq!(parse("").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        for input in [
+            "a",
+            "a:",
+            "a 1",
+            "a: {",
+            "a { b: 1 >",
+            "a: \"unterminated",
+            "a: \"new\nline\"",
+            "a: \"\\q\"",
+            "a: \"\\400\"",
+            "a: \"\\ud800\"",
+            "a: [1 2]",
+            "a [1]",
+            "1: 2",
+            "a: }",
+        ] {
+            assert!(parse(input).is_err(), "{input}");
+        }
+        assert_eq!(
+            parse("a: 1\n  b 2").unwrap_err().to_string(),
+            "failed to process Protobuf text format: invalid text format: expected `:` at line 2, \
+             column 5"
+        );
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth| "a {".repeat(depth) + &"}".repeat(depth);
+        assert!(parse(&nested(NESTING_LIMIT as usize)).is_ok());
+        assert!(parse(&nested(NESTING_LIMIT as usize + 1)).is_err());
+    }
+
+    #[test]
+    fn print_fields() {
+        let message = vec![
+            field("a", scalar("1")),
+            field(
+                "b",
+                TextValue::Message(vec![
+                    field("c", TextValue::String(b"q\"\x01\xff".to_vec())),
+                    field("d", TextValue::Message(vec![])),
+                ]),
+            ),
+            field("e", TextValue::String("é\t'".as_bytes().to_vec())),
+        ];
+        let text = print(&message);
+        assert_eq!(
+            text,
+            "a: 1\nb {\n  c: \"q\\\"\\001\\377\"\n  d {\n  }\n}\ne: \"é\\t\\'\"\n"
+        );
+        assert_eq!(parse(&text).unwrap(), message);
+
+        let text = print_short(&message);
+        assert_eq!(
+            text,
+            "a: 1 b { c: \"q\\\"\\001\\377\" d { } } e: \"é\\t\\'\""
+        );
+        assert_eq!(parse(&text).unwrap(), message);
+    }
+}
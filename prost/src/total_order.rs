@@ -0,0 +1,164 @@
+//! Support for comparing floats by their total order.
+//!
+//! `f32` and `f64` don't implement `Eq`, `Hash` and `Ord`, because `NaN` is not equal to itself.
+//! Messages generated by `prost-build` with `Config::float_total_order` instead compare their
+//! `float` and `double` fields with [`TotalOrd`], which orders floats like [`f64::total_cmp`],
+//! so that they can implement these traits.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+
+/// A total order of values holding floats, consistent with their equality and hash.
+///
+/// Floats are equal only if their bits are equal, so `NaN` equals itself, `-0.0` and `0.0` differ,
+/// and they are ordered like [`f64::total_cmp`]. Options, vectors and the values of maps are
+/// compared like the corresponding `Ord` implementations of the standard library.
+///
+/// # Examples
+///
+/// ```rust
+/// use core::cmp::Ordering;
+/// use prost::total_order::TotalOrd;
+///
+/// assert!(f64::NAN.total_eq(&f64::NAN));
+/// assert!(!(-0.0f64).total_eq(&0.0));
+/// assert_eq!(vec![1.0f32, f32::NAN].total_cmp(&vec![1.0, f32::INFINITY]), Ordering::Greater);
+/// ```
+pub trait TotalOrd {
+    /// Returns `true` if the values are equal in the total order.
+    fn total_eq(&self, other: &Self) -> bool;
+
+    /// Compares the values in the total order.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// Feeds the value into the hasher, so that values which are equal in the total order have
+    /// the same hash.
+    fn total_hash<H: Hasher>(&self, state: &mut H);
+}
+
+macro_rules! impl_float {
+    ($ty:ty) => {
+        impl TotalOrd for $ty {
+            fn total_eq(&self, other: &Self) -> bool {
+                self.to_bits() == other.to_bits()
+            }
+
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                <$ty>::total_cmp(self, other)
+            }
+
+            fn total_hash<H: Hasher>(&self, state: &mut H) {
+                self.to_bits().hash(state);
+            }
+        }
+    };
+}
+
+impl_float!(f32);
+impl_float!(f64);
+
+impl<T: TotalOrd> TotalOrd for Option<T> {
+    fn total_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.total_eq(b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Some(a), Some(b)) => a.total_cmp(b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        }
+    }
+
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.is_some().hash(state);
+        if let Some(value) = self {
+            value.total_hash(state);
+        }
+    }
+}
+
+impl<T: TotalOrd> TotalOrd for Vec<T> {
+    fn total_eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.total_eq(b))
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.iter()
+            .zip(other)
+            .map(|(a, b)| a.total_cmp(b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| self.len().cmp(&other.len()))
+    }
+
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for value in self {
+            value.total_hash(state);
+        }
+    }
+}
+
+impl<K: Ord + Hash, V: TotalOrd> TotalOrd for BTreeMap<K, V> {
+    fn total_eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other)
+                .all(|((k1, v1), (k2, v2))| k1 == k2 && v1.total_eq(v2))
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.iter()
+            .zip(other)
+            .map(|((k1, v1), (k2, v2))| k1.cmp(k2).then_with(|| v1.total_cmp(v2)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| self.len().cmp(&other.len()))
+    }
+
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for (key, value) in self {
+            key.hash(state);
+            value.total_hash(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn floats() {
+        assert!(f64::NAN.total_eq(&f64::NAN));
+        assert!(!0.0f64.total_eq(&-0.0));
+        assert_eq!((-0.0f32).total_cmp(&0.0), Ordering::Less);
+        assert_eq!(1.0f64.total_cmp(&f64::NAN), Ordering::Less);
+    }
+
+    #[test]
+    fn collections() {
+        assert_eq!(None.total_cmp(&Some(f64::NEG_INFINITY)), Ordering::Less);
+        assert!(Some(f32::NAN).total_eq(&Some(f32::NAN)));
+
+        assert_eq!(vec![1.0f64].total_cmp(&vec![1.0, 0.0]), Ordering::Less);
+        assert_eq!(vec![2.0f64].total_cmp(&vec![1.0, 0.0]), Ordering::Greater);
+        assert!(!vec![1.0f64].total_eq(&vec![1.0, 0.0]));
+
+        let a = BTreeMap::from([(1, 0.5f64), (2, f64::NAN)]);
+        let mut b = a.clone();
+        assert!(a.total_eq(&b));
+        b.insert(2, 0.5);
+        assert_eq!(a.total_cmp(&b), Ordering::Greater);
+        b.remove(&2);
+        assert_eq!(a.total_cmp(&b), Ordering::Greater);
+    }
+}
@@ -0,0 +1,140 @@
+//! Instrumentation of encoding and decoding with [`tracing`](::tracing), behind the `tracing`
+//! feature.
+//!
+//! The top-level encoding and decoding methods of [`Message`](crate::Message) run in a
+//! `TRACE`-level span named `prost::encode` or `prost::decode`, whose `message` field is the Rust
+//! type name of the message and whose `len` field is the length of the encoded message. The time
+//! spent on a message is the duration of its span. A failed decode emits a `DEBUG`-level event
+//! within the span, with the error in its `error` field.
+//!
+//! Without the feature, these functions only call the closure.
+
+use crate::DecodeError;
+
+/// Runs the encoding of a message of type `M`, of length `len`, in a span.
+#[inline]
+#[cfg_attr(not(feature = "tracing"), allow(clippy::extra_unused_type_parameters))]
+pub(crate) fn encode<M, R>(len: usize, encode: impl FnOnce() -> R) -> R
+where
+    M: ?Sized,
+{
+    #[cfg(feature = "tracing")]
+    let _span = tracing::trace_span!(
+        target: "prost",
+        "prost::encode",
+        message = core::any::type_name::<M>(),
+        len,
+    )
+    .entered();
+    #[cfg(not(feature = "tracing"))]
+    let _ = len;
+    encode()
+}
+
+/// Runs the decoding of a message of type `M`, from `len` bytes, in a span, and reports its
+/// error.
+#[inline]
+#[cfg_attr(not(feature = "tracing"), allow(clippy::extra_unused_type_parameters))]
+pub(crate) fn decode<M, R>(
+    len: usize,
+    decode: impl FnOnce() -> Result<R, DecodeError>,
+) -> Result<R, DecodeError>
+where
+    M: ?Sized,
+{
+    #[cfg(feature = "tracing")]
+    let _span = tracing::trace_span!(
+        target: "prost",
+        "prost::decode",
+        message = core::any::type_name::<M>(),
+        len,
+    )
+    .entered();
+    #[cfg(not(feature = "tracing"))]
+    let _ = len;
+    let result = decode();
+    #[cfg(feature = "tracing")]
+    if let Err(ref error) = result {
+        tracing::debug!(target: "prost", error = %error, "failed to decode Protobuf message");
+    }
+    result
+}
+
+#[cfg(all(test, feature = "tracing", feature = "std"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use crate::Message;
+
+    /// Records the names and fields of the spans and events.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn core::fmt::Debug) {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Fields(span.metadata().name().to_string());
+            span.record(&mut fields);
+            let mut recorded = self.0.lock().unwrap();
+            recorded.push(fields.0);
+            Id::from_u64(recorded.len() as u64)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields("event".to_string());
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn spans_and_events() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let buf = "hello".to_string().encode_to_vec();
+            String::decode(buf.as_slice()).unwrap();
+            String::decode(&buf[..3]).unwrap_err();
+        });
+
+        let recorded = recorder.0.lock().unwrap();
+        assert_eq!(
+            recorded[0],
+            "prost::encode message=\"alloc::string::String\" len=7"
+        );
+        assert_eq!(
+            recorded[1],
+            "prost::decode message=\"alloc::string::String\" len=7"
+        );
+        assert_eq!(
+            recorded[2],
+            "prost::decode message=\"alloc::string::String\" len=3"
+        );
+        assert!(recorded[3].starts_with("event "));
+        assert!(recorded[3].contains("message=failed to decode Protobuf message"));
+        assert!(recorded[3].contains("error=failed to decode Protobuf message: "));
+        assert_eq!(recorded.len(), 4);
+    }
+}
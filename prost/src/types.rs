@@ -16,7 +16,7 @@ use crate::{
     encoding::{
         bool, bytes, double, float, int32, int64, skip_field, string, uint32, uint64, DecodeContext,
     },
-    DecodeError, Message, Name,
+    DecodeError, Message, Name, Utf8Bytes,
 };
 
 /// `google.protobuf.BoolValue`
@@ -355,6 +355,48 @@ impl Name for String {
     }
 }
 
+/// `google.protobuf.StringValue`
+impl Message for Utf8Bytes {
+    fn encode_raw(&self, buf: &mut impl BufMut) {
+        if !self.is_empty() {
+            string::encode(1, self, buf)
+        }
+    }
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        if tag == 1 {
+            string::merge(wire_type, self, buf, ctx)
+        } else {
+            skip_field(wire_type, tag, buf, ctx)
+        }
+    }
+    fn encoded_len(&self) -> usize {
+        if !self.is_empty() {
+            string::encoded_len(1, self)
+        } else {
+            0
+        }
+    }
+    fn clear(&mut self) {
+        self.clear();
+    }
+}
+
+/// `google.protobuf.StringValue`
+impl Name for Utf8Bytes {
+    const NAME: &'static str = "StringValue";
+    const PACKAGE: &'static str = "google.protobuf";
+
+    fn type_url() -> String {
+        googleapis_type_url_for::<Self>()
+    }
+}
+
 /// `google.protobuf.BytesValue`
 impl Message for Vec<u8> {
     fn encode_raw(&self, buf: &mut impl BufMut) {
@@ -0,0 +1,272 @@
+//! Storage for fields which are not known to a message type.
+//!
+//! Messages opt in to unknown field preservation by adding a field of type [`UnknownFieldSet`]
+//! annotated with `#[prost(unknown_fields)]`. Any field with a tag that is not otherwise handled
+//! by the message is captured into the set while decoding, and written back out after the known
+//! fields while encoding.
+
+use alloc::vec::Vec;
+use core::slice;
+
+use ::bytes::{Buf, BufMut};
+
+use crate::encoding::{
+    decode_varint, encode_key, encode_varint, encoded_len_varint, group, key_len, DecodeContext,
+    WireType,
+};
+use crate::error::DecodeErrorKind;
+use crate::{DecodeError, Message};
+
+/// A set of fields which were encountered while decoding, but are not known to the message type.
+///
+/// Fields are kept in the order they were decoded, and are re-encoded verbatim (modulo varint
+/// normalization), so that a decode/encode round-trip through an older schema does not lose data
+/// added by a newer one.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct UnknownFieldSet {
+    fields: Vec<UnknownField>,
+}
+
+/// A single unknown field.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnknownField {
+    /// The field number.
+    pub tag: u32,
+    /// The field value.
+    pub value: UnknownFieldValue,
+}
+
+/// The value of an unknown field, according to its wire type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum UnknownFieldValue {
+    /// A varint encoded value.
+    Varint(u64),
+    /// A 64-bit fixed width value.
+    SixtyFourBit([u8; 8]),
+    /// A length-delimited value.
+    LengthDelimited(Vec<u8>),
+    /// A group, containing nested fields.
+    Group(UnknownFieldSet),
+    /// A 32-bit fixed width value.
+    ThirtyTwoBit([u8; 4]),
+}
+
+impl UnknownFieldSet {
+    /// Creates an empty set of unknown fields.
+    pub const fn new() -> UnknownFieldSet {
+        UnknownFieldSet { fields: Vec::new() }
+    }
+
+    /// Returns the number of unknown fields in the set.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Returns `true` if the set contains no unknown fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns an iterator over the unknown fields, in the order they were decoded.
+    pub fn iter(&self) -> slice::Iter<'_, UnknownField> {
+        self.fields.iter()
+    }
+
+    /// Appends a field to the set.
+    pub fn push(&mut self, tag: u32, value: UnknownFieldValue) {
+        self.fields.push(UnknownField { tag, value });
+    }
+
+    /// Retains only the fields for which `f` returns `true`.
+    pub(crate) fn retain(&mut self, f: impl FnMut(&UnknownField) -> bool) {
+        self.fields.retain(f);
+    }
+}
+
+impl<'a> IntoIterator for &'a UnknownFieldSet {
+    type Item = &'a UnknownField;
+    type IntoIter = slice::Iter<'a, UnknownField>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl UnknownFieldValue {
+    pub(crate) fn wire_type(&self) -> WireType {
+        match self {
+            UnknownFieldValue::Varint(_) => WireType::Varint,
+            UnknownFieldValue::SixtyFourBit(_) => WireType::SixtyFourBit,
+            UnknownFieldValue::LengthDelimited(_) => WireType::LengthDelimited,
+            UnknownFieldValue::Group(_) => WireType::StartGroup,
+            UnknownFieldValue::ThirtyTwoBit(_) => WireType::ThirtyTwoBit,
+        }
+    }
+}
+
+impl UnknownField {
+    pub(crate) fn encode(&self, buf: &mut impl BufMut) {
+        match &self.value {
+            UnknownFieldValue::Group(fields) => group::encode(self.tag, fields, buf),
+            value => {
+                encode_key(self.tag, value.wire_type(), buf);
+                match value {
+                    UnknownFieldValue::Varint(value) => encode_varint(*value, buf),
+                    UnknownFieldValue::SixtyFourBit(value) => buf.put_slice(value),
+                    UnknownFieldValue::LengthDelimited(value) => {
+                        encode_varint(value.len() as u64, buf);
+                        buf.put_slice(value);
+                    }
+                    UnknownFieldValue::ThirtyTwoBit(value) => buf.put_slice(value),
+                    UnknownFieldValue::Group(_) => unreachable!(),
+                }
+            }
+        }
+    }
+
+    /// Encodes the field, with the fields of a group in the order of their field numbers.
+    fn encode_deterministic(&self, buf: &mut impl BufMut) {
+        match &self.value {
+            UnknownFieldValue::Group(fields) => group::encode_deterministic(self.tag, fields, buf),
+            _ => self.encode(buf),
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        match &self.value {
+            UnknownFieldValue::Varint(value) => key_len(self.tag) + encoded_len_varint(*value),
+            UnknownFieldValue::SixtyFourBit(_) => key_len(self.tag) + 8,
+            UnknownFieldValue::LengthDelimited(value) => {
+                key_len(self.tag) + encoded_len_varint(value.len() as u64) + value.len()
+            }
+            UnknownFieldValue::Group(fields) => group::encoded_len(self.tag, fields),
+            UnknownFieldValue::ThirtyTwoBit(_) => key_len(self.tag) + 4,
+        }
+    }
+}
+
+impl Message for UnknownFieldSet {
+    fn encode_raw(&self, buf: &mut impl BufMut) {
+        for field in &self.fields {
+            field.encode(buf);
+        }
+    }
+
+    /// Encodes the fields in the order of their field numbers. Fields with the same number keep
+    /// the order in which they were decoded, which is significant for repeated fields.
+    fn encode_raw_deterministic(&self, buf: &mut impl BufMut) {
+        let mut fields = self.fields.iter().collect::<Vec<_>>();
+        fields.sort_by_key(|field| field.tag);
+        for field in fields {
+            field.encode_deterministic(buf);
+        }
+    }
+
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        let value = match wire_type {
+            WireType::Varint => UnknownFieldValue::Varint(decode_varint(buf)?),
+            WireType::SixtyFourBit => {
+                let mut value = [0; 8];
+                if buf.remaining() < value.len() {
+                    return Err(DecodeErrorKind::BufferUnderflow.into());
+                }
+                buf.copy_to_slice(&mut value);
+                UnknownFieldValue::SixtyFourBit(value)
+            }
+            WireType::LengthDelimited => {
+                let len = decode_varint(buf)?;
+                ctx.check_len(len)?;
+                if len > buf.remaining() as u64 {
+                    return Err(DecodeErrorKind::BufferUnderflow.into());
+                }
+                let mut value = Vec::with_capacity(len as usize);
+                value.put(buf.take(len as usize));
+                UnknownFieldValue::LengthDelimited(value)
+            }
+            WireType::StartGroup => {
+                let mut fields = UnknownFieldSet::default();
+                group::merge(tag, wire_type, &mut fields, buf, ctx)?;
+                UnknownFieldValue::Group(fields)
+            }
+            WireType::EndGroup => return Err(DecodeErrorKind::UnexpectedEndGroupTag.into()),
+            WireType::ThirtyTwoBit => {
+                let mut value = [0; 4];
+                if buf.remaining() < value.len() {
+                    return Err(DecodeErrorKind::BufferUnderflow.into());
+                }
+                buf.copy_to_slice(&mut value);
+                UnknownFieldValue::ThirtyTwoBit(value)
+            }
+        };
+        self.push(tag, value);
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.fields.iter().map(UnknownField::encoded_len).sum()
+    }
+
+    fn merge_from(&mut self, other: &Self) {
+        self.fields.extend(other.fields.iter().cloned());
+    }
+
+    fn clear(&mut self) {
+        self.fields.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip_all_wire_types() {
+        let mut fields = UnknownFieldSet::new();
+        fields.push(1, UnknownFieldValue::Varint(150));
+        fields.push(2, UnknownFieldValue::SixtyFourBit([1, 2, 3, 4, 5, 6, 7, 8]));
+        fields.push(3, UnknownFieldValue::LengthDelimited(b"testing".to_vec()));
+        let mut nested = UnknownFieldSet::new();
+        nested.push(5, UnknownFieldValue::Varint(1));
+        fields.push(4, UnknownFieldValue::Group(nested));
+        fields.push(6, UnknownFieldValue::ThirtyTwoBit([9, 10, 11, 12]));
+        // Repeated tags are kept in decode order.
+        fields.push(1, UnknownFieldValue::Varint(3));
+
+        let buf = fields.encode_to_vec();
+        assert_eq!(buf.len(), fields.encoded_len());
+
+        let decoded = UnknownFieldSet::decode(buf.as_slice()).unwrap();
+        assert_eq!(decoded, fields);
+        assert_eq!(decoded.len(), 6);
+        assert_eq!(decoded.encode_to_vec(), buf);
+    }
+
+    #[test]
+    fn decode_errors() {
+        // Truncated length-delimited value.
+        assert!(UnknownFieldSet::decode(&[0x0a, 0x05, 0x01][..]).is_err());
+        // Truncated fixed width value.
+        assert!(UnknownFieldSet::decode(&[0x0d, 0x01][..]).is_err());
+        // Mismatched end group tag.
+        assert!(UnknownFieldSet::decode(&[0x0b, 0x14][..]).is_err());
+        // Unexpected end group tag.
+        assert!(UnknownFieldSet::decode(&[0x0c][..]).is_err());
+    }
+
+    #[test]
+    fn clear() {
+        let mut fields = UnknownFieldSet::decode(&vec![0x08, 0x96, 0x01][..]).unwrap();
+        assert!(!fields.is_empty());
+        fields.clear();
+        assert!(fields.is_empty());
+        assert_eq!(fields.encoded_len(), 0);
+    }
+}
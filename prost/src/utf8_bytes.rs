@@ -0,0 +1,240 @@
+//! A UTF-8 string backed by [`Bytes`].
+
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+use core::{fmt, str};
+
+use bytes::Bytes;
+
+/// An immutable UTF-8 string backed by [`Bytes`].
+///
+/// `Utf8Bytes` is used for Protobuf `string` fields configured with
+/// [`Config::utf8_bytes`][1]. When a message is decoded from a [`Bytes`] buffer, such fields share
+/// the memory of the buffer instead of being copied into a new allocation, and only the UTF-8
+/// validity of the field is checked.
+///
+/// `Utf8Bytes` dereferences to [`str`], and cloning it is cheap.
+///
+/// [1]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.utf8_bytes
+#[derive(Clone, Default)]
+pub struct Utf8Bytes(Bytes);
+
+impl Utf8Bytes {
+    /// Creates an empty string.
+    pub const fn new() -> Utf8Bytes {
+        Utf8Bytes(Bytes::new())
+    }
+
+    /// Creates a string from a static string slice, without copying it.
+    pub const fn from_static(value: &'static str) -> Utf8Bytes {
+        Utf8Bytes(Bytes::from_static(value.as_bytes()))
+    }
+
+    /// Creates a string by copying a string slice.
+    pub fn copy_from_str(value: &str) -> Utf8Bytes {
+        Utf8Bytes(Bytes::copy_from_slice(value.as_bytes()))
+    }
+
+    /// Creates a string from bytes, without checking that they are valid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// The bytes must be valid UTF-8.
+    pub unsafe fn from_bytes_unchecked(bytes: Bytes) -> Utf8Bytes {
+        Utf8Bytes(bytes)
+    }
+
+    /// Returns the string as a string slice.
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the bytes are valid UTF-8, which is checked on construction.
+        unsafe { str::from_utf8_unchecked(&self.0) }
+    }
+
+    /// Returns the underlying bytes of the string.
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.0
+    }
+
+    /// Converts the string into its underlying bytes.
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
+
+    /// Truncates the string, removing all contents.
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+}
+
+impl Deref for Utf8Bytes {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Utf8Bytes {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<[u8]> for Utf8Bytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Utf8Bytes {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for Utf8Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Utf8Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for Utf8Bytes {
+    fn eq(&self, other: &Utf8Bytes) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Utf8Bytes {}
+
+impl PartialEq<str> for Utf8Bytes {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Utf8Bytes {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Utf8Bytes {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<Utf8Bytes> for str {
+    fn eq(&self, other: &Utf8Bytes) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<Utf8Bytes> for &str {
+    fn eq(&self, other: &Utf8Bytes) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<Utf8Bytes> for String {
+    fn eq(&self, other: &Utf8Bytes) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialOrd for Utf8Bytes {
+    fn partial_cmp(&self, other: &Utf8Bytes) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Utf8Bytes {
+    fn cmp(&self, other: &Utf8Bytes) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+// Hashes like `str`, as required by the `Borrow<str>` implementation.
+impl Hash for Utf8Bytes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl From<String> for Utf8Bytes {
+    fn from(value: String) -> Utf8Bytes {
+        Utf8Bytes(Bytes::from(value))
+    }
+}
+
+impl From<&'static str> for Utf8Bytes {
+    fn from(value: &'static str) -> Utf8Bytes {
+        Utf8Bytes::from_static(value)
+    }
+}
+
+impl From<Utf8Bytes> for Bytes {
+    fn from(value: Utf8Bytes) -> Bytes {
+        value.0
+    }
+}
+
+impl From<Utf8Bytes> for String {
+    fn from(value: Utf8Bytes) -> String {
+        String::from(value.as_str())
+    }
+}
+
+impl TryFrom<Bytes> for Utf8Bytes {
+    type Error = str::Utf8Error;
+
+    fn try_from(bytes: Bytes) -> Result<Utf8Bytes, str::Utf8Error> {
+        str::from_utf8(&bytes)?;
+        Ok(Utf8Bytes(bytes))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Utf8Bytes {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        String::arbitrary(u).map(Utf8Bytes::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        let value = Utf8Bytes::from_static("hello");
+        assert_eq!(value, "hello");
+        assert_eq!(value.len(), 5);
+        assert_eq!(value.to_string(), "hello");
+        assert_eq!(String::from(value.clone()), "hello");
+        assert_eq!(Bytes::from(value), Bytes::from_static(b"hello"));
+
+        let bytes = Bytes::from_static("π".as_bytes());
+        assert_eq!(Utf8Bytes::try_from(bytes.clone()).unwrap(), "π");
+        assert!(Utf8Bytes::try_from(bytes.slice(..1)).is_err());
+    }
+
+    #[test]
+    fn clear() {
+        let mut value = Utf8Bytes::from("hello".to_string());
+        value.clear();
+        assert!(value.is_empty());
+        assert_eq!(value, Utf8Bytes::new());
+    }
+}
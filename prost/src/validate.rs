@@ -0,0 +1,290 @@
+//! Validation of messages against the [`buf.validate`][1] rules of their Protobuf definitions.
+//!
+//! `prost-build` implements [`Validate`] for the messages selected with
+//! [`Config::validate_messages`][2], checking the rules set with the `(buf.validate.field)` and
+//! `(buf.validate.oneof)` options:
+//!
+//! ```proto
+//! message User {
+//!   string name = 1 [(buf.validate.field).string = { min_len: 1, pattern: "^[a-z]+$" }];
+//!   uint32 age = 2 [(buf.validate.field).uint32.lte = 150];
+//!   repeated string emails = 3 [(buf.validate.field).repeated.max_items = 3];
+//! }
+//! ```
+//!
+//! ```rust,ignore
+//! use prost::validate::Validate;
+//!
+//! if let Err(error) = user.validate() {
+//!     for violation in error.violations() {
+//!         println!("{violation}");
+//!     }
+//! }
+//! ```
+//!
+//! [1]: https://protovalidate.com/
+//! [2]: https://docs.rs/prost-build/latest/prost_build/struct.Config.html#method.validate_messages
+
+use std::error::Error;
+use std::fmt;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// A message type which checks the validation rules of its Protobuf definition.
+pub trait Validate {
+    /// Checks the rules of the message, and of the messages in its fields.
+    ///
+    /// All the rules are checked, and every violated rule is reported.
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+impl<T> Validate for Box<T>
+where
+    T: Validate,
+{
+    fn validate(&self) -> Result<(), ValidationError> {
+        (**self).validate()
+    }
+}
+
+impl<T> Validate for std::sync::Arc<T>
+where
+    T: Validate,
+{
+    fn validate(&self) -> Result<(), ValidationError> {
+        (**self).validate()
+    }
+}
+
+/// A lazy field is decoded to be validated, and a decoding error is reported as a violation.
+impl<T> Validate for crate::Lazy<T>
+where
+    T: Validate + crate::Message + Default,
+{
+    fn validate(&self) -> Result<(), ValidationError> {
+        match self.get() {
+            Ok(message) => message.validate(),
+            Err(error) => Err(ValidationError::new(vec![Violation::new(
+                "",
+                "prost.decode",
+                error.to_string(),
+            )])),
+        }
+    }
+}
+
+/// A validation rule which a message does not satisfy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    field_path: String,
+    rule_id: String,
+    message: String,
+}
+
+impl Violation {
+    /// Creates a violation of a rule.
+    pub fn new(
+        field_path: impl Into<String>,
+        rule_id: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Violation {
+        Violation {
+            field_path: field_path.into(),
+            rule_id: rule_id.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Returns the path of the field which violates the rule, such as `address.lines[2]`.
+    ///
+    /// The path is made of the Protobuf names of the fields, and is empty for violations of the
+    /// message itself.
+    pub fn field_path(&self) -> &str {
+        &self.field_path
+    }
+
+    /// Returns the identifier of the rule, such as `string.min_len`.
+    pub fn rule_id(&self) -> &str {
+        &self.rule_id
+    }
+
+    /// Returns the description of the violation.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.field_path.is_empty() {
+            write!(f, "{}: ", self.field_path)?;
+        }
+        write!(f, "{} [{}]", self.message, self.rule_id)
+    }
+}
+
+/// The violations of the validation rules of a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    violations: Vec<Violation>,
+}
+
+impl ValidationError {
+    /// Creates an error from the violations of a message, which must not be empty.
+    pub fn new(violations: Vec<Violation>) -> ValidationError {
+        debug_assert!(!violations.is_empty());
+        ValidationError { violations }
+    }
+
+    /// Returns the violations, in the order of the fields of the message.
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    /// Converts the error into its violations.
+    pub fn into_violations(self) -> Vec<Violation> {
+        self.violations
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to validate Protobuf message")?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            f.write_str(if i == 0 { ": " } else { "; " })?;
+            fmt::Display::fmt(violation, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ValidationError {}
+
+/// The regular expression of a `pattern` rule, which is compiled when it is first used.
+///
+/// Patterns are checked by `prost-build`, so generated code does not panic.
+pub struct Pattern {
+    pattern: &'static str,
+    regex: OnceLock<Regex>,
+}
+
+impl Pattern {
+    /// Creates a pattern.
+    pub const fn new(pattern: &'static str) -> Pattern {
+        Pattern {
+            pattern,
+            regex: OnceLock::new(),
+        }
+    }
+
+    /// Returns `true` if the pattern matches a part of the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is not a valid regular expression.
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex
+            .get_or_init(|| Regex::new(self.pattern).expect("invalid validation pattern"))
+            .is_match(value)
+    }
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Pattern").field(&self.pattern).finish()
+    }
+}
+
+/// Collects the violations of a message in `Validate` implementations.
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct Violations {
+    violations: Vec<Violation>,
+}
+
+impl Violations {
+    pub fn new() -> Violations {
+        Violations::default()
+    }
+
+    /// Adds a violation of the field.
+    pub fn push(
+        &mut self,
+        field_path: impl Into<String>,
+        rule_id: &str,
+        message: impl Into<String>,
+    ) {
+        self.violations
+            .push(Violation::new(field_path, rule_id, message));
+    }
+
+    /// Adds the violations of a message in the field.
+    pub fn nested(&mut self, field_path: &str, result: Result<(), ValidationError>) {
+        if let Err(error) = result {
+            self.violations
+                .extend(error.violations.into_iter().map(|mut violation| {
+                    violation.field_path = if violation.field_path.is_empty() {
+                        field_path.into()
+                    } else {
+                        format!("{field_path}.{}", violation.field_path)
+                    };
+                    violation
+                }));
+        }
+    }
+
+    pub fn finish(self) -> Result<(), ValidationError> {
+        if self.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError::new(self.violations))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nested_violations() {
+        let mut inner = Violations::new();
+        inner.push("name", "string.min_len", "must be at least 1 characters");
+        inner.push("", "oneof.required", "exactly one field is required");
+
+        let mut outer = Violations::new();
+        outer.nested("users[0]", inner.finish());
+        outer.nested("owner", Ok(()));
+        let error = outer.finish().unwrap_err();
+        assert_eq!(
+            error.violations(),
+            [
+                Violation::new(
+                    "users[0].name",
+                    "string.min_len",
+                    "must be at least 1 characters"
+                ),
+                Violation::new(
+                    "users[0]",
+                    "oneof.required",
+                    "exactly one field is required"
+                ),
+            ]
+        );
+        assert_eq!(
+            error.to_string(),
+            "failed to validate Protobuf message: users[0].name: must be at least 1 characters \
+             [string.min_len]; users[0]: exactly one field is required [oneof.required]"
+        );
+        assert_eq!(Violations::new().finish(), Ok(()));
+    }
+
+    #[test]
+    fn pattern() {
+        static PATTERN: Pattern = Pattern::new("^[a-z]+$");
+        assert!(PATTERN.is_match("abc"));
+        assert!(!PATTERN.is_match("ABC"));
+        assert!(!PATTERN.is_match(""));
+    }
+}
@@ -0,0 +1,410 @@
+//! Inspection of the Protobuf wire format.
+//!
+//! [`FieldIter`] iterates over the fields of an encoded message without decoding it into a
+//! message type, and [`debug_dump`] formats an encoded message in the style of
+//! `protoc --decode_raw`. They are meant for diagnosing malformed payloads, and for tools such as
+//! fuzzers and proxies which handle messages without knowing their types. [`FieldInfo`] describes
+//! the declared fields of a message type, to find them among the fields of an encoding.
+//!
+//! ```
+//! use prost::wire::{FieldIter, WireType};
+//!
+//! // Field 1 is the varint 150, and field 2 is the string "hello".
+//! let buf = [0x08, 0x96, 0x01, 0x12, 0x05, b'h', b'e', b'l', b'l', b'o'];
+//!
+//! let fields = FieldIter::new(&buf).collect::<Result<Vec<_>, _>>().unwrap();
+//! assert_eq!(fields[0].number, 1);
+//! assert_eq!(fields[0].as_varint(), Some(150));
+//! assert_eq!(fields[1].wire_type, WireType::LengthDelimited);
+//! assert_eq!(fields[1].value, b"hello");
+//!
+//! assert_eq!(prost::wire::debug_dump(&buf), "1: 150\n2: \"hello\"\n");
+//! ```
+
+use alloc::string::String;
+use core::fmt::Write;
+use core::iter::FusedIterator;
+use core::str;
+
+use crate::encoding::{decode_key, decode_varint, skip_field, DecodeContext};
+use crate::error::DecodeErrorKind;
+use crate::DecodeError;
+
+pub use crate::encoding::WireType;
+
+/// A field of an encoded message, as read from the wire.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Field<'a> {
+    /// The field number.
+    pub number: u32,
+    /// The wire type of the field.
+    pub wire_type: WireType,
+    /// The offset of the field key in the buffer iterated by the [`FieldIter`].
+    pub offset: usize,
+    /// The raw bytes of the value.
+    ///
+    /// These are the bytes of the varint of a varint field, the four or eight bytes of a fixed
+    /// width field, the bytes following the length of a length-delimited field, and the encoded
+    /// fields of a group, without its end group key.
+    pub value: &'a [u8],
+    /// The offset of the value in the buffer iterated by the [`FieldIter`].
+    value_offset: usize,
+}
+
+impl<'a> Field<'a> {
+    /// Returns the value of a varint field, or `None` for other wire types.
+    pub fn as_varint(&self) -> Option<u64> {
+        match self.wire_type {
+            WireType::Varint => decode_varint(&mut &*self.value).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a 32-bit fixed width field, or `None` for other wire types.
+    pub fn as_fixed32(&self) -> Option<u32> {
+        match self.wire_type {
+            WireType::ThirtyTwoBit => self.value.try_into().ok().map(u32::from_le_bytes),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a 64-bit fixed width field, or `None` for other wire types.
+    pub fn as_fixed64(&self) -> Option<u64> {
+        match self.wire_type {
+            WireType::SixtyFourBit => self.value.try_into().ok().map(u64::from_le_bytes),
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the fields encoded in the value, such as the fields of a nested
+    /// message or a group.
+    ///
+    /// The offsets of the nested fields are relative to the same buffer as the offset of this
+    /// field. Values which are not encoded messages, such as strings, usually yield an error.
+    pub fn fields(&self) -> FieldIter<'a> {
+        FieldIter {
+            buf: self.value,
+            offset: self.value_offset,
+            done: false,
+        }
+    }
+}
+
+/// The declaration of a field of a message type, as listed by the `fields()` method which
+/// `prost-build` generates with `Config::generate_field_numbers`.
+///
+/// The wire type is the one the field is encoded with, which is `LengthDelimited` for packed
+/// repeated fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldInfo {
+    /// The name of the field in the Protobuf definition.
+    pub name: &'static str,
+    /// The field number.
+    pub number: u32,
+    /// The wire type of the field.
+    pub wire_type: WireType,
+}
+
+/// An iterator over the fields of an encoded message.
+///
+/// Each item is a [`Field`], or the error which ended the iteration if the buffer is not a valid
+/// encoding. After an error, the iterator yields no further items, and [`remaining`] returns the
+/// bytes starting at the malformed field.
+///
+/// [`remaining`]: FieldIter::remaining
+#[derive(Clone, Debug)]
+pub struct FieldIter<'a> {
+    buf: &'a [u8],
+    offset: usize,
+    done: bool,
+}
+
+impl<'a> FieldIter<'a> {
+    /// Creates an iterator over the fields encoded in `buf`.
+    pub fn new(buf: &'a [u8]) -> FieldIter<'a> {
+        FieldIter {
+            buf,
+            offset: 0,
+            done: false,
+        }
+    }
+
+    /// Returns the offset of the next field.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the bytes which have not been iterated over.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    fn decode_field(&mut self) -> Result<Field<'a>, DecodeError> {
+        let mut buf = self.buf;
+        let (number, wire_type) = decode_key(&mut buf)?;
+        let value = match wire_type {
+            WireType::Varint => {
+                let start = buf;
+                decode_varint(&mut buf)?;
+                &start[..start.len() - buf.len()]
+            }
+            WireType::ThirtyTwoBit => take(&mut buf, 4)?,
+            WireType::SixtyFourBit => take(&mut buf, 8)?,
+            WireType::LengthDelimited => {
+                let len = decode_varint(&mut buf)?;
+                take(&mut buf, len)?
+            }
+            WireType::StartGroup => {
+                let start = buf;
+                loop {
+                    let end = buf;
+                    let (inner_number, inner_wire_type) = decode_key(&mut buf)?;
+                    if inner_wire_type == WireType::EndGroup {
+                        if inner_number != number {
+                            return Err(DecodeErrorKind::UnexpectedEndGroupTag.into());
+                        }
+                        break &start[..start.len() - end.len()];
+                    }
+                    skip_field(
+                        inner_wire_type,
+                        inner_number,
+                        &mut buf,
+                        DecodeContext::default(),
+                    )?;
+                }
+            }
+            WireType::EndGroup => return Err(DecodeErrorKind::UnexpectedEndGroupTag.into()),
+        };
+
+        let value_offset = self.offset + (value.as_ptr() as usize - self.buf.as_ptr() as usize);
+        let field = Field {
+            number,
+            wire_type,
+            offset: self.offset,
+            value,
+            value_offset,
+        };
+        self.offset += self.buf.len() - buf.len();
+        self.buf = buf;
+        Ok(field)
+    }
+}
+
+impl<'a> Iterator for FieldIter<'a> {
+    type Item = Result<Field<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.buf.is_empty() {
+            return None;
+        }
+        let field = self.decode_field();
+        self.done = field.is_err();
+        Some(field)
+    }
+}
+
+impl FusedIterator for FieldIter<'_> {}
+
+/// Splits the first `len` bytes off `buf`.
+fn take<'a>(buf: &mut &'a [u8], len: u64) -> Result<&'a [u8], DecodeError> {
+    if len > buf.len() as u64 {
+        return Err(DecodeErrorKind::BufferUnderflow.into());
+    }
+    let (value, rest) = buf.split_at(len as usize);
+    *buf = rest;
+    Ok(value)
+}
+
+/// Formats the fields of an encoded message, in the style of `protoc --decode_raw`.
+///
+/// Each field is printed on its own line as its field number and value. Length-delimited values
+/// are printed as nested messages if they are valid encodings of fields, as strings if they are
+/// valid UTF-8, and as escaped bytes otherwise. Fixed width values are printed in hexadecimal.
+///
+/// If the buffer is not a valid encoding, the fields before the malformed one are printed,
+/// followed by a line with the offset of the malformed field and the error.
+pub fn debug_dump(buf: &[u8]) -> String {
+    let mut out = String::new();
+    dump_fields(FieldIter::new(buf), 0, &mut out);
+    out
+}
+
+fn dump_fields(mut fields: FieldIter<'_>, depth: usize, out: &mut String) {
+    while let Some(field) = fields.next() {
+        let indent = depth * 2;
+        let field = match field {
+            Ok(field) => field,
+            Err(error) => {
+                let _ = writeln!(
+                    out,
+                    "{:indent$}error at offset {}: {error}",
+                    "",
+                    fields.offset()
+                );
+                return;
+            }
+        };
+        let number = field.number;
+        match field.wire_type {
+            WireType::Varint => {
+                let _ = writeln!(
+                    out,
+                    "{:indent$}{number}: {}",
+                    "",
+                    field.as_varint().unwrap()
+                );
+            }
+            WireType::ThirtyTwoBit => {
+                let _ = writeln!(
+                    out,
+                    "{:indent$}{number}: 0x{:08x}",
+                    "",
+                    field.as_fixed32().unwrap()
+                );
+            }
+            WireType::SixtyFourBit => {
+                let _ = writeln!(
+                    out,
+                    "{:indent$}{number}: 0x{:016x}",
+                    "",
+                    field.as_fixed64().unwrap()
+                );
+            }
+            WireType::StartGroup => {
+                dump_nested(number, field.fields(), depth, out);
+            }
+            WireType::LengthDelimited => {
+                if !field.value.is_empty()
+                    && depth < crate::RECURSION_LIMIT as usize
+                    && field.fields().all(|field| field.is_ok())
+                {
+                    dump_nested(number, field.fields(), depth, out);
+                } else if let Ok(value) = str::from_utf8(field.value) {
+                    let _ = writeln!(out, "{:indent$}{number}: \"{}\"", "", value.escape_debug());
+                } else {
+                    let _ = write!(out, "{:indent$}{number}: \"", "");
+                    for &byte in field.value {
+                        match byte {
+                            b'"' | b'\\' => {
+                                out.push('\\');
+                                out.push(byte as char);
+                            }
+                            0x20..=0x7e => out.push(byte as char),
+                            _ => {
+                                let _ = write!(out, "\\x{byte:02x}");
+                            }
+                        }
+                    }
+                    out.push_str("\"\n");
+                }
+            }
+            WireType::EndGroup => unreachable!(),
+        }
+    }
+}
+
+fn dump_nested(number: u32, fields: FieldIter<'_>, depth: usize, out: &mut String) {
+    let indent = depth * 2;
+    let _ = writeln!(out, "{:indent$}{number} {{", "");
+    dump_fields(fields, depth + 1, out);
+    let _ = writeln!(out, "{:indent$}}}", "");
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::encoding::{encode_key, encode_varint, string, uint32};
+
+    fn collect(buf: &[u8]) -> Vec<Result<Field<'_>, DecodeError>> {
+        FieldIter::new(buf).collect()
+    }
+
+    #[test]
+    fn iterate_fields() {
+        let mut buf = Vec::new();
+        uint32::encode(1, &150, &mut buf);
+        encode_key(2, WireType::ThirtyTwoBit, &mut buf);
+        buf.extend_from_slice(&7u32.to_le_bytes());
+        encode_key(3, WireType::SixtyFourBit, &mut buf);
+        buf.extend_from_slice(&9u64.to_le_bytes());
+        encode_key(4, WireType::StartGroup, &mut buf);
+        uint32::encode(5, &1, &mut buf);
+        encode_key(4, WireType::EndGroup, &mut buf);
+
+        let fields = collect(&buf)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[0].as_varint(), Some(150));
+        assert_eq!(fields[0].value, &[0x96, 0x01]);
+        assert_eq!(fields[1].as_fixed32(), Some(7));
+        assert_eq!(fields[1].offset, 3);
+        assert_eq!(fields[2].as_fixed64(), Some(9));
+        assert_eq!(fields[2].as_varint(), None);
+        assert_eq!(fields[3].wire_type, WireType::StartGroup);
+
+        let nested = fields[3].fields().collect::<Vec<_>>();
+        assert_eq!(nested.len(), 1);
+        let nested = nested[0].as_ref().unwrap();
+        assert_eq!((nested.number, nested.as_varint()), (5, Some(1)));
+        assert_eq!(nested.offset, 18);
+    }
+
+    #[test]
+    fn malformed_fields() {
+        let mut buf = Vec::new();
+        uint32::encode(1, &1, &mut buf);
+        encode_key(2, WireType::LengthDelimited, &mut buf);
+        encode_varint(10, &mut buf);
+        buf.extend_from_slice(b"short");
+
+        let mut iter = FieldIter::new(&buf);
+        assert!(iter.next().unwrap().is_ok());
+        assert_eq!(iter.offset(), 2);
+        assert!(iter.next().unwrap().is_err());
+        assert_eq!(iter.remaining(), &buf[2..]);
+        assert!(iter.next().is_none());
+
+        let mut buf = Vec::new();
+        encode_key(1, WireType::EndGroup, &mut buf);
+        assert!(collect(&buf)[0].is_err());
+    }
+
+    #[test]
+    fn dump() {
+        let mut nested = Vec::new();
+        string::encode(1, &String::from("a \"quoted\" name"), &mut nested);
+        uint32::encode(2, &3, &mut nested);
+
+        let mut buf = Vec::new();
+        uint32::encode(1, &150, &mut buf);
+        encode_key(2, WireType::LengthDelimited, &mut buf);
+        encode_varint(nested.len() as u64, &mut buf);
+        buf.extend_from_slice(&nested);
+        encode_key(3, WireType::LengthDelimited, &mut buf);
+        encode_varint(2, &mut buf);
+        buf.extend_from_slice(&[0xff, b'"']);
+        encode_key(4, WireType::ThirtyTwoBit, &mut buf);
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        string::encode(5, &String::new(), &mut buf);
+
+        assert_eq!(
+            debug_dump(&buf),
+            "1: 150\n\
+             2 {\n  1: \"a \\\"quoted\\\" name\"\n  2: 3\n}\n\
+             3: \"\\xff\\\"\"\n\
+             4: 0x00000001\n\
+             5: \"\"\n"
+        );
+
+        let truncated = vec![0x08, 0x96, 0x01, 0x10];
+        assert_eq!(
+            debug_dump(&truncated),
+            "1: 150\nerror at offset 3: failed to decode Protobuf message: invalid varint\n"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+#!/bin/bash
+
+# Script which builds protobuf from source and packages it as a prebuilt artifact for the
+# host, which `build.rs` downloads when `PROST_PREBUILT_PROTOBUF=1` is set. The artifact
+# should be uploaded to the release at `PREBUILT_URL` in `prost-build/build/protobuf.rs`, and
+# the printed checksum line appended to `prost-build/build/prebuilt.sha256`.
+
+set -ex
+
+if [ "$#" -ne 0 ]
+then
+  echo "Usage: $0"
+  exit 1
+fi
+
+DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" >/dev/null 2>&1 && pwd )"
+VERSION="$( sed -n -E 's/^pub const PROTOBUF_VERSION: &str = "(.*)";$/\1/p' "$DIR/../prost-build/build/protobuf.rs" )"
+HOST="$( rustc -vV | sed -n 's/^host: //p' )"
+ARTIFACT="protobuf-${VERSION}-${HOST}.tar.gz"
+
+# Build protobuf from source, even if a prebuilt artifact is configured.
+PROST_PREBUILT_PROTOBUF=0 cargo build --manifest-path "$DIR/Cargo.toml" --release
+
+OUT_DIR="$( ls -d "$DIR"/../target/release/build/protobuf-*/out/protobuf-"${VERSION}" | head -n 1 )"
+OUT_DIR="$( dirname "$OUT_DIR" )"
+PROTOBUF_SRC="$OUT_DIR/build-protobuf-${VERSION}/build/_deps/protobuf-src"
+
+STAGING="$( mktemp -d )"
+trap 'rm -rf "$STAGING"' EXIT
+
+cp -R "$OUT_DIR/protobuf-${VERSION}/." "$STAGING"
+# Only the .proto files of the source tree are needed to generate the test messages.
+(cd "$PROTOBUF_SRC" && find conformance src -name '*.proto') | while read -r FILE; do
+  mkdir -p "$STAGING/protobuf-src/$( dirname "$FILE" )"
+  cp "$PROTOBUF_SRC/$FILE" "$STAGING/protobuf-src/$FILE"
+done
+
+tar -czf "$DIR/$ARTIFACT" -C "$STAGING" .
+
+set +x
+echo
+echo "Created $DIR/$ARTIFACT, add the following line to prost-build/build/prebuilt.sha256:"
+(cd "$DIR" && (sha256sum "$ARTIFACT" 2>/dev/null || shasum -a 256 "$ARTIFACT"))
@@ -3,6 +3,8 @@
 pub mod conformance {
     use std::path::Path;
 
+    /// Returns the path of the native conformance test runner, which is not built for wasm.
+    #[cfg(not(target_family = "wasm"))]
     pub fn test_runner() -> &'static Path {
         Path::new(concat!(env!("PROTOBUF"), "/bin/conformance-test-runner"))
     }
@@ -10,6 +12,35 @@ pub mod conformance {
     include!(concat!(env!("OUT_DIR"), "/conformance.rs"));
 }
 
+/// Returns the directory of the binary test data of the Protobuf sources, such as the
+/// `golden_message` encoding of a `protobuf_unittest.TestAllTypes` message.
+pub fn test_data_dir() -> &'static std::path::Path {
+    std::path::Path::new(concat!(
+        env!("PROTOBUF_SRC"),
+        "/src/google/protobuf/testdata"
+    ))
+}
+
+/// Returns the path of the native `protoc` built from the Protobuf sources, which is not built
+/// for wasm.
+#[cfg(not(target_family = "wasm"))]
+pub fn protoc() -> std::path::PathBuf {
+    let name = if cfg!(windows) {
+        "protoc.exe"
+    } else {
+        "protoc"
+    };
+    std::path::Path::new(env!("PROTOBUF"))
+        .join("bin")
+        .join(name)
+}
+
+/// Returns the directory to import the `.proto` files of the Protobuf sources from, such as
+/// `google/protobuf/test_messages_proto3.proto`.
+pub fn include_dir() -> &'static std::path::Path {
+    std::path::Path::new(concat!(env!("PROTOBUF_SRC"), "/src"))
+}
+
 pub mod test_messages {
     pub mod proto2 {
         include!(concat!(
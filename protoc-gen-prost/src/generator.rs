@@ -0,0 +1,72 @@
+//! Generation of the response to a `CodeGeneratorRequest`.
+
+use prost_build::Module;
+use prost_types::compiler::code_generator_response::{Feature, File};
+use prost_types::compiler::{CodeGeneratorRequest, CodeGeneratorResponse};
+use prost_types::Edition;
+
+use crate::params::Params;
+
+/// Generates the Rust files for the `.proto` files of a request.
+///
+/// Errors are reported in the `error` field of the response, as `protoc` expects from plugins.
+pub(crate) fn generate(request: CodeGeneratorRequest) -> CodeGeneratorResponse {
+    let mut response = CodeGeneratorResponse {
+        supported_features: Some(Feature::Proto3Optional as u64 | Feature::SupportsEditions as u64),
+        minimum_edition: Some(Edition::Edition2023 as i32),
+        maximum_edition: Some(Edition::Edition2023 as i32),
+        ..Default::default()
+    };
+    match generate_files(request) {
+        Ok(files) => response.file = files,
+        Err(error) => response.error = Some(error),
+    }
+    response
+}
+
+fn generate_files(request: CodeGeneratorRequest) -> Result<Vec<File>, String> {
+    let params = Params::parse(request.parameter())?;
+    let mut config = params.to_config();
+
+    // `proto_file` also holds the files imported by the files to generate, which are generated
+    // by their own `protoc` invocations.
+    let requests = request
+        .proto_file
+        .into_iter()
+        .filter(|file| {
+            request
+                .file_to_generate
+                .iter()
+                .any(|name| name == file.name())
+        })
+        .map(|file| (Module::from_protobuf_package_name(file.package()), file))
+        .collect::<Vec<_>>();
+    let default_package_filename = params.default_package_filename();
+
+    let modules = config
+        .generate(requests)
+        .map_err(|error| error.to_string())?;
+
+    let mut files = modules
+        .iter()
+        .map(|(module, content)| File {
+            name: Some(module.to_file_name_or(default_package_filename)),
+            content: Some(content.clone()),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if let Some(include_file) = params.include_file() {
+        let content = config
+            .generate_include_file(modules.keys().collect())
+            .map_err(|error| error.to_string())?;
+        files.push(File {
+            name: Some(include_file.to_string()),
+            content: Some(content),
+            ..Default::default()
+        });
+    }
+
+    Ok(files)
+}
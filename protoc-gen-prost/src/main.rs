@@ -0,0 +1,75 @@
+//! `protoc-gen-prost` is a `protoc` plugin which generates Prost annotated Rust types from
+//! Protocol Buffers files.
+//!
+//! It allows build systems which invoke `protoc` plugins, such as Buf and Bazel, to generate code
+//! with `prost-build` instead of running it from a `build.rs` file. `protoc` runs the plugin when
+//! `--prost_out` is passed, and the generated files are written to the given directory:
+//!
+//! ```text
+//! protoc --prost_out=src/gen --prost_opt=enable_type_names,include_file=mod.rs items.proto
+//! ```
+//!
+//! Each package is generated into a file named after the package, such as `my.package.rs`. The
+//! options in `--prost_opt` are separated by commas, and correspond to the methods of
+//! [`prost_build::Config`] with the same names:
+//!
+//! * `btree_map=<path>`, `bytes=<path>`, `boxed=<path>`, `cow=<path>`, `disable_comments=<path>`,
+//!   `skip_debug=<path>`, `preserve_unknown_fields=<path>`, `open_enums=<path>`,
+//!   `enum_display_from_str=<path>`, `enum_from_str_ignore_case=<path>`,
+//!   `const_defaults=<path>`, `presence_bits=<path>` and `non_exhaustive_messages=<path>`.
+//! * `field_attribute=<path>=<attribute>`, `type_attribute=<path>=<attribute>`,
+//!   `message_attribute=<path>=<attribute>`, `enum_attribute=<path>=<attribute>`,
+//!   `oneof_attribute=<path>=<attribute>` and `variant_attribute=<path>=<attribute>`. A comma in
+//!   an attribute is escaped as `\,`.
+//! * `extern_path=<proto path>=<rust path>`, `message_type=<path>=<rust type>`,
+//!   `bytes_type=<path>=<rust type>`, `string_type=<path>=<rust type>`,
+//!   `wrapper_type=<path>=<box|arc>`, `type_name_domain=<path>=<domain>` and
+//!   `version_conversion=<package>=<package>`.
+//! * `default_package_filename=<name>`, `prost_path=<path>` and `prost_types_path=<path>`.
+//! * `box_large_oneof_variants=<size>` boxes the message fields of oneofs whose messages are
+//!   larger than the given number of bytes.
+//! * `recursive_wrapper=<box|arc>` chooses the pointer wrapping recursive message fields, and
+//!   `no_box_recursive` disables their automatic wrapping.
+//! * `include_file=<name>` generates an include file which nests the generated files in modules.
+//!   `include_file_feature=<package>=<feature>` gates the module of a package behind a Cargo
+//!   feature, and `include_file_extern_paths` re-exports the extern paths in the include file.
+//! * `compile_well_known_types`, `retain_enum_prefix`, `enable_type_names`, `enable_json`,
+//!   `enable_serde`, `enable_text_format`, `enable_field_masks` and `enable_reflection`.
+//! * `builders`, `oneof_accessors`, `conversions` and `arbitrary` enable [`generate_builders`],
+//!   [`generate_oneof_accessors`], [`generate_conversions`] and [`generate_arbitrary`], and
+//!   `file_descriptor_set` and `message_descriptors` enable [`embed_file_descriptor_sets`] and
+//!   [`embed_message_descriptors`].
+//! * `field_numbers` enables [`generate_field_numbers`], which generates field number constants.
+//! * `text_format_debug` enables [`text_format_debug`], which implements `Debug` with the text
+//!   format.
+//! * `intra_doc_links` converts links to Protobuf types in comments into intra-doc links.
+//! * `no_format` disables formatting of the generated code.
+//!
+//! [`generate_builders`]: prost_build::Config::generate_builders
+//! [`generate_oneof_accessors`]: prost_build::Config::generate_oneof_accessors
+//! [`generate_conversions`]: prost_build::Config::generate_conversions
+//! [`generate_arbitrary`]: prost_build::Config::generate_arbitrary
+//! [`generate_field_numbers`]: prost_build::Config::generate_field_numbers
+//! [`embed_file_descriptor_sets`]: prost_build::Config::embed_file_descriptor_sets
+//! [`embed_message_descriptors`]: prost_build::Config::embed_message_descriptors
+//! [`text_format_debug`]: prost_build::Config::text_format_debug
+
+use std::io::{self, Read, Write};
+
+use prost::Message;
+use prost_types::compiler::CodeGeneratorRequest;
+
+mod generator;
+mod params;
+
+fn main() -> io::Result<()> {
+    let mut buf = Vec::new();
+    io::stdin().read_to_end(&mut buf)?;
+    let request = CodeGeneratorRequest::decode(buf.as_slice())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+    let response = generator::generate(request);
+
+    io::stdout().write_all(&response.encode_to_vec())?;
+    io::stdout().flush()
+}
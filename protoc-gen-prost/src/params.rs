@@ -0,0 +1,400 @@
+//! Parsing of the plugin parameter string.
+//!
+//! `protoc` passes the value of `--prost_opt` to the plugin as a single string. Options are
+//! separated by commas, and a literal comma, as in an attribute, is escaped as `\,`. Each option
+//! is either a flag, such as `enable_type_names`, or a `key=value` pair. Options which configure
+//! a path take the path followed by another `=` and the value, such as
+//! `type_attribute=.my.Message=#[derive(Eq)]`.
+
+use prost_build::{Config, Wrapper};
+
+/// A single option of the parameter string, corresponding to a method of [`Config`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Param {
+    BtreeMap(String),
+    Bytes(String),
+    Boxed(String),
+    Cow(String),
+    BoxLargeOneofVariants(usize),
+    NoBoxRecursive,
+    RecursiveWrapper(Wrapper),
+    DisableComments(String),
+    SkipDebug(String),
+    PreserveUnknownFields(String),
+    OpenEnums(String),
+    EnumDisplayFromStr(String),
+    EnumFromStrIgnoreCase(String),
+    ConstDefaults(String),
+    NonExhaustiveMessages(String),
+    PresenceBits(String),
+    FieldAttribute(String, String),
+    TypeAttribute(String, String),
+    MessageAttribute(String, String),
+    EnumAttribute(String, String),
+    OneofAttribute(String, String),
+    VariantAttribute(String, String),
+    ExternPath(String, String),
+    VersionConversion(String, String),
+    MessageType(String, String),
+    BytesType(String, String),
+    StringType(String, String),
+    WrapperType(String, Wrapper),
+    TypeNameDomain(String, String),
+    DefaultPackageFilename(String),
+    ProstPath(String),
+    ProstTypesPath(String),
+    IncludeFile(String),
+    IncludeFileFeature(String, String),
+    IncludeFileExternPaths,
+    IntraDocLinks,
+    CompileWellKnownTypes,
+    RetainEnumPrefix,
+    EnableTypeNames,
+    EnableJson,
+    EnableSerde,
+    EnableTextFormat,
+    TextFormatDebug,
+    EnableFieldMasks,
+    EnableReflection,
+    Builders,
+    OneofAccessors,
+    Conversions,
+    Arbitrary,
+    FieldNumbers,
+    FileDescriptorSet,
+    MessageDescriptors,
+    NoFormat,
+}
+
+/// The parsed plugin parameter string.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Params {
+    params: Vec<Param>,
+}
+
+impl Params {
+    /// Parses a plugin parameter string.
+    pub(crate) fn parse(parameter: &str) -> Result<Params, String> {
+        let params = split_escaped(parameter)
+            .iter()
+            .filter(|option| !option.is_empty())
+            .map(|option| parse_param(option))
+            .collect::<Result<_, _>>()?;
+        Ok(Params { params })
+    }
+
+    /// The name of the include file, if one should be generated.
+    pub(crate) fn include_file(&self) -> Option<&str> {
+        self.params.iter().rev().find_map(|param| match param {
+            Param::IncludeFile(name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The file name of the module of files without a package, `_` by default.
+    pub(crate) fn default_package_filename(&self) -> &str {
+        self.params
+            .iter()
+            .rev()
+            .find_map(|param| match param {
+                Param::DefaultPackageFilename(filename) => Some(filename.as_str()),
+                _ => None,
+            })
+            .unwrap_or("_")
+    }
+
+    /// Creates a [`Config`] configured by the parameters.
+    pub(crate) fn to_config(&self) -> Config {
+        let mut config = Config::new();
+        for param in &self.params {
+            match param {
+                Param::BtreeMap(path) => config.btree_map([path]),
+                Param::Bytes(path) => config.bytes([path]),
+                Param::Boxed(path) => config.boxed(path),
+                Param::Cow(path) => config.cow([path]),
+                Param::BoxLargeOneofVariants(max_size) => {
+                    config.box_large_oneof_variants(*max_size)
+                }
+                Param::NoBoxRecursive => config.box_recursive(false),
+                Param::RecursiveWrapper(wrapper) => config.recursive_wrapper(*wrapper),
+                Param::DisableComments(path) => config.disable_comments([path]),
+                Param::SkipDebug(path) => config.skip_debug([path]),
+                Param::PreserveUnknownFields(path) => config.preserve_unknown_fields([path]),
+                Param::OpenEnums(path) => config.open_enums([path]),
+                Param::EnumDisplayFromStr(path) => config.enum_display_from_str([path]),
+                Param::EnumFromStrIgnoreCase(path) => config.enum_from_str_ignore_case([path]),
+                Param::ConstDefaults(path) => config.const_defaults([path]),
+                Param::NonExhaustiveMessages(path) => config.non_exhaustive_messages([path]),
+                Param::PresenceBits(path) => config.presence_bits(path),
+                Param::FieldAttribute(path, attribute) => config.field_attribute(path, attribute),
+                Param::TypeAttribute(path, attribute) => config.type_attribute(path, attribute),
+                Param::MessageAttribute(path, attribute) => {
+                    config.message_attribute(path, attribute)
+                }
+                Param::EnumAttribute(path, attribute) => config.enum_attribute(path, attribute),
+                Param::OneofAttribute(path, attribute) => config.oneof_attribute(path, attribute),
+                Param::VariantAttribute(path, attribute) => {
+                    config.variant_attribute(path, attribute)
+                }
+                Param::ExternPath(proto_path, rust_path) => {
+                    config.extern_path(proto_path, rust_path)
+                }
+                Param::VersionConversion(package, other_package) => {
+                    config.version_conversion(package, other_package)
+                }
+                Param::MessageType(path, rust_type) => config.message_type(path, rust_type),
+                Param::BytesType(path, rust_type) => config.bytes_type(path, rust_type),
+                Param::StringType(path, rust_type) => config.string_type(path, rust_type),
+                Param::WrapperType(path, wrapper) => config.wrapper_type(path, *wrapper),
+                Param::TypeNameDomain(path, domain) => config.type_name_domain([path], domain),
+                Param::DefaultPackageFilename(filename) => {
+                    config.default_package_filename(filename)
+                }
+                Param::ProstPath(path) => config.prost_path(path),
+                Param::ProstTypesPath(path) => config.prost_types_path(path),
+                Param::IncludeFile(name) => config.include_file(name),
+                Param::IncludeFileFeature(package, feature) => {
+                    config.include_file_feature(package, feature)
+                }
+                Param::IncludeFileExternPaths => config.include_file_extern_paths(true),
+                Param::IntraDocLinks => config.intra_doc_links(true),
+                Param::CompileWellKnownTypes => config.compile_well_known_types(),
+                Param::RetainEnumPrefix => config.retain_enum_prefix(),
+                Param::EnableTypeNames => config.enable_type_names(),
+                Param::EnableJson => config.enable_json(),
+                Param::EnableSerde => config.enable_serde(),
+                Param::EnableTextFormat => config.enable_text_format(),
+                Param::TextFormatDebug => config.text_format_debug(),
+                Param::EnableFieldMasks => config.enable_field_masks(),
+                Param::EnableReflection => config.enable_reflection(),
+                Param::Builders => config.generate_builders(true),
+                Param::OneofAccessors => config.generate_oneof_accessors(true),
+                Param::Conversions => config.generate_conversions(true),
+                Param::Arbitrary => config.generate_arbitrary(true),
+                Param::FieldNumbers => config.generate_field_numbers(true),
+                Param::FileDescriptorSet => config.embed_file_descriptor_sets(true),
+                Param::MessageDescriptors => config.embed_message_descriptors(true),
+                #[cfg(feature = "format")]
+                Param::NoFormat => config.format(false),
+                #[cfg(not(feature = "format"))]
+                Param::NoFormat => &mut config,
+            };
+        }
+        config
+    }
+}
+
+/// Splits the parameter string at unescaped commas, and unescapes `\,`.
+fn split_escaped(parameter: &str) -> Vec<String> {
+    let mut options = vec![String::new()];
+    let mut chars = parameter.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&',') => {
+                chars.next();
+                options.last_mut().unwrap().push(',');
+            }
+            ',' => options.push(String::new()),
+            c => options.last_mut().unwrap().push(c),
+        }
+    }
+    options
+}
+
+fn parse_param(option: &str) -> Result<Param, String> {
+    let (key, value) = match option.split_once('=') {
+        Some((key, value)) => (key.trim(), Some(value)),
+        None => (option.trim(), None),
+    };
+
+    let flag = |param: Param| match value {
+        None => Ok(param),
+        Some(_) => Err(format!("option `{key}` does not take a value")),
+    };
+    let value = || value.ok_or_else(|| format!("option `{key}` requires a value"));
+    let pair = || {
+        value()?
+            .split_once('=')
+            .map(|(path, value)| (path.to_string(), value.to_string()))
+            .ok_or_else(|| format!("option `{key}` requires a value of the form `<path>=<value>`"))
+    };
+
+    Ok(match key {
+        "btree_map" => Param::BtreeMap(value()?.to_string()),
+        "bytes" => Param::Bytes(value()?.to_string()),
+        "boxed" => Param::Boxed(value()?.to_string()),
+        "cow" => Param::Cow(value()?.to_string()),
+        "box_large_oneof_variants" => {
+            let value = value()?;
+            let max_size = value
+                .parse()
+                .map_err(|_| format!("option `{key}` requires a size, not `{value}`"))?;
+            Param::BoxLargeOneofVariants(max_size)
+        }
+        "no_box_recursive" => flag(Param::NoBoxRecursive)?,
+        "recursive_wrapper" => Param::RecursiveWrapper(parse_wrapper(key, value()?)?),
+        "disable_comments" => Param::DisableComments(value()?.to_string()),
+        "skip_debug" => Param::SkipDebug(value()?.to_string()),
+        "preserve_unknown_fields" => Param::PreserveUnknownFields(value()?.to_string()),
+        "open_enums" => Param::OpenEnums(value()?.to_string()),
+        "enum_display_from_str" => Param::EnumDisplayFromStr(value()?.to_string()),
+        "enum_from_str_ignore_case" => Param::EnumFromStrIgnoreCase(value()?.to_string()),
+        "const_defaults" => Param::ConstDefaults(value()?.to_string()),
+        "non_exhaustive_messages" => Param::NonExhaustiveMessages(value()?.to_string()),
+        "presence_bits" => Param::PresenceBits(value()?.to_string()),
+        "field_attribute" => pair().map(|(path, value)| Param::FieldAttribute(path, value))?,
+        "type_attribute" => pair().map(|(path, value)| Param::TypeAttribute(path, value))?,
+        "message_attribute" => pair().map(|(path, value)| Param::MessageAttribute(path, value))?,
+        "enum_attribute" => pair().map(|(path, value)| Param::EnumAttribute(path, value))?,
+        "oneof_attribute" => pair().map(|(path, value)| Param::OneofAttribute(path, value))?,
+        "variant_attribute" => pair().map(|(path, value)| Param::VariantAttribute(path, value))?,
+        "extern_path" => pair().map(|(path, value)| Param::ExternPath(path, value))?,
+        "version_conversion" => {
+            pair().map(|(package, other)| Param::VersionConversion(package, other))?
+        }
+        "message_type" => pair().map(|(path, value)| Param::MessageType(path, value))?,
+        "bytes_type" => pair().map(|(path, value)| Param::BytesType(path, value))?,
+        "string_type" => pair().map(|(path, value)| Param::StringType(path, value))?,
+        "wrapper_type" => {
+            let (path, wrapper) = pair()?;
+            Param::WrapperType(path, parse_wrapper(key, &wrapper)?)
+        }
+        "type_name_domain" => pair().map(|(path, value)| Param::TypeNameDomain(path, value))?,
+        "default_package_filename" => Param::DefaultPackageFilename(value()?.to_string()),
+        "prost_path" => Param::ProstPath(value()?.to_string()),
+        "prost_types_path" => Param::ProstTypesPath(value()?.to_string()),
+        "include_file" => Param::IncludeFile(value()?.to_string()),
+        "include_file_feature" => {
+            pair().map(|(package, feature)| Param::IncludeFileFeature(package, feature))?
+        }
+        "include_file_extern_paths" => flag(Param::IncludeFileExternPaths)?,
+        "intra_doc_links" => flag(Param::IntraDocLinks)?,
+        "compile_well_known_types" => flag(Param::CompileWellKnownTypes)?,
+        "retain_enum_prefix" => flag(Param::RetainEnumPrefix)?,
+        "enable_type_names" => flag(Param::EnableTypeNames)?,
+        "enable_json" => flag(Param::EnableJson)?,
+        "enable_serde" => flag(Param::EnableSerde)?,
+        "enable_text_format" => flag(Param::EnableTextFormat)?,
+        "text_format_debug" => flag(Param::TextFormatDebug)?,
+        "enable_field_masks" => flag(Param::EnableFieldMasks)?,
+        "enable_reflection" => flag(Param::EnableReflection)?,
+        "builders" => flag(Param::Builders)?,
+        "oneof_accessors" => flag(Param::OneofAccessors)?,
+        "conversions" => flag(Param::Conversions)?,
+        "arbitrary" => flag(Param::Arbitrary)?,
+        "field_numbers" => flag(Param::FieldNumbers)?,
+        "file_descriptor_set" => flag(Param::FileDescriptorSet)?,
+        "message_descriptors" => flag(Param::MessageDescriptors)?,
+        "no_format" => flag(Param::NoFormat)?,
+        _ => return Err(format!("unknown option `{key}`")),
+    })
+}
+
+/// Parses the name of a [`Wrapper`], `box` or `arc`.
+fn parse_wrapper(key: &str, value: &str) -> Result<Wrapper, String> {
+    match value {
+        "box" => Ok(Wrapper::Box),
+        "arc" => Ok(Wrapper::Arc),
+        _ => Err(format!(
+            "option `{key}` requires `box` or `arc`, not `{value}`"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_empty() {
+        assert_eq!(Params::parse(""), Ok(Params::default()));
+    }
+
+    #[test]
+    fn parse_options() {
+        let params = Params::parse(
+            "btree_map=.,extern_path=.google.protobuf=::pbjson_types,enable_type_names,include_file=mod.rs",
+        )
+        .unwrap();
+        assert_eq!(
+            params.params,
+            vec![
+                Param::BtreeMap(".".to_string()),
+                Param::ExternPath(".google.protobuf".to_string(), "::pbjson_types".to_string()),
+                Param::EnableTypeNames,
+                Param::IncludeFile("mod.rs".to_string()),
+            ]
+        );
+        assert_eq!(params.include_file(), Some("mod.rs"));
+    }
+
+    #[test]
+    fn parse_escaped_comma() {
+        let params =
+            Params::parse(r"type_attribute=.my.Message=#[derive(Eq\, Hash)],enable_serde").unwrap();
+        assert_eq!(
+            params.params,
+            vec![
+                Param::TypeAttribute(".my.Message".to_string(), "#[derive(Eq, Hash)]".to_string()),
+                Param::EnableSerde,
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_attribute_containing_equals() {
+        let params =
+            Params::parse(r#"field_attribute=.my.Message.id=#[serde(rename = "ID")]"#).unwrap();
+        assert_eq!(
+            params.params,
+            vec![Param::FieldAttribute(
+                ".my.Message.id".to_string(),
+                r#"#[serde(rename = "ID")]"#.to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn parse_wrappers() {
+        let params = Params::parse(
+            "no_box_recursive,recursive_wrapper=arc,wrapper_type=.my.Envelope.payload=box",
+        )
+        .unwrap();
+        assert_eq!(
+            params.params,
+            vec![
+                Param::NoBoxRecursive,
+                Param::RecursiveWrapper(Wrapper::Arc),
+                Param::WrapperType(".my.Envelope.payload".to_string(), Wrapper::Box),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Params::parse("frobnicate"),
+            Err("unknown option `frobnicate`".to_string())
+        );
+        assert_eq!(
+            Params::parse("btree_map"),
+            Err("option `btree_map` requires a value".to_string())
+        );
+        assert_eq!(
+            Params::parse("enable_serde=true"),
+            Err("option `enable_serde` does not take a value".to_string())
+        );
+        assert_eq!(
+            Params::parse("extern_path=.foo"),
+            Err("option `extern_path` requires a value of the form `<path>=<value>`".to_string())
+        );
+        assert_eq!(
+            Params::parse("box_large_oneof_variants=large"),
+            Err("option `box_large_oneof_variants` requires a size, not `large`".to_string())
+        );
+        assert_eq!(
+            Params::parse("recursive_wrapper=rc"),
+            Err("option `recursive_wrapper` requires `box` or `arc`, not `rc`".to_string())
+        );
+    }
+}
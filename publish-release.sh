@@ -17,6 +17,7 @@ CRATES=( \
   "prost" \
   "prost-types" \
   "prost-build" \
+  "protoc-gen-prost" \
 )
 
 for CRATE in "${CRATES[@]}"; do
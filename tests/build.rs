@@ -5,6 +5,7 @@ cfg_if! {
     if #[cfg(feature = "edition-2015")] {
         extern crate env_logger;
         extern crate prost_build;
+        extern crate prost_types;
     }
 }
 
@@ -13,6 +14,9 @@ use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
 
+use prost_build::DeriveProvider;
+use prost_types::{DescriptorProto, EnumDescriptorProto, FieldDescriptorProto};
+
 fn main() {
     env_logger::init();
 
@@ -69,6 +73,13 @@ fn main() {
         .field_attribute("custom_attributes.AnotherEnum.D", "/// The D docs")
         .field_attribute("custom_attributes.Msg.field.a", "/// Oneof A docs")
         .field_attribute("custom_attributes.Msg.field.b", "/// Oneof B docs")
+        .message_attribute(".custom_attributes.OtherMsg", "#[allow(missing_docs)]")
+        .enum_attribute(".custom_attributes.ThirdEnum", "/// Enum docs")
+        .oneof_attribute(".custom_attributes.OtherMsg.choice", "/// Oneof docs")
+        .variant_attribute(".custom_attributes.ThirdEnum", "/// Variant docs")
+        .variant_attribute(".custom_attributes.OtherMsg.choice", "/// Variant docs")
+        // Enums would get conflicting implementations of `PartialOrd` and `Ord`.
+        .oneof_attribute(".custom_attributes", "#[derive(PartialOrd, Ord)]")
         .compile_protos(&[src.join("custom_attributes.proto")], includes)
         .unwrap();
 
@@ -93,6 +104,7 @@ fn main() {
         .unwrap();
 
     prost_build::Config::new()
+        .enable_text_format()
         .compile_protos(&[src.join("groups.proto")], includes)
         .unwrap();
 
@@ -134,11 +146,338 @@ fn main() {
         .compile_protos(&[src.join("proto3_presence.proto")], includes)
         .unwrap();
 
+    prost_build::Config::new()
+        .preserve_unknown_fields([".unknown_fields.Old"])
+        .compile_protos(&[src.join("unknown_fields.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map([
+            ".deterministic.SortedInventory",
+            ".deterministic.SortedItem",
+        ])
+        .preserve_unknown_fields([".deterministic.PartialInventory"])
+        .compile_protos(&[src.join("deterministic.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .required_field_presence([".required_fields.Item", ".required_fields.Order"])
+        .compile_protos(&[src.join("required_fields.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .protoc_arg("--experimental_allow_proto3_optional")
+        .btree_map(["."])
+        .enable_type_names()
+        .enable_json()
+        .compile_protos(&[src.join("json.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .protoc_arg("--experimental_allow_proto3_optional")
+        .btree_map(["."])
+        .enable_json()
+        .enable_serde()
+        .compile_protos(&[src.join("serde_mapping.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .enable_text_format()
+        .file_descriptor_set_path(
+            PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR environment variable not set"))
+                .join("text_format.bin"),
+        )
+        .compile_protos(&[src.join("text_format.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .preserve_extensions(["."])
+        .file_descriptor_set_path(
+            PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR environment variable not set"))
+                .join("extensions.bin"),
+        )
+        .compile_protos(&[src.join("extensions.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .utf8_bytes(["."])
+        .enable_json()
+        .enable_text_format()
+        .compile_protos(&[src.join("utf8_bytes.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .bytes([".message_ref.Document.shared"])
+        .utf8_bytes([".message_ref.Document.label"])
+        .preserve_unknown_fields([".message_ref.Section"])
+        .message_refs(["."])
+        .compile_protos(&[src.join("message_ref.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .arena_messages(["."])
+        .compile_protos(&[src.join("arena.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .generate_builders(true)
+        .compile_protos(&[src.join("builders.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .lazy(".lazy.Envelope")
+        .message_refs(["."])
+        .generate_builders(true)
+        .compile_protos(&[src.join("lazy.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .map_type(".custom_map", "crate::custom_map::VecMap")
+        .generate_builders(true)
+        .compile_protos(&[src.join("custom_map.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .bytes_type(".custom_bytes", "::prost::alloc::sync::Arc<[u8]>")
+        .compile_protos(&[src.join("custom_bytes.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .string_type(".custom_string", "crate::custom_string::ShortString")
+        .compile_protos(&[src.join("custom_string.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .const_defaults(["."])
+        .btree_map(["."])
+        .utf8_bytes([".const_default.Settings.name"])
+        .bytes([".const_default.Settings.magic"])
+        .preserve_unknown_fields([".const_default.Limit"])
+        .compile_protos(&[src.join("const_default.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .box_large_oneof_variants(200)
+        .compile_protos(&[src.join("large_oneof.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .recursive_wrapper(prost_build::Wrapper::Arc)
+        .compile_protos(&[src.join("recursive_wrapper_arc.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .box_recursive(false)
+        .boxed(".recursive_wrapper.unboxed.Outer.inner")
+        .compile_protos(&[src.join("recursive_wrapper_unboxed.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .wrapper_type(".wrapper_type.Envelope.payload", prost_build::Wrapper::Arc)
+        .wrapper_type(
+            ".wrapper_type.Envelope.attachment.shared",
+            prost_build::Wrapper::Arc,
+        )
+        .wrapper_type(
+            ".wrapper_type.Envelope.attachment.boxed",
+            prost_build::Wrapper::Box,
+        )
+        .wrapper_type(".wrapper_type.Envelope.history", prost_build::Wrapper::Arc)
+        .compile_protos(&[src.join("wrapper_type.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .cow([".cow"])
+        .compile_protos(&[src.join("cow.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .non_exhaustive_messages(["."])
+        .presence_bits(".non_exhaustive.Counter")
+        .compile_protos(&[src.join("non_exhaustive.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .compile_protos(&[src.join("redact.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .text_format_debug()
+        .compile_protos(&[src.join("text_format_debug.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .enable_reflection()
+        .compile_protos(&[src.join("reflect_message.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .output_layout(prost_build::Layout::FilePerMessage)
+        .compile_protos(&[src.join("file_per_message.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .message_type(".message_types.Order.id", "crate::message_types::OrderId")
+        .message_type(".message_types.Order.email", "crate::message_types::Email")
+        .message_type(".message_types.Order.tags", "crate::message_types::Tag")
+        .message_type(".message_types.Order.price", "crate::message_types::Price")
+        .enable_json()
+        .enable_text_format()
+        .generate_builders(true)
+        .compile_protos(&[src.join("message_types.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .open_enums([".open_enums.Light"])
+        .enable_json()
+        .enable_serde()
+        .enable_text_format()
+        .generate_builders(true)
+        .compile_protos(&[src.join("open_enums.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .generate_oneof_accessors(true)
+        .compile_protos(&[src.join("oneof_accessors.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .generate_conversions(true)
+        .compile_protos(&[src.join("conversions.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .embed_message_descriptors(true)
+        .compile_protos(&[src.join("message_descriptors.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .presence_bits(".presence_bits.Settings")
+        .presence_bits(".presence_bits.Wide")
+        .enable_serde()
+        .enable_json()
+        .generate_builders(true)
+        .compile_protos(&[src.join("presence_bits.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .heapless_messages([".heapless"])
+        .compile_protos(&[src.join("heapless.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .service_generator(Box::new(prost_build::ServiceDefinitionGenerator::new()))
+        .embed_file_descriptor_sets(true)
+        .compile_protos(&[src.join("service_definitions.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .compile_protos(&[src.join("editions.proto")], includes)
+        .unwrap();
+
+    // Arbitrary messages depend on the `std` feature of `prost`.
+    #[cfg(feature = "std")]
+    prost_build::Config::new()
+        .bytes([".arbitrary.Tree.data"])
+        .generate_arbitrary(true)
+        .compile_protos(&[src.join("arbitrary.proto")], includes)
+        .unwrap();
+
+    // Validation depends on the `std` feature of `prost`.
+    #[cfg(feature = "std")]
+    prost_build::Config::new()
+        .validate_messages(["."])
+        .compile_protos(&[src.join("validate.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .derive_provider(Box::new(OptionDerives))
+        .compile_protos(&[src.join("derive_provider.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .generate_field_numbers(true)
+        .compile_protos(&[src.join("field_numbers.proto")], includes)
+        .unwrap();
+
     prost_build::Config::new()
         .disable_comments(["."])
         .compile_protos(&[src.join("disable_comments.proto")], includes)
         .unwrap();
 
+    prost_build::Config::new()
+        .intra_doc_links(true)
+        .compile_protos(&[src.join("doc_links.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .version_conversion(".version_conversion.v1", ".version_conversion.v2")
+        .compile_protos(
+            &[
+                src.join("version_conversion_v1.proto"),
+                src.join("version_conversion_v2.proto"),
+            ],
+            includes,
+        )
+        .unwrap();
+
+    prost_build::Config::new()
+        .enum_display_from_str(["."])
+        .enum_from_str_ignore_case([".enum_str.Mode"])
+        .compile_protos(&[src.join("enum_str.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .derive_eq_hash(["."])
+        .derive_ord([
+            ".derive_eq_hash.Point",
+            ".derive_eq_hash.Path",
+            ".derive_eq_hash.Shape",
+        ])
+        .float_total_order([
+            ".derive_eq_hash.Point",
+            ".derive_eq_hash.Path",
+            ".derive_eq_hash.Shape",
+        ])
+        .compile_protos(&[src.join("derive_eq_hash.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .btree_map(["."])
+        .proto_cmp([
+            ".proto_cmp.Entry",
+            ".proto_cmp.Index",
+            ".proto_cmp.Partial",
+            ".proto_cmp.Wrapper",
+        ])
+        .preserve_unknown_fields([".proto_cmp.Partial"])
+        .compile_protos(&[src.join("proto_cmp.proto")], includes)
+        .unwrap();
+
+    prost_build::Config::new()
+        .compile_protos(
+            &[
+                src.join("packed_encoding_proto2.proto"),
+                src.join("packed_encoding_proto3.proto"),
+            ],
+            includes,
+        )
+        .unwrap();
+
     config
         .bytes(["."])
         .compile_protos(&[src.join("well_known_types.proto")], includes)
@@ -249,3 +588,51 @@ fn main() {
         .compile_protos(&[] as &[&str], &[] as &[&str])
         .unwrap();
 }
+
+/// Generates attributes from the custom options declared in `derive_provider.proto`, as a
+/// GraphQL or OpenAPI crate would.
+struct OptionDerives;
+
+impl DeriveProvider for OptionDerives {
+    fn message_attributes(&self, _: &str, message: &DescriptorProto) -> Vec<String> {
+        // `(ordered)`
+        let ordered = message.options.as_ref().is_some_and(|options| {
+            let mut fields = options._extensions.fields().iter();
+            fields.any(|field| field.tag == 50001)
+        });
+        if ordered {
+            vec!["#[derive(PartialOrd)]".to_string()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn enum_attributes(&self, _: &str, enum_type: &EnumDescriptorProto) -> Vec<String> {
+        // `(aliased)`
+        let aliased = enum_type.options.as_ref().is_some_and(|options| {
+            let mut fields = options._extensions.fields().iter();
+            fields.any(|field| field.tag == 50003)
+        });
+        if aliased {
+            vec![format!(
+                "#[doc(alias = \"{}\")]",
+                enum_type.name().to_lowercase()
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn field_attributes(&self, _: &str, field: &FieldDescriptorProto) -> Vec<String> {
+        // `(searchable)`
+        let searchable = field.options.as_ref().is_some_and(|options| {
+            let mut fields = options._extensions.fields().iter();
+            fields.any(|field| field.tag == 50002)
+        });
+        if searchable {
+            vec!["#[doc(alias = \"searchable\")]".to_string()]
+        } else {
+            Vec::new()
+        }
+    }
+}
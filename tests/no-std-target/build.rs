@@ -0,0 +1,8 @@
+fn main() {
+    prost_build::Config::new()
+        .btree_map(["."])
+        .bytes([".no_std_target.Frame.payload"])
+        .enable_type_names()
+        .compile_protos(&["protos/no_std_target.proto"], &["protos"])
+        .unwrap();
+}
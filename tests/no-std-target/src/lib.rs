@@ -0,0 +1,20 @@
+//! Generated code compiled with only `core` and `alloc`.
+//!
+//! CI builds this crate for a target without `std`, such as `thumbv7em-none-eabihf`, so that
+//! generated code which names a `std` path, or an item which is only in the `std` prelude, fails
+//! to compile.
+#![no_std]
+
+extern crate alloc;
+
+include!(concat!(env!("OUT_DIR"), "/no_std_target.rs"));
+
+use alloc::vec::Vec;
+
+use prost::Message;
+
+/// Encodes a frame and decodes it again.
+pub fn roundtrip(frame: &Frame) -> Result<Frame, prost::DecodeError> {
+    let encoded: Vec<u8> = frame.encode_to_vec();
+    Frame::decode(encoded.as_slice())
+}
@@ -0,0 +1,95 @@
+include!(concat!(env!("OUT_DIR"), "/arbitrary.rs"));
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use prost::arbitrary::{Arbitrary, Unstructured};
+use prost::Message;
+
+/// Returns `len` pseudo-random bytes, from a xorshift generator seeded with `seed`.
+pub(crate) fn input(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn arbitrary_trees() -> impl Iterator<Item = Tree> {
+    (0..64).map(|seed| {
+        let input = input(seed, 4096);
+        Tree::arbitrary(&mut Unstructured::new(&input)).unwrap()
+    })
+}
+
+/// Asserts that the enumeration fields of `tree` and its nested trees hold variants of `Color`.
+fn assert_valid_colors(tree: &Tree) {
+    let valid = |value: &i32| Color::try_from(*value).is_ok();
+    assert!(valid(&tree.color));
+    assert!(tree.optional_color.iter().all(valid));
+    assert!(tree.colors.iter().all(valid));
+    assert!(tree.color_map.values().all(valid));
+    if let Some(tree::Value::OneofColor(color)) = &tree.value {
+        assert!(valid(color));
+    }
+
+    let nested = tree
+        .children
+        .iter()
+        .chain(tree.left.as_deref())
+        .chain(tree.tree_map.values());
+    for nested in nested {
+        assert_valid_colors(nested);
+    }
+    if let Some(tree::Value::OneofTree(nested)) = &tree.value {
+        assert_valid_colors(nested);
+    }
+}
+
+#[test]
+fn enumerations_are_valid() {
+    for tree in arbitrary_trees() {
+        assert_valid_colors(&tree);
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut nested = 0;
+    for tree in arbitrary_trees() {
+        let decoded = Tree::decode(tree.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, tree);
+        nested += tree.children.len() + tree.tree_map.len();
+    }
+    // The inputs are large enough to generate nested messages.
+    assert_ne!(nested, 0);
+}
+
+#[test]
+fn nesting_is_limited() {
+    // Every byte decides to nest another message.
+    let input = vec![1; 1000];
+    let chain = Chain::arbitrary(&mut Unstructured::new(&input)).unwrap();
+
+    let mut depth = 1;
+    let mut next = chain.next.as_deref();
+    while let Some(chain) = next {
+        depth += 1;
+        next = chain.next.as_deref();
+    }
+    assert_eq!(depth, 100);
+
+    let decoded = Chain::decode(chain.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, chain);
+}
+
+#[test]
+fn empty_input() {
+    let tree = Tree::arbitrary(&mut Unstructured::new(&[])).unwrap();
+    assert_eq!(tree, Tree::default());
+}
@@ -0,0 +1,189 @@
+#![allow(dead_code)]
+
+include!(concat!(env!("OUT_DIR"), "/arena.rs"));
+
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::vec;
+
+use prost::bumpalo::Bump;
+use prost::{ArenaMessage, Message};
+
+fn document() -> Document {
+    let section = Section {
+        heading: Some("intro".to_string()),
+        children: vec![Section {
+            heading: Some("child".to_string()),
+            ..Default::default()
+        }],
+        parent: Some(Box::new(Section {
+            heading: Some("parent".to_string()),
+            ..Default::default()
+        })),
+    };
+    Document {
+        title: "title".to_string(),
+        payload: Some(vec![1, 2, 3]),
+        kind: Some(Kind::Leaf as i32),
+        tags: vec!["a".to_string(), "b".to_string()],
+        scores: vec![-1, 0, 1],
+        ids: vec![1, u64::MAX],
+        root: Some(section.clone()),
+        sections: vec![section.clone(), Section::default()],
+        counters: vec![(
+            "counter".to_string(),
+            Counter {
+                count: Some(3),
+                choice: Some(counter::Choice::Flag(true)),
+            },
+        )]
+        .into_iter()
+        .collect(),
+        blobs: vec![(1, vec![0xff]), (2, vec![])].into_iter().collect(),
+        body: Some(document::Body::Section(section)),
+        meta: Some(document::Meta {
+            author: Some("author".to_string()),
+        }),
+        counter: Counter {
+            count: None,
+            choice: Some(counter::Choice::Number(5)),
+        },
+    }
+}
+
+#[test]
+fn decode_in_arena() {
+    let buf = document().encode_to_vec();
+    let arena = Bump::new();
+    let message = DocumentArena::decode_in(buf.as_slice(), &arena).unwrap();
+
+    assert_eq!(message.title, "title");
+    assert_eq!(message.payload, Some(&[1, 2, 3][..]));
+    assert_eq!(message.kind, Some(Kind::Leaf as i32));
+    assert_eq!(message.tags, ["a", "b"]);
+    assert_eq!(message.scores, [-1, 0, 1]);
+    assert_eq!(message.ids, [1, u64::MAX]);
+
+    let root = message.root.as_ref().unwrap();
+    assert_eq!(root.heading, Some("intro"));
+    assert_eq!(root.children[0].heading, Some("child"));
+    assert_eq!(root.parent.as_ref().unwrap().heading, Some("parent"));
+    assert_eq!(message.sections.len(), 2);
+    assert_eq!(message.sections[0], *root);
+
+    assert_eq!(message.counters.len(), 1);
+    assert_eq!(message.counters[0].0, "counter");
+    assert_eq!(message.counters[0].1.count, Some(3));
+    assert_eq!(
+        message.counters[0].1.choice,
+        Some(counter::ChoiceArena::Flag(true))
+    );
+    assert_eq!(message.blobs, [(1, &[0xff][..]), (2, &[][..])]);
+    match message.body {
+        Some(document::BodyArena::Section(ref section)) => assert_eq!(section, root),
+        ref body => panic!("unexpected body: {:?}", body),
+    }
+    assert_eq!(message.meta.as_ref().unwrap().author, Some("author"));
+    assert_eq!(
+        message.counter.choice,
+        Some(counter::ChoiceArena::Number(5))
+    );
+}
+
+#[test]
+fn allocates_in_arena() {
+    let buf = document().encode_to_vec();
+    let arena = Bump::new();
+    let message = DocumentArena::decode_in(buf.as_slice(), &arena).unwrap();
+
+    // Strings and bytes are copied out of the buffer into the arena.
+    let range = buf.as_ptr_range();
+    assert!(!range.contains(&message.title.as_ptr()));
+    assert!(!range.contains(&message.payload.unwrap().as_ptr()));
+    assert!(arena.allocated_bytes() >= buf.len());
+
+    // Messages without fields allocated in the arena decode into it as well.
+    let buf = document().counter.encode_to_vec();
+    let decoded = CounterArena::decode_in(buf.as_slice(), &arena).unwrap();
+    assert_eq!(decoded.choice, Some(counter::ChoiceArena::Number(5)));
+}
+
+#[test]
+fn merge_semantics() {
+    // The last value of a singular field wins, nested messages are merged, and map entries are
+    // kept in the order they were decoded.
+    let first = Document {
+        title: "first".to_string(),
+        root: Some(Section {
+            heading: Some("root".to_string()),
+            ..Default::default()
+        }),
+        blobs: vec![(1, vec![1])].into_iter().collect(),
+        body: Some(document::Body::Text("text".to_string())),
+        ..Default::default()
+    };
+    let second = Document {
+        title: "second".to_string(),
+        root: Some(Section {
+            children: vec![Section::default()],
+            ..Default::default()
+        }),
+        blobs: vec![(1, vec![2])].into_iter().collect(),
+        body: Some(document::Body::Id(7)),
+        ..Default::default()
+    };
+    let mut buf = first.encode_to_vec();
+    second.encode(&mut buf).unwrap();
+
+    let arena = Bump::new();
+    let mut message = DocumentArena::new_in(&arena);
+    message.merge_in(buf.as_slice(), &arena).unwrap();
+    assert_eq!(message.title, "second");
+    let root = message.root.as_ref().unwrap();
+    assert_eq!(root.heading, Some("root"));
+    assert_eq!(root.children.len(), 1);
+    assert_eq!(message.blobs, [(1, &[1][..]), (1, &[2][..])]);
+    assert_eq!(message.body, Some(document::BodyArena::Id(7)));
+}
+
+#[test]
+fn invalid_string() {
+    let arena = Bump::new();
+    let buf = [0x0a, 0x02, 0xc3, 0x28];
+    let error = DocumentArena::decode_in(&buf[..], &arena).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "failed to decode Protobuf message: DocumentArena.title: invalid string value: data is not UTF-8 encoded"
+    );
+}
+
+#[test]
+fn default_getters() {
+    let arena = Bump::new();
+    let buf = Settings::default().encode_to_vec();
+    let settings = SettingsArena::decode_in(buf.as_slice(), &arena).unwrap();
+    assert_eq!(settings.port, None);
+    assert_eq!(settings.port(), 8080);
+    assert_eq!(settings.host(), "localhost");
+    assert_eq!(settings.magic(), [1, 2]);
+    assert_eq!(settings.kind(), Kind::Leaf);
+    assert_eq!(settings.ratio(), f64::INFINITY);
+    assert_eq!(settings.scale(), 1.5);
+    assert!(!settings.enabled());
+
+    let buf = Settings {
+        port: Some(443),
+        host: Some("example.com".to_string()),
+        magic: Some(vec![]),
+        kind: Some(Kind::Unspecified as i32),
+        enabled: Some(true),
+        ..Default::default()
+    }
+    .encode_to_vec();
+    let settings = SettingsArena::decode_in(buf.as_slice(), &arena).unwrap();
+    assert_eq!(settings.port(), 443);
+    assert_eq!(settings.host(), "example.com");
+    assert_eq!(settings.magic(), [0u8; 0]);
+    assert_eq!(settings.kind(), Kind::Unspecified);
+    assert!(settings.enabled());
+}
@@ -1,4 +1,4 @@
-#![cfg(feature = "std")]
+#![cfg(all(feature = "std", not(target_family = "wasm")))]
 
 use std::path::Path;
 use std::path::PathBuf;
@@ -21,7 +21,8 @@ macro_rules! assert_eq_bootstrapped_file {
 }
 
 /// Test which bootstraps protobuf.rs and compiler.rs from the .proto definitions in the Protobuf
-/// repo. Ensures that the checked-in compiled versions are up-to-date.
+/// repo, and rpc.rs and type.rs from the .proto definitions in the googleapis repo. Ensures that
+/// the checked-in compiled versions are up-to-date.
 #[test]
 fn bootstrap() {
     let include = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -40,6 +41,8 @@ fn bootstrap() {
     prost_build::Config::new()
         .compile_well_known_types()
         .btree_map(["."])
+        .preserve_extensions(["."])
+        .enable_field_masks()
         .type_attribute(
             ".",
             r#"#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]"#,
@@ -61,7 +64,7 @@ fn bootstrap() {
                 protobuf.join("timestamp.proto"),
                 protobuf.join("type.proto"),
             ],
-            &[include],
+            &[&include],
         )
         .unwrap();
 
@@ -80,4 +83,75 @@ fn bootstrap() {
         src.join("compiler.rs"),
         tempdir.path().join("google.protobuf.compiler.rs")
     );
+
+    // The common types of Google APIs, which refer to the well-known types of this crate.
+    let googleapis = tempdir.path().join("googleapis");
+    std::fs::create_dir(&googleapis).unwrap();
+    let rpc = include.join("google").join("rpc");
+    let r#type = include.join("google").join("type");
+
+    prost_build::Config::new()
+        .extern_path(".google.protobuf", "crate")
+        .btree_map(["."])
+        .enable_field_masks()
+        .type_attribute(
+            ".",
+            r#"#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]"#,
+        )
+        .out_dir(&googleapis)
+        .compile_protos(
+            &[
+                rpc.join("status.proto"),
+                rpc.join("code.proto"),
+                rpc.join("error_details.proto"),
+                r#type.join("date.proto"),
+                r#type.join("latlng.proto"),
+                r#type.join("money.proto"),
+                r#type.join("timeofday.proto"),
+            ],
+            &[&include],
+        )
+        .unwrap();
+
+    assert_eq_bootstrapped_file!(src.join("rpc.rs"), googleapis.join("google.rpc.rs"));
+
+    assert_eq_bootstrapped_file!(src.join("type.rs"), googleapis.join("google.type.rs"));
+
+    // The descriptors of the well-known types, which descriptor pools add to the files which
+    // import them.
+    let well_known_types = tempdir.path().join("well_known_types.bin");
+    let status = std::process::Command::new(prost_build::protoc_from_env())
+        .arg("--include_imports")
+        .arg("-I")
+        .arg(&include)
+        .arg("--descriptor_set_out")
+        .arg(&well_known_types)
+        .args(
+            [
+                "any.proto",
+                "api.proto",
+                "descriptor.proto",
+                "duration.proto",
+                "empty.proto",
+                "field_mask.proto",
+                "source_context.proto",
+                "struct.proto",
+                "timestamp.proto",
+                "type.proto",
+                "wrappers.proto",
+            ]
+            .iter()
+            .map(|file| protobuf.join(file)),
+        )
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let expected_path = src.join("reflect").join("well_known_types.bin");
+    let expected = std::fs::read(&expected_path).unwrap_or_default();
+    let actual = std::fs::read(&well_known_types).unwrap();
+    if expected != actual {
+        std::fs::write(&expected_path, &actual).unwrap();
+    }
+    assert!(expected == actual, "well_known_types.bin is out of date");
 }
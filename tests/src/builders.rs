@@ -0,0 +1,85 @@
+#![allow(dead_code)]
+
+include!(concat!(env!("OUT_DIR"), "/builders.rs"));
+
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::vec;
+
+use prost::BuildError;
+
+#[test]
+fn build_message() {
+    let person = Person::builder()
+        .name("Ferris")
+        .id(7)
+        .email("ferris@example.com")
+        .role(Role::Admin)
+        .tags(vec!["a", "b"])
+        .push_tags("c")
+        .address(
+            Address::builder()
+                .street("Main Street")
+                .previous(Address::builder().street("Side Street").build().unwrap())
+                .build()
+                .unwrap(),
+        )
+        .scores(vec![("go", 5)])
+        .insert_scores("rust", 10)
+        .avatar(&b"png"[..])
+        .r#type("crab")
+        .set_build(true)
+        .phone("555")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        person,
+        Person {
+            name: "Ferris".to_string(),
+            id: 7,
+            email: Some("ferris@example.com".to_string()),
+            role: Some(Role::Admin as i32),
+            tags: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            address: Some(Address {
+                street: Some("Main Street".to_string()),
+                previous: Some(Box::new(Address {
+                    street: Some("Side Street".to_string()),
+                    previous: None,
+                })),
+            }),
+            scores: vec![("go".to_string(), 5), ("rust".to_string(), 10)]
+                .into_iter()
+                .collect(),
+            avatar: Some(b"png".to_vec()),
+            r#type: Some("crab".to_string()),
+            build: Some(true),
+            contact: Some(person::Contact::Phone("555".to_string())),
+        }
+    );
+
+    let rebuilt = PersonBuilder::from(person.clone())
+        .office(Address::default())
+        .build()
+        .unwrap();
+    assert_eq!(
+        rebuilt.contact,
+        Some(person::Contact::Office(Address::default()))
+    );
+    assert_eq!(rebuilt.name, person.name);
+}
+
+#[test]
+fn missing_required_field() {
+    let error = Person::builder().name("Ferris").build().unwrap_err();
+    assert_eq!(error, BuildError::missing_field("builders.Person", "id"));
+    assert_eq!(error.message(), "builders.Person");
+    assert_eq!(error.field(), "id");
+    assert_eq!(
+        error.to_string(),
+        "failed to build Protobuf message builders.Person: missing required field id"
+    );
+
+    // Required fields which are set to their default value are present.
+    assert!(PersonBuilder::new().name("").id(0).build().is_ok());
+}
@@ -0,0 +1,45 @@
+include!(concat!(env!("OUT_DIR"), "/const_default.rs"));
+
+use prost::Message;
+
+static SETTINGS: Settings = Settings::DEFAULT;
+
+#[test]
+fn const_default_matches_default() {
+    assert_eq!(Settings::DEFAULT, Settings::default());
+    assert_eq!(Limit::DEFAULT, Limit::default());
+    assert_eq!(SETTINGS, Settings::default());
+}
+
+#[test]
+fn const_default_has_declared_defaults() {
+    let settings = Settings::DEFAULT;
+    assert_eq!(settings.retries, 3);
+    assert_eq!(settings.name, "settings");
+    assert_eq!(settings.magic, &[1, 2][..]);
+    assert_eq!(settings.level(), Level::Low);
+    assert_eq!(settings.fallback, None);
+    assert_eq!(settings.fallback(), Level::High);
+    assert_eq!(settings.comment(), "none");
+    assert_eq!(settings.limit.max, 100);
+    assert_eq!(settings.limit.ratio(), 0.5);
+    assert!(settings.limits.is_empty());
+    assert!(settings.weights.is_empty());
+    assert_eq!(settings.target, None);
+}
+
+#[test]
+fn default_instance_is_static() {
+    let instance = Settings::default_instance();
+    assert!(core::ptr::eq(instance, Settings::default_instance()));
+    assert_eq!(instance, &Settings::default());
+    assert_eq!(
+        Settings::decode(instance.encode_to_vec().as_slice()).unwrap(),
+        *instance
+    );
+}
+
+#[test]
+fn unsupported_fields_keep_default() {
+    assert_eq!(Label::default().text, "label");
+}
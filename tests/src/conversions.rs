@@ -0,0 +1,41 @@
+include!(concat!(env!("OUT_DIR"), "/conversions.rs"));
+
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use prost::bytes::Bytes;
+use prost::Message;
+
+fn shirt() -> Shirt {
+    Shirt {
+        color: "red".to_string(),
+        sizes: vec![("m".to_string(), 2)].into_iter().collect(),
+        tags: vec![shirt::Tag {
+            label: "sale".to_string(),
+        }],
+    }
+}
+
+#[test]
+fn encode_with_from() {
+    let shirt = shirt();
+    assert_eq!(Vec::<u8>::from(shirt.clone()), shirt.encode_to_vec());
+
+    let tag = shirt::Tag {
+        label: "new".to_string(),
+    };
+    let encoded: Vec<u8> = tag.clone().into();
+    assert_eq!(encoded, tag.encode_to_vec());
+}
+
+#[test]
+fn decode_with_try_from() {
+    let shirt = shirt();
+    let encoded = shirt.encode_to_vec();
+    assert_eq!(Shirt::try_from(encoded.as_slice()), Ok(shirt.clone()));
+    assert_eq!(Shirt::try_from(Bytes::from(encoded.clone())), Ok(shirt));
+
+    assert!(Shirt::try_from(&encoded[..encoded.len() - 1]).is_err());
+}
@@ -0,0 +1,46 @@
+include!(concat!(env!("OUT_DIR"), "/cow.rs"));
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use prost::Message;
+
+const WIDGET: CatalogEntry = CatalogEntry {
+    name: Some(Cow::Borrowed("widget")),
+    kind: Cow::Borrowed("tool"),
+    checksum: Some(Cow::Borrowed(b"\x12\x34")),
+    aliases: Vec::new(),
+    labels: BTreeMap::new(),
+};
+
+#[test]
+fn defaults_are_borrowed() {
+    let entry = CatalogEntry::default();
+    assert!(matches!(entry.kind, Cow::Borrowed("item")));
+    assert_eq!(entry.checksum(), b"\x00\x01");
+}
+
+#[test]
+fn static_messages_round_trip() {
+    let decoded = CatalogEntry::decode(WIDGET.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, WIDGET);
+    // Decoded values are owned.
+    assert!(matches!(decoded.kind, Cow::Owned(_)));
+    assert!(matches!(decoded.checksum, Some(Cow::Owned(_))));
+}
+
+#[test]
+fn decoding_replaces_borrowed_values() {
+    let entry = CatalogEntry {
+        aliases: vec![Cow::Borrowed("gadget"), Cow::Owned("gizmo".to_string())],
+        labels: BTreeMap::from([("color".to_string(), "red".to_string())]),
+        ..WIDGET
+    };
+    let mut decoded = WIDGET;
+    decoded.merge(entry.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, entry);
+    assert!(matches!(decoded.name, Some(Cow::Owned(_))));
+}
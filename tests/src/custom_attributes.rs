@@ -6,3 +6,14 @@
 #![deny(missing_docs)]
 
 include!(concat!(env!("OUT_DIR"), "/custom_attributes.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oneof_attributes() {
+        assert!(other_msg::Choice::E("e".into()) < other_msg::Choice::F(b"e".to_vec()));
+        assert!(msg::Field::A("b".into()) > msg::Field::A("a".into()));
+    }
+}
@@ -0,0 +1,55 @@
+include!(concat!(env!("OUT_DIR"), "/custom_bytes.rs"));
+
+use alloc::sync::Arc;
+use alloc::vec;
+
+use prost::Message;
+
+#[test]
+fn roundtrip() {
+    let blob = Blob {
+        data: Arc::from(&b"data"[..]),
+        checksum: Some(Arc::from(&b"sum"[..])),
+        chunks: vec![Arc::from(&b"a"[..]), Arc::from(&b""[..])],
+        source: Some(blob::Source::Inline(Arc::from(&b"inline"[..]))),
+    };
+
+    let decoded = Blob::decode(blob.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, blob);
+    assert_eq!(decoded.encoded_len(), blob.encoded_len());
+}
+
+#[test]
+fn default_fields_are_not_encoded() {
+    let blob = Blob::default();
+    assert!(blob.data.is_empty());
+    assert_eq!(blob.checksum(), b"");
+    assert!(blob.encode_to_vec().is_empty());
+}
+
+#[test]
+fn clones_share_the_bytes() {
+    let blob = Blob {
+        data: Arc::from(&b"data"[..]),
+        ..Blob::default()
+    };
+    let clone = blob.clone();
+    assert!(Arc::ptr_eq(&blob.data, &clone.data));
+}
+
+#[test]
+fn merge_replaces_bytes() {
+    let mut blob = Blob {
+        data: Arc::from(&b"old"[..]),
+        ..Blob::default()
+    };
+    let new = Blob {
+        data: Arc::from(&b"new"[..]),
+        ..Blob::default()
+    };
+    blob.merge(new.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(&blob.data[..], b"new");
+
+    blob.clear();
+    assert_eq!(blob, Blob::default());
+}
@@ -0,0 +1,108 @@
+//! Tests for fields encoded by the functions of a module, with `#[prost(with = "...")]`.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use prost::Message;
+
+/// Encodes a `u128` as a nested message with its high and low halves in two `fixed64` fields.
+mod halves {
+    use prost::bytes::{Buf, BufMut};
+    use prost::encoding::{message, DecodeContext, WireType};
+    use prost::DecodeError;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Halves {
+        #[prost(fixed64, tag = "1")]
+        pub high: u64,
+        #[prost(fixed64, tag = "2")]
+        pub low: u64,
+    }
+
+    fn split(value: u128) -> Halves {
+        Halves {
+            high: (value >> 64) as u64,
+            low: value as u64,
+        }
+    }
+
+    pub fn encode(tag: u32, value: &u128, buf: &mut impl BufMut) {
+        if *value != 0 {
+            message::encode(tag, &split(*value), buf);
+        }
+    }
+
+    pub fn merge(
+        wire_type: WireType,
+        value: &mut u128,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        let mut halves = split(*value);
+        message::merge(wire_type, &mut halves, buf, ctx)?;
+        *value = u128::from(halves.high) << 64 | u128::from(halves.low);
+        Ok(())
+    }
+
+    pub fn encoded_len(tag: u32, value: &u128) -> usize {
+        if *value == 0 {
+            0
+        } else {
+            message::encoded_len(tag, &split(*value))
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Account {
+    #[prost(string, tag = "1")]
+    name: prost::alloc::string::String,
+    #[prost(with = "halves", tag = "2")]
+    balance: u128,
+    #[prost(with = "prost::encoding::uint32", tag = "3")]
+    flags: u32,
+}
+
+#[test]
+fn with_fields_use_the_module_encoding() {
+    let account = Account {
+        name: "savings".into(),
+        balance: u128::MAX - 1,
+        flags: 0,
+    };
+    let encoded = account.encode_to_vec();
+    assert_eq!(encoded.len(), account.encoded_len());
+
+    let halves = halves::Halves {
+        high: u64::MAX,
+        low: u64::MAX - 1,
+    };
+    let mut expected = Vec::new();
+    prost::encoding::string::encode(1, &account.name, &mut expected);
+    prost::encoding::message::encode(2, &halves, &mut expected);
+    // The module of `prost::encoding` always encodes the field, even if it is zero.
+    prost::encoding::uint32::encode(3, &0, &mut expected);
+    assert_eq!(encoded, expected);
+
+    assert_eq!(Account::decode(encoded.as_slice()).unwrap(), account);
+}
+
+#[test]
+fn with_fields_are_cleared_and_merged() {
+    let mut account = Account {
+        name: "savings".into(),
+        balance: 1 << 100,
+        flags: 7,
+    };
+    account.merge_from(&Account {
+        name: "".into(),
+        balance: 1,
+        flags: 0,
+    });
+    // The halves are merged as the fields of a message, so the zero high half is not encoded.
+    assert_eq!(account.balance, 1 << 100 | 1);
+    assert_eq!(account.flags, 0);
+
+    account.clear();
+    assert_eq!(account, Account::default());
+}
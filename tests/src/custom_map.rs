@@ -0,0 +1,142 @@
+include!(concat!(env!("OUT_DIR"), "/custom_map.rs"));
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use prost::Message;
+
+/// A map which keeps its entries in insertion order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VecMap<K, V>(Vec<(K, V)>);
+
+impl<K, V> Default for VecMap<K, V> {
+    fn default() -> Self {
+        VecMap(Vec::new())
+    }
+}
+
+impl<K: PartialEq, V> VecMap<K, V> {
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: PartialEq + ?Sized,
+    {
+        self.0
+            .iter()
+            .find(|(k, _)| k.borrow() == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => Some(core::mem::replace(v, value)),
+            None => {
+                self.0.push((key, value));
+                None
+            }
+        }
+    }
+}
+
+impl<K: PartialEq, V> Extend<(K, V)> for VecMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: PartialEq, V> FromIterator<(K, V)> for VecMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = VecMap::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a VecMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter =
+        core::iter::Map<core::slice::Iter<'a, (K, V)>, fn(&'a (K, V)) -> (&'a K, &'a V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter().map(|(k, v)| (k, v))
+    }
+}
+
+fn inventory() -> Inventory {
+    let mut inventory = Inventory {
+        counts: [("b".to_string(), 2), ("a".to_string(), 1)]
+            .into_iter()
+            .collect(),
+        items: [(
+            3,
+            Item {
+                name: "item".to_string(),
+            },
+        )]
+        .into_iter()
+        .collect(),
+        blobs: [(1, vec![0xff])].into_iter().collect(),
+        ..Default::default()
+    };
+    inventory.insert_colors("red".to_string(), Color::Red);
+    inventory
+}
+
+#[test]
+fn roundtrip_in_insertion_order() {
+    let inventory = inventory();
+    let decoded = Inventory::decode(inventory.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, inventory);
+    assert_eq!(decoded.encoded_len(), inventory.encoded_len());
+    assert_eq!(
+        decoded.counts.0,
+        [("b".to_string(), 2), ("a".to_string(), 1)]
+    );
+    assert_eq!(decoded.get_colors("red"), Some(Color::Red));
+}
+
+#[test]
+fn later_entries_replace_earlier_ones() {
+    let mut buf = inventory().encode_to_vec();
+    Inventory {
+        counts: [("b".to_string(), 5)].into_iter().collect(),
+        ..Default::default()
+    }
+    .encode(&mut buf)
+    .unwrap();
+
+    let decoded = Inventory::decode(buf.as_slice()).unwrap();
+    assert_eq!(decoded.counts.get("b"), Some(&5));
+    assert_eq!(decoded.counts.0.len(), 2);
+
+    let mut merged = inventory();
+    merged.clear();
+    assert_eq!(merged, Inventory::default());
+}
+
+#[test]
+fn debug_custom_map() {
+    let debug = format!("{:?}", inventory());
+    assert!(debug.contains(r#"counts: {"b": 2, "a": 1}"#), "{debug}");
+    assert!(debug.contains(r#"colors: {"red": Red}"#), "{debug}");
+}
+
+#[test]
+fn build_custom_map() {
+    let inventory = Inventory::builder()
+        .counts([("b", 2)])
+        .insert_counts("a", 1)
+        .build()
+        .unwrap();
+    assert_eq!(
+        inventory.counts,
+        [("b".to_string(), 2), ("a".to_string(), 1)]
+            .into_iter()
+            .collect::<VecMap<String, i32>>()
+    );
+}
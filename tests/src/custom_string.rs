@@ -0,0 +1,128 @@
+include!(concat!(env!("OUT_DIR"), "/custom_string.rs"));
+
+use alloc::string::String;
+use alloc::vec;
+use core::hash::{Hash, Hasher};
+
+use prost::{Message, StringAdapter};
+
+const INLINE: usize = 22;
+
+/// A string which stores values of up to `INLINE` bytes inline, without allocating.
+#[derive(Clone, Debug)]
+pub enum ShortString {
+    Inline { len: u8, bytes: [u8; INLINE] },
+    Heap(String),
+}
+
+impl ShortString {
+    fn is_inline(&self) -> bool {
+        matches!(self, ShortString::Inline { .. })
+    }
+}
+
+impl Default for ShortString {
+    fn default() -> Self {
+        ShortString::Inline {
+            len: 0,
+            bytes: [0; INLINE],
+        }
+    }
+}
+
+impl AsRef<str> for ShortString {
+    fn as_ref(&self) -> &str {
+        match self {
+            ShortString::Inline { len, bytes } => {
+                core::str::from_utf8(&bytes[..*len as usize]).unwrap()
+            }
+            ShortString::Heap(value) => value,
+        }
+    }
+}
+
+impl From<&str> for ShortString {
+    fn from(value: &str) -> Self {
+        let mut string = ShortString::default();
+        string.replace_with(value);
+        string
+    }
+}
+
+impl PartialEq for ShortString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for ShortString {}
+
+impl Hash for ShortString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl StringAdapter for ShortString {
+    fn as_str(&self) -> &str {
+        self.as_ref()
+    }
+
+    fn replace_with(&mut self, value: &str) {
+        *self = if value.len() <= INLINE {
+            let mut bytes = [0; INLINE];
+            bytes[..value.len()].copy_from_slice(value.as_bytes());
+            ShortString::Inline {
+                len: value.len() as u8,
+                bytes,
+            }
+        } else {
+            ShortString::Heap(value.into())
+        };
+    }
+}
+
+#[test]
+fn roundtrip() {
+    let label = Label {
+        key: "env".into(),
+        value: Some("a value which does not fit inline".into()),
+        tags: vec!["red".into(), "".into()],
+        target: Some(label::Target::Id("id-1".into())),
+    };
+
+    let decoded = Label::decode(label.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, label);
+    assert!(decoded.key.is_inline());
+    assert!(!decoded.value.as_ref().unwrap().is_inline());
+}
+
+#[test]
+fn default_fields_are_not_encoded() {
+    let label = Label::default();
+    assert_eq!(label.value(), "");
+    assert!(label.encode_to_vec().is_empty());
+}
+
+#[test]
+fn invalid_utf8_is_rejected() {
+    let encoded = [0x0a, 0x02, 0xff, 0xfe];
+    assert!(Label::decode(&encoded[..]).is_err());
+}
+
+#[test]
+fn merge_replaces_strings() {
+    let mut label = Label {
+        key: "old".into(),
+        ..Label::default()
+    };
+    let new = Label {
+        key: "new".into(),
+        ..Label::default()
+    };
+    label.merge(new.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(label.key.as_str(), "new");
+
+    label.clear();
+    assert_eq!(label, Label::default());
+}
@@ -1,6 +1,6 @@
 #![cfg(test)]
 
-use alloc::{boxed::Box, string::ToString, vec::Vec};
+use alloc::{boxed::Box, format, string::ToString, vec, vec::Vec};
 use prost::Message;
 use protobuf::test_messages::proto3::TestAllTypesProto3;
 
@@ -148,3 +148,52 @@ fn test_decode_error_any() {
             "failed to decode Protobuf message: unexpected type URL.type_url: expected type URL: \"type.googleapis.com/google.protobuf.Timestamp\" (got: \"non-existing-url\")"
         );
 }
+
+#[test]
+fn test_decode_error_context() {
+    use prost::encoding::WireType;
+
+    // `optional_int32: 150`, followed by `optional_int32` with the length-delimited wire type.
+    let msg = [0x08, 0x96, 0x01, 0x0A, 0x00].as_slice();
+    let error = TestAllTypesProto3::decode(msg).unwrap_err();
+
+    assert_eq!(error.offset(), Some(4));
+    assert_eq!(error.path(), "optional_int32");
+    assert_eq!(error.field_numbers(), [1]);
+    assert_eq!(error.expected_wire_type(), Some(WireType::Varint));
+    assert_eq!(error.actual_wire_type(), Some(WireType::LengthDelimited));
+    assert_eq!(
+        format!("{error:#}"),
+        "failed to decode Protobuf message: TestAllTypesProto3.optional_int32: invalid wire type: LengthDelimited (expected Varint) (at offset 4, in field optional_int32)"
+    );
+}
+
+#[test]
+fn test_decode_error_context_repeated() {
+    use protobuf::test_messages::proto3::test_all_types_proto3::NestedMessage;
+
+    let msg = TestAllTypesProto3 {
+        repeated_nested_message: vec![
+            NestedMessage {
+                a: 1,
+                ..Default::default()
+            },
+            NestedMessage {
+                a: -1,
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    let mut buf = msg.encode_to_vec();
+
+    // Last byte is part of varint value `-1`. Set it to an invalid value.
+    assert_eq!(buf.last().unwrap(), &0x01);
+    *buf.last_mut().unwrap() = 0xFF;
+
+    let error = TestAllTypesProto3::decode(buf.as_slice()).unwrap_err();
+    assert_eq!(error.path(), "repeated_nested_message[1].a");
+    assert_eq!(error.field_numbers(), [48, 1]);
+    assert_eq!(error.expected_wire_type(), None);
+    assert!(error.offset().unwrap() <= buf.len());
+}
@@ -83,6 +83,13 @@ fn test_enum_try_from_i32() {
         PrivacyLevel::try_from(4)
     );
     assert_eq!(Err(prost::UnknownEnumValue(5)), PrivacyLevel::try_from(5));
+    assert_eq!(
+        Err(prost::UnknownEnumValue(5)),
+        PrivacyLevel::try_from_i32(5)
+    );
+    assert_eq!(Ok(PrivacyLevel::Two), PrivacyLevel::try_from_i32(2));
+    assert!(PrivacyLevel::is_valid(4));
+    assert!(!PrivacyLevel::is_valid(5));
 
     assert_eq!(
         Ok(ERemoteClientBroadcastMsg::KERemoteClientBroadcastMsgDiscovery),
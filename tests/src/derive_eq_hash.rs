@@ -0,0 +1,90 @@
+include!(concat!(env!("OUT_DIR"), "/derive_eq_hash.rs"));
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+
+trait TestEqHashIsImplemented: Eq + Hash {}
+
+impl TestEqHashIsImplemented for Point {}
+
+impl TestEqHashIsImplemented for Path {}
+
+impl TestEqHashIsImplemented for Shape {}
+
+impl TestEqHashIsImplemented for shape::Kind {}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    // `DefaultHasher` is not available without `std`.
+    struct Fnv(u64);
+    impl Hasher for Fnv {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+        fn write(&mut self, bytes: &[u8]) {
+            for byte in bytes {
+                self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x100000001b3);
+            }
+        }
+    }
+    let mut hasher = Fnv(0xcbf29ce484222325);
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn path(x: f64) -> Path {
+    Path {
+        name: "path".to_string(),
+        points: vec![Point { x: 0.0, y: 0.0 }, Point { x, y: 1.0 }],
+        weights: BTreeMap::from([("a".to_string(), 0.5), ("b".to_string(), f32::NAN)]),
+        tolerance: Some(f32::NAN),
+    }
+}
+
+#[test]
+fn floats_are_compared_by_total_order() {
+    assert_eq!(path(f64::NAN), path(f64::NAN));
+    assert_eq!(hash(&path(f64::NAN)), hash(&path(f64::NAN)));
+    assert_ne!(path(0.0), path(-0.0));
+
+    let nan = Shape {
+        kind: Some(shape::Kind::Radius(f32::NAN)),
+    };
+    assert_eq!(nan, nan.clone());
+    assert_eq!(hash(&nan), hash(&nan.clone()));
+
+    // Without `float_total_order`, `NaN` is not equal to itself.
+    let measurement = Measurement { value: f32::NAN };
+    assert_ne!(measurement, measurement.clone());
+}
+
+#[test]
+fn messages_are_ordered_by_fields() {
+    let a = Point { x: 1.0, y: 2.0 };
+    let b = Point {
+        x: 1.0,
+        y: f64::NAN,
+    };
+    let c = Point { x: 2.0, y: -1.0 };
+    assert_eq!(a.cmp(&b), Ordering::Less);
+    assert_eq!(b.partial_cmp(&c), Some(Ordering::Less));
+
+    let set = BTreeSet::from([c.clone(), b.clone(), a.clone(), b.clone()]);
+    assert_eq!(set.into_iter().collect::<Vec<_>>(), [a, b, c]);
+
+    assert!(path(1.0) < path(2.0));
+    assert!(path(2.0) < path(f64::NAN));
+
+    // Oneof variants are ordered by their declaration order.
+    let radius = Shape {
+        kind: Some(shape::Kind::Radius(10.0)),
+    };
+    let polyline = Shape {
+        kind: Some(shape::Kind::Path(path(0.0))),
+    };
+    assert!(Shape::default() < radius);
+    assert!(radius < polyline);
+}
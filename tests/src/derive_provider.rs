@@ -0,0 +1,33 @@
+//! The attributes of these types are generated by a `DeriveProvider` from their custom options.
+include!(concat!(env!("OUT_DIR"), "/derive_provider.rs"));
+
+const GENERATED: &str = include_str!(concat!(env!("OUT_DIR"), "/derive_provider.rs"));
+
+#[test]
+fn message_attributes() {
+    // `Product` derives `PartialOrd` through its `(ordered)` option.
+    let cheap = Product {
+        price: Some(1),
+        ..Product::default()
+    };
+    let expensive = Product {
+        price: Some(2),
+        ..Product::default()
+    };
+    assert!(cheap < expensive);
+    assert_eq!(GENERATED.matches("#[derive(PartialOrd)]").count(), 1);
+}
+
+#[test]
+fn field_attributes() {
+    // Fields, oneof variants and map fields with the `(searchable)` option get a doc alias.
+    assert_eq!(
+        GENERATED.matches("#[doc(alias = \"searchable\")]").count(),
+        3
+    );
+}
+
+#[test]
+fn enum_attributes() {
+    assert_eq!(GENERATED.matches("#[doc(alias = \"size\")]").count(), 1);
+}
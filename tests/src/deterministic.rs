@@ -0,0 +1,99 @@
+include!(concat!(env!("OUT_DIR"), "/deterministic.rs"));
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use prost::Message;
+
+fn counts(names: &[&str]) -> Vec<(String, i32)> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.to_string(), i as i32 + 1))
+        .collect()
+}
+
+fn item(name: &str) -> Item {
+    Item {
+        name: name.to_string(),
+        counts: counts(&["zeta", "alpha", "mu", "beta", "omega"])
+            .into_iter()
+            .collect(),
+    }
+}
+
+fn inventory() -> Inventory {
+    Inventory {
+        counts: counts(&["pears", "apples", "plums", "figs", "kiwis", "limes"])
+            .into_iter()
+            .collect(),
+        items: (0..16).map(|i| (i * 7 % 11 - 5, item("item"))).collect(),
+        featured: Some(item("featured")),
+        choice: Some(inventory::Choice::Chosen(item("chosen"))),
+    }
+}
+
+/// Returns the `BTreeMap` version of `inventory()`.
+fn sorted_inventory() -> SortedInventory {
+    SortedInventory::decode(inventory().encode_to_vec().as_slice()).unwrap()
+}
+
+#[test]
+fn deterministic_maps() {
+    let inventory = inventory();
+    let encoded = inventory.encode_to_vec_deterministic();
+    assert_eq!(encoded.len(), inventory.encoded_len());
+
+    // Map entries, including those of nested messages, are encoded in the order of their keys.
+    assert_eq!(encoded, sorted_inventory().encode_to_vec());
+    assert_eq!(Inventory::decode(encoded.as_slice()).unwrap(), inventory);
+
+    let mut buf = Vec::new();
+    inventory.encode_deterministic(&mut buf).unwrap();
+    assert_eq!(buf, encoded);
+}
+
+#[test]
+fn deterministic_unknown_fields() {
+    // Encode the fields out of order, which `PartialInventory` keeps for the fields it does not
+    // know.
+    let inventory = sorted_inventory();
+    let mut buf = Vec::new();
+    SortedInventory {
+        featured: inventory.featured.clone(),
+        choice: inventory.choice.clone(),
+        ..SortedInventory::default()
+    }
+    .encode(&mut buf)
+    .unwrap();
+    SortedInventory {
+        items: inventory.items.clone(),
+        ..SortedInventory::default()
+    }
+    .encode(&mut buf)
+    .unwrap();
+    SortedInventory {
+        counts: inventory.counts.clone(),
+        ..SortedInventory::default()
+    }
+    .encode(&mut buf)
+    .unwrap();
+    assert_ne!(buf, inventory.encode_to_vec());
+
+    // Unknown fields are encoded in the order of their field numbers, after the known fields.
+    let partial = PartialInventory::decode(buf.as_slice()).unwrap();
+    let mut expected = Vec::new();
+    SortedInventory {
+        featured: inventory.featured.clone(),
+        ..SortedInventory::default()
+    }
+    .encode(&mut expected)
+    .unwrap();
+    SortedInventory {
+        featured: None,
+        ..inventory
+    }
+    .encode(&mut expected)
+    .unwrap();
+    assert_eq!(partial.encode_to_vec_deterministic(), expected);
+}
@@ -0,0 +1,92 @@
+//! Differential tests against `protoc`: arbitrary messages are encoded by prost, decoded to the
+//! text format and encoded again by `protoc`, and decoded by prost, which must yield the original
+//! messages.
+
+use std::fmt::Debug;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use prost::arbitrary::{Arbitrary, Unstructured};
+use prost::Message;
+use protobuf::test_messages::proto2::TestAllTypesProto2;
+use protobuf::test_messages::proto3::TestAllTypesProto3;
+
+use crate::arbitrary::input;
+
+/// Runs `protoc` with `args`, feeding it `stdin`, and returns its output.
+fn protoc(args: &[&str], stdin: &[u8]) -> Vec<u8> {
+    let protoc = protobuf::protoc();
+    let mut command = Command::new(&protoc);
+    if cfg!(target_os = "macos") {
+        command.env(
+            "DYLD_LIBRARY_PATH",
+            protoc.parent().unwrap().with_file_name("lib"),
+        );
+    }
+    let mut child = command
+        .arg(format!(
+            "--proto_path={}",
+            protobuf::include_dir().display()
+        ))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run protoc");
+
+    // `protoc` reads all of its input before writing any output.
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "protoc {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output.stdout
+}
+
+/// Checks arbitrary messages of type `M`, declared as `type_name` in `file`, through
+/// `protoc --decode` and `protoc --encode`.
+fn check_arbitrary<M>(file: &str, type_name: &str)
+where
+    M: Message + Default + Debug + for<'a> Arbitrary<'a>,
+{
+    let decode = format!("--decode={type_name}");
+    let encode = format!("--encode={type_name}");
+    for seed in 0..32 {
+        let input = input(seed, 2048);
+        let message = M::arbitrary(&mut Unstructured::new(&input)).unwrap();
+
+        let text = protoc(&[&decode, file], &message.encode_to_vec());
+        let encoded = protoc(&[&encode, file], &text);
+        let decoded = M::decode(encoded.as_slice()).unwrap_or_else(|error| {
+            panic!("failed to decode the encoding of protoc (seed {seed}): {error}")
+        });
+
+        // NaNs are unequal to themselves and `protoc` does not keep their payloads, so the
+        // messages are compared by their `Debug` output, which prints every NaN alike.
+        assert_eq!(
+            format!("{decoded:?}"),
+            format!("{message:?}"),
+            "seed {seed}, text format:\n{}",
+            String::from_utf8_lossy(&text)
+        );
+    }
+}
+
+#[test]
+fn test_all_types_proto3() {
+    check_arbitrary::<TestAllTypesProto3>(
+        "google/protobuf/test_messages_proto3.proto",
+        "protobuf_test_messages.proto3.TestAllTypesProto3",
+    );
+}
+
+#[test]
+fn test_all_types_proto2() {
+    check_arbitrary::<TestAllTypesProto2>(
+        "google/protobuf/test_messages_proto2.proto",
+        "protobuf_test_messages.proto2.TestAllTypesProto2",
+    );
+}
@@ -0,0 +1,23 @@
+//! Links to Protobuf types in comments are converted into intra-doc links by
+//! `Config::intra_doc_links`, which rustdoc checks when the generated code is documented.
+include!(concat!(env!("OUT_DIR"), "/doc_links.v1.rs"));
+
+const GENERATED: &str = include_str!(concat!(env!("OUT_DIR"), "/doc_links.v1.rs"));
+
+#[test]
+fn test_intra_doc_links() {
+    for link in [
+        // Messages, nested messages and fields.
+        "[Color][Color] values",
+        "see also [Swatch][palette::Swatch]",
+        "named [Color][super::Color]",
+        "each a [Swatch][palette::Swatch]",
+        // Oneof variants.
+        "highlighted [Swatch][Swatch]",
+        "highlighted [Color][super::Color]",
+        // Enum values.
+        "[Palette][Palette] with no swatches",
+    ] {
+        assert!(GENERATED.contains(link), "missing {link}:\n{GENERATED}");
+    }
+}
@@ -0,0 +1,87 @@
+include!(concat!(env!("OUT_DIR"), "/editions.rs"));
+
+use alloc::boxed::Box;
+use alloc::vec;
+
+use prost::Message;
+
+#[test]
+fn field_presence() {
+    let message = Scalars {
+        explicit: Some(0),
+        implicit: 0,
+        ..Default::default()
+    };
+    // Explicit fields are encoded when they are set to their default value, implicit fields are
+    // not, and required fields are always encoded.
+    assert_eq!(message.encode_to_vec(), [0x08, 0x00, 0x18, 0x00]);
+    assert_eq!(message.name(), "anonymous");
+
+    let decoded = Scalars::decode(&[0x10, 0x05][..]).unwrap();
+    assert_eq!(decoded.explicit, None);
+    assert_eq!(decoded.implicit, 5);
+}
+
+#[test]
+fn repeated_field_encoding() {
+    let message = Scalars {
+        packed: vec![1, 2],
+        expanded: vec![1, 2],
+        ..Default::default()
+    };
+    assert_eq!(
+        message.encode_to_vec(),
+        [0x18, 0x00, 0x2a, 0x02, 0x01, 0x02, 0x30, 0x01, 0x30, 0x02]
+    );
+}
+
+#[test]
+fn message_encoding() {
+    let message = Scalars {
+        delimited: Some(Box::new(Scalars::default())),
+        ..Default::default()
+    };
+    // The delimited field is encoded as a group.
+    assert_eq!(
+        message.encode_to_vec(),
+        [0x18, 0x00, 0x3b, 0x18, 0x00, 0x3c]
+    );
+
+    let items = Items {
+        items: vec![Item { id: 1 }],
+        by_name: vec![("a".into(), Item { id: 2 })].into_iter().collect(),
+        count: 0,
+    };
+    // Map entries are length-delimited, even when the delimited encoding is inherited.
+    let encoded = items.encode_to_vec();
+    assert_eq!(
+        encoded,
+        [0x0b, 0x08, 0x01, 0x0c, 0x12, 0x07, 0x0a, 0x01, b'a', 0x12, 0x02, 0x08, 0x02]
+    );
+    assert_eq!(Items::decode(encoded.as_slice()).unwrap(), items);
+
+    // Fields of files which do not inherit the delimited encoding are length-delimited.
+    let container = Container {
+        items: Some(Items::default()),
+    };
+    assert_eq!(container.encode_to_vec(), [0x0a, 0x00]);
+}
+
+#[test]
+fn utf8_validation() {
+    // Strings which are not validated are generated as bytes, so invalid UTF-8 is kept as is.
+    let message = Unvalidated {
+        validated: Some("señal".into()),
+        raw: Some(vec![0xff, b'a']),
+        raw_list: vec![vec![0xfe]],
+        raw_values: [(1, vec![0xc3])].into_iter().collect(),
+    };
+    let encoded = message.encode_to_vec();
+    assert_eq!(Unvalidated::decode(encoded.as_slice()).unwrap(), message);
+
+    assert_eq!(
+        Unvalidated::decode(&[0x12, 0x01, 0xff][..]).unwrap().raw(),
+        [0xff]
+    );
+    assert!(Unvalidated::decode(&[0x0a, 0x01, 0xff][..]).is_err());
+}
@@ -0,0 +1,41 @@
+include!(concat!(env!("OUT_DIR"), "/enum_str.rs"));
+
+use alloc::string::ToString;
+
+use prost::UnknownEnumName;
+
+#[test]
+fn display() {
+    assert_eq!(Level::High.to_string(), "LEVEL_HIGH");
+    assert_eq!(Mode::Fast.to_string(), "MODE_FAST");
+}
+
+#[test]
+fn from_str_accepts_aliases() {
+    assert_eq!(Level::from_str_name("LEVEL_MAX"), Some(Level::High));
+    assert_eq!("LEVEL_HIGH".parse::<Level>(), Ok(Level::High));
+    assert_eq!("LEVEL_MAX".parse::<Level>(), Ok(Level::High));
+}
+
+#[test]
+fn from_str_is_case_sensitive() {
+    assert_eq!(
+        "level_high".parse::<Level>(),
+        Err(UnknownEnumName("level_high".to_string()))
+    );
+    assert_eq!(
+        UnknownEnumName("level_high".to_string()).to_string(),
+        "unknown enumeration name \"level_high\""
+    );
+}
+
+#[test]
+fn from_str_ignore_case() {
+    assert_eq!(
+        Mode::from_str_name_ignore_case("Mode_Fast"),
+        Some(Mode::Fast)
+    );
+    assert_eq!("mode_fast".parse::<Mode>(), Ok(Mode::Fast));
+    assert_eq!("MODE_FAST".parse::<Mode>(), Ok(Mode::Fast));
+    assert!("fast".parse::<Mode>().is_err());
+}
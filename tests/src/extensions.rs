@@ -0,0 +1,101 @@
+#![allow(dead_code)]
+
+include!(concat!(env!("OUT_DIR"), "/extensions.rs"));
+
+use alloc::string::ToString;
+use alloc::vec;
+
+use prost::{Extendable, Message};
+use prost_types::FileDescriptorSet;
+
+#[test]
+fn set_and_get_extensions() {
+    let mut base = Base {
+        name: Some("base".to_string()),
+        ..Default::default()
+    };
+    assert!(!base.has_extension(&SCORE));
+    assert_eq!(base.get_extension(&SCORE), Ok(None));
+
+    base.set_extension(&SCORE, 42);
+    base.set_extension(&TAGS, vec!["a".to_string(), "b".to_string()]);
+    base.set_extension(&PAYLOAD, Payload { id: Some(7) });
+    base.set_extension(&PACKED_VALUES, vec![-1, 0, 1]);
+    base.set_extension(
+        &NOTE,
+        Note {
+            text: Some("note".to_string()),
+        },
+    );
+    base.set_extension(&scope::FLAGGED, true);
+
+    let decoded = Base::decode(base.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, base);
+    assert_eq!(decoded.name.as_deref(), Some("base"));
+    assert_eq!(decoded.get_extension(&SCORE), Ok(Some(42)));
+    assert_eq!(
+        decoded.get_extension(&TAGS),
+        Ok(Some(vec!["a".to_string(), "b".to_string()]))
+    );
+    assert_eq!(
+        decoded.get_extension(&PAYLOAD),
+        Ok(Some(Payload { id: Some(7) }))
+    );
+    assert_eq!(
+        decoded.get_extension(&PACKED_VALUES),
+        Ok(Some(vec![-1, 0, 1]))
+    );
+    assert_eq!(
+        decoded.get_extension(&NOTE),
+        Ok(Some(Note {
+            text: Some("note".to_string()),
+        }))
+    );
+    assert_eq!(decoded.get_extension(&scope::FLAGGED), Ok(Some(true)));
+
+    base.clear_extension(&TAGS);
+    assert!(!base.has_extension(&TAGS));
+    assert!(base.has_extension(&SCORE));
+
+    base.clear();
+    assert!(base.extension_set().is_empty());
+}
+
+#[test]
+fn unknown_fields_are_not_extensions() {
+    // Field 50 is outside of the extension ranges of `Base`, and is skipped.
+    let bytes = [0x90, 0x03, 0x01, 0xa0, 0x06, 0x05];
+    let base = Base::decode(&bytes[..]).unwrap();
+    assert_eq!(base.extension_set().fields().len(), 1);
+    assert_eq!(base.get_extension(&SCORE), Ok(Some(5)));
+    assert_eq!(base.encode_to_vec(), &bytes[3..]);
+}
+
+#[test]
+#[should_panic(expected = "extension extensions.column extends google.protobuf.FieldOptions")]
+fn extendee_mismatch() {
+    let base = Base::default();
+    let _ = base.get_extension(&COLUMN);
+}
+
+#[test]
+fn custom_options() {
+    let set =
+        FileDescriptorSet::decode(&include_bytes!(concat!(env!("OUT_DIR"), "/extensions.bin"))[..])
+            .unwrap();
+    let file = set
+        .file
+        .iter()
+        .find(|file| file.package() == "extensions")
+        .unwrap();
+    let row = file
+        .message_type
+        .iter()
+        .find(|message| message.name() == "Row")
+        .unwrap();
+    let options = row.field[0].options.as_ref().unwrap();
+    assert_eq!(
+        options.get_extension(&COLUMN),
+        Ok(Some("row_name".to_string()))
+    );
+}
@@ -0,0 +1,70 @@
+include!(concat!(env!("OUT_DIR"), "/field_numbers.rs"));
+
+use alloc::vec::Vec;
+
+use prost::wire::{FieldInfo, FieldIter, WireType};
+use prost::Message;
+
+#[test]
+fn constants() {
+    assert_eq!(Order::ID_FIELD_NUMBER, 3);
+    assert_eq!(Order::QUANTITIES_FIELD_NUMBER, 1);
+    assert_eq!(Order::NOTE_FIELD_NUMBER, 5);
+    assert_eq!(Order::TYPE_FIELD_NUMBER, 7);
+    assert_eq!(Order::CARD_FIELD_NUMBER, 9);
+    assert_eq!(Order::CASH_FIELD_NUMBER, 8);
+    assert_eq!(order::Note::TEXT_FIELD_NUMBER, 6);
+    assert_eq!(Item::NAME_FIELD_NUMBER, 1);
+}
+
+#[test]
+fn fields() {
+    let field = |name, number, wire_type| FieldInfo {
+        name,
+        number,
+        wire_type,
+    };
+    assert_eq!(
+        Order::fields(),
+        [
+            field("quantities", 1, WireType::LengthDelimited),
+            field("discounts", 2, WireType::Varint),
+            field("id", 3, WireType::Varint),
+            field("item", 4, WireType::LengthDelimited),
+            field("note", 5, WireType::StartGroup),
+            field("type", 7, WireType::LengthDelimited),
+            field("cash", 8, WireType::SixtyFourBit),
+            field("card", 9, WireType::ThirtyTwoBit),
+            field("extras", 10, WireType::LengthDelimited),
+        ]
+    );
+}
+
+#[test]
+fn fields_match_encoding() {
+    let mut order = Order {
+        id: Some(1),
+        quantities: [1, 2].into(),
+        discounts: [3].into(),
+        item: Some(Item::default()),
+        note: Some(order::Note::default()),
+        r#type: Some("express".into()),
+        payment: Some(order::Payment::Card(4)),
+        extras: Default::default(),
+    };
+    order.extras.insert("gift".into(), Item::default());
+    let buf = order.encode_to_vec();
+    let encoded = FieldIter::new(&buf)
+        .map(|field| {
+            let field = field.unwrap();
+            (field.number, field.wire_type)
+        })
+        .collect::<Vec<_>>();
+    for (number, wire_type) in encoded {
+        let info = Order::fields()
+            .iter()
+            .find(|info| info.number == number)
+            .unwrap();
+        assert_eq!(info.wire_type, wire_type, "field {}", info.name);
+    }
+}
@@ -0,0 +1,43 @@
+include!(concat!(env!("OUT_DIR"), "/file_per_message.rs"));
+
+use alloc::string::ToString;
+use alloc::vec;
+
+use prost::Message;
+
+const PACKAGE_FILE: &str = include_str!(concat!(env!("OUT_DIR"), "/file_per_message.rs"));
+const SHIRT_FILE: &str = include_str!(concat!(env!("OUT_DIR"), "/file_per_message/Shirt.rs"));
+const WARDROBE_FILE: &str = include_str!(concat!(env!("OUT_DIR"), "/file_per_message/Wardrobe.rs"));
+
+#[test]
+fn package_file_includes_message_files() {
+    assert!(PACKAGE_FILE.contains(r#"include!("file_per_message/Shirt.rs");"#));
+    assert!(PACKAGE_FILE.contains(r#"include!("file_per_message/Wardrobe.rs");"#));
+    assert!(PACKAGE_FILE.contains("pub enum Size"));
+    assert!(!PACKAGE_FILE.contains("pub struct Shirt"));
+}
+
+#[test]
+fn message_files_hold_nested_types() {
+    assert!(SHIRT_FILE.starts_with("// This file is @generated by prost-build."));
+    assert!(SHIRT_FILE.contains("pub struct Shirt"));
+    assert!(SHIRT_FILE.contains("pub enum Color"));
+    assert!(WARDROBE_FILE.contains("pub struct Wardrobe"));
+    assert!(WARDROBE_FILE.contains("pub enum Owner"));
+}
+
+#[test]
+fn split_messages_roundtrip() {
+    let wardrobe = Wardrobe {
+        shirts: vec![Shirt {
+            brand: "Ferris".to_string(),
+            size: Size::Large as i32,
+            color: shirt::Color::Red as i32,
+        }],
+        owner: Some(wardrobe::Owner::Id(7)),
+    };
+
+    let decoded = Wardrobe::decode(wardrobe.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, wardrobe);
+    assert_eq!(decoded.shirts[0].size(), Size::Large);
+}
@@ -104,3 +104,45 @@ fn test_deep_nesting_group() {
     assert!(build_and_roundtrip(50).is_ok());
     assert!(build_and_roundtrip(51).is_err());
 }
+
+#[test]
+fn test_group_text_format() {
+    use prost::text_format::TextFormatMessage;
+
+    // Group fields are named by their message type in the text format.
+    let msg = Test2 {
+        i14: Some(1),
+        groupb: Vec::from([test2::GroupB { i16: Some(2) }]),
+        i17: None,
+    };
+    let text = msg.to_text_format();
+    assert_eq!(text, "i14: 1\nGroupB {\n  i16: 2\n}\n");
+    assert_eq!(Test2::from_text_format(&text).unwrap(), msg);
+    assert_eq!(
+        Test2::from_text_format("i14: 1 groupb { i16: 2 }").unwrap(),
+        msg
+    );
+
+    let msg = OneofGroup {
+        i1: None,
+        field: Some(oneof_group::Field::G(oneof_group::G {
+            i2: None,
+            s1: "foo".to_string(),
+            t1: None,
+        })),
+    };
+    let text = msg.to_text_format();
+    assert_eq!(text, "G {\n  s1: \"foo\"\n}\n");
+    assert_eq!(OneofGroup::from_text_format(&text).unwrap(), msg);
+    assert_eq!(
+        OneofGroup::from_text_format("g { s1: 'foo' }").unwrap(),
+        msg
+    );
+
+    let msg = NestedGroup2 {
+        optionalgroup: Some(Box::new(nested_group2::OptionalGroup {
+            nested_group: None,
+        })),
+    };
+    assert_eq!(msg.to_text_format(), "OptionalGroup {\n}\n");
+}
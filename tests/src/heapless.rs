@@ -0,0 +1,66 @@
+include!(concat!(env!("OUT_DIR"), "/heapless.rs"));
+
+use alloc::vec::Vec;
+
+use prost::heapless;
+use prost::Message;
+
+fn reading() -> Reading {
+    let mut reading = Reading {
+        sensor: Some(heapless::String::try_from("thermometer").unwrap()),
+        checksum: Some(heapless::Vec::from_slice(&[0xde, 0xad]).unwrap()),
+        source: Some(reading::Source::Label(
+            heapless::String::try_from("probe").unwrap(),
+        )),
+        ..Default::default()
+    };
+    reading.samples.extend_from_slice(&[-1, 2, 3]).unwrap();
+    reading.history.push(Sample { value: Some(7) }).unwrap();
+    reading
+        .tags
+        .push(heapless::String::try_from("indoor").unwrap())
+        .unwrap();
+    reading.push_units(Unit::Celsius);
+    reading
+}
+
+#[test]
+fn roundtrip_heapless() {
+    let reading = reading();
+    let decoded = Reading::decode(reading.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, reading);
+    assert_eq!(decoded.encoded_len(), reading.encoded_len());
+    assert_eq!(decoded.location(), "lab");
+    assert_eq!(decoded.units().collect::<Vec<_>>(), [Unit::Celsius]);
+}
+
+#[test]
+fn merge_heapless() {
+    let mut update = Reading::default();
+    update.samples.push(4).unwrap();
+    update.history.push(Sample { value: Some(8) }).unwrap();
+
+    let mut merged = reading();
+    merged.merge(update.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(merged.samples, [-1, 2, 3, 4]);
+    assert_eq!(merged.history.len(), 2);
+    assert_eq!(merged.sensor, reading().sensor);
+
+    // The repeated fields are full.
+    assert!(merged.merge(update.encode_to_vec().as_slice()).is_err());
+}
+
+#[test]
+fn capacity_exceeded() {
+    let mut buf = reading().encode_to_vec();
+    buf.extend_from_slice(&reading().encode_to_vec());
+    assert!(Reading::decode(buf.as_slice()).is_err());
+
+    let sensor = Reading {
+        sensor: Some(heapless::String::try_from("thermometer").unwrap()),
+        ..Default::default()
+    };
+    let mut buf = sensor.encode_to_vec();
+    buf.extend_from_slice(b"\x2a\x09too long!");
+    assert!(Reading::decode(buf.as_slice()).is_err());
+}
@@ -0,0 +1,100 @@
+//! Tests for 128-bit integer fields, with `#[prost(uint128)]` and `#[prost(int128)]`.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use prost::Message;
+
+#[derive(Clone, PartialEq, Message)]
+struct Balances {
+    #[prost(uint128, tag = "1")]
+    total: u128,
+    #[prost(int128, optional, tag = "2")]
+    delta: Option<i128>,
+    #[prost(uint128 = "fixed64", repeated, tag = "3")]
+    history: Vec<u128>,
+    #[prost(int128 = "fixed64", tag = "4")]
+    adjustment: i128,
+    #[prost(uint128, optional, tag = "5", default = "1000")]
+    limit: Option<u128>,
+}
+
+/// The message of `Balances.history`, as other implementations read it.
+#[derive(Clone, PartialEq, Message)]
+struct Halves {
+    #[prost(fixed64, tag = "1")]
+    high: u64,
+    #[prost(fixed64, tag = "2")]
+    low: u64,
+}
+
+#[test]
+fn int128_fields_roundtrip() {
+    let balances = Balances {
+        total: u128::MAX,
+        delta: Some(i128::MIN),
+        history: vec![0, 1 << 64, u128::MAX],
+        adjustment: -1,
+        limit: None,
+    };
+    let encoded = balances.encode_to_vec();
+    assert_eq!(encoded.len(), balances.encoded_len());
+    assert_eq!(Balances::decode(encoded.as_slice()).unwrap(), balances);
+}
+
+#[test]
+fn int128_fields_are_big_endian_bytes() {
+    let balances = Balances {
+        total: 0x0102,
+        ..Balances::default()
+    };
+    let mut expected = vec![0x0a, 16];
+    expected.extend_from_slice(&[0; 14]);
+    expected.extend_from_slice(&[0x01, 0x02]);
+    assert_eq!(balances.encode_to_vec(), expected);
+
+    // A default value is not encoded.
+    assert!(Balances::default().encode_to_vec().is_empty());
+
+    let error = Balances::decode(&[0x0a, 0x02, 0x01, 0x02][..]).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("invalid 128-bit integer: expected 16 bytes, found 2"));
+}
+
+#[test]
+fn int128_fixed64_fields_are_nested_messages() {
+    let balances = Balances {
+        history: vec![(7 << 64) | 9],
+        adjustment: -2,
+        ..Balances::default()
+    };
+    let encoded = balances.encode_to_vec();
+
+    let mut history = Vec::new();
+    prost::encoding::message::encode(3, &Halves { high: 7, low: 9 }, &mut history);
+    assert_eq!(encoded[..history.len()], history[..]);
+
+    // The halves of a negative integer are its two's complement.
+    let mut adjustment = Vec::new();
+    let halves = Halves {
+        high: u64::MAX,
+        low: u64::MAX - 1,
+    };
+    prost::encoding::message::encode(4, &halves, &mut adjustment);
+    assert_eq!(encoded[history.len()..], adjustment[..]);
+
+    // A half which is not encoded is zero.
+    let mut encoded = Vec::new();
+    prost::encoding::message::encode(4, &Halves { high: 0, low: 5 }, &mut encoded);
+    assert_eq!(Balances::decode(encoded.as_slice()).unwrap().adjustment, 5);
+}
+
+#[test]
+fn int128_defaults() {
+    let balances = Balances::default();
+    assert_eq!(balances.limit(), 1000);
+    assert_eq!(balances.delta(), 0);
+}
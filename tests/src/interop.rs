@@ -0,0 +1,28 @@
+use prost::interop::ProtobufInterop;
+use prost::rust_protobuf::well_known_types::{duration, timestamp};
+use prost_types::{Duration, Timestamp};
+
+#[test]
+fn round_trip() {
+    let timestamp = Timestamp {
+        seconds: 1_700_000_000,
+        nanos: 42,
+    };
+    let converted: timestamp::Timestamp = timestamp.to_protobuf().unwrap();
+    assert_eq!(converted.seconds, 1_700_000_000);
+    assert_eq!(converted.nanos, 42);
+    assert_eq!(Timestamp::from_protobuf(&converted).unwrap(), timestamp);
+}
+
+#[test]
+fn type_mismatch() {
+    let error = Timestamp::default()
+        .to_protobuf::<duration::Duration>()
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "failed to convert between prost and rust-protobuf: the prost message \
+         `google.protobuf.Timestamp` is not the rust-protobuf message `google.protobuf.Duration`"
+    );
+    assert!(Duration::from_protobuf(&timestamp::Timestamp::new()).is_err());
+}
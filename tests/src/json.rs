@@ -0,0 +1,227 @@
+include!(concat!(env!("OUT_DIR"), "/json.rs"));
+
+use alloc::string::ToString;
+use alloc::vec;
+
+use prost::json::{from_str, from_str_with_options, to_string, to_string_with_options};
+use prost::json::{JsonOptions, JsonValue};
+
+fn everything() -> Everything {
+    Everything {
+        snake_case_field: 7,
+        color: Color::Red as i32,
+        colors: vec![Color::Green as i32, 5],
+        names: vec!["a".to_string(), "b".to_string()],
+        counts: vec![("x".to_string(), -3)].into_iter().collect(),
+        color_map: vec![(1, Color::Green as i32)].into_iter().collect(),
+        nested_map: vec![(
+            true,
+            Nested {
+                label: "t".to_string(),
+            },
+        )]
+        .into_iter()
+        .collect(),
+        nested: Some(Nested {
+            label: "n".to_string(),
+        }),
+        nested_list: vec![Nested::default()],
+        choice: Some(everything::Choice::ChosenColor(Color::Green as i32)),
+        timestamp: Some(prost_types::Timestamp {
+            seconds: 1,
+            nanos: 500_000_000,
+        }),
+        duration: Some(prost_types::Duration {
+            seconds: -2,
+            nanos: 0,
+        }),
+        wrapped_int: Some(0),
+        value: Some(prost_types::Value {
+            kind: Some(prost_types::value::Kind::NullValue(0)),
+        }),
+        r#struct: Some(prost_types::Struct::default()),
+        any: Some(
+            prost_types::Any::from_msg(&Nested {
+                label: "packed".to_string(),
+            })
+            .unwrap(),
+        ),
+        mask: Some(prost_types::FieldMask {
+            paths: vec!["snake_case_field".to_string()],
+        }),
+        empty: Some(()),
+        scalars: Some(Scalars {
+            double: 1.5,
+            float: f32::NEG_INFINITY,
+            int64: -1,
+            uint64: u64::MAX,
+            bytes: b"\x00\xff".to_vec(),
+            maybe: Some(0),
+            ..Default::default()
+        }),
+    }
+}
+
+const EVERYTHING_JSON: &str = concat!(
+    r#"{"snakeCaseField":7,"color":"COLOR_RED","colors":["COLOR_GREEN",5],"names":["a","b"],"#,
+    r#""counts":{"x":"-3"},"colorMap":{"1":"COLOR_GREEN"},"nestedMap":{"true":{"label":"t"}},"#,
+    r#""nested":{"label":"n"},"nestedList":[{}],"chosenColor":"COLOR_GREEN","#,
+    r#""timestamp":"1970-01-01T00:00:01.500Z","duration":"-2s","wrappedInt":"0","value":null,"#,
+    r#""struct":{},"any":{"@type":"/json.Nested","label":"packed"},"mask":"snakeCaseField","#,
+    r#""empty":{},"scalars":{"double":1.5,"float":"-Infinity","int64":"-1","#,
+    r#""uint64":"18446744073709551615","bytes":"AP8=","maybe":0}}"#,
+);
+
+fn options() -> JsonOptions {
+    JsonOptions::new().register::<Nested>()
+}
+
+#[test]
+fn roundtrip() {
+    let message = everything();
+    let json = to_string_with_options(&message, &options()).unwrap();
+    assert_eq!(json, EVERYTHING_JSON);
+    assert_eq!(
+        from_str_with_options::<Everything>(&json, &options()).unwrap(),
+        message
+    );
+}
+
+#[test]
+fn default_values() {
+    assert_eq!(to_string(&Everything::default()).unwrap(), "{}");
+    assert_eq!(from_str::<Everything>("{}").unwrap(), Everything::default());
+
+    let options = JsonOptions::new().emit_default_values(true);
+    let json = to_string_with_options(&Scalars::default(), &options).unwrap();
+    assert_eq!(
+        json,
+        concat!(
+            r#"{"double":0,"float":0,"int32":0,"int64":"0","uint32":0,"uint64":"0","#,
+            r#""sint32":0,"sint64":"0","fixed32":0,"fixed64":"0","sfixed32":0,"sfixed64":"0","#,
+            r#""bool":false,"string":"","bytes":""}"#,
+        )
+    );
+
+    // Unset message and oneof fields are not emitted.
+    let json = to_string_with_options(&Everything::default(), &options).unwrap();
+    let value = json.parse::<JsonValue>().unwrap();
+    assert!(value.get("nested").is_none());
+    assert!(value.get("text").is_none());
+    assert_eq!(value.get("colors"), Some(&JsonValue::Array(vec![])));
+}
+
+#[test]
+fn field_names() {
+    let message = Everything {
+        snake_case_field: 1,
+        ..Default::default()
+    };
+    let options = JsonOptions::new().use_proto_field_names(true);
+    let json = to_string_with_options(&message, &options).unwrap();
+    assert_eq!(json, r#"{"snake_case_field":1}"#);
+
+    // Both names are accepted when parsing.
+    assert_eq!(from_str::<Everything>(&json).unwrap(), message);
+    assert_eq!(
+        from_str::<Everything>(r#"{"snakeCaseField":1}"#).unwrap(),
+        message
+    );
+}
+
+#[test]
+fn parse_leniency() {
+    let message = from_str::<Everything>(
+        r#"{
+            "color": 2,
+            "snakeCaseField": "12",
+            "nested": null,
+            "names": null,
+            "text": "chosen",
+            "scalars": {"float": 1e2, "int32": 3.0, "bytes": "_w", "double": "NaN"}
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(message.color, Color::Green as i32);
+    assert_eq!(message.snake_case_field, 12);
+    assert_eq!(message.nested, None);
+    assert_eq!(
+        message.choice,
+        Some(everything::Choice::Text("chosen".to_string()))
+    );
+    let scalars = message.scalars.unwrap();
+    assert_eq!(scalars.float, 100.0);
+    assert_eq!(scalars.int32, 3);
+    assert_eq!(scalars.bytes, b"\xff");
+    assert!(scalars.double.is_nan());
+}
+
+#[test]
+fn parse_errors() {
+    for json in [
+        "[]",
+        r#"{"unknown":1}"#,
+        r#"{"color":"COLOR_BLUE"}"#,
+        r#"{"snakeCaseField":1.5}"#,
+        r#"{"snakeCaseField":true}"#,
+        r#"{"names":[null]}"#,
+        r#"{"counts":{"x":null}}"#,
+        r#"{"colorMap":{"x":"COLOR_RED"}}"#,
+        r#"{"timestamp":"yesterday"}"#,
+        r#"{"scalars":{"uint32":-1}}"#,
+    ] {
+        assert!(from_str::<Everything>(json).is_err(), "{}", json);
+    }
+
+    let error = from_str::<Everything>(r#"{"scalars":{"int32":"x"}}"#).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "failed to process Protobuf JSON: Everything.scalars: Scalars.int32: invalid integer: x"
+    );
+}
+
+#[test]
+fn ignore_unknown_fields() {
+    let options = JsonOptions::new().ignore_unknown_fields(true);
+    let message = from_str_with_options::<Everything>(
+        r#"{"unknown":1,"color":"COLOR_BLUE","colors":["COLOR_BLUE","COLOR_RED"]}"#,
+        &options,
+    )
+    .unwrap();
+    assert_eq!(message.color, 0);
+    assert_eq!(message.colors, vec![Color::Red as i32]);
+}
+
+#[test]
+fn value_null() {
+    let message = from_str::<Everything>(r#"{"value":null}"#).unwrap();
+    assert_eq!(
+        message.value,
+        Some(prost_types::Value {
+            kind: Some(prost_types::value::Kind::NullValue(0)),
+        })
+    );
+}
+
+#[test]
+fn declared_json_names() {
+    let message = Renamed {
+        item_count: 3,
+        tallies: vec![("a".to_string(), 1)].into_iter().collect(),
+        pick: Some(renamed::Pick::Label("x".to_string())),
+    };
+    let json = r#"{"total":3,"scores":{"a":1},"title":"x"}"#;
+    assert_eq!(to_string(&message).unwrap(), json);
+    assert_eq!(from_str::<Renamed>(json).unwrap(), message);
+
+    let proto_names = r#"{"item_count":3,"tallies":{"a":1},"label":"x"}"#;
+    let options = JsonOptions::new().use_proto_field_names(true);
+    assert_eq!(
+        to_string_with_options(&message, &options).unwrap(),
+        proto_names
+    );
+    assert_eq!(from_str::<Renamed>(proto_names).unwrap(), message);
+
+    let message = from_str::<Renamed>(r#"{"rank":2}"#).unwrap();
+    assert_eq!(message.pick, Some(renamed::Pick::Rank(2)));
+}
@@ -0,0 +1,42 @@
+include!(concat!(env!("OUT_DIR"), "/large_oneof.rs"));
+
+use alloc::boxed::Box;
+use alloc::string::ToString;
+
+use prost::Message;
+
+fn large() -> Large {
+    Large {
+        a: "a".to_string(),
+        i: "i".to_string(),
+        ..Large::default()
+    }
+}
+
+#[test]
+fn large_variants_are_boxed() {
+    assert!(core::mem::size_of::<event::Kind>() <= 32);
+    // Small messages are held directly.
+    let small = event::Kind::Small(Small { value: 1 });
+    assert_eq!(small.as_large(), None);
+
+    let event = Event {
+        kind: Some(event::Kind::Large(Box::new(large()))),
+        pair: Some(event::Pair::Second(Box::new(large()))),
+    };
+    let decoded = Event::decode(event.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, event);
+}
+
+#[test]
+fn accessors() {
+    let mut kind = event::Kind::from(large());
+    assert_eq!(kind.as_large(), Some(&large()));
+    kind.as_large_mut().unwrap().b = "b".to_string();
+    assert_eq!(kind.as_large().unwrap().b, "b");
+
+    // Variants holding the same message have no `From` implementation.
+    let pair = event::Pair::First(Box::new(large()));
+    assert_eq!(pair.as_first(), Some(&large()));
+    assert_eq!(pair.as_second(), None);
+}
@@ -0,0 +1,128 @@
+#![allow(dead_code)]
+
+include!(concat!(env!("OUT_DIR"), "/lazy.rs"));
+
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::vec;
+
+use prost::bytes::Bytes;
+use prost::{Lazy, Message, MessageRef};
+
+fn payload(name: &str) -> Payload {
+    Payload {
+        name: name.to_string(),
+        values: vec![1, 2, 3],
+    }
+}
+
+fn envelope() -> Envelope {
+    Envelope {
+        id: 7,
+        payload: Some(Lazy::new(payload("payload"))),
+        items: vec![Lazy::new(payload("first")), Lazy::new(payload("second"))],
+        extra: None,
+        next: Some(Lazy::new(Box::new(Envelope {
+            id: 8,
+            ..Default::default()
+        }))),
+    }
+}
+
+#[test]
+fn decode_lazily() {
+    let buf = Bytes::from(envelope().encode_to_vec());
+    let message = Envelope::decode(buf.clone()).unwrap();
+
+    let payload = message.payload.as_ref().unwrap();
+    assert!(!payload.is_decoded());
+    assert!(!message.items[1].is_decoded());
+    assert_eq!(payload.get().unwrap().name, "payload");
+    assert!(payload.is_decoded());
+    assert!(!message.items[1].is_decoded());
+    assert_eq!(message.items[1].get().unwrap().name, "second");
+    assert_eq!(message.next.as_ref().unwrap().get().unwrap().id, 8);
+
+    // The bytes of lazy fields are shared with the decoded buffer.
+    let range = buf.as_ptr_range();
+    assert!(range.contains(&payload.encoded().unwrap().as_ptr()));
+
+    assert_eq!(message, envelope());
+}
+
+#[test]
+fn encode_untouched_bytes() {
+    let original = envelope();
+    let buf = original.encode_to_vec();
+    let mut message = Envelope::decode(buf.as_slice()).unwrap();
+    assert_eq!(message.encoded_len(), buf.len());
+    assert_eq!(message.encode_to_vec(), buf);
+
+    message.items[0].get_mut().unwrap().name = "changed".to_string();
+    assert!(message.items[0].encoded().is_none());
+    assert!(message.items[1].encoded().is_some());
+
+    let decoded = Envelope::decode(message.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded.items[0].get().unwrap().name, "changed");
+    assert_eq!(decoded.items[1].get().unwrap().name, "second");
+    assert_eq!(decoded, message);
+}
+
+#[test]
+fn merge_repeated_field() {
+    // Occurrences of a singular lazy field are merged, as for eagerly decoded fields.
+    let first = Envelope {
+        payload: Some(Lazy::new(Payload {
+            name: "first".to_string(),
+            values: vec![1],
+        })),
+        ..Default::default()
+    };
+    let second = Envelope {
+        payload: Some(Lazy::new(Payload {
+            values: vec![2],
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    let mut buf = first.encode_to_vec();
+    second.encode(&mut buf).unwrap();
+
+    let message = Envelope::decode(buf.as_slice()).unwrap();
+    assert_eq!(
+        message.payload.unwrap().into_inner().unwrap(),
+        Payload {
+            name: "first".to_string(),
+            values: vec![1, 2],
+        }
+    );
+}
+
+#[test]
+fn invalid_lazy_field() {
+    // `payload` holds a string with a length that exceeds the nested message.
+    let buf = [0x12, 0x02, 0x0a, 0x05];
+    let message = Envelope::decode(&buf[..]).unwrap();
+    assert!(message.payload.as_ref().unwrap().get().is_err());
+    assert_eq!(message.encode_to_vec(), buf);
+}
+
+#[test]
+fn message_ref_and_builder() {
+    let buf = envelope().encode_to_vec();
+    let view = EnvelopeRef::decode(&buf).unwrap();
+    assert_eq!(view.to_owned_message(), envelope());
+
+    let message = Envelope::builder()
+        .id(7)
+        .payload(payload("payload"))
+        .items(vec![payload("first")])
+        .push_items(payload("second"))
+        .next(Envelope {
+            id: 8,
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+    assert_eq!(message, envelope());
+}
@@ -58,6 +58,24 @@ mod custom_debug;
 // Must be `pub` as doc tests are only executed on public types.
 pub mod disable_comments;
 
+#[cfg(test)]
+mod doc_links;
+
+#[cfg(test)]
+mod version_conversion;
+
+#[cfg(test)]
+mod packed_encoding;
+
+#[cfg(test)]
+mod enum_str;
+
+#[cfg(test)]
+mod derive_eq_hash;
+
+#[cfg(test)]
+mod proto_cmp;
+
 #[cfg(test)]
 // Must be `pub` as `missing_docs` lint is only executed on public types.
 pub mod custom_attributes;
@@ -107,6 +125,136 @@ pub mod oneof_attributes {
 #[cfg(test)]
 mod proto3_presence;
 
+#[cfg(test)]
+mod unknown_fields;
+
+#[cfg(test)]
+mod required_fields;
+
+#[cfg(test)]
+mod json;
+
+#[cfg(test)]
+mod serde_mapping;
+
+#[cfg(test)]
+mod text_format;
+
+#[cfg(test)]
+mod text_format_debug;
+
+#[cfg(test)]
+mod reflect;
+
+#[cfg(test)]
+mod reflect_message;
+
+#[cfg(test)]
+mod redact;
+
+#[cfg(test)]
+mod file_per_message;
+
+#[cfg(test)]
+mod extensions;
+
+#[cfg(test)]
+mod utf8_bytes;
+
+#[cfg(test)]
+mod message_ref;
+
+#[cfg(test)]
+mod arena;
+
+#[cfg(test)]
+mod builders;
+
+#[cfg(test)]
+mod editions;
+
+#[cfg(all(test, feature = "std"))]
+mod validate;
+
+#[cfg(all(test, feature = "std"))]
+mod lazy;
+
+#[cfg(all(test, feature = "std"))]
+mod arbitrary;
+
+#[cfg(all(test, feature = "std"))]
+mod interop;
+
+#[cfg(all(test, feature = "std", not(target_family = "wasm")))]
+mod differential;
+
+#[cfg(test)]
+mod service_definitions;
+
+#[cfg(test)]
+mod custom_map;
+
+#[cfg(test)]
+mod custom_bytes;
+
+#[cfg(test)]
+mod custom_string;
+
+#[cfg(test)]
+mod const_default;
+
+#[cfg(test)]
+mod non_exhaustive;
+
+#[cfg(test)]
+mod large_oneof;
+
+#[cfg(test)]
+mod recursive_wrapper;
+
+#[cfg(test)]
+mod wrapper_type;
+
+#[cfg(test)]
+mod cow;
+
+#[cfg(test)]
+mod field_numbers;
+
+#[cfg(test)]
+mod derive_provider;
+
+#[cfg(test)]
+mod heapless;
+
+#[cfg(test)]
+mod oneof_accessors;
+
+#[cfg(test)]
+mod conversions;
+
+#[cfg(test)]
+mod message_descriptors;
+
+#[cfg(test)]
+mod message_types;
+
+#[cfg(test)]
+mod presence_bits;
+
+#[cfg(test)]
+mod open_enums;
+
+#[cfg(test)]
+mod custom_encoding;
+
+#[cfg(test)]
+mod int128;
+
+// `HashMap` fields depend on the `std` feature.
+#[cfg(all(test, feature = "std"))]
+mod deterministic;
+
 use core::fmt::Debug;
 
 #[cfg(not(feature = "std"))]
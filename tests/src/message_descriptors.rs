@@ -0,0 +1,31 @@
+include!(concat!(env!("OUT_DIR"), "/message_descriptors.v1.rs"));
+
+use prost::Message;
+use prost_types::DescriptorProto;
+
+#[test]
+fn full_names() {
+    assert_eq!(
+        HelloRequest::FULL_NAME,
+        "message_descriptors.v1.HelloRequest"
+    );
+    assert_eq!(
+        hello_request::Greeting::FULL_NAME,
+        "message_descriptors.v1.HelloRequest.Greeting"
+    );
+}
+
+#[test]
+fn descriptor_protos() {
+    let descriptor = DescriptorProto::decode(HelloRequest::DESCRIPTOR_PROTO).unwrap();
+    assert_eq!(descriptor.name(), "HelloRequest");
+    assert_eq!(descriptor.field[0].name(), "name");
+    assert_eq!(
+        descriptor.field[1].type_name(),
+        ".message_descriptors.v1.HelloRequest.Greeting"
+    );
+    assert_eq!(descriptor.nested_type[0].name(), "Greeting");
+
+    let nested = DescriptorProto::decode(hello_request::Greeting::DESCRIPTOR_PROTO).unwrap();
+    assert_eq!(nested, descriptor.nested_type[0]);
+}
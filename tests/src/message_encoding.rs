@@ -432,3 +432,74 @@ fn roundtrip() {
     };
     check_message(&msg);
 }
+
+#[test]
+fn merge_from() {
+    let basic = Basic {
+        int32: 1,
+        bools: Vec::from([true]),
+        optional_string: Some("a".into()),
+        enumeration_btree_map: BTreeMap::from([(1, 1), (2, 2)]),
+        oneof: Some(BasicOneof::Int(1)),
+        ..Default::default()
+    };
+    let other = Basic {
+        bools: Vec::from([false]),
+        string: "b".into(),
+        enumeration_btree_map: BTreeMap::from([(2, 3)]),
+        oneof: Some(BasicOneof::String("c".into())),
+        ..Default::default()
+    };
+
+    let mut merged = basic.clone();
+    merged.merge_from(&other);
+    assert_eq!(
+        merged,
+        Basic {
+            int32: 1,
+            bools: Vec::from([true, false]),
+            string: "b".into(),
+            optional_string: Some("a".into()),
+            enumeration_btree_map: BTreeMap::from([(1, 1), (2, 3)]),
+            oneof: Some(BasicOneof::String("c".into())),
+            ..Default::default()
+        }
+    );
+
+    let mut msg = Compound {
+        optional_message: Some(basic.clone()),
+        required_message: basic.clone(),
+        repeated_message: Vec::from([basic.clone()]),
+        #[cfg(feature = "std")]
+        message_map: HashMap::from([(1, basic.clone())]),
+        message_btree_map: BTreeMap::from([(1, basic.clone())]),
+    };
+    let other_msg = Compound {
+        optional_message: Some(other.clone()),
+        required_message: other.clone(),
+        repeated_message: Vec::from([other.clone()]),
+        #[cfg(feature = "std")]
+        message_map: HashMap::from([(1, other.clone())]),
+        message_btree_map: BTreeMap::from([(2, other.clone())]),
+    };
+
+    // Merging is equivalent to decoding the encoded message on top of the existing one.
+    let mut expected = msg.clone();
+    expected
+        .merge(other_msg.encode_to_vec().as_slice())
+        .unwrap();
+    msg.merge_from(&other_msg);
+    assert_eq!(msg, expected);
+    assert_eq!(msg.optional_message, Some(merged.clone()));
+    assert_eq!(msg.required_message, merged);
+    assert_eq!(
+        msg.repeated_message,
+        Vec::from([basic.clone(), other.clone()])
+    );
+    #[cfg(feature = "std")]
+    assert_eq!(msg.message_map, HashMap::from([(1, other.clone())]));
+    assert_eq!(
+        msg.message_btree_map,
+        BTreeMap::from([(1, basic), (2, other)])
+    );
+}
@@ -0,0 +1,164 @@
+#![allow(dead_code)]
+
+include!(concat!(env!("OUT_DIR"), "/message_ref.rs"));
+
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::vec;
+
+use prost::bytes::Bytes;
+use prost::{Message, MessageRef, Utf8Bytes};
+
+fn document() -> Document {
+    let section = Section {
+        heading: Some("intro".to_string()),
+        children: vec![Section {
+            heading: Some("child".to_string()),
+            ..Default::default()
+        }],
+        parent: Some(Box::new(Section {
+            heading: Some("parent".to_string()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    Document {
+        title: "title".to_string(),
+        payload: Some(vec![1, 2, 3]),
+        kind: Some(Kind::Leaf as i32),
+        tags: vec!["a".to_string(), "b".to_string()],
+        scores: vec![-1, 0, 1],
+        root: Some(section.clone()),
+        sections: vec![section.clone(), Section::default()],
+        counters: vec![(
+            "counter".to_string(),
+            Counter {
+                count: Some(3),
+                choice: Some(counter::Choice::Flag(true)),
+            },
+        )]
+        .into_iter()
+        .collect(),
+        blobs: vec![(1, vec![0xff]), (2, vec![])].into_iter().collect(),
+        body: Some(document::Body::Section(section)),
+        meta: Some(document::Meta {
+            author: Some("author".to_string()),
+        }),
+        shared: Some(Bytes::from_static(b"shared")),
+        label: Some(Utf8Bytes::from_static("label")),
+        counter: Some(Counter {
+            count: None,
+            choice: Some(counter::Choice::Number(5)),
+        }),
+    }
+}
+
+#[test]
+fn decode_and_convert() {
+    let document = document();
+    let buf = document.encode_to_vec();
+
+    let view = DocumentRef::decode(&buf).unwrap();
+    assert_eq!(view.title, "title");
+    assert_eq!(view.payload, Some(&[1, 2, 3][..]));
+    assert_eq!(view.tags, ["a", "b"]);
+    assert_eq!(view.scores, [-1, 0, 1]);
+    assert_eq!(
+        view.root.as_ref().unwrap().children[0].heading,
+        Some("child")
+    );
+    assert_eq!(
+        view.root.as_ref().unwrap().parent.as_ref().unwrap().heading,
+        Some("parent")
+    );
+    assert_eq!(view.counters[0].0, "counter");
+    assert_eq!(view.blobs, [(1, &[0xff][..]), (2, &[][..])]);
+    assert!(matches!(view.body, Some(document::BodyRef::Section(_))));
+    assert_eq!(view.meta.as_ref().unwrap().author, Some("author"));
+    assert_eq!(
+        view.counter.as_ref().unwrap().choice,
+        Some(counter::ChoiceRef::Number(5))
+    );
+
+    assert_eq!(view.to_owned(), document);
+    assert_eq!(view.to_owned_message(), Document::decode(&buf[..]).unwrap());
+}
+
+#[test]
+fn borrows_from_buffer() {
+    let buf = document().encode_to_vec();
+    let view = DocumentRef::decode(&buf).unwrap();
+
+    let range = buf.as_ptr_range();
+    assert!(range.contains(&view.title.as_ptr()));
+    assert!(range.contains(&view.payload.unwrap().as_ptr()));
+    assert!(range.contains(&view.tags[1].as_ptr()));
+    assert!(range.contains(&view.label.unwrap().as_ptr()));
+    assert!(range.contains(&view.counters[0].0.as_ptr()));
+}
+
+#[test]
+fn merge_semantics() {
+    // The last value of a singular field wins, and the last entry for a map key wins once the
+    // view is converted.
+    let first = Document {
+        title: "first".to_string(),
+        blobs: vec![(1, vec![1])].into_iter().collect(),
+        body: Some(document::Body::Text("text".to_string())),
+        ..Default::default()
+    };
+    let second = Document {
+        title: "second".to_string(),
+        blobs: vec![(1, vec![2])].into_iter().collect(),
+        body: Some(document::Body::Id(7)),
+        ..Default::default()
+    };
+    let mut buf = first.encode_to_vec();
+    second.encode(&mut buf).unwrap();
+
+    let view = DocumentRef::decode(&buf).unwrap();
+    assert_eq!(view.title, "second");
+    assert_eq!(view.blobs, [(1, &[1][..]), (1, &[2][..])]);
+    assert_eq!(view.body, Some(document::BodyRef::Id(7)));
+
+    let mut merged = first.clone();
+    merged.merge(&buf[first.encoded_len()..]).unwrap();
+    assert_eq!(view.to_owned(), merged);
+}
+
+#[test]
+fn invalid_string() {
+    let buf = [0x0a, 0x02, 0xc3, 0x28];
+    assert!(DocumentRef::decode(&buf).is_err());
+    assert!(Document::decode(&buf[..]).is_err());
+}
+
+#[test]
+fn default_getters() {
+    let buf = Settings::default().encode_to_vec();
+    let settings = SettingsRef::decode(&buf).unwrap();
+    assert_eq!(settings.port, None);
+    assert_eq!(settings.port(), 8080);
+    assert_eq!(settings.host(), "localhost");
+    assert_eq!(settings.magic(), [1, 2]);
+    assert_eq!(settings.kind(), Kind::Leaf);
+    assert_eq!(settings.ratio(), f64::INFINITY);
+    assert_eq!(settings.scale(), 1.5);
+    assert!(!settings.enabled());
+
+    let buf = Settings {
+        port: Some(443),
+        host: Some("example.com".to_string()),
+        magic: Some(vec![]),
+        kind: Some(Kind::Unspecified as i32),
+        enabled: Some(true),
+        ..Default::default()
+    }
+    .encode_to_vec();
+    let settings = SettingsRef::decode(&buf).unwrap();
+    assert_eq!(settings.port(), 443);
+    assert_eq!(settings.host(), "example.com");
+    assert_eq!(settings.magic(), [0u8; 0]);
+    assert_eq!(settings.kind(), Kind::Unspecified);
+    assert!(settings.enabled());
+}
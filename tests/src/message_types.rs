@@ -0,0 +1,175 @@
+include!(concat!(env!("OUT_DIR"), "/message_types.rs"));
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use prost::json::{from_str, to_string};
+use prost::text_format::TextFormatMessage;
+use prost::Message;
+
+/// An order ID, encoded as the big-endian bytes of a non-zero ID.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OrderId(pub u32);
+
+impl From<Vec<u8>> for OrderId {
+    fn from(bytes: Vec<u8>) -> Self {
+        OrderId(u32::from_be_bytes(
+            <[u8; 4]>::try_from(bytes).unwrap_or_default(),
+        ))
+    }
+}
+
+impl From<OrderId> for Vec<u8> {
+    fn from(id: OrderId) -> Self {
+        if id.0 == 0 {
+            Vec::new()
+        } else {
+            id.0.to_be_bytes().to_vec()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Email(pub String);
+
+impl From<String> for Email {
+    fn from(email: String) -> Self {
+        Email(email)
+    }
+}
+
+impl From<Email> for String {
+    fn from(email: Email) -> Self {
+        email.0
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tag(pub String);
+
+impl From<String> for Tag {
+    fn from(tag: String) -> Self {
+        Tag(tag)
+    }
+}
+
+impl From<Tag> for String {
+    fn from(tag: Tag) -> Self {
+        tag.0
+    }
+}
+
+/// A hand-written message, which is wire compatible with `Money`.
+#[derive(Clone, Copy, PartialEq, Message)]
+#[prost(json, text_format)]
+pub struct Price {
+    #[prost(int64, tag = "1")]
+    pub units: i64,
+    #[prost(int32, tag = "2")]
+    pub nanos: i32,
+}
+
+fn order() -> Order {
+    Order {
+        id: OrderId(7),
+        email: Some(Email("ferris@example.com".to_string())),
+        tags: vec![Tag("a".to_string()), Tag("b".to_string())],
+        price: Some(Price {
+            units: 3,
+            nanos: 500,
+        }),
+        quantity: 2,
+    }
+}
+
+#[test]
+fn roundtrip_message_types() {
+    let order = order();
+    let encoded = order.encode_to_vec();
+    assert_eq!(encoded.len(), order.encoded_len());
+    assert_eq!(Order::decode(encoded.as_slice()).unwrap(), order);
+}
+
+#[test]
+fn message_types_are_wire_compatible() {
+    let order = order();
+    let money = Money::decode(order.price.unwrap().encode_to_vec().as_slice()).unwrap();
+    assert_eq!(
+        money,
+        Money {
+            units: 3,
+            nanos: 500
+        }
+    );
+
+    let decoded = Order::decode(&[0x0a, 0x04, 0x00, 0x00, 0x01, 0x00][..]).unwrap();
+    assert_eq!(decoded.id, OrderId(256));
+}
+
+#[test]
+fn default_message_types() {
+    let order = Order::default();
+    assert_eq!(order.id, OrderId(0));
+    assert_eq!(order.encoded_len(), 0);
+
+    let mut cleared = self::order();
+    cleared.clear();
+    assert_eq!(cleared, Order::default());
+}
+
+#[test]
+fn merge_message_types() {
+    let mut buf = order().encode_to_vec();
+    Order {
+        tags: vec![Tag("c".to_string())],
+        ..Default::default()
+    }
+    .encode(&mut buf)
+    .unwrap();
+
+    let decoded = Order::decode(buf.as_slice()).unwrap();
+    assert_eq!(decoded.id, OrderId(7));
+    assert_eq!(
+        decoded.tags,
+        vec![
+            Tag("a".to_string()),
+            Tag("b".to_string()),
+            Tag("c".to_string())
+        ]
+    );
+}
+
+#[test]
+fn json_and_text_format_message_types() {
+    let order = order();
+    let json = to_string(&order).unwrap();
+    assert_eq!(from_str::<Order>(&json).unwrap(), order);
+
+    let text = order.to_text_format();
+    assert!(text.contains(r#"email: "ferris@example.com""#), "{text}");
+    assert_eq!(Order::from_text_format(&text).unwrap(), order);
+}
+
+#[test]
+fn build_message_types() {
+    let order = Order::builder()
+        .id(OrderId(7))
+        .email(Email("ferris@example.com".to_string()))
+        .push_tags(Tag("a".to_string()))
+        .build()
+        .unwrap();
+    assert_eq!(order.id, OrderId(7));
+    assert_eq!(order.tags, vec![Tag("a".to_string())]);
+}
+
+#[test]
+fn debug_message_types() {
+    let debug = alloc::format!("{:?}", order());
+    assert!(debug.contains("id: OrderId(7)"), "{debug}");
+    assert!(
+        debug.contains(r#"email: Some(Email("ferris@example.com"))"#),
+        "{debug}"
+    );
+}
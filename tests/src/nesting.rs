@@ -74,3 +74,38 @@ fn test_deep_nesting_map() {
     assert!(build_and_roundtrip(50).is_ok());
     assert!(build_and_roundtrip(51).is_err());
 }
+
+#[test]
+fn test_encode_cached() {
+    let mut a = A::default();
+    for i in 0..6 {
+        let mut next = A {
+            a: Some(Box::new(a.clone())),
+            b: Some(Box::new(B {
+                a: Some(Box::default()),
+            })),
+            ..A::default()
+        };
+        next.repeated_a.push(a.clone());
+        next.map_a.insert(i, a);
+        a = next;
+    }
+
+    let mut cache = prost::LengthCache::new();
+    let mut buf = Vec::new();
+    a.encode_cached(&mut buf, &mut cache).unwrap();
+    assert_eq!(buf, a.encode_to_vec());
+    assert_eq!(
+        a.encoded_len_cached(&mut prost::LengthCache::new()),
+        buf.len()
+    );
+
+    // The cache is cleared before encoding another message.
+    let c = C {
+        r: Vec::from([C::default(), C::default()]),
+    };
+    buf.clear();
+    c.encode_cached(&mut buf, &mut cache).unwrap();
+    assert_eq!(buf, c.encode_to_vec());
+    assert_eq!(cache.len(), 2);
+}
@@ -0,0 +1,52 @@
+include!(concat!(env!("OUT_DIR"), "/non_exhaustive.rs"));
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec;
+
+#[test]
+fn construct_with_accessors() {
+    let account = Account::new()
+        .with_name("Ferris".to_string())
+        .with_age(Some(7))
+        .with_role(Role::Admin as i32)
+        .with_emails(vec!["ferris@example.com".to_string()])
+        .with_manager(Some(Box::new(
+            Account::new().with_name("Corro".to_string()),
+        )))
+        .with_contact(Some(account::Contact::Phone("555".to_string())));
+
+    assert_eq!(account.name(), "Ferris");
+    assert_eq!(account.emails(), &["ferris@example.com"]);
+    assert_eq!(account.manager().as_ref().unwrap().name(), "Corro");
+    assert_eq!(
+        account.contact(),
+        &Some(account::Contact::Phone("555".to_string()))
+    );
+    // Optional scalar and enumeration fields keep their getters.
+    assert_eq!(account.age(), 7);
+    assert_eq!(account.role(), Role::Admin);
+}
+
+#[test]
+fn modify_with_accessors() {
+    let mut account = Account::new();
+    account.name_mut().push_str("Ferris");
+    account
+        .labels_mut()
+        .insert("team".to_string(), "rust".to_string());
+    *account.age_mut() = Some(7);
+
+    assert_eq!(account.name, "Ferris");
+    assert_eq!(account.labels.get("team").map(String::as_str), Some("rust"));
+    assert_eq!(account.age, Some(7));
+    assert_eq!(Account::new(), Account::default());
+}
+
+#[test]
+fn with_sets_presence() {
+    let counter = Counter::new().with_count(0);
+    assert!(counter.has_count());
+    assert_eq!(counter.count(), &0);
+    assert!(!Counter::new().has_count());
+}
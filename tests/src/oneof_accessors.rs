@@ -0,0 +1,50 @@
+include!(concat!(env!("OUT_DIR"), "/oneof_accessors.rs"));
+
+use alloc::boxed::Box;
+use alloc::string::ToString;
+
+#[test]
+fn set_and_get_oneof_fields() {
+    let mut shape = Shape::default();
+    assert_eq!(shape.which_kind(), None);
+    assert_eq!(shape.kind_as_circle(), None);
+
+    shape.set_kind_circle(Circle { radius: 1.0 });
+    assert_eq!(shape.which_kind(), Some("circle"));
+    assert_eq!(shape.kind_as_circle(), Some(&Circle { radius: 1.0 }));
+    assert_eq!(shape.kind_as_square(), None);
+
+    shape.set_kind_label("label".to_string());
+    assert_eq!(shape.which_kind(), Some("label"));
+    assert_eq!(shape.kind_as_circle(), None);
+    assert_eq!(
+        shape.kind_as_label().map(|label| label.as_str()),
+        Some("label")
+    );
+    assert_eq!(shape.kind, Some(shape::Kind::Label("label".to_string())));
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_oneof_fields() {
+    let mut shape = Shape::default();
+    shape.set_kind_legacy_id(7);
+    assert_eq!(shape.which_kind(), Some("legacy_id"));
+    assert_eq!(shape.kind_as_legacy_id(), Some(&7));
+}
+
+#[test]
+fn boxed_and_keyword_oneof_fields() {
+    let mut shape = Shape::default();
+    shape.set_type_async(true);
+    assert_eq!(shape.which_type(), Some("async"));
+    assert_eq!(shape.type_as_async(), Some(&true));
+
+    let mut child = Shape::default();
+    child.set_kind_square(Square { side: 2.0 });
+    shape.set_type_child(child.clone());
+    assert_eq!(shape.which_type(), Some("child"));
+    assert_eq!(shape.type_as_child(), Some(&child));
+    assert_eq!(shape.r#type, Some(shape::Type::Child(Box::new(child))));
+    assert_eq!(shape.type_as_async(), None);
+}
@@ -0,0 +1,122 @@
+include!(concat!(env!("OUT_DIR"), "/open_enums.rs"));
+
+use alloc::vec;
+
+use prost::json::{from_str, to_string};
+use prost::text_format::TextFormatMessage;
+use prost::{Message, OpenEnum};
+
+fn legacy_light() -> LegacyLight {
+    LegacyLight {
+        color: Color::Green as i32,
+        fallback: Some(9),
+        palette: vec![Color::Red as i32, 8],
+    }
+}
+
+fn light() -> Light {
+    Light {
+        color: OpenEnum::Known(Color::Green),
+        fallback: Some(OpenEnum::Unknown(9)),
+        palette: vec![OpenEnum::Known(Color::Red), OpenEnum::Unknown(8)],
+        choice: Some(light::Choice::Chosen(Color::Red as i32)),
+    }
+}
+
+#[test]
+fn decode_open_enums() {
+    let encoded = legacy_light().encode_to_vec();
+    let decoded = Light::decode(encoded.as_slice()).unwrap();
+    assert_eq!(
+        decoded,
+        Light {
+            choice: None,
+            ..light()
+        }
+    );
+
+    // Unknown values are encoded again unchanged.
+    assert_eq!(decoded.encoded_len(), encoded.len());
+    assert_eq!(decoded.encode_to_vec(), encoded);
+}
+
+#[test]
+fn roundtrip_open_enums() {
+    let light = light();
+    let encoded = light.encode_to_vec();
+    assert_eq!(encoded.len(), light.encoded_len());
+    assert_eq!(Light::decode(encoded.as_slice()).unwrap(), light);
+}
+
+#[test]
+fn match_open_enums() {
+    let description = |light: &Light| match light.color {
+        OpenEnum::Known(Color::Unspecified) => "unspecified",
+        OpenEnum::Known(Color::Red) => "red",
+        OpenEnum::Known(Color::Green) => "green",
+        OpenEnum::Unknown(_) => "unknown",
+    };
+    assert_eq!(description(&light()), "green");
+
+    let decoded = Light::decode(&[0x08, 0x07][..]).unwrap();
+    assert_eq!(decoded.color, OpenEnum::Unknown(7));
+    assert_eq!(description(&decoded), "unknown");
+}
+
+#[test]
+fn default_open_enums() {
+    let light = Light::default();
+    assert_eq!(light.color, OpenEnum::Known(Color::Unspecified));
+    assert_eq!(light.fallback, None);
+    assert_eq!(light.encoded_len(), 0);
+
+    let mut cleared = self::light();
+    cleared.clear();
+    assert_eq!(cleared, Light::default());
+}
+
+#[test]
+fn json_open_enums() {
+    let light = light();
+    let json = to_string(&light).unwrap();
+    let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+    assert_eq!(value["color"], "COLOR_GREEN");
+    assert_eq!(value["fallback"], 9);
+    assert_eq!(from_str::<Light>(&json).unwrap(), light);
+
+    assert_eq!(serde_json::to_value(&light).unwrap(), value);
+    assert_eq!(serde_json::from_str::<Light>(&json).unwrap(), light);
+}
+
+#[test]
+fn text_format_open_enums() {
+    let light = light();
+    let text = light.to_text_format();
+    assert!(text.contains("color: COLOR_GREEN"), "{text}");
+    assert_eq!(Light::from_text_format(&text).unwrap(), light);
+}
+
+#[test]
+fn build_open_enums() {
+    let light = Light::builder()
+        .color(Color::Green)
+        .fallback(OpenEnum::Unknown(9))
+        .push_palette(Color::Red)
+        .push_palette(OpenEnum::Unknown(8))
+        .build()
+        .unwrap();
+    assert_eq!(
+        light,
+        Light {
+            choice: None,
+            ..self::light()
+        }
+    );
+}
+
+#[test]
+fn debug_open_enums() {
+    let debug = alloc::format!("{:?}", light());
+    assert!(debug.contains("color: Known(Green)"), "{debug}");
+    assert!(debug.contains("fallback: Some(Unknown(9))"), "{debug}");
+}
@@ -0,0 +1,58 @@
+#![allow(deprecated)]
+
+pub mod proto2 {
+    include!(concat!(env!("OUT_DIR"), "/packed_encoding.proto2.rs"));
+}
+
+pub mod proto3 {
+    include!(concat!(env!("OUT_DIR"), "/packed_encoding.proto3.rs"));
+}
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use prost::Message;
+
+#[test]
+fn proto2_defaults_to_unpacked() {
+    let samples = proto2::Samples {
+        unpacked: vec![1, 2],
+        implicit: vec![3, 4],
+        packed: vec![5, 6],
+        levels: vec![proto2::Level::High as i32],
+        deprecated: vec![],
+    };
+    let expected: Vec<u8> = vec![
+        0x08, 0x01, 0x08, 0x02, // unpacked
+        0x10, 0x03, 0x10, 0x04, // implicit
+        0x1A, 0x02, 0x05, 0x06, // packed
+        0x20, 0x01, // levels
+    ];
+    assert_eq!(samples.encode_to_vec(), expected);
+    assert_eq!(
+        proto2::Samples::decode(expected.as_slice()).unwrap(),
+        samples
+    );
+}
+
+#[test]
+fn proto3_defaults_to_packed() {
+    let samples = proto3::Samples {
+        unpacked: vec![1, 2],
+        implicit: vec![3, 4],
+        packed: vec![5, 6],
+        deprecated: vec![0.0],
+    };
+    let mut expected: Vec<u8> = vec![
+        0x08, 0x01, 0x08, 0x02, // unpacked
+        0x12, 0x02, 0x03, 0x04, // implicit
+        0x1A, 0x02, 0x05, 0x06, // packed
+        0x2A, 0x08, // deprecated, which has options but no `packed` option
+    ];
+    expected.extend_from_slice(&[0; 8]);
+    assert_eq!(samples.encode_to_vec(), expected);
+    assert_eq!(
+        proto3::Samples::decode(expected.as_slice()).unwrap(),
+        samples
+    );
+}
@@ -0,0 +1,155 @@
+include!(concat!(env!("OUT_DIR"), "/presence_bits.rs"));
+
+use alloc::format;
+use alloc::string::String;
+
+use prost::json::{from_str, to_string};
+use prost::Message;
+
+fn option_settings() -> OptionSettings {
+    OptionSettings {
+        volume: Some(0),
+        name: Some(String::new()),
+        mode: Some(Mode::Fast as i32),
+        muted: None,
+        level: 3,
+    }
+}
+
+#[test]
+fn set_has_and_clear() {
+    let mut settings = Settings::default();
+    assert!(!settings.has_volume());
+    assert!(!settings.has_name());
+    assert_eq!(settings.encoded_len(), 0);
+
+    settings.set_volume(0);
+    settings.set_name(String::new());
+    settings.set_mode(Mode::Fast);
+    settings.level = 3;
+    assert!(settings.has_volume());
+    assert!(settings.has_name());
+    assert!(settings.has_mode());
+    assert!(!settings.has_muted());
+    assert_eq!(settings.mode(), Mode::Fast);
+
+    // Fields which are set are encoded even with their default value, as with `Option`s.
+    assert_eq!(settings.encode_to_vec(), option_settings().encode_to_vec());
+
+    settings.clear_mode();
+    assert!(!settings.has_mode());
+    assert_eq!(settings.mode, 0);
+
+    settings.clear();
+    assert!(!settings.has_volume());
+    assert_eq!(settings, Settings::default());
+}
+
+#[test]
+fn decode_sets_presence() {
+    let encoded = option_settings().encode_to_vec();
+    let settings = Settings::decode(encoded.as_slice()).unwrap();
+    assert!(settings.has_volume());
+    assert!(settings.has_name());
+    assert!(settings.has_mode());
+    assert!(!settings.has_muted());
+    assert_eq!(settings.volume, 0);
+    assert_eq!(settings.level, 3);
+    assert_eq!(settings.encode_to_vec(), encoded);
+}
+
+#[test]
+fn merge_from_keeps_presence() {
+    let mut settings = Settings::default();
+    settings.set_muted(true);
+    let mut other = Settings::default();
+    other.set_volume(7);
+
+    settings.merge_from(&other);
+    assert!(settings.has_muted());
+    assert!(settings.has_volume());
+    assert_eq!(settings.volume, 7);
+    assert!(!settings.has_name());
+}
+
+#[test]
+fn many_presence_bits() {
+    let mut wide = Wide::default();
+    wide.set_f1(0);
+    wide.set_f33(0);
+    assert!(wide.has_f1());
+    assert!(!wide.has_f32());
+    assert!(wide.has_f33());
+    assert!(!wide.has_f34());
+
+    let decoded = Wide::decode(wide.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, wide);
+}
+
+#[test]
+fn builder_sets_presence() {
+    let settings = Settings::builder().volume(0).level(3).build().unwrap();
+    assert!(settings.has_volume());
+    assert!(!settings.has_name());
+    assert_eq!(settings.level, 3);
+}
+
+#[test]
+fn json() {
+    assert_eq!(to_string(&Settings::default()).unwrap(), "{}");
+
+    let mut settings = Settings::default();
+    settings.set_volume(0);
+    let json = to_string(&settings).unwrap();
+    assert_eq!(json, r#"{"volume":0}"#);
+    assert_eq!(from_str::<Settings>(&json).unwrap(), settings);
+
+    let settings = from_str::<Settings>(r#"{"name":"","mode":null}"#).unwrap();
+    assert!(settings.has_name());
+    assert!(!settings.has_mode());
+    assert!(settings.name.is_empty());
+}
+
+#[test]
+fn debug_prints_options() {
+    let mut settings = Settings::default();
+    settings.set_volume(0);
+    settings.set_name(String::new());
+    settings.set_mode(Mode::Fast);
+    settings.level = 3;
+    assert_eq!(
+        format!("{settings:?}").replace("Settings", ""),
+        format!("{:?}", option_settings()).replace("OptionSettings", ""),
+    );
+}
+
+#[test]
+fn smaller_than_options() {
+    assert!(core::mem::size_of::<Wide>() < 34 * core::mem::size_of::<Option<u32>>());
+    assert_eq!(
+        core::mem::size_of::<Wide>(),
+        34 * core::mem::size_of::<u32>() + 2 * core::mem::size_of::<u32>()
+    );
+}
+
+#[test]
+fn serde() {
+    let mut settings = Settings::default();
+    settings.set_volume(0);
+    settings.set_mode(Mode::Fast);
+    let json = serde_json::to_string(&settings).unwrap();
+    assert_eq!(json, r#"{"volume":0,"mode":"MODE_FAST"}"#);
+    assert_eq!(
+        json,
+        serde_json::to_string(&option_settings_without_name()).unwrap()
+    );
+    assert_eq!(serde_json::from_str::<Settings>(&json).unwrap(), settings);
+}
+
+fn option_settings_without_name() -> OptionSettings {
+    OptionSettings {
+        name: None,
+        level: 0,
+        ..option_settings()
+    }
+}
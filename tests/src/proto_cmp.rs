@@ -0,0 +1,170 @@
+include!(concat!(env!("OUT_DIR"), "/proto_cmp.rs"));
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use prost::encoding::{encode_key, encode_varint, WireType};
+use prost::proto_ord::ProtoOrd;
+use prost::Message;
+
+fn entry(weight: Option<f64>, name: &str, values: &[i32]) -> Entry {
+    Entry {
+        values: values.to_vec(),
+        name: name.to_string(),
+        weight,
+    }
+}
+
+#[test]
+fn fields_are_compared_by_field_number() {
+    // `weight` has the lowest field number, although it is declared last.
+    assert_eq!(
+        entry(Some(1.0), "b", &[2]).proto_cmp(&entry(Some(2.0), "a", &[1])),
+        Ordering::Less
+    );
+    assert_eq!(
+        entry(Some(1.0), "b", &[1]).proto_cmp(&entry(Some(1.0), "a", &[2])),
+        Ordering::Greater
+    );
+
+    // An unset optional field is less than any value, and floats are compared by their total
+    // order.
+    assert!(entry(None, "b", &[])
+        .proto_cmp(&entry(Some(f64::MIN), "a", &[]))
+        .is_lt());
+    assert!(entry(Some(-0.0), "", &[])
+        .proto_cmp(&entry(Some(0.0), "", &[]))
+        .is_lt());
+    assert!(entry(Some(f64::NAN), "", &[])
+        .proto_cmp(&entry(Some(f64::NAN), "", &[]))
+        .is_eq());
+}
+
+#[test]
+fn repeated_fields_are_compared_element_by_element() {
+    let cmp = |a: &[i32], b: &[i32]| entry(None, "", a).proto_cmp(&entry(None, "", b));
+    assert_eq!(cmp(&[1, 2], &[1, 2]), Ordering::Equal);
+    assert_eq!(cmp(&[1, 2], &[1, 3]), Ordering::Less);
+    assert_eq!(cmp(&[2], &[1, 3]), Ordering::Greater);
+    // A prefix is less than the longer field.
+    assert_eq!(cmp(&[], &[0]), Ordering::Less);
+    assert_eq!(cmp(&[1, 2], &[1]), Ordering::Greater);
+
+    let history = |names: &[&str]| Index {
+        history: names.iter().map(|name| entry(None, name, &[])).collect(),
+        ..Index::default()
+    };
+    assert!(history(&["a", "b"])
+        .proto_cmp(&history(&["a", "c"]))
+        .is_lt());
+    assert!(history(&["b"]).proto_cmp(&history(&["a", "c"])).is_gt());
+}
+
+#[test]
+fn maps_are_compared_by_sorted_entries() {
+    let index = |entries: &[(&str, i32)]| Index {
+        entries: entries
+            .iter()
+            .map(|&(key, value)| (key.to_string(), entry(None, "", &[value])))
+            .collect::<BTreeMap<_, _>>(),
+        ..Index::default()
+    };
+    assert!(index(&[("a", 1), ("b", 2)])
+        .proto_cmp(&index(&[("b", 2), ("a", 1)]))
+        .is_eq());
+    // Entries are compared by key, then by value.
+    assert!(index(&[("a", 1)]).proto_cmp(&index(&[("b", 0)])).is_lt());
+    assert!(index(&[("a", 2)])
+        .proto_cmp(&index(&[("a", 1), ("b", 0)]))
+        .is_gt());
+    assert!(index(&[("a", 1)])
+        .proto_cmp(&index(&[("a", 1), ("b", 0)]))
+        .is_lt());
+    assert!(index(&[]).proto_cmp(&index(&[("", 0)])).is_lt());
+}
+
+#[test]
+fn oneof_fields_are_compared_at_their_field_numbers() {
+    let index = |key| Index {
+        key,
+        ..Index::default()
+    };
+    let unset = index(None);
+    let id = |id| index(Some(index::Key::Id(id)));
+    let label = |label: &str| index(Some(index::Key::Label(label.to_string())));
+
+    assert!(unset.proto_cmp(&id(0)).is_lt());
+    assert!(unset.proto_cmp(&label("")).is_lt());
+    assert!(id(1).proto_cmp(&id(2)).is_lt());
+    assert!(label("b").proto_cmp(&label("a")).is_gt());
+    // `label` has a lower field number than `id`, so any set label is greater than any id.
+    assert!(label("").proto_cmp(&id(u64::MAX)).is_gt());
+    assert!(id(u64::MAX).proto_cmp(&label("")).is_lt());
+
+    // The oneof is compared before `parent`, which has a higher field number.
+    let child = Index {
+        parent: Some(Box::new(label("z"))),
+        ..id(1)
+    };
+    assert!(child.proto_cmp(&id(2)).is_lt());
+    assert!(child.proto_cmp(&id(1)).is_gt());
+}
+
+#[test]
+fn unknown_fields_are_compared_after_known_fields() {
+    let partial = |id: i32, unknown: &[(u32, u64)]| {
+        let mut buf = Partial {
+            id,
+            ..Partial::default()
+        }
+        .encode_to_vec();
+        for &(tag, value) in unknown {
+            encode_key(tag, WireType::Varint, &mut buf);
+            encode_varint(value, &mut buf);
+        }
+        Partial::decode(buf.as_slice()).unwrap()
+    };
+
+    assert!(partial(1, &[(2, 5)])
+        .proto_cmp(&partial(1, &[(2, 5)]))
+        .is_eq());
+    assert!(partial(1, &[(9, 0)]).proto_cmp(&partial(2, &[])).is_lt());
+    assert!(partial(1, &[]).proto_cmp(&partial(1, &[(2, 0)])).is_lt());
+    assert!(partial(1, &[(2, 6)])
+        .proto_cmp(&partial(1, &[(2, 5)]))
+        .is_gt());
+    // Unknown fields are sorted by field number, keeping the order of repeated fields.
+    assert!(partial(1, &[(3, 0), (2, 1)])
+        .proto_cmp(&partial(1, &[(2, 1), (3, 0)]))
+        .is_eq());
+    assert!(partial(1, &[(2, 1), (2, 0)])
+        .proto_cmp(&partial(1, &[(2, 0), (2, 1)]))
+        .is_gt());
+    assert!(partial(1, &[(3, 0)])
+        .proto_cmp(&partial(1, &[(2, 9)]))
+        .is_gt());
+}
+
+#[test]
+fn sort_and_dedup() {
+    let mut entries = vec![
+        entry(Some(1.0), "b", &[]),
+        entry(None, "c", &[1]),
+        entry(Some(1.0), "a", &[2]),
+        entry(None, "c", &[1]),
+    ];
+    entries.sort_by(ProtoOrd::proto_cmp);
+    entries.dedup_by(|a, b| a.proto_cmp(b).is_eq());
+    assert_eq!(
+        entries,
+        [
+            entry(None, "c", &[1]),
+            entry(Some(1.0), "a", &[2]),
+            entry(Some(1.0), "b", &[]),
+        ]
+    );
+}
@@ -0,0 +1,83 @@
+pub mod arc {
+    include!(concat!(env!("OUT_DIR"), "/recursive_wrapper.arc.rs"));
+}
+
+pub mod unboxed {
+    include!(concat!(env!("OUT_DIR"), "/recursive_wrapper.unboxed.rs"));
+}
+
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+
+use prost::Message;
+
+fn leaf(label: &str, value: i64) -> arc::Node {
+    arc::Node {
+        label: label.to_string(),
+        value: Some(arc::node::Value::Leaf(value)),
+        ..arc::Node::default()
+    }
+}
+
+#[test]
+fn recursive_fields_are_shared() {
+    let left = Arc::new(leaf("left", 1));
+    let tree = arc::Node {
+        label: "root".to_string(),
+        left: Some(left.clone()),
+        right: Some(Arc::new(leaf("right", 2))),
+        value: Some(arc::node::Value::Subtree(left.clone())),
+        children: vec![leaf("child", 3)],
+    };
+
+    // Cloning the tree shares its subtrees.
+    let clone = tree.clone();
+    assert!(Arc::ptr_eq(
+        clone.left.as_ref().unwrap(),
+        tree.left.as_ref().unwrap()
+    ));
+    assert_eq!(Arc::strong_count(&left), 5);
+
+    let decoded = arc::Node::decode(tree.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, tree);
+}
+
+#[test]
+fn shared_fields_are_copied_on_merge() {
+    let left = Arc::new(leaf("left", 1));
+    let mut tree = arc::Node {
+        left: Some(left.clone()),
+        ..arc::Node::default()
+    };
+
+    let update = arc::Node {
+        left: Some(Arc::new(arc::Node {
+            label: "updated".to_string(),
+            ..arc::Node::default()
+        })),
+        ..arc::Node::default()
+    };
+    tree.merge(update.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(tree.left.as_ref().unwrap().label, "updated");
+    assert_eq!(tree.left.as_ref().unwrap().value, left.value);
+    // The shared subtree is unchanged.
+    assert_eq!(left.label, "left");
+}
+
+#[test]
+fn only_configured_fields_are_boxed() {
+    // `Inner.outer` holds its message directly.
+    let inner = unboxed::Inner {
+        outer: Some(unboxed::Outer {
+            inner: Some(Box::new(unboxed::Inner {
+                outer: None,
+                depth: 2,
+            })),
+        }),
+        depth: 1,
+    };
+    let decoded = unboxed::Inner::decode(inner.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, inner);
+}
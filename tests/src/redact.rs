@@ -0,0 +1,72 @@
+include!(concat!(env!("OUT_DIR"), "/redact.rs"));
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec;
+
+use prost::Message;
+
+fn credentials() -> Credentials {
+    Credentials {
+        user: "ferris".to_string(),
+        token: "hunter2".to_string(),
+        headers: BTreeMap::from([("authorization".to_string(), "Bearer abc".to_string())]),
+    }
+}
+
+#[test]
+fn debug_hides_sensitive_fields() {
+    let debug = format!("{:?}", credentials());
+    assert_eq!(
+        debug,
+        r#"Credentials { user: "ferris", token: <redacted>, headers: <redacted> }"#
+    );
+
+    let session = Session {
+        auth: Some(session::Auth::Password("hunter2".to_string())),
+        ..Default::default()
+    };
+    let debug = format!("{session:?}");
+    assert!(debug.contains("Password(<redacted>)"), "{debug}");
+    assert!(!debug.contains("hunter2"), "{debug}");
+}
+
+#[test]
+fn redact_clears_sensitive_fields() {
+    let mut session = Session {
+        credentials: Some(credentials()),
+        history: vec![credentials(), credentials()],
+        by_host: BTreeMap::from([("example.com".to_string(), credentials())]),
+        secret: vec![1, 2, 3],
+        auth: Some(session::Auth::Delegated(credentials())),
+    };
+    session.redact();
+
+    let redacted = Credentials {
+        user: "ferris".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(session.credentials, Some(redacted.clone()));
+    assert_eq!(session.history, vec![redacted.clone(), redacted.clone()]);
+    assert_eq!(session.by_host["example.com"], redacted);
+    assert!(session.secret.is_empty());
+    assert_eq!(session.auth, Some(session::Auth::Delegated(redacted)));
+
+    let mut session = Session {
+        auth: Some(session::Auth::Password("hunter2".to_string())),
+        ..Default::default()
+    };
+    session.redact();
+    assert_eq!(
+        session.auth,
+        Some(session::Auth::Password(Default::default()))
+    );
+
+    let mut session = Session {
+        auth: Some(session::Auth::PinLength(4)),
+        ..Default::default()
+    };
+    session.redact();
+    assert_eq!(session.auth, Some(session::Auth::PinLength(4)));
+}
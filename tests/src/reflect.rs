@@ -0,0 +1,155 @@
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use prost::Message;
+use prost_types::reflect::{DescriptorPool, DynamicMessage, MapKey, MessageDescriptor, Value};
+
+use crate::text_format::{drawing, Drawing, Point, Shape};
+
+fn drawing_descriptor() -> MessageDescriptor {
+    let pool =
+        DescriptorPool::decode(&include_bytes!(concat!(env!("OUT_DIR"), "/text_format.bin"))[..])
+            .unwrap();
+    pool.get_message_by_name("text_format.Drawing").unwrap()
+}
+
+#[test]
+fn decode_generated() {
+    let drawing = drawing();
+    let bytes = drawing.encode_to_vec();
+    let message = DynamicMessage::decode(drawing_descriptor(), bytes.as_slice()).unwrap();
+
+    assert_eq!(message.encoded_len(), bytes.len());
+    assert_eq!(message.encode_to_vec(), bytes);
+
+    assert_eq!(
+        message.get_field_by_name("title").unwrap().as_str(),
+        Some(drawing.title.as_str())
+    );
+    assert_eq!(
+        *message.get_field_by_name("shapes").unwrap(),
+        Value::List(vec![Value::EnumNumber(1), Value::EnumNumber(7)])
+    );
+    assert_eq!(
+        *message.get_field_by_name("flags").unwrap(),
+        Value::List(vec![Value::Bool(true), Value::Bool(false)])
+    );
+    let named_points = message.get_field_by_name("named_points").unwrap();
+    let point = &named_points.as_map().unwrap()[&MapKey::String("a".to_string())];
+    assert_eq!(
+        point
+            .as_message()
+            .unwrap()
+            .get_field_by_name("y")
+            .unwrap()
+            .as_i32(),
+        Some(2)
+    );
+    let layer = message.get_field_by_name("layer").unwrap();
+    assert_eq!(
+        layer
+            .as_message()
+            .unwrap()
+            .get_field_by_name("depth")
+            .unwrap()
+            .as_u64(),
+        Some(9)
+    );
+    assert_eq!(
+        message
+            .get_field_by_name("texture")
+            .unwrap()
+            .as_enum_number(),
+        Some(0)
+    );
+    let created = message.get_field_by_name("created").unwrap();
+    let created = created.as_message().unwrap();
+    assert_eq!(
+        created.descriptor().full_name(),
+        "google.protobuf.Timestamp"
+    );
+    assert_eq!(
+        created.get_field_by_name("seconds").unwrap().as_i64(),
+        Some(5)
+    );
+}
+
+#[test]
+fn encode_generated() {
+    let desc = drawing_descriptor();
+    let mut message = DynamicMessage::new(desc.clone());
+    message.set_field_by_name("title", Value::String("dynamic".to_string()));
+    message.set_field_by_name("scale", Value::F64(2.0));
+
+    let point_desc = desc
+        .get_field_by_name("points")
+        .unwrap()
+        .kind()
+        .as_message()
+        .unwrap()
+        .clone();
+    let mut point = DynamicMessage::new(point_desc);
+    point.set_field_by_name("x", Value::I32(-3));
+    message.set_field_by_name("origin", Value::Message(point.clone()));
+    message.set_field_by_name("points", Value::List(vec![Value::Message(point)]));
+    message.set_field_by_name(
+        "shape_map",
+        Value::Map(
+            vec![(MapKey::I32(1), Value::EnumNumber(Shape::Circle as i32))]
+                .into_iter()
+                .collect(),
+        ),
+    );
+
+    let point = Point {
+        x: Some(-3),
+        y: None,
+    };
+    let decoded = Drawing::decode(message.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(
+        decoded,
+        Drawing {
+            title: "dynamic".to_string(),
+            scale: Some(2.0),
+            points: vec![point],
+            shape_map: vec![(1, Shape::Circle as i32)].into_iter().collect(),
+            fill: Some(crate::text_format::drawing::Fill::Origin(point)),
+            ..Default::default()
+        }
+    );
+
+    // Unset proto2 fields read as their declared defaults.
+    assert_eq!(
+        message.get_field_by_name("shape").unwrap().as_enum_number(),
+        Some(Shape::Square as i32)
+    );
+    assert_eq!(
+        message
+            .fields()
+            .map(|(field, _)| field.name().to_string())
+            .collect::<Vec<_>>(),
+        ["title", "scale", "points", "shape_map", "origin"]
+    );
+}
+
+#[test]
+fn embedded_file_descriptor_set() {
+    use crate::service_definitions::FILE_DESCRIPTOR_SET;
+
+    let mut pool = DescriptorPool::new();
+    pool.add_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .unwrap();
+
+    // The imported well-known types are embedded with the package.
+    assert!(pool
+        .get_file_by_name("google/protobuf/empty.proto")
+        .is_some());
+    let service = pool
+        .get_service_by_name("service_definitions.Greeter")
+        .unwrap();
+    assert_eq!(service.methods().len(), 3);
+    assert!(pool
+        .get_message_by_name("service_definitions.HelloRequest")
+        .is_some());
+}
@@ -0,0 +1,115 @@
+include!(concat!(env!("OUT_DIR"), "/reflect_message.rs"));
+
+use alloc::string::ToString;
+use alloc::vec;
+
+use prost::Message;
+use prost_types::reflect::{DynamicMessage, ReflectMessage, Value};
+
+/// Replaces the `password` fields of a message and of its sub-messages.
+fn redact<M: ReflectMessage>(message: &mut M) {
+    for field in message.descriptor().fields() {
+        let value = message.get_field_by_number(field.number()).unwrap();
+        if field.name() == "password" {
+            message.set_field_by_number(field.number(), Value::String("<redacted>".to_string()));
+        } else if let Value::Message(mut sub_message) = value {
+            redact(&mut sub_message);
+            message.set_field_by_number(field.number(), Value::Message(sub_message));
+        }
+    }
+}
+
+fn account() -> Account {
+    Account {
+        user: "ferris".to_string(),
+        password: "hunter2".to_string(),
+        emails: vec!["ferris@example.com".to_string()],
+        profile: Some(account::Profile {
+            display_name: "Ferris".to_string(),
+            password: "hunter3".to_string(),
+        }),
+        contact: Some(account::Contact::Phone("555".to_string())),
+    }
+}
+
+#[test]
+fn descriptor() {
+    let account = account();
+    assert_eq!(account.descriptor().full_name(), "reflect_message.Account");
+    assert_eq!(
+        account.profile.as_ref().unwrap().descriptor().full_name(),
+        "reflect_message.Account.Profile"
+    );
+    assert_eq!(
+        DESCRIPTOR_POOL
+            .get_message("reflect_message.Account")
+            .fields()
+            .len(),
+        6
+    );
+}
+
+#[test]
+fn get_field_by_number() {
+    let account = account();
+    assert_eq!(
+        account.get_field_by_number(1),
+        Some(Value::String("ferris".to_string()))
+    );
+    assert_eq!(
+        account.get_field_by_number(3),
+        Some(Value::List(vec![Value::String(
+            "ferris@example.com".to_string()
+        )]))
+    );
+    assert_eq!(
+        account.get_field_by_number(5),
+        Some(Value::String("555".to_string()))
+    );
+    assert_eq!(account.get_field_by_number(6), Some(Value::U32(0)));
+    assert_eq!(account.get_field_by_number(7), None);
+
+    let profile = account.get_field_by_number(4).unwrap();
+    assert_eq!(
+        profile
+            .as_message()
+            .unwrap()
+            .get_field_by_number(1)
+            .unwrap()
+            .as_str(),
+        Some("Ferris")
+    );
+}
+
+#[test]
+fn set_field_by_number() {
+    let mut account = account();
+    account.set_field_by_number(1, Value::String("crab".to_string()));
+    account.set_field_by_number(6, Value::U32(42));
+    assert_eq!(account.user, "crab");
+    assert_eq!(account.contact, Some(account::Contact::Extension(42)));
+    assert_eq!(account.password, "hunter2");
+}
+
+#[test]
+#[should_panic]
+fn set_invalid_field() {
+    account().set_field_by_number(1, Value::Bool(true));
+}
+
+#[test]
+fn generic_redaction() {
+    let mut redacted = account();
+    redact(&mut redacted);
+    assert_eq!(redacted.password, "<redacted>");
+    assert_eq!(redacted.profile.as_ref().unwrap().password, "<redacted>");
+    assert_eq!(redacted.user, "ferris");
+
+    let mut message: DynamicMessage = account().transcode_to_dynamic();
+    redact(&mut message);
+    assert_eq!(
+        message.get_field_by_name("password").unwrap().as_str(),
+        Some("<redacted>")
+    );
+    assert_eq!(message.encode_to_vec(), redacted.encode_to_vec());
+}
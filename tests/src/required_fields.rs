@@ -0,0 +1,98 @@
+include!(concat!(env!("OUT_DIR"), "/required_fields.rs"));
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use prost::Message;
+
+fn item(id: i32) -> Item {
+    Item {
+        id: Some(id),
+        label: None,
+    }
+}
+
+#[test]
+fn test_presence_accessors() {
+    let mut item = item(0);
+    assert!(item.has_id());
+    assert_eq!(item.id(), 0);
+
+    item.clear_id();
+    assert!(!item.has_id());
+    assert_eq!(item.id, None);
+    assert_eq!(item.id(), 0);
+}
+
+#[test]
+fn test_missing_required_fields() {
+    let order = Order {
+        name: None,
+        item: Some(Item::default()),
+        items: vec![item(1), Item::default()],
+        items_by_key: vec![("a".to_string(), Item::default())]
+            .into_iter()
+            .collect(),
+        choice: Some(order::Choice::Chosen(Item::default())),
+    };
+    assert!(!order.is_initialized());
+    assert_eq!(
+        order.missing_required_fields(),
+        vec![
+            "name",
+            "item.id",
+            "items[1].id",
+            "items_by_key[\"a\"].id",
+            "chosen.id"
+        ]
+    );
+
+    let order = Order {
+        item: None,
+        choice: Some(order::Choice::Count(1)),
+        ..Order::default()
+    };
+    assert_eq!(order.missing_required_fields(), vec!["name", "item"]);
+
+    let order = Order {
+        name: Some("order".to_string()),
+        item: Some(item(1)),
+        ..Order::default()
+    };
+    assert!(order.is_initialized());
+    assert_eq!(order.check_initialized(), Ok(()));
+
+    // Required fields of messages which do not track presence are always set.
+    assert!(Untracked::default().is_initialized());
+    let untracked = Untracked {
+        id: 0,
+        item: Some(Item::default()),
+    };
+    assert_eq!(untracked.missing_required_fields(), vec!["item.id"]);
+}
+
+#[test]
+fn test_check_initialized_after_decode() {
+    let order = Order {
+        name: Some("order".to_string()),
+        item: Some(Item::default()),
+        ..Order::default()
+    };
+    let buf = order.encode_to_vec();
+
+    // Decoding does not fail on missing required fields, but checking the decoded message does.
+    let decoded = Order::decode(buf.as_slice()).unwrap();
+    assert_eq!(decoded, order);
+    let error = decoded.check_initialized().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "failed to decode Protobuf message: missing required fields: item.id"
+    );
+
+    // Unset required fields are not encoded.
+    let fields: Vec<String> = Item::decode(Item::default().encode_to_vec().as_slice())
+        .unwrap()
+        .missing_required_fields();
+    assert_eq!(fields, vec!["id"]);
+}
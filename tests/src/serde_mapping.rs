@@ -0,0 +1,79 @@
+include!(concat!(env!("OUT_DIR"), "/serde_mapping.rs"));
+
+use alloc::string::ToString;
+use alloc::vec;
+
+fn record() -> Record {
+    Record {
+        snake_case_field: 7,
+        big: -(1 << 60),
+        sizes: vec![u64::MAX, 0],
+        payload: vec![0xfb, 0xff],
+        ratio: f64::INFINITY,
+        color: Color::Red as i32,
+        colors: vec![Color::Green as i32, 5],
+        maybe_color: Some(Color::Unspecified as i32),
+        counts: vec![("x".to_string(), -3)].into_iter().collect(),
+        color_map: vec![(1, Color::Green as i32)].into_iter().collect(),
+        nested: Some(Nested {
+            display_name: "n".to_string(),
+        }),
+        nested_list: vec![Nested::default()],
+        choice: Some(record::Choice::ChosenColor(Color::Green as i32)),
+    }
+}
+
+/// The serde representation matches the Protobuf JSON mapping of `prost::json`.
+#[test]
+fn serde_matches_json_mapping() {
+    let choices = [
+        None,
+        Some(record::Choice::ChosenName("name".to_string())),
+        Some(record::Choice::ChosenColor(Color::Red as i32)),
+        Some(record::Choice::ChosenNested(Nested::default())),
+        Some(record::Choice::ChosenNumber(u64::MAX)),
+    ];
+    for choice in choices {
+        let record = Record { choice, ..record() };
+        let json = prost::json::to_string(&record).unwrap();
+        assert_eq!(
+            serde_json::to_value(&record).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+        );
+        assert_eq!(serde_json::from_str::<Record>(&json).unwrap(), record);
+    }
+    assert_eq!(serde_json::to_string(&Record::default()).unwrap(), "{}");
+}
+
+#[test]
+fn serde_accepts_json_variants() {
+    let record: Record = serde_json::from_str(
+        r#"{
+            "snake_case_field": 1,
+            "big": 5,
+            "sizes": ["1", 2],
+            "color": 1,
+            "colors": null,
+            "maybe_color": "COLOR_GREEN",
+            "color_map": {"3": 2},
+            "chosenNumber": "9"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        record,
+        Record {
+            snake_case_field: 1,
+            big: 5,
+            sizes: vec![1, 2],
+            color: Color::Red as i32,
+            maybe_color: Some(Color::Green as i32),
+            color_map: vec![(3, Color::Green as i32)].into_iter().collect(),
+            choice: Some(record::Choice::ChosenNumber(9)),
+            ..Default::default()
+        }
+    );
+
+    assert!(serde_json::from_str::<Record>(r#"{"color": "COLOR_BLUE"}"#).is_err());
+    assert!(serde_json::from_str::<Record>(r#"{"payload": "not base64!"}"#).is_err());
+}
@@ -0,0 +1,49 @@
+include!(concat!(env!("OUT_DIR"), "/service_definitions.rs"));
+
+use alloc::string::ToString;
+
+use prost::service::{MethodDescriptor, Service, ServiceMethod};
+use prost::Message;
+
+#[test]
+fn service_descriptor() {
+    let service = Greeter::DESCRIPTOR;
+    assert_eq!(service.name, "Greeter");
+    assert_eq!(service.package, "service_definitions");
+    assert_eq!(service.full_name, "service_definitions.Greeter");
+    assert_eq!(service.methods.len(), 3);
+    assert_eq!(
+        service.method_by_path("/service_definitions.Greeter/SayHelloStream"),
+        Some(&greeter::SayHelloStream::DESCRIPTOR)
+    );
+}
+
+#[test]
+fn method_descriptors() {
+    assert_eq!(
+        greeter::SayHello::DESCRIPTOR,
+        MethodDescriptor {
+            name: "SayHello",
+            path: "/service_definitions.Greeter/SayHello",
+            client_streaming: false,
+            server_streaming: false,
+        }
+    );
+    assert!(greeter::SayHelloStream::DESCRIPTOR.client_streaming);
+    assert!(greeter::SayHelloStream::DESCRIPTOR.server_streaming);
+    assert_eq!(
+        greeter::Ping::DESCRIPTOR.path,
+        "/service_definitions.Greeter/Ping"
+    );
+}
+
+#[test]
+fn method_codecs() {
+    let request = HelloRequest {
+        name: "Ferris".to_string(),
+    };
+    let decoded = greeter::SayHello::decode_request(request.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, request);
+
+    let () = greeter::Ping::decode_response(&[][..]).unwrap();
+}
@@ -0,0 +1,175 @@
+include!(concat!(env!("OUT_DIR"), "/text_format.rs"));
+
+use alloc::string::ToString;
+use alloc::vec;
+
+use prost::text_format::TextFormatMessage;
+
+pub(crate) fn drawing() -> Drawing {
+    Drawing {
+        title: "sketch \"1\"".to_string(),
+        shape: Some(Shape::Circle as i32),
+        shapes: vec![Shape::Square as i32, 7],
+        scale: Some(-0.5),
+        data: Some(b"\x00\n\xff".to_vec()),
+        points: vec![
+            Point {
+                x: Some(1),
+                y: None,
+            },
+            Point::default(),
+        ],
+        named_points: vec![(
+            "a".to_string(),
+            Point {
+                x: None,
+                y: Some(2),
+            },
+        )]
+        .into_iter()
+        .collect(),
+        shape_map: vec![(3, Shape::Circle as i32)].into_iter().collect(),
+        layer: Some(drawing::Layer { depth: Some(9) }),
+        fill: Some(drawing::Fill::Texture(Shape::Unspecified as i32)),
+        created: Some(prost_types::Timestamp {
+            seconds: 5,
+            nanos: 0,
+        }),
+        revision: Some(0),
+        flags: vec![true, false],
+    }
+}
+
+const DRAWING_TEXT: &str = r#"title: "sketch \"1\""
+shape: SHAPE_CIRCLE
+shapes: SHAPE_SQUARE
+shapes: 7
+scale: -0.5
+data: "\000\n\377"
+points {
+  x: 1
+}
+points {
+}
+named_points {
+  key: "a"
+  value {
+    y: 2
+  }
+}
+shape_map {
+  key: 3
+  value: SHAPE_CIRCLE
+}
+Layer {
+  depth: 9
+}
+texture: SHAPE_UNSPECIFIED
+created {
+  seconds: 5
+}
+revision: 0
+flags: true
+flags: false
+"#;
+
+#[test]
+fn roundtrip() {
+    let drawing = drawing();
+    let text = drawing.to_text_format();
+    assert_eq!(text, DRAWING_TEXT);
+    assert_eq!(Drawing::from_text_format(&text).unwrap(), drawing);
+}
+
+#[test]
+fn required_fields_are_always_written() {
+    assert_eq!(Drawing::default().to_text_format(), "title: \"\"\n");
+}
+
+#[test]
+fn parse_alternative_syntax() {
+    let drawing = Drawing::from_text_format(
+        r#"
+        # Comments, list syntax, angle brackets and separators are accepted.
+        title: 'sketch ' "two";
+        shapes: [SHAPE_CIRCLE, 1],
+        points: [{x: 1}, <y: 0x10>]
+        # Groups are also accepted by their lowercase field name.
+        layer: { depth: 017 }
+        origin { x: -3 }
+        scale: 1e2
+        "#,
+    )
+    .unwrap();
+    assert_eq!(drawing.title, "sketch two");
+    assert_eq!(
+        drawing.shapes,
+        vec![Shape::Circle as i32, Shape::Square as i32]
+    );
+    assert_eq!(
+        drawing.points,
+        vec![
+            Point {
+                x: Some(1),
+                y: None
+            },
+            Point {
+                x: None,
+                y: Some(16)
+            },
+        ]
+    );
+    assert_eq!(drawing.layer, Some(drawing::Layer { depth: Some(15) }));
+    assert_eq!(
+        drawing.fill,
+        Some(drawing::Fill::Origin(Point {
+            x: Some(-3),
+            y: None
+        }))
+    );
+    assert_eq!(drawing.scale, Some(100.0));
+}
+
+#[test]
+fn merge() {
+    let mut drawing = Drawing::from_text_format("points { x: 1 } color: 'red'").unwrap();
+    drawing
+        .merge_text_format("points { y: 2 } texture: SHAPE_CIRCLE")
+        .unwrap();
+    assert_eq!(drawing.points.len(), 2);
+    assert_eq!(
+        drawing.fill,
+        Some(drawing::Fill::Texture(Shape::Circle as i32))
+    );
+}
+
+#[test]
+fn parse_errors() {
+    for text in [
+        "unknown: 1",
+        "shape: SHAPE_TRIANGLE",
+        "shape: 'SHAPE_CIRCLE'",
+        "scale: x",
+        "points: 1",
+        "title { }",
+        "named_points { key: 'a' other: 1 }",
+        "layer { depth: -1 }",
+        "created { nanos: 1.5 }",
+        "flags: yes",
+        "title: 'unterminated",
+    ] {
+        assert!(Drawing::from_text_format(text).is_err(), "{}", text);
+    }
+
+    let error = Drawing::from_text_format("points { x: 'one' }").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "failed to process Protobuf text format: Drawing.points: Point.x: expected integer, found \
+         string"
+    );
+    let error = Drawing::from_text_format("origin { z: 1 }").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "failed to process Protobuf text format: Drawing.origin: unknown field `z` in Point"
+    );
+}
@@ -0,0 +1,40 @@
+include!(concat!(env!("OUT_DIR"), "/text_format_debug.rs"));
+
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec;
+
+fn request() -> Request {
+    Request {
+        display_name: "Ferris".to_string(),
+        kind: Kind::Crab as i32,
+        payload: b"\x00\"".to_vec(),
+        origin: Some(request::Origin {
+            host: "example.com".to_string(),
+        }),
+        ids: vec![1, 2],
+        token: "hunter2".to_string(),
+    }
+}
+
+#[test]
+fn debug_prints_text_format() {
+    assert_eq!(
+        format!("{:?}", request()),
+        r#"display_name: "Ferris" kind: KIND_CRAB payload: "\000\"" origin { host: "example.com" } ids: 1 ids: 2"#
+    );
+    assert_eq!(format!("{:?}", Request::default()), "");
+}
+
+#[test]
+fn alternate_debug_prints_one_field_per_line() {
+    assert_eq!(
+        format!("{:#?}", request()),
+        "display_name: \"Ferris\"\n\
+         kind: KIND_CRAB\n\
+         payload: \"\\000\\\"\"\n\
+         origin {\n  host: \"example.com\"\n}\n\
+         ids: 1\n\
+         ids: 2\n"
+    );
+}
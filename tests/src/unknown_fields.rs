@@ -0,0 +1,178 @@
+include!(concat!(env!("OUT_DIR"), "/unknown_fields.rs"));
+
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use prost::{Message, UnknownFieldValue};
+
+fn new_message() -> New {
+    New {
+        id: 42,
+        nested: Some(Nested {
+            label: "nested".to_string(),
+        }),
+        name: "hello".to_string(),
+        values: vec![1, 2, 3],
+        extra: Some(Nested {
+            label: "extra".to_string(),
+        }),
+    }
+}
+
+#[test]
+fn test_unknown_fields_roundtrip() {
+    let new = new_message();
+    let old = Old::decode(new.encode_to_vec().as_slice()).unwrap();
+
+    assert_eq!(old.id, 42);
+    assert_eq!(old._unknown_fields.len(), 3);
+
+    let roundtrip = New::decode(old.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(roundtrip, new);
+}
+
+#[test]
+fn test_unknown_fields_contents() {
+    let old = Old::decode(new_message().encode_to_vec().as_slice()).unwrap();
+
+    let tags = old
+        ._unknown_fields
+        .iter()
+        .map(|f| f.tag)
+        .collect::<Vec<_>>();
+    assert_eq!(tags, vec![3, 4, 5]);
+    assert_eq!(
+        old._unknown_fields.iter().next().unwrap().value,
+        UnknownFieldValue::LengthDelimited(b"hello".to_vec())
+    );
+}
+
+#[test]
+fn test_unknown_fields_clear() {
+    let mut old = Old::decode(new_message().encode_to_vec().as_slice()).unwrap();
+    old.clear();
+    assert_eq!(old, Old::default());
+    assert!(old._unknown_fields.is_empty());
+}
+
+#[test]
+fn test_unknown_fields_not_preserved() {
+    // Messages which are not configured to preserve unknown fields drop them.
+    let new = New {
+        id: 0,
+        ..new_message()
+    };
+    let nested = Nested::decode(new.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(nested, Nested::default());
+    assert!(nested.encode_to_vec().is_empty());
+}
+
+#[test]
+fn test_unknown_fields_derive() {
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Derived {
+        #[prost(int32, tag = "1")]
+        id: i32,
+        #[prost(unknown_fields)]
+        unknown: prost::UnknownFieldSet,
+    }
+
+    let derived = Derived::decode(new_message().encode_to_vec().as_slice()).unwrap();
+    assert_eq!(derived.id, 42);
+    assert_eq!(derived.unknown.len(), 4);
+    assert_eq!(derived.encoded_len(), new_message().encoded_len());
+    crate::check_message(&derived);
+}
+
+#[test]
+fn test_decode_fields() {
+    let encoded = new_message().encode_to_vec();
+
+    let new = New::decode_fields(encoded.as_slice(), &[1, 4]).unwrap();
+    assert_eq!(
+        new,
+        New {
+            id: 42,
+            values: vec![1, 2, 3],
+            ..New::default()
+        }
+    );
+
+    // Selected fields which are not known to the message are preserved, and others are skipped.
+    let old = Old::decode_fields(encoded.as_slice(), &[2, 3]).unwrap();
+    assert_eq!(old.id, 0);
+    assert_eq!(old.nested, new_message().nested);
+    let tags = old
+        ._unknown_fields
+        .iter()
+        .map(|f| f.tag)
+        .collect::<Vec<_>>();
+    assert_eq!(tags, vec![3]);
+
+    let mut merged = new;
+    merged.merge_fields(encoded.as_slice(), &[3]).unwrap();
+    assert_eq!(merged.name, "hello");
+    assert_eq!(merged.nested, None);
+
+    assert!(New::decode_fields(&[0x08][..], &[]).is_err());
+}
+
+#[test]
+fn test_invalid_utf8_kept_as_bytes() {
+    use prost::{DecodeOptions, UnknownFieldSet, Utf8Policy};
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Record {
+        #[prost(string, tag = "1")]
+        name: alloc::string::String,
+        #[prost(string, optional, tag = "2")]
+        label: Option<alloc::string::String>,
+        #[prost(string, repeated, tag = "3")]
+        tags: Vec<alloc::string::String>,
+        #[prost(message, optional, tag = "4")]
+        nested: Option<Nested>,
+        #[prost(unknown_fields)]
+        unknown: UnknownFieldSet,
+    }
+
+    let buf = [
+        0x0a, 0x02, 0xc3, 0x28, // name: invalid
+        0x12, 0x02, b'o', b'k', // label: valid
+        0x12, 0x01, 0xff, // label: invalid, replacing the valid value
+        0x1a, 0x01, b'a', // tags[0]: valid
+        0x1a, 0x01, 0x80, // tags[1]: invalid
+        0x1a, 0x01, b'b', // tags[2]: valid
+    ];
+    let options = DecodeOptions::new().utf8_policy(Utf8Policy::Bytes);
+    let record = Record::decode_with_options(&buf[..], &options).unwrap();
+    assert_eq!(record.name, "");
+    assert_eq!(record.label, None);
+    assert_eq!(record.tags, ["a", "b"]);
+    let unknown = record
+        .unknown
+        .iter()
+        .map(|field| (field.tag, field.value.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        unknown,
+        [
+            (1, UnknownFieldValue::LengthDelimited(vec![0xc3, 0x28])),
+            (2, UnknownFieldValue::LengthDelimited(vec![0xff])),
+            (3, UnknownFieldValue::LengthDelimited(vec![0x80])),
+        ]
+    );
+
+    // Re-encoding writes the bytes back after the known fields.
+    let reencoded = Record::decode_with_options(record.encode_to_vec().as_slice(), &options);
+    assert_eq!(reencoded.unwrap(), record);
+
+    // Without unknown fields, the value fails to decode as under the strict policy.
+    let buf = [0x22, 0x03, 0x0a, 0x01, 0xff];
+    let error = Record::decode_with_options(&buf[..], &options).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "failed to decode Protobuf message: Nested.label: Record.nested: \
+         invalid string value: data is not UTF-8 encoded"
+    );
+}
@@ -0,0 +1,61 @@
+include!(concat!(env!("OUT_DIR"), "/utf8_bytes.rs"));
+
+use alloc::string::ToString;
+use alloc::vec;
+
+use prost::bytes::Bytes;
+use prost::text_format::TextFormatMessage;
+use prost::{Message, Utf8Bytes};
+
+fn borrowed() -> Borrowed {
+    Borrowed {
+        name: "name".into(),
+        label: Some("label".into()),
+        tags: vec!["a".into(), "b".into()],
+        attributes: vec![("key".to_string(), "value".to_string())]
+            .into_iter()
+            .collect(),
+        value: Some(borrowed::Value::Text("text".into())),
+    }
+}
+
+/// Returns `true` if `value` points into the memory of `buf`.
+fn is_shared(value: &Utf8Bytes, buf: &Bytes) -> bool {
+    let range = buf.as_ptr_range();
+    range.contains(&value.as_ptr())
+}
+
+#[test]
+fn decode_without_copy() {
+    let message = borrowed();
+    let buf = Bytes::from(message.encode_to_vec());
+    let decoded = Borrowed::decode(buf.clone()).unwrap();
+    assert_eq!(decoded, message);
+
+    assert!(is_shared(&decoded.name, &buf));
+    assert!(is_shared(decoded.label.as_ref().unwrap(), &buf));
+    assert!(decoded.tags.iter().all(|tag| is_shared(tag, &buf)));
+    match decoded.value {
+        Some(borrowed::Value::Text(ref text)) => assert!(is_shared(text, &buf)),
+        _ => panic!("unexpected oneof value"),
+    }
+
+    assert_eq!(decoded.label(), "label");
+}
+
+#[test]
+fn decode_invalid_utf8() {
+    let error = Borrowed::decode(&[0x0a, 0x02, 0xc3, 0x28][..]).unwrap_err();
+    assert!(error.to_string().contains("invalid string value"));
+}
+
+#[test]
+fn json_and_text_format() {
+    let message = borrowed();
+
+    let json = prost::json::to_string(&message).unwrap();
+    assert_eq!(prost::json::from_str::<Borrowed>(&json).unwrap(), message);
+
+    let text = message.to_text_format();
+    assert_eq!(Borrowed::from_text_format(&text).unwrap(), message);
+}
@@ -0,0 +1,155 @@
+include!(concat!(env!("OUT_DIR"), "/validate.rs"));
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use prost::validate::Validate;
+
+fn user() -> User {
+    User {
+        name: "alice".into(),
+        age: 30,
+        score: Some(1.5),
+        role: Role::Admin as i32,
+        emails: vec!["alice@example.com".into()],
+        limits: vec![("max_items".into(), 10)].into_iter().collect(),
+        ..Default::default()
+    }
+}
+
+fn team() -> Team {
+    Team {
+        owner: Some(user()),
+        members: vec![user()],
+        size: -1,
+        contact: Some(team::Contact::Email("team@example.com".into())),
+        ..Default::default()
+    }
+}
+
+/// Returns the field paths and rule identifiers of the violations of a message.
+fn violations<M: Validate>(message: &M) -> Vec<(String, String)> {
+    match message.validate() {
+        Ok(()) => Vec::new(),
+        Err(error) => error
+            .violations()
+            .iter()
+            .map(|violation| (violation.field_path().into(), violation.rule_id().into()))
+            .collect(),
+    }
+}
+
+fn paths(violations: &[(&str, &str)]) -> Vec<(String, String)> {
+    violations
+        .iter()
+        .map(|&(path, rule)| (path.into(), rule.into()))
+        .collect()
+}
+
+#[test]
+fn valid_messages() {
+    assert_eq!(user().validate(), Ok(()));
+    assert_eq!(team().validate(), Ok(()));
+}
+
+#[test]
+fn scalar_rules() {
+    let message = User {
+        name: "Alice".into(),
+        age: 12,
+        score: Some(f64::NAN),
+        role: 7,
+        token: vec![1, 2],
+        nickname: "ignored".into(),
+        ..user()
+    };
+    assert_eq!(
+        violations(&message),
+        paths(&[
+            ("name", "string.pattern"),
+            ("age", "uint32.gte_lte"),
+            ("score", "double.gt"),
+            ("score", "double.finite"),
+            ("role", "enum.defined_only"),
+            ("token", "bytes.len"),
+        ])
+    );
+
+    let error = message.validate().unwrap_err();
+    assert_eq!(
+        error.violations()[1].message(),
+        "must be greater than or equal to 18 and less than or equal to 150"
+    );
+
+    // Unset optional fields and zero values of ignored fields are not checked.
+    let message = User {
+        name: String::new(),
+        score: None,
+        token: Vec::new(),
+        ..user()
+    };
+    assert_eq!(
+        violations(&message),
+        paths(&[("name", "string.min_len"), ("name", "string.pattern")])
+    );
+}
+
+#[test]
+fn repeated_and_map_rules() {
+    let message = User {
+        emails: vec!["a@b".into(), "c".into(), "a@b".into()],
+        limits: vec![("min_items".into(), 0), ("max_size".into(), 1)]
+            .into_iter()
+            .collect(),
+        ..user()
+    };
+    assert_eq!(
+        violations(&message),
+        paths(&[
+            ("emails", "repeated.max_items"),
+            ("emails", "repeated.unique"),
+            ("emails[1]", "string.contains"),
+            ("limits[\"min_items\"]", "string.prefix"),
+            ("limits[\"min_items\"]", "int32.gt"),
+        ])
+    );
+}
+
+#[test]
+fn nested_messages() {
+    let mut invalid = user();
+    invalid.age = 200;
+    let message = Team {
+        owner: None,
+        members: vec![user(), invalid.clone()],
+        by_name: vec![("bob".into(), invalid.clone())].into_iter().collect(),
+        size: 5,
+        contact: Some(team::Contact::Delegate(invalid)),
+    };
+    assert_eq!(
+        violations(&message),
+        paths(&[
+            ("owner", "required"),
+            ("members[1].age", "uint32.gte_lte"),
+            ("by_name[\"bob\"].age", "uint32.gte_lte"),
+            ("size", "int32.gt_lt_exclusive"),
+            ("delegate.age", "uint32.gte_lte"),
+        ])
+    );
+
+    let message = Team {
+        members: Vec::new(),
+        contact: None,
+        ..team()
+    };
+    assert_eq!(
+        violations(&message),
+        paths(&[("members", "repeated.min_items"), ("contact", "required")])
+    );
+    assert_eq!(
+        message.validate().unwrap_err().to_string(),
+        "failed to validate Protobuf message: members: must contain at least 1 item(s) \
+         [repeated.min_items]; contact: exactly one field is required in oneof [required]"
+    );
+}
@@ -0,0 +1,78 @@
+pub mod v1 {
+    include!(concat!(env!("OUT_DIR"), "/version_conversion.v1.rs"));
+}
+
+pub mod v2 {
+    include!(concat!(env!("OUT_DIR"), "/version_conversion.v2.rs"));
+}
+
+use alloc::string::ToString;
+use alloc::vec;
+use core::convert::TryFrom;
+
+fn v1_user() -> v1::User {
+    v1::User {
+        name: "alice".to_string(),
+        age: 42,
+        address: Some(v1::user::Address {
+            city: "Lisbon".to_string(),
+        }),
+        status: v1::Status::Active as i32,
+        nickname: "al".to_string(),
+    }
+}
+
+#[test]
+fn test_upgrade() {
+    let user = v2::User::try_from(v1_user()).unwrap();
+    assert_eq!(
+        user,
+        v2::User {
+            name: "alice".to_string(),
+            age: 42,
+            address: Some(v2::user::Address {
+                city: "Lisbon".to_string(),
+                country: "".to_string(),
+            }),
+            status: v2::Status::Active as i32,
+            emails: vec![],
+        }
+    );
+}
+
+#[test]
+fn test_downgrade() {
+    let user = v2::User {
+        emails: vec!["alice@example.com".to_string()],
+        status: v2::Status::Suspended as i32,
+        ..v2::User::try_from(v1_user()).unwrap()
+    };
+
+    // Fields which do not exist in the older version are dropped, and enum values which do not
+    // exist are kept as integers.
+    let downgraded = v1::User::try_from(user).unwrap();
+    assert_eq!(
+        downgraded,
+        v1::User {
+            nickname: "".to_string(),
+            status: v2::Status::Suspended as i32,
+            ..v1_user()
+        }
+    );
+
+    let nested = v1::user::Address::try_from(v2::user::Address {
+        city: "Porto".to_string(),
+        country: "Portugal".to_string(),
+    })
+    .unwrap();
+    assert_eq!(nested.city, "Porto");
+}
+
+#[test]
+fn test_incompatible() {
+    assert!(v2::Account::try_from(v1::Account { id: 7 }).is_err());
+    assert_eq!(
+        v2::Account::try_from(v1::Account::default()).unwrap(),
+        v2::Account::default()
+    );
+}
@@ -0,0 +1,77 @@
+include!(concat!(env!("OUT_DIR"), "/wrapper_type.rs"));
+
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use prost::Message;
+
+fn payload() -> Payload {
+    Payload {
+        data: vec![0; 1024],
+        tags: vec!["large".to_string()],
+    }
+}
+
+#[test]
+fn clones_share_wrapped_fields() {
+    let payload = Arc::new(payload());
+    let envelope = Envelope {
+        recipient: "a".to_string(),
+        payload: Some(payload.clone()),
+        attachment: Some(envelope::Attachment::Shared(payload.clone())),
+        history: vec![],
+    };
+
+    let envelopes = (0..8)
+        .map(|i| Envelope {
+            recipient: i.to_string(),
+            ..envelope.clone()
+        })
+        .collect::<Vec<_>>();
+    assert!(envelopes
+        .iter()
+        .all(|envelope| Arc::ptr_eq(envelope.payload.as_ref().unwrap(), &payload)));
+    assert_eq!(Arc::strong_count(&payload), 19);
+}
+
+#[test]
+fn wrapped_fields_round_trip() {
+    let envelope = Envelope {
+        recipient: "b".to_string(),
+        payload: Some(Arc::new(payload())),
+        attachment: Some(envelope::Attachment::Boxed(Box::new(payload()))),
+        history: vec![payload()],
+    };
+    let decoded = Envelope::decode(envelope.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, envelope);
+
+    let envelope = Envelope {
+        attachment: Some(envelope::Attachment::Shared(Arc::new(payload()))),
+        ..envelope
+    };
+    let decoded = Envelope::decode(envelope.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, envelope);
+}
+
+#[test]
+fn decoding_copies_shared_messages() {
+    let payload = Arc::new(payload());
+    let mut envelope = Envelope {
+        payload: Some(payload.clone()),
+        ..Envelope::default()
+    };
+
+    let update = Envelope {
+        payload: Some(Arc::new(Payload {
+            tags: vec!["update".to_string()],
+            ..Payload::default()
+        })),
+        ..Envelope::default()
+    };
+    envelope.merge(update.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(envelope.payload.as_ref().unwrap().tags, ["large", "update"]);
+    assert_eq!(payload.tags, ["large"]);
+}
@@ -0,0 +1,58 @@
+//! Decode and round-trip tests for wasm targets, where `usize` is 32 bits wide.
+//!
+//! On `wasm32-wasip1` these run with the standard test harness under a runner such as
+//! `wasmtime`, and on `wasm32-unknown-unknown` with `wasm-bindgen-test-runner`. The tests of the
+//! library also run on `wasm32-wasip1`, except for those which need `protoc` at runtime.
+#![cfg(target_arch = "wasm32")]
+
+#[cfg(target_os = "unknown")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use prost::Message;
+use protobuf::test_messages::proto2::TestAllTypesProto2;
+use protobuf::test_messages::proto3::{test_all_types_proto3, TestAllTypesProto3};
+use tests::{check_message, roundtrip};
+
+#[test]
+fn usize_is_32_bits() {
+    assert_eq!(usize::BITS, 32);
+}
+
+#[test]
+fn roundtrip_all_types() {
+    let message = TestAllTypesProto3 {
+        optional_int64: i64::MIN,
+        optional_uint64: u64::MAX,
+        optional_fixed64: u64::MAX,
+        optional_double: f64::MAX,
+        optional_string: "wasm".to_string(),
+        optional_bytes: vec![0; 300],
+        repeated_int32: vec![i32::MIN, -1, 0, 1, i32::MAX],
+        repeated_uint64: vec![u64::MAX; 3],
+        map_int64_int64: [(i64::MIN, i64::MAX)].into_iter().collect(),
+        oneof_field: Some(test_all_types_proto3::OneofField::OneofUint32(u32::MAX)),
+        ..Default::default()
+    };
+
+    check_message(&message);
+    roundtrip::<TestAllTypesProto3>(&message.encode_to_vec()).unwrap();
+    roundtrip::<TestAllTypesProto2>(&message.encode_to_vec()).unwrap();
+}
+
+#[test]
+fn decode_length_beyond_usize() {
+    // `optional_bytes` with a length of 2^32, which does not fit in a 32-bit `usize`.
+    let data = [0x7A, 0x80, 0x80, 0x80, 0x80, 0x10, 0x00];
+    assert!(TestAllTypesProto3::decode(&data[..]).is_err());
+
+    // `optional_bytes` with a length of `u32::MAX`, beyond the end of the buffer.
+    let data = [0x7A, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0x00];
+    assert!(TestAllTypesProto3::decode(&data[..]).is_err());
+}
+
+#[test]
+fn decode_packed_length_beyond_usize() {
+    // `repeated_int32` packed with a length of 2^35.
+    let data = [0xFA, 0x01, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01, 0x00];
+    assert!(TestAllTypesProto3::decode(&data[..]).is_err());
+}